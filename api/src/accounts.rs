@@ -15,8 +15,8 @@ use crate::{
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    AccountData, Address, AptosErrorCode, AsConverter, LedgerInfo, MoveModuleBytecode,
-    MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
+    AccountData, AccountStateKey, Address, AptosErrorCode, AsConverter, LedgerInfo,
+    MoveModuleBytecode, MoveModuleId, MoveResource, MoveStructTag, StateKeyWrapper, U64,
 };
 use aptos_types::{
     access_path::AccessPath,
@@ -169,6 +169,64 @@ impl AccountsApi {
         )?;
         account.modules(&accept_type)
     }
+
+    /// Get account state keys
+    ///
+    /// Lists the state keys stored under an account (resources, resource groups and modules)
+    /// without their values, optionally filtered to the ones whose type or module id starts with
+    /// `prefix`. This is much cheaper than listing resources when only the names are needed.
+    /// If the ledger version is not specified in the request, the latest ledger version is used.
+    ///
+    /// At most `api.max_state_keys_scan_range` keys are scanned per request, matching or not, so
+    /// a page can hold fewer than `limit` keys, or none, and still have a cursor to continue from
+    /// in the X-Aptos-Cursor header.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/state_keys",
+        method = "get",
+        operation_id = "get_account_state_keys",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_state_keys(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Only return keys whose resource type or module id starts with this
+        ///
+        /// e.g. `0x1::coin` for everything from the coin module, or
+        /// `0x1::coin::CoinStore` for all coin stores
+        prefix: Query<Option<String>>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+        /// Cursor specifying where to start for pagination
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<StateKeyWrapper>>,
+        /// Max number of state keys to retrieve
+        ///
+        /// If not provided, defaults to default page size.
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<AccountStateKey>> {
+        fail_point_poem("endpoint_get_account_state_keys")?;
+        self.context
+            .check_api_output_enabled("Get account state keys", &accept_type)?;
+        let account = Account::new(
+            self.context.clone(),
+            address.0,
+            ledger_version.0,
+            start.0.map(StateKey::from),
+            limit.0,
+        )?;
+        account.state_keys(&accept_type, prefix.0.as_deref())
+    }
 }
 
 /// A struct representing Account related lookups for resources and modules
@@ -444,6 +502,58 @@ impl Account {
         }
     }
 
+    /// Retrieves the state keys stored under the account, see [`Context::get_state_keys_by_pagination`]
+    ///
+    /// * JSON: Return a JSON encoded version of [`Vec<AccountStateKey>`]
+    /// * BCS: Return a BCS encoded version of [`Vec<AccountStateKey>`]
+    pub fn state_keys(
+        self,
+        accept_type: &AcceptType,
+        prefix: Option<&str>,
+    ) -> BasicResultWith404<Vec<AccountStateKey>> {
+        // check account exists
+        self.verify_account_or_object_resource()?;
+        let max_account_resources_page_size = self.context.max_account_resources_page_size();
+        let (state_keys, next_state_key) = self
+            .context
+            .get_state_keys_by_pagination(
+                self.address.into(),
+                prefix,
+                self.start.as_ref(),
+                self.ledger_version,
+                // Just use the max as the default
+                determine_limit(
+                    self.limit,
+                    max_account_resources_page_size,
+                    max_account_resources_page_size,
+                    &self.latest_ledger_info,
+                )? as u64,
+            )
+            .context("Failed to get state keys from storage")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &self.latest_ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => BasicResponse::try_from_json((
+                state_keys,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            ))
+            .map(|v| v.with_cursor(next_state_key)),
+            AcceptType::Bcs => BasicResponse::try_from_bcs((
+                state_keys,
+                &self.latest_ledger_info,
+                BasicResponseStatus::Ok,
+            ))
+            .map(|v| v.with_cursor(next_state_key)),
+        }
+    }
+
     /// Retrieves an event key from a [`MoveStructTag`] and a [`Identifier`] field name
     ///
     /// e.g. If there's the `CoinStore` module, it has a field named `withdraw_events` for
@@ -0,0 +1,262 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Endpoints for controlling a local testnet, so products built on time based features
+//! (staking, vesting, ...) can be tested without waiting for real time to pass. They are
+//! disabled unless `api.admin_endpoints_enabled` is set.
+//!
+//! The endpoint for reloading the node config is meant for operators of real networks instead,
+//! so it has its own flag, `api.config_reload_endpoint_enabled`. So are the endpoints for
+//! inspecting and flushing the loader cache of the VM mempool validates transactions with,
+//! which help diagnose stale modules after an upgrade. They require the bearer token set in
+//! `api.admin_auth_token`.
+//!
+//! Chain time is moved forward by offsetting the clock consensus uses for block timestamps, which
+//! only follows the offset where the admin endpoints are enabled.
+//! Transaction expiration is checked against chain time, so once it has been moved forward,
+//! clients must derive expiration timestamps from the ledger timestamp rather than the wall
+//! clock.
+
+use crate::context::Context;
+use anyhow::format_err;
+use aptos_api_types::MoveModuleId;
+use aptos_logger::prelude::*;
+use aptos_state_view::account_with_state_view::AsAccountWithStateView;
+use aptos_types::{
+    account_config::{BlockResource, CORE_CODE_ADDRESS},
+    account_view::AccountView,
+    on_chain_config::ConfigurationResource,
+    timestamp::advance_chain_clock,
+};
+use move_core_types::language_storage::ModuleId;
+use poem::{
+    handler,
+    http::{header, StatusCode},
+    web::{Data, Json, Query},
+    Request,
+};
+use serde::{Deserialize, Serialize};
+use std::{str::FromStr, time::Duration};
+use subtle::ConstantTimeEq;
+
+#[derive(Deserialize, Serialize)]
+pub struct AdvanceTimeConf {
+    seconds: u64,
+}
+
+#[handler]
+pub fn advance_time_poem(
+    context: Data<&std::sync::Arc<Context>>,
+    Query(conf): Query<AdvanceTimeConf>,
+) -> poem::Result<String> {
+    check_admin_endpoints_enabled(&context)?;
+    let offset = conf
+        .seconds
+        .checked_mul(1_000_000)
+        .and_then(|usecs| advance_chain_clock(Duration::from_micros(usecs)))
+        .ok_or_else(|| {
+            poem::Error::from_string(
+                format!(
+                    "Can't advance chain time by {}s: it overflows",
+                    conf.seconds
+                ),
+                StatusCode::BAD_REQUEST,
+            )
+        })?;
+    info!(
+        "Advanced chain time by {}s, now {}s ahead of the wall clock",
+        conf.seconds,
+        offset.as_secs()
+    );
+    Ok(format!(
+        "Advanced chain time by {}s, now {}s ahead of the wall clock",
+        conf.seconds,
+        offset.as_secs()
+    ))
+}
+
+/// Moves chain time forward just enough for the next block to end the epoch.
+#[handler]
+pub fn force_epoch_change_poem(context: Data<&std::sync::Arc<Context>>) -> poem::Result<String> {
+    check_admin_endpoints_enabled(&context)?;
+    let ledger_info = context
+        .get_latest_ledger_info_with_signatures()
+        .map_err(poem::Error::from)?;
+    let ledger_info = ledger_info.ledger_info();
+    let state_view = context
+        .state_view_at_version(ledger_info.version())
+        .map_err(poem::Error::from)?;
+    let framework = state_view.as_account_with_state_view(&CORE_CODE_ADDRESS);
+    let block_resource: BlockResource = framework
+        .get_move_resource()?
+        .ok_or_else(|| format_err!("BlockResource not found"))?;
+    let configuration: ConfigurationResource = framework
+        .get_configuration_resource()?
+        .ok_or_else(|| format_err!("Configuration resource not found"))?;
+
+    // The block prologue starts a new epoch once a block's timestamp is at least
+    // `epoch_interval` after the last reconfiguration
+    let epoch_end_usecs = configuration
+        .last_reconfiguration_time()
+        .saturating_add(block_resource.epoch_interval());
+    let advance_by =
+        Duration::from_micros(epoch_end_usecs.saturating_sub(ledger_info.timestamp_usecs()));
+    advance_chain_clock(advance_by).ok_or_else(|| {
+        poem::Error::from_string(
+            "Can't advance chain time to the end of the epoch: it overflows",
+            StatusCode::BAD_REQUEST,
+        )
+    })?;
+    info!(
+        "Advanced chain time by {}us to end epoch {}",
+        advance_by.as_micros(),
+        configuration.epoch()
+    );
+    Ok(format!(
+        "Advanced chain time by {}us to end epoch {}",
+        advance_by.as_micros(),
+        configuration.epoch()
+    ))
+}
+
+/// Reloads the reloadable part of the node config (log level, mempool capacity and API limits)
+/// from the config file, like a SIGHUP does.
+#[handler]
+pub fn reload_config_poem(context: Data<&std::sync::Arc<Context>>) -> poem::Result<String> {
+    if !context.config_reload_endpoint_enabled() {
+        return Err(poem::Error::from(anyhow::anyhow!(
+            "Config reload endpoint is not enabled at a config level"
+        )));
+    }
+    let config = context
+        .config_reloader()
+        .reload()
+        .map_err(|e| poem::Error::from(anyhow::Error::from(e)))?;
+    info!("Reloaded node config: {:?}", config);
+    Ok(format!("Reloaded node config: {:?}", config))
+}
+
+#[derive(Serialize)]
+pub struct LoaderCacheResponse {
+    modules: Vec<CachedModuleResponse>,
+    hits: u64,
+    misses: u64,
+    /// Number of modules flushed for going over the limits of `execution.module_cache`
+    evictions: u64,
+    /// Share of module loads served from the cache, 0 if nothing has been loaded yet
+    hit_rate: f64,
+    /// Whether the cache is going to be flushed before the next validation
+    invalidated: bool,
+}
+
+#[derive(Serialize)]
+pub struct CachedModuleResponse {
+    module: String,
+    size_bytes: usize,
+}
+
+/// Lists the modules in the loader cache of the validation VM, with their sizes and the cache
+/// hit rate.
+#[handler]
+pub async fn loader_cache_poem(
+    request: &Request,
+    context: Data<&std::sync::Arc<Context>>,
+) -> poem::Result<Json<LoaderCacheResponse>> {
+    check_admin_auth_token(&context, request)?;
+    let stats = context
+        .get_loader_cache_stats()
+        .await
+        .map_err(poem::Error::from)?;
+    let loads = stats.hits + stats.misses;
+    let hit_rate = if loads == 0 {
+        0.0
+    } else {
+        stats.hits as f64 / loads as f64
+    };
+    Ok(Json(LoaderCacheResponse {
+        modules: stats
+            .modules
+            .into_iter()
+            .map(|module| CachedModuleResponse {
+                module: MoveModuleId::from(module.id).to_string(),
+                size_bytes: module.size_bytes,
+            })
+            .collect(),
+        hits: stats.hits,
+        misses: stats.misses,
+        evictions: stats.evictions,
+        hit_rate,
+        invalidated: stats.invalidated,
+    }))
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct FlushLoaderCacheConf {
+    /// Comma separated module ids, e.g. `0x1::coin,0x1::aptos_account`
+    modules: Option<String>,
+}
+
+/// Flushes the loader cache of the validation VM if it holds any of the given modules, or
+/// unconditionally if none are given. The cache can't drop single modules, so a flush always
+/// empties it.
+#[handler]
+pub async fn flush_loader_cache_poem(
+    request: &Request,
+    context: Data<&std::sync::Arc<Context>>,
+    Query(conf): Query<FlushLoaderCacheConf>,
+) -> poem::Result<String> {
+    check_admin_auth_token(&context, request)?;
+    let module_ids = conf
+        .modules
+        .iter()
+        .flat_map(|modules| modules.split(','))
+        .filter(|module| !module.trim().is_empty())
+        .map(|module| MoveModuleId::from_str(module.trim()).map(ModuleId::from))
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| poem::Error::from_string(e.to_string(), StatusCode::BAD_REQUEST))?;
+    let flushed = context
+        .invalidate_cached_modules(module_ids)
+        .await
+        .map_err(poem::Error::from)?;
+    if flushed {
+        info!("Flushed the loader cache of the validation VM");
+        Ok("Flushed the loader cache of the validation VM".to_string())
+    } else {
+        Ok("The loader cache of the validation VM holds none of the given modules".to_string())
+    }
+}
+
+fn check_admin_auth_token(context: &Context, request: &Request) -> poem::Result<()> {
+    let expected_token = context.admin_auth_token().ok_or_else(|| {
+        poem::Error::from(anyhow::anyhow!(
+            "Loader cache endpoints are not enabled at a config level"
+        ))
+    })?;
+    let token = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    // Compared in constant time, so that response times don't leak how much of a guess is right
+    let valid = token.map_or(false, |token| {
+        bool::from(token.as_bytes().ct_eq(expected_token.as_bytes()))
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(poem::Error::from_string(
+            "Missing or invalid admin auth token",
+            StatusCode::UNAUTHORIZED,
+        ))
+    }
+}
+
+fn check_admin_endpoints_enabled(context: &Context) -> poem::Result<()> {
+    if context.admin_endpoints_enabled() {
+        Ok(())
+    } else {
+        Err(poem::Error::from(anyhow::anyhow!(
+            "Admin endpoints are not enabled at a config level"
+        )))
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    accept_type::AcceptType,
+    failpoint::fail_point_poem,
+    response::{
+        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
+    },
+    ApiTags, Context,
+};
+use anyhow::Context as AnyhowContext;
+use aptos_api_types::{
+    AptosError, AptosErrorCode, AsConverter, BatchReadRequest, BatchReadResult,
+    BatchResourceRequest, BatchTableItemRequest, VerifyInputWithRecursion, ViewRequest, U64,
+};
+use aptos_state_view::TStateView;
+use aptos_storage_interface::state_view::DbStateView;
+use aptos_types::state_store::{state_key::StateKey, table::TableHandle};
+use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
+use move_core_types::{
+    language_storage::{StructTag, TypeTag},
+    resolver::ResourceResolver,
+};
+use poem_openapi::{param::Query, payload::Json, OpenApi};
+use serde::Serialize;
+use serde_json::Value;
+use std::{convert::TryInto, fmt::Display, sync::Arc};
+
+/// API for reading several pieces of state in a single request
+pub struct BatchApi {
+    pub context: Arc<Context>,
+}
+
+#[OpenApi]
+impl BatchApi {
+    /// Execute a batch of reads
+    ///
+    /// Execute a batch of reads (account resources, view functions and table items) against
+    /// a single ledger version, and return their results in the same order as the requests.
+    /// This lets clients that need many values at once get a consistent view of the state in
+    /// a single round trip.
+    ///
+    /// Each read succeeds or fails on its own: a failed read is reported in its result rather
+    /// than failing the whole batch. Only JSON output is supported.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/batch",
+        method = "post",
+        operation_id = "batch_read",
+        tag = "ApiTags::General"
+    )]
+    async fn batch_read(
+        &self,
+        accept_type: AcceptType,
+        /// Reads to execute, in order
+        requests: Json<Vec<BatchReadRequest>>,
+        /// Ledger version to execute the reads at
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<BatchReadResult>> {
+        fail_point_poem("endpoint_batch_read")?;
+        self.context
+            .check_api_output_enabled("Batch read", &accept_type)?;
+        if accept_type == AcceptType::Bcs {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                "Batch read only supports JSON output",
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+        if requests.0.len() > self.context.max_batch_read_size() {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                format!(
+                    "Requested too many reads: {}, while limit is {}",
+                    requests.0.len(),
+                    self.context.max_batch_read_size(),
+                ),
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        let (ledger_info, ledger_version, state_view) = self
+            .context
+            .state_view(ledger_version.map(|inner| inner.0))?;
+        let results = requests
+            .0
+            .into_iter()
+            .map(
+                |request| match self.read(&state_view, ledger_version, request) {
+                    Ok(data) => BatchReadResult::data(data),
+                    Err(err) => BatchReadResult::error(err),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+    }
+}
+
+impl BatchApi {
+    fn read(
+        &self,
+        state_view: &DbStateView,
+        ledger_version: u64,
+        request: BatchReadRequest,
+    ) -> Result<Value, AptosError> {
+        match request {
+            BatchReadRequest::Resource(request) => {
+                self.resource(state_view, ledger_version, request)
+            },
+            BatchReadRequest::View(request) => self.view(state_view, request),
+            BatchReadRequest::TableItem(request) => {
+                self.table_item(state_view, ledger_version, request)
+            },
+        }
+    }
+
+    fn resource(
+        &self,
+        state_view: &DbStateView,
+        ledger_version: u64,
+        request: BatchResourceRequest,
+    ) -> Result<Value, AptosError> {
+        let address = request.address;
+        let resource_type: StructTag = request
+            .resource_type
+            .try_into()
+            .context("Failed to parse given resource type")
+            .map_err(invalid_input)?;
+
+        let resolver = state_view.as_move_resolver();
+        let bytes = resolver
+            .get_resource(&address.into(), &resource_type)
+            .context(format!(
+                "Failed to query DB to check for {} at {}",
+                resource_type, address
+            ))
+            .map_err(internal_error)?
+            .ok_or_else(|| {
+                AptosError::new_with_error_code(
+                    format!(
+                        "Resource not found by Address({}), Struct tag({}) and Ledger version({})",
+                        address, resource_type, ledger_version
+                    ),
+                    AptosErrorCode::ResourceNotFound,
+                )
+            })?;
+
+        let resource = resolver
+            .as_converter(self.context.db.clone())
+            .try_into_resource(&resource_type, &bytes)
+            .context("Failed to deserialize resource data retrieved from DB")
+            .map_err(internal_error)?;
+        to_json(resource)
+    }
+
+    fn view(&self, state_view: &DbStateView, request: ViewRequest) -> Result<Value, AptosError> {
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+        let entry_func = converter
+            .convert_view_function(request)
+            .map_err(invalid_input)?;
+
+        let return_vals = AptosVM::execute_view_function(
+            state_view,
+            entry_func.module().clone(),
+            entry_func.function().to_owned(),
+            entry_func.ty_args().to_owned(),
+            entry_func.args().to_owned(),
+            self.context.max_gas_view_function(),
+        )
+        .map_err(invalid_input)?;
+        let return_types = converter
+            .function_return_types(&entry_func)
+            .and_then(|tys| {
+                tys.into_iter()
+                    .map(TypeTag::try_from)
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })
+            .map_err(internal_error)?;
+
+        let move_vals = return_vals
+            .into_iter()
+            .zip(return_types.into_iter())
+            .map(|(v, ty)| converter.try_into_move_value(&ty, &v))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map_err(internal_error)?;
+        to_json(move_vals)
+    }
+
+    fn table_item(
+        &self,
+        state_view: &DbStateView,
+        ledger_version: u64,
+        request: BatchTableItemRequest,
+    ) -> Result<Value, AptosError> {
+        request.key_type.verify(0).map_err(invalid_input)?;
+        request.value_type.verify(0).map_err(invalid_input)?;
+        let key_type = request
+            .key_type
+            .try_into()
+            .context("Failed to parse key_type")
+            .map_err(invalid_input)?;
+        let value_type = request
+            .value_type
+            .try_into()
+            .context("Failed to parse value_type")
+            .map_err(invalid_input)?;
+        let key = request.key;
+
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        // Convert key to lookup version for DB
+        let vm_key = converter
+            .try_into_vm_value(&key_type, key.clone())
+            .map_err(invalid_input)?;
+        let raw_key = vm_key
+            .undecorate()
+            .simple_serialize()
+            .ok_or_else(|| invalid_input("Failed to serialize table key"))?;
+
+        let state_key = StateKey::table_item(TableHandle(request.table_handle.into()), raw_key);
+        let bytes = state_view
+            .get_state_value_bytes(&state_key)
+            .context(format!(
+                "Failed when trying to retrieve table item from the DB with key: {}",
+                key
+            ))
+            .map_err(internal_error)?
+            .ok_or_else(|| {
+                AptosError::new_with_error_code(
+                    format!(
+                        "Table Item not found by Table handle({}), Table key({}) and Ledger version({})",
+                        request.table_handle, key, ledger_version
+                    ),
+                    AptosErrorCode::TableItemNotFound,
+                )
+            })?;
+
+        let move_value = converter
+            .try_into_move_value(&value_type, &bytes)
+            .context("Failed to deserialize table item retrieved from DB")
+            .map_err(internal_error)?;
+        to_json(move_value)
+    }
+}
+
+fn to_json<T: Serialize>(value: T) -> Result<Value, AptosError> {
+    serde_json::to_value(value)
+        .context("Failed to serialize read result")
+        .map_err(internal_error)
+}
+
+fn invalid_input<E: Display>(err: E) -> AptosError {
+    AptosError::new_with_error_code(err, AptosErrorCode::InvalidInput)
+}
+
+fn internal_error<E: Display>(err: E) -> AptosError {
+    AptosError::new_with_error_code(err, AptosErrorCode::InternalError)
+}
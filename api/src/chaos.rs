@@ -0,0 +1,114 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fault injection for testing client resilience (retries, failover) against a local testnet,
+//! configured by `api.chaos`. See `ChaosConfig` for the faults that can be injected.
+
+use aptos_api_types::{AptosError, AptosErrorCode};
+use aptos_config::config::ChaosConfig;
+use aptos_logger::prelude::*;
+use poem::{
+    http::{header, StatusCode},
+    Endpoint, Request, Response, Result,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Decides which faults to inject. Every decision draws from the same seeded RNG, so the
+/// faults only depend on the seed and the order of the requests.
+pub struct Chaos {
+    config: ChaosConfig,
+    rng: Mutex<StdRng>,
+}
+
+impl Chaos {
+    pub fn new(config: ChaosConfig) -> Self {
+        let rng = Mutex::new(StdRng::seed_from_u64(config.seed));
+        Self { config, rng }
+    }
+
+    /// Whether to report a transaction submission as accepted without forwarding it to mempool
+    pub fn drop_submission(&self) -> bool {
+        self.happens(self.config.drop_submission_percent)
+    }
+
+    /// How long to hold a transaction submission back before forwarding it to mempool
+    pub fn submission_delay(&self) -> Option<Duration> {
+        self.happens(self.config.delay_submission_percent)
+            .then(|| Duration::from_millis(self.config.submission_delay_ms))
+    }
+
+    /// Whether to reject a request as rate limited
+    pub fn too_many_requests(&self) -> bool {
+        self.happens(self.config.too_many_requests_percent)
+    }
+
+    /// How many versions behind the latest version to serve a request at
+    pub fn stale_read_lag(&self) -> Option<u64> {
+        self.happens(self.config.stale_read_percent)
+            .then_some(self.config.stale_read_lag_versions)
+    }
+
+    fn happens(&self, percent: u8) -> bool {
+        if percent == 0 {
+            return false;
+        }
+        self.rng.lock().unwrap().gen_range(0..100) < percent
+    }
+}
+
+/// Rejects requests with a 429 when the chaos config says so, before they reach the API.
+pub async fn middleware_chaos<E: Endpoint>(
+    chaos: Option<Arc<Chaos>>,
+    next: E,
+    request: Request,
+) -> Result<Response> {
+    if chaos.map_or(false, |chaos| chaos.too_many_requests()) {
+        debug!(
+            "Injecting a 429 for {} {}",
+            request.method(),
+            request.uri().path()
+        );
+        let error = AptosError::new_with_error_code(
+            "Injected fault: too many requests",
+            AptosErrorCode::WebFrameworkError,
+        );
+        return Ok(Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(serde_json::to_vec(&error).unwrap_or_default()));
+    }
+    Ok(next.get_response(request).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faults_are_deterministic() {
+        let config = ChaosConfig {
+            seed: 42,
+            drop_submission_percent: 50,
+            too_many_requests_percent: 100,
+            ..Default::default()
+        };
+        let draw = |chaos: &Chaos| {
+            (0..100)
+                .map(|_| chaos.drop_submission())
+                .collect::<Vec<_>>()
+        };
+
+        let drops = draw(&Chaos::new(config.clone()));
+        assert_eq!(drops, draw(&Chaos::new(config.clone())));
+        assert!(drops.contains(&true) && drops.contains(&false));
+
+        let chaos = Chaos::new(config);
+        assert!(chaos.too_many_requests());
+        assert_eq!(chaos.submission_delay(), None);
+        assert_eq!(chaos.stale_read_lag(), None);
+    }
+}
@@ -4,6 +4,7 @@
 
 use crate::{
     accept_type::AcceptType,
+    chaos::Chaos,
     response::{
         bcs_api_disabled, block_not_found_by_height, block_not_found_by_version,
         block_pruned_by_height, json_api_disabled, version_not_found, version_pruned,
@@ -12,13 +13,13 @@ use crate::{
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, BcsBlock, GasEstimation, LedgerInfo, ResourceGroup,
-    TransactionOnChainData,
+    AccountStateKey, AccountStateKeyKind, AptosErrorCode, AsConverter, BcsBlock, GasEstimation,
+    LedgerInfo, MoveModuleId, MoveStructTag, ResourceGroup, TransactionOnChainData,
 };
-use aptos_config::config::{NodeConfig, RoleType};
-use aptos_crypto::HashValue;
+use aptos_config::config::{ConfigReloader, NodeConfig, RoleType};
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_gas::{AptosGasParameters, FromOnChainGasSchedule};
-use aptos_logger::error;
+use aptos_logger::{debug, error};
 use aptos_mempool::{MempoolClientRequest, MempoolClientSender, SubmissionStatus};
 use aptos_state_view::TStateView;
 use aptos_storage_interface::{
@@ -35,6 +36,7 @@ use aptos_types::{
     contract_event::EventWithVersion,
     event::EventKey,
     ledger_info::LedgerInfoWithSignatures,
+    mempool_status::{MempoolStatus, MempoolStatusCode},
     on_chain_config::{GasSchedule, GasScheduleV2, OnChainConfig},
     state_store::{
         state_key::{StateKey, StateKeyInner},
@@ -48,7 +50,8 @@ use aptos_vm::{
     move_vm_ext::MoveResolverExt,
 };
 use futures::{channel::oneshot, SinkExt};
-use move_core_types::language_storage::{ModuleId, StructTag};
+use move_core_types::language_storage::{ModuleId, StructTag, TypeTag};
+use move_vm_runtime::move_vm::LoaderCacheStats;
 use std::{
     collections::HashMap,
     sync::{Arc, RwLock},
@@ -62,6 +65,8 @@ pub struct Context {
     mp_sender: MempoolClientSender,
     pub node_config: NodeConfig,
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
+    chaos: Option<Arc<Chaos>>,
+    config_reloader: ConfigReloader,
 }
 
 impl std::fmt::Debug for Context {
@@ -77,6 +82,12 @@ impl Context {
         mp_sender: MempoolClientSender,
         node_config: NodeConfig,
     ) -> Self {
+        let chaos = node_config
+            .api
+            .chaos
+            .clone()
+            .map(|config| Arc::new(Chaos::new(config)));
+        let config_reloader = ConfigReloader::new(None, &node_config);
         Self {
             chain_id,
             db,
@@ -86,23 +97,59 @@ impl Context {
                 last_updated_epoch: None,
                 gas_schedule_params: None,
             })),
+            chaos,
+            config_reloader,
         }
     }
 
+    /// Shares the reloadable part of the config with the node, so the API's limits follow
+    /// config reloads. Without it, the config can't be reloaded through the API.
+    pub fn with_config_reloader(mut self, config_reloader: ConfigReloader) -> Self {
+        self.config_reloader = config_reloader;
+        self
+    }
+
+    pub fn config_reloader(&self) -> &ConfigReloader {
+        &self.config_reloader
+    }
+
+    /// Faults to inject into requests, if `api.chaos` is configured
+    pub fn chaos(&self) -> Option<&Arc<Chaos>> {
+        self.chaos.as_ref()
+    }
+
     pub fn max_transactions_page_size(&self) -> u16 {
-        self.node_config.api.max_transactions_page_size
+        self.config_reloader
+            .current()
+            .api_max_transactions_page_size
     }
 
     pub fn max_events_page_size(&self) -> u16 {
-        self.node_config.api.max_events_page_size
+        self.config_reloader.current().api_max_events_page_size
+    }
+
+    pub fn max_events_by_type_scan_range(&self) -> u64 {
+        // Storage doesn't read more versions at once
+        self.config_reloader
+            .current()
+            .api_max_events_by_type_scan_range
+            .min(MAX_REQUEST_LIMIT)
+    }
+
+    pub fn max_state_keys_scan_range(&self) -> u64 {
+        self.config_reloader.current().api_max_state_keys_scan_range
     }
 
     pub fn max_account_resources_page_size(&self) -> u16 {
-        self.node_config.api.max_account_resources_page_size
+        self.config_reloader
+            .current()
+            .api_max_account_resources_page_size
     }
 
     pub fn max_account_modules_page_size(&self) -> u16 {
-        self.node_config.api.max_account_modules_page_size
+        self.config_reloader
+            .current()
+            .api_max_account_modules_page_size
     }
 
     pub fn move_resolver(&self) -> Result<StorageAdapterOwned<DbStateView>> {
@@ -156,11 +203,62 @@ impl Context {
         self.node_config.api.failpoints_enabled
     }
 
+    pub fn admin_endpoints_enabled(&self) -> bool {
+        self.node_config.api.admin_endpoints_enabled
+    }
+
+    pub fn config_reload_endpoint_enabled(&self) -> bool {
+        self.node_config.api.config_reload_endpoint_enabled
+    }
+
+    pub fn admin_auth_token(&self) -> Option<&str> {
+        self.node_config.api.admin_auth_token.as_deref()
+    }
+
     pub fn max_submit_transaction_batch_size(&self) -> usize {
-        self.node_config.api.max_submit_transaction_batch_size
+        self.config_reloader
+            .current()
+            .api_max_submit_transaction_batch_size
+    }
+
+    pub fn max_batch_read_size(&self) -> usize {
+        self.config_reloader.current().api_max_batch_read_size
+    }
+
+    pub fn max_gas_view_function(&self) -> u64 {
+        self.config_reloader.current().api_max_gas_view_function
     }
 
     pub async fn submit_transaction(&self, txn: SignedTransaction) -> Result<SubmissionStatus> {
+        if let Some(chaos) = &self.chaos {
+            if chaos.drop_submission() {
+                debug!(
+                    "Injected fault: dropping submission of {}:{}",
+                    txn.sender(),
+                    txn.sequence_number()
+                );
+                return Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None));
+            }
+            if let Some(delay) = chaos.submission_delay() {
+                debug!(
+                    "Injected fault: delaying submission of {}:{} by {:?}",
+                    txn.sender(),
+                    txn.sequence_number(),
+                    delay
+                );
+                let mp_sender = self.mp_sender.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let (req_sender, _callback) = oneshot::channel();
+                    let _ = mp_sender
+                        .clone()
+                        .send(MempoolClientRequest::SubmitTransaction(txn, req_sender))
+                        .await;
+                });
+                return Ok((MempoolStatus::new(MempoolStatusCode::Accepted), None));
+            }
+        }
+
         let (req_sender, callback) = oneshot::channel();
         self.mp_sender
             .clone()
@@ -206,13 +304,53 @@ impl Context {
                 E::service_unavailable_with_code_no_info(e, AptosErrorCode::InternalError)
             })?;
 
-        Ok(LedgerInfo::new(
+        let latest_ledger_info = LedgerInfo::new(
             &self.chain_id(),
             &ledger_info,
             oldest_version,
             oldest_block_event.height(),
             newest_block_event.height(),
-        ))
+        );
+        match self.chaos.as_ref().and_then(|chaos| chaos.stale_read_lag()) {
+            Some(lag) => self.stale_ledger_info(latest_ledger_info, lag),
+            None => Ok(latest_ledger_info),
+        }
+    }
+
+    /// Ledger info as a node lagging `lag` versions behind would report it: as of the end of
+    /// the block that was committed `lag` versions ago.
+    fn stale_ledger_info<E: ServiceUnavailableError>(
+        &self,
+        latest_ledger_info: LedgerInfo,
+        lag: u64,
+    ) -> Result<LedgerInfo, E> {
+        let stale_version = latest_ledger_info
+            .version()
+            .saturating_sub(lag)
+            .max(latest_ledger_info.oldest_ledger_version.0);
+        let (_, end_version, block_event) = self
+            .db
+            .get_block_info_by_version(stale_version)
+            .context("Failed to retrieve stale block information")
+            .map_err(|e| {
+                E::service_unavailable_with_code_no_info(e, AptosErrorCode::InternalError)
+            })?;
+        if end_version >= latest_ledger_info.version() {
+            return Ok(latest_ledger_info);
+        }
+
+        debug!(
+            "Injected fault: serving version {} instead of {}",
+            end_version,
+            latest_ledger_info.version()
+        );
+        Ok(LedgerInfo {
+            epoch: block_event.epoch().into(),
+            ledger_version: end_version.into(),
+            block_height: block_event.height().into(),
+            ledger_timestamp: block_event.proposed_time().into(),
+            ..latest_ledger_info
+        })
     }
 
     pub fn get_latest_ledger_info_and_verify_lookup_version<E: StdApiError>(
@@ -406,6 +544,63 @@ impl Context {
         Ok((kvs, next_key))
     }
 
+    /// Lists the state keys under an account without reading their values. If `name_prefix` is
+    /// set, only keys whose resource type or module id starts with it are returned, e.g. `0x1::coin`
+    /// or `0x1::coin::CoinStore`. `limit` bounds the number of matching keys, and at most
+    /// `api.max_state_keys_scan_range` keys are scanned, matching or not. The key to continue
+    /// from is returned if either bound was hit before the end of the account.
+    pub fn get_state_keys_by_pagination(
+        &self,
+        address: AccountAddress,
+        name_prefix: Option<&str>,
+        prev_state_key: Option<&StateKey>,
+        version: u64,
+        limit: u64,
+    ) -> Result<(Vec<AccountStateKey>, Option<StateKey>)> {
+        let account_iter = self.db.get_prefixed_state_value_iterator(
+            &StateKeyPrefix::from(address),
+            prev_state_key,
+            version,
+        )?;
+        let max_scanned = self.max_state_keys_scan_range().max(1);
+        let mut keys = vec![];
+        for (scanned, res) in account_iter.enumerate() {
+            let (k, _v) = res?;
+            if keys.len() as u64 >= limit || scanned as u64 >= max_scanned {
+                return Ok((keys, Some(k)));
+            }
+            let path = match k.inner() {
+                StateKeyInner::AccessPath(AccessPath { address: _, path }) => path,
+                _ => {
+                    error!("storage prefix scan return inconsistent key ({:?}) with expected key prefix ({:?}).", k, StateKeyPrefix::from(address));
+                    bail!("storage prefix scan return inconsistent key ({:?})", k)
+                },
+            };
+            let (kind, name) = match Path::try_from(path.as_slice())? {
+                Path::Resource(struct_tag) => (
+                    AccountStateKeyKind::Resource,
+                    MoveStructTag::from(struct_tag).to_string(),
+                ),
+                Path::ResourceGroup(struct_tag) => (
+                    AccountStateKeyKind::ResourceGroup,
+                    MoveStructTag::from(struct_tag).to_string(),
+                ),
+                Path::Code(module_id) => (
+                    AccountStateKeyKind::Module,
+                    MoveModuleId::from(module_id).to_string(),
+                ),
+            };
+            if name_prefix.map_or(true, |prefix| name.starts_with(prefix)) {
+                keys.push(AccountStateKey {
+                    kind,
+                    name,
+                    state_key_hash: k.hash().into(),
+                });
+            }
+        }
+        Ok((keys, None))
+    }
+
     // This function should be deprecated. DO NOT USE it.
     // Instead, call either `get_modules_by_pagination` or `get_modules_by_pagination`.
     pub fn get_account_state<E: InternalError>(
@@ -517,7 +712,7 @@ impl Context {
 
         // We can only get the max_transactions page size
         let max_txns = std::cmp::min(
-            self.node_config.api.max_transactions_page_size,
+            self.max_transactions_page_size(),
             (last_version - first_version + 1) as u16,
         );
         let txns = if with_transactions {
@@ -736,6 +931,36 @@ impl Context {
         callback.await.map_err(anyhow::Error::from)
     }
 
+    /// Describes the loader cache of the VM mempool validates transactions with
+    pub async fn get_loader_cache_stats(&self) -> Result<LoaderCacheStats> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::GetLoaderCacheStats(req_sender))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
+    /// Flushes the loader cache of the VM mempool validates transactions with, if it holds any
+    /// of `module_ids`, or unconditionally if `module_ids` is empty. Returns whether it will be
+    /// flushed.
+    pub async fn invalidate_cached_modules(&self, module_ids: Vec<ModuleId>) -> Result<bool> {
+        let (req_sender, callback) = oneshot::channel();
+
+        self.mp_sender
+            .clone()
+            .send(MempoolClientRequest::InvalidateCachedModules(
+                module_ids, req_sender,
+            ))
+            .await
+            .map_err(anyhow::Error::from)?;
+
+        callback.await.map_err(anyhow::Error::from)
+    }
+
     pub fn get_transaction_by_version(
         &self,
         version: u64,
@@ -796,6 +1021,40 @@ impl Context {
         }
     }
 
+    /// The events of type `event_type` emitted by the transactions from `start_version` up to
+    /// `end_version` (exclusive), along with the version to continue scanning at. Scanning stops
+    /// early rather than return more than `limit` events, unless a single transaction emitted
+    /// more, as the events of a transaction are never split.
+    pub fn get_events_by_type(
+        &self,
+        event_type: &TypeTag,
+        start_version: u64,
+        end_version: u64,
+        limit: u16,
+    ) -> Result<(Vec<EventWithVersion>, u64)> {
+        let mut events = vec![];
+        let mut version = start_version;
+        if end_version <= start_version {
+            return Ok((events, version));
+        }
+        for txn_events in self
+            .db
+            .get_events_iterator(start_version, end_version - start_version)?
+        {
+            let matching: Vec<_> = txn_events?
+                .into_iter()
+                .filter(|event| event.type_tag() == event_type)
+                .map(|event| EventWithVersion::new(version, event))
+                .collect();
+            if !events.is_empty() && events.len() + matching.len() > limit as usize {
+                break;
+            }
+            events.extend(matching);
+            version += 1;
+        }
+        Ok((events, version))
+    }
+
     pub fn estimate_gas_price<E: InternalError>(
         &self,
         ledger_info: &LedgerInfo,
@@ -9,17 +9,18 @@ use crate::{
     failpoint::fail_point_poem,
     page::Page,
     response::{
-        BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
-        InternalError,
+        version_not_found, version_pruned, BadRequestError, BasicErrorWith404, BasicResponse,
+        BasicResponseStatus, BasicResultWith404, InternalError,
     },
     ApiTags,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
-    verify_field_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper, LedgerInfo,
-    MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
+    verify_field_identifier, Address, AptosErrorCode, AsConverter, EventFilter, IdentifierWrapper,
+    LedgerInfo, MoveStructTag, VerifyInputWithRecursion, VersionedEvent, U64,
 };
-use aptos_types::event::EventKey;
+use aptos_types::{contract_event::EventWithVersion, event::EventKey};
+use move_core_types::language_storage::{StructTag, TypeTag};
 use poem_openapi::{
     param::{Path, Query},
     OpenApi,
@@ -63,7 +64,15 @@ impl EventsApi {
         ///
         /// If unspecified, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Only return the events of the page matching this filter, e.g.
+        /// `data.amount >= 100 && data.amount < 1000`. Clauses are separated by `&&`
+        /// and are one of `account == A | B`, `module == M | N`, `type == T | U` or
+        /// `data.<field> <op> <value>`, with `op` one of `==`, `!=`, `<`, `<=`, `>`, `>=`.
+        ///
+        /// Events are filtered after paging, so a page may hold fewer than `limit` events.
+        filter: Query<Option<String>>,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        let filter = parse_filter(filter.0.as_deref())?;
         fail_point_poem("endpoint_get_events_by_event_key")?;
         self.context
             .check_api_output_enabled("Get events by event key", &accept_type)?;
@@ -81,6 +90,7 @@ impl EventsApi {
             accept_type,
             page,
             EventKey::new(creation_number.0 .0, address.0.into()),
+            filter,
         )
     }
 
@@ -115,6 +125,13 @@ impl EventsApi {
         ///
         /// If unspecified, defaults to default page size
         limit: Query<Option<u16>>,
+        /// Only return the events of the page matching this filter, e.g.
+        /// `data.amount >= 100 && data.amount < 1000`. Clauses are separated by `&&`
+        /// and are one of `account == A | B`, `module == M | N`, `type == T | U` or
+        /// `data.<field> <op> <value>`, with `op` one of `==`, `!=`, `<`, `<=`, `>`, `>=`.
+        ///
+        /// Events are filtered after paging, so a page may hold fewer than `limit` events.
+        filter: Query<Option<String>>,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
         event_handle
             .0
@@ -128,6 +145,7 @@ impl EventsApi {
             .map_err(|err| {
                 BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
             })?;
+        let filter = parse_filter(filter.0.as_deref())?;
         fail_point_poem("endpoint_get_events_by_event_handle")?;
         self.context
             .check_api_output_enabled("Get events by event handle", &accept_type)?;
@@ -138,18 +156,129 @@ impl EventsApi {
         );
         let account = Account::new(self.context.clone(), address.0, None, None, None)?;
         let key = account.find_event_key(event_handle.0, field_name.0.into())?;
-        self.list(account.latest_ledger_info, accept_type, page, key)
+        self.list(account.latest_ledger_info, accept_type, page, key, filter)
+    }
+
+    /// Get events by type
+    ///
+    /// Returns the events of the given type emitted by the transactions in a range of
+    /// versions, whichever accounts they were emitted to, e.g. every deposit of a coin
+    /// since a version.
+    ///
+    /// At most `api.max_events_by_type_scan_range` versions are scanned per request. If
+    /// the range isn't scanned to its end, because of that or because `limit` events were
+    /// found, the version to start the next request at is returned in the X-Aptos-Cursor
+    /// header.
+    #[oai(
+        path = "/events/by_type/:event_type",
+        method = "get",
+        operation_id = "get_events_by_type",
+        tag = "ApiTags::Events"
+    )]
+    async fn get_events_by_type(
+        &self,
+        accept_type: AcceptType,
+        /// Type of the events e.g. `0x1::coin::DepositEvent`
+        event_type: Path<MoveStructTag>,
+        /// First version scanned for events
+        start_version: Query<U64>,
+        /// Version the scan ends at, exclusive
+        ///
+        /// If unspecified, scans up to the latest ledger version, included
+        end_version: Query<Option<U64>>,
+        /// Max number of events to retrieve.
+        ///
+        /// If unspecified, defaults to default page size
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<Vec<VersionedEvent>> {
+        event_type
+            .0
+            .verify(0)
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_events_by_type")?;
+        self.context
+            .check_api_output_enabled("Get events by type", &accept_type)?;
+        let latest_ledger_info = self.context.get_latest_ledger_info()?;
+        let event_type: StructTag = event_type
+            .0
+            .try_into()
+            .context("'event_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &latest_ledger_info,
+                )
+            })?;
+
+        let start_version = start_version.0 .0;
+        if start_version < latest_ledger_info.oldest_ledger_version.0 {
+            return Err(version_pruned(start_version, &latest_ledger_info));
+        } else if start_version > latest_ledger_info.version() {
+            return Err(version_not_found(start_version, &latest_ledger_info));
+        }
+        let end_version = end_version
+            .0
+            .map_or(latest_ledger_info.version() + 1, |version| version.0);
+        if end_version < start_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "'end_version' ({}) is lower than 'start_version' ({})",
+                    end_version, start_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &latest_ledger_info,
+            ));
+        }
+        let scan_end = end_version
+            .min(latest_ledger_info.version() + 1)
+            .min(start_version.saturating_add(self.context.max_events_by_type_scan_range()));
+        let limit = Page::new(None, limit.0, self.context.max_events_page_size())
+            .limit(&latest_ledger_info)?;
+
+        let (events, next_version) = self
+            .context
+            .get_events_by_type(
+                &TypeTag::Struct(Box::new(event_type.clone())),
+                start_version,
+                scan_end,
+                limit,
+            )
+            .context(format!("Failed to find events of type {}", event_type))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &latest_ledger_info,
+                )
+            })?;
+        let cursor = (next_version < end_version).then_some(next_version);
+
+        match accept_type {
+            AcceptType::Json => {
+                let events = self.render_events(&latest_ledger_info, &events)?;
+                BasicResponse::try_from_json((events, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((events, &latest_ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+        .map(|response| response.with_version_cursor(cursor))
     }
 }
 
 impl EventsApi {
-    /// List events from an [`EventKey`]
+    /// List events from an [`EventKey`], keeping those matching `filter`
     fn list(
         &self,
         latest_ledger_info: LedgerInfo,
         accept_type: AcceptType,
         page: Page,
         event_key: EventKey,
+        filter: Option<EventFilter>,
     ) -> BasicResultWith404<Vec<VersionedEvent>> {
         let ledger_version = latest_ledger_info.version();
         let events = self
@@ -171,24 +300,60 @@ impl EventsApi {
 
         match accept_type {
             AcceptType::Json => {
-                let resolver = self.context.move_resolver_poem(&latest_ledger_info)?;
-                let events = resolver
-                    .as_converter(self.context.db.clone())
-                    .try_into_versioned_events(&events)
-                    .context("Failed to convert events from storage into response")
-                    .map_err(|err| {
-                        BasicErrorWith404::internal_with_code(
-                            err,
-                            AptosErrorCode::InternalError,
-                            &latest_ledger_info,
-                        )
-                    })?;
+                let mut events = self.render_events(&latest_ledger_info, &events)?;
+                if let Some(filter) = &filter {
+                    events.retain(|event| filter.matches_versioned_event(event));
+                }
 
                 BasicResponse::try_from_json((events, &latest_ledger_info, BasicResponseStatus::Ok))
             },
             AcceptType::Bcs => {
+                // Filters may look at the data of events, so they're evaluated on the JSON form
+                let events = match &filter {
+                    Some(filter) => {
+                        let rendered = self.render_events(&latest_ledger_info, &events)?;
+                        events
+                            .into_iter()
+                            .zip(rendered)
+                            .filter(|(_, rendered)| filter.matches_versioned_event(rendered))
+                            .map(|(event, _)| event)
+                            .collect()
+                    },
+                    None => events,
+                };
                 BasicResponse::try_from_bcs((events, &latest_ledger_info, BasicResponseStatus::Ok))
             },
         }
     }
+
+    fn render_events(
+        &self,
+        latest_ledger_info: &LedgerInfo,
+        events: &[EventWithVersion],
+    ) -> Result<Vec<VersionedEvent>, BasicErrorWith404> {
+        let resolver = self.context.move_resolver_poem(latest_ledger_info)?;
+        resolver
+            .as_converter(self.context.db.clone())
+            .try_into_versioned_events(events)
+            .context("Failed to convert events from storage into response")
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    latest_ledger_info,
+                )
+            })
+    }
+}
+
+/// An empty filter matches every event, so it's the same as no filter.
+fn parse_filter(filter: Option<&str>) -> Result<Option<EventFilter>, BasicErrorWith404> {
+    filter
+        .map(str::parse::<EventFilter>)
+        .transpose()
+        .context("'filter' invalid")
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })
+        .map(|filter| filter.filter(|filter| !filter.is_empty()))
 }
@@ -8,7 +8,7 @@ use crate::{
     response::{BasicResponse, BasicResponseStatus, BasicResult},
     ApiTags,
 };
-use aptos_api_types::{IndexResponse, IndexResponseBcs};
+use aptos_api_types::{IndexResponse, IndexResponseBcs, NodeInfo, API_FEATURES};
 use poem_openapi::OpenApi;
 use std::sync::Arc;
 
@@ -55,4 +55,49 @@ impl IndexApi {
             },
         }
     }
+
+    /// Get node info
+    ///
+    /// Get what the API of the node supports: the features of its release, whether
+    /// transactions can be submitted and simulated, and the limits on page and batch sizes.
+    /// Clients talking to nodes of different releases can use it to pick the code paths each
+    /// node supports.
+    #[oai(
+        path = "/info",
+        method = "get",
+        operation_id = "get_node_info",
+        tag = "ApiTags::General"
+    )]
+    async fn get_node_info(&self, accept_type: AcceptType) -> BasicResult<NodeInfo> {
+        self.context
+            .check_api_output_enabled("Get node info", &accept_type)?;
+        let ledger_info = self.context.get_latest_ledger_info()?;
+
+        let api_config = &self.context.node_config.api;
+        let node_info = NodeInfo {
+            git_hash: aptos_build_info::get_git_hash(),
+            features: API_FEATURES
+                .iter()
+                .map(|feature| feature.to_string())
+                .collect(),
+            transaction_submission_enabled: api_config.transaction_submission_enabled,
+            transaction_simulation_enabled: api_config.transaction_simulation_enabled,
+            max_submit_transaction_batch_size: (self.context.max_submit_transaction_batch_size()
+                as u64)
+                .into(),
+            max_batch_read_size: (self.context.max_batch_read_size() as u64).into(),
+            max_transactions_page_size: self.context.max_transactions_page_size(),
+            max_events_page_size: self.context.max_events_page_size(),
+            max_account_resources_page_size: self.context.max_account_resources_page_size(),
+            max_account_modules_page_size: self.context.max_account_modules_page_size(),
+            max_events_by_type_scan_range: self.context.max_events_by_type_scan_range().into(),
+            max_gas_view_function: self.context.max_gas_view_function().into(),
+        };
+        BasicResponse::try_from_rust_value((
+            node_info,
+            &ledger_info,
+            BasicResponseStatus::Ok,
+            &accept_type,
+        ))
+    }
 }
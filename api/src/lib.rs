@@ -6,9 +6,12 @@ use poem_openapi::Tags;
 
 mod accept_type;
 mod accounts;
+mod admin;
 mod basic;
+mod batch;
 mod bcs_payload;
 mod blocks;
+mod chaos;
 mod check_size;
 pub mod context;
 mod error_converter;
@@ -471,6 +471,19 @@ macro_rules! generate_success_response {
                 }
                 self
             }
+
+            /// Sets the cursor to the version the next page starts at, for endpoints paging
+            /// through ranges of versions.
+            pub fn with_version_cursor(mut self, next_version: Option<u64>) -> Self {
+                match self {
+                    $(
+                    [<$enum_name>]::$name(_, _, _, _, _, _, _, _, ref mut cursor) => {
+                        *cursor = next_version.map(|version| version.to_string());
+                    }
+                    )*
+                }
+                self
+            }
         }
         }
     };
@@ -3,13 +3,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
-    context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    accounts::AccountsApi, admin, basic::BasicApi, batch::BatchApi, blocks::BlocksApi,
+    chaos::middleware_chaos, check_size::PostSizeLimit, context::Context,
+    error_converter::convert_error, events::EventsApi, index::IndexApi, log::middleware_log,
+    set_failpoints, state::StateApi, transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
-use aptos_config::config::{ApiConfig, NodeConfig};
+use aptos_config::config::{ApiConfig, ConfigReloader, NodeConfig};
 use aptos_logger::info;
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::DbReader;
@@ -32,11 +32,13 @@ pub fn bootstrap(
     chain_id: ChainId,
     db: Arc<dyn DbReader>,
     mp_sender: MempoolClientSender,
+    config_reloader: ConfigReloader,
 ) -> anyhow::Result<Runtime> {
     let max_runtime_workers = get_max_runtime_workers(&config.api);
     let runtime = aptos_runtimes::spawn_named_runtime("api".into(), Some(max_runtime_workers));
 
-    let context = Context::new(chain_id, db, mp_sender, config.clone());
+    let context =
+        Context::new(chain_id, db, mp_sender, config.clone()).with_config_reloader(config_reloader);
 
     attach_poem_to_runtime(runtime.handle(), context, config, false)
         .context("Failed to attach poem to runtime")?;
@@ -57,6 +59,7 @@ pub fn get_api_service(
     (
         AccountsApi,
         BasicApi,
+        BatchApi,
         BlocksApi,
         EventsApi,
         IndexApi,
@@ -74,6 +77,9 @@ pub fn get_api_service(
         BasicApi {
             context: context.clone(),
         },
+        BatchApi {
+            context: context.clone(),
+        },
         BlocksApi {
             context: context.clone(),
         },
@@ -117,6 +123,7 @@ pub fn attach_poem_to_runtime(
     let context = Arc::new(context);
 
     let size_limit = context.content_length_limit();
+    let chaos = context.chaos().cloned();
 
     let api_service = get_api_service(context.clone());
 
@@ -184,12 +191,33 @@ pub fn attach_poem_to_runtime(
                     .at(
                         "/set_failpoint",
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/admin/advance_time",
+                        poem::post(admin::advance_time_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/admin/force_epoch_change",
+                        poem::post(admin::force_epoch_change_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/admin/reload_config",
+                        poem::post(admin::reload_config_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/admin/loader_cache",
+                        poem::get(admin::loader_cache_poem).data(context.clone()),
+                    )
+                    .at(
+                        "/admin/loader_cache/flush",
+                        poem::post(admin::flush_loader_cache_poem).data(context.clone()),
                     ),
             )
             .with(cors)
             .with(PostSizeLimit::new(size_limit))
             // NOTE: Make sure to keep this after all the `with` middleware.
             .catch_all_error(convert_error)
+            .around(move |endpoint, request| middleware_chaos(chaos.clone(), endpoint, request))
             .around(middleware_log);
         Server::new_with_acceptor(acceptor)
             .run(route)
@@ -216,7 +244,7 @@ mod tests {
     use super::bootstrap;
     use crate::runtime::get_max_runtime_workers;
     use aptos_api_test_context::{new_test_context, TestContext};
-    use aptos_config::config::{ApiConfig, NodeConfig};
+    use aptos_config::config::{ApiConfig, ConfigReloader, NodeConfig};
     use aptos_types::chain_id::ChainId;
     use std::time::Duration;
 
@@ -278,6 +306,7 @@ mod tests {
             ChainId::test(),
             context.db.clone(),
             context.mempool.ac_client.clone(),
+            ConfigReloader::new(None, &cfg),
         );
         assert!(ret.is_ok());
 
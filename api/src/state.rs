@@ -84,6 +84,53 @@ impl StateApi {
         )
     }
 
+    /// Get account resource group
+    ///
+    /// Retrieves the resources of a resource group, e.g. `0x1::object::ObjectGroup`, from a given
+    /// account and at a specific ledger version. If the ledger version is not specified in the
+    /// request, the latest ledger version is used.
+    ///
+    /// The BCS response is the group as stored, a BCS encoded map from the type of each resource
+    /// to its BCS encoded value.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/resource_group/:resource_group_type",
+        method = "get",
+        operation_id = "get_account_resource_group",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_resource_group(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Name of the resource group to retrieve e.g. `0x1::object::ObjectGroup`
+        resource_group_type: Path<MoveStructTag>,
+        /// Ledger version to get state of account
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<MoveResource>> {
+        resource_group_type
+            .0
+            .verify(0)
+            .context("'resource_group_type' invalid")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+        fail_point_poem("endpoint_get_account_resource_group")?;
+        self.context
+            .check_api_output_enabled("Get account resource group", &accept_type)?;
+        self.resource_group(
+            &accept_type,
+            address.0,
+            resource_group_type.0,
+            ledger_version.0.map(|inner| inner.0),
+        )
+    }
+
     /// Get account module
     ///
     /// Retrieves an individual module from a given account and at a specific ledger version. If the
@@ -277,6 +324,69 @@ impl StateApi {
         }
     }
 
+    /// Retrieve the resources of a resource group
+    ///
+    /// JSON: Convert each resource to JSON
+    /// BCS: Leave the group as is BCS encoded
+    pub fn resource_group(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        resource_group_type: MoveStructTag,
+        ledger_version: Option<u64>,
+    ) -> BasicResultWith404<Vec<MoveResource>> {
+        let resource_group_type: StructTag = resource_group_type
+            .try_into()
+            .context("Failed to parse given resource group type")
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+            })?;
+
+        let (ledger_info, ledger_version, state_view) = self.context.state_view(ledger_version)?;
+        let state_key = StateKey::access_path(AccessPath::resource_group_access_path(
+            address.into(),
+            resource_group_type.clone(),
+        ));
+        let bytes = state_view
+            .get_state_value_bytes(&state_key)
+            .context(format!(
+                "Failed to query DB to check for {} at {}",
+                resource_group_type, address
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?
+            .ok_or_else(|| {
+                resource_not_found(address, &resource_group_type, ledger_version, &ledger_info)
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                let resources = state_view
+                    .as_move_resolver()
+                    .as_converter(self.context.db.clone())
+                    .try_into_resources_from_resource_group(&bytes)
+                    .context("Failed to deserialize resource group data retrieved from DB")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?;
+
+                BasicResponse::try_from_json((resources, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_encoded((bytes, &ledger_info, BasicResponseStatus::Ok))
+            },
+        }
+    }
+
     /// Retrieve the module
     ///
     /// JSON: Parse ABI and bytecode
@@ -4,7 +4,10 @@
 
 use super::new_test_context;
 use aptos_api_test_context::{current_function_name, find_value};
-use aptos_api_types::{MoveModuleBytecode, MoveResource, StateKeyWrapper};
+use aptos_api_types::{
+    AccountStateKey, AccountStateKeyKind, MoveModuleBytecode, MoveResource, StateKeyWrapper,
+};
+use aptos_types::account_address::AccountAddress;
 use serde_json::json;
 use std::str::FromStr;
 
@@ -348,6 +351,111 @@ async fn test_get_account_modules_with_pagination() {
     assert_eq!(modules, all_modules[10..].to_vec());
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_state_keys_with_prefix() {
+    let context = new_test_context(current_function_name!());
+    let address = "0x1";
+
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}", account_state_keys(address)));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let all_keys: Vec<AccountStateKey> = serde_json::from_slice(resp.body()).unwrap();
+    let num_modules = all_keys
+        .iter()
+        .filter(|key| key.kind == AccountStateKeyKind::Module)
+        .count();
+    assert!(num_modules >= 10);
+    assert!(num_modules < all_keys.len());
+
+    // Only the coin module and the resources defined in it
+    let req = warp::test::request().method("GET").path(&format!(
+        "/v1{}?prefix=0x1::coin",
+        account_state_keys(address)
+    ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let coin_keys: Vec<AccountStateKey> = serde_json::from_slice(resp.body()).unwrap();
+    assert!(coin_keys
+        .iter()
+        .any(|key| key.kind == AccountStateKeyKind::Module && key.name == "0x1::coin"));
+    assert!(coin_keys
+        .iter()
+        .all(|key| key.name.starts_with("0x1::coin")));
+    let expected: Vec<AccountStateKey> = all_keys
+        .iter()
+        .filter(|key| key.name.starts_with("0x1::coin"))
+        .cloned()
+        .collect();
+    assert_eq!(coin_keys, expected);
+
+    // Pagination only counts the matching keys
+    let req = warp::test::request().method("GET").path(&format!(
+        "/v1{}?prefix=0x1::coin&limit=1",
+        account_state_keys(address)
+    ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor_header = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing");
+    let cursor_header = StateKeyWrapper::from_str(cursor_header.to_str().unwrap()).unwrap();
+    let keys: Vec<AccountStateKey> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(keys, expected[0..1].to_vec());
+
+    let req = warp::test::request().method("GET").path(&format!(
+        "/v1{}?prefix=0x1::coin&limit=1000&start={}",
+        account_state_keys(address),
+        cursor_header
+    ));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Aptos-Cursor"));
+    let keys: Vec<AccountStateKey> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(keys, expected[1..].to_vec());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_state_keys_scan_range() {
+    let context = new_test_context(current_function_name!());
+    let version = context.get_latest_ledger_info().version();
+    let (expected, next_key) = context
+        .context
+        .get_state_keys_by_pagination(AccountAddress::ONE, Some("0x1::coin"), None, version, 1000)
+        .unwrap();
+    assert!(next_key.is_none());
+    assert!(expected.len() > 1);
+
+    // Few keys match, so every page is cut short by the scan range rather than by the limit
+    let mut api_context = context.context.clone();
+    api_context.node_config.api.max_state_keys_scan_range = 5;
+    let mut keys = vec![];
+    let mut cursor = None;
+    let mut num_pages = 0;
+    loop {
+        let (page, next_key) = api_context
+            .get_state_keys_by_pagination(
+                AccountAddress::ONE,
+                Some("0x1::coin"),
+                cursor.as_ref(),
+                version,
+                1000,
+            )
+            .unwrap();
+        assert!(page.len() <= 5);
+        keys.extend(page);
+        num_pages += 1;
+        match next_key {
+            Some(next_key) => cursor = Some(next_key),
+            None => break,
+        }
+    }
+    assert!(num_pages > 1);
+    assert_eq!(keys, expected);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_get_account_items_limit_params() {
     let context = new_test_context(current_function_name!());
@@ -388,6 +496,10 @@ fn account_resources_with_ledger_version(address: &str, ledger_version: i128) ->
     )
 }
 
+fn account_state_keys(address: &str) -> String {
+    format!("/accounts/{}/state_keys", address)
+}
+
 fn account_modules(address: &str) -> String {
     format!("/accounts/{}/modules", address)
 }
@@ -0,0 +1,86 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use crate::admin;
+use aptos_api_test_context::current_function_name;
+use aptos_types::timestamp::chain_clock_offset;
+use poem::{
+    http::{Method, StatusCode},
+    Endpoint, EndpointExt, Request, Route,
+};
+use std::{sync::Arc, time::Duration};
+
+fn admin_routes(admin_endpoints_enabled: bool) -> impl Endpoint {
+    let mut context = new_test_context(current_function_name!()).context;
+    context.node_config.api.admin_endpoints_enabled = admin_endpoints_enabled;
+    let context = Arc::new(context);
+    Route::new()
+        .at(
+            "/admin/advance_time",
+            poem::post(admin::advance_time_poem).data(context.clone()),
+        )
+        .at(
+            "/admin/force_epoch_change",
+            poem::post(admin::force_epoch_change_poem).data(context),
+        )
+}
+
+async fn post(routes: &impl Endpoint, uri: &str) -> (StatusCode, String) {
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri(uri.parse().unwrap())
+        .finish();
+    let response = routes.get_response(request).await;
+    let status = response.status();
+    (status, response.into_body().into_string().await.unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_admin_endpoints_disabled() {
+    let routes = admin_routes(false);
+    let offset = chain_clock_offset();
+
+    let (status, _) = post(&routes, "/admin/advance_time?seconds=10").await;
+    assert!(!status.is_success());
+    let (status, _) = post(&routes, "/admin/force_epoch_change").await;
+    assert!(!status.is_success());
+    assert_eq!(chain_clock_offset(), offset);
+}
+
+// The chain clock offset is global to the process, so everything changing it is in one test
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_advance_time() {
+    let routes = admin_routes(true);
+    let offset = chain_clock_offset();
+
+    let (status, body) = post(&routes, "/admin/advance_time?seconds=10").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.starts_with("Advanced chain time by 10s"), "{}", body);
+    assert_eq!(chain_clock_offset(), offset + Duration::from_secs(10));
+
+    // Chain time is still at genesis, a whole epoch interval before the epoch ends
+    let (status, body) = post(&routes, "/admin/force_epoch_change").await;
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert!(body.contains("to end epoch"), "{}", body);
+    assert!(chain_clock_offset() > offset + Duration::from_secs(10));
+    let offset = chain_clock_offset();
+
+    // The seconds don't fit in a u64 of microseconds
+    let (status, body) = post(
+        &routes,
+        &format!("/admin/advance_time?seconds={}", u64::MAX),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(chain_clock_offset(), offset);
+
+    // The microseconds fit, but chain time wouldn't anymore
+    let (status, body) = post(
+        &routes,
+        &format!("/admin/advance_time?seconds={}", u64::MAX / 1_000_000),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST, "{}", body);
+    assert_eq!(chain_clock_offset(), offset);
+}
@@ -0,0 +1,73 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::new_test_context;
+use aptos_api_test_context::current_function_name;
+use serde_json::json;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_read() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn1 = context.mint_user_account(creator).await;
+    let txn2 = context.account_transfer(creator, owner, 100_000);
+
+    context.commit_block(&vec![txn1, txn2]).await;
+
+    let resp = context
+        .post(
+            "/batch",
+            json!([
+                {
+                    "type": "resource",
+                    "address": owner.address().to_hex_literal(),
+                    "resource_type": "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                },
+                {
+                    "type": "view",
+                    "function": "0x1::coin::balance",
+                    "arguments": vec![owner.address().to_string()],
+                    "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                },
+                {
+                    "type": "resource",
+                    "address": owner.address().to_hex_literal(),
+                    "resource_type": "0x1::guid::GUID",
+                },
+            ]),
+        )
+        .await;
+
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["data"]["data"]["coin"]["value"], json!("100000"));
+    assert_eq!(results[0]["error"], json!(null));
+    assert_eq!(results[1]["data"], json!(["100000"]));
+    // A failed read doesn't fail the rest of the batch
+    assert_eq!(results[2]["data"], json!(null));
+    assert_eq!(
+        results[2]["error"]["error_code"],
+        json!("resource_not_found")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_batch_read_too_many_requests() {
+    let mut context = new_test_context(current_function_name!());
+    let requests = (0..=context.context.max_batch_read_size())
+        .map(|_| {
+            json!({
+                "type": "resource",
+                "address": "0x1",
+                "resource_type": "0x1::account::Account",
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let resp = context
+        .expect_status_code(400)
+        .post("/batch", json!(requests))
+        .await;
+    assert_eq!(resp["error_code"], json!("invalid_input"));
+}
@@ -155,3 +155,126 @@ async fn test_get_events_by_struct_type_has_generic_type_parameter() {
     let resp = context.expect_status_code(404).get(path.as_str()).await;
     context.check_golden_output(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type() {
+    let mut context = new_test_context(current_function_name!());
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let first = context.create_account().await;
+    let second = context.create_account().await;
+    let receivers = |events: &[serde_json::Value]| -> Vec<String> {
+        events
+            .iter()
+            .map(|event| {
+                event["guid"]["account_address"]
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect()
+    };
+
+    let path = format!(
+        "/events/by_type/0x1::coin::DepositEvent?start_version={}",
+        start_version
+    );
+    let resp = context.get(&path).await;
+    assert_eq!(receivers(resp.as_array().unwrap()), vec![
+        first.address().to_hex_literal(),
+        second.address().to_hex_literal()
+    ]);
+
+    // With a limit, the next page starts at the cursor
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}&limit=1", path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let events: Vec<serde_json::Value> = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(receivers(&events), vec![first.address().to_hex_literal()]);
+
+    let resp = context
+        .get(&format!(
+            "/events/by_type/0x1::coin::DepositEvent?start_version={}&limit=1",
+            cursor
+        ))
+        .await;
+    assert_eq!(receivers(resp.as_array().unwrap()), vec![second
+        .address()
+        .to_hex_literal()]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_by_type_invalid_range() {
+    let context = new_test_context(current_function_name!());
+    let version = context.get_latest_ledger_info().version();
+    context
+        .expect_status_code(400)
+        .get(&format!(
+            "/events/by_type/0x1::coin::DepositEvent?start_version={}&end_version={}",
+            version,
+            version - 1
+        ))
+        .await;
+    context
+        .expect_status_code(404)
+        .get(&format!(
+            "/events/by_type/0x1::coin::DepositEvent?start_version={}",
+            version + 1
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_events_with_filter() {
+    let mut context = new_test_context(current_function_name!());
+    let receiver = context.create_account().await;
+    let mut root = context.root_account().await;
+    let txns = [100, 1_000]
+        .map(|amount| context.account_transfer_to(&mut root, receiver.address(), amount));
+    context.commit_block(&txns).await;
+
+    let path = |filter: &str| {
+        format!(
+            "/accounts/{}/events/{}/deposit_events?filter={}",
+            receiver.address().to_hex_literal(),
+            utf8_percent_encode(
+                "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                NON_ALPHANUMERIC
+            ),
+            utf8_percent_encode(filter, NON_ALPHANUMERIC)
+        )
+    };
+    let amounts = |events: serde_json::Value| -> Vec<String> {
+        events
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|event| event["data"]["amount"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    // The account was created with a deposit of 10_000_000
+    let resp = context.get(&path("")).await;
+    assert_eq!(amounts(resp), vec!["10000000", "100", "1000"]);
+    let resp = context
+        .get(&path("data.amount >= 100 && data.amount < 10000"))
+        .await;
+    assert_eq!(amounts(resp), vec!["100", "1000"]);
+    let resp = context.get(&path("data.amount == 1000")).await;
+    assert_eq!(amounts(resp), vec!["1000"]);
+    let resp = context.get(&path("type == 0x1::coin::WithdrawEvent")).await;
+    assert_eq!(amounts(resp), Vec::<String>::new());
+
+    context
+        .expect_status_code(400)
+        .get(&path("amount >= 100"))
+        .await;
+}
@@ -4,6 +4,7 @@
 
 use super::new_test_context;
 use aptos_api_test_context::current_function_name;
+use aptos_api_types::API_FEATURES;
 use serde_json::json;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -13,6 +14,22 @@ async fn test_get_index() {
     context.check_golden_output(resp);
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_node_info() {
+    let mut context = new_test_context(current_function_name!());
+    let resp = context.get("/info").await;
+    let features: Vec<&str> = resp["features"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|feature| feature.as_str().unwrap())
+        .collect();
+    assert_eq!(features, API_FEATURES);
+    assert_eq!(resp["transaction_submission_enabled"], true);
+    assert_eq!(resp["max_batch_read_size"], "50");
+    assert_eq!(resp["max_events_page_size"], 100);
+}
+
 // TODO: Un-ignore this pending https://github.com/poem-web/poem/issues/343.
 #[ignore]
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
@@ -3,6 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod accounts_test;
+mod admin_test;
+mod batch_test;
 mod blocks_test;
 mod converter_test;
 mod events_test;
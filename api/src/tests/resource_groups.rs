@@ -17,6 +17,7 @@ use std::path::PathBuf;
 // 8. Read the resources from the new resource group and verify they don't exist
 // 9. Init data for that resource group / member
 // 10. Read and ensure data is present
+// 11. Read the resource group and ensure it has both resources
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn test_gen_resource_group() {
     let mut context = new_test_context(current_function_name!());
@@ -91,6 +92,27 @@ async fn test_gen_resource_group() {
     let response = context.gen_resource(&user.address(), &secondary).await;
     assert_eq!(response.unwrap()["data"]["value"], 55);
 
+    // Read both members at once through the resource group
+    let group = format!(
+        "0x{}::{}::{}",
+        admin0.address(),
+        "primary",
+        "ResourceGroupContainer"
+    );
+    let resp = context
+        .get(format!("/accounts/{}/resource_group/{}", user.address(), group).as_str())
+        .await;
+    let resources = resp.as_array().unwrap();
+    assert_eq!(resources.len(), 2);
+    let value_of = |name: &str| {
+        resources
+            .iter()
+            .find(|resource| resource["type"].as_str().unwrap().ends_with(name))
+            .map(|resource| resource["data"]["value"].clone())
+    };
+    assert_eq!(value_of("::primary::Primary"), Some(json!("35")));
+    assert_eq!(value_of("::secondary::Secondary"), Some(json!(55)));
+
     let resp = context
         .get(format!("/accounts/{}/transactions", user.address()).as_str())
         .await;
@@ -36,7 +36,7 @@ use aptos_types::{
         EntryFunction, ExecutionStatus, MultisigTransactionPayload, RawTransaction,
         RawTransactionWithData, SignedTransaction, TransactionPayload, TransactionStatus,
     },
-    vm_status::StatusCode,
+    vm_status::{StatusCode, VMStatus},
 };
 use aptos_vm::AptosVM;
 use poem_openapi::{
@@ -1173,7 +1173,7 @@ impl TransactionsApi {
 
         // Simulate transaction
         let move_resolver = self.context.move_resolver_poem(&ledger_info)?;
-        let (_, output_ext) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
+        let (vm_status, output_ext) = AptosVM::simulate_signed_transaction(&txn, &move_resolver);
         let version = ledger_info.version();
 
         // Apply transaction outputs to build up a transaction
@@ -1221,7 +1221,19 @@ impl TransactionsApi {
                 let mut user_transactions = Vec::new();
                 for transaction in transactions.into_iter() {
                     match transaction {
-                        Transaction::UserTransaction(user_txn) => user_transactions.push(*user_txn),
+                        Transaction::UserTransaction(mut user_txn) => {
+                            // The status stored with transactions has no room for why a module
+                            // upgrade is incompatible, but simulations can show it
+                            if let VMStatus::Error(
+                                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
+                                Some(message),
+                            ) = &vm_status
+                            {
+                                user_txn.info.vm_status =
+                                    format!("{}: {}", user_txn.info.vm_status, message);
+                            }
+                            user_transactions.push(*user_txn)
+                        },
                         _ => {
                             return Err(SubmitTransactionError::internal_with_code(
                                 "Simulation transaction resulted in a non-UserTransaction",
@@ -2,9 +2,9 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{HexEncodedBytes, U64};
+use crate::{HashValue, HexEncodedBytes, U64};
 use aptos_types::account_config::AccountResource;
-use poem_openapi::Object;
+use poem_openapi::{Enum, Object};
 use serde::{Deserialize, Serialize};
 
 /// Account data
@@ -25,3 +25,27 @@ impl From<AccountResource> for AccountData {
         }
     }
 }
+
+/// What a state key under an account points at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, Enum)]
+#[serde(rename_all = "snake_case")]
+#[oai(rename_all = "snake_case")]
+pub enum AccountStateKeyKind {
+    Resource,
+    ResourceGroup,
+    Module,
+}
+
+/// A state key stored under an account
+///
+/// Lets tooling discover what's stored under an account without downloading the
+/// data itself
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct AccountStateKey {
+    pub kind: AccountStateKeyKind,
+    /// Struct tag of the resource or resource group, or id of the module
+    ///
+    /// e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>` or `0x1::coin`
+    pub name: String,
+    pub state_key_hash: HashValue,
+}
@@ -0,0 +1,245 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{bail, ensure, format_err};
+use std::{fmt, str::FromStr};
+
+/// The number of decimals of APT: one APT is 10^8 octas.
+pub const APT_DECIMALS: u8 = 8;
+pub const APT_SYMBOL: &str = "APT";
+
+/// The largest number of decimals an amount can have, as 10^19 doesn't fit in a u64.
+const MAX_DECIMALS: u8 = 19;
+
+/// An amount of a coin: its value in the smallest unit of the coin, along with the number of
+/// decimals of the coin. For example, 150000000 octas are 1.5 APT.
+///
+/// Arithmetic is checked, and only combines amounts with the same number of decimals.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Amount {
+    value: u64,
+    decimals: u8,
+}
+
+impl Amount {
+    /// An amount of `value` units of a coin with `decimals` decimals.
+    pub fn new(value: u64, decimals: u8) -> anyhow::Result<Self> {
+        ensure!(
+            decimals <= MAX_DECIMALS,
+            "Coins have at most {} decimals, got {}",
+            MAX_DECIMALS,
+            decimals
+        );
+        Ok(Self { value, decimals })
+    }
+
+    pub const fn from_octas(octas: u64) -> Self {
+        Self {
+            value: octas,
+            decimals: APT_DECIMALS,
+        }
+    }
+
+    /// The value in the smallest unit of the coin, e.g. in octas for APT.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn checked_add(self, other: Amount) -> anyhow::Result<Amount> {
+        self.check_decimals(other)?;
+        let value = self
+            .value
+            .checked_add(other.value)
+            .ok_or_else(|| format_err!("{} + {} overflows", self, other))?;
+        Ok(Self { value, ..self })
+    }
+
+    pub fn checked_sub(self, other: Amount) -> anyhow::Result<Amount> {
+        self.check_decimals(other)?;
+        let value = self
+            .value
+            .checked_sub(other.value)
+            .ok_or_else(|| format_err!("{} - {} is negative", self, other))?;
+        Ok(Self { value, ..self })
+    }
+
+    pub fn checked_mul(self, factor: u64) -> anyhow::Result<Amount> {
+        let value = self
+            .value
+            .checked_mul(factor)
+            .ok_or_else(|| format_err!("{} * {} overflows", self, factor))?;
+        Ok(Self { value, ..self })
+    }
+
+    /// Parses an amount written for humans, e.g. `1.5` or `1.5 APT` for `symbol` `APT`. The
+    /// symbol is optional, and compared case insensitively.
+    pub fn parse(str: &str, symbol: &str, decimals: u8) -> anyhow::Result<Amount> {
+        let trimmed = str.trim();
+        let number = match trimmed.len().checked_sub(symbol.len()) {
+            Some(end)
+                if !symbol.is_empty()
+                    && trimmed.is_char_boundary(end)
+                    && trimmed[end..].eq_ignore_ascii_case(symbol) =>
+            {
+                trimmed[..end].trim_end()
+            },
+            _ => trimmed,
+        };
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        ensure!(
+            !whole.is_empty() || !fraction.is_empty(),
+            "Invalid amount {:?}",
+            str
+        );
+        ensure!(
+            whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit()),
+            "Invalid amount {:?}",
+            str
+        );
+        // Trailing zeros don't add precision
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals as usize {
+            bail!(
+                "Amount {:?} has more than the {} decimals of {}",
+                str,
+                decimals,
+                symbol
+            );
+        }
+
+        let amount = Self::new(0, decimals)?;
+        let scale = 10u64.pow(decimals as u32);
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole
+                .parse::<u64>()
+                .map_err(|_| format_err!("Amount {:?} is too large", str))?
+        };
+        let fraction = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse::<u64>()? * 10u64.pow((decimals as usize - fraction.len()) as u32)
+        };
+        let value = whole
+            .checked_mul(scale)
+            .and_then(|value| value.checked_add(fraction))
+            .ok_or_else(|| format_err!("Amount {:?} is too large", str))?;
+        Ok(Self { value, ..amount })
+    }
+
+    /// The amount followed by `symbol`, e.g. `1.5 APT`.
+    pub fn display_with_symbol(&self, symbol: &str) -> String {
+        format!("{} {}", self, symbol)
+    }
+
+    fn check_decimals(&self, other: Amount) -> anyhow::Result<()> {
+        ensure!(
+            self.decimals == other.decimals,
+            "Amounts with {} and {} decimals can't be combined",
+            self.decimals,
+            other.decimals
+        );
+        Ok(())
+    }
+}
+
+/// Formats the amount in whole coins, without trailing zeros, e.g. `1.5` for 150000000 octas.
+impl fmt::Display for Amount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let scale = 10u64.pow(self.decimals as u32);
+        let whole = self.value / scale;
+        let fraction = self.value % scale;
+        if fraction == 0 {
+            return write!(f, "{}", whole);
+        }
+        let fraction = format!("{:0width$}", fraction, width = self.decimals as usize);
+        write!(f, "{}.{}", whole, fraction.trim_end_matches('0'))
+    }
+}
+
+/// Parses an amount of APT, e.g. `1.5` or `1.5 APT`.
+impl FromStr for Amount {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, APT_SYMBOL, APT_DECIMALS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display() {
+        for (str, octas, display) in [
+            ("1.5 APT", 150_000_000, "1.5"),
+            ("1.5apt", 150_000_000, "1.5"),
+            ("  2 ", 200_000_000, "2"),
+            ("0.00000001", 1, "0.00000001"),
+            (".25", 25_000_000, "0.25"),
+            ("3.", 300_000_000, "3"),
+            ("1.100000000", 110_000_000, "1.1"),
+            ("0", 0, "0"),
+            ("184467440737.09551615", u64::MAX, "184467440737.09551615"),
+        ] {
+            let amount: Amount = str.parse().unwrap();
+            assert_eq!(amount, Amount::from_octas(octas));
+            assert_eq!(amount.to_string(), display);
+        }
+
+        for str in [
+            "",
+            ".",
+            "APT",
+            "1.5 BTC",
+            "-1",
+            "1e8",
+            "1,5",
+            "0.000000001",
+            "184467440737.09551616",
+        ] {
+            assert!(str.parse::<Amount>().is_err(), "{:?} parsed", str);
+        }
+    }
+
+    #[test]
+    fn test_decimals() {
+        let usdc = Amount::parse("12.34 USDC", "USDC", 6).unwrap();
+        assert_eq!(usdc.value(), 12_340_000);
+        assert_eq!(usdc.display_with_symbol("USDC"), "12.34 USDC");
+        assert_eq!(Amount::parse("7", "", 0).unwrap().to_string(), "7");
+        assert!(Amount::parse("0.5", "", 0).is_err());
+        assert!(Amount::new(1, 20).is_err());
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let one = Amount::from_octas(100_000_000);
+        assert_eq!(
+            one.checked_add(one).unwrap(),
+            Amount::from_octas(200_000_000)
+        );
+        assert_eq!(
+            one.checked_sub(Amount::from_octas(1)).unwrap().to_string(),
+            "0.99999999"
+        );
+        assert_eq!(one.checked_mul(3).unwrap().to_string(), "3");
+        assert!(Amount::from_octas(u64::MAX)
+            .checked_add(Amount::from_octas(1))
+            .is_err());
+        assert!(Amount::from_octas(0)
+            .checked_sub(Amount::from_octas(1))
+            .is_err());
+        assert!(one.checked_mul(u64::MAX).is_err());
+        assert!(one.checked_add(Amount::new(1, 6).unwrap()).is_err());
+    }
+}
@@ -0,0 +1,61 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Address, AptosError, MoveStructTag, MoveType, ViewRequest};
+use poem_openapi::{Object, Union};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A single read in a batch read request
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Union)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[oai(one_of, discriminator_name = "type", rename_all = "snake_case")]
+pub enum BatchReadRequest {
+    Resource(BatchResourceRequest),
+    View(ViewRequest),
+    TableItem(BatchTableItemRequest),
+}
+
+/// Read a resource of an account
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BatchResourceRequest {
+    pub address: Address,
+    pub resource_type: MoveStructTag,
+}
+
+/// Read an item of a table
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct BatchTableItemRequest {
+    pub table_handle: Address,
+    pub key_type: MoveType,
+    pub value_type: MoveType,
+    /// The value of the table item's key
+    pub key: Value,
+}
+
+/// Result of a single read in a batch read request
+///
+/// Exactly one of `data` and `error` is set. A failed read doesn't fail the rest of the batch.
+#[derive(Clone, Debug, Serialize, Deserialize, Object)]
+pub struct BatchReadResult {
+    /// The resource, the list of view function return values or the table item, in the same
+    /// JSON format as the corresponding single read API
+    pub data: Option<Value>,
+    pub error: Option<AptosError>,
+}
+
+impl BatchReadResult {
+    pub fn data(data: Value) -> Self {
+        Self {
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn error(error: AptosError) -> Self {
+        Self {
+            data: None,
+            error: Some(error),
+        }
+    }
+}
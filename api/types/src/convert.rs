@@ -10,11 +10,12 @@ use crate::{
         WriteTableItem,
     },
     view::ViewRequest,
-    Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event, HexEncodedBytes,
-    MoveFunction, MoveModuleBytecode, MoveResource, MoveScriptBytecode, MoveType, MoveValue,
-    PendingTransaction, ResourceGroup, ScriptPayload, ScriptWriteSet, SubmitTransactionRequest,
-    Transaction, TransactionInfo, TransactionOnChainData, TransactionPayload,
-    UserTransactionRequest, VersionedEvent, WriteSet, WriteSetChange, WriteSetPayload,
+    Bytecode, DirectWriteSet, EntryFunctionId, EntryFunctionPayload, Event, FixedPointDisplay,
+    FixedPointKind, HexEncodedBytes, MoveFunction, MoveModuleBytecode, MoveResource,
+    MoveScriptBytecode, MoveType, MoveValue, PendingTransaction, ResourceGroup, ScriptPayload,
+    ScriptWriteSet, SubmitTransactionRequest, Transaction, TransactionInfo, TransactionOnChainData,
+    TransactionPayload, UserTransactionRequest, VersionedEvent, WriteSet, WriteSetChange,
+    WriteSetPayload,
 };
 use anyhow::{bail, ensure, format_err, Context as AnyhowContext, Result};
 use aptos_crypto::{hash::CryptoHash, HashValue};
@@ -41,7 +42,7 @@ use move_core_types::{
     language_storage::{ModuleId, StructTag, TypeTag},
     value::{MoveStructLayout, MoveTypeLayout},
 };
-use move_resource_viewer::MoveValueAnnotator;
+use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 use serde_json::Value;
 use std::{
     convert::{TryFrom, TryInto},
@@ -57,6 +58,7 @@ use std::{
 pub struct MoveConverter<'a, R: ?Sized> {
     inner: MoveValueAnnotator<'a, R>,
     db: Arc<dyn DbReader>,
+    fixed_point_display: FixedPointDisplay,
 }
 
 impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
@@ -64,9 +66,16 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
         Self {
             inner: MoveValueAnnotator::new(inner),
             db,
+            fixed_point_display: FixedPointDisplay::default(),
         }
     }
 
+    /// Renders the fixed point numbers in the values converted as `display` says.
+    pub fn with_fixed_point_display(mut self, display: FixedPointDisplay) -> Self {
+        self.fixed_point_display = display;
+        self
+    }
+
     pub fn try_into_resources<'b>(
         &self,
         data: impl Iterator<Item = (StructTag, &'b [u8])>,
@@ -76,7 +85,10 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
     }
 
     pub fn try_into_resource<'b>(&self, typ: &StructTag, bytes: &'b [u8]) -> Result<MoveResource> {
-        self.inner.view_resource(typ, bytes)?.try_into()
+        MoveResource::try_from_annotated(
+            self.inner.view_resource(typ, bytes)?,
+            self.fixed_point_display,
+        )
     }
 
     pub fn try_into_resources_from_resource_group(
@@ -192,7 +204,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                 let json_args = match func_args {
                     Ok(values) => values
                         .into_iter()
-                        .map(|v| MoveValue::try_from(v)?.json())
+                        .map(|v| self.to_move_value(v)?.json())
                         .collect::<Result<_>>()?,
                     Err(_e) => args
                         .into_iter()
@@ -222,7 +234,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                             let json_args = match func_args {
                                 Ok(values) => values
                                     .into_iter()
-                                    .map(|v| MoveValue::try_from(v)?.json())
+                                    .map(|v| self.to_move_value(v)?.json())
                                     .collect::<Result<_>>()?,
                                 Err(_e) => args
                                     .into_iter()
@@ -470,7 +482,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
             let data = self
                 .inner
                 .view_value(event.type_tag(), event.event_data())?;
-            ret.push((event, MoveValue::try_from(data)?.json()?).into());
+            ret.push((event, self.to_move_value(data)?.json()?).into());
         }
         Ok(ret)
     }
@@ -484,7 +496,7 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
             let data = self
                 .inner
                 .view_value(event.event.type_tag(), event.event.event_data())?;
-            ret.push((event, MoveValue::try_from(data)?.json()?).into());
+            ret.push((event, self.to_move_value(data)?.json()?).into());
         }
         Ok(ret)
     }
@@ -824,6 +836,18 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
                 .ok_or_else(|| format_err!("failed to parse string::String."))?;
             return Ok(new_vm_utf8_string(string));
         }
+        // Fixed point numbers may be given as decimals rather than structs
+        if let (Some(kind), Value::String(decimal)) = (FixedPointKind::of_struct(struct_tag), &val)
+        {
+            let raw = kind.parse_decimal(decimal)?;
+            let raw = match kind {
+                FixedPointKind::FixedPoint32 => move_core_types::value::MoveValue::U64(raw as u64),
+                FixedPointKind::FixedPoint64 => move_core_types::value::MoveValue::U128(raw),
+            };
+            return Ok(move_core_types::value::MoveValue::Struct(
+                move_core_types::value::MoveStruct::Runtime(vec![raw]),
+            ));
+        }
 
         let mut field_values = if let Value::Object(fields) = val {
             fields
@@ -848,7 +872,11 @@ impl<'a, R: MoveResolverExt + ?Sized> MoveConverter<'a, R> {
     }
 
     pub fn try_into_move_value(&self, typ: &TypeTag, bytes: &[u8]) -> Result<MoveValue> {
-        self.inner.view_value(typ, bytes)?.try_into()
+        self.to_move_value(self.inner.view_value(typ, bytes)?)
+    }
+
+    fn to_move_value(&self, value: AnnotatedMoveValue) -> Result<MoveValue> {
+        MoveValue::try_from_annotated(value, self.fixed_point_display)
     }
 
     pub fn function_return_types(&self, function: &EntryFunction) -> Result<Vec<MoveType>> {
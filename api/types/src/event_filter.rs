@@ -0,0 +1,500 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A small language for filtering events, shared by the events endpoints (through the `filter`
+//! query parameter), the REST client and the indexer's event sinks. A filter is a conjunction of
+//! clauses separated by `&&`:
+//!
+//! ```text
+//! account == 0x1 | 0x2 && type == 0x1::coin::DepositEvent && data.amount >= 100
+//! ```
+//!
+//! - `account == A | B` matches events emitted under any of the accounts
+//! - `type == 0x1::coin::DepositEvent | 0x3::token` matches events of any of the types. A module
+//!   matches any type declared in it, and a generic type without its type arguments matches any
+//!   instantiation of it
+//! - `data.<field>.<field> <op> <value>` compares a field of the decoded event data to `value`,
+//!   with `op` one of `==`, `!=`, `<`, `<=`, `>`, `>=`. Numbers (including the integers encoded as
+//!   strings) are compared numerically and addresses regardless of leading zeros. Array elements
+//!   are selected by index, e.g. `data.items.0`. A clause on a missing field never matches
+
+use crate::{Address, Event, MoveModuleId, MoveStructTag, VersionedEvent};
+use anyhow::{bail, format_err};
+use move_core_types::{account_address::AccountAddress, u256::U256};
+use serde_json::Value;
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+/// The query parameter carrying a filter in its text form.
+pub const FILTER_QUERY_PARAM: &str = "filter";
+
+/// Events matching every clause of the filter. An empty filter matches every event.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EventFilter {
+    clauses: Vec<EventClause>,
+}
+
+/// A single condition of an [`EventFilter`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EventClause {
+    /// The event was emitted under any of the accounts.
+    Account(Vec<Address>),
+    /// The type of the event matches any of the patterns.
+    Type(Vec<TypePattern>),
+    /// A field of the decoded event data compares to `value` by `comparison`.
+    Data {
+        path: Vec<String>,
+        comparison: Comparison,
+        value: String,
+    },
+}
+
+/// Event types matched by a [`EventClause::Type`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TypePattern {
+    /// Any type declared in the module.
+    Module(MoveModuleId),
+    /// The type, or any instantiation of it if it's generic and given without type arguments.
+    Struct(MoveStructTag),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl EventFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only events emitted under any of `accounts`. Adds nothing if `accounts` is empty.
+    pub fn accounts(self, accounts: impl IntoIterator<Item = Address>) -> Self {
+        self.with_any(accounts, EventClause::Account)
+    }
+
+    /// Only events whose type matches any of `types`. Adds nothing if `types` is empty.
+    pub fn types(self, types: impl IntoIterator<Item = TypePattern>) -> Self {
+        self.with_any(types, EventClause::Type)
+    }
+
+    /// Only events whose data has a field at `path`, e.g. `"coin.value"`, comparing to `value`.
+    pub fn data(mut self, path: &str, comparison: Comparison, value: impl Into<String>) -> Self {
+        self.clauses.push(EventClause::Data {
+            path: path.split('.').map(str::to_string).collect(),
+            comparison,
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Only events matching both filters.
+    pub fn and(mut self, other: EventFilter) -> Self {
+        self.clauses.extend(other.clauses);
+        self
+    }
+
+    pub fn clauses(&self) -> &[EventClause] {
+        &self.clauses
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.clauses.is_empty()
+    }
+
+    /// The query parameters passing the filter to the events endpoints.
+    pub fn to_query_params(&self) -> Vec<(&'static str, String)> {
+        if self.is_empty() {
+            vec![]
+        } else {
+            vec![(FILTER_QUERY_PARAM, self.to_string())]
+        }
+    }
+
+    /// Whether an event emitted under `account`, whose type is `typ` as displayed by
+    /// [`crate::MoveType`], with the decoded `data` matches.
+    pub fn matches(&self, account: &Address, typ: &str, data: &Value) -> bool {
+        self.clauses
+            .iter()
+            .all(|clause| clause.matches(account, typ, data))
+    }
+
+    pub fn matches_event(&self, event: &Event) -> bool {
+        self.matches(
+            &event.guid.account_address,
+            &event.typ.to_string(),
+            &event.data,
+        )
+    }
+
+    pub fn matches_versioned_event(&self, event: &VersionedEvent) -> bool {
+        self.matches(
+            &event.guid.account_address,
+            &event.typ.to_string(),
+            &event.data,
+        )
+    }
+
+    fn with_any<T>(
+        mut self,
+        items: impl IntoIterator<Item = T>,
+        clause: fn(Vec<T>) -> EventClause,
+    ) -> Self {
+        let items: Vec<_> = items.into_iter().collect();
+        if !items.is_empty() {
+            self.clauses.push(clause(items));
+        }
+        self
+    }
+}
+
+impl EventClause {
+    fn matches(&self, account: &Address, typ: &str, data: &Value) -> bool {
+        match self {
+            EventClause::Account(accounts) => accounts.contains(account),
+            EventClause::Type(patterns) => patterns.iter().any(|pattern| pattern.matches(typ)),
+            EventClause::Data {
+                path,
+                comparison,
+                value,
+            } => lookup(data, path).map_or(false, |field| compare(field, *comparison, value)),
+        }
+    }
+}
+
+impl TypePattern {
+    fn matches(&self, typ: &str) -> bool {
+        match self {
+            TypePattern::Module(module) => typ
+                .strip_prefix(&module.to_string())
+                .map_or(false, |rest| rest.starts_with("::")),
+            TypePattern::Struct(tag) => {
+                let name = tag.to_string();
+                typ == name
+                    || (tag.generic_type_params.is_empty()
+                        && typ
+                            .strip_prefix(&name)
+                            .map_or(false, |rest| rest.starts_with('<')))
+            },
+        }
+    }
+}
+
+impl From<MoveModuleId> for TypePattern {
+    fn from(module: MoveModuleId) -> Self {
+        TypePattern::Module(module)
+    }
+}
+
+impl From<MoveStructTag> for TypePattern {
+    fn from(tag: MoveStructTag) -> Self {
+        TypePattern::Struct(tag)
+    }
+}
+
+impl fmt::Display for TypePattern {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TypePattern::Module(module) => write!(f, "{}", module),
+            TypePattern::Struct(tag) => write!(f, "{}", tag),
+        }
+    }
+}
+
+impl FromStr for TypePattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.parse::<MoveModuleId>() {
+            Ok(module) => Ok(TypePattern::Module(module)),
+            Err(_) => Ok(TypePattern::Struct(s.parse().map_err(|_| {
+                format_err!("'{}' is neither a module nor a struct type", s)
+            })?)),
+        }
+    }
+}
+
+fn lookup<'a>(data: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(data, |value, key| match value {
+        Value::Object(fields) => fields.get(key),
+        Value::Array(items) => items.get(key.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+fn compare(field: &Value, comparison: Comparison, value: &str) -> bool {
+    let field = match field {
+        Value::String(string) => string.clone(),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(boolean) => boolean.to_string(),
+        Value::Null | Value::Array(_) | Value::Object(_) => return false,
+    };
+    let ordering = if let (Ok(left), Ok(right)) = (U256::from_str(&field), U256::from_str(value)) {
+        Some(left.cmp(&right))
+    } else if let (Ok(left), Ok(right)) = (
+        AccountAddress::from_hex_literal(&field),
+        AccountAddress::from_hex_literal(value),
+    ) {
+        (left == right).then_some(Ordering::Equal)
+    } else {
+        (field == value).then_some(Ordering::Equal)
+    };
+    match comparison {
+        Comparison::Eq => ordering == Some(Ordering::Equal),
+        Comparison::Ne => ordering != Some(Ordering::Equal),
+        Comparison::Lt => ordering == Some(Ordering::Less),
+        Comparison::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        Comparison::Gt => ordering == Some(Ordering::Greater),
+        Comparison::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+impl Comparison {
+    /// Two character operators first, so `<=` isn't read as `<`.
+    const ALL: [Comparison; 6] = [
+        Comparison::Eq,
+        Comparison::Ne,
+        Comparison::Le,
+        Comparison::Ge,
+        Comparison::Lt,
+        Comparison::Gt,
+    ];
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            Comparison::Eq => "==",
+            Comparison::Ne => "!=",
+            Comparison::Lt => "<",
+            Comparison::Le => "<=",
+            Comparison::Gt => ">",
+            Comparison::Ge => ">=",
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for EventFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, clause) in self.clauses.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" && ")?;
+            }
+            write!(f, "{}", clause)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for EventClause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_any<T: fmt::Display>(
+            f: &mut fmt::Formatter,
+            key: &str,
+            items: &[T],
+        ) -> fmt::Result {
+            write!(f, "{} == ", key)?;
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" | ")?;
+                }
+                write!(f, "{}", item)?;
+            }
+            Ok(())
+        }
+
+        match self {
+            EventClause::Account(accounts) => write_any(f, "account", accounts),
+            EventClause::Type(types) => write_any(f, "type", types),
+            EventClause::Data {
+                path,
+                comparison,
+                value,
+            } => {
+                // Quote values which wouldn't survive trimming
+                if value.is_empty() || value.trim() != value {
+                    write!(f, "data.{} {} \"{}\"", path.join("."), comparison, value)
+                } else {
+                    write!(f, "data.{} {} {}", path.join("."), comparison, value)
+                }
+            },
+        }
+    }
+}
+
+impl FromStr for EventFilter {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if s.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(Self {
+            clauses: s
+                .split("&&")
+                .map(|clause| clause.trim().parse())
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+impl FromStr for EventClause {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        if let Some(rest) = s.strip_prefix("data.") {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len());
+            let (path, rest) = rest.split_at(end);
+            let path: Vec<_> = path.split('.').map(str::to_string).collect();
+            if path.iter().any(String::is_empty) {
+                bail!("Invalid data path in filter clause '{}'", s);
+            }
+            let rest = rest.trim_start();
+            let comparison = Comparison::ALL
+                .into_iter()
+                .find(|comparison| rest.starts_with(comparison.as_str()))
+                .ok_or_else(|| format_err!("Missing comparison in filter clause '{}'", s))?;
+            let value = rest[comparison.as_str().len()..].trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+            if value.is_empty() && !rest.ends_with('"') {
+                bail!("Missing value in filter clause '{}'", s);
+            }
+            return Ok(EventClause::Data {
+                path,
+                comparison,
+                value: value.to_string(),
+            });
+        }
+
+        let (key, items) = s
+            .split_once("==")
+            .ok_or_else(|| format_err!("Invalid filter clause '{}'", s))?;
+        let items = items.split('|').map(str::trim);
+        Ok(match key.trim() {
+            "account" => EventClause::Account(items.map(str::parse).collect::<Result<_, _>>()?),
+            "type" => EventClause::Type(items.map(str::parse).collect::<Result<_, _>>()?),
+            key => bail!(
+                "Unknown filter key '{}', expected account, type or data.<field>",
+                key
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn address(address: &str) -> Address {
+        address.parse().unwrap()
+    }
+
+    fn matches_type(filter: &str, typ: &str) -> bool {
+        filter
+            .parse::<EventFilter>()
+            .unwrap()
+            .matches(&address("0x1"), typ, &json!({}))
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let text = "account == 0x1 | 0xa && type == 0x1::coin | 0x3::token::Token && \
+            type == 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin> && data.coin.value >= 100";
+        let filter: EventFilter = text.parse().unwrap();
+        assert_eq!(filter.clauses().len(), 4);
+        assert_eq!(filter.to_string().parse::<EventFilter>().unwrap(), filter);
+        assert_eq!(filter.clauses()[3], EventClause::Data {
+            path: vec!["coin".to_string(), "value".to_string()],
+            comparison: Comparison::Ge,
+            value: "100".to_string(),
+        });
+
+        let quoted: EventFilter = "data.name == \" a \"".parse().unwrap();
+        assert_eq!(quoted.to_string().parse::<EventFilter>().unwrap(), quoted);
+        assert!("".parse::<EventFilter>().unwrap().is_empty());
+
+        assert!("account = 0x1".parse::<EventFilter>().is_err());
+        assert!("sender == 0x1".parse::<EventFilter>().is_err());
+        assert!("type == 0x1".parse::<EventFilter>().is_err());
+        assert!("data. == 1".parse::<EventFilter>().is_err());
+        assert!("data.amount 1".parse::<EventFilter>().is_err());
+        assert!("data.amount >=".parse::<EventFilter>().is_err());
+    }
+
+    #[test]
+    fn test_type_matches() {
+        assert!(matches_type(
+            "type == 0x1::coin::DepositEvent",
+            "0x1::coin::DepositEvent"
+        ));
+        assert!(matches_type("type == 0x1::coin", "0x1::coin::DepositEvent"));
+        assert!(matches_type(
+            "type == 0x1::coin::CoinStore",
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"
+        ));
+        assert!(!matches_type(
+            "type == 0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            "0x1::coin::CoinStore<0x2::fake::AptosCoin>"
+        ));
+        assert!(!matches_type(
+            "type == 0x1::coin::Deposit",
+            "0x1::coin::DepositEvent"
+        ));
+        assert!(!matches_type(
+            "type == 0x1::coin",
+            "0x1::coins::DepositEvent"
+        ));
+        assert!(!matches_type(
+            "type == 0x3::token",
+            "0x1::coin::DepositEvent"
+        ));
+        assert!(matches_type(
+            "type == 0x3::token | 0x1::coin",
+            "0x1::coin::DepositEvent"
+        ));
+    }
+
+    #[test]
+    fn test_data_matches() {
+        let data = json!({
+            "amount": "1000",
+            "count": 3,
+            "owner": "0x00000000000000000000000000000000000000000000000000000000000000ab",
+            "name": "coin",
+            "items": [{ "id": "7" }],
+        });
+        let matches = |filter: &str| {
+            filter.parse::<EventFilter>().unwrap().matches(
+                &address("0x1"),
+                "0x1::coin::DepositEvent",
+                &data,
+            )
+        };
+
+        assert!(matches("data.amount > 999 && data.amount <= 1000"));
+        assert!(matches("data.count == 3 && data.count != 4"));
+        assert!(matches("data.owner == 0xab"));
+        assert!(matches("data.name == coin && data.name != token"));
+        assert!(matches("data.items.0.id == 7"));
+        assert!(!matches("data.amount < 1000"));
+        assert!(!matches("data.name < d"));
+        assert!(!matches("data.missing == 1"));
+        assert!(!matches("data.items.1.id == 7"));
+        assert!(matches("account == 0x01 && data.amount >= 10"));
+        assert!(!matches("account == 0x2 && data.amount >= 10"));
+    }
+}
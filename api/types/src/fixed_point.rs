@@ -0,0 +1,191 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, format_err};
+use move_core_types::language_storage::{StructTag, CORE_CODE_ADDRESS};
+use std::fmt;
+
+/// A binary fixed point number of the Move standard library, whose raw value is the number
+/// times 2^32 for `0x1::fixed_point32::FixedPoint32`, or times 2^64 for
+/// `0x1::fixed_point64::FixedPoint64`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FixedPointKind {
+    FixedPoint32,
+    FixedPoint64,
+}
+
+impl FixedPointKind {
+    /// The kind of fixed point number a value of `struct_tag` is, if any.
+    pub fn of_struct(struct_tag: &StructTag) -> Option<Self> {
+        if struct_tag.address != CORE_CODE_ADDRESS {
+            return None;
+        }
+        match (struct_tag.module.as_str(), struct_tag.name.as_str()) {
+            ("fixed_point32", "FixedPoint32") => Some(FixedPointKind::FixedPoint32),
+            ("fixed_point64", "FixedPoint64") => Some(FixedPointKind::FixedPoint64),
+            _ => None,
+        }
+    }
+
+    pub fn fractional_bits(self) -> u32 {
+        match self {
+            FixedPointKind::FixedPoint32 => 32,
+            FixedPointKind::FixedPoint64 => 64,
+        }
+    }
+
+    /// The largest raw value, as the raw value of a `FixedPoint32` is a u64.
+    fn max_raw(self) -> u128 {
+        match self {
+            FixedPointKind::FixedPoint32 => u64::MAX as u128,
+            FixedPointKind::FixedPoint64 => u128::MAX,
+        }
+    }
+
+    /// Formats the number with the raw value `raw` in decimal, without trailing zeros, e.g. `1.5`
+    /// for the `FixedPoint32` with the raw value 6442450944. The decimal expansion of a binary
+    /// fraction is finite, so it's exact unless rounded down to `max_decimals` decimals.
+    pub fn to_decimal(self, raw: u128, max_decimals: Option<u32>) -> String {
+        let bits = self.fractional_bits();
+        let mask = (1u128 << bits) - 1;
+        let whole = raw >> bits;
+        let mut fraction = raw & mask;
+        let mut decimals = String::new();
+        while fraction != 0 && max_decimals.map_or(true, |max| decimals.len() < max as usize) {
+            // The fraction is below 2^64, so it can't overflow
+            fraction *= 10;
+            decimals.push(char::from(b'0' + (fraction >> bits) as u8));
+            fraction &= mask;
+        }
+        let decimals = decimals.trim_end_matches('0');
+        if decimals.is_empty() {
+            whole.to_string()
+        } else {
+            format!("{}.{}", whole, decimals)
+        }
+    }
+
+    /// Parses a number written in decimal, e.g. `1.5`, into its raw value. Decimals which aren't
+    /// a multiple of 2^-32 or 2^-64 are rounded down.
+    pub fn parse_decimal(self, str: &str) -> anyhow::Result<u128> {
+        let trimmed = str.trim();
+        let (whole, fraction) = trimmed.split_once('.').unwrap_or((trimmed, ""));
+        ensure!(
+            !whole.is_empty() || !fraction.is_empty(),
+            "Invalid fixed point number {:?}",
+            str
+        );
+        ensure!(
+            whole
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit()),
+            "Invalid fixed point number {:?}",
+            str
+        );
+
+        let bits = self.fractional_bits();
+        let whole = if whole.is_empty() {
+            0
+        } else {
+            whole
+                .parse::<u128>()
+                .ok()
+                .filter(|whole| *whole <= self.max_raw() >> bits)
+                .ok_or_else(|| {
+                    format_err!("Fixed point number {:?} is too large for a {}", str, self)
+                })?
+        };
+
+        // Rounding down a fraction to a multiple of 2^-bits only depends on its first `bits`
+        // decimals, as every such multiple has at most `bits` decimals. The bits of the raw
+        // fraction are the integer parts of the fraction doubled again and again.
+        let mut decimals: Vec<u8> = fraction
+            .bytes()
+            .take(bits as usize)
+            .map(|digit| digit - b'0')
+            .collect();
+        let mut raw_fraction = 0u128;
+        for _ in 0..bits {
+            let mut carry = 0;
+            for decimal in decimals.iter_mut().rev() {
+                let doubled = *decimal * 2 + carry;
+                *decimal = doubled % 10;
+                carry = doubled / 10;
+            }
+            raw_fraction = (raw_fraction << 1) | carry as u128;
+        }
+        Ok((whole << bits) | raw_fraction)
+    }
+}
+
+impl fmt::Display for FixedPointKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedPointKind::FixedPoint32 => write!(f, "0x1::fixed_point32::FixedPoint32"),
+            FixedPointKind::FixedPoint64 => write!(f, "0x1::fixed_point64::FixedPoint64"),
+        }
+    }
+}
+
+/// How values of fixed point numbers, see [`FixedPointKind`], are rendered in JSON. Either
+/// rendering is accepted as input.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FixedPointDisplay {
+    /// As their struct, with the raw value, e.g. `{"value": "6442450944"}` for 1.5.
+    #[default]
+    Struct,
+    /// As a decimal string, e.g. `"1.5"`, rounded down to `max_decimals` decimals if set.
+    Decimal { max_decimals: Option<u32> },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimal_round_trip() {
+        for (kind, raw, decimal) in [
+            (FixedPointKind::FixedPoint32, 6_442_450_944, "1.5"),
+            (FixedPointKind::FixedPoint32, 0, "0"),
+            (FixedPointKind::FixedPoint32, 1 << 32, "1"),
+            (
+                FixedPointKind::FixedPoint32,
+                1,
+                "0.00000000023283064365386962890625",
+            ),
+            (
+                FixedPointKind::FixedPoint32,
+                u64::MAX as u128,
+                "4294967295.99999999976716935634613037109375",
+            ),
+            (FixedPointKind::FixedPoint64, 3 << 62, "0.75"),
+            (
+                FixedPointKind::FixedPoint64,
+                1,
+                "0.0000000000000000000542101086242752217003726400434970855712890625",
+            ),
+        ] {
+            assert_eq!(kind.to_decimal(raw, None), decimal);
+            assert_eq!(kind.parse_decimal(decimal).unwrap(), raw, "{}", decimal);
+        }
+    }
+
+    #[test]
+    fn test_decimal_rounding() {
+        let kind = FixedPointKind::FixedPoint32;
+        assert_eq!(kind.to_decimal(1, Some(9)), "0");
+        assert_eq!(kind.to_decimal(6_442_450_945, Some(4)), "1.5");
+        // 0.1 isn't a multiple of 2^-32, so it's rounded down
+        assert_eq!(kind.parse_decimal("0.1").unwrap(), 429_496_729);
+        assert_eq!(kind.parse_decimal(" .5 ").unwrap(), 1 << 31);
+        assert_eq!(kind.parse_decimal("2.").unwrap(), 2 << 32);
+
+        for str in ["", ".", "-1", "1e3", "1,5", "4294967296"] {
+            assert!(kind.parse_decimal(str).is_err(), "{:?} parsed", str);
+        }
+        assert!(FixedPointKind::FixedPoint64
+            .parse_decimal("18446744073709551616")
+            .is_err());
+    }
+}
@@ -4,28 +4,38 @@
 
 mod account;
 mod address;
+mod amount;
+mod batch;
 mod block;
 mod bytecode;
 mod convert;
 mod derives;
 mod error;
+mod event_filter;
+mod fixed_point;
 mod hash;
 mod headers;
 mod index;
 mod ledger_info;
 pub mod mime_types;
 mod move_types;
+mod node_info;
 mod table;
 pub mod transaction;
 mod view;
+pub mod well_known;
 mod wrappers;
 
-pub use account::AccountData;
+pub use account::{AccountData, AccountStateKey, AccountStateKeyKind};
 pub use address::Address;
+pub use amount::{Amount, APT_DECIMALS, APT_SYMBOL};
+pub use batch::{BatchReadRequest, BatchReadResult, BatchResourceRequest, BatchTableItemRequest};
 pub use block::{BcsBlock, Block};
 pub use bytecode::Bytecode;
 pub use convert::{new_vm_utf8_string, AsConverter, ExplainVMStatus, MoveConverter};
 pub use error::{AptosError, AptosErrorCode};
+pub use event_filter::{Comparison, EventClause, EventFilter, TypePattern, FILTER_QUERY_PARAM};
+pub use fixed_point::{FixedPointDisplay, FixedPointKind};
 pub use hash::HashValue;
 pub use headers::*;
 pub use index::{IndexResponse, IndexResponseBcs};
@@ -37,6 +47,10 @@ pub use move_types::{
     MoveScriptBytecode, MoveStruct, MoveStructField, MoveStructTag, MoveType, MoveValue,
     ResourceGroup, MAX_RECURSIVE_TYPES_ALLOWED, U128, U256, U64,
 };
+pub use node_info::{
+    NodeInfo, API_FEATURES, FEATURE_ACCOUNT_STATE_KEYS, FEATURE_BATCH_READ, FEATURE_EVENTS_BY_TYPE,
+    FEATURE_EVENT_FILTER, FEATURE_RESOURCE_GROUPS,
+};
 use serde::{Deserialize, Deserializer};
 use std::str::FromStr;
 pub use table::{RawTableItemRequest, TableItemRequest};
@@ -2,7 +2,10 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{Address, Bytecode, IdentifierWrapper, VerifyInput, VerifyInputWithRecursion};
+use crate::{
+    Address, Bytecode, FixedPointDisplay, FixedPointKind, IdentifierWrapper, VerifyInput,
+    VerifyInputWithRecursion,
+};
 use anyhow::{bail, format_err};
 use aptos_types::{account_config::CORE_CODE_ADDRESS, event::EventKey, transaction::Module};
 use move_binary_format::{
@@ -45,9 +48,20 @@ impl TryFrom<AnnotatedMoveStruct> for MoveResource {
     type Error = anyhow::Error;
 
     fn try_from(s: AnnotatedMoveStruct) -> anyhow::Result<Self> {
+        Self::try_from_annotated(s, FixedPointDisplay::default())
+    }
+}
+
+impl MoveResource {
+    /// Converts the resource, rendering the fixed point numbers in it as `fixed_point_display`
+    /// says.
+    pub fn try_from_annotated(
+        s: AnnotatedMoveStruct,
+        fixed_point_display: FixedPointDisplay,
+    ) -> anyhow::Result<Self> {
         Ok(Self {
             typ: s.type_.clone().into(),
-            data: s.try_into()?,
+            data: MoveStructValue::try_from_annotated(s, fixed_point_display)?,
         })
     }
 }
@@ -224,9 +238,23 @@ impl TryFrom<AnnotatedMoveStruct> for MoveStructValue {
     type Error = anyhow::Error;
 
     fn try_from(s: AnnotatedMoveStruct) -> anyhow::Result<Self> {
+        Self::try_from_annotated(s, FixedPointDisplay::default())
+    }
+}
+
+impl MoveStructValue {
+    /// Converts the struct, rendering the fixed point numbers in it as `fixed_point_display`
+    /// says.
+    pub fn try_from_annotated(
+        s: AnnotatedMoveStruct,
+        fixed_point_display: FixedPointDisplay,
+    ) -> anyhow::Result<Self> {
         let mut map = BTreeMap::new();
         for (id, val) in s.value {
-            map.insert(id.into(), MoveValue::try_from(val)?.json()?);
+            map.insert(
+                id.into(),
+                MoveValue::try_from_annotated(val, fixed_point_display)?.json()?,
+            );
         }
         Ok(Self(map))
     }
@@ -280,12 +308,26 @@ impl MoveValue {
             bail!("expect string::String, but failed to decode struct value");
         }
     }
-}
 
-impl TryFrom<AnnotatedMoveValue> for MoveValue {
-    type Error = anyhow::Error;
+    /// Renders a fixed point number as a decimal string, see [`FixedPointKind::to_decimal`].
+    pub fn convert_fixed_point(
+        v: AnnotatedMoveStruct,
+        kind: FixedPointKind,
+        max_decimals: Option<u32>,
+    ) -> anyhow::Result<MoveValue> {
+        let raw = match v.value.into_iter().next() {
+            Some((_, AnnotatedMoveValue::U64(raw))) => raw as u128,
+            Some((_, AnnotatedMoveValue::U128(raw))) => raw,
+            _ => bail!("expect {}, but failed to decode struct value", kind),
+        };
+        Ok(MoveValue::String(kind.to_decimal(raw, max_decimals)))
+    }
 
-    fn try_from(val: AnnotatedMoveValue) -> anyhow::Result<Self> {
+    /// Converts the value, rendering the fixed point numbers in it as `fixed_point_display` says.
+    pub fn try_from_annotated(
+        val: AnnotatedMoveValue,
+        fixed_point_display: FixedPointDisplay,
+    ) -> anyhow::Result<Self> {
         Ok(match val {
             AnnotatedMoveValue::U8(v) => MoveValue::U8(v),
             AnnotatedMoveValue::U16(v) => MoveValue::U16(v),
@@ -297,21 +339,35 @@ impl TryFrom<AnnotatedMoveValue> for MoveValue {
             AnnotatedMoveValue::Address(v) => MoveValue::Address(v.into()),
             AnnotatedMoveValue::Vector(_, vals) => MoveValue::Vector(
                 vals.into_iter()
-                    .map(MoveValue::try_from)
+                    .map(|val| MoveValue::try_from_annotated(val, fixed_point_display))
                     .collect::<anyhow::Result<_>>()?,
             ),
             AnnotatedMoveValue::Bytes(v) => MoveValue::Bytes(HexEncodedBytes(v)),
             AnnotatedMoveValue::Struct(v) => {
-                if MoveValue::is_utf8_string(&v.type_) {
-                    MoveValue::convert_utf8_string(v)?
-                } else {
-                    MoveValue::Struct(v.try_into()?)
+                let fixed_point = FixedPointKind::of_struct(&v.type_);
+                match (fixed_point, fixed_point_display) {
+                    _ if MoveValue::is_utf8_string(&v.type_) => MoveValue::convert_utf8_string(v)?,
+                    (Some(kind), FixedPointDisplay::Decimal { max_decimals }) => {
+                        MoveValue::convert_fixed_point(v, kind, max_decimals)?
+                    },
+                    _ => MoveValue::Struct(MoveStructValue::try_from_annotated(
+                        v,
+                        fixed_point_display,
+                    )?),
                 }
             },
         })
     }
 }
 
+impl TryFrom<AnnotatedMoveValue> for MoveValue {
+    type Error = anyhow::Error;
+
+    fn try_from(val: AnnotatedMoveValue) -> anyhow::Result<Self> {
+        Self::try_from_annotated(val, FixedPointDisplay::default())
+    }
+}
+
 impl From<TransactionArgument> for MoveValue {
     fn from(val: TransactionArgument) -> Self {
         match val {
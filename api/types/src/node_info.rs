@@ -0,0 +1,60 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::U64;
+use poem_openapi::Object as PoemObject;
+use serde::{Deserialize, Serialize};
+
+/// Reading resources, view functions and table items at one ledger version: `POST /batch`.
+pub const FEATURE_BATCH_READ: &str = "batch_read";
+/// Listing the state keys under an account: `/accounts/{address}/state_keys`.
+pub const FEATURE_ACCOUNT_STATE_KEYS: &str = "account_state_keys";
+/// Reading all the resources of a resource group at once:
+/// `/accounts/{address}/resource_group/{resource_group_type}`.
+pub const FEATURE_RESOURCE_GROUPS: &str = "resource_groups";
+/// Listing the events of a type across accounts: `/events/by_type/{event_type}`.
+pub const FEATURE_EVENTS_BY_TYPE: &str = "events_by_type";
+/// Filtering the events of a page with the `filter` query parameter.
+pub const FEATURE_EVENT_FILTER: &str = "event_filter";
+
+/// The features of the API of this release, as listed by `GET /info`.
+pub const API_FEATURES: &[&str] = &[
+    FEATURE_BATCH_READ,
+    FEATURE_ACCOUNT_STATE_KEYS,
+    FEATURE_RESOURCE_GROUPS,
+    FEATURE_EVENTS_BY_TYPE,
+    FEATURE_EVENT_FILTER,
+];
+
+/// What the API of a node supports, returned by the node info endpoint (i.e., GET "/info")
+///
+/// Lets clients talking to nodes of different releases pick the code paths each node supports.
+/// Features are listed by name, so clients can tell which of the features they know of a node
+/// supports, whatever features it knows of.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, PoemObject, Serialize)]
+pub struct NodeInfo {
+    /// Git hash of the build of the node
+    pub git_hash: String,
+    /// Features of the API the node supports, e.g. `batch_read`
+    pub features: Vec<String>,
+    pub transaction_submission_enabled: bool,
+    pub transaction_simulation_enabled: bool,
+    /// Maximum number of transactions submitted in a single batch
+    pub max_submit_transaction_batch_size: U64,
+    /// Maximum number of reads in a single batch read
+    pub max_batch_read_size: U64,
+    pub max_transactions_page_size: u16,
+    pub max_events_page_size: u16,
+    pub max_account_resources_page_size: u16,
+    pub max_account_modules_page_size: u16,
+    /// Maximum number of versions scanned by a single request for events by type
+    pub max_events_by_type_scan_range: U64,
+    /// Maximum gas units a view function can use
+    pub max_gas_view_function: U64,
+}
+
+impl NodeInfo {
+    pub fn supports(&self, feature: &str) -> bool {
+        self.features.iter().any(|supported| supported == feature)
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Struct tags of framework resources commonly read by clients, so that they don't have to be
+//! parsed from strings like `"0x1::code::PackageRegistry"`. The resource type of a path is the
+//! struct tag formatted with `to_string`.
+
+use crate::{IdentifierWrapper, MoveStructTag, MoveType};
+use aptos_types::{account_address::AccountAddress, utility_coin::APTOS_COIN_TYPE};
+use move_core_types::{ident_str, identifier::IdentStr};
+
+fn framework_struct(
+    module: &IdentStr,
+    name: &IdentStr,
+    generic_type_params: Vec<MoveType>,
+) -> MoveStructTag {
+    MoveStructTag::new(
+        AccountAddress::ONE.into(),
+        IdentifierWrapper(module.to_owned()),
+        IdentifierWrapper(name.to_owned()),
+        generic_type_params,
+    )
+}
+
+/// `0x1::aptos_coin::AptosCoin`, the type of APT.
+pub fn aptos_coin() -> MoveType {
+    MoveType::from(&*APTOS_COIN_TYPE)
+}
+
+/// `0x1::coin::CoinStore<coin_type>`, the balance of an account in a coin.
+pub fn coin_store(coin_type: MoveType) -> MoveStructTag {
+    framework_struct(ident_str!("coin"), ident_str!("CoinStore"), vec![coin_type])
+}
+
+/// `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`, the APT balance of an account.
+pub fn apt_coin_store() -> MoveStructTag {
+    coin_store(aptos_coin())
+}
+
+/// `0x1::code::PackageRegistry`, the packages published at an address.
+pub fn package_registry() -> MoveStructTag {
+    framework_struct(ident_str!("code"), ident_str!("PackageRegistry"), vec![])
+}
+
+/// `0x1::object::ObjectCore`, the owner and transfer settings of an object.
+pub fn object_core() -> MoveStructTag {
+    framework_struct(ident_str!("object"), ident_str!("ObjectCore"), vec![])
+}
+
+/// `0x1::stake::StakePool`, the stake of a validator.
+pub fn stake_pool() -> MoveStructTag {
+    framework_struct(ident_str!("stake"), ident_str!("StakePool"), vec![])
+}
+
+/// `0x1::delegation_pool::DelegationPool`, the delegators of a delegated stake pool.
+pub fn delegation_pool() -> MoveStructTag {
+    framework_struct(
+        ident_str!("delegation_pool"),
+        ident_str!("DelegationPool"),
+        vec![],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_well_known_struct_tags_match_their_strings() {
+        for (tag, string) in [
+            (
+                apt_coin_store(),
+                "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            ),
+            (package_registry(), "0x1::code::PackageRegistry"),
+            (object_core(), "0x1::object::ObjectCore"),
+            (stake_pool(), "0x1::stake::StakePool"),
+            (delegation_pool(), "0x1::delegation_pool::DelegationPool"),
+        ] {
+            assert_eq!(tag.to_string(), string);
+            assert_eq!(tag, MoveStructTag::from_str(string).unwrap());
+        }
+    }
+}
@@ -3,10 +3,12 @@
 
 use anyhow::{format_err, Result};
 use aptos_gas::{
-    AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters, StandardGasMeter,
-    LATEST_GAS_FEATURE_VERSION,
+    AbstractValueSizeGasParameters, AptosGasMeter, ChangeSetConfigs, NativeGasParameters,
+    StandardGasMeter, LATEST_GAS_FEATURE_VERSION,
+};
+use aptos_gas_profiling::{
+    ExecutionHook, ExecutionTracer, GasProfiler, HookedGasMeter, TransactionGasLog,
 };
-use aptos_gas_profiling::{GasProfiler, TransactionGasLog};
 use aptos_resource_viewer::{AnnotatedAccountStateBlob, AptosValueAnnotator};
 use aptos_rest_client::Client;
 use aptos_state_view::TStateView;
@@ -15,8 +17,8 @@ use aptos_types::{
     chain_id::ChainId,
     on_chain_config::{Features, OnChainConfig, TimedFeatures},
     transaction::{
-        ChangeSet, SignedTransaction, Transaction, TransactionInfo, TransactionOutput,
-        TransactionPayload, Version,
+        ChangeSet, SignatureCheckedTransaction, SignedTransaction, Transaction, TransactionInfo,
+        TransactionOutput, TransactionPayload, Version,
     },
     vm_status::VMStatus,
 };
@@ -66,40 +68,103 @@ impl AptosDebugger {
         version: Version,
         txn: SignedTransaction,
     ) -> Result<(VMStatus, TransactionOutput, TransactionGasLog)> {
+        let (status, output, gas_profiler) =
+            self.execute_transaction_at_version_with_gas_meter(version, txn, |txn, gas_meter| {
+                match txn.payload() {
+                    TransactionPayload::Script(_) => {
+                        HookedGasMeter::new_script(gas_meter, GasProfiler::new())
+                    },
+                    TransactionPayload::EntryFunction(entry_func) => HookedGasMeter::new_function(
+                        gas_meter,
+                        GasProfiler::new(),
+                        entry_func.module().clone(),
+                        entry_func.function().to_owned(),
+                        entry_func.ty_args().to_vec(),
+                    ),
+                    TransactionPayload::ModuleBundle(..) => unreachable!("not supported"),
+                    TransactionPayload::Multisig(..) => unimplemented!("not supported yet"),
+                }
+            })?;
+
+        Ok((status, output, gas_profiler.finish()))
+    }
+
+    /// Executes the transaction with `hook` called on the function calls and storage accesses,
+    /// e.g. an `ExecutionTracer` to see what the transaction did.
+    pub fn execute_transaction_at_version_with_hook<H: ExecutionHook>(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+        hook: H,
+    ) -> Result<(VMStatus, TransactionOutput, H)> {
+        let (status, output, gas_meter) =
+            self.execute_transaction_at_version_with_gas_meter(version, txn, |txn, gas_meter| {
+                HookedGasMeter::for_payload(gas_meter, hook, txn.payload())
+            })?;
+
+        Ok((status, output, gas_meter.into_inner().1))
+    }
+
+    /// Executes the transaction with the gas meter made by `wrap_gas_meter` from the standard gas
+    /// meter.
+    fn execute_transaction_at_version_with_gas_meter<G, F>(
+        &self,
+        version: Version,
+        txn: SignedTransaction,
+        wrap_gas_meter: F,
+    ) -> Result<(VMStatus, TransactionOutput, G)>
+    where
+        G: AptosGasMeter,
+        F: FnOnce(&SignatureCheckedTransaction, StandardGasMeter) -> G,
+    {
         let state_view = DebuggerStateView::new(self.debugger.clone(), version);
         let log_context = AdapterLogSchema::new(state_view.id(), 0);
         let txn = txn
             .check_signature()
             .map_err(|err| format_err!("Unexpected VM Error: {:?}", err))?;
 
-        let (status, output, gas_profiler) =
-            AptosVM::execute_user_transaction_with_custom_gas_meter(
-                &state_view,
-                &txn,
-                &log_context,
-                |gas_feature_version, gas_params, storage_gas_params, balance| {
-                    let gas_meter = StandardGasMeter::new(
-                        gas_feature_version,
-                        gas_params,
-                        storage_gas_params,
-                        balance,
-                    );
-                    let gas_profiler = match txn.payload() {
-                        TransactionPayload::Script(_) => GasProfiler::new_script(gas_meter),
-                        TransactionPayload::EntryFunction(entry_func) => GasProfiler::new_function(
-                            gas_meter,
-                            entry_func.module().clone(),
-                            entry_func.function().to_owned(),
-                            entry_func.ty_args().to_vec(),
-                        ),
-                        TransactionPayload::ModuleBundle(..) => unreachable!("not supported"),
-                        TransactionPayload::Multisig(..) => unimplemented!("not supported yet"),
-                    };
-                    Ok(gas_profiler)
-                },
-            )?;
+        let (status, output, gas_meter) = AptosVM::execute_user_transaction_with_custom_gas_meter(
+            &state_view,
+            &txn,
+            &log_context,
+            |gas_feature_version, gas_params, storage_gas_params, balance| {
+                let gas_meter = StandardGasMeter::new(
+                    gas_feature_version,
+                    gas_params,
+                    storage_gas_params,
+                    balance,
+                );
+                Ok(wrap_gas_meter(&txn, gas_meter))
+            },
+        )?;
 
-        Ok((status, output, gas_profiler.finish()))
+        Ok((status, output, gas_meter))
+    }
+
+    /// Traces the user transactions among the `limit` transactions from version `begin`, see
+    /// `execute_transaction_at_version_with_hook`.
+    pub async fn trace_past_transactions(
+        &self,
+        begin: Version,
+        limit: u64,
+    ) -> Result<Vec<(Version, ExecutionTracer)>> {
+        let (txns, _) = self
+            .debugger
+            .get_committed_transactions(begin, limit)
+            .await?;
+
+        let mut traces = vec![];
+        for (version, txn) in (begin..).zip(txns) {
+            if let Transaction::UserTransaction(txn) = txn {
+                let (_, _, tracer) = self.execute_transaction_at_version_with_hook(
+                    version,
+                    txn,
+                    ExecutionTracer::new(),
+                )?;
+                traces.push((version, tracer));
+            }
+        }
+        Ok(traces)
     }
 
     pub async fn execute_past_transactions(
@@ -29,6 +29,11 @@ pub struct Argument {
 
     #[clap(long, default_value = "1")]
     concurrency_level: usize,
+
+    /// Print the function calls and storage accesses of the user transactions, instead of the
+    /// outputs of all the transactions
+    #[clap(long)]
+    trace: bool,
 }
 
 #[tokio::main]
@@ -44,12 +49,21 @@ async fn main() -> Result<()> {
         Target::DB { path } => AptosDebugger::db(path)?,
     };
 
-    println!(
-        "{:#?}",
-        debugger
-            .execute_past_transactions(args.begin_version, args.limit)
+    if args.trace {
+        for (version, tracer) in debugger
+            .trace_past_transactions(args.begin_version, args.limit)
             .await?
-    );
+        {
+            println!("Transaction {}:\n{}", version, tracer);
+        }
+    } else {
+        println!(
+            "{:#?}",
+            debugger
+                .execute_past_transactions(args.begin_version, args.limit)
+                .await?
+        );
+    }
 
     Ok(())
 }
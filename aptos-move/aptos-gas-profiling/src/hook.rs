@@ -0,0 +1,618 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{write_op_type, EventStorage, FrameName, StorageFees, WriteStorage};
+use aptos_gas::{AptosGasMeter, Fee, Gas, GasScalingFactor};
+use aptos_types::{
+    contract_event::ContractEvent, state_store::state_key::StateKey,
+    transaction::TransactionPayload, write_set::WriteOp,
+};
+use move_binary_format::{
+    errors::{PartialVMResult, VMResult},
+    file_format::CodeOffset,
+    file_format_common::Opcodes,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+
+/// Callbacks on what a transaction does while it executes, along with the gas it costs, so tools
+/// replaying or debugging transactions (the gas profiler, the replay tool, the Move harness, ...)
+/// can instrument the execution without writing their own gas meter adapter. Plugged into the VM
+/// with a [`HookedGasMeter`], which only the debugging entry points of the VM accept: hooks never
+/// run on the validators. A pair of hooks is a hook calling both in turn.
+///
+/// All callbacks do nothing by default.
+pub trait ExecutionHook {
+    /// A function, native function or script starts executing.
+    fn on_function_entry(&mut self, _name: &FrameName) {}
+
+    /// The function or script that started executing last returns. Not called for the functions
+    /// that were executing when the transaction aborted.
+    fn on_function_exit(&mut self) {}
+
+    /// The native function that started executing last returns, having cost `cost`.
+    fn on_native_function_exit(&mut self, _cost: InternalGas) {
+        self.on_function_exit()
+    }
+
+    /// A bytecode instruction is executed at the cost of `cost`. For calls and returns, called
+    /// before the function is entered or exited.
+    fn on_bytecode(&mut self, _op: Opcodes, _cost: InternalGas) {}
+
+    /// A branch instruction jumps to `offset`. Called after [`Self::on_bytecode`].
+    fn on_branch(&mut self, _offset: CodeOffset) {}
+
+    /// A resource is read from storage at the cost of `cost`, `num_bytes` is `None` if it
+    /// doesn't exist. Only called the first time the transaction accesses the resource.
+    fn on_load_resource(
+        &mut self,
+        _addr: AccountAddress,
+        _ty: &TypeTag,
+        _num_bytes: Option<NumBytes>,
+        _cost: InternalGas,
+    ) {
+    }
+
+    /// The intrinsic gas of the transaction is charged, before it executes.
+    fn on_intrinsic_gas(&mut self, _cost: InternalGas) {}
+
+    /// The transaction writes `op` to `key`, at the IO cost of `cost`. Called for the whole
+    /// write set once the transaction is done executing.
+    fn on_write(&mut self, _key: &StateKey, _op: &WriteOp, _cost: InternalGas) {}
+
+    /// The storage fees of the transaction are charged, after its write set.
+    fn on_storage_fees(&mut self, _fees: &StorageFees) {}
+}
+
+impl<A, B> ExecutionHook for (A, B)
+where
+    A: ExecutionHook,
+    B: ExecutionHook,
+{
+    fn on_function_entry(&mut self, name: &FrameName) {
+        self.0.on_function_entry(name);
+        self.1.on_function_entry(name);
+    }
+
+    fn on_function_exit(&mut self) {
+        self.0.on_function_exit();
+        self.1.on_function_exit();
+    }
+
+    fn on_native_function_exit(&mut self, cost: InternalGas) {
+        self.0.on_native_function_exit(cost);
+        self.1.on_native_function_exit(cost);
+    }
+
+    fn on_bytecode(&mut self, op: Opcodes, cost: InternalGas) {
+        self.0.on_bytecode(op, cost);
+        self.1.on_bytecode(op, cost);
+    }
+
+    fn on_branch(&mut self, offset: CodeOffset) {
+        self.0.on_branch(offset);
+        self.1.on_branch(offset);
+    }
+
+    fn on_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: &TypeTag,
+        num_bytes: Option<NumBytes>,
+        cost: InternalGas,
+    ) {
+        self.0.on_load_resource(addr, ty, num_bytes, cost);
+        self.1.on_load_resource(addr, ty, num_bytes, cost);
+    }
+
+    fn on_intrinsic_gas(&mut self, cost: InternalGas) {
+        self.0.on_intrinsic_gas(cost);
+        self.1.on_intrinsic_gas(cost);
+    }
+
+    fn on_write(&mut self, key: &StateKey, op: &WriteOp, cost: InternalGas) {
+        self.0.on_write(key, op, cost);
+        self.1.on_write(key, op, cost);
+    }
+
+    fn on_storage_fees(&mut self, fees: &StorageFees) {
+        self.0.on_storage_fees(fees);
+        self.1.on_storage_fees(fees);
+    }
+}
+
+macro_rules! hook_bytecode {
+    ($(
+        $([$op: expr])?
+        fn $fn: ident $(<$($lt: lifetime),*>)? (&mut self $(, $arg: ident : $ty: ty)* $(,)?) -> PartialVMResult<()>;
+    )*) => {
+        $(fn $fn $(<$($lt)*>)? (&mut self, $($arg: $ty),*) -> PartialVMResult<()> {
+            #[allow(unused)]
+            use Opcodes::*;
+
+            #[allow(unused)]
+            let (cost, res) = self.delegate_charge(|base| base.$fn($($arg),*));
+
+            $(
+                self.hook.on_bytecode($op, cost);
+            )?
+
+            res
+        })*
+    };
+}
+
+/// A gas meter adapter calling an [`ExecutionHook`] with the gas charged by the gas meter it
+/// wraps, e.g. a [`crate::GasProfiler`] to profile the execution.
+pub struct HookedGasMeter<G, H> {
+    base: G,
+    hook: H,
+}
+
+impl<G, H> HookedGasMeter<G, H>
+where
+    H: ExecutionHook,
+{
+    pub fn new_script(base: G, mut hook: H) -> Self {
+        hook.on_function_entry(&FrameName::Script);
+        Self { base, hook }
+    }
+
+    pub fn new_function(
+        base: G,
+        mut hook: H,
+        module_id: ModuleId,
+        name: Identifier,
+        ty_args: Vec<TypeTag>,
+    ) -> Self {
+        hook.on_function_entry(&FrameName::Function {
+            module_id,
+            name,
+            ty_args,
+        });
+        Self { base, hook }
+    }
+
+    /// Starts in the script or entry function of `payload`.
+    pub fn for_payload(base: G, hook: H, payload: &TransactionPayload) -> Self {
+        match payload {
+            TransactionPayload::Script(_) => Self::new_script(base, hook),
+            TransactionPayload::EntryFunction(entry_func) => Self::new_function(
+                base,
+                hook,
+                entry_func.module().clone(),
+                entry_func.function().to_owned(),
+                entry_func.ty_args().to_vec(),
+            ),
+            TransactionPayload::ModuleBundle(..) | TransactionPayload::Multisig(..) => {
+                Self { base, hook }
+            },
+        }
+    }
+
+    pub fn base(&self) -> &G {
+        &self.base
+    }
+
+    pub fn hook(&self) -> &H {
+        &self.hook
+    }
+
+    pub fn into_inner(self) -> (G, H) {
+        (self.base, self.hook)
+    }
+
+    fn enter_function(&mut self, module_id: &ModuleId, func_name: &str, ty_args: Vec<TypeTag>) {
+        self.hook.on_function_entry(&FrameName::Function {
+            module_id: module_id.clone(),
+            name: Identifier::new(func_name).unwrap(),
+            ty_args,
+        });
+    }
+}
+
+impl<G, H> HookedGasMeter<G, H>
+where
+    G: AptosGasMeter,
+    H: ExecutionHook,
+{
+    /// Delegate the charging call to the base gas meter and measure variation in balance.
+    fn delegate_charge<F, R>(&mut self, charge: F) -> (InternalGas, R)
+    where
+        F: FnOnce(&mut G) -> R,
+    {
+        let old = self.base.balance_internal();
+        let res = charge(&mut self.base);
+        let new = self.base.balance_internal();
+        let cost = old.checked_sub(new).expect("gas cost must be non-negative");
+
+        (cost, res)
+    }
+
+    fn storage_fees<'a>(
+        &self,
+        write_ops: &[(&'a StateKey, &'a WriteOp)],
+        events: &[&'a ContractEvent],
+        txn_size: NumBytes,
+    ) -> StorageFees {
+        let write_set_storage = write_ops
+            .iter()
+            .map(|(key, op)| WriteStorage {
+                key: (*key).clone(),
+                op_type: write_op_type(op),
+                cost: self.base.storage_fee_per_write(key, op),
+            })
+            .collect();
+
+        let mut event_fee = Fee::new(0);
+        let mut event_fees = vec![];
+        for event in events {
+            let fee = self.base.storage_fee_per_event(event);
+            event_fees.push(EventStorage {
+                ty: event.type_tag().clone(),
+                cost: fee,
+            });
+            event_fee += fee;
+        }
+
+        StorageFees {
+            write_set_storage,
+            events: event_fees,
+            event_discount: self.base.storage_discount_for_events(event_fee),
+            txn_storage: self.base.storage_fee_for_transaction_storage(txn_size),
+        }
+    }
+}
+
+impl<G, H> GasMeter for HookedGasMeter<G, H>
+where
+    G: AptosGasMeter,
+    H: ExecutionHook,
+{
+    delegate! {
+        fn balance_internal(&self) -> InternalGas;
+    }
+
+    delegate_mut! {
+        // Note: we only use this callback for memory tracking, not for charging gas.
+        fn charge_ld_const_after_deserialization(&mut self, val: impl ValueView)
+            -> PartialVMResult<()>;
+
+        // Note: we don't use this to charge gas so no need to record anything.
+        fn charge_native_function_before_execution(
+            &mut self,
+            ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        // Note: we don't use this to charge gas so no need to record anything.
+        fn charge_drop_frame(
+            &mut self,
+            locals: impl Iterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+    }
+
+    hook_bytecode! {
+        [POP]
+        fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()>;
+
+        [LD_CONST]
+        fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()>;
+
+        [COPY_LOC]
+        fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        [MOVE_LOC]
+        fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        [ST_LOC]
+        fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        [PACK]
+        fn charge_pack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        [UNPACK]
+        fn charge_unpack(
+            &mut self,
+            is_generic: bool,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        [READ_REF]
+        fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()>;
+
+        [WRITE_REF]
+        fn charge_write_ref(
+            &mut self,
+            new_val: impl ValueView,
+            old_val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        [EQ]
+        fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        [NEQ]
+        fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()>;
+
+        [
+            match (is_mut, is_generic) {
+                (false, false) => IMM_BORROW_GLOBAL,
+                (false, true) => IMM_BORROW_GLOBAL_GENERIC,
+                (true, false) => MUT_BORROW_GLOBAL,
+                (true, true) => MUT_BORROW_GLOBAL_GENERIC
+            }
+        ]
+        fn charge_borrow_global(
+            &mut self,
+            is_mut: bool,
+            is_generic: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        [if is_generic { EXISTS } else { EXISTS_GENERIC }]
+        fn charge_exists(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            exists: bool,
+        ) -> PartialVMResult<()>;
+
+        [if is_generic { MOVE_FROM } else { MOVE_FROM_GENERIC }]
+        fn charge_move_from(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        [if is_generic { MOVE_TO } else { MOVE_TO_GENERIC }]
+        fn charge_move_to(
+            &mut self,
+            is_generic: bool,
+            ty: impl TypeView,
+            val: impl ValueView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        [VEC_PACK]
+        fn charge_vec_pack<'a>(
+            &mut self,
+            ty: impl TypeView + 'a,
+            args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        [VEC_LEN]
+        fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+
+        [VEC_IMM_BORROW]
+        fn charge_vec_borrow(
+            &mut self,
+            is_mut: bool,
+            ty: impl TypeView,
+            is_success: bool,
+        ) -> PartialVMResult<()>;
+
+        [VEC_PUSH_BACK]
+        fn charge_vec_push_back(
+            &mut self,
+            ty: impl TypeView,
+            val: impl ValueView,
+        ) -> PartialVMResult<()>;
+
+        [VEC_POP_BACK]
+        fn charge_vec_pop_back(
+            &mut self,
+            ty: impl TypeView,
+            val: Option<impl ValueView>,
+        ) -> PartialVMResult<()>;
+
+        [VEC_UNPACK]
+        fn charge_vec_unpack(
+            &mut self,
+            ty: impl TypeView,
+            expect_num_elements: NumArgs,
+            elems: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        ) -> PartialVMResult<()>;
+
+        [VEC_SWAP]
+        fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()>;
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView> + Clone>,
+    ) -> PartialVMResult<()> {
+        let (cost, res) =
+            self.delegate_charge(|base| base.charge_native_function(amount, ret_vals));
+        self.hook.on_native_function_exit(cost);
+        res
+    }
+
+    fn charge_br_false(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        let (cost, res) = self.delegate_charge(|base| base.charge_br_false(target_offset));
+
+        self.hook.on_bytecode(Opcodes::BR_FALSE, cost);
+        if let Some(offset) = target_offset {
+            self.hook.on_branch(offset);
+        }
+
+        res
+    }
+
+    fn charge_br_true(&mut self, target_offset: Option<CodeOffset>) -> PartialVMResult<()> {
+        let (cost, res) = self.delegate_charge(|base| base.charge_br_true(target_offset));
+
+        self.hook.on_bytecode(Opcodes::BR_TRUE, cost);
+        if let Some(offset) = target_offset {
+            self.hook.on_branch(offset);
+        }
+
+        res
+    }
+
+    fn charge_branch(&mut self, target_offset: CodeOffset) -> PartialVMResult<()> {
+        let (cost, res) = self.delegate_charge(|base| base.charge_branch(target_offset));
+
+        self.hook.on_bytecode(Opcodes::BRANCH, cost);
+        self.hook.on_branch(target_offset);
+
+        res
+    }
+
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        let (cost, res) = self.delegate_charge(|base| base.charge_simple_instr(instr));
+
+        self.hook.on_bytecode(instr.to_opcode(), cost);
+        if matches!(instr, SimpleInstruction::Ret) {
+            self.hook.on_function_exit();
+        }
+
+        res
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let (cost, res) =
+            self.delegate_charge(|base| base.charge_call(module_id, func_name, args, num_locals));
+
+        self.hook.on_bytecode(Opcodes::CALL, cost);
+        self.enter_function(module_id, func_name, vec![]);
+
+        res
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView> + Clone,
+        args: impl ExactSizeIterator<Item = impl ValueView> + Clone,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        let ty_tags = ty_args
+            .clone()
+            .map(|ty| ty.to_type_tag())
+            .collect::<Vec<_>>();
+
+        let (cost, res) = self.delegate_charge(|base| {
+            base.charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+        });
+
+        self.hook.on_bytecode(Opcodes::CALL_GENERIC, cost);
+        self.enter_function(module_id, func_name, ty_tags);
+
+        res
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: impl TypeView,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        let ty_tag = ty.to_type_tag();
+        let num_bytes = loaded.as_ref().map(|(num_bytes, _)| *num_bytes);
+
+        let (cost, res) = self.delegate_charge(|base| base.charge_load_resource(addr, ty, loaded));
+
+        self.hook.on_load_resource(addr, &ty_tag, num_bytes, cost);
+
+        res
+    }
+}
+
+impl<G, H> AptosGasMeter for HookedGasMeter<G, H>
+where
+    G: AptosGasMeter,
+    H: ExecutionHook,
+{
+    delegate! {
+        fn feature_version(&self) -> u64;
+
+        fn balance(&self) -> Gas;
+
+        fn gas_unit_scaling_factor(&self) -> GasScalingFactor;
+
+        fn io_gas_per_write(&self, key: &StateKey, op: &WriteOp) -> InternalGas;
+
+        fn storage_fee_per_write(&self, key: &StateKey, op: &WriteOp) -> Fee;
+
+        fn storage_fee_per_event(&self, event: &ContractEvent) -> Fee;
+
+        fn storage_discount_for_events(&self, total_cost: Fee) -> Fee;
+
+        fn storage_fee_for_transaction_storage(&self, txn_size: NumBytes) -> Fee;
+    }
+
+    delegate_mut! {
+        fn charge_execution(&mut self, amount: InternalGas) -> PartialVMResult<()>;
+
+        fn charge_io(&mut self, amount: InternalGas) -> PartialVMResult<()>;
+
+        fn charge_storage_fee(
+            &mut self,
+            amount: aptos_gas::Fee,
+            gas_unit_price: aptos_gas::FeePerGasUnit,
+        ) -> PartialVMResult<()>;
+    }
+
+    fn charge_io_gas_for_write_set<'a>(
+        &mut self,
+        ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+    ) -> VMResult<()> {
+        let ops = ops.into_iter().collect::<Vec<_>>();
+        for (key, op) in &ops {
+            self.hook
+                .on_write(key, op, self.base.io_gas_per_write(key, op));
+        }
+        self.base.charge_io_gas_for_write_set(ops)
+    }
+
+    fn charge_storage_fee_for_all<'a>(
+        &mut self,
+        write_ops: impl IntoIterator<Item = (&'a StateKey, &'a WriteOp)>,
+        events: impl IntoIterator<Item = &'a ContractEvent>,
+        txn_size: NumBytes,
+        gas_unit_price: aptos_gas::FeePerGasUnit,
+    ) -> VMResult<()> {
+        let write_ops = write_ops.into_iter().collect::<Vec<_>>();
+        let events = events.into_iter().collect::<Vec<_>>();
+
+        // The gas meters charge no storage fee before version 7, nor at a gas unit price of 0
+        if self.feature_version() >= 7 && !gas_unit_price.is_zero() {
+            let fees = self.storage_fees(&write_ops, &events, txn_size);
+            self.hook.on_storage_fees(&fees);
+        }
+
+        self.base
+            .charge_storage_fee_for_all(write_ops, events, txn_size, gas_unit_price)
+    }
+
+    fn charge_intrinsic_gas_for_transaction(&mut self, txn_size: NumBytes) -> VMResult<()> {
+        let (cost, res) =
+            self.delegate_charge(|base| base.charge_intrinsic_gas_for_transaction(txn_size));
+
+        self.hook.on_intrinsic_gas(cost);
+
+        res
+    }
+}
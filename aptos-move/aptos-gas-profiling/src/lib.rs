@@ -1,9 +1,18 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+#[macro_use]
+mod macros;
+
 mod flamegraph;
+mod hook;
 mod log;
 mod profiler;
+mod report;
+mod trace;
 
-pub use log::{FrameName, TransactionGasLog};
+pub use hook::{ExecutionHook, HookedGasMeter};
+pub use log::{FrameName, StorageFees, TransactionGasLog};
 pub use profiler::GasProfiler;
+pub use report::{FunctionGas, GasBudget, GasReport};
+pub use trace::{ExecutionTracer, TraceEvent};
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use aptos_gas::{Fee, GasScalingFactor};
-use aptos_types::state_store::state_key::StateKey;
+use aptos_types::{state_store::state_key::StateKey, write_set::WriteOp};
 use move_binary_format::{file_format::CodeOffset, file_format_common::Opcodes};
 use move_core_types::{
     account_address::AccountAddress,
@@ -39,7 +39,7 @@ pub enum ExecutionGasEvent {
 
 /// An enum representing the name of a call frame.
 /// Could be either a script or a function.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum FrameName {
     Script,
     Function {
@@ -60,7 +60,7 @@ pub struct CallFrame {
 /// The type of an operation performed on a storage item.
 ///
 /// Possible values: Creation, Modification & Deletion.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum WriteOpType {
     Creation,
     Modification,
@@ -76,21 +76,21 @@ pub struct WriteTransient {
 }
 
 /// Struct representing the storage cost of a write operation.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct WriteStorage {
     pub key: StateKey,
     pub op_type: WriteOpType,
     pub cost: Fee,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// Struct representing the storage cost of an event.
 pub struct EventStorage {
     pub ty: TypeTag,
     pub cost: Fee,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 // Struct containing all types of storage fees.
 pub struct StorageFees {
     pub write_set_storage: Vec<WriteStorage>,
@@ -132,6 +132,17 @@ impl CallFrame {
     }
 }
 
+pub(crate) fn write_op_type(op: &WriteOp) -> WriteOpType {
+    use WriteOp as O;
+    use WriteOpType as T;
+
+    match op {
+        O::Creation(..) | O::CreationWithMetadata { .. } => T::Creation,
+        O::Modification(..) | O::ModificationWithMetadata { .. } => T::Modification,
+        O::Deletion | O::DeletionWithMetadata { .. } => T::Deletion,
+    }
+}
+
 impl TransactionGasLog {
     pub fn entry_point(&self) -> &FrameName {
         &self.call_graph.name
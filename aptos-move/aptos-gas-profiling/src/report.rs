@@ -0,0 +1,282 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::log::{CallFrame, ExecutionGasEvent, FrameName, StorageFees, TransactionGasLog};
+use aptos_gas::GasScalingFactor;
+use move_core_types::{gas_algebra::InternalGas, identifier::IdentStr, language_storage::ModuleId};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Gas spent by a function, in gas units.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct FunctionGas {
+    /// Number of times the function was called.
+    pub calls: u64,
+    /// Gas spent by the function itself, including the resources it loads. The natives it calls
+    /// are accounted as functions of their own.
+    pub self_gas: f64,
+    /// Gas spent by the function and everything it calls. Recursive calls are only counted once.
+    pub total_gas: f64,
+}
+
+/// A machine-readable summary of a [`TransactionGasLog`], attributing the execution & IO gas to
+/// the functions and modules that spent it, including the ones of the framework and of other
+/// dependencies.
+///
+/// Amounts are in gas units, except for the storage fee which is in Octa. Functions and modules
+/// are named like `0x1::coin::transfer` and `0x1::coin`, without type arguments.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GasReport {
+    /// `<script>` or the entry function.
+    pub entry_point: String,
+    /// The execution & IO gas, including the intrinsic gas and the IO of the write set.
+    pub execution_io_gas: f64,
+    pub intrinsic_gas: f64,
+    pub write_set_io_gas: f64,
+    pub storage_fee: u64,
+    /// Gas spent by the functions of each module, see [`FunctionGas::self_gas`].
+    pub modules: BTreeMap<String, f64>,
+    pub functions: BTreeMap<String, FunctionGas>,
+}
+
+/// Max gas units the functions of a transaction can spend, see [`GasReport::check_budget`].
+///
+/// Written as a map from the function name to its budget, e.g.
+/// `{ "0x42::market::place_order": 1500 }`.
+pub type GasBudget = BTreeMap<String, f64>;
+
+fn function_name(module_id: &ModuleId, name: &IdentStr) -> String {
+    format!("{}::{}", module_id.short_str_lossless(), name)
+}
+
+struct FunctionGasInternal {
+    calls: u64,
+    self_gas: InternalGas,
+    total_gas: InternalGas,
+}
+
+impl Default for FunctionGasInternal {
+    fn default() -> Self {
+        Self {
+            calls: 0,
+            self_gas: InternalGas::new(0),
+            total_gas: InternalGas::new(0),
+        }
+    }
+}
+
+struct Aggregator {
+    functions: BTreeMap<String, FunctionGasInternal>,
+    /// Functions on the call stack, to not count recursive calls twice in their total gas.
+    on_stack: BTreeSet<String>,
+}
+
+impl Aggregator {
+    /// Returns the total gas spent by the frame and its callees.
+    fn visit(&mut self, frame: &CallFrame) -> InternalGas {
+        let name = match &frame.name {
+            FrameName::Script => "<script>".to_string(),
+            FrameName::Function {
+                module_id, name, ..
+            } => function_name(module_id, name),
+        };
+
+        let mut self_gas = InternalGas::new(0);
+        let mut total_gas = InternalGas::new(0);
+        let is_recursive = !self.on_stack.insert(name.clone());
+        for event in &frame.events {
+            use ExecutionGasEvent::*;
+
+            match event {
+                Loc(_) => (),
+                Bytecode { cost, .. } | LoadResource { cost, .. } => self_gas += *cost,
+                CallNative {
+                    module_id,
+                    fn_name,
+                    cost,
+                    ..
+                } => {
+                    let native = self
+                        .functions
+                        .entry(function_name(module_id, fn_name))
+                        .or_default();
+                    native.calls += 1;
+                    native.self_gas += *cost;
+                    native.total_gas += *cost;
+                    total_gas += *cost;
+                },
+                Call(inner_frame) => total_gas += self.visit(inner_frame),
+            }
+        }
+        total_gas += self_gas;
+        if !is_recursive {
+            self.on_stack.remove(&name);
+        }
+
+        let function = self.functions.entry(name).or_default();
+        function.calls += 1;
+        function.self_gas += self_gas;
+        if !is_recursive {
+            function.total_gas += total_gas;
+        }
+        total_gas
+    }
+}
+
+impl StorageFees {
+    /// The total storage fee, in Octa.
+    pub fn total(&self) -> u64 {
+        let write_set: u64 = self
+            .write_set_storage
+            .iter()
+            .map(|item| u64::from(item.cost))
+            .sum();
+        let events: u64 = self.events.iter().map(|event| u64::from(event.cost)).sum();
+        (u64::from(self.txn_storage) + write_set + events)
+            .saturating_sub(u64::from(self.event_discount))
+    }
+}
+
+impl TransactionGasLog {
+    /// Summarizes the log into a [`GasReport`], which can be saved as JSON.
+    pub fn to_gas_report(&self) -> GasReport {
+        let to_gas_units = |cost: InternalGas| to_gas_units(cost, self.gas_scaling_factor);
+
+        let mut aggregator = Aggregator {
+            functions: BTreeMap::new(),
+            on_stack: BTreeSet::new(),
+        };
+        let call_graph_gas = aggregator.visit(&self.call_graph);
+        let write_set_io_gas = self
+            .write_set_transient
+            .iter()
+            .fold(InternalGas::new(0), |total, item| total + item.cost);
+
+        let mut modules = BTreeMap::new();
+        for (name, function) in &aggregator.functions {
+            // Scripts don't belong to any module
+            if let Some((module, _)) = name.rsplit_once("::") {
+                *modules.entry(module.to_string()).or_insert(0.0) +=
+                    to_gas_units(function.self_gas);
+            }
+        }
+
+        GasReport {
+            entry_point: format!("{}", self.entry_point()),
+            execution_io_gas: to_gas_units(self.intrinsic_cost + call_graph_gas + write_set_io_gas),
+            intrinsic_gas: to_gas_units(self.intrinsic_cost),
+            write_set_io_gas: to_gas_units(write_set_io_gas),
+            storage_fee: self.storage.total(),
+            modules,
+            functions: aggregator
+                .functions
+                .into_iter()
+                .map(|(name, function)| {
+                    (name, FunctionGas {
+                        calls: function.calls,
+                        self_gas: to_gas_units(function.self_gas),
+                        total_gas: to_gas_units(function.total_gas),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+fn to_gas_units(cost: InternalGas, gas_scaling_factor: GasScalingFactor) -> f64 {
+    u64::from(cost) as f64 / u64::from(gas_scaling_factor) as f64
+}
+
+impl GasReport {
+    /// Checks the total gas of the functions against `budget`, returning a description of every
+    /// function over its budget. A function with a budget that wasn't called is within it.
+    pub fn check_budget(&self, budget: &GasBudget) -> Vec<String> {
+        budget
+            .iter()
+            .filter_map(|(name, max_gas)| {
+                let function = self.functions.get(name)?;
+                (function.total_gas > *max_gas).then(|| {
+                    format!(
+                        "{} used {} gas units, over its budget of {}",
+                        name, function.total_gas, max_gas
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format_common::Opcodes;
+    use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+
+    fn frame(module: &str, name: &str, events: Vec<ExecutionGasEvent>) -> CallFrame {
+        let mut frame = CallFrame::new_function(
+            ModuleId::new(AccountAddress::ONE, Identifier::new(module).unwrap()),
+            Identifier::new(name).unwrap(),
+            vec![],
+        );
+        frame.events = events;
+        frame
+    }
+
+    fn bytecode(cost: u64) -> ExecutionGasEvent {
+        ExecutionGasEvent::Bytecode {
+            op: Opcodes::ADD,
+            cost: InternalGas::new(cost),
+        }
+    }
+
+    #[test]
+    fn test_gas_report_attribution() {
+        let call_graph = frame("market", "place_order", vec![
+            bytecode(100),
+            ExecutionGasEvent::Call(frame("coin", "transfer", vec![
+                bytecode(200),
+                ExecutionGasEvent::CallNative {
+                    module_id: ModuleId::new(AccountAddress::ONE, Identifier::new("hash").unwrap()),
+                    fn_name: Identifier::new("sha3_256").unwrap(),
+                    ty_args: vec![],
+                    cost: InternalGas::new(300),
+                },
+            ])),
+            ExecutionGasEvent::Call(frame("coin", "transfer", vec![bytecode(200)])),
+        ]);
+        let log = TransactionGasLog {
+            gas_scaling_factor: 100.into(),
+            intrinsic_cost: InternalGas::new(1000),
+            call_graph,
+            write_set_transient: vec![],
+            storage: StorageFees {
+                write_set_storage: vec![],
+                events: vec![],
+                event_discount: 0.into(),
+                txn_storage: 7.into(),
+            },
+        };
+
+        let report = log.to_gas_report();
+        assert_eq!(report.entry_point, "0x1::market::place_order");
+        assert_eq!(report.execution_io_gas, 18.0);
+        assert_eq!(report.storage_fee, 7);
+        assert_eq!(report.functions["0x1::coin::transfer"], FunctionGas {
+            calls: 2,
+            self_gas: 4.0,
+            total_gas: 7.0,
+        });
+        assert_eq!(report.functions["0x1::market::place_order"].total_gas, 8.0);
+        assert_eq!(report.modules["0x1::hash"], 3.0);
+        assert_eq!(report.modules["0x1::coin"], 4.0);
+
+        let budget = GasBudget::from([
+            ("0x1::coin::transfer".to_string(), 7.0),
+            ("0x1::market::place_order".to_string(), 7.5),
+            ("0x1::market::cancel_order".to_string(), 1.0),
+        ]);
+        assert_eq!(report.check_budget(&budget), vec![
+            "0x1::market::place_order used 8 gas units, over its budget of 7.5".to_string()
+        ]);
+    }
+}
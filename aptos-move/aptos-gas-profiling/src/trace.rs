@@ -0,0 +1,129 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{hook::ExecutionHook, log::FrameName};
+use aptos_types::{state_store::state_key::StateKey, write_set::WriteOp};
+use move_core_types::{
+    account_address::AccountAddress,
+    gas_algebra::{InternalGas, NumBytes},
+    language_storage::TypeTag,
+};
+use std::fmt::{self, Display};
+
+/// Something a transaction did, as recorded by an [`ExecutionTracer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TraceEvent {
+    /// A call, `depth` being 0 for the entry function or script.
+    Call {
+        depth: usize,
+        function: String,
+    },
+    LoadResource {
+        depth: usize,
+        addr: AccountAddress,
+        ty: TypeTag,
+        num_bytes: Option<u64>,
+    },
+    Write {
+        key: StateKey,
+        op: WriteOp,
+    },
+}
+
+/// An [`ExecutionHook`] recording the calls and storage accesses of a transaction, in order.
+/// Displays as an indented call tree.
+#[derive(Clone, Debug, Default)]
+pub struct ExecutionTracer {
+    depth: usize,
+    events: Vec<TraceEvent>,
+}
+
+impl ExecutionTracer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> &[TraceEvent] {
+        &self.events
+    }
+
+    /// The functions called, in order, e.g. `0x1::coin::transfer<0x1::aptos_coin::AptosCoin>`.
+    pub fn calls(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Call { function, .. } => Some(function.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl ExecutionHook for ExecutionTracer {
+    fn on_function_entry(&mut self, name: &FrameName) {
+        self.events.push(TraceEvent::Call {
+            depth: self.depth,
+            function: format!("{}", name),
+        });
+        self.depth += 1;
+    }
+
+    fn on_function_exit(&mut self) {
+        self.depth = self.depth.saturating_sub(1);
+    }
+
+    fn on_load_resource(
+        &mut self,
+        addr: AccountAddress,
+        ty: &TypeTag,
+        num_bytes: Option<NumBytes>,
+        _cost: InternalGas,
+    ) {
+        self.events.push(TraceEvent::LoadResource {
+            depth: self.depth,
+            addr,
+            ty: ty.clone(),
+            num_bytes: num_bytes.map(u64::from),
+        });
+    }
+
+    fn on_write(&mut self, key: &StateKey, op: &WriteOp, _cost: InternalGas) {
+        self.events.push(TraceEvent::Write {
+            key: key.clone(),
+            op: op.clone(),
+        });
+    }
+}
+
+impl Display for ExecutionTracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for event in &self.events {
+            match event {
+                TraceEvent::Call { depth, function } => {
+                    writeln!(f, "{:indent$}{}", "", function, indent = depth * 2)?
+                },
+                TraceEvent::LoadResource {
+                    depth,
+                    addr,
+                    ty,
+                    num_bytes,
+                } => {
+                    write!(
+                        f,
+                        "{:indent$}load {} at {}",
+                        "",
+                        ty,
+                        addr,
+                        indent = depth * 2
+                    )?;
+                    match num_bytes {
+                        Some(num_bytes) => writeln!(f, " ({} bytes)", num_bytes)?,
+                        None => writeln!(f, " (not found)")?,
+                    }
+                },
+                TraceEvent::Write { key, op } => writeln!(f, "write {:?}: {:?}", key, op)?,
+            }
+        }
+        Ok(())
+    }
+}
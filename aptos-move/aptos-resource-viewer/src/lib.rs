@@ -5,10 +5,10 @@
 use anyhow::{bail, Result};
 use aptos_types::{
     access_path::AccessPath, account_address::AccountAddress, account_state::AccountState,
-    contract_event::ContractEvent,
+    contract_event::ContractEvent, state_store::table::TableHandle,
 };
 use aptos_vm::move_vm_ext::MoveResolverExt;
-use move_core_types::language_storage::StructTag;
+use move_core_types::language_storage::{StructTag, TypeTag};
 use move_resource_viewer::MoveValueAnnotator;
 pub use move_resource_viewer::{AnnotatedMoveStruct, AnnotatedMoveValue};
 use std::{
@@ -31,6 +31,10 @@ impl<'a, T: MoveResolverExt> AptosValueAnnotator<'a, T> {
         self.0.view_resource(tag, blob)
     }
 
+    pub fn view_value(&self, ty_tag: &TypeTag, blob: &[u8]) -> Result<AnnotatedMoveValue> {
+        self.0.view_value(ty_tag, blob)
+    }
+
     pub fn view_access_path(
         &self,
         access_path: AccessPath,
@@ -73,3 +77,57 @@ impl Display for AnnotatedAccountStateBlob {
         writeln!(f, "}}")
     }
 }
+
+/// A `0x1::table::Table` held in a value, with the types of its keys and values.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FoundTable {
+    pub handle: TableHandle,
+    pub key_type: TypeTag,
+    pub value_type: TypeTag,
+}
+
+/// Collects the `0x1::table::Table`s in `value`. Tables aren't stored with the value holding
+/// them, so this is how their items are found: the items may hold tables of their own in turn.
+pub fn find_tables(value: &AnnotatedMoveValue, tables: &mut impl Extend<FoundTable>) {
+    match value {
+        AnnotatedMoveValue::Struct(annotated_struct) => {
+            find_tables_in_struct(annotated_struct, tables)
+        },
+        AnnotatedMoveValue::Vector(_, values) => {
+            for value in values {
+                find_tables(value, tables);
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Collects the `0x1::table::Table`s in `annotated_struct`, see [`find_tables`].
+pub fn find_tables_in_struct(
+    annotated_struct: &AnnotatedMoveStruct,
+    tables: &mut impl Extend<FoundTable>,
+) {
+    let struct_tag = &annotated_struct.type_;
+    if struct_tag.address == AccountAddress::ONE
+        && struct_tag.module.as_str() == "table"
+        && struct_tag.name.as_str() == "Table"
+    {
+        let handle = annotated_struct
+            .value
+            .iter()
+            .find(|(field, _)| field.as_str() == "handle");
+        if let (Some((_, AnnotatedMoveValue::Address(handle))), [key_type, value_type]) =
+            (handle, struct_tag.type_params.as_slice())
+        {
+            tables.extend(Some(FoundTable {
+                handle: TableHandle(*handle),
+                key_type: key_type.clone(),
+                value_type: value_type.clone(),
+            }));
+        }
+        return;
+    }
+    for (_, value) in &annotated_struct.value {
+        find_tables(value, tables);
+    }
+}
@@ -60,7 +60,7 @@ use move_core_types::{
     transaction_argument::convert_txn_args,
     value::{serialize_values, MoveValue},
 };
-use move_vm_runtime::session::SerializedReturnValues;
+use move_vm_runtime::{config::ModuleCacheConfig, session::SerializedReturnValues};
 use move_vm_types::gas::UnmeteredGasMeter;
 use num_cpus;
 use once_cell::sync::OnceCell;
@@ -78,6 +78,7 @@ use std::{
 static EXECUTION_CONCURRENCY_LEVEL: OnceCell<usize> = OnceCell::new();
 static NUM_PROOF_READING_THREADS: OnceCell<usize> = OnceCell::new();
 static PARANOID_TYPE_CHECKS: OnceCell<bool> = OnceCell::new();
+static MODULE_CACHE_CONFIG: OnceCell<ModuleCacheConfig> = OnceCell::new();
 static PROCESSED_TRANSACTIONS_DETAILED_COUNTERS: OnceCell<bool> = OnceCell::new();
 static TIMED_FEATURE_OVERRIDE: OnceCell<TimedFeatureOverride> = OnceCell::new();
 
@@ -146,6 +147,17 @@ impl AptosVM {
         }
     }
 
+    /// Sets the limits of the loader cache when invoked the first time.
+    pub fn set_module_cache_config_once(config: ModuleCacheConfig) {
+        // Only the first call succeeds, due to OnceCell semantics.
+        MODULE_CACHE_CONFIG.set(config).ok();
+    }
+
+    /// Get the limits of the loader cache if already set, otherwise return default (unlimited)
+    pub fn get_module_cache_config() -> ModuleCacheConfig {
+        MODULE_CACHE_CONFIG.get().cloned().unwrap_or_default()
+    }
+
     // Set the override profile for timed features.
     pub fn set_timed_feature_override(profile: TimedFeatureOverride) {
         TIMED_FEATURE_OVERRIDE.set(profile).ok();
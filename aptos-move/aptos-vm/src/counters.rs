@@ -105,3 +105,31 @@ pub static TXN_VALIDATION_SECONDS: Lazy<Histogram> = Lazy::new(|| {
 pub static TXN_GAS_USAGE: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!("aptos_vm_txn_gas_usage", "Gas used per transaction").unwrap()
 });
+
+/// Count the module loads served from the loader cache of the VM.
+pub static MODULE_CACHE_HITS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_module_cache_hits",
+        "Number of module loads served from the loader cache"
+    )
+    .unwrap()
+});
+
+/// Count the module loads that missed the loader cache and went to storage.
+pub static MODULE_CACHE_MISSES: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_module_cache_misses",
+        "Number of module loads that missed the loader cache"
+    )
+    .unwrap()
+});
+
+/// Count the modules flushed from the loader cache for going over the limits of
+/// `execution.module_cache`.
+pub static MODULE_CACHE_EVICTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "aptos_vm_module_cache_evictions",
+        "Number of modules evicted from the loader cache"
+    )
+    .unwrap()
+});
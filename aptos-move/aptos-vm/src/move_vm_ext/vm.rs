@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
+    counters::{MODULE_CACHE_EVICTIONS, MODULE_CACHE_HITS, MODULE_CACHE_MISSES},
     move_vm_ext::{MoveResolverExt, SessionExt, SessionId},
     natives::aptos_natives,
 };
@@ -61,6 +62,7 @@ impl MoveVmExt {
                     verifier: verifier_config(treat_friend_as_private, &timed_features),
                     max_binary_format_version,
                     paranoid_type_checks: crate::AptosVM::get_paranoid_checks(),
+                    module_cache: crate::AptosVM::get_module_cache_config(),
                 },
             )?,
             chain_id,
@@ -99,7 +101,12 @@ impl MoveVmExt {
 
         // The VM code loader has bugs around module upgrade. After a module upgrade, the internal
         // cache needs to be flushed to work around those bugs.
+        // The cache is also flushed if it grew over its limits.
         self.inner.flush_loader_cache_if_invalidated();
+        let cache_counts = self.inner.take_loader_cache_counts();
+        MODULE_CACHE_HITS.inc_by(cache_counts.hits);
+        MODULE_CACHE_MISSES.inc_by(cache_counts.misses);
+        MODULE_CACHE_EVICTIONS.inc_by(cache_counts.evictions);
 
         SessionExt::new(
             self.inner.new_session_with_extensions(remote, extensions),
@@ -24,6 +24,17 @@ use {
     move_vm_test_utils::BlankStorage,
     once_cell::sync::Lazy,
 };
+#[cfg(feature = "failpoints")]
+use {
+    move_binary_format::errors::{PartialVMError, PartialVMResult},
+    move_core_types::{
+        gas_algebra::InternalGas, parser::parse_transaction_argument,
+        transaction_argument::TransactionArgument, vm_status::StatusCode,
+    },
+    move_vm_runtime::native_functions::NativeFunction,
+    move_vm_types::{natives::function::NativeResult, values::Value},
+    smallvec::smallvec,
+};
 
 #[cfg(feature = "testing")]
 static DUMMY_RESOLVER: Lazy<BlankStorage> = Lazy::new(|| BlankStorage);
@@ -35,24 +46,95 @@ pub fn aptos_natives(
     timed_features: TimedFeatures,
     features: Arc<Features>,
 ) -> NativeFunctionTable {
-    aptos_move_stdlib::natives::all_natives(CORE_CODE_ADDRESS, gas_params.move_stdlib.clone())
+    let natives =
+        aptos_move_stdlib::natives::all_natives(CORE_CODE_ADDRESS, gas_params.move_stdlib.clone())
+            .into_iter()
+            .filter(|(_, name, _, _)| name.as_str() != "vector")
+            .chain(aptos_framework::natives::all_natives(
+                CORE_CODE_ADDRESS,
+                gas_params.move_stdlib,
+                gas_params.aptos_framework,
+                timed_features,
+                features,
+                move |val| abs_val_size_gas_params.abstract_value_size(val, gas_feature_version),
+            ))
+            .chain(move_table_extension::table_natives(
+                CORE_CODE_ADDRESS,
+                gas_params.table,
+            ))
+            .collect();
+    with_failpoints(natives)
+}
+
+#[cfg(not(feature = "failpoints"))]
+fn with_failpoints(natives: NativeFunctionTable) -> NativeFunctionTable {
+    natives
+}
+
+/// Guards each native with the failpoint `native::<address>::<module>::<function>`, e.g.
+/// `native::0x1::table::add_box`, so tests can reach the error handling of the code calling it.
+/// Besides the actions of the `fail` crate, the native:
+/// - aborts with `code` on `return(abort:<code>)`,
+/// - returns `value` on `return(<value>)`, where `value` is a transaction argument such as
+///   `false`, `0u64` or `x"00"`,
+/// - fails with an invariant violation on `return`.
+#[cfg(feature = "failpoints")]
+fn with_failpoints(natives: NativeFunctionTable) -> NativeFunctionTable {
+    natives
         .into_iter()
-        .filter(|(_, name, _, _)| name.as_str() != "vector")
-        .chain(aptos_framework::natives::all_natives(
-            CORE_CODE_ADDRESS,
-            gas_params.move_stdlib,
-            gas_params.aptos_framework,
-            timed_features,
-            features,
-            move |val| abs_val_size_gas_params.abstract_value_size(val, gas_feature_version),
-        ))
-        .chain(move_table_extension::table_natives(
-            CORE_CODE_ADDRESS,
-            gas_params.table,
-        ))
+        .map(|(address, module, function, native)| {
+            let name = format!(
+                "native::{}::{}::{}",
+                address.to_hex_literal(),
+                module,
+                function
+            );
+            let guarded: NativeFunction = Arc::new(move |context, ty_args, args| {
+                if let Some(result) = fail::eval(&name, |action| native_fault(&name, action)) {
+                    return result;
+                }
+                native(context, ty_args, args)
+            });
+            (address, module, function, guarded)
+        })
         .collect()
 }
 
+#[cfg(feature = "failpoints")]
+fn native_fault(name: &str, action: Option<String>) -> PartialVMResult<NativeResult> {
+    let invariant_violation = |message: String| {
+        Err(
+            PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR)
+                .with_message(message),
+        )
+    };
+    let action = match action {
+        Some(action) => action,
+        None => return invariant_violation(format!("Failpoint {} failed the native", name)),
+    };
+    if let Some(code) = action.strip_prefix("abort:") {
+        return match code.parse() {
+            Ok(code) => Ok(NativeResult::err(InternalGas::zero(), code)),
+            Err(_) => invariant_violation(format!("Invalid abort code {} for {}", code, name)),
+        };
+    }
+    let value = match parse_transaction_argument(&action) {
+        Ok(TransactionArgument::Bool(value)) => Value::bool(value),
+        Ok(TransactionArgument::U8(value)) => Value::u8(value),
+        Ok(TransactionArgument::U16(value)) => Value::u16(value),
+        Ok(TransactionArgument::U32(value)) => Value::u32(value),
+        Ok(TransactionArgument::U64(value)) => Value::u64(value),
+        Ok(TransactionArgument::U128(value)) => Value::u128(value),
+        Ok(TransactionArgument::U256(value)) => Value::u256(value),
+        Ok(TransactionArgument::Address(value)) => Value::address(value),
+        Ok(TransactionArgument::U8Vector(value)) => Value::vector_u8(value),
+        Err(err) => {
+            return invariant_violation(format!("Invalid value {} for {}: {}", action, name, err))
+        },
+    };
+    Ok(NativeResult::ok(InternalGas::zero(), smallvec![value]))
+}
+
 pub fn assert_no_test_natives(err_msg: &str) {
     assert!(
         aptos_natives(
@@ -0,0 +1,52 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use e2e_move_tests::{assert_success, BenchHarness, MoveHarness};
+use move_core_types::account_address::AccountAddress;
+use std::path::PathBuf;
+
+fn package_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/tests/code_publishing.data/pack_initial")
+}
+
+fn publish(c: &mut Criterion) {
+    c.bench_function("publish", |b| {
+        let mut bench = BenchHarness::new(MoveHarness::new());
+        let account = bench
+            .harness()
+            .new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+        bench.bench_publish(b, &account, &package_path())
+    });
+}
+
+fn entry_function(c: &mut Criterion) {
+    c.bench_function("entry_function", |b| {
+        let mut bench = BenchHarness::new(MoveHarness::new());
+        let h = bench.harness();
+        let account = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+        assert_success!(h.publish_package(&account, &package_path()));
+        bench.bench_entry_function(
+            b,
+            &account,
+            str::parse("0xcafe::test::hello").unwrap(),
+            vec![],
+            vec![bcs::to_bytes(&42u64).unwrap()],
+        )
+    });
+}
+
+fn transfers(c: &mut Criterion) {
+    c.bench_function("transfers_block", |b| {
+        let mut bench = BenchHarness::new(MoveHarness::new());
+        bench.bench_transfers(b, 100)
+    });
+}
+
+criterion_group!(
+    name = e2e_benches;
+    config = Criterion::default().sample_size(10);
+    targets = publish, entry_function, transfers
+);
+
+criterion_main!(e2e_benches);
@@ -1,11 +1,26 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{assert_success, harness::MoveHarness};
+use crate::{
+    assert_success,
+    harness::{AggregatorHandle, MoveHarness},
+};
 use aptos_language_e2e_tests::account::Account;
-use aptos_types::{account_address::AccountAddress, transaction::SignedTransaction};
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::{state_key::StateKey, table::TableHandle},
+    transaction::SignedTransaction,
+};
+use move_core_types::parser::parse_struct_tag;
+use serde::Deserialize;
 use std::path::PathBuf;
 
+/// Mimics `0x1::aggregator_test::AggregatorStore`
+#[derive(Deserialize)]
+struct AggregatorStore {
+    aggregators: TableHandle,
+}
+
 pub fn initialize(path: PathBuf) -> (MoveHarness, Account) {
     let mut harness = MoveHarness::new();
     let account = harness.new_account_at(AccountAddress::ONE);
@@ -19,6 +34,20 @@ pub fn initialize(path: PathBuf) -> (MoveHarness, Account) {
     (harness, account)
 }
 
+/// Reads the value of the ith aggregator directly from storage, rather than with a transaction
+/// like `check`. Returns `None` if it doesn't exist.
+pub fn read(harness: &MoveHarness, account: &Account, index: u64) -> Option<u128> {
+    let store = harness.read_resource::<AggregatorStore>(
+        account.address(),
+        parse_struct_tag("0x1::aggregator_test::AggregatorStore").unwrap(),
+    )?;
+    let aggregator = harness.read_state_value(&StateKey::table_item(
+        store.aggregators,
+        bcs::to_bytes(&index).unwrap(),
+    ))?;
+    harness.read_aggregator_value(&bcs::from_bytes::<AggregatorHandle>(&aggregator).unwrap())
+}
+
 pub fn check(
     harness: &mut MoveHarness,
     account: &Account,
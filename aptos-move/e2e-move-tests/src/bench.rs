@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos::move_tool::MemberId;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::transaction::SignedTransaction;
+use criterion::{measurement::Measurement, BatchSize, Bencher};
+use move_core_types::language_storage::TypeTag;
+use std::path::Path;
+
+/// Measures the scenarios of the e2e tests with criterion, so that regressions in the VM or the
+/// framework show up for the workloads the tests define.
+///
+/// The state is set up with the wrapped `MoveHarness` as in a test, see `harness`. Each routine
+/// then creates its transactions, checks they succeed, and measures executing them as a block
+/// over and over. The outputs are never applied, so the transactions stay valid across iterations
+/// and routines don't affect each other.
+pub struct BenchHarness {
+    harness: MoveHarness,
+}
+
+impl BenchHarness {
+    pub fn new(harness: MoveHarness) -> Self {
+        Self { harness }
+    }
+
+    /// The harness the benchmarks run on, to set up their state.
+    pub fn harness(&mut self) -> &mut MoveHarness {
+        &mut self.harness
+    }
+
+    /// Measures publishing the Move package at `path` on behalf of `account`. The package is
+    /// built once, outside of the measurement.
+    pub fn bench_publish<M: Measurement>(
+        &mut self,
+        b: &mut Bencher<M>,
+        account: &Account,
+        path: &Path,
+    ) {
+        let txn = self
+            .harness
+            .create_publish_package(account, path, None, |_| {});
+        self.bench_block(b, vec![txn]);
+    }
+
+    /// Measures running the entry function `fun`. Arguments need to be provided in
+    /// bcs-serialized form.
+    pub fn bench_entry_function<M: Measurement>(
+        &mut self,
+        b: &mut Bencher<M>,
+        account: &Account,
+        fun: MemberId,
+        ty_args: Vec<TypeTag>,
+        args: Vec<Vec<u8>>,
+    ) {
+        let txn = self
+            .harness
+            .create_entry_function(account, fun, ty_args, args);
+        self.bench_block(b, vec![txn]);
+    }
+
+    /// Measures a block of `count` APT transfers between distinct accounts, which the block
+    /// executor can run in parallel. The accounts are created once, outside of the measurement.
+    pub fn bench_transfers<M: Measurement>(&mut self, b: &mut Bencher<M>, count: usize) {
+        let accounts: Vec<_> = (0..count)
+            .map(|_| self.harness.new_account_with_key_pair())
+            .collect();
+        let txns = accounts
+            .iter()
+            .zip(accounts.iter().cycle().skip(1))
+            .map(|(sender, receiver)| {
+                self.harness.create_transaction_payload(
+                    sender,
+                    aptos_stdlib::aptos_account_transfer(*receiver.address(), 1),
+                )
+            })
+            .collect();
+        self.bench_block(b, txns);
+    }
+
+    /// Measures executing `txns` as a block, after checking that they all succeed.
+    pub fn bench_block<M: Measurement>(
+        &mut self,
+        b: &mut Bencher<M>,
+        txns: Vec<SignedTransaction>,
+    ) {
+        for txn in &txns {
+            self.harness.reset_sequence_number(&txn.sender());
+        }
+        let executor = &self.harness.executor;
+        for output in executor.execute_block(txns.clone()).unwrap() {
+            assert_success!(output.status().to_owned());
+        }
+        b.iter_batched(
+            || txns.clone(),
+            |txns| executor.execute_block(txns).unwrap(),
+            BatchSize::SmallInput,
+        )
+    }
+}
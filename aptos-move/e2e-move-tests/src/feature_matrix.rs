@@ -0,0 +1,148 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_types::on_chain_config::FeatureFlag;
+use std::{
+    fmt::{self, Display},
+    panic::{self, AssertUnwindSafe},
+};
+
+/// The genesis a [`FeatureCombination`] starts from, which determines the default features.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Genesis {
+    Head,
+    Testnet,
+    Mainnet,
+}
+
+/// A set of features to run a test with: the features of a genesis, with some flags explicitly
+/// enabled or disabled.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeatureCombination {
+    pub genesis: Genesis,
+    pub enabled: Vec<FeatureFlag>,
+    pub disabled: Vec<FeatureFlag>,
+}
+
+impl FeatureCombination {
+    pub fn head() -> Self {
+        Self::new(Genesis::Head)
+    }
+
+    pub fn testnet() -> Self {
+        Self::new(Genesis::Testnet)
+    }
+
+    pub fn mainnet() -> Self {
+        Self::new(Genesis::Mainnet)
+    }
+
+    fn new(genesis: Genesis) -> Self {
+        Self {
+            genesis,
+            enabled: vec![],
+            disabled: vec![],
+        }
+    }
+
+    pub fn enable(mut self, flag: FeatureFlag) -> Self {
+        self.disabled.retain(|f| *f != flag);
+        self.enabled.push(flag);
+        self
+    }
+
+    pub fn disable(mut self, flag: FeatureFlag) -> Self {
+        self.enabled.retain(|f| *f != flag);
+        self.disabled.push(flag);
+        self
+    }
+
+    /// Whether `flag` is explicitly enabled. Flags neither enabled nor disabled keep the value of
+    /// the genesis.
+    pub fn is_enabled(&self, flag: FeatureFlag) -> bool {
+        self.enabled.contains(&flag)
+    }
+
+    /// The head genesis with `flag` disabled, then enabled.
+    pub fn toggling(flag: FeatureFlag) -> Vec<Self> {
+        vec![Self::head().disable(flag), Self::head().enable(flag)]
+    }
+
+    /// The default features of each genesis.
+    pub fn defaults() -> Vec<Self> {
+        vec![Self::head(), Self::testnet(), Self::mainnet()]
+    }
+
+    /// Creates a harness with these features.
+    pub fn harness(&self) -> MoveHarness {
+        let mut h = match self.genesis {
+            Genesis::Head => MoveHarness::new(),
+            Genesis::Testnet => MoveHarness::new_testnet(),
+            Genesis::Mainnet => MoveHarness::new_mainnet(),
+        };
+        if !self.enabled.is_empty() || !self.disabled.is_empty() {
+            h.enable_features(self.enabled.clone(), self.disabled.clone());
+        }
+        h
+    }
+}
+
+impl Display for FeatureCombination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.genesis)?;
+        for flag in &self.enabled {
+            write!(f, " +{:?}", flag)?;
+        }
+        for flag in &self.disabled {
+            write!(f, " -{:?}", flag)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs `body` with a harness for each of `combinations`. All of them are run even if some
+/// fail, then the test fails listing the ones which did.
+pub fn run_feature_matrix(
+    combinations: impl IntoIterator<Item = FeatureCombination>,
+    body: impl Fn(MoveHarness, &FeatureCombination),
+) {
+    let mut failed = vec![];
+    for combination in combinations {
+        let h = combination.harness();
+        match panic::catch_unwind(AssertUnwindSafe(|| body(h, &combination))) {
+            Ok(()) => println!("[{}] ok", combination),
+            Err(_) => {
+                println!("[{}] FAILED", combination);
+                failed.push(combination.to_string());
+            },
+        }
+    }
+    if !failed.is_empty() {
+        panic!("failed with features: [{}]", failed.join("], ["))
+    }
+}
+
+/// Defines a test running its body for each of a list of [`FeatureCombination`]s, e.g.
+///
+/// ```ignore
+/// feature_matrix!(
+///     FeatureCombination::toggling(FeatureFlag::CODE_DEPENDENCY_CHECK),
+///     fn publishing(h, features) {
+///         let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+///         ...
+///     }
+/// );
+/// ```
+#[macro_export]
+macro_rules! feature_matrix {
+    ($combinations:expr, $(#[$attr:meta])* fn $name:ident($h:ident, $features:ident) $body:block) => {
+        $(#[$attr])*
+        #[test]
+        fn $name() {
+            #[allow(unused_mut, unused_variables)]
+            let body = |mut $h: $crate::MoveHarness, $features: &$crate::FeatureCombination| $body;
+            $crate::run_feature_matrix($combinations, body)
+        }
+    };
+}
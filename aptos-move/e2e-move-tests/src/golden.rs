@@ -0,0 +1,147 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_types::{
+    access_path::Path, state_store::state_key::StateKeyInner, transaction::TransactionOutput,
+    write_set::WriteOp,
+};
+use aptos_vm::move_vm_ext::MoveResolverExt;
+use goldenfile::Mint;
+use move_core_types::language_storage::StructTag;
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    fs::File,
+    io::Write,
+    path::{Path as FsPath, PathBuf},
+};
+
+/// The extension of golden files.
+pub const GOLDEN_EXT: &str = "exp";
+
+/// A golden file recording the decoded outputs of transactions: their status, gas, events and
+/// write set, with resources and events shown as Move values.
+///
+/// When dropped, the file is compared against the checked-in one and the test fails if they
+/// differ. Run the tests with `UPDATE_GOLDENFILES=1` to update the checked-in files instead.
+pub struct GoldenOutputs {
+    // Compares the files when dropped, so must outlive `file`
+    _mint: Mint,
+    file: File,
+}
+
+impl GoldenOutputs {
+    pub fn new(dir: PathBuf, name: &str) -> Self {
+        let mut mint = Mint::new(dir);
+        let file = mint
+            .new_goldenfile(FsPath::new(name).with_extension(GOLDEN_EXT))
+            .expect("creating a golden file must succeed");
+        Self { _mint: mint, file }
+    }
+
+    /// Records `output`. Types are resolved with `resolver`, which must include the modules
+    /// published by the transaction.
+    pub fn record(&mut self, resolver: &impl MoveResolverExt, output: &TransactionOutput) {
+        let text = decode_output(&AptosValueAnnotator::new(resolver), output);
+        self.file
+            .write_all(text.as_bytes())
+            .expect("writing a golden file must succeed");
+    }
+}
+
+fn decode_output<T: MoveResolverExt>(
+    annotator: &AptosValueAnnotator<T>,
+    output: &TransactionOutput,
+) -> String {
+    let mut text = String::new();
+    writeln!(text, "status: {:?}", output.status()).unwrap();
+    writeln!(text, "gas used: {}", output.gas_used()).unwrap();
+
+    writeln!(text, "events:").unwrap();
+    for event in output.events() {
+        match annotator.view_contract_event(event) {
+            Ok(value) => writeln!(text, "  {}", value).unwrap(),
+            Err(_) => writeln!(
+                text,
+                "  {} 0x{}",
+                event.type_tag(),
+                hex::encode(event.event_data())
+            )
+            .unwrap(),
+        }
+    }
+
+    writeln!(text, "write set:").unwrap();
+    for (key, op) in output.write_set().iter() {
+        let (kind, data) = match op {
+            WriteOp::Creation(data) | WriteOp::CreationWithMetadata { data, .. } => {
+                ("create", Some(data))
+            },
+            WriteOp::Modification(data) | WriteOp::ModificationWithMetadata { data, .. } => {
+                ("modify", Some(data))
+            },
+            WriteOp::Deletion | WriteOp::DeletionWithMetadata { .. } => ("delete", None),
+        };
+        match key.inner() {
+            StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+                // Module bytecode changes with the compiler, so only its name is recorded
+                Path::Code(module_id) => writeln!(text, "  {} code {}", kind, module_id).unwrap(),
+                Path::Resource(tag) => {
+                    writeln!(text, "  {} {} at {}", kind, tag, access_path.address).unwrap();
+                    if let Some(data) = data {
+                        write_resource(&mut text, annotator, &tag, data);
+                    }
+                },
+                Path::ResourceGroup(tag) => {
+                    writeln!(text, "  {} {} at {}", kind, tag, access_path.address).unwrap();
+                    match data.map(|data| bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(data)) {
+                        Some(Ok(group)) => {
+                            for (tag, data) in &group {
+                                writeln!(text, "    {}", tag).unwrap();
+                                write_resource(&mut text, annotator, tag, data);
+                            }
+                        },
+                        Some(Err(_)) => {
+                            writeln!(text, "    0x{}", hex::encode(data.unwrap())).unwrap()
+                        },
+                        None => (),
+                    }
+                },
+            },
+            StateKeyInner::TableItem { handle, key } => {
+                writeln!(
+                    text,
+                    "  {} table item {}[0x{}]",
+                    kind,
+                    handle.0,
+                    hex::encode(key)
+                )
+                .unwrap();
+                if let Some(data) = data {
+                    writeln!(text, "    0x{}", hex::encode(data)).unwrap();
+                }
+            },
+            StateKeyInner::Raw(key) => {
+                writeln!(text, "  {} raw 0x{}", kind, hex::encode(key)).unwrap();
+                if let Some(data) = data {
+                    writeln!(text, "    0x{}", hex::encode(data)).unwrap();
+                }
+            },
+        }
+    }
+    writeln!(text).unwrap();
+    text
+}
+
+fn write_resource<T: MoveResolverExt>(
+    text: &mut String,
+    annotator: &AptosValueAnnotator<T>,
+    tag: &StructTag,
+    data: &[u8],
+) {
+    match annotator.view_resource(tag, data) {
+        Ok(value) => writeln!(text, "    {}", value).unwrap(),
+        Err(_) => writeln!(text, "    0x{}", hex::encode(data)).unwrap(),
+    }
+}
@@ -1,34 +1,51 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{assert_success, AptosPackageHooks};
+use crate::{
+    assert_success,
+    golden::GoldenOutputs,
+    ledger_check::LedgerChecker,
+    ordering::{orderings, BlockOutcome},
+    repro::{LastBlock, ReproBundle, SEED_ENV_VAR},
+    state_consistency::check_state,
+    usage_report::UsageRecorder,
+    AptosPackageHooks,
+};
 use aptos::move_tool::MemberId;
+use aptos_api_types::MoveModule;
 use aptos_cached_packages::aptos_stdlib;
 use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
-use aptos_framework::{natives::code::PackageMetadata, BuildOptions, BuiltPackage};
+use aptos_framework::{natives::code::PackageMetadata, BuildOptions, BuiltPackage, ReleaseBundle};
 use aptos_gas::{
     AptosGasParameters, FromOnChainGasSchedule, InitialGasSchedule, ToOnChainGasSchedule,
 };
+use aptos_gas_profiling::ExecutionHook;
 use aptos_language_e2e_tests::{
     account::{Account, AccountData},
     executor::FakeExecutor,
 };
+use aptos_rest_client::TypeAccessor;
 use aptos_types::{
     access_path::AccessPath,
     account_address::AccountAddress,
-    account_config::{AccountResource, CORE_CODE_ADDRESS},
+    account_config::{AccountResource, CoinStoreResource, CORE_CODE_ADDRESS},
+    chain_id::ChainId,
     contract_event::ContractEvent,
     on_chain_config::{FeatureFlag, GasScheduleV2, OnChainConfig},
-    state_store::state_key::StateKey,
+    state_store::{state_export::StateExport, state_key::StateKey, table::TableHandle},
     transaction::{
-        EntryFunction, Script, SignedTransaction, TransactionArgument, TransactionOutput,
-        TransactionPayload, TransactionStatus,
+        EntryFunction, ExecutionStatus, Script, SignedTransaction, Transaction,
+        TransactionArgument, TransactionOutput, TransactionPayload, TransactionStatus,
     },
 };
+use aptos_vm::data_cache::AsMoveResolver;
+use fail::FailScenario;
+use move_binary_format::CompiledModule;
 use move_core_types::{
     language_storage::{StructTag, TypeTag},
     move_resource::MoveStructType,
     value::MoveValue,
+    vm_status::StatusCode,
 };
 use move_package::package_hooks::register_package_hooks;
 use project_root::get_project_root;
@@ -36,10 +53,97 @@ use rand::{
     rngs::{OsRng, StdRng},
     Rng, SeedableRng,
 };
-use serde::{de::DeserializeOwned, Serialize};
-use std::{collections::BTreeMap, path::Path};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{collections::BTreeMap, env, path::Path};
 
 const DEFAULT_GAS_UNIT_PRICE: u64 = 100;
+const DEFAULT_MAX_GAS_AMOUNT: u64 = 2_000_000;
+/// 10M Aptos coins (with 8 decimals).
+const DEFAULT_BALANCE: u64 = 1_000_000_000_000_000;
+
+/// Mimics `0x1::aggregator::Aggregator`, which only holds where the value of the aggregator is
+/// stored. Read the value with `MoveHarness::read_aggregator_value`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AggregatorHandle {
+    pub handle: AccountAddress,
+    pub key: AccountAddress,
+    pub limit: u128,
+}
+
+/// A way to make the prologue or the epilogue of the next transaction the harness creates fail,
+/// see `MoveHarness::inject_fault`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionFault {
+    /// Signs the transaction for another chain.
+    BadChainId,
+    /// Makes the transaction expire at the start of the chain.
+    Expired,
+    /// Reuses the last sequence number of the sender.
+    SequenceNumberTooOld,
+    /// Skips the next sequence number of the sender.
+    SequenceNumberTooNew,
+    /// Lowers the APT balance of the sender to one octa less than the maximum fee of the
+    /// transaction.
+    InsufficientBalance,
+    /// Lowers the APT balance of the sender to exactly the maximum fee of the transaction. The
+    /// prologue passes, but the epilogue fails if the payload leaves the sender less APT than the
+    /// fee of the gas it used.
+    EpilogueInsufficientBalance,
+}
+
+impl TransactionFault {
+    /// The status of the transaction if the fault makes its prologue fail, in which case the
+    /// transaction is discarded without charging any fee.
+    pub fn discard_status(&self) -> Option<StatusCode> {
+        match self {
+            TransactionFault::BadChainId => Some(StatusCode::BAD_CHAIN_ID),
+            TransactionFault::Expired => Some(StatusCode::TRANSACTION_EXPIRED),
+            TransactionFault::SequenceNumberTooOld => Some(StatusCode::SEQUENCE_NUMBER_TOO_OLD),
+            TransactionFault::SequenceNumberTooNew => Some(StatusCode::SEQUENCE_NUMBER_TOO_NEW),
+            TransactionFault::InsufficientBalance => {
+                Some(StatusCode::INSUFFICIENT_BALANCE_FOR_TRANSACTION_FEE)
+            },
+            TransactionFault::EpilogueInsufficientBalance => None,
+        }
+    }
+}
+
+/// What a native function does instead of running once made to fail with
+/// `MoveHarness::fail_native`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NativeFault {
+    /// Aborts with the code, as natives do on invalid input.
+    Abort(u64),
+    /// Returns the value, e.g. `false` for a signature verification.
+    Return(TransactionArgument),
+    /// Fails with an invariant violation, as on a bug in the native.
+    InvariantViolation,
+}
+
+impl NativeFault {
+    /// The failpoint action making the native behave as the fault, see
+    /// `aptos_vm::natives::aptos_natives`.
+    #[cfg(feature = "failpoints")]
+    fn action(&self) -> String {
+        let value = match self {
+            NativeFault::Abort(code) => return format!("return(abort:{})", code),
+            NativeFault::InvariantViolation => return "return".to_string(),
+            NativeFault::Return(value) => value,
+        };
+        let value = match value {
+            TransactionArgument::Bool(value) => value.to_string(),
+            TransactionArgument::U8(value) => format!("{}u8", value),
+            TransactionArgument::U16(value) => format!("{}u16", value),
+            TransactionArgument::U32(value) => format!("{}u32", value),
+            TransactionArgument::U64(value) => format!("{}u64", value),
+            TransactionArgument::U128(value) => format!("{}u128", value),
+            TransactionArgument::U256(value) => format!("{}u256", value),
+            TransactionArgument::Address(address) => address.to_hex_literal(),
+            TransactionArgument::U8Vector(bytes) => format!("x\"{}\"", hex::encode(bytes)),
+        };
+        format!("return({})", value)
+    }
+}
 
 /// A simple test harness for defining Move e2e tests.
 ///
@@ -63,35 +167,45 @@ pub struct MoveHarness {
     txn_seq_no: BTreeMap<AccountAddress, u64>,
 
     default_gas_unit_price: u64,
+    /// The APT balance of the accounts created by `new_account_*`, unless given explicitly.
+    default_balance: u64,
+    /// Where the outputs of the transactions are recorded, see `enable_golden`.
+    golden: Option<GoldenOutputs>,
+    /// Checks the ledger after each block, see `enable_ledger_checks`.
+    ledger_checker: Option<LedgerChecker>,
+    /// Applied to the next transaction created, see `inject_fault`.
+    fault: Option<TransactionFault>,
+    /// Holds the failpoints of the natives made to fail, see `fail_native`.
+    native_faults: Option<FailScenario<'static>>,
+    /// Seeds `rng`, printed if the test fails, see `with_seed`.
+    seed: u64,
+    /// Generates the keys of the accounts created, and whatever else tests randomize with `rng`.
+    rng: StdRng,
+    /// Recorded for `export_repro_bundle`.
+    last_block: LastBlock,
+    /// Set if the usage of the test is reported, see `USAGE_REPORT_ENV_VAR`.
+    usage: Option<UsageRecorder>,
 }
 
 impl MoveHarness {
     /// Creates a new harness.
     pub fn new() -> Self {
-        register_package_hooks(Box::new(AptosPackageHooks {}));
-        Self {
-            executor: FakeExecutor::from_head_genesis(),
-            txn_seq_no: BTreeMap::default(),
-            default_gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
-        }
+        Self::with_executor(FakeExecutor::from_head_genesis())
     }
 
     pub fn new_with_validators(count: u64) -> Self {
-        register_package_hooks(Box::new(AptosPackageHooks {}));
-        Self {
-            executor: FakeExecutor::from_head_genesis_with_count(count),
-            txn_seq_no: BTreeMap::default(),
-            default_gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
-        }
+        Self::with_executor(FakeExecutor::from_head_genesis_with_count(count))
     }
 
     pub fn new_testnet() -> Self {
-        register_package_hooks(Box::new(AptosPackageHooks {}));
-        Self {
-            executor: FakeExecutor::from_testnet_genesis(),
-            txn_seq_no: BTreeMap::default(),
-            default_gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
-        }
+        Self::with_executor(FakeExecutor::from_testnet_genesis())
+    }
+
+    /// Creates a harness with the previous framework release, the one of the last testnet. A test
+    /// can run a scenario with it, call `upgrade_framework`, and continue the scenario with the
+    /// current framework.
+    pub fn new_with_previous_framework() -> Self {
+        Self::new_testnet()
     }
 
     pub fn new_with_features(
@@ -104,21 +218,71 @@ impl MoveHarness {
     }
 
     pub fn new_mainnet() -> Self {
+        Self::with_executor(FakeExecutor::from_mainnet_genesis())
+    }
+
+    /// Creates a harness around `executor`, seeded from `E2E_MOVE_TESTS_SEED` if it is set, and
+    /// randomly otherwise.
+    pub(crate) fn with_executor(executor: FakeExecutor) -> Self {
         register_package_hooks(Box::new(AptosPackageHooks {}));
+        let seed = match env::var(SEED_ENV_VAR) {
+            Ok(seed) => seed
+                .parse()
+                .unwrap_or_else(|_| panic!("{} must be a u64, got {:?}", SEED_ENV_VAR, seed)),
+            Err(_) => OsRng.gen(),
+        };
         Self {
-            executor: FakeExecutor::from_mainnet_genesis(),
+            executor,
             txn_seq_no: BTreeMap::default(),
             default_gas_unit_price: DEFAULT_GAS_UNIT_PRICE,
+            default_balance: DEFAULT_BALANCE,
+            golden: None,
+            ledger_checker: None,
+            fault: None,
+            native_faults: None,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            last_block: LastBlock::default(),
+            usage: UsageRecorder::from_env(),
         }
     }
 
+    /// Reseeds the harness, so that the accounts created from now on, and whatever tests
+    /// randomize with `rng`, are the same on every run. Call it right after creating the harness
+    /// to re-run a test with the seed it printed when it failed, or set `E2E_MOVE_TESTS_SEED`.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// The seed of the harness, printed if the test panics.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The random number generator of the harness. Tests randomizing e.g. amounts or the order
+    /// of transactions with it can be re-run exactly with the seed of the harness.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+
+    /// Sets the APT balance of the accounts created from now on, which is 10M Aptos coins by
+    /// default.
+    pub fn with_default_balance(mut self, amount: u64) -> Self {
+        self.default_balance = amount;
+        self
+    }
+
     /// Creates an account for the given static address. This address needs to be static so
     /// we can load regular Move code to there without need to rewrite code addresses.
+    ///
+    /// Like all accounts created by `new_account_*`, it is registered for APT and funded with the
+    /// default balance, see `with_default_balance`.
     pub fn new_account_at(&mut self, addr: AccountAddress) -> Account {
         // The below will use the genesis keypair but that should be fine.
         let acc = Account::new_genesis_account(addr);
-        // Mint the account 10M Aptos coins (with 8 decimals).
-        let data = AccountData::with_account(acc, 1_000_000_000_000_000, 10);
+        let data = AccountData::with_account(acc, self.default_balance, 10);
         self.executor.add_account_data(&data);
         self.txn_seq_no.insert(addr, 10);
         data.account().clone()
@@ -126,12 +290,10 @@ impl MoveHarness {
 
     // Creates an account with a randomly generated address and key pair
     pub fn new_account_with_key_pair(&mut self) -> Account {
-        let mut rng = StdRng::from_seed(OsRng.gen());
-
-        let privkey = Ed25519PrivateKey::generate(&mut rng);
+        let privkey = Ed25519PrivateKey::generate(&mut self.rng);
         let pubkey = privkey.public_key();
         let acc = Account::with_keypair(privkey, pubkey);
-        let data = AccountData::with_account(acc.clone(), 1_000_000_000_000_000, 0);
+        let data = AccountData::with_account(acc.clone(), self.default_balance, 0);
         self.executor.add_account_data(&data);
         self.txn_seq_no.insert(*acc.address(), 0);
         data.account().clone()
@@ -142,9 +304,7 @@ impl MoveHarness {
         balance: u64,
         sequence_number: u64,
     ) -> Account {
-        let mut rng = StdRng::from_seed(OsRng.gen());
-
-        let privkey = Ed25519PrivateKey::generate(&mut rng);
+        let privkey = Ed25519PrivateKey::generate(&mut self.rng);
         let pubkey = privkey.public_key();
         let acc = Account::with_keypair(privkey, pubkey);
         let data = AccountData::with_account(acc.clone(), balance, sequence_number);
@@ -160,11 +320,26 @@ impl MoveHarness {
 
     /// Runs a signed transaction. On success, applies the write set.
     pub fn run_raw(&mut self, txn: SignedTransaction) -> TransactionOutput {
+        self.last_block.start(vec![txn.clone()]);
         let output = self.executor.execute_transaction(txn);
+        self.apply_output(&output);
+        output
+    }
+
+    /// Applies the write set of `output` if it is kept, and records it in the golden file and the
+    /// usage report.
+    fn apply_output(&mut self, output: &TransactionOutput) {
+        if let Some(usage) = &mut self.usage {
+            usage.record(self.executor.get_state_view(), output);
+        }
         if matches!(output.status(), TransactionStatus::Keep(_)) {
+            self.last_block
+                .record(self.executor.get_state_view(), output);
             self.executor.apply_write_set(output.write_set());
         }
-        output
+        if let Some(golden) = &mut self.golden {
+            golden.record(&self.executor.get_state_view().as_move_resolver(), output);
+        }
     }
 
     /// Runs a signed transaction. On success, applies the write set.
@@ -177,25 +352,196 @@ impl MoveHarness {
         &mut self,
         txn: SignedTransaction,
     ) -> (TransactionStatus, Vec<ContractEvent>) {
+        self.last_block.start(vec![txn.clone()]);
         let output = self.executor.execute_transaction(txn);
-        if matches!(output.status(), TransactionStatus::Keep(_)) {
-            self.executor.apply_write_set(output.write_set());
-        }
+        self.apply_output(&output);
         (output.status().to_owned(), output.events().to_owned())
     }
 
+    /// Runs a signed transaction with `hook` called on its function calls and storage accesses,
+    /// e.g. an `ExecutionTracer`. On success, applies the write set.
+    pub fn run_with_hook<H: ExecutionHook>(
+        &mut self,
+        txn: SignedTransaction,
+        hook: H,
+    ) -> (TransactionStatus, H) {
+        self.last_block.start(vec![txn.clone()]);
+        let (output, hook) = self.executor.execute_transaction_with_hook(txn, hook);
+        self.apply_output(&output);
+        (output.status().to_owned(), hook)
+    }
+
     /// Runs a block of signed transactions. On success, applies the write set.
+    ///
+    /// With `enable_ledger_checks`, panics if a node wouldn't commit the block as the harness ran
+    /// it.
     pub fn run_block(&mut self, txn_block: Vec<SignedTransaction>) -> Vec<TransactionStatus> {
+        if let Some(checker) = &mut self.ledger_checker {
+            checker
+                .sync_state(self.executor.get_state_view())
+                .expect("cannot start the ledger of the checks");
+        }
+        self.last_block.start(txn_block.clone());
+        let outputs = self.executor.execute_block(txn_block.clone()).unwrap();
         let mut result = vec![];
-        for output in self.executor.execute_block(txn_block).unwrap() {
-            if matches!(output.status(), TransactionStatus::Keep(_)) {
-                self.executor.apply_write_set(output.write_set());
-            }
+        for output in &outputs {
+            self.apply_output(output);
             result.push(output.status().to_owned())
         }
+        if let Some(checker) = &mut self.ledger_checker {
+            if let Err(e) =
+                checker.check_block(&txn_block, &outputs, self.executor.get_state_view())
+            {
+                panic!(
+                    "the ledger of a node is inconsistent with the block: {:#}",
+                    e
+                )
+            }
+        }
         result
     }
 
+    /// Runs a block of transactions `runs` times with the parallel executor, with 1, 2, 4 and 8
+    /// workers in turn (at most as many as there are CPUs), and panics unless every run has the
+    /// same outputs as sequential execution. Guards against nondeterminism, e.g. in a new native,
+    /// which only shows when transactions are executed speculatively and re-executed. The block
+    /// is then applied, as by `run_block`.
+    pub fn run_block_check_determinism(
+        &mut self,
+        txn_block: Vec<SignedTransaction>,
+        runs: usize,
+    ) -> Vec<TransactionStatus> {
+        let expected = self.executor.execute_block(txn_block.clone()).unwrap();
+        let txns: Vec<Transaction> = txn_block
+            .iter()
+            .cloned()
+            .map(Transaction::UserTransaction)
+            .collect();
+        for run in 0..runs {
+            let concurrency_level = 1 << (run % 4);
+            let outputs = self
+                .executor
+                .execute_transaction_block_with_concurrency(txns.clone(), concurrency_level)
+                .unwrap();
+            assert_eq!(outputs.len(), expected.len());
+            let mismatch = expected.iter().zip(&outputs).position(|(a, b)| a != b);
+            if let Some(index) = mismatch {
+                panic!(
+                    "run {} with {} workers has another output for transaction {} ({}) than \
+                     sequential execution: {:?} instead of {:?}",
+                    run + 1,
+                    concurrency_level,
+                    index,
+                    txn_block[index].clone().committed_hash(),
+                    outputs[index],
+                    expected[index]
+                )
+            }
+        }
+        self.run_block(txn_block)
+    }
+
+    /// Runs a block of transactions in several orderings mempool could give them, and panics if
+    /// the orderings disagree on the status of a transaction or on the state after the block, which
+    /// catches framework code depending on the order of transactions it should commute over.
+    /// The transactions of each sender keep their order. Besides the given order, the senders run
+    /// in reverse order and interleaved randomly, using `shuffle_seed`. Only the given order is
+    /// then applied, as by `run_block`.
+    ///
+    /// Gas usage depending on the order, e.g. because a transaction creates a resource another
+    /// one would otherwise create, shows up as a difference too.
+    pub fn run_block_with_conflicts(
+        &mut self,
+        txn_block: Vec<SignedTransaction>,
+        shuffle_seed: u64,
+    ) -> Vec<TransactionStatus> {
+        let outputs = self.executor.execute_block(txn_block.clone()).unwrap();
+        let expected = BlockOutcome::new(&txn_block, &outputs);
+        let mut rng = StdRng::seed_from_u64(shuffle_seed);
+        for (index, ordering) in orderings(&txn_block, &mut rng).into_iter().enumerate() {
+            let outputs = self.executor.execute_block(ordering.clone()).unwrap();
+            if let Some(difference) = expected.difference(&BlockOutcome::new(&ordering, &outputs)) {
+                panic!(
+                    "the block has another outcome in ordering {} (shuffle seed {}): {}",
+                    index + 1,
+                    shuffle_seed,
+                    difference
+                )
+            }
+        }
+        self.run_block(txn_block)
+    }
+
+    /// Checks after each `run_block` that a node, executing the block on `AptosDB`, commits the
+    /// transaction infos, transaction accumulator and state tree the outputs and state of the
+    /// harness give, see `LedgerChecker`. This is slow, so is opt-in.
+    ///
+    /// Blocks run with `run_block_with_metadata` and single transactions are not checked.
+    pub fn enable_ledger_checks(&mut self) {
+        self.ledger_checker = Some(
+            LedgerChecker::new(self.executor.get_state_view())
+                .expect("cannot start the ledger of the checks"),
+        );
+    }
+
+    /// Panics if the state breaks an invariant which must hold whatever transactions failed, e.g.
+    /// table items no resource holds or modules missing from the package registry after a failed
+    /// `init_module`. See `state_consistency::check_state` for the invariants checked.
+    pub fn assert_state_consistency(&self) {
+        let inconsistencies = check_state(self.executor.get_state_view());
+        if !inconsistencies.is_empty() {
+            panic!(
+                "inconsistent state:\n{}",
+                inconsistencies
+                    .iter()
+                    .map(|inconsistency| format!("  {}", inconsistency))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        }
+    }
+
+    /// Makes the prologue or the epilogue of the next transaction created by the harness fail,
+    /// whichever way it is run. Faults on the balance change the state right away, the others
+    /// only change the transaction.
+    ///
+    /// A transaction failing its prologue doesn't use up its sequence number, so the one created
+    /// after it gets the same.
+    pub fn inject_fault(&mut self, fault: TransactionFault) {
+        self.fault = Some(fault);
+    }
+
+    /// Makes the native function `address::module::function` behave as `fault` until
+    /// `clear_native_faults` is called or the harness is dropped, e.g. to cover how framework
+    /// modules handle natives failing in ways tests can't otherwise trigger.
+    ///
+    /// Failpoints are global to the process: harnesses making natives fail wait for each other,
+    /// but tests running in the meantime without making natives fail see the faults too.
+    #[cfg(feature = "failpoints")]
+    pub fn fail_native(
+        &mut self,
+        address: AccountAddress,
+        module: &str,
+        function: &str,
+        fault: NativeFault,
+    ) {
+        self.native_faults.get_or_insert_with(FailScenario::setup);
+        let name = format!(
+            "native::{}::{}::{}",
+            address.to_hex_literal(),
+            module,
+            function
+        );
+        fail::cfg(&name, &fault.action()).unwrap();
+    }
+
+    /// Restores the natives made to fail with `fail_native`.
+    pub fn clear_native_faults(&mut self) {
+        if let Some(scenario) = self.native_faults.take() {
+            scenario.teardown();
+        }
+    }
+
     /// Creates a transaction, based on provided payload.
     pub fn create_transaction_payload(
         &mut self,
@@ -205,14 +551,60 @@ impl MoveHarness {
         let on_chain_seq_no = self.sequence_number(account.address());
         let seq_no_ref = self.txn_seq_no.get_mut(account.address()).unwrap();
         let seq_no = std::cmp::max(on_chain_seq_no, *seq_no_ref);
-        *seq_no_ref = seq_no + 1;
-        account
+        let fault = self.fault.take();
+        if fault.and_then(|fault| fault.discard_status()).is_none() {
+            *seq_no_ref = seq_no + 1;
+        }
+        let max_fee = self.max_transaction_fee();
+        let builder = account
             .transaction()
             .sequence_number(seq_no)
-            .max_gas_amount(2_000_000)
+            .max_gas_amount(DEFAULT_MAX_GAS_AMOUNT)
             .gas_unit_price(self.default_gas_unit_price)
-            .payload(payload)
-            .sign()
+            .payload(payload);
+        let builder = match fault {
+            None => builder,
+            Some(TransactionFault::BadChainId) => builder.chain_id(ChainId::new(u8::MAX)),
+            Some(TransactionFault::Expired) => builder.ttl(0),
+            Some(TransactionFault::SequenceNumberTooOld) => builder.sequence_number(
+                seq_no
+                    .checked_sub(1)
+                    .expect("the sender has no sequence number to reuse"),
+            ),
+            Some(TransactionFault::SequenceNumberTooNew) => builder.sequence_number(seq_no + 1),
+            Some(TransactionFault::InsufficientBalance) => {
+                assert!(max_fee > 0, "transactions without a fee can't lack balance");
+                self.set_balance(account.address(), max_fee - 1);
+                builder
+            },
+            Some(TransactionFault::EpilogueInsufficientBalance) => {
+                self.set_balance(account.address(), max_fee);
+                builder
+            },
+        };
+        builder.sign()
+    }
+
+    /// The most APT the transactions created by the harness can be charged for gas.
+    pub fn max_transaction_fee(&self) -> u64 {
+        DEFAULT_MAX_GAS_AMOUNT * self.default_gas_unit_price
+    }
+
+    /// Overwrites the APT balance of `addr`, which must have an APT coin store.
+    fn set_balance(&mut self, addr: &AccountAddress, amount: u64) {
+        let coin_store = self
+            .read_resource::<CoinStoreResource>(addr, CoinStoreResource::struct_tag())
+            .expect("the account has no APT coin store");
+        self.set_resource(
+            *addr,
+            CoinStoreResource::struct_tag(),
+            &CoinStoreResource::new(
+                amount,
+                coin_store.frozen(),
+                coin_store.deposit_events().clone(),
+                coin_store.withdraw_events().clone(),
+            ),
+        );
     }
 
     /// Runs a transaction, based on provided payload. If the transaction succeeds, any generated
@@ -359,6 +751,37 @@ impl MoveHarness {
         self.run(txn)
     }
 
+    /// Upgrades the framework to the packages of `bundle`, e.g.
+    /// `aptos_cached_packages::head_release_bundle()` for the current one, panicking if any of
+    /// them can't be published.
+    pub fn upgrade_framework(&mut self, bundle: &ReleaseBundle) {
+        // The packages are too large for the default transaction size
+        self.increase_transaction_size();
+        // The bundle is ordered bottom up, as packages may depend on each other
+        for package in &bundle.packages {
+            let address = *package
+                .compiled_module_at(0)
+                .expect("framework modules must deserialize")
+                .self_id()
+                .address();
+            let account = self.new_account_at(address);
+            let txn = self.create_transaction_payload(
+                &account,
+                aptos_stdlib::code_publish_package_txn(
+                    bcs::to_bytes(package.package_metadata()).expect("PackageMetadata has BCS"),
+                    package.code().into_iter().map(<[u8]>::to_vec).collect(),
+                ),
+            );
+            let status = self.run(txn);
+            assert!(
+                matches!(status, TransactionStatus::Keep(ExecutionStatus::Success)),
+                "cannot upgrade `{}`: {:?}",
+                package.name(),
+                status
+            );
+        }
+    }
+
     pub fn fast_forward(&mut self, seconds: u64) {
         let current_time = self.executor.get_block_time();
         self.executor
@@ -403,6 +826,15 @@ impl MoveHarness {
         })
     }
 
+    /// Reads the value of an aggregator, e.g. a field of a resource read with `read_resource`.
+    /// Returns `None` if the aggregator doesn't exist.
+    pub fn read_aggregator_value(&self, aggregator: &AggregatorHandle) -> Option<u128> {
+        let state_key =
+            StateKey::table_item(TableHandle(aggregator.handle), aggregator.key.to_vec());
+        self.read_state_value(&state_key)
+            .map(|bytes| bcs::from_bytes(&bytes).expect("aggregator values are u128"))
+    }
+
     /// Reads the raw, serialized data of a resource.
     pub fn read_resource_raw(
         &self,
@@ -427,6 +859,31 @@ impl MoveHarness {
         )
     }
 
+    /// Reads a resource as JSON, in the format of the REST API, decoded with the modules
+    /// published in the harness, so that tests don't need a Rust struct mirroring it.
+    pub fn read_resource_json(
+        &self,
+        addr: &AccountAddress,
+        struct_tag: StructTag,
+    ) -> Option<serde_json::Value> {
+        let bytes = self.read_resource_raw(addr, struct_tag.clone())?;
+        let accessor = TypeAccessor::from_local_modules(
+            &[TypeTag::Struct(Box::new(struct_tag.clone()))],
+            |module_id| {
+                let path = AccessPath::code_access_path(module_id.clone());
+                let bytes = self.read_state_value(&StateKey::access_path(path))?;
+                let module =
+                    CompiledModule::deserialize(&bytes).expect("published modules are valid");
+                Some(MoveModule::from(module))
+            },
+        );
+        Some(
+            accessor
+                .decode_struct(&struct_tag, &bytes)
+                .unwrap_or_else(|err| panic!("Failed to decode {}: {:#}", struct_tag, err)),
+        )
+    }
+
     pub fn read_resource_group(
         &self,
         addr: &AccountAddress,
@@ -469,6 +926,36 @@ impl MoveHarness {
             .write_state_value(state_key, bcs::to_bytes(data).unwrap());
     }
 
+    /// Loads state exported from a real network with `aptos-db-tool export-state`, e.g. to test
+    /// against deployed protocols. Overwrites any existing value of the same keys.
+    pub fn import_state(&mut self, export: &StateExport) {
+        self.executor.apply_write_set(&export.to_write_set());
+    }
+
+    /// Writes what it takes to run the last transaction or block again to `dir`: the state before
+    /// it, the transactions and the seed of the harness. Read it back with `replay_repro_bundle`,
+    /// e.g. to debug a flaky failure without re-running the whole test.
+    pub fn export_repro_bundle(&self, dir: &Path) -> anyhow::Result<()> {
+        ReproBundle {
+            seed: self.seed,
+            state: self.last_block.state_before(self.executor.get_state_view()),
+            transactions: self.last_block.transactions().to_vec(),
+        }
+        .write_to(dir)
+    }
+
+    /// Runs the block of a bundle written by `export_repro_bundle` on the state it was written
+    /// with, returning the harness, seeded like the one which wrote it, to inspect the state after.
+    pub fn replay_repro_bundle(
+        dir: &Path,
+    ) -> anyhow::Result<(MoveHarness, Vec<TransactionStatus>)> {
+        let bundle = ReproBundle::read_from(dir)?;
+        let mut h = Self::with_executor(FakeExecutor::no_genesis()).with_seed(bundle.seed);
+        h.import_state(&bundle.state);
+        let statuses = h.run_block(bundle.transactions);
+        Ok((h, statuses))
+    }
+
     /// Enables features
     pub fn enable_features(&mut self, enabled: Vec<FeatureFlag>, disabled: Vec<FeatureFlag>) {
         let acc = self.aptos_framework_account();
@@ -517,6 +1004,13 @@ impl MoveHarness {
             ]);
     }
 
+    /// Forgets the transactions created for `addr` which didn't run, so that the next one created
+    /// gets the sequence number on chain again.
+    pub fn reset_sequence_number(&mut self, addr: &AccountAddress) {
+        let seq_no = self.sequence_number(addr);
+        self.txn_seq_no.insert(*addr, seq_no);
+    }
+
     pub fn sequence_number(&self, addr: &AccountAddress) -> u64 {
         self.read_resource::<AccountResource>(addr, AccountResource::struct_tag())
             .unwrap()
@@ -555,8 +1049,26 @@ impl Default for MoveHarness {
     }
 }
 
+impl Drop for MoveHarness {
+    fn drop(&mut self) {
+        if let Some(usage) = self.usage.take() {
+            usage.finish();
+        }
+        if std::thread::panicking() {
+            eprintln!(
+                "MoveHarness seed: {} (re-run with {}={})",
+                self.seed, SEED_ENV_VAR, self.seed
+            );
+        }
+    }
+}
+
 /// Enables golden files for the given harness. The golden file will be stored side-by-side
 /// with the data directory of a Rust source, named after the test function.
+///
+/// Every transaction the harness runs from then on has its decoded output recorded, and the test
+/// fails if the recording differs from the checked-in file. Run the test with
+/// `UPDATE_GOLDENFILES=1` to create or update the file.
 #[macro_export]
 macro_rules! enable_golden {
     ($h:expr) => {
@@ -577,8 +1089,7 @@ impl MoveHarness {
         // The result of the `current_function` macro gives us the fully qualified
         // We only want the trailing simple name.
         let fun = function_macro_value.split("::").last().unwrap();
-        self.executor
-            .set_golden_file_at(&path.display().to_string(), fun)
+        self.golden = Some(GoldenOutputs::new(path, fun))
     }
 }
 
@@ -630,3 +1141,47 @@ macro_rules! assert_move_abort {
         });
     }};
 }
+
+/// The type of each event of `events`, in the order each type was first emitted, with how many
+/// events of the type there are.
+pub fn event_type_counts(events: &[ContractEvent]) -> Vec<(TypeTag, usize)> {
+    let mut counts: Vec<(TypeTag, usize)> = vec![];
+    for event in events {
+        match counts.iter_mut().find(|(ty, _)| ty == event.type_tag()) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((event.type_tag().clone(), 1)),
+        }
+    }
+    counts
+}
+
+/// Internal function to support the `assert_events` macro.
+#[track_caller]
+pub fn internal_assert_events(events: &[ContractEvent], expected: &[(&str, usize)]) {
+    let actual: Vec<(String, usize)> = event_type_counts(events)
+        .into_iter()
+        .map(|(ty, count)| (ty.to_string(), count))
+        .collect();
+    // Parsed and formatted again, so that e.g. `0x0000000000000001::...` matches `0x1::...`
+    let expected: Vec<(String, usize)> = expected
+        .iter()
+        .map(|(ty, count)| {
+            let ty = ty
+                .parse::<TypeTag>()
+                .unwrap_or_else(|e| panic!("Invalid event type {}: {}", ty, e));
+            (ty.to_string(), *count)
+        })
+        .collect();
+    assert_eq!(actual, expected, "Unexpected event types or counts");
+}
+
+/// Helper to assert the types of the events a transaction emitted, in the order each type was
+/// first emitted, and how many of each, e.g.
+/// `assert_events!(events, [("0x1::coin::WithdrawEvent", 1), ("0xcafe::test::Created", 2)])`
+/// with the events returned by `run_with_events`.
+#[macro_export]
+macro_rules! assert_events {
+    ($events:expr, [$(($ty:expr, $count:expr)),* $(,)?]) => {{
+        $crate::internal_assert_events(&$events, &[$(($ty, $count)),*])
+    }};
+}
@@ -0,0 +1,443 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, format_err, Result};
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher, TransactionAccumulatorHasher},
+    HashValue,
+};
+use aptos_db::AptosDB;
+use aptos_executor::block_executor::BlockExecutor;
+use aptos_executor_test_helpers::bootstrap_genesis;
+use aptos_executor_types::BlockExecutorTrait;
+use aptos_gas::{ChangeSetConfigs, LATEST_GAS_FEATURE_VERSION};
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_scratchpad::{FrozenSparseMerkleTree, ProofRead, SparseMerkleTree};
+use aptos_storage_interface::DbReaderWriter;
+use aptos_temppath::TempPath;
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    account_config::{new_block_event_key, NewBlockEvent, NewEpochEvent, CORE_CODE_ADDRESS},
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    contract_event::ContractEvent,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    on_chain_config::{new_epoch_event_key, ConfigurationResource},
+    proof::{accumulator::InMemoryAccumulator, SparseMerkleProofExt},
+    state_store::{
+        state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
+    },
+    transaction::{
+        ChangeSet, ExecutionStatus, SignedTransaction, Transaction, TransactionInfo,
+        TransactionOutput, TransactionStatus, Version, WriteSetPayload,
+    },
+    write_set::{TransactionWrite, WriteOp, WriteSet, WriteSetMut},
+};
+use aptos_vm::AptosVM;
+use move_core_types::{
+    language_storage::TypeTag,
+    move_resource::{MoveResource, MoveStructType},
+};
+use std::collections::HashMap;
+
+/// Checks the blocks run by a [`MoveHarness`] against a node: the blocks are executed again by
+/// the block executor on an `AptosDB`, and committed with a ledger info signing the transaction
+/// accumulator the checker computed from the outputs of the harness. After each block, the
+/// checker panics unless
+/// - the state the harness applied holds what the write sets of the block wrote,
+/// - the node has the same transaction infos, i.e. the same statuses, gas, write sets, events
+///   and state checkpoint, as the checker computed for the outputs of the harness,
+/// - the transaction accumulator of the node proves these transaction infos against the ledger
+///   info, and its state tree proves the values the block wrote against the state root of the
+///   harness.
+///
+/// See `MoveHarness::enable_ledger_checks`.
+///
+/// State written outside of blocks, e.g. by `MoveHarness::new_account_at`, isn't written by
+/// transactions, so whenever the harness writes some, the checker starts a new ledger with a
+/// genesis writing the state of the harness.
+///
+/// [`MoveHarness`]: crate::MoveHarness
+pub struct LedgerChecker {
+    ledger: Ledger,
+    /// The state the harness had after the last block, as the node stores it.
+    state: HashMap<StateKey, StateValue>,
+}
+
+/// The node a [`LedgerChecker`] runs the blocks on, and what it expects the node to commit.
+struct Ledger {
+    executor: BlockExecutor<AptosVM, Transaction>,
+    db: DbReaderWriter,
+    /// Holds the DB, and deletes it when the ledger is dropped.
+    _db_dir: TempPath,
+    /// The transaction accumulator, computed from the transaction infos of the harness.
+    accumulator: InMemoryAccumulator<TransactionAccumulatorHasher>,
+    /// The state tree, computed from the state of the harness.
+    state_tree: FrozenSparseMerkleTree<StateValue>,
+}
+
+impl LedgerChecker {
+    pub fn new(store: &FakeDataStore) -> Result<Self> {
+        let state = legacy_state(store);
+        Ok(Self {
+            ledger: Ledger::start(&state)?,
+            state,
+        })
+    }
+
+    /// Takes the state of `store` as the state before the next block, starting a new ledger if
+    /// it was written outside of blocks.
+    pub fn sync_state(&mut self, store: &FakeDataStore) -> Result<()> {
+        if !self.holds_state_of(store) {
+            *self = Self::new(store)?;
+        }
+        Ok(())
+    }
+
+    fn holds_state_of(&self, store: &FakeDataStore) -> bool {
+        store.inner().len() == self.state.len()
+            && store.inner().iter().all(|(key, bytes)| {
+                self.state
+                    .get(key)
+                    .map_or(false, |value| value.bytes() == bytes.as_slice())
+            })
+    }
+
+    /// Runs `txns` on the node, and checks its ledger against `outputs`, the outputs the harness
+    /// had for `txns`, and `store`, the state after the harness applied them.
+    pub fn check_block(
+        &mut self,
+        txns: &[SignedTransaction],
+        outputs: &[TransactionOutput],
+        store: &FakeDataStore,
+    ) -> Result<()> {
+        ensure!(
+            txns.len() == outputs.len(),
+            "{} transactions but {} outputs",
+            txns.len(),
+            outputs.len()
+        );
+
+        // The transactions and transaction infos the node should commit, as the harness ran them
+        let mut written = HashMap::new();
+        let mut committed = vec![];
+        for (txn, output) in txns.iter().zip(outputs) {
+            let status = match output.status() {
+                TransactionStatus::Keep(status) => status.clone(),
+                _ => continue,
+            };
+            written.extend(
+                output
+                    .write_set()
+                    .iter()
+                    .map(|(key, op)| (key.clone(), op.as_state_value())),
+            );
+            let reconfigures = output
+                .events()
+                .iter()
+                .any(|event| *event.key() == new_epoch_event_key());
+            let state_checkpoint_hash = if reconfigures {
+                Some(self.ledger.update_state(&written)?)
+            } else {
+                None
+            };
+            let txn = Transaction::UserTransaction(txn.clone());
+            let info = TransactionInfo::new(
+                txn.hash(),
+                output.write_set().hash(),
+                event_root_hash(output.events()),
+                state_checkpoint_hash,
+                output.gas_used(),
+                status,
+            );
+            committed.push((txn, info));
+            // The executor retries what follows a reconfiguration in the next block, including
+            // the checkpoint it appends
+            if reconfigures {
+                break;
+            }
+        }
+        let block_id = HashValue::sha3_256_of(&self.ledger.accumulator.num_leaves().to_be_bytes());
+        if !matches!(committed.last(), Some((_, info)) if info.is_state_checkpoint()) {
+            let checkpoint = Transaction::StateCheckpoint(block_id);
+            let info = TransactionInfo::new(
+                checkpoint.hash(),
+                WriteSet::default().hash(),
+                event_root_hash(&[]),
+                Some(self.ledger.update_state(&written)?),
+                0,
+                ExecutionStatus::Success,
+            );
+            committed.push((checkpoint, info));
+        }
+
+        // The store must hold what the block wrote, and nothing else must have changed
+        for (key, value) in &written {
+            let in_store = store.inner().get(key);
+            ensure!(
+                in_store.map(Vec::as_slice) == value.as_ref().map(StateValue::bytes),
+                "{:?} is {:?} in the store but {:?} in the block outputs",
+                key,
+                in_store,
+                value
+            );
+            match value {
+                Some(value) => self.state.insert(key.clone(), value.clone()),
+                None => self.state.remove(key),
+            };
+        }
+        ensure!(
+            self.holds_state_of(store),
+            "the store changed state the block outputs didn't write"
+        );
+
+        let block = txns
+            .iter()
+            .cloned()
+            .map(Transaction::UserTransaction)
+            .chain([Transaction::StateCheckpoint(block_id)])
+            .collect();
+        let version = self.ledger.commit(block_id, block, committed)?;
+        self.ledger.check_state(&written, version)
+    }
+}
+
+impl Ledger {
+    /// Starts a ledger with a genesis writing `state`.
+    fn start(state: &HashMap<StateKey, StateValue>) -> Result<Self> {
+        let write_set = WriteSetMut::new(
+            state
+                .iter()
+                .map(|(key, value)| (key.clone(), WriteOp::Creation(value.bytes().to_vec()))),
+        )
+        .freeze()?;
+        let epoch = state
+            .get(&StateKey::access_path(AccessPath::new(
+                CORE_CODE_ADDRESS,
+                ConfigurationResource::resource_path(),
+            )))
+            .map(|value| bcs::from_bytes::<ConfigurationResource>(value.bytes()))
+            .transpose()?
+            .ok_or_else(|| format_err!("the harness has no ConfigurationResource"))?
+            .epoch();
+        // The events the VM requires from a genesis
+        let events = vec![
+            ContractEvent::new(
+                new_block_event_key(),
+                0,
+                TypeTag::Struct(Box::new(NewBlockEvent::struct_tag())),
+                bcs::to_bytes(&NewBlockEvent::new(
+                    AccountAddress::ZERO,
+                    epoch,
+                    0,
+                    0,
+                    vec![],
+                    AccountAddress::ZERO,
+                    vec![],
+                    0,
+                ))?,
+            ),
+            ContractEvent::new(
+                new_epoch_event_key(),
+                0,
+                TypeTag::Struct(Box::new(NewEpochEvent::struct_tag())),
+                bcs::to_bytes(&epoch)?,
+            ),
+        ];
+        let change_set = ChangeSet::new(
+            write_set,
+            events,
+            &ChangeSetConfigs::unlimited_at_gas_feature_version(LATEST_GAS_FEATURE_VERSION),
+        )
+        .map_err(|e| format_err!("invalid genesis change set: {:?}", e))?;
+        let genesis = Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set.clone()));
+
+        let db_dir = TempPath::new();
+        let db = DbReaderWriter::new(AptosDB::new_for_test(&db_dir));
+        bootstrap_genesis::<AptosVM>(&db, &genesis)?;
+
+        let mut ledger = Self {
+            executor: BlockExecutor::new(db.clone()),
+            db,
+            _db_dir: db_dir,
+            accumulator: InMemoryAccumulator::new_empty(),
+            state_tree: SparseMerkleTree::new_empty().freeze(),
+        };
+        let updates = state
+            .iter()
+            .map(|(key, value)| (key.clone(), Some(value.clone())))
+            .collect();
+        let info = TransactionInfo::new(
+            genesis.hash(),
+            change_set.write_set().hash(),
+            event_root_hash(change_set.events()),
+            Some(ledger.update_state(&updates)?),
+            0,
+            ExecutionStatus::Success,
+        );
+        ledger.accumulator = ledger.accumulator.append(&[info.hash()]);
+        let ledger_info = ledger.db.reader.get_latest_ledger_info()?;
+        ensure!(
+            ledger_info.ledger_info().transaction_accumulator_hash()
+                == ledger.accumulator.root_hash(),
+            "the genesis of the node has another transaction info than the state of the harness: \
+             {:?}",
+            info
+        );
+        Ok(ledger)
+    }
+
+    /// Applies `updates` to the state tree, and returns its new root.
+    fn update_state(
+        &mut self,
+        updates: &HashMap<StateKey, Option<StateValue>>,
+    ) -> Result<HashValue> {
+        self.state_tree = self
+            .state_tree
+            .batch_update(
+                updates
+                    .iter()
+                    .map(|(key, value)| (key.hash(), value.as_ref()))
+                    .collect(),
+                StateStorageUsage::zero(),
+                &InMemoryProofs,
+            )
+            .map_err(|e| format_err!("cannot update the state tree: {}", e))?;
+        Ok(self.state_tree.root_hash())
+    }
+
+    /// Executes `block` on the node, commits it, and checks the node committed the transactions
+    /// and transaction infos of `expected`. Returns the version of the last.
+    fn commit(
+        &mut self,
+        block_id: HashValue,
+        block: Vec<Transaction>,
+        expected: Vec<(Transaction, TransactionInfo)>,
+    ) -> Result<Version> {
+        let (txns, infos): (Vec<_>, Vec<_>) = expected.into_iter().unzip();
+        let first_version = self.accumulator.num_leaves();
+        let result = self
+            .executor
+            .execute_block((block_id, block), self.executor.committed_block_id())?;
+
+        // Commits with the root of the node, so the transaction infos it computed can be read
+        // even if they are not the ones of the harness
+        let epoch = self
+            .db
+            .reader
+            .get_latest_ledger_info()?
+            .ledger_info()
+            .next_block_epoch();
+        let ledger_info = LedgerInfo::new(
+            BlockInfo::new(
+                epoch,
+                0,
+                block_id,
+                result.root_hash(),
+                result.version(),
+                0,
+                result.epoch_state().clone(),
+            ),
+            HashValue::zero(),
+        );
+        self.executor.commit_blocks(
+            vec![block_id],
+            LedgerInfoWithSignatures::new(ledger_info.clone(), AggregateSignature::empty()),
+        )?;
+
+        let committed = self.db.reader.get_transactions(
+            first_version,
+            result.version() + 1 - first_version,
+            result.version(),
+            false,
+        )?;
+        committed.verify(&ledger_info, Some(first_version))?;
+        for (index, (txn, info)) in txns.iter().zip(&infos).enumerate() {
+            let on_node = committed.transactions.get(index);
+            ensure!(
+                on_node == Some(txn),
+                "the node committed {:?} at version {} instead of {:?}",
+                on_node,
+                first_version + index as u64,
+                txn
+            );
+            let info_on_node = &committed.proof.transaction_infos[index];
+            ensure!(
+                info_on_node == info,
+                "{:?} at version {} has {:?} on the node but {:?} in the harness",
+                txn,
+                first_version + index as u64,
+                info_on_node,
+                info
+            );
+        }
+        ensure!(
+            committed.transactions.len() == txns.len(),
+            "the node committed {} transactions but the harness {}",
+            committed.transactions.len(),
+            txns.len()
+        );
+
+        self.accumulator = self
+            .accumulator
+            .append(&infos.iter().map(CryptoHash::hash).collect::<Vec<_>>());
+        ensure!(
+            self.accumulator.root_hash() == ledger_info.transaction_accumulator_hash(),
+            "transaction accumulator root {:x} in the harness but {:x} on the node",
+            self.accumulator.root_hash(),
+            ledger_info.transaction_accumulator_hash()
+        );
+        Ok(result.version())
+    }
+
+    /// Checks the state tree of the node proves the values in `written` at `version` against the
+    /// state root of the harness.
+    fn check_state(
+        &self,
+        written: &HashMap<StateKey, Option<StateValue>>,
+        version: Version,
+    ) -> Result<()> {
+        let root = self.state_tree.root_hash();
+        for (key, value) in written {
+            let (on_node, proof) = self
+                .db
+                .reader
+                .get_state_value_with_proof_by_version(key, version)?;
+            ensure!(
+                on_node == *value,
+                "{:?} is {:?} on the node but {:?} in the block outputs",
+                key,
+                on_node,
+                value
+            );
+            proof
+                .verify(root, key.hash(), value.as_ref())
+                .map_err(|e| format_err!("{:?} isn't proven by the state root: {}", key, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// The state of `store`, with values as a genesis writing them would store them.
+fn legacy_state(store: &FakeDataStore) -> HashMap<StateKey, StateValue> {
+    store
+        .inner()
+        .iter()
+        .map(|(key, bytes)| (key.clone(), StateValue::new_legacy(bytes.clone())))
+        .collect()
+}
+
+fn event_root_hash(events: &[ContractEvent]) -> HashValue {
+    let hashes = events.iter().map(CryptoHash::hash).collect::<Vec<_>>();
+    InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&hashes).root_hash()
+}
+
+/// The state trees are fully in memory, so never ask for proofs.
+struct InMemoryProofs;
+
+impl ProofRead for InMemoryProofs {
+    fn get_proof(&self, _key: HashValue) -> Option<&SparseMerkleProofExt> {
+        None
+    }
+}
@@ -2,16 +2,32 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod aggregator;
+pub mod bench;
+pub mod feature_matrix;
+pub mod golden;
 pub mod harness;
+pub mod ledger_check;
+mod ordering;
+pub mod policy_matrix;
+pub mod publish_fuzz;
+pub mod repro;
 pub mod stake;
+pub mod state_consistency;
 pub mod transaction_fee;
+pub mod usage_report;
 
 use anyhow::bail;
 use aptos_framework::UPGRADE_POLICY_CUSTOM_FIELD;
+pub use bench::BenchHarness;
+pub use feature_matrix::*;
 pub use harness::*;
 use move_package::{package_hooks::PackageHooks, source_package::parsed_manifest::CustomDepInfo};
 use move_symbol_pool::Symbol;
+pub use policy_matrix::{run_policy_matrix, PolicyMatrix, PolicyOutcome};
+pub use publish_fuzz::{run_publish_fuzz, PublishFuzzConfig};
+pub use repro::ReproBundle;
 pub use stake::*;
+pub use usage_report::{UsageReport, USAGE_REPORT_ENV_VAR};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,115 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_crypto::HashValue;
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::state_key::StateKey,
+    transaction::{SignedTransaction, TransactionOutput, TransactionStatus},
+};
+use rand::{rngs::StdRng, Rng};
+use std::collections::{HashMap, VecDeque};
+
+/// How many shuffled orderings of a block `MoveHarness::run_block_with_conflicts` runs, besides
+/// the given order and the one with the senders reversed.
+pub(crate) const SHUFFLED_ORDERINGS: usize = 3;
+
+/// What running a block came to, regardless of the order of its transactions.
+pub(crate) struct BlockOutcome {
+    statuses: HashMap<HashValue, TransactionStatus>,
+    /// The value of each key the block wrote, `None` if it deleted it.
+    state: HashMap<StateKey, Option<Vec<u8>>>,
+}
+
+impl BlockOutcome {
+    pub fn new(txns: &[SignedTransaction], outputs: &[TransactionOutput]) -> Self {
+        let mut statuses = HashMap::new();
+        let mut state = HashMap::new();
+        for (txn, output) in txns.iter().zip(outputs) {
+            statuses.insert(txn.clone().committed_hash(), output.status().clone());
+            if matches!(output.status(), TransactionStatus::Keep(_)) {
+                for (key, op) in output.write_set() {
+                    state.insert(key.clone(), op.bytes().map(|bytes| bytes.to_vec()));
+                }
+            }
+        }
+        Self { statuses, state }
+    }
+
+    /// Describes a difference between the outcomes, or returns `None` if they're the same.
+    pub fn difference(&self, other: &BlockOutcome) -> Option<String> {
+        for (hash, status) in &self.statuses {
+            let other_status = &other.statuses[hash];
+            if status != other_status {
+                return Some(format!(
+                    "transaction {} is {:?} in the given order, but {:?} in this one",
+                    hash, status, other_status
+                ));
+            }
+        }
+        let keys = self.state.keys().chain(other.state.keys());
+        for key in keys {
+            let value = self.state.get(key);
+            let other_value = other.state.get(key);
+            if value != other_value {
+                return Some(format!(
+                    "{:?} is {} after the given order, but {} after this one",
+                    key,
+                    describe_value(value),
+                    describe_value(other_value)
+                ));
+            }
+        }
+        None
+    }
+}
+
+fn describe_value(value: Option<&Option<Vec<u8>>>) -> String {
+    match value {
+        Some(Some(bytes)) => format!("0x{}", hex::encode(bytes)),
+        Some(None) => "deleted".to_string(),
+        None => "untouched".to_string(),
+    }
+}
+
+/// Orderings of `txns` other than the given one, as mempool could order them: the transactions of
+/// each sender keep their order, as their sequence numbers require. The first one runs the senders
+/// in reverse order, the others interleave the senders randomly.
+pub(crate) fn orderings(
+    txns: &[SignedTransaction],
+    rng: &mut StdRng,
+) -> Vec<Vec<SignedTransaction>> {
+    let mut senders: Vec<AccountAddress> = vec![];
+    let mut by_sender: HashMap<AccountAddress, VecDeque<SignedTransaction>> = HashMap::new();
+    for txn in txns {
+        if !by_sender.contains_key(&txn.sender()) {
+            senders.push(txn.sender());
+        }
+        by_sender
+            .entry(txn.sender())
+            .or_default()
+            .push_back(txn.clone());
+    }
+
+    let reversed = senders
+        .iter()
+        .rev()
+        .flat_map(|sender| by_sender[sender].iter().cloned())
+        .collect();
+    let mut orderings = vec![reversed];
+    for _ in 0..SHUFFLED_ORDERINGS {
+        let mut pending = by_sender.clone();
+        let mut remaining = senders.clone();
+        let mut ordering = Vec::with_capacity(txns.len());
+        while !remaining.is_empty() {
+            let index = rng.gen_range(0, remaining.len());
+            let queue = pending.get_mut(&remaining[index]).unwrap();
+            ordering.push(queue.pop_front().unwrap());
+            if queue.is_empty() {
+                remaining.swap_remove(index);
+            }
+        }
+        orderings.push(ordering);
+    }
+    orderings
+}
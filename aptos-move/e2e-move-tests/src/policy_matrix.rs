@@ -0,0 +1,106 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::harness::MoveHarness;
+use aptos_package_builder::PolicyTransition;
+use aptos_types::transaction::{ExecutionStatus, TransactionStatus};
+use std::fmt;
+
+/// What publishing the packages of a [`PolicyTransition`] came to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PolicyOutcome {
+    Allowed,
+    /// Publishing the initial package failed for the given reason, so the upgrade wasn't tried.
+    InitialDenied(String),
+    UpgradeDenied(String),
+}
+
+impl fmt::Display for PolicyOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyOutcome::Allowed => write!(f, "allowed"),
+            PolicyOutcome::InitialDenied(reason) => write!(f, "initial publish denied: {}", reason),
+            PolicyOutcome::UpgradeDenied(reason) => write!(f, "upgrade denied: {}", reason),
+        }
+    }
+}
+
+/// The outcome of each transition between upgrade policies. Displays as a table with one
+/// transition per line, e.g. `compatible -> immutable (compatible): allowed`.
+#[derive(Clone, Debug)]
+pub struct PolicyMatrix(pub Vec<(PolicyTransition, PolicyOutcome)>);
+
+impl PolicyMatrix {
+    pub fn outcome(&self, transition: &PolicyTransition) -> Option<&PolicyOutcome> {
+        self.0
+            .iter()
+            .find(|(t, _)| t == transition)
+            .map(|(_, outcome)| outcome)
+    }
+}
+
+impl fmt::Display for PolicyMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (transition, outcome) in &self.0 {
+            writeln!(f, "{}: {}", transition, outcome)?;
+        }
+        Ok(())
+    }
+}
+
+/// Publishes the packages of every [`PolicyTransition`], each under a new account, and records
+/// whether the upgrade was allowed.
+pub fn run_policy_matrix(harness: &mut MoveHarness) -> PolicyMatrix {
+    let outcomes = PolicyTransition::all()
+        .into_iter()
+        .map(|transition| {
+            let outcome = run_policy_transition(harness, &transition);
+            (transition, outcome)
+        })
+        .collect();
+    PolicyMatrix(outcomes)
+}
+
+fn run_policy_transition(
+    harness: &mut MoveHarness,
+    transition: &PolicyTransition,
+) -> PolicyOutcome {
+    let account = harness.new_account_with_key_pair();
+    let address = account.address().to_hex_literal();
+
+    let initial = transition
+        .initial_package(&address)
+        .write_to_temp()
+        .expect("writing the initial package must succeed");
+    let status = harness.publish_package(&account, initial.path());
+    if let Some(reason) = denial_reason(&status) {
+        return PolicyOutcome::InitialDenied(reason);
+    }
+
+    let upgrade = transition
+        .upgrade_package(&address)
+        .write_to_temp()
+        .expect("writing the upgrade package must succeed");
+    let status = harness.publish_package(&account, upgrade.path());
+    match denial_reason(&status) {
+        Some(reason) => PolicyOutcome::UpgradeDenied(reason),
+        None => PolicyOutcome::Allowed,
+    }
+}
+
+/// Why a publishing transaction failed, or `None` if it succeeded.
+fn denial_reason(status: &TransactionStatus) -> Option<String> {
+    match status {
+        TransactionStatus::Keep(ExecutionStatus::Success) => None,
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            info: Some(info), ..
+        }) => Some(info.reason_name.clone()),
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort { code, .. }) => {
+            Some(format!("abort {:#x}", code))
+        },
+        TransactionStatus::Keep(ExecutionStatus::MiscellaneousError(Some(code))) => {
+            Some(format!("{:?}", code))
+        },
+        status => Some(format!("{:?}", status)),
+    }
+}
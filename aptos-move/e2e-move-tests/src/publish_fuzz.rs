@@ -0,0 +1,297 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::harness::MoveHarness;
+use aptos::move_tool::MemberId;
+use aptos_cached_packages::aptos_stdlib;
+use aptos_framework::{natives::code::UpgradePolicy, BuildOptions, BuiltPackage};
+use aptos_language_e2e_tests::account::Account;
+use aptos_package_builder::PackageBuilder;
+use aptos_types::{
+    access_path::AccessPath,
+    state_store::state_key::StateKey,
+    transaction::{ExecutionStatus, SignedTransaction, TransactionPayload, TransactionStatus},
+};
+use move_binary_format::CompiledModule;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
+use rand::Rng;
+
+const ADDRESS_NAME: &str = "publish_fuzz";
+
+/// The version the incompatible upgrade reports, which calls must never see.
+const INCOMPATIBLE_VERSION: u64 = 999;
+
+/// How much a [`run_publish_fuzz`] run does.
+#[derive(Clone, Debug)]
+pub struct PublishFuzzConfig {
+    pub blocks: usize,
+    pub txns_per_block: usize,
+    /// How many accounts call the published modules.
+    pub callers: usize,
+    /// How many compatible versions of the package there are to upgrade through, the first one
+    /// being published before the first block.
+    pub versions: u64,
+}
+
+impl Default for PublishFuzzConfig {
+    fn default() -> Self {
+        Self {
+            blocks: 4,
+            txns_per_block: 16,
+            callers: 3,
+            versions: 5,
+        }
+    }
+}
+
+/// A transaction of a block, along with what it must come to.
+enum Step {
+    /// Upgrades to a compatible version, which must succeed.
+    Upgrade(u64),
+    /// Upgrades to an incompatible version, which must be rejected.
+    IncompatibleUpgrade,
+    /// Calls `check` of a module, which must run the given version.
+    Call { module: &'static str, version: u64 },
+}
+
+/// Runs blocks interleaving upgrades of a package with calls to its modules, in an order drawn
+/// from the random number generator of `harness`, so a failure is reproduced by its seed. Panics
+/// unless every transaction ran against the latest version published before it:
+/// - compatible upgrades succeed, and the incompatible one is rejected,
+/// - each call runs the version of the modules it expects, both of the module it calls and of a
+///   module called through it, so a stale module left in the loader cache fails the call,
+/// - after each block, the code in storage is the one of the latest version.
+///
+/// This generalizes tests of specific sequences, like
+/// `code_publishing_upgrade_loader_cache_consistency`.
+pub fn run_publish_fuzz(harness: &mut MoveHarness, config: &PublishFuzzConfig) {
+    assert!(config.versions > 0, "there must be a version to publish");
+    let publisher = harness.new_account_with_key_pair();
+    let address = publisher.address().to_hex_literal();
+    let callers: Vec<Account> = (0..config.callers)
+        .map(|_| harness.new_account_with_key_pair())
+        .collect();
+    let packages: Vec<(TransactionPayload, Vec<u8>)> = (1..=config.versions)
+        .map(|version| build_package(&address, version, false))
+        .collect();
+    let (incompatible, _) = build_package(&address, INCOMPATIBLE_VERSION, true);
+
+    let status = harness.run_transaction_payload(&publisher, packages[0].0.clone());
+    assert_eq!(
+        status,
+        TransactionStatus::Keep(ExecutionStatus::Success),
+        "publishing the first version failed"
+    );
+    let mut version = 1;
+
+    for block in 0..config.blocks {
+        let mut steps = vec![];
+        let mut txns: Vec<SignedTransaction> = vec![];
+        for _ in 0..config.txns_per_block {
+            let choice = harness.rng().gen_range(0, 10);
+            if choice < 2 && version < config.versions {
+                version += 1;
+                let payload = packages[version as usize - 1].0.clone();
+                txns.push(harness.create_transaction_payload(&publisher, payload));
+                steps.push(Step::Upgrade(version));
+            } else if choice < 3 {
+                txns.push(harness.create_transaction_payload(&publisher, incompatible.clone()));
+                steps.push(Step::IncompatibleUpgrade);
+            } else {
+                let caller = &callers[harness.rng().gen_range(0, callers.len())];
+                let module = if harness.rng().gen() { "m" } else { "n" };
+                txns.push(harness.create_entry_function(
+                    caller,
+                    MemberId {
+                        module_id: ModuleId::new(
+                            *publisher.address(),
+                            Identifier::new(module).unwrap(),
+                        ),
+                        member_id: Identifier::new("check").unwrap(),
+                    },
+                    vec![],
+                    vec![bcs::to_bytes(&version).unwrap()],
+                ));
+                steps.push(Step::Call { module, version });
+            }
+        }
+
+        let statuses = harness.run_block(txns);
+        for (index, (step, status)) in steps.iter().zip(&statuses).enumerate() {
+            check_step(step, status).unwrap_or_else(|violation| {
+                panic!(
+                    "block {}, transaction {}: {}",
+                    block + 1,
+                    index + 1,
+                    violation
+                )
+            });
+        }
+
+        let code = harness.read_state_value(&StateKey::access_path(AccessPath::code_access_path(
+            ModuleId::new(*publisher.address(), Identifier::new("m").unwrap()),
+        )));
+        assert!(
+            code.as_ref() == Some(&packages[version as usize - 1].1),
+            "block {}: the code of module m in storage isn't the one of version {}",
+            block + 1,
+            version
+        );
+    }
+}
+
+/// Checks that a transaction came to what `step` says, describing how it didn't otherwise.
+fn check_step(step: &Step, status: &TransactionStatus) -> Result<(), String> {
+    let expected = match step {
+        Step::Upgrade(_) => TransactionStatus::Keep(ExecutionStatus::Success),
+        Step::IncompatibleUpgrade => TransactionStatus::Keep(ExecutionStatus::MiscellaneousError(
+            Some(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE),
+        )),
+        Step::Call { .. } => TransactionStatus::Keep(ExecutionStatus::Success),
+    };
+    if *status == expected {
+        return Ok(());
+    }
+    Err(match (step, status) {
+        (
+            Step::Call { module, version },
+            TransactionStatus::Keep(ExecutionStatus::MoveAbort { code, .. }),
+        ) => format!(
+            "calling {}::check ran version {} of the modules instead of version {}",
+            module, code, version
+        ),
+        (Step::Call { module, version }, status) => format!(
+            "calling {}::check expecting version {} came to {:?}",
+            module, version, status
+        ),
+        (Step::Upgrade(version), status) => {
+            format!("upgrading to version {} came to {:?}", version, status)
+        },
+        (Step::IncompatibleUpgrade, status) => {
+            format!("the incompatible upgrade came to {:?}", status)
+        },
+    })
+}
+
+/// Builds the package at `address` whose modules report `version`, returning the payload
+/// publishing it along with the code of its module `m`. Module `n` calls `m`, so that a call to
+/// `n` also loads `m`. The incompatible package adds a field to a struct.
+fn build_package(address: &str, version: u64, incompatible: bool) -> (TransactionPayload, Vec<u8>) {
+    let mut builder = PackageBuilder::new("PublishFuzz").with_policy(UpgradePolicy::compat());
+    builder.add_alias(ADDRESS_NAME, address);
+    let fields = if incompatible {
+        "value: u64, extra: u64"
+    } else {
+        "value: u64"
+    };
+    builder.add_source(
+        "m",
+        &format!(
+            "module {address}::m {{
+                const VERSION: u64 = {version};
+                struct Data has key {{ {fields} }}
+                public fun version(): u64 {{ VERSION }}
+                public entry fun check(expected: u64) {{ assert!(expected == VERSION, VERSION) }}
+            }}",
+            address = ADDRESS_NAME,
+            version = version,
+            fields = fields,
+        ),
+    );
+    builder.add_source(
+        "n",
+        &format!(
+            "module {address}::n {{
+                use {address}::m;
+                public entry fun check(expected: u64) {{
+                    let version = m::version();
+                    assert!(expected == version, version)
+                }}
+            }}",
+            address = ADDRESS_NAME,
+        ),
+    );
+    let dir = builder
+        .write_to_temp()
+        .expect("writing the package must succeed");
+    let package = BuiltPackage::build(dir.path().to_owned(), BuildOptions::default())
+        .expect("building the package must succeed");
+    let code = package.extract_code();
+    let metadata = package
+        .extract_metadata()
+        .expect("extracting package metadata must succeed");
+    let module_m = code
+        .iter()
+        .find(|bytes| {
+            CompiledModule::deserialize(bytes)
+                .map_or(false, |module| module.self_id().name().as_str() == "m")
+        })
+        .expect("the package has module m")
+        .clone();
+    let payload = aptos_stdlib::code_publish_package_txn(
+        bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
+        code,
+    );
+    (payload, module_m)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{account_address::AccountAddress, vm_status::AbortLocation};
+
+    fn abort(code: u64) -> TransactionStatus {
+        TransactionStatus::Keep(ExecutionStatus::MoveAbort {
+            location: AbortLocation::Module(ModuleId::new(
+                AccountAddress::ONE,
+                Identifier::new("m").unwrap(),
+            )),
+            code,
+            info: None,
+        })
+    }
+
+    #[test]
+    fn test_check_step() {
+        let success = TransactionStatus::Keep(ExecutionStatus::Success);
+        let call = Step::Call {
+            module: "n",
+            version: 3,
+        };
+        assert_eq!(check_step(&call, &success), Ok(()));
+        assert_eq!(check_step(&Step::Upgrade(3), &success), Ok(()));
+        assert_eq!(
+            check_step(
+                &Step::IncompatibleUpgrade,
+                &TransactionStatus::Keep(ExecutionStatus::MiscellaneousError(Some(
+                    StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+                )))
+            ),
+            Ok(())
+        );
+
+        // A module left in the loader cache aborts with its own version
+        assert_eq!(
+            check_step(&call, &abort(2)),
+            Err("calling n::check ran version 2 of the modules instead of version 3".to_string())
+        );
+        assert!(check_step(&Step::IncompatibleUpgrade, &success)
+            .unwrap_err()
+            .starts_with("the incompatible upgrade came to"));
+        assert!(check_step(&Step::Upgrade(3), &abort(1))
+            .unwrap_err()
+            .starts_with("upgrading to version 3 came to"));
+    }
+
+    #[test]
+    fn test_run_without_upgrades() {
+        // With a single version, blocks only interleave calls with rejected upgrades
+        let mut h = MoveHarness::new();
+        run_publish_fuzz(&mut h, &PublishFuzzConfig {
+            blocks: 2,
+            txns_per_block: 8,
+            callers: 2,
+            versions: 1,
+        });
+    }
+}
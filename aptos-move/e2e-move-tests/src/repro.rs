@@ -0,0 +1,121 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_types::{
+    account_address::AccountAddress,
+    state_store::{
+        state_export::StateExport,
+        state_key::{StateKey, StateKeyInner},
+        state_value::StateValue,
+    },
+    transaction::{SignedTransaction, TransactionOutput},
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::Path,
+};
+
+/// The environment variable setting the seed of the harnesses created from then on, e.g. to
+/// re-run a failed test with the seed it printed.
+pub const SEED_ENV_VAR: &str = "E2E_MOVE_TESTS_SEED";
+
+const SEED_FILE: &str = "seed";
+const STATE_FILE: &str = "state.bcs";
+const TRANSACTIONS_FILE: &str = "transactions.bcs";
+
+/// What it takes to run the last block of a test again elsewhere, written to a directory by
+/// `MoveHarness::export_repro_bundle` and run by `MoveHarness::replay_repro_bundle`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReproBundle {
+    /// The seed of the harness, with which the whole test can be re-run, see `SEED_ENV_VAR`.
+    pub seed: u64,
+    /// The state before the block ran.
+    pub state: StateExport,
+    pub transactions: Vec<SignedTransaction>,
+}
+
+impl ReproBundle {
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+        fs::write(dir.join(SEED_FILE), self.seed.to_string())?;
+        self.state.write_to(&dir.join(STATE_FILE))?;
+        fs::write(
+            dir.join(TRANSACTIONS_FILE),
+            bcs::to_bytes(&self.transactions)?,
+        )?;
+        Ok(())
+    }
+
+    pub fn read_from(dir: &Path) -> Result<Self> {
+        let seed = fs::read_to_string(dir.join(SEED_FILE))
+            .with_context(|| format!("Failed to read the seed of {}", dir.display()))?;
+        let transactions = fs::read(dir.join(TRANSACTIONS_FILE))
+            .with_context(|| format!("Failed to read the transactions of {}", dir.display()))?;
+        Ok(Self {
+            seed: seed.trim().parse()?,
+            state: StateExport::read_from(&dir.join(STATE_FILE))?,
+            transactions: bcs::from_bytes(&transactions)?,
+        })
+    }
+}
+
+/// The transactions of the last block a harness ran, along with the values the block overwrote,
+/// from which the state before the block is rebuilt.
+#[derive(Default)]
+pub(crate) struct LastBlock {
+    transactions: Vec<SignedTransaction>,
+    /// The value of each key the block wrote, before the block. `None` if the key didn't exist.
+    previous_values: HashMap<StateKey, Option<Vec<u8>>>,
+}
+
+impl LastBlock {
+    pub fn start(&mut self, transactions: Vec<SignedTransaction>) {
+        self.transactions = transactions;
+        self.previous_values.clear();
+    }
+
+    /// Records the values `output` is about to overwrite in `store`. Keys written by earlier
+    /// transactions of the block keep the value from before the block.
+    pub fn record(&mut self, store: &FakeDataStore, output: &TransactionOutput) {
+        for (key, _) in output.write_set() {
+            self.previous_values
+                .entry(key.clone())
+                .or_insert_with(|| store.inner().get(key).cloned());
+        }
+    }
+
+    /// The state of `store` before the block. State written directly since the block, rather than
+    /// by transactions, is included as is.
+    pub fn state_before(&self, store: &FakeDataStore) -> StateExport {
+        let mut values = store.inner().clone();
+        for (key, value) in &self.previous_values {
+            match value {
+                Some(value) => values.insert(key.clone(), value.clone()),
+                None => values.remove(key),
+            };
+        }
+        let accounts: BTreeSet<AccountAddress> = values
+            .keys()
+            .filter_map(|key| match key.inner() {
+                StateKeyInner::AccessPath(access_path) => Some(access_path.address),
+                _ => None,
+            })
+            .collect();
+        StateExport {
+            version: 0,
+            accounts: accounts.into_iter().collect(),
+            state: values
+                .into_iter()
+                .map(|(key, value)| (key, StateValue::new_legacy(value)))
+                .collect(),
+        }
+    }
+
+    pub fn transactions(&self) -> &[SignedTransaction] {
+        &self.transactions
+    }
+}
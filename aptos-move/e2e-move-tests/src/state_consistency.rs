@@ -0,0 +1,245 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_framework::natives::code::PackageRegistry;
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_resource_viewer::{find_tables, find_tables_in_struct, AptosValueAnnotator, FoundTable};
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::{
+        state_key::{StateKey, StateKeyInner},
+        table::TableHandle,
+    },
+};
+use aptos_vm::{data_cache::AsMoveResolver, move_vm_ext::MoveResolverExt};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// A violation of an invariant of the state, as found by `MoveHarness::assert_state_consistency`.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum StateInconsistency {
+    /// Items of a table which no value in the state holds.
+    OrphanedTableItems { handle: TableHandle, count: usize },
+    /// Members of `0x1::object::ObjectGroup` at an address without an `0x1::object::ObjectCore`.
+    ObjectWithoutCore {
+        address: AccountAddress,
+        members: Vec<StructTag>,
+    },
+    /// A module listed in the package registry of its address which isn't published.
+    MissingModule { package: String, module: ModuleId },
+    /// A published module which no package in the registry of its address lists.
+    UnregisteredModule(ModuleId),
+    /// A value which doesn't decode with the published modules, e.g. because its module failed
+    /// to publish.
+    UndecodableValue { key: StateKey, error: String },
+}
+
+impl fmt::Display for StateInconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateInconsistency::OrphanedTableItems { handle, count } => write!(
+                f,
+                "{} items of table {} which no value holds",
+                count, handle.0
+            ),
+            StateInconsistency::ObjectWithoutCore { address, members } => write!(
+                f,
+                "object {} has no ObjectCore but holds {:?}",
+                address, members
+            ),
+            StateInconsistency::MissingModule { package, module } => write!(
+                f,
+                "module {} of package {} isn't published",
+                module, package
+            ),
+            StateInconsistency::UnregisteredModule(module) => {
+                write!(f, "module {} is published outside of any package", module)
+            },
+            StateInconsistency::UndecodableValue { key, error } => {
+                write!(f, "{:?} doesn't decode: {}", key, error)
+            },
+        }
+    }
+}
+
+/// Checks the invariants the state of `store` must keep whatever transactions failed:
+/// - every table item belongs to a table held by a resource, or by another table item,
+/// - every object holding resources has its `ObjectCore`,
+/// - the package registry of each address lists exactly the modules published at it,
+/// - every resource and table item decodes with the published modules.
+pub fn check_state(store: &FakeDataStore) -> Vec<StateInconsistency> {
+    let resolver = store.as_move_resolver();
+    let annotator = AptosValueAnnotator::new(&resolver);
+    let object_group = StructTag {
+        address: AccountAddress::ONE,
+        module: Identifier::new("object").unwrap(),
+        name: Identifier::new("ObjectGroup").unwrap(),
+        type_params: vec![],
+    };
+    let object_core = StructTag {
+        name: Identifier::new("ObjectCore").unwrap(),
+        ..object_group.clone()
+    };
+
+    let mut inconsistencies = vec![];
+    let mut tables = BTreeMap::new();
+    let mut table_items = vec![];
+    let mut published = BTreeSet::new();
+    let mut registered = BTreeSet::new();
+    for (key, bytes) in store.inner() {
+        let access_path = match key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path,
+            StateKeyInner::TableItem { handle, .. } => {
+                table_items.push((key, *handle, bytes));
+                continue;
+            },
+            StateKeyInner::Raw(_) => continue,
+        };
+        match access_path.get_path() {
+            Path::Code(module) => {
+                published.insert(module);
+            },
+            Path::Resource(tag) => {
+                if tag.address == AccountAddress::ONE
+                    && tag.module.as_str() == "code"
+                    && tag.name.as_str() == "PackageRegistry"
+                {
+                    if let Ok(registry) = bcs::from_bytes::<PackageRegistry>(bytes) {
+                        for package in registry.packages {
+                            for module in package.modules {
+                                let id = ModuleId::new(
+                                    access_path.address,
+                                    Identifier::new(module.name).unwrap(),
+                                );
+                                registered.insert((package.name.clone(), id));
+                            }
+                        }
+                    }
+                }
+                view_resource(
+                    &annotator,
+                    key,
+                    &tag,
+                    bytes,
+                    &mut tables,
+                    &mut inconsistencies,
+                );
+            },
+            Path::ResourceGroup(group) => {
+                let members = match bcs::from_bytes::<BTreeMap<StructTag, Vec<u8>>>(bytes) {
+                    Ok(members) => members,
+                    Err(err) => {
+                        inconsistencies.push(StateInconsistency::UndecodableValue {
+                            key: key.clone(),
+                            error: err.to_string(),
+                        });
+                        continue;
+                    },
+                };
+                if group == object_group && !members.contains_key(&object_core) {
+                    inconsistencies.push(StateInconsistency::ObjectWithoutCore {
+                        address: access_path.address,
+                        members: members.keys().cloned().collect(),
+                    });
+                }
+                for (tag, bytes) in &members {
+                    view_resource(
+                        &annotator,
+                        key,
+                        tag,
+                        bytes,
+                        &mut tables,
+                        &mut inconsistencies,
+                    );
+                }
+            },
+        }
+    }
+
+    // Tables may hold tables, so decode items until no more tables are found
+    loop {
+        let count = table_items.len();
+        table_items.retain(|(key, handle, bytes)| {
+            let value_type: &TypeTag = match tables.get(handle) {
+                Some(value_type) => value_type,
+                None => return true,
+            };
+            match annotator.view_value(value_type, bytes) {
+                Ok(value) => {
+                    let mut found = vec![];
+                    find_tables(&value, &mut found);
+                    tables.extend(found.into_iter().map(table_value_type));
+                },
+                Err(err) => inconsistencies.push(StateInconsistency::UndecodableValue {
+                    key: (*key).clone(),
+                    error: err.to_string(),
+                }),
+            }
+            false
+        });
+        if table_items.len() == count {
+            break;
+        }
+    }
+    let mut orphaned = BTreeMap::new();
+    for (_, handle, _) in table_items {
+        *orphaned.entry(handle).or_insert(0) += 1;
+    }
+    inconsistencies.extend(
+        orphaned
+            .into_iter()
+            .map(|(handle, count)| StateInconsistency::OrphanedTableItems { handle, count }),
+    );
+
+    let registered_ids = registered
+        .iter()
+        .map(|(_, module)| module.clone())
+        .collect::<BTreeSet<_>>();
+    inconsistencies.extend(
+        registered
+            .into_iter()
+            .filter(|(_, module)| !published.contains(module))
+            .map(|(package, module)| StateInconsistency::MissingModule { package, module }),
+    );
+    inconsistencies.extend(
+        published
+            .into_iter()
+            .filter(|module| !registered_ids.contains(module))
+            .map(StateInconsistency::UnregisteredModule),
+    );
+
+    inconsistencies.sort();
+    inconsistencies
+}
+
+fn view_resource<T: MoveResolverExt>(
+    annotator: &AptosValueAnnotator<T>,
+    key: &StateKey,
+    tag: &StructTag,
+    bytes: &[u8],
+    tables: &mut BTreeMap<TableHandle, TypeTag>,
+    inconsistencies: &mut Vec<StateInconsistency>,
+) {
+    match annotator.view_resource(tag, bytes) {
+        Ok(value) => {
+            let mut found = vec![];
+            find_tables_in_struct(&value, &mut found);
+            tables.extend(found.into_iter().map(table_value_type));
+        },
+        Err(err) => inconsistencies.push(StateInconsistency::UndecodableValue {
+            key: key.clone(),
+            error: err.to_string(),
+        }),
+    }
+}
+
+fn table_value_type(table: FoundTable) -> (TableHandle, TypeTag) {
+    (table.handle, table.value_type)
+}
@@ -4,7 +4,7 @@
 use crate::{
     aggregator::{
         add, add_and_materialize, check, destroy, initialize, materialize, materialize_and_add,
-        materialize_and_sub, new, sub, sub_add, sub_and_materialize,
+        materialize_and_sub, new, read, sub, sub_add, sub_and_materialize,
     },
     assert_abort, assert_success,
     tests::common,
@@ -14,12 +14,13 @@ use aptos_language_e2e_tests::account::Account;
 use aptos_types::transaction::SignedTransaction;
 
 fn setup() -> (MoveHarness, Account) {
-    initialize(common::test_dir_path("aggregator.data/pack"))
+    initialize(common::Fixture::Aggregator.path())
 }
 
 #[test]
 fn test_aggregators_e2e() {
     let (mut h, acc) = setup();
+    h.enable_ledger_checks();
     let block_size = 1000;
 
     // Create many aggregators with deterministic limit.
@@ -161,3 +162,17 @@ fn test_aggregator_materialize_overflow() {
     assert_success!(h.run(txn1));
     assert_abort!(h.run(txn2), 131073);
 }
+
+#[test]
+fn test_aggregator_read_from_storage() {
+    let (mut h, acc) = setup();
+    assert_eq!(read(&h, &acc, 0), None);
+
+    let txns = vec![
+        new(&mut h, &acc, 0, 1000),
+        add(&mut h, &acc, 0, 400),
+        sub(&mut h, &acc, 0, 100),
+    ];
+    h.run_block(txns);
+    assert_eq!(read(&h, &acc, 0), Some(300));
+}
@@ -0,0 +1,83 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{account_address::AccountAddress, account_config::CoinStoreResource};
+use move_core_types::move_resource::MoveStructType;
+
+fn balance(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+#[test]
+fn test_transfers_to_one_receiver_commute() {
+    let mut h = MoveHarness::new();
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+    let receiver_balance = balance(&h, receiver.address());
+
+    let mut txns = vec![];
+    for sender in ["0xcafe1", "0xcafe2", "0xcafe3", "0xcafe4"] {
+        let sender = h.new_account_at(AccountAddress::from_hex_literal(sender).unwrap());
+        for _ in 0..2 {
+            txns.push(h.create_transaction_payload(
+                &sender,
+                aptos_stdlib::aptos_account_transfer(*receiver.address(), 10),
+            ));
+        }
+    }
+
+    for status in h.run_block_with_conflicts(txns, 42) {
+        assert_success!(status);
+    }
+    assert_eq!(balance(&h, receiver.address()), receiver_balance + 80);
+}
+
+#[test]
+#[should_panic(expected = "the block has another outcome")]
+fn test_transfer_of_received_coins_depends_on_order() {
+    let mut h = MoveHarness::new();
+    let max_fee = h.max_transaction_fee();
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    // Bob can only pay more than the fee once Alice's coins arrived
+    let bob = h.new_account_with_balance_and_sequence_number(max_fee, 0);
+    let carol = h.new_account_at(AccountAddress::from_hex_literal("0xca401").unwrap());
+
+    let txns = vec![
+        h.create_transaction_payload(
+            &alice,
+            aptos_stdlib::aptos_account_transfer(*bob.address(), max_fee),
+        ),
+        h.create_transaction_payload(
+            &bob,
+            aptos_stdlib::aptos_account_transfer(*carol.address(), max_fee + 1),
+        ),
+    ];
+    h.run_block_with_conflicts(txns, 42);
+}
+
+#[test]
+fn test_conflicting_transfers_are_deterministic() {
+    let mut h = MoveHarness::new();
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+    let receiver_balance = balance(&h, receiver.address());
+
+    // Every transfer writes the receiver's balance, so parallel execution has to re-execute them
+    let mut txns = vec![];
+    for sender in ["0xcafe1", "0xcafe2", "0xcafe3", "0xcafe4"] {
+        let sender = h.new_account_at(AccountAddress::from_hex_literal(sender).unwrap());
+        for _ in 0..4 {
+            txns.push(h.create_transaction_payload(
+                &sender,
+                aptos_stdlib::aptos_account_transfer(*receiver.address(), 10),
+            ));
+        }
+    }
+
+    for status in h.run_block_check_determinism(txns, 8) {
+        assert_success!(status);
+    }
+    assert_eq!(balance(&h, receiver.address()), receiver_balance + 160);
+}
@@ -52,7 +52,7 @@ fn call_get_chain_id_from_native_txn_context(harness: &mut MoveHarness, account:
 }
 
 fn setup(harness: &mut MoveHarness) -> Account {
-    let path = common::test_dir_path("chain_id.data/pack");
+    let path = common::Fixture::ChainId.path();
 
     let account = harness.new_account_at(AccountAddress::ONE);
 
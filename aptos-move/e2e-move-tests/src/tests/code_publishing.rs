@@ -1,74 +1,165 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{assert_abort, assert_success, assert_vm_status, tests::common, MoveHarness};
-use aptos_framework::natives::code::{PackageRegistry, UpgradePolicy};
-use aptos_package_builder::PackageBuilder;
+use crate::{
+    assert_abort, assert_success, assert_vm_status, feature_matrix, run_policy_matrix,
+    run_publish_fuzz, tests::common, FeatureCombination, MoveHarness, PublishFuzzConfig,
+};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_framework::{
+    natives::code::{PackageRegistry, UpgradePolicy},
+    BuildOptions, BuiltPackage,
+};
+use aptos_package_builder::{Malformation, MalformedPackageBuilder, PackageBuilder};
 use aptos_types::{
     account_address::{create_resource_address, AccountAddress},
     on_chain_config::FeatureFlag,
+    transaction::TransactionStatus,
 };
 use move_core_types::{parser::parse_struct_tag, vm_status::StatusCode};
-use rstest::rstest;
-use serde::{Deserialize, Serialize};
-
-// Note: this module uses parameterized tests via the
-// [`rstest` crate](https://crates.io/crates/rstest)
-// to test for multiple feature combinations.
+use serde_json::json;
+
+// Note: this module uses `feature_matrix!` to test for multiple feature combinations.
+
+feature_matrix!(
+    FeatureCombination::toggling(FeatureFlag::CODE_DEPENDENCY_CHECK),
+    /// Runs the basic publishing test for all legacy flag combinations. Otherwise we will only
+    /// run tests which are expected to make a difference for legacy flag combinations.
+    fn code_publishing_basic(h, features) {
+        let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+        assert_success!(h.publish_package(&acc, &common::Fixture::PackInitial.path(),));
+
+        // Validate metadata as expected.
+        let registry = h
+            .read_resource::<PackageRegistry>(
+                acc.address(),
+                parse_struct_tag("0x1::code::PackageRegistry").unwrap(),
+            )
+            .unwrap();
+        assert_eq!(registry.packages.len(), 1);
+        assert_eq!(registry.packages[0].name, "test_package");
+        assert_eq!(registry.packages[0].modules.len(), 1);
+        assert_eq!(registry.packages[0].modules[0].name, "test");
+
+        // Validate code loaded as expected.
+        assert_success!(h.run_entry_function(
+            &acc,
+            str::parse("0xcafe::test::hello").unwrap(),
+            vec![],
+            vec![bcs::to_bytes::<u64>(&42).unwrap()]
+        ));
+        let state = h
+            .read_resource_json(
+                acc.address(),
+                parse_struct_tag("0xcafe::test::State").unwrap(),
+            )
+            .unwrap();
+        assert_eq!(state, json!({ "value": "42" }))
+    }
+);
 
-/// Mimics `0xcafe::test::State`
-#[derive(Serialize, Deserialize)]
-struct State {
-    value: u64,
+fn publish_malformed(h: &mut MoveHarness, malformation: Malformation) -> TransactionStatus {
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let mut pack = PackageBuilder::new("Package");
+    pack.add_source("m", "module 0xcafe::m { public fun f() {} }");
+    pack.add_source("n", "module 0xcafe::n { public fun g() {} }");
+    let package = MalformedPackageBuilder::new(pack)
+        .with(malformation)
+        .build()
+        .expect("building a malformed package must succeed");
+    h.run_transaction_payload(
+        &acc,
+        aptos_stdlib::code_publish_package_txn(package.metadata_bytes(), package.code),
+    )
 }
 
-/// Mimics `0xcafe::test::State`
-#[derive(Serialize, Deserialize)]
-struct StateWithCoins {
-    important_value: u64,
-    value: u64,
-}
+#[test]
+fn code_publishing_malformed_package() {
+    for malformation in [
+        Malformation::ModuleWithoutCode,
+        Malformation::ModuleWithoutMetadata,
+        Malformation::DuplicateModule,
+    ] {
+        let mut h = MoveHarness::new();
+        let status = publish_malformed(&mut h, malformation);
+        assert_vm_status!(status, StatusCode::CONSTRAINT_NOT_SATISFIED)
+    }
 
-/// Runs the basic publishing test for all legacy flag combinations. Otherwise we will only
-/// run tests which are expected to make a difference for legacy flag combinations.
-#[rstest(enabled, disabled,
-    case(vec![], vec![FeatureFlag::CODE_DEPENDENCY_CHECK]),
-    case(vec![FeatureFlag::CODE_DEPENDENCY_CHECK], vec![]),
-)]
-fn code_publishing_basic(enabled: Vec<FeatureFlag>, disabled: Vec<FeatureFlag>) {
-    let mut h = MoveHarness::new_with_features(enabled, disabled);
-    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_initial"),
-    ));
+    let mut h = MoveHarness::new();
+    let status = publish_malformed(&mut h, Malformation::OversizedIdentifier);
+    assert_vm_status!(status, StatusCode::CODE_DESERIALIZATION_ERROR)
+}
 
-    // Validate metadata as expected.
+#[test]
+fn code_publishing_ignores_claimed_upgrade_number() {
+    let mut h = MoveHarness::new();
+    assert_success!(publish_malformed(&mut h, Malformation::UpgradeNumber(5)));
     let registry = h
         .read_resource::<PackageRegistry>(
-            acc.address(),
+            &AccountAddress::from_hex_literal("0xcafe").unwrap(),
             parse_struct_tag("0x1::code::PackageRegistry").unwrap(),
         )
         .unwrap();
-    assert_eq!(registry.packages.len(), 1);
-    assert_eq!(registry.packages[0].name, "test_package");
-    assert_eq!(registry.packages[0].modules.len(), 1);
-    assert_eq!(registry.packages[0].modules[0].name, "test");
+    assert_eq!(registry.packages[0].upgrade_number, 0);
+}
+
+#[test]
+fn code_publishing_with_source_maps() {
+    let read_module = |h: &MoveHarness, acc: &AccountAddress| {
+        h.read_resource::<PackageRegistry>(
+            acc,
+            parse_struct_tag("0x1::code::PackageRegistry").unwrap(),
+        )
+        .unwrap()
+        .packages[0]
+            .modules[0]
+            .clone()
+    };
+    let options = BuildOptions {
+        with_srcs: true,
+        with_source_maps: true,
+        ..BuildOptions::default()
+    };
 
-    // Validate code loaded as expected.
-    assert_success!(h.run_entry_function(
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package_with_options(
         &acc,
-        str::parse("0xcafe::test::hello").unwrap(),
-        vec![],
-        vec![bcs::to_bytes::<u64>(&42).unwrap()]
+        &common::Fixture::PackInitial.path(),
+        options.clone()
     ));
-    let state = h
-        .read_resource::<State>(
-            acc.address(),
-            parse_struct_tag("0xcafe::test::State").unwrap(),
-        )
+    let module = read_module(&h, acc.address());
+    assert!(module
+        .source()
+        .unwrap()
+        .unwrap()
+        .contains("module 0xcafe::test"));
+    assert!(module.source_map().unwrap().is_some());
+
+    // Source maps are the first to go when the package doesn't fit
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let size_without_source_maps = {
+        let package = BuiltPackage::build(common::Fixture::PackInitial.path(), BuildOptions {
+            with_srcs: true,
+            ..BuildOptions::default()
+        })
         .unwrap();
-    assert_eq!(state.value, 42)
+        let metadata = package.extract_metadata().unwrap();
+        bcs::serialized_size(&metadata).unwrap()
+            + package.extract_code().iter().map(Vec::len).sum::<usize>()
+    };
+    assert_success!(h.publish_package_with_options(
+        &acc,
+        &common::Fixture::PackInitial.path(),
+        BuildOptions {
+            max_publish_size: Some(size_without_source_maps),
+            ..options
+        }
+    ));
+    let module = read_module(&h, acc.address());
+    assert!(module.source().unwrap().is_some());
+    assert!(module.source_map().unwrap().is_none());
 }
 
 #[test]
@@ -77,16 +168,10 @@ fn code_publishing_upgrade_success_compat() {
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
 
     // Install the initial version with compat requirements
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_initial"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackInitial.path(),));
 
     // We should be able to upgrade it with the compatible version
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_upgrade_compat"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackUpgradeCompat.path(),));
 }
 
 #[test]
@@ -95,16 +180,10 @@ fn code_publishing_upgrade_fail_compat() {
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
 
     // Install the initial version with compat requirements
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_initial"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackInitial.path(),));
 
     // We should not be able to upgrade it with the incompatible version
-    let status = h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_upgrade_incompat"),
-    );
+    let status = h.publish_package(&acc, &common::Fixture::PackUpgradeIncompat.path());
     assert_vm_status!(status, StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
 }
 
@@ -114,35 +193,54 @@ fn code_publishing_upgrade_fail_immutable() {
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
 
     // Install the initial version with immutable requirements
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_initial_immutable"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackInitialImmutable.path(),));
 
     // We should not be able to upgrade it with the compatible version
-    let status = h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_upgrade_compat"),
-    );
+    let status = h.publish_package(&acc, &common::Fixture::PackUpgradeCompat.path());
     assert_abort!(status, _);
 }
 
+/// Covers every transition between upgrade policies, with a compatible and an incompatible
+/// upgrade each.
+#[test]
+fn code_publishing_upgrade_policy_matrix() {
+    let mut h = MoveHarness::new();
+    let matrix = run_policy_matrix(&mut h);
+    assert_eq!(
+        matrix.to_string(),
+        "\
+arbitrary -> arbitrary (compatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+arbitrary -> arbitrary (incompatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+arbitrary -> compatible (compatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+arbitrary -> compatible (incompatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+arbitrary -> immutable (compatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+arbitrary -> immutable (incompatible): initial publish denied: EINCOMPATIBLE_POLICY_DISABLED
+compatible -> arbitrary (compatible): upgrade denied: EINCOMPATIBLE_POLICY_DISABLED
+compatible -> arbitrary (incompatible): upgrade denied: EINCOMPATIBLE_POLICY_DISABLED
+compatible -> compatible (compatible): allowed
+compatible -> compatible (incompatible): upgrade denied: BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+compatible -> immutable (compatible): allowed
+compatible -> immutable (incompatible): upgrade denied: BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+immutable -> arbitrary (compatible): upgrade denied: EINCOMPATIBLE_POLICY_DISABLED
+immutable -> arbitrary (incompatible): upgrade denied: EINCOMPATIBLE_POLICY_DISABLED
+immutable -> compatible (compatible): upgrade denied: EUPGRADE_IMMUTABLE
+immutable -> compatible (incompatible): upgrade denied: EUPGRADE_IMMUTABLE
+immutable -> immutable (compatible): upgrade denied: EUPGRADE_IMMUTABLE
+immutable -> immutable (incompatible): upgrade denied: EUPGRADE_IMMUTABLE
+"
+    );
+}
+
 #[test]
 fn code_publishing_upgrade_fail_overlapping_module() {
     let mut h = MoveHarness::new();
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
 
     // Install the initial version
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_initial"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackInitial.path(),));
 
     // Install a different package with the same module.
-    let status = h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_other_name"),
-    );
+    let status = h.publish_package(&acc, &common::Fixture::PackOtherName.path());
     assert_abort!(status, _);
 }
 
@@ -160,16 +258,11 @@ fn code_publishing_upgrade_loader_cache_consistency() {
 
     // Create a sequence of package upgrades
     let txns = vec![
-        h.create_publish_package(
-            &acc,
-            &common::test_dir_path("code_publishing.data/pack_initial"),
-            None,
-            |_| {},
-        ),
+        h.create_publish_package(&acc, &common::Fixture::PackInitial.path(), None, |_| {}),
         // Compatible with above package
         h.create_publish_package(
             &acc,
-            &common::test_dir_path("code_publishing.data/pack_upgrade_compat"),
+            &common::Fixture::PackUpgradeCompat.path(),
             None,
             |_| {},
         ),
@@ -178,7 +271,7 @@ fn code_publishing_upgrade_loader_cache_consistency() {
         // Bug behavior: succeeds because is compared with the first module
         h.create_publish_package(
             &acc,
-            &common::test_dir_path("code_publishing.data/pack_compat_first_not_second"),
+            &common::Fixture::PackCompatFirstNotSecond.path(),
             None,
             |_| {},
         ),
@@ -189,6 +282,14 @@ fn code_publishing_upgrade_loader_cache_consistency() {
     assert_vm_status!(result[2], StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
 }
 
+/// Interleaves upgrades and calls in random orders within blocks, checking that every call sees
+/// the latest version published before it. Re-run a failure with the seed it prints.
+#[test]
+fn code_publishing_upgrade_loader_cache_fuzz() {
+    let mut h = MoveHarness::new();
+    run_publish_fuzz(&mut h, &PublishFuzzConfig::default());
+}
+
 #[test]
 fn code_publishing_framework_upgrade() {
     let mut h = MoveHarness::new();
@@ -196,10 +297,7 @@ fn code_publishing_framework_upgrade() {
 
     // We should be able to upgrade move-stdlib, as our local package has only
     // compatible changes. (We added a new function to string.move.)
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_stdlib"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::PackStdlib.path(),));
 }
 
 #[test]
@@ -209,10 +307,7 @@ fn code_publishing_framework_upgrade_fail() {
 
     // We should not be able to upgrade move-stdlib because we removed a function
     // from the string module.
-    let result = h.publish_package(
-        &acc,
-        &common::test_dir_path("code_publishing.data/pack_stdlib_incompat"),
-    );
+    let result = h.publish_package(&acc, &common::Fixture::PackStdlibIncompat.path());
     assert_vm_status!(result, StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
 }
 
@@ -227,7 +322,7 @@ fn code_publishing_using_resource_account() {
         "m",
         &format!("module 0x{}::m {{ public fun f() {{}} }}", module_address),
     );
-    let pack_dir = pack.write_to_temp().unwrap();
+    let pack_dir = common::GeneratedFixture::new(pack);
     let package = aptos_framework::BuiltPackage::build(
         pack_dir.path().to_owned(),
         aptos_framework::BuildOptions::default(),
@@ -266,13 +361,13 @@ fn code_publishing_with_two_attempts_and_verify_loader_is_invalidated() {
     // entire init_module from the first attempt still lingers around and will fail if invoked.
     let failed_module_publish = h.create_publish_package(
         &acc,
-        &common::test_dir_path("code_publishing.data/pack_init_module_failed"),
+        &common::Fixture::PackInitModuleFailed.path(),
         None,
         |_| {},
     );
     let module_publish_second_attempt = h.create_publish_package(
         &acc,
-        &common::test_dir_path("code_publishing.data/pack_init_module_second_attempt"),
+        &common::Fixture::PackInitModuleSecondAttempt.path(),
         None,
         |_| {},
     );
@@ -281,75 +376,72 @@ fn code_publishing_with_two_attempts_and_verify_loader_is_invalidated() {
     assert_success!(results[1]);
 
     let value_resource = h
-        .read_resource::<StateWithCoins>(
+        .read_resource_json(
             acc.address(),
             parse_struct_tag("0xcafe::test::State").unwrap(),
         )
         .unwrap();
-    assert_eq!(2, value_resource.important_value);
+    assert_eq!(value_resource["important_value"], "2");
+    h.assert_state_consistency();
 }
 
-#[rstest(enabled, disabled,
-         case(vec![], vec![FeatureFlag::CODE_DEPENDENCY_CHECK]),
-         case(vec![FeatureFlag::CODE_DEPENDENCY_CHECK], vec![]),
-)]
-fn code_publishing_faked_dependency(enabled: Vec<FeatureFlag>, disabled: Vec<FeatureFlag>) {
-    let mut h = MoveHarness::new_with_features(enabled.clone(), disabled);
-    let acc1 = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    let acc2 = h.new_account_at(AccountAddress::from_hex_literal("0xdeaf").unwrap());
-
-    let mut pack1 = PackageBuilder::new("Package1").with_policy(UpgradePolicy::compat());
-    pack1.add_source("m", "module 0xcafe::m { public fun f() {} }");
-    let pack1_dir = pack1.write_to_temp().unwrap();
-    assert_success!(h.publish_package(&acc1, pack1_dir.path()));
-
-    // pack2 has a higher policy and should not be able to depend on pack1
-    let mut pack2 = PackageBuilder::new("Package2").with_policy(UpgradePolicy::immutable());
-    pack2.add_local_dep("Package1", &pack1_dir.path().to_string_lossy());
-    pack2.add_source(
-        "m",
-        "module 0xdeaf::m { use 0xcafe::m; public fun f() { m::f() } }",
-    );
-    let pack2_dir = pack2.write_to_temp().unwrap();
-    let result = h.publish_package_with_patcher(&acc2, pack2_dir.path(), |metadata| {
-        // Hide the dependency from the lower policy package from the metadata. We detect this
-        // this via checking the actual bytecode module dependencies.
-        metadata.deps.clear()
-    });
-    if !enabled.contains(&FeatureFlag::CODE_DEPENDENCY_CHECK) {
-        // In the previous version we were not able to detect this problem
-        assert_success!(result)
-    } else {
-        assert_vm_status!(result, StatusCode::CONSTRAINT_NOT_SATISFIED)
+feature_matrix!(
+    FeatureCombination::toggling(FeatureFlag::CODE_DEPENDENCY_CHECK),
+    fn code_publishing_faked_dependency(h, features) {
+        let acc1 = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+        let acc2 = h.new_account_at(AccountAddress::from_hex_literal("0xdeaf").unwrap());
+
+        let mut pack1 = PackageBuilder::new("Package1").with_policy(UpgradePolicy::compat());
+        pack1.add_source("m", "module 0xcafe::m { public fun f() {} }");
+        let pack1_dir = common::GeneratedFixture::new(pack1);
+        assert_success!(h.publish_package(&acc1, pack1_dir.path()));
+
+        // pack2 has a higher policy and should not be able to depend on pack1
+        let mut pack2 = PackageBuilder::new("Package2").with_policy(UpgradePolicy::immutable());
+        pack2.add_local_dep("Package1", &pack1_dir.path().to_string_lossy());
+        pack2.add_source(
+            "m",
+            "module 0xdeaf::m { use 0xcafe::m; public fun f() { m::f() } }",
+        );
+        let pack2_dir = common::GeneratedFixture::new(pack2);
+        let result = h.publish_package_with_patcher(&acc2, pack2_dir.path(), |metadata| {
+            // Hide the dependency from the lower policy package from the metadata. We detect this
+            // this via checking the actual bytecode module dependencies.
+            metadata.deps.clear()
+        });
+        if !features.is_enabled(FeatureFlag::CODE_DEPENDENCY_CHECK) {
+            // In the previous version we were not able to detect this problem
+            assert_success!(result)
+        } else {
+            assert_vm_status!(result, StatusCode::CONSTRAINT_NOT_SATISFIED)
+        }
     }
-}
-
-#[rstest(enabled, disabled,
-         case(vec![], vec![FeatureFlag::TREAT_FRIEND_AS_PRIVATE]),
-         case(vec![FeatureFlag::TREAT_FRIEND_AS_PRIVATE], vec![]),
-)]
-fn code_publishing_friend_as_private(enabled: Vec<FeatureFlag>, disabled: Vec<FeatureFlag>) {
-    let mut h = MoveHarness::new_with_features(enabled.clone(), disabled);
-    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-
-    let mut pack1 = PackageBuilder::new("Package").with_policy(UpgradePolicy::compat());
-    pack1.add_source(
-        "m",
-        "module 0xcafe::m { public fun f() {}  public(friend) fun g() {} }",
-    );
-    let pack1_dir = pack1.write_to_temp().unwrap();
-    assert_success!(h.publish_package(&acc, pack1_dir.path()));
-
-    let mut pack2 = PackageBuilder::new("Package").with_policy(UpgradePolicy::compat());
-    // Removes friend
-    pack2.add_source("m", "module 0xcafe::m { public fun f() {} }");
-    let pack2_dir = pack2.write_to_temp().unwrap();
-
-    let result = h.publish_package(&acc, pack2_dir.path());
-    if enabled.contains(&FeatureFlag::TREAT_FRIEND_AS_PRIVATE) {
-        // With this feature we can remove friends
-        assert_success!(result)
-    } else {
-        assert_vm_status!(result, StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
+);
+
+feature_matrix!(
+    FeatureCombination::toggling(FeatureFlag::TREAT_FRIEND_AS_PRIVATE),
+    fn code_publishing_friend_as_private(h, features) {
+        let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+
+        let mut pack1 = PackageBuilder::new("Package").with_policy(UpgradePolicy::compat());
+        pack1.add_source(
+            "m",
+            "module 0xcafe::m { public fun f() {}  public(friend) fun g() {} }",
+        );
+        let pack1_dir = common::GeneratedFixture::new(pack1);
+        assert_success!(h.publish_package(&acc, pack1_dir.path()));
+
+        let mut pack2 = PackageBuilder::new("Package").with_policy(UpgradePolicy::compat());
+        // Removes friend
+        pack2.add_source("m", "module 0xcafe::m { public fun f() {} }");
+        let pack2_dir = common::GeneratedFixture::new(pack2);
+
+        let result = h.publish_package(&acc, pack2_dir.path());
+        if features.is_enabled(FeatureFlag::TREAT_FRIEND_AS_PRIVATE) {
+            // With this feature we can remove friends
+            assert_success!(result)
+        } else {
+            assert_vm_status!(result, StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE)
+        }
     }
-}
+);
@@ -1,9 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use std::{collections::BTreeMap, path::PathBuf};
+use aptos_package_builder::PackageBuilder;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
 
-pub fn test_dir_path(s: &str) -> PathBuf {
+fn test_dir_path(s: &str) -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .join("src")
         .join("tests")
@@ -17,17 +22,112 @@ pub fn framework_dir_path(s: &str) -> PathBuf {
         .join(s)
 }
 
-pub fn build_scripts(package_folder: &str, package_names: Vec<&str>) -> BTreeMap<String, Vec<u8>> {
+macro_rules! fixtures {
+    ($($variant:ident => $path:literal,)*) => {
+        /// The Move packages the tests publish, relative to this directory.
+        #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+        pub enum Fixture {
+            $($variant,)*
+        }
+
+        impl Fixture {
+            pub fn path(self) -> PathBuf {
+                match self {
+                    $(Fixture::$variant => {
+                        // Fails to compile if the package was moved or deleted
+                        const _: &str = include_str!(concat!($path, "/Move.toml"));
+                        test_dir_path($path)
+                    },)*
+                }
+            }
+        }
+    };
+}
+
+fixtures! {
+    Aggregator => "aggregator.data/pack",
+    ChainId => "chain_id.data/pack",
+    ConstructorArgs => "constructor_args.data/pack",
+    DerivedAddress => "derived_address.data/pack",
+    ErrorMap => "error_map.data/pack",
+    ExecutionLimit => "execution_limit.data/test",
+    EmptyLoop => "infinite_loop.data/empty_loop",
+    InitModule => "init_module.data/pack",
+    InitModuleInitial => "init_module.data/pack_initial",
+    MaxLoopDepthBad => "max_loop_depth.data/pack-bad",
+    MaxLoopDepthGood => "max_loop_depth.data/pack-good",
+    CloneVec => "memory_quota.data/clone_vec",
+    TableAndVec => "memory_quota.data/table_and_vec",
+    VecPushU128 => "memory_quota.data/vec_push_u128",
+    PackCompatFirstNotSecond => "code_publishing.data/pack_compat_first_not_second",
+    PackInitModuleFailed => "code_publishing.data/pack_init_module_failed",
+    PackInitModuleSecondAttempt => "code_publishing.data/pack_init_module_second_attempt",
+    PackInitial => "code_publishing.data/pack_initial",
+    PackInitialImmutable => "code_publishing.data/pack_initial_immutable",
+    PackOtherName => "code_publishing.data/pack_other_name",
+    PackStdlib => "code_publishing.data/pack_stdlib",
+    PackStdlibIncompat => "code_publishing.data/pack_stdlib_incompat",
+    PackUpgradeCompat => "code_publishing.data/pack_upgrade_compat",
+    PackUpgradeIncompat => "code_publishing.data/pack_upgrade_incompat",
+    SmartDataStructures => "smart_data_structures.data",
+    EnableRewardsRateDecrease => "stake.data/enable_rewards_rate_decrease",
+    StringArgs => "string_args.data/pack",
+    DisableCollection => "transaction_fee.data/disable_collection",
+    EnableCollection => "transaction_fee.data/enable_collection",
+    InitializeCollection => "transaction_fee.data/initialize_collection",
+    RemoveValidator => "transaction_fee.data/remove_validator",
+    UpgradeBurnPercentage => "transaction_fee.data/upgrade_burn_percentage",
+    TypeTooLarge => "type_too_large.data/type_too_large",
+    NftDao => "../../../move-examples/dao/nft_dao",
+    MintNftProductionReady => "../../../move-examples/mint_nft/4-Getting-Production-Ready",
+    ResourceAccount => "../../../move-examples/resource_account",
+    ResourceGroupsPrimary => "../../../move-examples/resource_groups/primary",
+    ResourceGroupsSecondary => "../../../move-examples/resource_groups/secondary",
+    TwoByTwoTransfer => "../../../move-examples/scripts/two_by_two_transfer",
+    TokenObjects => "../../../move-examples/token_objects",
+}
+
+impl Fixture {
+    /// The name of the package directory, e.g. `pack_initial`.
+    pub fn name(self) -> String {
+        self.path()
+            .file_name()
+            .expect("fixtures are directories")
+            .to_string_lossy()
+            .into_owned()
+    }
+}
+
+/// A package generated with a `PackageBuilder` instead of living in this directory. It is
+/// deleted when dropped.
+pub struct GeneratedFixture(TempDir);
+
+impl GeneratedFixture {
+    pub fn new(builder: PackageBuilder) -> Self {
+        Self(
+            builder
+                .write_to_temp()
+                .expect("generating a fixture must succeed"),
+        )
+    }
+
+    pub fn path(&self) -> &Path {
+        self.0.path()
+    }
+}
+
+/// Builds the script of each package, by package name.
+pub fn build_scripts(fixtures: &[Fixture]) -> BTreeMap<String, Vec<u8>> {
     let mut scripts = BTreeMap::new();
-    for package_name in package_names {
+    for fixture in fixtures {
         let script = aptos_framework::BuiltPackage::build(
-            test_dir_path(format!("{}/{}", package_folder, package_name).as_str()),
+            fixture.path(),
             aptos_framework::BuildOptions::default(),
         )
         .expect("building packages with scripts must succeed")
         .extract_script_code()[0]
             .clone();
-        scripts.insert(package_name.to_string(), script);
+        scripts.insert(fixture.name(), script);
     }
     scripts
 }
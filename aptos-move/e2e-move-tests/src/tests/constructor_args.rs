@@ -30,7 +30,7 @@ fn success_generic(ty_args: Vec<TypeTag>, tests: Vec<(&str, Vec<Vec<u8>>, &str)>
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("constructor_args.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::ConstructorArgs.path()));
 
     let module_data = parse_struct_tag("0xCAFE::test::ModuleData").unwrap();
 
@@ -67,7 +67,7 @@ fn fail_generic(ty_args: Vec<TypeTag>, tests: Vec<(&str, Vec<Vec<u8>>, Closure)>
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("constructor_args.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::ConstructorArgs.path()));
 
     let module_data = parse_struct_tag("0xCAFE::test::ModuleData").unwrap();
 
@@ -0,0 +1,28 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::MoveHarness;
+use aptos_types::{account_address::AccountAddress, account_config::CoinStoreResource};
+use move_core_types::move_resource::MoveStructType;
+
+fn read_coin(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+#[test]
+fn test_default_balance() {
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_with_key_pair();
+    assert_eq!(read_coin(&h, acc.address()), 1_000_000_000_000_000);
+
+    let mut h = MoveHarness::new().with_default_balance(1_000);
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_eq!(read_coin(&h, acc.address()), 1_000);
+    let acc = h.new_account_with_key_pair();
+    assert_eq!(read_coin(&h, acc.address()), 1_000);
+    // An explicit balance wins over the default one
+    let acc = h.new_account_with_balance_and_sequence_number(5, 0);
+    assert_eq!(read_coin(&h, acc.address()), 5);
+}
@@ -0,0 +1,34 @@
+module 0xcafe::derived_address {
+    use aptos_framework::object;
+    use aptos_token_objects::collection;
+    use aptos_token_objects::token;
+    use std::option;
+    use std::string::{Self, String};
+
+    /// Creates a named object from `seed`, an object derived from it, and an object from the next
+    /// GUID of `creator`.
+    public entry fun create_objects(creator: &signer, seed: vector<u8>) {
+        let named = object::create_named_object(creator, seed);
+        object::create_user_derived_object(creator, &object::generate_derive_ref(&named));
+        object::create_object_from_account(creator);
+    }
+
+    /// Creates a collection and a named token in it.
+    public entry fun create_collection_and_token(creator: &signer, collection: String, name: String) {
+        collection::create_unlimited_collection(
+            creator,
+            string::utf8(b""),
+            collection,
+            option::none(),
+            string::utf8(b""),
+        );
+        token::create_named_token(
+            creator,
+            collection,
+            string::utf8(b""),
+            name,
+            option::none(),
+            string::utf8(b""),
+        );
+    }
+}
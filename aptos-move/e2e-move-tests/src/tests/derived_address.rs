@@ -0,0 +1,95 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, tests::common, MoveHarness};
+use aptos_language_e2e_tests::account::Account;
+use aptos_sdk::derived_address::{
+    create_collection_address, create_guid_object_address, create_named_object_address,
+    create_resource_address, create_token_address, create_user_derived_object_address,
+};
+use aptos_types::account_config::AccountResource;
+use move_core_types::{account_address::AccountAddress, parser::parse_struct_tag};
+
+fn object_exists(h: &MoveHarness, addr: AccountAddress) -> bool {
+    let group = parse_struct_tag("0x1::object::ObjectGroup").unwrap();
+    let object_core = parse_struct_tag("0x1::object::ObjectCore").unwrap();
+    h.read_resource_group(&addr, group)
+        .map_or(false, |group| group.contains_key(&object_core))
+}
+
+fn setup() -> (MoveHarness, Account) {
+    let mut h = MoveHarness::new();
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    assert_success!(h.publish_package(&acc, &common::Fixture::DerivedAddress.path()));
+    (h, acc)
+}
+
+#[test]
+fn derived_resource_account() {
+    let (mut h, acc) = setup();
+    let seed = b"resource".to_vec();
+    assert_success!(h.run_entry_function(
+        &acc,
+        str::parse("0x1::resource_account::create_resource_account").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&seed).unwrap(),
+            bcs::to_bytes(&Vec::<u8>::new()).unwrap()
+        ],
+    ));
+    let account_tag = parse_struct_tag("0x1::account::Account").unwrap();
+    let resource_account = create_resource_address(*acc.address(), &seed);
+    assert!(h.exists_resource(&resource_account, account_tag));
+}
+
+#[test]
+fn derived_objects() {
+    let (mut h, acc) = setup();
+    let addr = *acc.address();
+    let account_tag = parse_struct_tag("0x1::account::Account").unwrap();
+    let creation_num = h
+        .read_resource::<AccountResource>(&addr, account_tag)
+        .unwrap()
+        .guid_creation_num();
+    let seed = b"named".to_vec();
+    assert_success!(h.run_entry_function(
+        &acc,
+        str::parse("0xcafe::derived_address::create_objects").unwrap(),
+        vec![],
+        vec![bcs::to_bytes(&seed).unwrap()],
+    ));
+
+    let named = create_named_object_address(addr, &seed);
+    assert!(object_exists(&h, named));
+    assert!(object_exists(
+        &h,
+        create_user_derived_object_address(addr, named)
+    ));
+    assert!(object_exists(
+        &h,
+        create_guid_object_address(addr, creation_num)
+    ));
+}
+
+#[test]
+fn derived_collection_and_token() {
+    let (mut h, acc) = setup();
+    let addr = *acc.address();
+    assert_success!(h.run_entry_function(
+        &acc,
+        str::parse("0xcafe::derived_address::create_collection_and_token").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes("Collection::with::separators").unwrap(),
+            bcs::to_bytes("Token").unwrap(),
+        ],
+    ));
+    assert!(object_exists(
+        &h,
+        create_collection_address(addr, "Collection::with::separators")
+    ));
+    assert!(object_exists(
+        &h,
+        create_token_address(addr, "Collection::with::separators", "Token")
+    ));
+}
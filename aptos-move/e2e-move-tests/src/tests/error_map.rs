@@ -26,7 +26,7 @@ fn error_map() {
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
     assert_success!(h.publish_package_with_options(
         &acc,
-        &common::test_dir_path("error_map.data/pack"),
+        &common::Fixture::ErrorMap.path(),
         BuildOptions {
             with_error_map: true,
             ..BuildOptions::default()
@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_events, event_type_counts};
+use aptos_types::{
+    account_address::AccountAddress, contract_event::ContractEvent, event::EventKey,
+};
+use move_core_types::language_storage::TypeTag;
+
+fn event(ty: &str) -> ContractEvent {
+    ContractEvent::new(
+        EventKey::new(0, AccountAddress::ONE),
+        0,
+        ty.parse().unwrap(),
+        vec![],
+    )
+}
+
+#[test]
+fn test_event_type_counts_keep_first_emission_order() {
+    let events = vec![
+        event("0xcafe::test::Created"),
+        event("0x1::coin::DepositEvent"),
+        event("0xcafe::test::Created"),
+    ];
+    let counts: Vec<(TypeTag, usize)> = vec![
+        ("0xcafe::test::Created".parse().unwrap(), 2),
+        ("0x1::coin::DepositEvent".parse().unwrap(), 1),
+    ];
+    assert_eq!(event_type_counts(&events), counts);
+    assert_events!(events, [
+        ("0xcafe::test::Created", 2),
+        ("0x0000000000000000000000000000000000000000000000000000000000000001::coin::DepositEvent", 1),
+    ]);
+}
+
+#[test]
+#[should_panic(expected = "Unexpected event types or counts")]
+fn test_assert_events_checks_order() {
+    let events = vec![
+        event("0xcafe::test::Created"),
+        event("0x1::coin::DepositEvent"),
+    ];
+    assert_events!(events, [
+        ("0x1::coin::DepositEvent", 1),
+        ("0xcafe::test::Created", 1),
+    ]);
+}
@@ -0,0 +1,98 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_gas_profiling::{ExecutionTracer, GasProfiler, TraceEvent};
+use aptos_types::{access_path::AccessPath, state_store::state_key::StateKey};
+use move_core_types::{account_address::AccountAddress, parser::parse_struct_tag};
+use std::collections::BTreeMap;
+
+const TRANSFER: &str = "0x1::coin::transfer<0x1::aptos_coin::AptosCoin>";
+const COIN_STORE: &str = "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>";
+
+#[test]
+fn test_trace_transfer() {
+    let mut h = MoveHarness::new();
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = h.new_account_at(AccountAddress::from_hex_literal("0xb0b").unwrap());
+    let txn =
+        h.create_transaction_payload(&alice, aptos_stdlib::aptos_coin_transfer(*bob.address(), 1));
+    let (status, tracer) = h.run_with_hook(txn, ExecutionTracer::new());
+    assert_success!(status);
+
+    let events = tracer.events();
+    assert_eq!(events[0], TraceEvent::Call {
+        depth: 0,
+        function: TRANSFER.to_string(),
+    });
+    let called_by_transfer = |function: &str| {
+        events.contains(&TraceEvent::Call {
+            depth: 1,
+            function: function.to_string(),
+        })
+    };
+    assert!(called_by_transfer(
+        "0x1::coin::withdraw<0x1::aptos_coin::AptosCoin>"
+    ));
+    assert!(called_by_transfer(
+        "0x1::coin::deposit<0x1::aptos_coin::AptosCoin>"
+    ));
+
+    let coin_store = parse_struct_tag(COIN_STORE).unwrap();
+    for account in [&alice, &bob] {
+        assert!(events.iter().any(|event| matches!(
+            event,
+            TraceEvent::LoadResource { addr, ty, num_bytes: Some(_), .. }
+                if addr == account.address() && ty.to_string() == COIN_STORE
+        )));
+        let key = StateKey::access_path(
+            AccessPath::resource_access_path(*account.address(), coin_store.clone()).unwrap(),
+        );
+        assert!(events.iter().any(
+            |event| matches!(event, TraceEvent::Write { key: written, .. } if *written == key)
+        ));
+    }
+    // The writes are reported once the transaction is done executing
+    let first_write = events
+        .iter()
+        .position(|event| matches!(event, TraceEvent::Write { .. }))
+        .unwrap();
+    assert!(events[first_write..]
+        .iter()
+        .all(|event| matches!(event, TraceEvent::Write { .. })));
+
+    let trace = tracer.to_string();
+    assert!(trace.starts_with(&format!("{}\n", TRANSFER)));
+    assert!(trace.contains("\n  0x1::coin::withdraw<0x1::aptos_coin::AptosCoin>\n"));
+}
+
+#[test]
+fn test_profile_and_trace() {
+    let mut h = MoveHarness::new();
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = h.new_account_at(AccountAddress::from_hex_literal("0xb0b").unwrap());
+    let txn =
+        h.create_transaction_payload(&alice, aptos_stdlib::aptos_coin_transfer(*bob.address(), 1));
+    let (status, (profiler, tracer)) =
+        h.run_with_hook(txn, (GasProfiler::new(), ExecutionTracer::new()));
+    assert_success!(status);
+
+    // Both hooks see the same calls of the same execution
+    let report = profiler.finish(1.into()).to_gas_report();
+    assert_eq!(report.entry_point, TRANSFER);
+    let mut traced_calls = BTreeMap::new();
+    for function in tracer.calls() {
+        // The report adds up the calls of a function whatever its type arguments
+        let name = function.split('<').next().unwrap();
+        *traced_calls.entry(name.to_string()).or_insert(0) += 1;
+    }
+    let profiled_calls: BTreeMap<_, _> = report
+        .functions
+        .iter()
+        .map(|(function, gas)| (function.clone(), gas.calls))
+        .collect();
+    assert_eq!(profiled_calls, traced_calls);
+    assert!(report.execution_io_gas > 0.0);
+    assert!(report.write_set_io_gas > 0.0);
+}
@@ -1,10 +1,14 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{tests::common, MoveHarness};
+use crate::{assert_success, tests::common, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
 use aptos_language_e2e_tests::account::Account;
-use aptos_types::transaction::{ExecutionStatus, TransactionStatus};
-use move_core_types::account_address::AccountAddress;
+use aptos_types::{
+    account_config::CoinStoreResource,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use move_core_types::{account_address::AccountAddress, move_resource::MoveStructType};
 
 #[test]
 fn can_upgrade_framework_on_testnet() {
@@ -28,3 +32,32 @@ fn publish(acc: &Account, h: &mut MoveHarness, path: &str) {
         },
     }
 }
+
+#[test]
+fn transfers_continue_across_framework_upgrade() {
+    let mut h = MoveHarness::new_with_previous_framework();
+    let alice = h.new_account_at(AccountAddress::from_hex_literal("0xa11ce").unwrap());
+    let bob = h.new_account_at(AccountAddress::from_hex_literal("0xb0b").unwrap());
+    let initial_balance = balance(&h, bob.address());
+
+    assert_success!(h.run_transaction_payload(
+        &alice,
+        aptos_stdlib::aptos_account_transfer(*bob.address(), 100)
+    ));
+    assert_eq!(balance(&h, bob.address()), initial_balance + 100);
+
+    h.upgrade_framework(aptos_cached_packages::head_release_bundle());
+
+    // State created with the previous framework must still work with the current one
+    assert_success!(h.run_transaction_payload(
+        &alice,
+        aptos_stdlib::aptos_account_transfer(*bob.address(), 100)
+    ));
+    assert_eq!(balance(&h, bob.address()), initial_balance + 200);
+}
+
+fn balance(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
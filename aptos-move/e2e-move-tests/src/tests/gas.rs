@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{tests::common::test_dir_path, MoveHarness};
+use crate::{tests::common, MoveHarness};
 use aptos_cached_packages::{aptos_stdlib, aptos_token_sdk_builder};
 use aptos_crypto::{bls12381, PrivateKey, Uniform};
 use aptos_types::account_address::{default_stake_pool_address, AccountAddress};
@@ -240,25 +240,16 @@ fn test_gas() {
     let publisher = &harness.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
     print_gas_cost(
         "PublishSmall",
-        harness.evaluate_publish_gas(
-            publisher,
-            &test_dir_path("code_publishing.data/pack_initial"),
-        ),
+        harness.evaluate_publish_gas(publisher, &common::Fixture::PackInitial.path()),
     );
     print_gas_cost(
         "UpgradeSmall",
-        harness.evaluate_publish_gas(
-            publisher,
-            &test_dir_path("code_publishing.data/pack_upgrade_compat"),
-        ),
+        harness.evaluate_publish_gas(publisher, &common::Fixture::PackUpgradeCompat.path()),
     );
     let publisher = &harness.aptos_framework_account();
     print_gas_cost(
         "PublishLarge",
-        harness.evaluate_publish_gas(
-            publisher,
-            &test_dir_path("code_publishing.data/pack_stdlib"),
-        ),
+        harness.evaluate_publish_gas(publisher, &common::Fixture::PackStdlib.path()),
     );
 }
 
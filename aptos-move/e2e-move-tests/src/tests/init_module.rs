@@ -19,7 +19,7 @@ fn init_module() {
 
     // Load the code
     let acc = h.aptos_framework_account();
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("init_module.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::InitModule.path()));
 
     // Verify that init_module was called.
     let module_data = parse_struct_tag("0x1::test::ModuleData").unwrap();
@@ -32,7 +32,7 @@ fn init_module() {
 
     // Republish to show that init_module is not called again. If init_module would be called again,
     // we would get an abort here because the first time, it used move_to for initialization.
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("init_module.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::InitModule.path()));
     assert_eq!(
         h.read_resource::<ModuleData>(acc.address(), module_data)
             .unwrap()
@@ -47,13 +47,10 @@ fn init_module_when_republishing_package() {
 
     // Deploy a package that initially does not have the module that has the init_module function.
     let acc = h.aptos_framework_account();
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("init_module.data/pack_initial")
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::InitModuleInitial.path()));
 
     // Now republish the package with the new module that has init_module.
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("init_module.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::InitModule.path()));
 
     // Verify that init_module was called.
     let module_data = parse_struct_tag("0x1::test::ModuleData").unwrap();
@@ -93,4 +90,5 @@ fn init_module_with_abort_and_republish() {
 
     // 2nd publish succeeds, not the old but the new init_module is called.
     assert_success!(res[1]);
+    h.assert_state_consistency();
 }
@@ -0,0 +1,99 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_abort, assert_success, ledger_check::LedgerChecker, MoveHarness};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_language_e2e_tests::account::Account;
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{SignedTransaction, TransactionOutput},
+};
+
+fn setup() -> (MoveHarness, Account, Account) {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+    (h, sender, receiver)
+}
+
+fn transfer(
+    h: &mut MoveHarness,
+    sender: &Account,
+    receiver: &Account,
+    amount: u64,
+) -> SignedTransaction {
+    h.create_transaction_payload(
+        sender,
+        aptos_stdlib::aptos_account_transfer(*receiver.address(), amount),
+    )
+}
+
+#[test]
+fn test_ledger_checks() {
+    let (mut h, sender, receiver) = setup();
+    h.enable_ledger_checks();
+
+    let txns = vec![
+        transfer(&mut h, &sender, &receiver, 1),
+        transfer(&mut h, &receiver, &sender, 2),
+    ];
+    for status in h.run_block(txns) {
+        assert_success!(status);
+    }
+
+    // Accounts created outside of blocks start a new ledger
+    let other = h.new_account_at(AccountAddress::from_hex_literal("0xf00d").unwrap());
+    let txns = vec![
+        transfer(&mut h, &other, &sender, 3),
+        transfer(&mut h, &sender, &other, u64::MAX),
+    ];
+    let statuses = h.run_block(txns);
+    assert_success!(statuses[0].clone());
+    assert_abort!(statuses[1].clone(), _);
+}
+
+#[test]
+fn test_ledger_check_rejects_other_outputs() {
+    let (mut h, sender, receiver) = setup();
+    let mut checker = LedgerChecker::new(h.executor.get_state_view()).unwrap();
+
+    let txn = transfer(&mut h, &sender, &receiver, 1);
+    let output = h
+        .executor
+        .execute_block(vec![txn.clone()])
+        .unwrap()
+        .remove(0);
+    h.executor.apply_write_set(output.write_set());
+    // The node charges the gas the harness did
+    let overcharged = TransactionOutput::new(
+        output.write_set().clone(),
+        output.events().to_vec(),
+        output.gas_used() + 1,
+        output.status().clone(),
+    );
+    let error = checker
+        .check_block(&[txn], &[overcharged], h.executor.get_state_view())
+        .unwrap_err();
+    assert!(
+        format!("{:#}", error).contains("on the node but"),
+        "{:#}",
+        error
+    );
+}
+
+#[test]
+fn test_ledger_check_rejects_unapplied_outputs() {
+    let (mut h, sender, receiver) = setup();
+    let mut checker = LedgerChecker::new(h.executor.get_state_view()).unwrap();
+
+    let txn = transfer(&mut h, &sender, &receiver, 1);
+    let outputs = h.executor.execute_block(vec![txn.clone()]).unwrap();
+    let error = checker
+        .check_block(&[txn], &outputs, h.executor.get_state_view())
+        .unwrap_err();
+    assert!(
+        format!("{:#}", error).contains("in the store but"),
+        "{:#}",
+        error
+    );
+}
@@ -17,10 +17,7 @@ fn module_loop_depth_at_limit() {
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("max_loop_depth.data/pack-good"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::MaxLoopDepthGood.path(),));
 }
 
 #[test]
@@ -30,7 +27,7 @@ fn module_loop_depth_just_above_limit() {
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
     assert_vm_status!(
-        h.publish_package(&acc, &common::test_dir_path("max_loop_depth.data/pack-bad"),),
+        h.publish_package(&acc, &common::Fixture::MaxLoopDepthBad.path(),),
         StatusCode::LOOP_MAX_DEPTH_REACHED
     );
 }
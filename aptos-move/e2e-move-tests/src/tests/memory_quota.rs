@@ -19,7 +19,7 @@ fn push_u128s_onto_vector() {
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
     assert_success!(h.publish_package(
         &acc,
-        &common::test_dir_path("memory_quota.data/vec_push_u128"),
+        &common::Fixture::VecPushU128.path(),
     ));
 
     let result = h.run_entry_function(
@@ -49,7 +49,7 @@ fn clone_large_vectors() {
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("memory_quota.data/clone_vec"),));
+    assert_success!(h.publish_package(&acc, &common::Fixture::CloneVec.path(),));
 
     let result = h.run_entry_function(
         &acc,
@@ -77,10 +77,7 @@ fn add_vec_to_table() {
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("memory_quota.data/table_and_vec"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::TableAndVec.path(),));
 
     let result = h.run_entry_function(
         &acc,
@@ -65,7 +65,7 @@ fn mint_nft_e2e() {
 
     // build the package from our example code
     let package = aptos_framework::BuiltPackage::build(
-        common::test_dir_path("../../../move-examples/mint_nft/4-Getting-Production-Ready"),
+        common::Fixture::MintNftProductionReady.path(),
         build_options,
     )
     .expect("building package must succeed");
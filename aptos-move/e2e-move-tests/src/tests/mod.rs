@@ -4,11 +4,16 @@
 mod access_path_test;
 mod aggregator;
 mod attributes;
+mod block_conflicts;
 mod chain_id;
 mod code_publishing;
 mod common;
 mod constructor_args;
+mod default_balance;
+mod derived_address;
 mod error_map;
+mod events;
+mod execution_hooks;
 mod framework_compatibility;
 mod gas;
 mod generate_upgrade_script;
@@ -16,15 +21,20 @@ mod governance_updates;
 mod infinite_loop;
 mod init_module;
 mod lazy_natives;
+mod ledger_check;
 mod max_loop_depth;
 mod memory_quota;
 mod metadata;
 mod mint_nft;
+#[cfg(feature = "failpoints")]
+mod native_faults;
 mod new_integer_types;
 mod nft_dao;
 mod offer_rotation_capability;
 mod offer_signer_capability;
 mod per_category_gas_limits;
+mod random_packages;
+mod repro_bundle;
 mod resource_groups;
 mod rotate_auth_key;
 mod scripts;
@@ -34,6 +44,8 @@ mod stake;
 mod string_args;
 mod token_event_store;
 mod token_objects;
+mod transaction_faults;
 mod transaction_fee;
 mod type_too_large;
+mod usage_report;
 mod vector_numeric_address;
@@ -0,0 +1,90 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_abort, assert_success, tests::common, MoveHarness, NativeFault};
+use aptos_language_e2e_tests::account::Account;
+use aptos_package_builder::PackageBuilder;
+use aptos_types::{account_address::AccountAddress, transaction::TransactionArgument};
+
+fn publish_faults(h: &mut MoveHarness) -> Account {
+    let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let mut builder = PackageBuilder::new("Faults");
+    builder.add_source(
+        "faults",
+        "
+module 0xcafe::faults {
+    use aptos_std::table;
+    struct Holder has key { t: table::Table<u64, u64> }
+    public entry fun add_to_table(s: &signer) {
+        let t = table::new();
+        table::add(&mut t, 1, 1);
+        move_to(s, Holder { t })
+    }
+    public entry fun hash_length() {
+        assert!(std::vector::length(&std::hash::sha3_256(b\"aptos\")) == 32, 42)
+    }
+}
+    ",
+    );
+    builder.add_local_dep(
+        "AptosFramework",
+        &common::framework_dir_path("aptos-framework")
+            .display()
+            .to_string(),
+    );
+    let dir = builder.write_to_temp().unwrap();
+    assert_success!(h.publish_package(&acc, dir.path()));
+    acc
+}
+
+#[test]
+fn test_native_abort() {
+    let mut h = MoveHarness::new();
+    let acc = publish_faults(&mut h);
+
+    h.fail_native(
+        AccountAddress::ONE,
+        "table",
+        "add_box",
+        NativeFault::Abort(7),
+    );
+    assert_abort!(
+        h.run_entry_function(
+            &acc,
+            str::parse("0xcafe::faults::add_to_table").unwrap(),
+            vec![],
+            vec![],
+        ),
+        7
+    );
+
+    h.clear_native_faults();
+    assert_success!(h.run_entry_function(
+        &acc,
+        str::parse("0xcafe::faults::add_to_table").unwrap(),
+        vec![],
+        vec![],
+    ));
+}
+
+#[test]
+fn test_native_return() {
+    let mut h = MoveHarness::new();
+    let acc = publish_faults(&mut h);
+
+    h.fail_native(
+        AccountAddress::ONE,
+        "hash",
+        "sha3_256",
+        NativeFault::Return(TransactionArgument::U8Vector(vec![0])),
+    );
+    assert_abort!(
+        h.run_entry_function(
+            &acc,
+            str::parse("0xcafe::faults::hash_length").unwrap(),
+            vec![],
+            vec![],
+        ),
+        42
+    );
+}
@@ -18,11 +18,9 @@ fn test_nft_dao_txn_arguments() {
         .insert("dao_platform".to_string(), *acc.address());
 
     // build the package from our example code
-    let package = aptos_framework::BuiltPackage::build(
-        common::test_dir_path("../../../move-examples/dao/nft_dao"),
-        build_options,
-    )
-    .expect("building package must succeed");
+    let package =
+        aptos_framework::BuiltPackage::build(common::Fixture::NftDao.path(), build_options)
+            .expect("building package must succeed");
 
     let code = package.extract_code();
     let metadata = package
@@ -0,0 +1,140 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential tests of publishing, upgrade compatibility and the `TypeAccessor` of the REST
+//! client against the compiler, with randomly generated modules.
+
+use crate::{assert_success, assert_vm_status, MoveHarness};
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_package_builder::{PackageBuilder, RandomModule, RandomModuleGenerator};
+use aptos_sdk::rest_client::{
+    aptos_api_types::MoveModule, Client, TypeAccessor, TypeAccessorBuilder,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    transaction::{EntryFunction, TransactionPayload},
+};
+use move_core_types::{
+    identifier::Identifier,
+    language_storage::{ModuleId, TypeTag},
+    vm_status::StatusCode,
+};
+
+const SEEDS: u64 = 8;
+
+fn package(address: AccountAddress, modules: &[RandomModule]) -> PackageBuilder {
+    let mut builder = PackageBuilder::new("Random");
+    for module in modules {
+        builder.add_random_module(address, module);
+    }
+    builder
+}
+
+/// Builds the package with the compiler, and parses the modules it compiled to.
+async fn type_accessor(builder: PackageBuilder) -> TypeAccessor {
+    let dir = builder.write_to_temp().unwrap();
+    let built = BuiltPackage::build(dir.path().to_path_buf(), BuildOptions::default()).unwrap();
+    TypeAccessorBuilder::new(Client::new("http://localhost:8080".parse().unwrap()))
+        .add_modules(built.modules().cloned().map(MoveModule::from))
+        .build()
+        .await
+        .unwrap()
+}
+
+fn struct_layout_hashes(
+    accessor: &TypeAccessor,
+    address: AccountAddress,
+    module: &RandomModule,
+) -> Vec<String> {
+    let module_id = ModuleId::new(address, Identifier::new(module.name.as_str()).unwrap());
+    (0..module.structs.len())
+        .map(|i| {
+            let name = Identifier::new(format!("S{}", i)).unwrap();
+            accessor
+                .struct_layout_hash(&module_id, &name)
+                .unwrap()
+                .to_hex()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn random_packages() {
+    let address = AccountAddress::from_hex_literal("0xcafe").unwrap();
+    for seed in 0..SEEDS {
+        let mut gen = RandomModuleGenerator::new(seed);
+        let mut h = MoveHarness::new();
+        let acc = h.new_account_at(address);
+        let (builder, modules) = gen.package("Random", address, 2);
+        let accessor = type_accessor(builder.clone()).await;
+        let dir = builder.write_to_temp().unwrap();
+        assert_success!(h.publish_package(&acc, dir.path()));
+
+        // The accessor decodes the arguments the VM accepts, with the declared types
+        for module in &modules {
+            let module_id = ModuleId::new(address, Identifier::new(module.name.as_str()).unwrap());
+            for (i, function) in module.functions.iter().enumerate() {
+                if !function.is_entry {
+                    continue;
+                }
+                let args = gen.entry_function_args(function);
+                let payload = TransactionPayload::EntryFunction(EntryFunction::new(
+                    module_id.clone(),
+                    Identifier::new(format!("fun{}", i)).unwrap(),
+                    vec![TypeTag::U8; function.type_params],
+                    args.iter()
+                        .map(|arg| arg.simple_serialize().unwrap())
+                        .collect(),
+                ));
+                let decoded = accessor.decode_entry_function_args(&payload).unwrap();
+                assert_eq!(
+                    decoded
+                        .iter()
+                        .map(|(_, ty, _)| ty.to_string())
+                        .collect::<Vec<_>>(),
+                    function
+                        .params
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>(),
+                    "seed {}",
+                    seed
+                );
+                assert_success!(h.run_transaction_payload(&acc, payload));
+            }
+        }
+
+        // Compatible upgrades keep the layout of the existing structs
+        let upgrades: Vec<_> = modules
+            .iter()
+            .map(|module| gen.compatible_upgrade(module))
+            .collect();
+        let upgraded_accessor = type_accessor(package(address, &upgrades)).await;
+        for module in &modules {
+            assert_eq!(
+                struct_layout_hashes(&accessor, address, module),
+                struct_layout_hashes(&upgraded_accessor, address, module),
+                "seed {}",
+                seed
+            );
+        }
+        let dir = package(address, &upgrades).write_to_temp().unwrap();
+        assert_success!(h.publish_package(&acc, dir.path()));
+
+        // Incompatible upgrades change the layout of a struct
+        let mut incompatible = upgrades.clone();
+        incompatible[0] = gen.incompatible_upgrade(&upgrades[0]);
+        let incompatible_accessor = type_accessor(package(address, &incompatible)).await;
+        assert_ne!(
+            struct_layout_hashes(&upgraded_accessor, address, &upgrades[0]),
+            struct_layout_hashes(&incompatible_accessor, address, &upgrades[0]),
+            "seed {}",
+            seed
+        );
+        let dir = package(address, &incompatible).write_to_temp().unwrap();
+        assert_vm_status!(
+            h.publish_package(&acc, dir.path()),
+            StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+        );
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{MoveHarness, ReproBundle};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{account_address::AccountAddress, account_config::CoinStoreResource};
+use move_core_types::move_resource::MoveStructType;
+
+fn balance(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+#[test]
+fn test_seed_determines_accounts() {
+    let mut h1 = MoveHarness::new().with_seed(42);
+    let mut h2 = MoveHarness::new().with_seed(42);
+    assert_eq!(h1.seed(), 42);
+    assert_eq!(
+        h1.new_account_with_key_pair().address(),
+        h2.new_account_with_key_pair().address()
+    );
+    assert_ne!(
+        h1.new_account_with_key_pair().address(),
+        MoveHarness::new()
+            .with_seed(43)
+            .new_account_with_key_pair()
+            .address()
+    );
+}
+
+#[test]
+fn test_replay_repro_bundle() {
+    let mut h = MoveHarness::new().with_seed(7);
+    let sender = h.new_account_with_key_pair();
+    let receiver = h.new_account_with_key_pair();
+    let txns = vec![
+        h.create_transaction_payload(
+            &sender,
+            aptos_stdlib::aptos_account_transfer(*receiver.address(), 100),
+        ),
+        h.create_transaction_payload(
+            &sender,
+            aptos_stdlib::aptos_account_transfer(*receiver.address(), 200),
+        ),
+    ];
+    let statuses = h.run_block(txns.clone());
+
+    let dir = tempfile::tempdir().unwrap();
+    h.export_repro_bundle(dir.path()).unwrap();
+    let bundle = ReproBundle::read_from(dir.path()).unwrap();
+    assert_eq!(bundle.seed, 7);
+    assert_eq!(bundle.transactions, txns);
+
+    let (replayed, replayed_statuses) = MoveHarness::replay_repro_bundle(dir.path()).unwrap();
+    assert_eq!(replayed_statuses, statuses);
+    assert_eq!(replayed.seed(), 7);
+    for account in [sender.address(), receiver.address()] {
+        assert_eq!(balance(&replayed, account), balance(&h, account));
+    }
+}
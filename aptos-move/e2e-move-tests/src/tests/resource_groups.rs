@@ -35,7 +35,7 @@ fn test_resource_groups() {
 
     let result = h.publish_package_with_options(
         &primary_account,
-        &common::test_dir_path("../../../move-examples/resource_groups/primary"),
+        &common::Fixture::ResourceGroupsPrimary.path(),
         build_options.clone(),
     );
     assert_success!(result);
@@ -45,7 +45,7 @@ fn test_resource_groups() {
         .insert("resource_groups_secondary".to_string(), secondary_addr);
     let result = h.publish_package_with_options(
         &secondary_account,
-        &common::test_dir_path("../../../move-examples/resource_groups/secondary"),
+        &common::Fixture::ResourceGroupsSecondary.path(),
         build_options,
     );
     assert_success!(result);
@@ -237,7 +237,7 @@ fn test_resource_groups_container_not_enabled() {
 
     let result = h.publish_package_with_options(
         &primary_account,
-        &common::test_dir_path("../../../move-examples/resource_groups/primary"),
+        &common::Fixture::ResourceGroupsPrimary.path(),
         build_options.clone(),
     );
     assert_success!(result);
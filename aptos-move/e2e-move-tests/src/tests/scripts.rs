@@ -33,7 +33,7 @@ fn test_two_to_two_transfer() {
     };
 
     let package = aptos_framework::BuiltPackage::build(
-        common::test_dir_path("../../../move-examples/scripts/two_by_two_transfer"),
+        common::Fixture::TwoByTwoTransfer.path(),
         build_options,
     )
     .expect("building package must succeed");
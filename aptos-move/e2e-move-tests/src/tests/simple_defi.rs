@@ -37,11 +37,8 @@ fn exchange_e2e_test() {
     build_options
         .named_addresses
         .insert("resource_account".to_string(), resource_address);
-    let package = BuiltPackage::build(
-        common::test_dir_path("../../../move-examples/resource_account"),
-        build_options,
-    )
-    .expect("building package must succeed");
+    let package = BuiltPackage::build(common::Fixture::ResourceAccount.path(), build_options)
+        .expect("building package must succeed");
     let code = package.extract_code();
     let metadata = package
         .extract_metadata()
@@ -14,7 +14,7 @@ fn test_smart_data_structures_gas() {
     let mut h = MoveHarness::new();
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("smart_data_structures.data")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::SmartDataStructures.path()));
 
     print_gas_cost(
         "huge_smart_vector_create_gas",
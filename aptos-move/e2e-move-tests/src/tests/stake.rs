@@ -4,7 +4,9 @@
 use crate::{
     assert_abort, assert_success, get_stake_pool, get_validator_config, get_validator_set,
     initialize_staking, join_validator_set, leave_validator_set, rotate_consensus_key,
-    setup_staking, tests::common, unlock_stake, withdraw_stake, MoveHarness,
+    setup_staking,
+    tests::common::{self, Fixture},
+    unlock_stake, withdraw_stake, MoveHarness,
 };
 use aptos_cached_packages::aptos_stdlib;
 use aptos_types::account_address::{default_stake_pool_address, AccountAddress};
@@ -15,9 +17,7 @@ use std::collections::BTreeMap;
 pub static PROPOSAL_SCRIPTS: Lazy<BTreeMap<String, Vec<u8>>> = Lazy::new(build_scripts);
 
 fn build_scripts() -> BTreeMap<String, Vec<u8>> {
-    let package_folder = "stake.data";
-    let package_names = vec!["enable_rewards_rate_decrease"];
-    common::build_scripts(package_folder, package_names)
+    common::build_scripts(&[Fixture::EnableRewardsRateDecrease])
 }
 
 fn update_stake_amount_and_assert_with_errors(
@@ -29,7 +29,7 @@ fn success_generic(ty_args: Vec<TypeTag>, tests: Vec<(&str, Vec<(Vec<Vec<u8>>, &
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("string_args.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::StringArgs.path()));
 
     let mut module_data = parse_struct_tag("0xCAFE::test::ModuleData").unwrap();
     let string_struct = StructTag {
@@ -90,7 +90,7 @@ fn fail_generic(
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
-    assert_success!(h.publish_package(&acc, &common::test_dir_path("string_args.data/pack")));
+    assert_success!(h.publish_package(&acc, &common::Fixture::StringArgs.path()));
 
     let module_data = parse_struct_tag("0xCAFE::test::ModuleData").unwrap();
 
@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::MoveHarness;
+use crate::{assert_events, MoveHarness};
 use aptos_cached_packages::aptos_stdlib::aptos_token_stdlib;
 
 #[test]
@@ -48,6 +48,7 @@ fn test_token_creation_with_token_events_store() {
     let signed_txn =
         h.create_transaction_payload(&acc, aptos_token_stdlib::token_opt_in_direct_transfer(true));
     let (_, mut events) = h.run_with_events(signed_txn);
+    assert_events!(events, [("0x3::token_event_store::OptInTransferEvent", 1)]);
     let event = events.pop().unwrap();
     assert_eq!(
         "0x3::token_event_store::OptInTransferEvent".to_string(),
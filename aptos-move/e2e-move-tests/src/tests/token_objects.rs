@@ -41,7 +41,7 @@ fn test_basic_token() {
 
     let result = h.publish_package_with_options(
         &account,
-        &common::test_dir_path("../../../move-examples/token_objects"),
+        &common::Fixture::TokenObjects.path(),
         build_options,
     );
     assert_success!(result);
@@ -0,0 +1,112 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{assert_success, enable_golden, MoveHarness, TransactionFault};
+use aptos_cached_packages::aptos_stdlib;
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::CoinStoreResource,
+    transaction::{ExecutionStatus, TransactionStatus},
+};
+use move_core_types::move_resource::MoveStructType;
+
+fn balance(h: &MoveHarness, account: &AccountAddress) -> u64 {
+    h.read_resource::<CoinStoreResource>(account, CoinStoreResource::struct_tag())
+        .unwrap()
+        .coin()
+}
+
+#[test]
+fn test_prologue_faults_discard() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+    // Discarded transactions emit no event and write nothing, not even the fee
+    enable_golden!(h);
+
+    for fault in [
+        TransactionFault::BadChainId,
+        TransactionFault::Expired,
+        TransactionFault::SequenceNumberTooOld,
+        TransactionFault::SequenceNumberTooNew,
+        TransactionFault::InsufficientBalance,
+    ] {
+        h.inject_fault(fault);
+        let txn = h.create_transaction_payload(
+            &sender,
+            aptos_stdlib::aptos_account_transfer(*receiver.address(), 1),
+        );
+        let sender_balance = balance(&h, sender.address());
+        let receiver_balance = balance(&h, receiver.address());
+        let sequence_number = h.sequence_number(sender.address());
+
+        assert_eq!(
+            h.run(txn),
+            TransactionStatus::Discard(fault.discard_status().unwrap()),
+            "{:?}",
+            fault
+        );
+        assert_eq!(balance(&h, sender.address()), sender_balance);
+        assert_eq!(balance(&h, receiver.address()), receiver_balance);
+        assert_eq!(h.sequence_number(sender.address()), sequence_number);
+    }
+}
+
+#[test]
+fn test_prologue_fault_frees_sequence_number() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+
+    h.inject_fault(TransactionFault::BadChainId);
+    assert!(matches!(
+        h.run_transaction_payload(
+            &sender,
+            aptos_stdlib::aptos_account_transfer(*receiver.address(), 1),
+        ),
+        TransactionStatus::Discard(_)
+    ));
+
+    // The sequence number of the discarded transaction is still free
+    assert_success!(h.run_transaction_payload(
+        &sender,
+        aptos_stdlib::aptos_account_transfer(*receiver.address(), 1),
+    ));
+}
+
+#[test]
+fn test_epilogue_fault_charges_fee() {
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_at(AccountAddress::from_hex_literal("0xcafe").unwrap());
+    let receiver = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
+    let gas_unit_price = 100;
+    h.set_default_gas_unit_price(gas_unit_price);
+
+    // Spend all the balance left by the fault, so that the fee can't be paid.
+    let max_fee = h.max_transaction_fee();
+    h.inject_fault(TransactionFault::EpilogueInsufficientBalance);
+    let txn = h.create_transaction_payload(
+        &sender,
+        aptos_stdlib::aptos_account_transfer(*receiver.address(), max_fee),
+    );
+    assert_eq!(balance(&h, sender.address()), max_fee);
+    let receiver_balance = balance(&h, receiver.address());
+    let sequence_number = h.sequence_number(sender.address());
+
+    let output = h.run_raw(txn);
+    assert!(
+        matches!(
+            output.status(),
+            TransactionStatus::Keep(ExecutionStatus::MoveAbort { .. })
+        ),
+        "{:?}",
+        output.status()
+    );
+    // The transfer is reverted, but the fee is charged and the sequence number used.
+    assert_eq!(
+        balance(&h, sender.address()),
+        max_fee - output.gas_used() * gas_unit_price
+    );
+    assert_eq!(balance(&h, receiver.address()), receiver_balance);
+    assert_eq!(h.sequence_number(sender.address()), sequence_number + 1);
+}
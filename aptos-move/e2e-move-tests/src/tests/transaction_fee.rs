@@ -2,7 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    assert_success, get_stake_pool, setup_staking, tests::common, transaction_fee, MoveHarness,
+    assert_success, get_stake_pool, setup_staking,
+    tests::common::{self, Fixture},
+    transaction_fee, MoveHarness,
 };
 use aptos_cached_packages::aptos_stdlib;
 use aptos_language_e2e_tests::account::Account;
@@ -22,15 +24,13 @@ use std::collections::BTreeMap;
 pub static PROPOSAL_SCRIPTS: Lazy<BTreeMap<String, Vec<u8>>> = Lazy::new(build_scripts);
 
 fn build_scripts() -> BTreeMap<String, Vec<u8>> {
-    let package_folder = "transaction_fee.data";
-    let package_names = vec![
-        "initialize_collection",
-        "enable_collection",
-        "disable_collection",
-        "upgrade_burn_percentage",
-        "remove_validator",
-    ];
-    common::build_scripts(package_folder, package_names)
+    common::build_scripts(&[
+        Fixture::InitializeCollection,
+        Fixture::EnableCollection,
+        Fixture::DisableCollection,
+        Fixture::UpgradeBurnPercentage,
+        Fixture::RemoveValidator,
+    ])
 }
 
 // Constants for calculating rewards for validators at the end of each epoch.
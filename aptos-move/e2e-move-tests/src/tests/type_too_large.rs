@@ -10,10 +10,7 @@ fn type_too_large() {
 
     // Load the code
     let acc = h.new_account_at(AccountAddress::from_hex_literal("0xbeef").unwrap());
-    assert_success!(h.publish_package(
-        &acc,
-        &common::test_dir_path("type_too_large.data/type_too_large"),
-    ));
+    assert_success!(h.publish_package(&acc, &common::Fixture::TypeTooLarge.path(),));
 
     let result = h.run_entry_function(
         &acc,
@@ -0,0 +1,45 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{usage_report::UsageRecorder, MoveHarness, UsageReport};
+use aptos_cached_packages::aptos_stdlib;
+
+#[test]
+fn test_usage_report_sums_harnesses_of_a_test() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("usage.jsonl");
+    let mut h = MoveHarness::new();
+    let sender = h.new_account_with_key_pair();
+    let receiver = h.new_account_with_key_pair();
+
+    for test in ["transfers", "transfers", "idle"] {
+        let mut recorder = UsageRecorder::new(path.clone(), test.to_string());
+        if test == "transfers" {
+            let txn = h.create_transaction_payload(
+                &sender,
+                aptos_stdlib::aptos_account_transfer(*receiver.address(), 1),
+            );
+            let output = h.executor.execute_transaction(txn);
+            recorder.record(h.executor.get_state_view(), &output);
+            h.executor.apply_write_set(output.write_set());
+        }
+        recorder.finish();
+    }
+
+    let report = UsageReport::read_from(&path).unwrap();
+    let transfers = report
+        .tests
+        .iter()
+        .find(|usage| usage.test == "transfers")
+        .unwrap();
+    assert_eq!(report.tests.len(), 2);
+    assert_eq!(transfers.transactions, 2);
+    // Transfers between existing accounts only modify state
+    assert_eq!(transfers.state_items_growth, 0);
+    let idle = report
+        .tests
+        .iter()
+        .find(|usage| usage.test == "idle")
+        .unwrap();
+    assert_eq!(idle.transactions, 0);
+}
@@ -0,0 +1,168 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{Context, Result};
+use aptos_language_e2e_tests::data_store::FakeDataStore;
+use aptos_types::transaction::{TransactionOutput, TransactionStatus};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+/// The environment variable naming the file the harnesses created from then on append their
+/// resource usage to, one JSON object per line, see `UsageReport`. Unset by default, in which
+/// case nothing is recorded.
+pub const USAGE_REPORT_ENV_VAR: &str = "E2E_MOVE_TESTS_USAGE_REPORT";
+
+/// The resources a test used, summed over the harnesses it created.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TestUsage {
+    /// The name of the test, as given by the test runner to the thread running it.
+    pub test: String,
+    /// From the creation of each harness until it was dropped.
+    pub wall_time_ms: u64,
+    /// The transactions whose output was applied, kept or discarded. Transactions executed only
+    /// to be compared, e.g. by `run_block_check_determinism`, aren't counted.
+    pub transactions: u64,
+    /// How many state items the transactions created, less the ones they deleted.
+    pub state_items_growth: i64,
+    /// How many bytes of state values the transactions added, less the ones they removed.
+    pub state_bytes_growth: i64,
+    /// The peak resident memory of the test process when the harness was dropped. Tests of a
+    /// binary run in the same process, so this is an upper bound of what the test itself used.
+    /// `None` where it can't be read, i.e. outside of Linux.
+    pub peak_memory_kb: Option<u64>,
+}
+
+impl TestUsage {
+    /// Adds the usage of another harness of the same test.
+    fn merge(&mut self, other: &TestUsage) {
+        self.wall_time_ms += other.wall_time_ms;
+        self.transactions += other.transactions;
+        self.state_items_growth += other.state_items_growth;
+        self.state_bytes_growth += other.state_bytes_growth;
+        self.peak_memory_kb = self.peak_memory_kb.max(other.peak_memory_kb);
+    }
+}
+
+/// The usage of every test recorded in a file named by `USAGE_REPORT_ENV_VAR`, slowest first, to
+/// find the tests worth optimizing.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UsageReport {
+    pub tests: Vec<TestUsage>,
+}
+
+impl UsageReport {
+    /// Reads the usage the harnesses appended to `path`, summing the harnesses of each test.
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read usage report {}", path.display()))?;
+        let mut tests: BTreeMap<String, TestUsage> = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let usage: TestUsage = serde_json::from_str(line).with_context(|| {
+                format!("Invalid usage at line {} of {}", index + 1, path.display())
+            })?;
+            tests
+                .entry(usage.test.clone())
+                .and_modify(|test| test.merge(&usage))
+                .or_insert(usage);
+        }
+        let mut tests: Vec<TestUsage> = tests.into_values().collect();
+        tests.sort_by(|a, b| b.wall_time_ms.cmp(&a.wall_time_ms));
+        Ok(Self { tests })
+    }
+
+    /// Writes the report as a single JSON document, e.g. as an artifact of a CI run.
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("Failed to write usage report {}", path.display()))
+    }
+}
+
+/// Records the usage of a harness, from its creation until it is dropped.
+pub(crate) struct UsageRecorder {
+    path: PathBuf,
+    start: Instant,
+    usage: TestUsage,
+}
+
+impl UsageRecorder {
+    /// A recorder appending to the file named by `USAGE_REPORT_ENV_VAR`, if it is set.
+    pub fn from_env() -> Option<Self> {
+        let path = env::var_os(USAGE_REPORT_ENV_VAR)?;
+        let test = std::thread::current()
+            .name()
+            .unwrap_or("<unnamed>")
+            .to_string();
+        Some(Self::new(PathBuf::from(path), test))
+    }
+
+    pub fn new(path: PathBuf, test: String) -> Self {
+        Self {
+            path,
+            start: Instant::now(),
+            usage: TestUsage {
+                test,
+                ..TestUsage::default()
+            },
+        }
+    }
+
+    /// Records `output`, about to be applied to `store`.
+    pub fn record(&mut self, store: &FakeDataStore, output: &TransactionOutput) {
+        self.usage.transactions += 1;
+        if !matches!(output.status(), TransactionStatus::Keep(_)) {
+            return;
+        }
+        for (key, op) in output.write_set() {
+            let before = store.inner().get(key).map(|value| value.len());
+            let after = op.bytes().map(|value| value.len());
+            self.usage.state_items_growth += after.is_some() as i64 - before.is_some() as i64;
+            self.usage.state_bytes_growth += after.unwrap_or(0) as i64 - before.unwrap_or(0) as i64;
+        }
+    }
+
+    /// Appends the usage to the report file. Failing to write it doesn't fail the test.
+    pub fn finish(mut self) {
+        self.usage.wall_time_ms = self.start.elapsed().as_millis() as u64;
+        self.usage.peak_memory_kb = peak_memory_kb();
+        let result = serde_json::to_string(&self.usage)
+            .map_err(anyhow::Error::from)
+            .and_then(|mut line| {
+                line.push('\n');
+                // A single write of the whole line, so that lines of tests running in parallel
+                // don't interleave
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.path)?
+                    .write_all(line.as_bytes())?;
+                Ok(())
+            });
+        if let Err(e) = result {
+            eprintln!(
+                "Failed to record the usage of {} in {}: {:#}",
+                self.usage.test,
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// The peak resident memory of the process, from `/proc/self/status`.
+fn peak_memory_kb() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
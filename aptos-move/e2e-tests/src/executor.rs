@@ -16,9 +16,10 @@ use aptos_bitvec::BitVec;
 use aptos_crypto::HashValue;
 use aptos_framework::ReleaseBundle;
 use aptos_gas::{
-    AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters,
+    AbstractValueSizeGasParameters, ChangeSetConfigs, NativeGasParameters, StandardGasMeter,
     LATEST_GAS_FEATURE_VERSION,
 };
+use aptos_gas_profiling::{ExecutionHook, HookedGasMeter};
 use aptos_keygen::KeyGen;
 use aptos_state_view::TStateView;
 use aptos_types::{
@@ -47,6 +48,7 @@ use aptos_vm::{
     AptosVM, VMExecutor, VMValidator,
 };
 use aptos_vm_genesis::{generate_genesis_change_set_for_testing_with_count, GenesisOptions};
+use aptos_vm_logging::log_schema::AdapterLogSchema;
 use move_core_types::{
     account_address::AccountAddress,
     identifier::Identifier,
@@ -388,7 +390,22 @@ impl FakeExecutor {
         &self,
         txn_block: Vec<Transaction>,
     ) -> Result<Vec<TransactionOutput>, VMStatus> {
-        BlockAptosVM::execute_block(txn_block, &self.data_store, usize::min(4, num_cpus::get()))
+        self.execute_transaction_block_with_concurrency(txn_block, 4)
+    }
+
+    /// Executes the given block of transactions with the parallel executor, using
+    /// `concurrency_level` workers, or as many as there are CPUs if there are fewer. Like
+    /// `execute_block`, this doesn't apply the results to the data store.
+    pub fn execute_transaction_block_with_concurrency(
+        &self,
+        txn_block: Vec<Transaction>,
+        concurrency_level: usize,
+    ) -> Result<Vec<TransactionOutput>, VMStatus> {
+        BlockAptosVM::execute_block(
+            txn_block,
+            &self.data_store,
+            usize::min(concurrency_level, num_cpus::get()),
+        )
     }
 
     pub fn execute_transaction_block(
@@ -453,6 +470,35 @@ impl FakeExecutor {
             .expect("A block with one transaction should have one output")
     }
 
+    /// Executes the transaction with `hook` called on its function calls and storage accesses,
+    /// e.g. an `ExecutionTracer` to see what it did. The output isn't applied.
+    pub fn execute_transaction_with_hook<H: ExecutionHook>(
+        &self,
+        txn: SignedTransaction,
+        hook: H,
+    ) -> (TransactionOutput, H) {
+        let txn = txn
+            .check_signature()
+            .expect("The transaction should be signed");
+        let log_context = AdapterLogSchema::new(self.data_store.id(), 0);
+        let (_, output, gas_meter) = AptosVM::execute_user_transaction_with_custom_gas_meter(
+            &self.data_store,
+            &txn,
+            &log_context,
+            |gas_feature_version, gas_params, storage_gas_params, balance| {
+                let gas_meter = StandardGasMeter::new(
+                    gas_feature_version,
+                    gas_params,
+                    storage_gas_params,
+                    balance,
+                );
+                Ok(HookedGasMeter::for_payload(gas_meter, hook, txn.payload()))
+            },
+        )
+        .expect("The VM should not fail to startup");
+        (output, gas_meter.into_inner().1)
+    }
+
     fn trace<P: AsRef<Path>, T: Serialize>(dir: P, item: &T) -> usize {
         let dir = dir.as_ref();
         let seq = fs::read_dir(dir).expect("Unable to read trace dir").count();
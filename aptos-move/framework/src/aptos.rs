@@ -117,6 +117,7 @@ impl ReleaseTarget {
                 }),
                 skip_fetch_latest_git_deps: false,
                 bytecode_version: None,
+                max_publish_size: None,
             },
             packages: packages.iter().map(|(path, _)| path.to_owned()).collect(),
             rust_bindings: packages
@@ -60,6 +60,11 @@ pub struct BuildOptions {
     pub skip_fetch_latest_git_deps: bool,
     #[clap(long)]
     pub bytecode_version: Option<u32>,
+    /// Maximal size in bytes of the code and metadata of the package together. When the
+    /// metadata would exceed it, source maps and then sources are left out of it, starting with
+    /// the largest modules.
+    #[clap(long)]
+    pub max_publish_size: Option<usize>,
 }
 
 // Because named_addresses has no parser, we can't use clap's default impl. This must be aligned
@@ -79,6 +84,7 @@ impl Default for BuildOptions {
             // while in a test (and cause some havoc)
             skip_fetch_latest_git_deps: false,
             bytecode_version: None,
+            max_publish_size: None,
         }
     }
 }
@@ -242,6 +248,17 @@ impl BuiltPackage {
             })
     }
 
+    /// Returns an iterator for the modules of the package along with the modules of its
+    /// dependencies.
+    pub fn all_modules(&self) -> impl Iterator<Item = &CompiledModule> {
+        self.package
+            .all_modules()
+            .filter_map(|unit| match &unit.unit {
+                CompiledUnit::Module(NamedCompiledModule { module, .. }) => Some(module),
+                CompiledUnit::Script(_) => None,
+            })
+    }
+
     /// Returns the number of scripts in the package.
     pub fn script_count(&self) -> usize {
         self.package.scripts().count()
@@ -314,7 +331,7 @@ impl BuiltPackage {
             .collect::<BTreeSet<_>>()
             .into_iter()
             .collect();
-        Ok(PackageMetadata {
+        let mut metadata = PackageMetadata {
             name: self.name().to_string(),
             upgrade_policy,
             upgrade_number: 0,
@@ -323,7 +340,12 @@ impl BuiltPackage {
             modules,
             deps,
             extension: MoveOption::none(),
-        })
+        };
+        if let Some(max_publish_size) = self.options.max_publish_size {
+            let code_size = self.extract_code().iter().map(Vec::len).sum::<usize>();
+            fit_metadata(&mut metadata, max_publish_size.saturating_sub(code_size))?;
+        }
+        Ok(metadata)
     }
 
     pub fn extract_metadata_and_save(&self) -> anyhow::Result<()> {
@@ -335,6 +357,27 @@ impl BuiltPackage {
     }
 }
 
+/// Drops source maps, then sources, from the modules with the largest ones until `metadata` is at
+/// most `max_size` bytes, or has none left.
+fn fit_metadata(metadata: &mut PackageMetadata, max_size: usize) -> anyhow::Result<()> {
+    let artifacts: [fn(&mut ModuleMetadata) -> &mut Vec<u8>; 2] =
+        [|m| &mut m.source_map, |m| &mut m.source];
+    for artifact in artifacts {
+        while bcs::serialized_size(metadata)? > max_size {
+            match metadata
+                .modules
+                .iter_mut()
+                .map(|m| artifact(m))
+                .max_by_key(|a| a.len())
+            {
+                Some(largest) if !largest.is_empty() => largest.clear(),
+                _ => break,
+            }
+        }
+    }
+    Ok(())
+}
+
 fn extract_custom_fields(toml: &str) -> anyhow::Result<BTreeMap<String, String>> {
     let manifest = parse_source_manifest(parse_move_manifest_string(toml.to_owned())?)?;
     Ok(manifest
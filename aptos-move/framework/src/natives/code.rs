@@ -6,7 +6,7 @@ use crate::{
         any::Any,
         helpers::{make_safe_native, SafeNativeContext, SafeNativeError, SafeNativeResult},
     },
-    safely_pop_arg,
+    safely_pop_arg, unzip_metadata, unzip_metadata_str,
 };
 use anyhow::bail;
 use aptos_types::{
@@ -16,6 +16,7 @@ use aptos_types::{
 };
 use better_any::{Tid, TidAble};
 use move_binary_format::errors::{PartialVMError, PartialVMResult};
+use move_bytecode_source_map::source_map::SourceMap;
 use move_core_types::{
     account_address::AccountAddress,
     gas_algebra::{InternalGas, InternalGasPerByte, NumBytes},
@@ -103,6 +104,24 @@ pub struct ModuleMetadata {
     pub extension: MoveOption<Any>,
 }
 
+impl ModuleMetadata {
+    /// The source of the module, if it was published with it.
+    pub fn source(&self) -> anyhow::Result<Option<String>> {
+        if self.source.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(unzip_metadata_str(&self.source)?))
+    }
+
+    /// The source map of the module, if it was published with it.
+    pub fn source_map(&self) -> anyhow::Result<Option<SourceMap>> {
+        if self.source_map.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(bcs::from_bytes(&unzip_metadata(&self.source_map)?)?))
+    }
+}
+
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct UpgradePolicy {
     pub policy: u8,
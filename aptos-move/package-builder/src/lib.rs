@@ -1,10 +1,16 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+mod malformed;
+mod policy_matrix;
+mod random;
 use aptos_framework::natives::code::UpgradePolicy;
 use itertools::Itertools;
+pub use malformed::{Malformation, MalformedPackage, MalformedPackageBuilder};
 use move_command_line_common::files::MOVE_EXTENSION;
 use move_package::compilation::package_layout::CompiledPackageLayout;
+pub use policy_matrix::{PolicyTransition, UpgradeKind, POLICY_MATRIX_ADDRESS_NAME};
+pub use random::{RandomFunction, RandomModule, RandomModuleGenerator, RandomStruct, RandomType};
 use std::path::Path;
 use tempfile::{tempdir, TempDir};
 
@@ -0,0 +1,148 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::PackageBuilder;
+use anyhow::{bail, Context};
+use aptos_framework::{natives::code::PackageMetadata, BuildOptions, BuiltPackage};
+use move_binary_format::file_format_common::{
+    read_u8, read_uleb128_as_u64, BinaryConstants, TableType, IDENTIFIER_SIZE_MAX,
+};
+use std::io::Cursor;
+
+/// A way of breaking a valid package, to test that publishing catches it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Malformation {
+    /// The metadata lists a module whose code is missing.
+    ModuleWithoutCode,
+    /// The code has a module the metadata doesn't list.
+    ModuleWithoutMetadata,
+    /// The first module is in both the code and the metadata twice.
+    DuplicateModule,
+    /// The code of the first module declares an identifier longer than the binary format allows.
+    /// Only the length of the identifier is encoded, as deserialization fails on it, which keeps
+    /// the transaction under the size limit.
+    OversizedIdentifier,
+    /// The metadata claims an upgrade number, which publishing must ignore as it assigns its own.
+    UpgradeNumber(u64),
+}
+
+/// The metadata and code of a package, as passed to `0x1::code::publish_package_txn`.
+#[derive(Clone, Debug)]
+pub struct MalformedPackage {
+    pub metadata: PackageMetadata,
+    pub code: Vec<Vec<u8>>,
+}
+
+impl MalformedPackage {
+    pub fn metadata_bytes(&self) -> Vec<u8> {
+        bcs::to_bytes(&self.metadata).expect("PackageMetadata has BCS")
+    }
+}
+
+/// Builds the package of a [`PackageBuilder`], then breaks it with each of the given
+/// [`Malformation`]s in turn.
+#[derive(Debug, Clone)]
+pub struct MalformedPackageBuilder {
+    package: PackageBuilder,
+    malformations: Vec<Malformation>,
+}
+
+impl MalformedPackageBuilder {
+    pub fn new(package: PackageBuilder) -> Self {
+        Self {
+            package,
+            malformations: vec![],
+        }
+    }
+
+    pub fn with(mut self, malformation: Malformation) -> Self {
+        self.malformations.push(malformation);
+        self
+    }
+
+    pub fn build(self) -> anyhow::Result<MalformedPackage> {
+        let dir = self.package.write_to_temp()?;
+        let built = BuiltPackage::build(dir.path().to_path_buf(), BuildOptions::default())?;
+        let mut package = MalformedPackage {
+            metadata: built.extract_metadata()?,
+            code: built.extract_code(),
+        };
+        for malformation in self.malformations {
+            if package.code.is_empty() || package.metadata.modules.is_empty() {
+                bail!("{:?} needs a package with modules", malformation)
+            }
+            match malformation {
+                Malformation::ModuleWithoutCode => {
+                    package.code.pop();
+                },
+                Malformation::ModuleWithoutMetadata => {
+                    package.metadata.modules.pop();
+                },
+                Malformation::DuplicateModule => {
+                    package.code.push(package.code[0].clone());
+                    let module = package.metadata.modules[0].clone();
+                    package.metadata.modules.push(module);
+                },
+                Malformation::OversizedIdentifier => {
+                    package.code[0] = add_oversized_identifier(&package.code[0])?;
+                },
+                Malformation::UpgradeNumber(upgrade_number) => {
+                    package.metadata.upgrade_number = upgrade_number;
+                },
+            }
+        }
+        Ok(package)
+    }
+}
+
+/// Appends the length of an identifier above `IDENTIFIER_SIZE_MAX` to the identifier table of
+/// the serialized `module`, shifting the tables which follow it.
+fn add_oversized_identifier(module: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut cursor = Cursor::new(module);
+    // Magic and version
+    cursor.set_position(BinaryConstants::MOVE_MAGIC_SIZE as u64 + 4);
+    let table_count = read_uleb128_as_u64(&mut cursor)?;
+    let mut tables = vec![];
+    for _ in 0..table_count {
+        let kind = read_u8(&mut cursor)?;
+        let offset = read_uleb128_as_u64(&mut cursor)?;
+        let count = read_uleb128_as_u64(&mut cursor)?;
+        tables.push((kind, offset, count));
+    }
+    let (header, data) = module.split_at(cursor.position() as usize);
+
+    let mut identifier = vec![];
+    write_uleb128(&mut identifier, IDENTIFIER_SIZE_MAX + 1);
+    let (_, offset, count) = *tables
+        .iter()
+        .find(|(kind, _, _)| *kind == TableType::IDENTIFIERS as u8)
+        .context("module has no identifiers")?;
+    let end = offset + count;
+    for (kind, offset, count) in &mut tables {
+        if *kind == TableType::IDENTIFIERS as u8 {
+            *count += identifier.len() as u64;
+        } else if *offset >= end {
+            *offset += identifier.len() as u64;
+        }
+    }
+
+    let mut patched = header[..BinaryConstants::MOVE_MAGIC_SIZE + 4].to_vec();
+    write_uleb128(&mut patched, table_count);
+    for (kind, offset, count) in tables {
+        patched.push(kind);
+        write_uleb128(&mut patched, offset);
+        write_uleb128(&mut patched, count);
+    }
+    patched.extend_from_slice(&data[..end as usize]);
+    patched.extend_from_slice(&identifier);
+    patched.extend_from_slice(&data[end as usize..]);
+    Ok(patched)
+}
+
+fn write_uleb128(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push((value as u8 & 0x7F) | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
@@ -0,0 +1,108 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::PackageBuilder;
+use aptos_framework::natives::code::UpgradePolicy;
+use std::fmt;
+
+/// The named address of the modules of the packages of a [`PolicyTransition`].
+pub const POLICY_MATRIX_ADDRESS_NAME: &str = "policy_matrix";
+
+const PACKAGE_NAME: &str = "PolicyMatrix";
+const MODULE_NAME: &str = "policy_matrix";
+
+/// How the upgrade of a [`PolicyTransition`] changes the modules of the initial package.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UpgradeKind {
+    /// Adds a function.
+    Compatible,
+    /// Adds a field to a struct, which breaks compatibility.
+    Incompatible,
+}
+
+impl fmt::Display for UpgradeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            UpgradeKind::Compatible => "compatible",
+            UpgradeKind::Incompatible => "incompatible",
+        })
+    }
+}
+
+/// A package published with the policy `from`, then upgraded to a package with the policy `to`,
+/// whose modules changed as `kind` says.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PolicyTransition {
+    pub from: UpgradePolicy,
+    pub to: UpgradePolicy,
+    pub kind: UpgradeKind,
+}
+
+impl PolicyTransition {
+    /// Every transition between two upgrade policies, each with a compatible and an incompatible
+    /// upgrade.
+    pub fn all() -> Vec<PolicyTransition> {
+        let policies = [
+            UpgradePolicy::arbitrary(),
+            UpgradePolicy::compat(),
+            UpgradePolicy::immutable(),
+        ];
+        let mut transitions = vec![];
+        for from in policies {
+            for to in policies {
+                for kind in [UpgradeKind::Compatible, UpgradeKind::Incompatible] {
+                    transitions.push(PolicyTransition { from, to, kind });
+                }
+            }
+        }
+        transitions
+    }
+
+    /// The package published first, with its modules at `address`.
+    pub fn initial_package(&self, address: &str) -> PackageBuilder {
+        let mut builder = PackageBuilder::new(PACKAGE_NAME).with_policy(self.from);
+        builder.add_alias(POLICY_MATRIX_ADDRESS_NAME, address);
+        builder.add_source(
+            MODULE_NAME,
+            &format!(
+                "module {}::{} {{
+                    struct Data has key {{ value: u64 }}
+                    public fun value(): u64 {{ 1 }}
+                }}",
+                POLICY_MATRIX_ADDRESS_NAME, MODULE_NAME
+            ),
+        );
+        builder
+    }
+
+    /// The package upgrading the initial package, with its modules at `address`.
+    pub fn upgrade_package(&self, address: &str) -> PackageBuilder {
+        let mut builder = PackageBuilder::new(PACKAGE_NAME).with_policy(self.to);
+        builder.add_alias(POLICY_MATRIX_ADDRESS_NAME, address);
+        let source = match self.kind {
+            UpgradeKind::Compatible => format!(
+                "module {}::{} {{
+                    struct Data has key {{ value: u64 }}
+                    public fun value(): u64 {{ 1 }}
+                    public fun other_value(): u64 {{ 2 }}
+                }}",
+                POLICY_MATRIX_ADDRESS_NAME, MODULE_NAME
+            ),
+            UpgradeKind::Incompatible => format!(
+                "module {}::{} {{
+                    struct Data has key {{ value: u64, flag: bool }}
+                    public fun value(): u64 {{ 1 }}
+                }}",
+                POLICY_MATRIX_ADDRESS_NAME, MODULE_NAME
+            ),
+        };
+        builder.add_source(MODULE_NAME, &source);
+        builder
+    }
+}
+
+impl fmt::Display for PolicyTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {} ({})", self.from, self.to, self.kind)
+    }
+}
@@ -0,0 +1,341 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::PackageBuilder;
+use itertools::Itertools;
+use move_core_types::{account_address::AccountAddress, value::MoveValue};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use std::fmt::{self, Write};
+
+/// The abilities of every generated struct, so that any generated type can be a field.
+const STRUCT_ABILITIES: &str = "copy, drop, store";
+
+/// A type of a generated module.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RandomType {
+    Bool,
+    U8,
+    U64,
+    U128,
+    Address,
+    Vector(Box<RandomType>),
+    /// A struct of the same module, by index.
+    Struct(usize, Vec<RandomType>),
+    /// A type parameter of the enclosing struct or function, by index.
+    TypeParam(usize),
+}
+
+/// A struct of a generated module. All generated structs have `copy, drop, store`, and use each
+/// of their type parameters.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RandomStruct {
+    pub type_params: usize,
+    pub fields: Vec<RandomType>,
+}
+
+/// A function of a generated module, with an empty body. Entry functions only take values which
+/// can be passed in transactions, the others take references to any type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RandomFunction {
+    pub is_entry: bool,
+    pub type_params: usize,
+    pub params: Vec<RandomType>,
+}
+
+/// The shape of a generated module, from which its source is rendered. Structs are named `S<i>`,
+/// fields `f<i>`, functions `fun<i>` and type parameters `T<i>`, by index.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RandomModule {
+    pub name: String,
+    pub structs: Vec<RandomStruct>,
+    pub functions: Vec<RandomFunction>,
+}
+
+/// Generates random but valid Move modules from a seed, for differential testing of whatever
+/// consumes modules against the compiler: the same seed always gives the same modules.
+///
+/// Besides fresh modules, it generates upgrades of a module which the compatibility checks of
+/// publishing must accept or reject, and arguments for its entry functions.
+#[derive(Clone, Debug)]
+pub struct RandomModuleGenerator {
+    rng: StdRng,
+    max_structs: usize,
+    max_fields: usize,
+    max_functions: usize,
+    max_params: usize,
+    max_type_params: usize,
+    max_type_depth: usize,
+}
+
+impl RandomModuleGenerator {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            max_structs: 8,
+            max_fields: 6,
+            max_functions: 8,
+            max_params: 5,
+            max_type_params: 3,
+            max_type_depth: 3,
+        }
+    }
+
+    pub fn with_max_structs(self, max_structs: usize) -> Self {
+        Self {
+            max_structs: max_structs.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_max_functions(self, max_functions: usize) -> Self {
+        Self {
+            max_functions,
+            ..self
+        }
+    }
+
+    pub fn with_max_type_depth(self, max_type_depth: usize) -> Self {
+        Self {
+            max_type_depth,
+            ..self
+        }
+    }
+
+    /// Generates a module with at least one struct.
+    pub fn module(&mut self, name: &str) -> RandomModule {
+        let mut module = RandomModule {
+            name: name.to_string(),
+            structs: vec![],
+            functions: vec![],
+        };
+        self.add_members(&mut module, 1);
+        module
+    }
+
+    /// Generates a package of `count` modules at `address`, named `m<i>`.
+    pub fn package(
+        &mut self,
+        name: &str,
+        address: AccountAddress,
+        count: usize,
+    ) -> (PackageBuilder, Vec<RandomModule>) {
+        let modules: Vec<_> = (0..count)
+            .map(|i| self.module(&format!("m{}", i)))
+            .collect();
+        let mut builder = PackageBuilder::new(name);
+        for module in &modules {
+            builder.add_random_module(address, module);
+        }
+        (builder, modules)
+    }
+
+    /// An upgrade of `module` which publishing must accept: it only adds structs and functions.
+    pub fn compatible_upgrade(&mut self, module: &RandomModule) -> RandomModule {
+        let mut upgrade = module.clone();
+        self.add_members(&mut upgrade, 0);
+        upgrade
+    }
+
+    /// An upgrade of `module` which publishing must reject: it changes the type of the last field
+    /// of one of its structs.
+    pub fn incompatible_upgrade(&mut self, module: &RandomModule) -> RandomModule {
+        let mut upgrade = module.clone();
+        let index = self.rng.gen_range(0, upgrade.structs.len());
+        let field = upgrade.structs[index]
+            .fields
+            .last_mut()
+            .expect("structs have fields");
+        *field = match field {
+            RandomType::U64 => RandomType::U128,
+            _ => RandomType::U64,
+        };
+        upgrade
+    }
+
+    /// Arguments for the entry function `function`, with any type arguments.
+    pub fn entry_function_args(&mut self, function: &RandomFunction) -> Vec<MoveValue> {
+        assert!(function.is_entry, "only entry functions take arguments");
+        function
+            .params
+            .iter()
+            .map(|param| self.value(param))
+            .collect()
+    }
+
+    fn add_members(&mut self, module: &mut RandomModule, min_structs: usize) {
+        for _ in 0..self.rng.gen_range(min_structs, self.max_structs + 1) {
+            let type_params = self.rng.gen_range(0, self.max_type_params + 1);
+            let mut fields: Vec<_> = (0..type_params).map(RandomType::TypeParam).collect();
+            for _ in 0..self.rng.gen_range(1, self.max_fields + 1) {
+                fields.push(self.field_type(&module.structs, type_params, self.max_type_depth));
+            }
+            module.structs.push(RandomStruct {
+                type_params,
+                fields,
+            });
+        }
+        for _ in 0..self.rng.gen_range(0, self.max_functions + 1) {
+            let is_entry = self.rng.gen_bool(0.5);
+            let type_params = self.rng.gen_range(0, self.max_type_params + 1);
+            let params = (0..self.rng.gen_range(0, self.max_params + 1))
+                .map(|_| {
+                    if is_entry {
+                        self.entry_type(self.max_type_depth)
+                    } else {
+                        self.field_type(&module.structs, type_params, self.max_type_depth)
+                    }
+                })
+                .collect();
+            module.functions.push(RandomFunction {
+                is_entry,
+                type_params,
+                params,
+            });
+        }
+    }
+
+    /// A type built from primitives, `structs` and `type_params` type parameters.
+    fn field_type(
+        &mut self,
+        structs: &[RandomStruct],
+        type_params: usize,
+        depth: usize,
+    ) -> RandomType {
+        let choice = if depth == 0 {
+            0
+        } else {
+            self.rng.gen_range(0, 4)
+        };
+        match choice {
+            1 => RandomType::Vector(Box::new(self.field_type(structs, type_params, depth - 1))),
+            2 if !structs.is_empty() => {
+                let index = self.rng.gen_range(0, structs.len());
+                let ty_args = (0..structs[index].type_params)
+                    .map(|_| self.field_type(structs, type_params, depth - 1))
+                    .collect();
+                RandomType::Struct(index, ty_args)
+            },
+            3 if type_params > 0 => RandomType::TypeParam(self.rng.gen_range(0, type_params)),
+            _ => self.primitive_type(),
+        }
+    }
+
+    /// A type of the arguments transactions can pass to entry functions.
+    fn entry_type(&mut self, depth: usize) -> RandomType {
+        if depth > 0 && self.rng.gen_bool(0.3) {
+            RandomType::Vector(Box::new(self.entry_type(depth - 1)))
+        } else {
+            self.primitive_type()
+        }
+    }
+
+    fn primitive_type(&mut self) -> RandomType {
+        [
+            RandomType::Bool,
+            RandomType::U8,
+            RandomType::U64,
+            RandomType::U128,
+            RandomType::Address,
+        ]
+        .choose(&mut self.rng)
+        .cloned()
+        .unwrap()
+    }
+
+    fn value(&mut self, ty: &RandomType) -> MoveValue {
+        match ty {
+            RandomType::Bool => MoveValue::Bool(self.rng.gen()),
+            RandomType::U8 => MoveValue::U8(self.rng.gen()),
+            RandomType::U64 => MoveValue::U64(self.rng.gen()),
+            RandomType::U128 => MoveValue::U128(self.rng.gen()),
+            RandomType::Address => MoveValue::Address(AccountAddress::new(self.rng.gen())),
+            RandomType::Vector(elem) => MoveValue::Vector(
+                (0..self.rng.gen_range(0, 4))
+                    .map(|_| self.value(elem))
+                    .collect(),
+            ),
+            RandomType::Struct(..) | RandomType::TypeParam(_) => {
+                unreachable!("entry functions only take primitives and vectors")
+            },
+        }
+    }
+}
+
+impl RandomModule {
+    /// The source of the module, published at `address`.
+    pub fn source(&self, address: AccountAddress) -> String {
+        let mut source = format!("module {}::{} {{\n", address.to_hex_literal(), self.name);
+        for (i, s) in self.structs.iter().enumerate() {
+            writeln!(
+                source,
+                "    struct S{}{} has {} {{ {} }}",
+                i,
+                type_params(s.type_params),
+                STRUCT_ABILITIES,
+                s.fields
+                    .iter()
+                    .enumerate()
+                    .map(|(j, field)| format!("f{}: {}", j, field))
+                    .join(", ")
+            )
+            .unwrap();
+        }
+        for (i, function) in self.functions.iter().enumerate() {
+            let params = function
+                .params
+                .iter()
+                .enumerate()
+                .map(|(j, param)| {
+                    if function.is_entry {
+                        format!("_p{}: {}", j, param)
+                    } else {
+                        format!("_p{}: &{}", j, param)
+                    }
+                })
+                .join(", ");
+            writeln!(
+                source,
+                "    public {}fun fun{}{}({}) {{}}",
+                if function.is_entry { "entry " } else { "" },
+                i,
+                type_params(function.type_params),
+                params
+            )
+            .unwrap();
+        }
+        source.push_str("}\n");
+        source
+    }
+}
+
+impl fmt::Display for RandomType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomType::Bool => write!(f, "bool"),
+            RandomType::U8 => write!(f, "u8"),
+            RandomType::U64 => write!(f, "u64"),
+            RandomType::U128 => write!(f, "u128"),
+            RandomType::Address => write!(f, "address"),
+            RandomType::Vector(elem) => write!(f, "vector<{}>", elem),
+            RandomType::Struct(index, ty_args) if ty_args.is_empty() => write!(f, "S{}", index),
+            RandomType::Struct(index, ty_args) => {
+                write!(f, "S{}<{}>", index, ty_args.iter().join(", "))
+            },
+            RandomType::TypeParam(index) => write!(f, "T{}", index),
+        }
+    }
+}
+
+fn type_params(count: usize) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    format!("<{}>", (0..count).map(|i| format!("T{}", i)).join(", "))
+}
+
+impl PackageBuilder {
+    /// Adds the source of a generated module, published at `address`.
+    pub fn add_random_module(&mut self, address: AccountAddress, module: &RandomModule) {
+        self.add_source(&module.name, &module.source(address))
+    }
+}
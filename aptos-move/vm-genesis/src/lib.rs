@@ -70,6 +70,10 @@ pub struct GenesisConfiguration {
     pub voting_power_increase_limit: u64,
     pub employee_vesting_start: u64,
     pub employee_vesting_period_duration: u64,
+    // Features enabled at genesis. If not set, `default_features()` are enabled.
+    pub initial_features_override: Option<Vec<FeatureFlag>>,
+    // Accounts to create and fund at genesis, after the validators have been initialized.
+    pub initial_balances: Vec<AccountBalance>,
 }
 
 pub static GENESIS_KEYPAIR: Lazy<(Ed25519PrivateKey, Ed25519PublicKey)> = Lazy::new(|| {
@@ -126,7 +130,7 @@ pub fn encode_aptos_mainnet_genesis_transaction(
         &consensus_config,
         &gas_schedule,
     );
-    initialize_features(&mut session);
+    initialize_features(&mut session, genesis_config);
     initialize_aptos_coin(&mut session);
     initialize_on_chain_governance(&mut session, genesis_config);
     create_accounts(&mut session, accounts);
@@ -236,7 +240,7 @@ pub fn encode_genesis_change_set(
         consensus_config,
         gas_schedule,
     );
-    initialize_features(&mut session);
+    initialize_features(&mut session, genesis_config);
     if genesis_config.is_test {
         initialize_core_resources_and_aptos_coin(&mut session, core_resources_key);
     } else {
@@ -244,6 +248,7 @@ pub fn encode_genesis_change_set(
     }
     initialize_on_chain_governance(&mut session, genesis_config);
     create_and_initialize_validators(&mut session, validators);
+    fund_initial_accounts(&mut session, genesis_config);
     if genesis_config.is_test {
         allow_core_resources_to_set_version(&mut session);
     }
@@ -424,8 +429,14 @@ pub fn default_features() -> Vec<FeatureFlag> {
     ]
 }
 
-fn initialize_features(session: &mut SessionExt<impl MoveResolver>) {
-    let features: Vec<u64> = default_features()
+fn initialize_features(
+    session: &mut SessionExt<impl MoveResolver>,
+    genesis_config: &GenesisConfiguration,
+) {
+    let features: Vec<u64> = genesis_config
+        .initial_features_override
+        .clone()
+        .unwrap_or_else(default_features)
         .into_iter()
         .map(|feature| feature as u64)
         .collect();
@@ -512,6 +523,32 @@ fn create_accounts(session: &mut SessionExt<impl MoveResolver>, accounts: &[Acco
     );
 }
 
+/// Funds `genesis_config.initial_balances`. In test genesis the coins are transferred from the
+/// core resources account, which also works for accounts that already exist (e.g. validator
+/// owners). Otherwise they are minted by the framework, which skips existing accounts.
+fn fund_initial_accounts(
+    session: &mut SessionExt<impl MoveResolver>,
+    genesis_config: &GenesisConfiguration,
+) {
+    if !genesis_config.is_test {
+        create_accounts(session, &genesis_config.initial_balances);
+        return;
+    }
+    for account in &genesis_config.initial_balances {
+        exec_function(
+            session,
+            "aptos_account",
+            "transfer",
+            vec![],
+            serialize_values(&vec![
+                MoveValue::Signer(aptos_test_root_address()),
+                MoveValue::Address(account.account_address),
+                MoveValue::U64(account.balance),
+            ]),
+        );
+    }
+}
+
 fn create_employee_validators(
     session: &mut SessionExt<impl MoveResolver>,
     employees: &[EmployeePool],
@@ -805,6 +842,8 @@ pub fn generate_test_genesis(
             voting_power_increase_limit: 50,
             employee_vesting_start: 1663456089,
             employee_vesting_period_duration: 5 * 60, // 5 minutes
+            initial_features_override: None,
+            initial_balances: vec![],
         },
         &OnChainConsensusConfig::default(),
         &default_gas_schedule(),
@@ -850,6 +889,8 @@ fn mainnet_genesis_config() -> GenesisConfiguration {
         voting_power_increase_limit: 30,
         employee_vesting_start: 1663456089,
         employee_vesting_period_duration: 5 * 60, // 5 minutes
+        initial_features_override: None,
+        initial_balances: vec![],
     }
 }
 
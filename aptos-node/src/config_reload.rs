@@ -0,0 +1,52 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_config::config::ConfigReloader;
+use aptos_logger::{info, warn, LoggerFilterUpdater};
+use tokio::runtime::Runtime;
+
+/// Applies config reloads to the logger, and reloads the config whenever the node receives a
+/// SIGHUP. Returns the runtime listening for the signal, if there's a config file to reload.
+pub fn start_config_reloader(
+    config_reloader: &ConfigReloader,
+    logger_filter_updater: Option<LoggerFilterUpdater>,
+) -> Option<Runtime> {
+    if let Some(logger_filter_updater) = logger_filter_updater {
+        config_reloader.subscribe(move |config| logger_filter_updater.set_level(config.log_level));
+    }
+
+    // Without a config file, keep the default behavior of SIGHUP
+    config_reloader.config_path()?;
+    start_sighup_listener(config_reloader.clone())
+}
+
+#[cfg(unix)]
+fn start_sighup_listener(config_reloader: ConfigReloader) -> Option<Runtime> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let runtime = aptos_runtimes::spawn_named_runtime("cfg-reload".into(), Some(1));
+    runtime.spawn(async move {
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(error) => {
+                warn!(
+                    "Failed to listen for SIGHUP, the node config can't be reloaded with it: {}",
+                    error
+                );
+                return;
+            },
+        };
+        while hangups.recv().await.is_some() {
+            match config_reloader.reload() {
+                Ok(config) => info!("Reloaded node config on SIGHUP: {:?}", config),
+                Err(error) => warn!("Failed to reload node config on SIGHUP: {}", error),
+            }
+        }
+    });
+    Some(runtime)
+}
+
+#[cfg(not(unix))]
+fn start_sighup_listener(_config_reloader: ConfigReloader) -> Option<Runtime> {
+    None
+}
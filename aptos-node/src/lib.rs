@@ -4,6 +4,7 @@
 
 #![forbid(unsafe_code)]
 
+mod config_reload;
 mod indexer;
 mod logger;
 mod network;
@@ -18,8 +19,9 @@ mod tests;
 use anyhow::anyhow;
 use aptos_api::bootstrap as bootstrap_api;
 use aptos_build_info::build_information;
-use aptos_config::config::{NodeConfig, PersistableConfig};
+use aptos_config::config::{ConfigReloader, NodeConfig, PersistableConfig};
 use aptos_framework::ReleaseBundle;
+use aptos_genesis::builder::InitGenesisConfigFn;
 use aptos_logger::{prelude::*, telemetry_log_writer::TelemetryLog, Level, LoggerFilterUpdater};
 use aptos_state_sync_driver::driver_factory::StateSyncRuntimes;
 use aptos_types::chain_id::ChainId;
@@ -123,6 +125,7 @@ impl AptosNodeArgs {
                 self.random_ports,
                 self.lazy,
                 &genesis_framework,
+                None,
                 rng,
             )
             .expect("Test node should start correctly!");
@@ -146,7 +149,7 @@ impl AptosNodeArgs {
             });
 
             // Start the node
-            start(config, None, true).expect("Node should start correctly");
+            start(config, Some(config_path), None, true).expect("Node should start correctly");
         };
     }
 }
@@ -155,6 +158,7 @@ impl AptosNodeArgs {
 pub struct AptosHandle {
     _api_runtime: Option<Runtime>,
     _backup_runtime: Option<Runtime>,
+    _config_reload_runtime: Option<Runtime>,
     _consensus_runtime: Option<Runtime>,
     _indexer_grpc_runtime: Option<Runtime>,
     _indexer_runtime: Option<Runtime>,
@@ -165,15 +169,40 @@ pub struct AptosHandle {
     _telemetry_runtime: Option<Runtime>,
 }
 
-/// Start an Aptos node
+/// Start an Aptos node. If `config_path` is given, the reloadable part of the config (see
+/// `ReloadableConfig`) is reloaded from it when the node receives a SIGHUP.
 pub fn start(
     config: NodeConfig,
+    config_path: Option<PathBuf>,
     log_file: Option<PathBuf>,
     create_global_rayon_pool: bool,
 ) -> anyhow::Result<()> {
     // Setup panic handler
     aptos_crash_handler::setup_panic_handler();
 
+    // Set up the node environment and start it
+    let _node_handle =
+        initialize_and_start_node(config, config_path, log_file, create_global_rayon_pool)?;
+    let term = Arc::new(AtomicBool::new(false));
+    while !term.load(Ordering::Acquire) {
+        thread::park();
+    }
+
+    Ok(())
+}
+
+/// Initializes the process wide state (rayon pool, node identity, logger and failpoints) and
+/// starts the node. The node runs until the returned handle is dropped.
+///
+/// Unlike [`start`], this doesn't install the crash handler, so it can be used to embed a node in
+/// another process (e.g. tests). The process wide state can only be initialized once, so at most
+/// one node can be started per process.
+pub fn initialize_and_start_node(
+    config: NodeConfig,
+    config_path: Option<PathBuf>,
+    log_file: Option<PathBuf>,
+    create_global_rayon_pool: bool,
+) -> anyhow::Result<AptosHandle> {
     // Create global rayon thread pool
     utils::create_global_rayon_pool(create_global_rayon_pool);
 
@@ -202,15 +231,12 @@ pub fn start(
         warn!("Failpoints is set in the node config, but the binary didn't compile with this feature!");
     }
 
-    // Set up the node environment and start it
-    let _node_handle =
-        setup_environment_and_start_node(config, remote_log_receiver, Some(logger_filter_update))?;
-    let term = Arc::new(AtomicBool::new(false));
-    while !term.load(Ordering::Acquire) {
-        thread::park();
-    }
-
-    Ok(())
+    setup_environment_and_start_node(
+        config,
+        config_path,
+        remote_log_receiver,
+        Some(logger_filter_update),
+    )
 }
 
 /// Creates a simple test environment and starts the node
@@ -220,6 +246,7 @@ pub fn setup_test_environment_and_start_node<R>(
     random_ports: bool,
     enable_lazy_mode: bool,
     framework: &ReleaseBundle,
+    init_genesis_config: Option<InitGenesisConfigFn>,
     rng: R,
 ) -> anyhow::Result<()>
 where
@@ -228,7 +255,89 @@ where
     // If there wasn't a test directory specified, create a temporary one
     let test_dir =
         test_dir.unwrap_or_else(|| aptos_temppath::TempPath::new().as_ref().to_path_buf());
+    let environment = create_test_environment(
+        config_path,
+        test_dir,
+        random_ports,
+        enable_lazy_mode,
+        framework,
+        init_genesis_config,
+        rng,
+    )?;
+    let config = environment.config.clone();
+
+    // Prepare log file since we cannot automatically route logs to stderr
+    let log_file = environment.log_file();
+
+    // Print out useful information about the environment and the node
+    println!("Completed generating configuration:");
+    println!("\tLog file: {:?}", log_file);
+    println!("\tTest dir: {:?}", environment.test_dir);
+    println!("\tAptos root key path: {:?}", environment.root_key_path());
+    println!("\tWaypoint: {}", config.base.waypoint.genesis_waypoint());
+    println!("\tChainId: {}", ChainId::test());
+    println!("\tREST API endpoint: http://{}", &config.api.address);
+    println!(
+        "\tMetrics endpoint: http://{}:{}/metrics",
+        &config.inspection_service.address, &config.inspection_service.port
+    );
+    println!(
+        "\tAptosnet fullnode network endpoint: {}",
+        &config.full_node_networks[0].listen_address
+    );
+    if enable_lazy_mode {
+        println!("\tLazy mode is enabled");
+    }
+    println!("\nAptos is running, press ctrl-c to exit\n");
+
+    start(
+        config,
+        Some(environment.config_path()),
+        Some(log_file),
+        false,
+    )
+}
+
+/// The config and files of a single validator test network
+pub struct TestEnvironment {
+    /// Config of the validator
+    pub config: NodeConfig,
+    /// Directory holding the node's data, genesis and keys
+    pub test_dir: PathBuf,
+}
+
+impl TestEnvironment {
+    /// Path of the config file of the validator
+    pub fn config_path(&self) -> PathBuf {
+        self.test_dir.join("0").join("node.yaml")
+    }
+
+    /// Path of the key of the root account, which can mint coins
+    pub fn root_key_path(&self) -> PathBuf {
+        self.test_dir.join("mint.key")
+    }
+
+    /// Path the node logs to
+    pub fn log_file(&self) -> PathBuf {
+        self.test_dir.join("validator.log")
+    }
+}
 
+/// Creates the config, genesis and keys of a single validator test network in `test_dir`, or
+/// reuses them if they already exist there. `init_genesis_config` is applied on top of the
+/// default test genesis config, e.g. to change the features enabled at genesis.
+pub fn create_test_environment<R>(
+    config_path: Option<PathBuf>,
+    test_dir: PathBuf,
+    random_ports: bool,
+    enable_lazy_mode: bool,
+    framework: &ReleaseBundle,
+    init_genesis_config: Option<InitGenesisConfigFn>,
+    rng: R,
+) -> anyhow::Result<TestEnvironment>
+where
+    R: rand::RngCore + rand::CryptoRng,
+{
     // Create the directories for the node
     fs::DirBuilder::new().recursive(true).create(&test_dir)?;
     let test_dir = test_dir.canonicalize()?;
@@ -250,10 +359,13 @@ where
             .with_init_config(Some(Arc::new(move |_, config, _| {
                 *config = node_config.clone();
             })))
-            .with_init_genesis_config(Some(Arc::new(|genesis_config| {
+            .with_init_genesis_config(Some(Arc::new(move |genesis_config| {
                 genesis_config.allow_new_validators = true;
                 genesis_config.epoch_duration_secs = EPOCH_LENGTH_SECS;
                 genesis_config.recurring_lockup_duration_secs = 7200;
+                if let Some(init_genesis_config) = &init_genesis_config {
+                    (init_genesis_config)(genesis_config);
+                }
             })))
             .with_randomize_first_validator_ports(random_ports);
         let (root_key, _genesis, genesis_waypoint, validators) = builder.build(rng)?;
@@ -274,31 +386,7 @@ where
         validators[0].config.clone()
     };
 
-    // Prepare log file since we cannot automatically route logs to stderr
-    let log_file = test_dir.join("validator.log");
-
-    // Print out useful information about the environment and the node
-    println!("Completed generating configuration:");
-    println!("\tLog file: {:?}", log_file);
-    println!("\tTest dir: {:?}", test_dir);
-    println!("\tAptos root key path: {:?}", aptos_root_key_path);
-    println!("\tWaypoint: {}", config.base.waypoint.genesis_waypoint());
-    println!("\tChainId: {}", ChainId::test());
-    println!("\tREST API endpoint: http://{}", &config.api.address);
-    println!(
-        "\tMetrics endpoint: http://{}:{}/metrics",
-        &config.inspection_service.address, &config.inspection_service.port
-    );
-    println!(
-        "\tAptosnet fullnode network endpoint: {}",
-        &config.full_node_networks[0].listen_address
-    );
-    if enable_lazy_mode {
-        println!("\tLazy mode is enabled");
-    }
-    println!("\nAptos is running, press ctrl-c to exit\n");
-
-    start(config, Some(log_file), false)
+    Ok(TestEnvironment { config, test_dir })
 }
 
 /// Creates a single node test config, with a few config tweaks to reduce
@@ -357,6 +445,9 @@ fn create_single_node_test_config(
         .parse()
         .expect("Unable to set the REST API address!");
 
+    // Enable the admin endpoints, for controlling the local chain (e.g. fast forwarding time)
+    node_config.api.admin_endpoints_enabled = true;
+
     // Set the correct poll count for mempool
     if enable_lazy_mode {
         node_config.consensus.quorum_store_poll_count = u64::MAX;
@@ -368,12 +459,18 @@ fn create_single_node_test_config(
 /// Initializes the node environment and starts the node
 pub fn setup_environment_and_start_node(
     mut node_config: NodeConfig,
+    config_path: Option<PathBuf>,
     remote_log_rx: Option<mpsc::Receiver<TelemetryLog>>,
     logger_filter_update_job: Option<LoggerFilterUpdater>,
 ) -> anyhow::Result<AptosHandle> {
     // Log the node config at node startup
     info!("Using node config {:?}", &node_config);
 
+    // Set up reloading of the reloadable part of the config (log level, mempool and API limits)
+    let config_reloader = ConfigReloader::new(config_path, &node_config);
+    let config_reload_runtime =
+        config_reload::start_config_reloader(&config_reloader, logger_filter_update_job.clone());
+
     // Start the node inspection service
     services::start_node_inspection_service(&node_config);
 
@@ -437,7 +534,7 @@ pub fn setup_environment_and_start_node(
 
     // Bootstrap the API and indexer
     let (mempool_client_receiver, api_runtime, indexer_runtime, indexer_grpc_runtime) =
-        services::bootstrap_api_and_indexer(&node_config, aptos_db, chain_id)?;
+        services::bootstrap_api_and_indexer(&node_config, aptos_db, chain_id, &config_reloader)?;
 
     // Create mempool and get the consensus to mempool sender
     let (mempool_runtime, consensus_to_mempool_sender) =
@@ -448,6 +545,7 @@ pub fn setup_environment_and_start_node(
             mempool_network_interfaces,
             mempool_listener,
             mempool_client_receiver,
+            &config_reloader,
         );
 
     // Create the consensus runtime (this blocks on state sync first)
@@ -471,6 +569,7 @@ pub fn setup_environment_and_start_node(
     Ok(AptosHandle {
         _api_runtime: api_runtime,
         _backup_runtime: backup_service,
+        _config_reload_runtime: config_reload_runtime,
         _consensus_runtime: consensus_runtime,
         _indexer_grpc_runtime: indexer_grpc_runtime,
         _indexer_runtime: indexer_runtime,
@@ -3,7 +3,7 @@
 
 use crate::{bootstrap_api, indexer, mpsc::Receiver, network::ApplicationNetworkInterfaces};
 use aptos_build_info::build_information;
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{ConfigReloader, NodeConfig};
 use aptos_consensus::network_interface::ConsensusMsg;
 use aptos_consensus_notifications::ConsensusNotifier;
 use aptos_event_notifications::ReconfigNotificationListener;
@@ -33,6 +33,7 @@ pub fn bootstrap_api_and_indexer(
     node_config: &NodeConfig,
     aptos_db: Arc<dyn DbReader>,
     chain_id: ChainId,
+    config_reloader: &ConfigReloader,
 ) -> anyhow::Result<(
     Receiver<MempoolClientRequest>,
     Option<Runtime>,
@@ -50,6 +51,7 @@ pub fn bootstrap_api_and_indexer(
             chain_id,
             aptos_db.clone(),
             mempool_client_sender.clone(),
+            config_reloader.clone(),
         )?)
     } else {
         None
@@ -107,6 +109,7 @@ pub fn start_mempool_runtime_and_get_consensus_sender(
     network_interfaces: ApplicationNetworkInterfaces<MempoolSyncMsg>,
     mempool_listener: MempoolNotificationListener,
     mempool_client_receiver: Receiver<MempoolClientRequest>,
+    config_reloader: &ConfigReloader,
 ) -> (Runtime, Sender<QuorumStoreRequest>) {
     // Create a communication channel between consensus and mempool
     let (consensus_to_mempool_sender, consensus_to_mempool_receiver) =
@@ -123,6 +126,7 @@ pub fn start_mempool_runtime_and_get_consensus_sender(
         consensus_to_mempool_receiver,
         mempool_listener,
         mempool_reconfig_subscription,
+        config_reloader,
     );
     debug!("Mempool started in {} ms", instant.elapsed().as_millis());
 
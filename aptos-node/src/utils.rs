@@ -9,6 +9,8 @@ use aptos_types::{
     account_config::CORE_CODE_ADDRESS, account_view::AccountView, chain_id::ChainId,
 };
 use aptos_vm::AptosVM;
+use move_vm_runtime::config::ModuleCacheConfig;
+use std::time::Duration;
 
 /// Error message to display when non-production features are enabled
 pub const ERROR_MSG_BAD_FEATURE_FLAGS: &str = r#"
@@ -53,6 +55,12 @@ pub fn set_aptos_vm_configurations(node_config: &NodeConfig) {
     AptosVM::set_num_proof_reading_threads_once(
         node_config.execution.num_proof_reading_threads as usize,
     );
+    let module_cache_config = &node_config.execution.module_cache;
+    AptosVM::set_module_cache_config_once(ModuleCacheConfig {
+        max_modules: module_cache_config.max_modules,
+        max_size_bytes: module_cache_config.max_size_bytes,
+        ttl: module_cache_config.ttl_secs.map(Duration::from_secs),
+    });
 
     if node_config
         .execution
@@ -21,6 +21,23 @@ pub struct ApiConfig {
     pub content_length_limit: Option<u64>,
     #[serde(default = "default_disabled")]
     pub failpoints_enabled: bool,
+    /// Enables the admin endpoints for controlling a local testnet, e.g. fast forwarding chain
+    /// time. Never enable this on a real network.
+    #[serde(default = "default_disabled")]
+    pub admin_endpoints_enabled: bool,
+    /// Enables the endpoint for reloading the reloadable part of the node config (see
+    /// `ReloadableConfig`) from the config file. Only enable this if the API is not exposed to
+    /// the public, the config can always be reloaded with a SIGHUP.
+    #[serde(default = "default_disabled")]
+    pub config_reload_endpoint_enabled: bool,
+    /// Bearer token required by the endpoints for inspecting and flushing the loader cache of
+    /// the VM that validates transactions. The endpoints are disabled if it is not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_auth_token: Option<String>,
+    /// Faults to inject into API requests, for testing client retry logic against a local
+    /// testnet. Never set this on a real network.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chaos: Option<ChaosConfig>,
     #[serde(default = "default_enabled")]
     pub json_output_enabled: bool,
     #[serde(default = "default_enabled")]
@@ -33,12 +50,20 @@ pub struct ApiConfig {
     pub transaction_simulation_enabled: bool,
 
     pub max_submit_transaction_batch_size: usize,
+    /// Maximum number of reads in a single batch read request.
+    pub max_batch_read_size: usize,
 
     // Maximum page size for paginated APIs
     pub max_transactions_page_size: u16,
     pub max_events_page_size: u16,
     pub max_account_resources_page_size: u16,
     pub max_account_modules_page_size: u16,
+    /// Maximum number of versions scanned by a single request for events by type, which reads
+    /// the events of every transaction in the range.
+    pub max_events_by_type_scan_range: u64,
+    /// Maximum number of state keys scanned by a single request for the state keys of an
+    /// account, whether they match its prefix filter or not.
+    pub max_state_keys_scan_range: u64,
 
     /// Max gas unit for view function.
     pub max_gas_view_function: u64,
@@ -52,9 +77,12 @@ pub const DEFAULT_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_PORT: u16 = 8080;
 pub const DEFAULT_REQUEST_CONTENT_LENGTH_LIMIT: u64 = 8 * 1024 * 1024; // 8 MB
 pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
+pub const DEFAULT_MAX_BATCH_READ_SIZE: usize = 50;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 pub const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 pub const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+pub const DEFAULT_MAX_EVENTS_BY_TYPE_SCAN_RANGE: u64 = 10_000;
+pub const DEFAULT_MAX_STATE_KEYS_SCAN_RANGE: u64 = 10_000;
 pub const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -76,16 +104,23 @@ impl Default for ApiConfig {
             tls_key_path: None,
             content_length_limit: None,
             failpoints_enabled: default_disabled(),
+            admin_endpoints_enabled: default_disabled(),
+            config_reload_endpoint_enabled: default_disabled(),
+            admin_auth_token: None,
+            chaos: None,
             bcs_output_enabled: default_enabled(),
             json_output_enabled: default_enabled(),
             encode_submission_enabled: default_enabled(),
             transaction_submission_enabled: default_enabled(),
             transaction_simulation_enabled: default_enabled(),
             max_submit_transaction_batch_size: DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE,
+            max_batch_read_size: DEFAULT_MAX_BATCH_READ_SIZE,
             max_transactions_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_events_page_size: DEFAULT_MAX_PAGE_SIZE,
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
+            max_events_by_type_scan_range: DEFAULT_MAX_EVENTS_BY_TYPE_SCAN_RANGE,
+            max_state_keys_scan_range: DEFAULT_MAX_STATE_KEYS_SCAN_RANGE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
@@ -93,6 +128,26 @@ impl Default for ApiConfig {
     }
 }
 
+/// Faults injected by the API, each with a percentage chance per request. The faults are drawn
+/// from an RNG seeded with `seed`, so a given sequence of requests always sees the same faults.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChaosConfig {
+    pub seed: u64,
+    /// Transaction submissions that are reported as accepted, but never reach mempool
+    pub drop_submission_percent: u8,
+    /// Transaction submissions that only reach mempool after `submission_delay_ms`, which
+    /// delays their commit
+    pub delay_submission_percent: u8,
+    pub submission_delay_ms: u64,
+    /// Requests rejected with a 429, as if rate limited
+    pub too_many_requests_percent: u8,
+    /// Requests served as if the node was lagging `stale_read_lag_versions` behind the latest
+    /// version
+    pub stale_read_percent: u8,
+    pub stale_read_lag_versions: u64,
+}
+
 impl ApiConfig {
     pub fn randomize_ports(&mut self) {
         self.address.set_port(utils::get_available_port());
@@ -24,6 +24,22 @@ pub struct ExecutionConfig {
     pub paranoid_type_verification: bool,
     pub paranoid_hot_potato_verification: bool,
     pub processed_transactions_detailed_counters: bool,
+    pub module_cache: ModuleCacheConfig,
+}
+
+/// Limits of the VM's module cache. It can't drop single modules, so once it goes over any of
+/// them it is flushed as a whole. They are all unlimited by default.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModuleCacheConfig {
+    /// Max number of modules in the cache
+    pub max_modules: Option<usize>,
+    /// Max total size of the modules in the cache, in bytes
+    pub max_size_bytes: Option<usize>,
+    /// Max number of seconds modules stay in the cache. A long lived VM, like the one mempool
+    /// validates transactions with, doesn't see the modules upgraded by transactions it didn't
+    /// execute, so this bounds how long it can keep using their previous versions.
+    pub ttl_secs: Option<u64>,
 }
 
 impl std::fmt::Debug for ExecutionConfig {
@@ -53,6 +69,7 @@ impl Default for ExecutionConfig {
             paranoid_type_verification: true,
             paranoid_hot_potato_verification: true,
             processed_transactions_detailed_counters: false,
+            module_cache: ModuleCacheConfig::default(),
         }
     }
 }
@@ -33,6 +33,13 @@ pub struct IndexerConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub starting_version: Option<u64>,
 
+    /// If set and there's no progress in the db yet, initializes the processor from the latest
+    /// state snapshot on the node and streams forward from there instead of replaying from
+    /// genesis. Only supported by processors that just need current state, e.g. object_processor.
+    /// Ignored if `starting_version` is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bootstrap_from_snapshot: Option<bool>,
+
     ///////////////////
     ///////////////////
     ///////////////////
@@ -74,6 +81,75 @@ pub struct IndexerConfig {
     /// Custom NFT points contract
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub nft_points_contract: Option<String>,
+
+    /// Endpoints that the webhook_processor pushes matching events to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+/// A single push notification target for the webhook_processor
+#[derive(Clone, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Where matching events are POSTed, ex: "https://example.com/aptos/events". With a
+    /// `redis://` or `rediss://` url, they are appended to `stream` instead.
+    pub url: String,
+
+    /// The Redis stream to append matching events to, for `redis://` and `rediss://` urls
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream: Option<String>,
+
+    /// Only send events of these types, ex: "0x1::coin::DepositEvent". A module ("0x3::token") or
+    /// a generic type without its parameters ("0x1::coin::CoinStore") matches everything inside it.
+    /// If empty, events of any type are sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub event_types: Vec<String>,
+
+    /// Only send events emitted under these accounts (the event handle's account). If empty,
+    /// events from any account are sent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub account_addresses: Vec<String>,
+
+    /// Only send events matching this event filter as well, in the syntax of the `filter` query
+    /// parameter of the events endpoints, ex: "data.amount >= 1000000"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+
+    /// If set, every request carries an `X-Aptos-Signature` header (every stream entry a
+    /// `signature` field) holding the hex encoded HMAC-SHA256 of the body under this secret
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing_secret: Option<String>,
+
+    /// How many times to retry a failed delivery before failing the batch (default 3)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+}
+
+impl WebhookConfig {
+    /// The url with its password, if any, masked
+    pub fn redacted_url(&self) -> String {
+        match url::Url::parse(&self.url) {
+            Ok(mut parsed_url) if parsed_url.password().is_some() => {
+                parsed_url.set_password(Some("*")).unwrap();
+                parsed_url.to_string()
+            },
+            _ => self.url.clone(),
+        }
+    }
+}
+
+impl Debug for WebhookConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookConfig")
+            .field("url", &self.redacted_url())
+            .field("stream", &self.stream)
+            .field("event_types", &self.event_types)
+            .field("account_addresses", &self.account_addresses)
+            .field("filter", &self.filter)
+            .field("signing_secret", &self.signing_secret.as_ref().map(|_| "*"))
+            .field("max_retries", &self.max_retries)
+            .finish()
+    }
 }
 
 impl Debug for IndexerConfig {
@@ -90,6 +166,7 @@ impl Debug for IndexerConfig {
             .field("postgres_uri", &postgres_uri)
             .field("processor", &self.processor)
             .field("starting_version", &self.starting_version)
+            .field("bootstrap_from_snapshot", &self.bootstrap_from_snapshot)
             .field("skip_migrations", &self.skip_migrations)
             .field("check_chain_id", &self.check_chain_id)
             .field("batch_size", &self.batch_size)
@@ -99,6 +176,7 @@ impl Debug for IndexerConfig {
             .field("gap_lookback_versions", &self.gap_lookback_versions)
             .field("ans_contract_address", &self.ans_contract_address)
             .field("nft_points_contract", &self.nft_points_contract)
+            .field("webhooks", &self.webhooks)
             .finish()
     }
 }
@@ -19,6 +19,7 @@ mod node_config;
 mod peer_monitoring_config;
 mod persistable_config;
 mod quorum_store_config;
+mod reloadable_config;
 mod safety_rules_config;
 mod secure_backend_config;
 mod state_sync_config;
@@ -43,6 +44,7 @@ pub use node_config::*;
 pub use peer_monitoring_config::*;
 pub use persistable_config::*;
 pub use quorum_store_config::*;
+pub use reloadable_config::*;
 pub use safety_rules_config::*;
 pub use secure_backend_config::*;
 pub use state_sync_config::*;
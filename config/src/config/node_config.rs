@@ -160,6 +160,7 @@ impl NodeConfig {
             },
         };
 
+        self.indexer.bootstrap_from_snapshot = self.indexer.bootstrap_from_snapshot.or(Some(false));
         self.indexer.skip_migrations = self.indexer.skip_migrations.or(Some(false));
         self.indexer.check_chain_id = self.indexer.check_chain_id.or(Some(true));
         self.indexer.batch_size = default_if_zero(
@@ -0,0 +1,162 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::{Error, NodeConfig, PersistableConfig};
+use aptos_infallible::{Mutex, RwLock};
+use aptos_logger::Level;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// The subset of the node config that can be changed without restarting the node, by editing
+/// the config file and then sending the node a SIGHUP or calling the `/v1/admin/reload_config`
+/// endpoint. Changes to any other field of the config file only take effect after a restart.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReloadableConfig {
+    /// `logger.level`
+    pub log_level: Level,
+    /// `mempool.capacity`
+    pub mempool_capacity: usize,
+    /// `mempool.capacity_bytes`
+    pub mempool_capacity_bytes: usize,
+    /// `mempool.capacity_per_user`
+    pub mempool_capacity_per_user: usize,
+    /// `api.max_submit_transaction_batch_size`
+    pub api_max_submit_transaction_batch_size: usize,
+    /// `api.max_batch_read_size`
+    pub api_max_batch_read_size: usize,
+    /// `api.max_transactions_page_size`
+    pub api_max_transactions_page_size: u16,
+    /// `api.max_events_page_size`
+    pub api_max_events_page_size: u16,
+    /// `api.max_account_resources_page_size`
+    pub api_max_account_resources_page_size: u16,
+    /// `api.max_account_modules_page_size`
+    pub api_max_account_modules_page_size: u16,
+    /// `api.max_gas_view_function`
+    pub api_max_gas_view_function: u64,
+    /// `api.max_events_by_type_scan_range`
+    pub api_max_events_by_type_scan_range: u64,
+    /// `api.max_state_keys_scan_range`
+    pub api_max_state_keys_scan_range: u64,
+}
+
+impl From<&NodeConfig> for ReloadableConfig {
+    fn from(config: &NodeConfig) -> Self {
+        Self {
+            log_level: config.logger.level,
+            mempool_capacity: config.mempool.capacity,
+            mempool_capacity_bytes: config.mempool.capacity_bytes,
+            mempool_capacity_per_user: config.mempool.capacity_per_user,
+            api_max_submit_transaction_batch_size: config.api.max_submit_transaction_batch_size,
+            api_max_batch_read_size: config.api.max_batch_read_size,
+            api_max_transactions_page_size: config.api.max_transactions_page_size,
+            api_max_events_page_size: config.api.max_events_page_size,
+            api_max_account_resources_page_size: config.api.max_account_resources_page_size,
+            api_max_account_modules_page_size: config.api.max_account_modules_page_size,
+            api_max_gas_view_function: config.api.max_gas_view_function,
+            api_max_events_by_type_scan_range: config.api.max_events_by_type_scan_range,
+            api_max_state_keys_scan_range: config.api.max_state_keys_scan_range,
+        }
+    }
+}
+
+type ReloadListener = Box<dyn Fn(&ReloadableConfig) + Send + Sync>;
+
+/// Holds the current [`ReloadableConfig`] of the node. Components either read the current
+/// values when they need them, or subscribe to be told about reloads. Cloning it shares the
+/// same state.
+#[derive(Clone)]
+pub struct ConfigReloader {
+    inner: Arc<ConfigReloaderInner>,
+}
+
+struct ConfigReloaderInner {
+    config_path: Option<PathBuf>,
+    current: RwLock<ReloadableConfig>,
+    listeners: Mutex<Vec<ReloadListener>>,
+}
+
+impl ConfigReloader {
+    /// `config_path` is the file the node config was loaded from. Without one, the config
+    /// can't be reloaded.
+    pub fn new(config_path: Option<PathBuf>, node_config: &NodeConfig) -> Self {
+        Self {
+            inner: Arc::new(ConfigReloaderInner {
+                config_path,
+                current: RwLock::new(node_config.into()),
+                listeners: Mutex::new(vec![]),
+            }),
+        }
+    }
+
+    pub fn config_path(&self) -> Option<&Path> {
+        self.inner.config_path.as_deref()
+    }
+
+    pub fn current(&self) -> ReloadableConfig {
+        *self.inner.current.read()
+    }
+
+    /// Calls `listener` with the new config on every reload
+    pub fn subscribe(&self, listener: impl Fn(&ReloadableConfig) + Send + Sync + 'static) {
+        self.inner.listeners.lock().push(Box::new(listener));
+    }
+
+    /// Reads the config file again and applies its reloadable fields
+    pub fn reload(&self) -> Result<ReloadableConfig, Error> {
+        let config_path = self
+            .config_path()
+            .ok_or(Error::Missing("the path of the node config file"))?;
+        let node_config = NodeConfig::load_config(config_path)?;
+        Ok(self.update((&node_config).into()))
+    }
+
+    /// Applies `config` and notifies the subscribers
+    pub fn update(&self, config: ReloadableConfig) -> ReloadableConfig {
+        *self.inner.current.write() = config;
+        for listener in self.inner.listeners.lock().iter() {
+            listener(&config);
+        }
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_temppath::TempPath;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_reload() {
+        let config_path = TempPath::new();
+        let mut node_config = NodeConfig::default();
+        node_config.save_config(config_path.path()).unwrap();
+
+        let config_reloader =
+            ConfigReloader::new(Some(config_path.path().to_path_buf()), &node_config);
+        let reloaded_capacity = Arc::new(AtomicUsize::new(0));
+        let listener_capacity = reloaded_capacity.clone();
+        config_reloader.subscribe(move |config| {
+            listener_capacity.store(config.mempool_capacity, Ordering::SeqCst);
+        });
+
+        node_config.mempool.capacity = 42;
+        node_config.api.max_batch_read_size = 7;
+        node_config.logger.level = Level::Debug;
+        node_config.save_config(config_path.path()).unwrap();
+
+        let config = config_reloader.reload().unwrap();
+        assert_eq!(config, ReloadableConfig::from(&node_config));
+        assert_eq!(config_reloader.current(), config);
+        assert_eq!(reloaded_capacity.load(Ordering::SeqCst), 42);
+    }
+
+    #[test]
+    fn test_reload_without_config_file() {
+        let config_reloader = ConfigReloader::new(None, &NodeConfig::default());
+        assert!(config_reloader.reload().is_err());
+    }
+}
@@ -109,6 +109,9 @@ pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
         prune_window: 0,
         batch_size: 0,
         user_pruning_window_offset: 0,
+        transaction_prune_window: None,
+        write_set_prune_window: None,
+        event_prune_window: None,
     },
     state_merkle_pruner_config: StateMerklePrunerConfig {
         enable: false,
@@ -138,6 +141,14 @@ pub struct LedgerPrunerConfig {
     pub batch_size: usize,
     /// The offset for user pruning window to adjust
     pub user_pruning_window_offset: u64,
+    /// Window size in versions of the transactions, their infos and the transaction accumulator,
+    /// overriding `prune_window`.
+    pub transaction_prune_window: Option<u64>,
+    /// Window size in versions of the write sets, overriding `prune_window`.
+    pub write_set_prune_window: Option<u64>,
+    /// Window size in versions of the events, overriding `prune_window`. E.g. to keep the events
+    /// for much longer than the transactions that emitted them.
+    pub event_prune_window: Option<u64>,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -193,6 +204,9 @@ impl Default for LedgerPrunerConfig {
             prune_window: 150_000_000,
             batch_size: 500,
             user_pruning_window_offset: 200_000,
+            transaction_prune_window: None,
+            write_set_prune_window: None,
+            event_prune_window: None,
         }
     }
 }
@@ -54,7 +54,15 @@ pub fn start_consensus(
         runtime.handle(),
     ));
 
-    let time_service = Arc::new(ClockTimeService::new(runtime.handle().clone()));
+    // Chain time can only be fast forwarded where the admin endpoints are enabled, i.e. on a
+    // local testnet
+    let time_service = if node_config.api.admin_endpoints_enabled {
+        Arc::new(ClockTimeService::new_local_testnet(
+            runtime.handle().clone(),
+        ))
+    } else {
+        Arc::new(ClockTimeService::new(runtime.handle().clone()))
+    };
 
     let (timeout_sender, timeout_receiver) =
         aptos_channels::new(1_024, &counters::PENDING_ROUND_TIMEOUTS);
@@ -94,13 +94,27 @@ where
 /// TimeService implementation that uses actual clock to schedule tasks
 pub struct ClockTimeService {
     executor: Handle,
+    /// Whether the chain clock offset set through the admin endpoints is added to the clock
+    follow_chain_clock_offset: bool,
 }
 
 impl ClockTimeService {
     /// Creates new TimeService that runs tasks based on actual clock
     /// It needs executor to schedule internal tasks that facilitates it's work
     pub fn new(executor: Handle) -> ClockTimeService {
-        ClockTimeService { executor }
+        ClockTimeService {
+            executor,
+            follow_chain_clock_offset: false,
+        }
+    }
+
+    /// Creates a TimeService for a local testnet, whose clock is ahead of the actual clock by
+    /// the offset set through the admin endpoints, see `aptos_types::timestamp::advance_chain_clock`.
+    pub fn new_local_testnet(executor: Handle) -> ClockTimeService {
+        ClockTimeService {
+            executor,
+            follow_chain_clock_offset: true,
+        }
     }
 }
 
@@ -120,7 +134,12 @@ impl TimeService for ClockTimeService {
     }
 
     fn get_current_timestamp(&self) -> Duration {
-        aptos_infallible::duration_since_epoch()
+        let now = aptos_infallible::duration_since_epoch();
+        if self.follow_chain_clock_offset {
+            now + aptos_types::timestamp::chain_clock_offset()
+        } else {
+            now
+        }
     }
 
     async fn sleep(&self, t: Duration) {
@@ -0,0 +1,48 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    bypasser::{Bypasser, BypasserConfig, BypasserTrait},
+    checkers::CheckerData,
+};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Funds requests matching a rule with a different amount by default, e.g. a
+/// larger amount for holders of a partner's auth token.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AmountRuleConfig {
+    /// Which requests the rule applies to. This uses the same matchers as the
+    /// Bypassers, but a match only changes the amount, the request still has
+    /// to pass the Checkers.
+    pub matcher: BypasserConfig,
+
+    /// The amount to fund matching requests with if they don't ask for a
+    /// specific amount. This is still capped by the Funder's maximum amount.
+    pub amount: u64,
+}
+
+impl AmountRuleConfig {
+    pub fn build(self) -> Result<AmountRule> {
+        Ok(AmountRule {
+            matcher: self.matcher.build()?,
+            amount: self.amount,
+        })
+    }
+}
+
+pub struct AmountRule {
+    matcher: Bypasser,
+    amount: u64,
+}
+
+impl AmountRule {
+    /// Returns the amount to fund the request with, if the rule applies to it.
+    pub async fn amount_for(&self, data: CheckerData) -> Result<Option<u64>> {
+        Ok(self
+            .matcher
+            .request_can_bypass(data)
+            .await?
+            .then_some(self.amount))
+    }
+}
@@ -1,15 +1,15 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{CheckerData, CheckerTrait, CompleteData};
+use super::{CheckerData, CheckerTrait, CompleteData, RatelimitKeyProvider};
 use crate::{
-    endpoints::{AptosTapError, RejectionReason, RejectionReasonCode},
+    endpoints::{AptosTapError, RejectionReason},
     helpers::{days_since_tap_epoch, get_current_time_secs},
 };
 use async_trait::async_trait;
 use lru::LruCache;
 use serde::{Deserialize, Serialize};
-use std::{net::IpAddr, num::NonZeroUsize, sync::atomic::AtomicU64};
+use std::{num::NonZeroUsize, sync::atomic::AtomicU64};
 use tokio::sync::Mutex;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -18,6 +18,10 @@ pub struct MemoryRatelimitCheckerConfig {
 
     #[serde(default = "MemoryRatelimitCheckerConfig::default_max_entries_in_map")]
     pub max_entries_in_map: NonZeroUsize,
+
+    /// Whether to limit requests per IP or per receiving account.
+    #[serde(default)]
+    pub ratelimit_key_provider: RatelimitKeyProvider,
 }
 
 impl MemoryRatelimitCheckerConfig {
@@ -26,15 +30,17 @@ impl MemoryRatelimitCheckerConfig {
     }
 }
 
-/// Simple in memory storage that rejects if we've ever seen a request from an
-/// IP that has succeeded.
+/// Simple in memory storage that rejects if an IP (or account, depending on
+/// the key provider) has exceeded its daily limit of successful requests.
 pub struct MemoryRatelimitChecker {
     pub max_requests_per_day: u32,
 
-    /// Map of IP to how many requests they've submitted today (where the
-    /// response wasn't a 500). To avoid OOMing the server, we set a limit
+    pub ratelimit_key_provider: RatelimitKeyProvider,
+
+    /// Map of ratelimit key to how many requests they've submitted today (where
+    /// the response wasn't a 500). To avoid OOMing the server, we set a limit
     /// on how many entries we have in the table.
-    pub ip_to_requests_today: Mutex<LruCache<IpAddr, u32>>,
+    pub key_to_requests_today: Mutex<LruCache<String, u32>>,
 
     /// Used for tracking daily ratelimit. See the comment in RedisRatelimitChecker
     /// for more information on how we track daily limits.
@@ -45,7 +51,8 @@ impl MemoryRatelimitChecker {
     pub fn new(args: MemoryRatelimitCheckerConfig) -> Self {
         Self {
             max_requests_per_day: args.max_requests_per_day,
-            ip_to_requests_today: Mutex::new(LruCache::new(args.max_entries_in_map)),
+            ratelimit_key_provider: args.ratelimit_key_provider,
+            key_to_requests_today: Mutex::new(LruCache::new(args.max_entries_in_map)),
             current_day: AtomicU64::new(days_since_tap_epoch(get_current_time_secs())),
         }
     }
@@ -58,7 +65,7 @@ impl MemoryRatelimitChecker {
                 days_since_tap_epoch(get_current_time_secs()),
                 std::sync::atomic::Ordering::Relaxed,
             );
-            self.ip_to_requests_today.lock().await.clear();
+            self.key_to_requests_today.lock().await.clear();
         }
     }
 }
@@ -72,17 +79,14 @@ impl CheckerTrait for MemoryRatelimitChecker {
     ) -> Result<Vec<RejectionReason>, AptosTapError> {
         self.clear_if_new_day().await;
 
-        let mut ip_to_requests_today = self.ip_to_requests_today.lock().await;
+        let mut key_to_requests_today = self.key_to_requests_today.lock().await;
 
-        let requests_today = ip_to_requests_today.get_or_insert_mut(data.source_ip, || 1);
+        let key = self.ratelimit_key_provider.key(&data);
+        let requests_today = key_to_requests_today.get_or_insert_mut(key, || 1);
         if *requests_today >= self.max_requests_per_day {
-            return Ok(vec![RejectionReason::new(
-                format!(
-                    "IP {} has exceeded the daily limit of {} requests",
-                    data.source_ip, self.max_requests_per_day
-                ),
-                RejectionReasonCode::IpUsageLimitExhausted,
-            )]);
+            return Ok(vec![self
+                .ratelimit_key_provider
+                .rejection_reason(&data, self.max_requests_per_day)]);
         } else if !dry_run {
             *requests_today += 1;
         }
@@ -92,11 +96,12 @@ impl CheckerTrait for MemoryRatelimitChecker {
 
     async fn complete(&self, data: CompleteData) -> Result<(), AptosTapError> {
         if data.response_is_500 {
+            let key = self.ratelimit_key_provider.key(&data.checker_data);
             *self
-                .ip_to_requests_today
+                .key_to_requests_today
                 .lock()
                 .await
-                .get_or_insert_mut(data.checker_data.source_ip, || 1) -= 1;
+                .get_or_insert_mut(key, || 1) -= 1;
         }
         Ok(())
     }
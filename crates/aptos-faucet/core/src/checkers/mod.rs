@@ -9,6 +9,7 @@ mod memory_ratelimit;
 mod redis_ratelimit;
 mod referer_blocklist;
 mod tap_captcha;
+mod webhook;
 
 pub use self::tap_captcha::CaptchaManager;
 use self::{
@@ -20,10 +21,11 @@ use self::{
     redis_ratelimit::{RedisRatelimitChecker, RedisRatelimitCheckerConfig},
     referer_blocklist::RefererBlocklistChecker,
     tap_captcha::{TapCaptchaChecker, TapCaptchaCheckerConfig},
+    webhook::{WebhookChecker, WebhookCheckerConfig},
 };
 use crate::{
     common::{IpRangeManagerConfig, ListManagerConfig},
-    endpoints::{AptosTapError, RejectionReason},
+    endpoints::{AptosTapError, RejectionReason, RejectionReasonCode},
 };
 use anyhow::Result;
 use aptos_sdk::types::account_address::AccountAddress;
@@ -104,6 +106,11 @@ pub enum CheckerConfig {
 
     /// In-house captcha solution.
     TapCaptcha(TapCaptchaCheckerConfig),
+
+    /// Asks an external service whether to allow the request, so operators can
+    /// enforce their own rules (e.g. token or captcha verification) without
+    /// patching the tap.
+    Webhook(WebhookCheckerConfig),
 }
 
 impl CheckerConfig {
@@ -127,6 +134,7 @@ impl CheckerConfig {
             CheckerConfig::TapCaptcha(config) => {
                 Checker::from(TapCaptchaChecker::new(config, captcha_manager)?)
             },
+            CheckerConfig::Webhook(config) => Checker::from(WebhookChecker::new(config)?),
         })
     }
 }
@@ -142,6 +150,7 @@ pub enum Checker {
     RedisRatelimitChecker,
     RefererBlocklistChecker,
     TapCaptchaChecker,
+    WebhookChecker,
 }
 
 #[derive(Clone, Debug)]
@@ -159,3 +168,47 @@ pub struct CompleteData {
     pub txn_hashes: Vec<String>,
     pub response_is_500: bool,
 }
+
+/// What the ratelimit Checkers count requests by.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RatelimitKeyProvider {
+    /// Count requests per source IP.
+    #[default]
+    Ip,
+
+    /// Count requests per receiving account, regardless of where they come from.
+    Account,
+}
+
+impl RatelimitKeyProvider {
+    /// The key to count the request under, e.g. `ip:127.0.0.1`.
+    pub fn key(&self, data: &CheckerData) -> String {
+        match self {
+            RatelimitKeyProvider::Ip => format!("ip:{}", data.source_ip),
+            RatelimitKeyProvider::Account => format!("account:{}", data.receiver),
+        }
+    }
+
+    pub fn rejection_reason(
+        &self,
+        data: &CheckerData,
+        max_requests_per_day: u32,
+    ) -> RejectionReason {
+        match self {
+            RatelimitKeyProvider::Ip => RejectionReason::new(
+                format!(
+                    "IP {} has exceeded the daily limit of {} requests",
+                    data.source_ip, max_requests_per_day
+                ),
+                RejectionReasonCode::IpUsageLimitExhausted,
+            ),
+            RatelimitKeyProvider::Account => RejectionReason::new(
+                format!(
+                    "Account {} has exceeded the daily limit of {} requests",
+                    data.receiver, max_requests_per_day
+                ),
+                RejectionReasonCode::AccountUsageLimitExhausted,
+            ),
+        }
+    }
+}
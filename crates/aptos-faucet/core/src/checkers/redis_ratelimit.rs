@@ -1,9 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use super::{CheckerData, CheckerTrait, CompleteData};
+use super::{CheckerData, CheckerTrait, CompleteData, RatelimitKeyProvider};
 use crate::{
-    endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode},
+    endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason},
     helpers::{days_since_tap_epoch, get_current_time_secs, seconds_until_next_day},
 };
 use anyhow::{Context, Result};
@@ -13,7 +13,6 @@ use deadpool_redis::{
     Config, Connection, Pool, Runtime,
 };
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct RedisRatelimitCheckerConfig {
@@ -36,8 +35,13 @@ pub struct RedisRatelimitCheckerConfig {
     pub database_password: Option<String>,
 
     /// Max number of requests per IP per day. 500s are not counted, because
-    /// they are not the user's fault, but everything else is.
+    /// they are not the user's fault, but everything else is. If the key
+    /// provider is `Account`, this is the limit per receiving account instead.
     pub max_requests_per_ip_per_day: u32,
+
+    /// Whether to limit requests per IP or per receiving account.
+    #[serde(default)]
+    pub ratelimit_key_provider: RatelimitKeyProvider,
 }
 
 impl RedisRatelimitCheckerConfig {
@@ -129,10 +133,14 @@ impl RedisRatelimitChecker {
     }
 
     // Returns the key and the seconds until the next day.
-    fn get_key_and_secs_until_next_day(&self, source_ip: &IpAddr) -> (String, u64) {
+    fn get_key_and_secs_until_next_day(&self, data: &CheckerData) -> (String, u64) {
         let now_secs = get_current_time_secs();
         let seconds_until_next_day = seconds_until_next_day(now_secs);
-        let key = format!("ip:{}:{}", source_ip, days_since_tap_epoch(now_secs));
+        let key = format!(
+            "{}:{}",
+            self.args.ratelimit_key_provider.key(data),
+            days_since_tap_epoch(now_secs)
+        );
         (key, seconds_until_next_day)
     }
 
@@ -144,14 +152,10 @@ impl RedisRatelimitChecker {
     ) -> Option<RejectionReason> {
         if limit_value.unwrap_or(0) > self.args.max_requests_per_ip_per_day as i64 {
             Some(
-                RejectionReason::new(
-                    format!(
-                        "IP {} has reached the maximum allowed number of requests per day: {}",
-                        data.source_ip, self.args.max_requests_per_ip_per_day
-                    ),
-                    RejectionReasonCode::IpUsageLimitExhausted,
-                )
-                .retry_after(seconds_until_next_day),
+                self.args
+                    .ratelimit_key_provider
+                    .rejection_reason(data, self.args.max_requests_per_ip_per_day)
+                    .retry_after(seconds_until_next_day),
             )
         } else {
             None
@@ -171,8 +175,8 @@ impl CheckerTrait for RedisRatelimitChecker {
             .await
             .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::StorageError))?;
 
-        // Generate a key corresponding to this IP address and the current day.
-        let (key, seconds_until_next_day) = self.get_key_and_secs_until_next_day(&data.source_ip);
+        // Generate a key corresponding to this IP address (or account) and the current day.
+        let (key, seconds_until_next_day) = self.get_key_and_secs_until_next_day(&data);
 
         // Get the value for the key, indicating how many non-500 requests we
         // have serviced for this it today.
@@ -249,8 +253,8 @@ impl CheckerTrait for RedisRatelimitChecker {
             .await
             .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::StorageError))?;
 
-        // Generate a key corresponding to this IP address and the current day.
-        let (key, _) = self.get_key_and_secs_until_next_day(&data.checker_data.source_ip);
+        // Generate a key corresponding to this IP address (or account) and the current day.
+        let (key, _) = self.get_key_and_secs_until_next_day(&data.checker_data);
 
         conn.decr(&key, 1).await.map_err(|e| {
             AptosTapError::new_with_error_code(
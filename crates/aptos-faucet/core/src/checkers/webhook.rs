@@ -0,0 +1,138 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CheckerData, CheckerTrait};
+use crate::endpoints::{AptosTapError, AptosTapErrorCode, RejectionReason, RejectionReasonCode};
+use anyhow::{Context, Result};
+use aptos_logger::debug;
+use async_trait::async_trait;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, time::Duration};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebhookCheckerConfig {
+    /// The URL to POST a WebhookRequest to for every request to the tap.
+    pub url: Url,
+
+    /// Headers of the incoming request to forward to the webhook, e.g. the
+    /// Authorization header or a captcha token header.
+    #[serde(default)]
+    pub forwarded_headers: Vec<String>,
+
+    /// How long to wait for the webhook to respond. If it doesn't respond in
+    /// time, the request fails with a 500.
+    #[serde(default = "WebhookCheckerConfig::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl WebhookCheckerConfig {
+    fn default_timeout_secs() -> u64 {
+        5
+    }
+}
+
+/// What we send to the webhook.
+#[derive(Debug, Serialize)]
+struct WebhookRequest {
+    receiver: String,
+    amount: u64,
+    source_ip: String,
+    headers: HashMap<String, String>,
+    dry_run: bool,
+}
+
+/// What we expect back from the webhook, with a 200.
+#[derive(Debug, Deserialize)]
+struct WebhookResponse {
+    allow: bool,
+    /// Explanation for the user of why the request was rejected.
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+/// This Checker delegates the decision of whether to allow a request to an
+/// external service. This lets operators of private testnets enforce their
+/// own policies (e.g. verifying tokens issued by their own auth system)
+/// without patching the tap.
+pub struct WebhookChecker {
+    config: WebhookCheckerConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookChecker {
+    pub fn new(config: WebhookCheckerConfig) -> Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .context("Failed to build webhook client")?;
+        Ok(Self { config, client })
+    }
+}
+
+#[async_trait]
+impl CheckerTrait for WebhookChecker {
+    async fn check(
+        &self,
+        data: CheckerData,
+        dry_run: bool,
+    ) -> Result<Vec<RejectionReason>, AptosTapError> {
+        let headers = self
+            .config
+            .forwarded_headers
+            .iter()
+            .filter_map(|name| {
+                data.headers
+                    .get(name.as_str())
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| (name.clone(), value.to_string()))
+            })
+            .collect();
+        let request = WebhookRequest {
+            receiver: data.receiver.to_hex_literal(),
+            amount: data.amount,
+            source_ip: data.source_ip.to_string(),
+            headers,
+            dry_run,
+        };
+
+        let response = self
+            .client
+            .post(self.config.url.clone())
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::CheckerError))?;
+        let status_code = response.status();
+        if !status_code.is_success() {
+            debug!(
+                message = "Webhook returned error status code",
+                status = status_code.as_str()
+            );
+            return Err(AptosTapError::new(
+                format!("Webhook returned error status code {}", status_code),
+                AptosTapErrorCode::CheckerError,
+            ));
+        }
+        let response: WebhookResponse = response
+            .json()
+            .await
+            .map_err(|e| AptosTapError::new_with_error_code(e, AptosTapErrorCode::CheckerError))?;
+
+        if response.allow {
+            Ok(vec![])
+        } else {
+            Ok(vec![RejectionReason::new(
+                response
+                    .reason
+                    .unwrap_or_else(|| "Rejected by webhook".to_string()),
+                RejectionReasonCode::WebhookRejected,
+            )])
+        }
+    }
+
+    fn cost(&self) -> u8 {
+        // An extra network round trip, like the Google captcha checker.
+        10
+    }
+}
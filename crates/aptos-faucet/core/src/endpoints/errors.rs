@@ -256,4 +256,10 @@ pub enum RejectionReasonCode {
 
     /// Referer was in the blocklist.
     RefererBlocklisted = 108,
+
+    /// Account has exhausted its usage limit.
+    AccountUsageLimitExhausted = 109,
+
+    /// An external webhook rejected the request.
+    WebhookRejected = 110,
 }
@@ -6,6 +6,7 @@ use super::{
     ApiTags,
 };
 use crate::{
+    amount_rules::AmountRule,
     bypasser::{Bypasser, BypasserTrait},
     checkers::{Checker, CheckerData, CheckerTrait, CompleteData},
     endpoints::AptosTapErrorCode,
@@ -172,6 +173,10 @@ pub struct FundApiComponents {
     /// If any of the checkers say no, the request is rejected.
     pub checkers: Vec<Checker>,
 
+    /// The first of these that matches a request decides how much it gets
+    /// funded with, unless the request asks for a specific amount.
+    pub amount_rules: Vec<AmountRule>,
+
     /// The component that funds accounts.
     pub funder: Arc<Funder>,
 
@@ -235,10 +240,7 @@ impl FundApiComponents {
         // but it's okay for now.
         let amount = self.funder.get_amount(fund_request.amount);
 
-        // Include some additional logging that the logging middleware doesn't do.
-        info!(source_ip = source_ip, account = receiver, amount = amount);
-
-        let checker_data = CheckerData {
+        let mut checker_data = CheckerData {
             amount,
             receiver,
             source_ip,
@@ -246,6 +248,31 @@ impl FundApiComponents {
             time_request_received_secs: get_current_time_secs(),
         };
 
+        // If the request didn't ask for a specific amount, the first matching
+        // amount rule (if any) decides how much to fund it with.
+        if fund_request.amount.is_none() {
+            for amount_rule in &self.amount_rules {
+                if let Some(amount) =
+                    amount_rule
+                        .amount_for(checker_data.clone())
+                        .await
+                        .map_err(|e| {
+                            AptosTapError::new_with_error_code(e, AptosTapErrorCode::BypasserError)
+                        })?
+                {
+                    checker_data.amount = self.funder.get_amount(Some(amount));
+                    break;
+                }
+            }
+        }
+
+        // Include some additional logging that the logging middleware doesn't do.
+        info!(
+            source_ip = source_ip,
+            account = receiver,
+            amount = checker_data.amount
+        );
+
         // See if this request meets the criteria to bypass checkers / storage.
         for bypasser in &self.bypassers {
             if bypasser
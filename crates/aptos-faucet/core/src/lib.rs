@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod amount_rules;
 pub mod bypasser;
 pub mod checkers;
 pub mod common;
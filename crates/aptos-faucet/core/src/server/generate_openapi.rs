@@ -24,6 +24,7 @@ impl GenerateOpenapi {
             components: Arc::new(FundApiComponents {
                 bypassers: Vec::new(),
                 checkers: Vec::new(),
+                amount_rules: Vec::new(),
                 funder: funder.clone(),
                 return_rejections_early: true,
                 concurrent_requests_semaphore: None,
@@ -3,6 +3,7 @@
 
 use super::server_args::ServerConfig;
 use crate::{
+    amount_rules::{AmountRule, AmountRuleConfig},
     bypasser::{Bypasser, BypasserConfig},
     checkers::{CaptchaManager, Checker, CheckerConfig, CheckerTrait},
     endpoints::{
@@ -59,6 +60,11 @@ pub struct RunConfig {
     /// Configs for any Checkers we might want to enable.
     checker_configs: Vec<CheckerConfig>,
 
+    /// Configs for rules that change how much matching requests are funded
+    /// with. The first matching rule wins.
+    #[serde(default)]
+    amount_rule_configs: Vec<AmountRuleConfig>,
+
     /// Config for the Funder component.
     funder_config: FunderConfig,
 
@@ -129,10 +135,23 @@ impl RunConfig {
         // cost Checkers are at the start of the vec.
         checkers.sort_by_key(|a| a.cost());
 
+        // Build amount rules, keeping them in the configured order.
+        let mut amount_rules: Vec<AmountRule> = Vec::new();
+        for amount_rule_config in &self.amount_rule_configs {
+            let amount_rule = amount_rule_config.clone().build().with_context(|| {
+                format!(
+                    "Failed to build amount rule with args: {:?}",
+                    amount_rule_config
+                )
+            })?;
+            amount_rules.push(amount_rule);
+        }
+
         // Using those, build the fund API components.
         let fund_api_components = Arc::new(FundApiComponents {
             bypassers,
             checkers,
+            amount_rules,
             funder,
             return_rejections_early: self.handler_config.return_rejections_early,
             concurrent_requests_semaphore,
@@ -259,6 +278,7 @@ impl RunConfig {
             },
             bypasser_configs: vec![],
             checker_configs: vec![],
+            amount_rule_configs: vec![],
             funder_config: FunderConfig::MintFunder(MintFunderConfig {
                 api_connection_config: ApiConnectionConfig::new(
                     api_url,
@@ -411,7 +431,7 @@ mod test {
         format!("{}/fund", get_root_endpoint(port))
     }
 
-    async fn start_server(config_content: &'static str) -> Result<(u16, JoinHandle<Result<()>>)> {
+    async fn start_server(config_content: &str) -> Result<(u16, JoinHandle<Result<()>>)> {
         // Load config.
         let run_config: RunConfig =
             serde_yaml::from_str(config_content).context("Failed to parse config content")?;
@@ -607,6 +627,168 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_account_ratelimit() -> Result<()> {
+        init();
+        let config_content = include_str!("../../../configs/testing_account_ratelimit.yaml");
+        let (port, _handle) = start_server(config_content).await?;
+
+        // The first request for an account succeeds.
+        let fund_request = get_fund_request(Some(10));
+        unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(fund_request.to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .send()
+                .await,
+        )
+        .await?;
+
+        // The second request for the same account is rejected.
+        let response = reqwest::Client::new()
+            .post(get_fund_endpoint(port))
+            .body(fund_request.to_json_string())
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await?;
+        let aptos_error = AptosTapError::parse_from_json_string(&response.text().await?)
+            .expect("Failed to read response as AptosError");
+        let rejection_reason_codes: HashSet<RejectionReasonCode> = aptos_error
+            .rejection_reasons
+            .into_iter()
+            .map(|r| r.get_code())
+            .collect();
+        assert!(rejection_reason_codes.contains(&RejectionReasonCode::AccountUsageLimitExhausted));
+
+        // A request for another account from the same IP still succeeds.
+        unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(get_fund_request(Some(10)).to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .send()
+                .await,
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    type WebhookRequests = Arc<std::sync::Mutex<Vec<serde_json::Value>>>;
+
+    /// Starts a webhook which only allows requests forwarding the magic header,
+    /// returning its URL and the requests it receives.
+    async fn start_webhook() -> Result<(String, WebhookRequests)> {
+        #[poem::handler]
+        fn webhook(
+            poem::web::Json(request): poem::web::Json<serde_json::Value>,
+            requests: poem::web::Data<&WebhookRequests>,
+        ) -> poem::web::Json<serde_json::Value> {
+            let allow = request["headers"]["what_wallet_my_guy"] == "the_wallet_that_rocks";
+            requests.lock().unwrap().push(request);
+            poem::web::Json(serde_json::json!({
+                "allow": allow,
+                "reason": "Unknown wallet",
+            }))
+        }
+
+        let requests = WebhookRequests::default();
+        let port = aptos_config::utils::get_available_port();
+        let route = Route::new().at("/", poem::post(webhook.data(requests.clone())));
+        tokio::spawn(Server::new(TcpListener::bind(("127.0.0.1", port))).run(route));
+        let url = get_root_endpoint(port);
+        while reqwest::get(&url).await.is_err() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Ok((url, requests))
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_webhook_and_amount_rules() -> Result<()> {
+        init();
+        make_auth_tokens_file(&["test_token"])?;
+        let (webhook_url, webhook_requests) = start_webhook().await?;
+        let config_content = format!(
+            r#"---
+server_config:
+  api_path_base: ""
+metrics_server_config:
+  listen_port: 9105
+bypasser_configs: []
+checker_configs:
+  - type: "Webhook"
+    url: "{}"
+    forwarded_headers: ["what_wallet_my_guy"]
+amount_rule_configs:
+  - matcher:
+      type: "AuthToken"
+      file: "/tmp/auth_tokens.txt"
+    amount: 1000
+funder_config:
+  type: "FakeFunder"
+handler_config:
+  use_helpful_errors: true
+  return_rejections_early: false
+"#,
+            webhook_url
+        );
+        let (port, _handle) = start_server(&config_content).await?;
+        let last_webhook_amount = || {
+            webhook_requests.lock().unwrap().last().unwrap()["amount"]
+                .as_u64()
+                .unwrap()
+        };
+
+        // The webhook rejects requests without the magic header, and is asked
+        // about the default amount.
+        let response = reqwest::Client::new()
+            .post(get_fund_endpoint(port))
+            .body(get_fund_request(None).to_json_string())
+            .header(CONTENT_TYPE, "application/json")
+            .send()
+            .await?;
+        let aptos_error = AptosTapError::parse_from_json_string(&response.text().await?)
+            .expect("Failed to read response as AptosError");
+        let rejection_reason_codes: HashSet<RejectionReasonCode> = aptos_error
+            .rejection_reasons
+            .into_iter()
+            .map(|r| r.get_code())
+            .collect();
+        assert!(rejection_reason_codes.contains(&RejectionReasonCode::WebhookRejected));
+        assert_eq!(last_webhook_amount(), 100);
+
+        // Requests matching the amount rule are funded with its amount.
+        unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(get_fund_request(None).to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .header(AUTHORIZATION, "Bearer test_token")
+                .header("what_wallet_my_guy", "the_wallet_that_rocks")
+                .send()
+                .await,
+        )
+        .await?;
+        assert_eq!(last_webhook_amount(), 1000);
+
+        // Unless they ask for a specific amount.
+        unwrap_reqwest_result(
+            reqwest::Client::new()
+                .post(get_fund_endpoint(port))
+                .body(get_fund_request(Some(10)).to_json_string())
+                .header(CONTENT_TYPE, "application/json")
+                .header(AUTHORIZATION, "Bearer test_token")
+                .header("what_wallet_my_guy", "the_wallet_that_rocks")
+                .send()
+                .await,
+        )
+        .await?;
+        assert_eq!(last_webhook_amount(), 10);
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
     async fn test_redis_ratelimiter() -> Result<()> {
         // Assert that a local testnet is alive.
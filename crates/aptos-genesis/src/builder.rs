@@ -27,11 +27,12 @@ use aptos_keygen::KeyGen;
 use aptos_logger::prelude::*;
 use aptos_types::{
     chain_id::ChainId,
-    on_chain_config::{GasScheduleV2, OnChainConsensusConfig},
+    on_chain_config::{FeatureFlag, GasScheduleV2, OnChainConsensusConfig},
+    state_store::state_export::StateExport,
     transaction::Transaction,
     waypoint::Waypoint,
 };
-use aptos_vm_genesis::default_gas_schedule;
+use aptos_vm_genesis::{default_gas_schedule, AccountBalance};
 use rand::Rng;
 use serde::{de::DeserializeOwned, Serialize};
 use std::{
@@ -409,6 +410,36 @@ pub struct GenesisConfiguration {
     pub employee_vesting_period_duration: Option<u64>,
     pub consensus_config: OnChainConsensusConfig,
     pub gas_schedule: GasScheduleV2,
+    pub initial_features_override: Option<Vec<FeatureFlag>>,
+    pub initial_balances: Vec<AccountBalance>,
+    /// State exported from another network, written over the genesis state
+    pub imported_state: Option<StateExport>,
+}
+
+impl Default for GenesisConfiguration {
+    /// Configuration for a test network: no minimum stake, one day epochs and lockups
+    fn default() -> Self {
+        Self {
+            allow_new_validators: false,
+            epoch_duration_secs: ONE_DAY,
+            is_test: true,
+            min_stake: 0,
+            min_voting_threshold: 0,
+            max_stake: u64::MAX,
+            recurring_lockup_duration_secs: ONE_DAY,
+            required_proposer_stake: 0,
+            rewards_apy_percentage: 10,
+            voting_duration_secs: ONE_DAY / 24,
+            voting_power_increase_limit: 50,
+            employee_vesting_start: None,
+            employee_vesting_period_duration: None,
+            consensus_config: OnChainConsensusConfig::default(),
+            gas_schedule: default_gas_schedule(),
+            initial_features_override: None,
+            initial_balances: vec![],
+            imported_state: None,
+        }
+    }
 }
 
 pub type InitConfigFn = Arc<dyn Fn(usize, &mut NodeConfig, &mut u64) + Send + Sync>;
@@ -592,23 +623,7 @@ impl Builder {
             configs.push(validator.try_into()?);
         }
 
-        let mut genesis_config = GenesisConfiguration {
-            allow_new_validators: false,
-            epoch_duration_secs: ONE_DAY,
-            is_test: true,
-            min_stake: 0,
-            min_voting_threshold: 0,
-            max_stake: u64::MAX,
-            recurring_lockup_duration_secs: ONE_DAY,
-            required_proposer_stake: 0,
-            rewards_apy_percentage: 10,
-            voting_duration_secs: ONE_DAY / 24,
-            voting_power_increase_limit: 50,
-            employee_vesting_start: None,
-            employee_vesting_period_duration: None,
-            consensus_config: OnChainConsensusConfig::default(),
-            gas_schedule: default_gas_schedule(),
-        };
+        let mut genesis_config = GenesisConfiguration::default();
         if let Some(init_genesis_config) = &self.init_genesis_config {
             (init_genesis_config)(&mut genesis_config);
         }
@@ -0,0 +1,274 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Builds the genesis transaction and waypoint of a custom chain in code, for tools and tests
+//! that bring up their own networks. Unlike [`crate::builder::Builder`], it doesn't generate
+//! node configs or keys: the validators are given as [`ValidatorConfiguration`]s.
+//!
+//! ```ignore
+//! let output = GenesisBuilder::new(root_key)
+//!     .with_chain_id(ChainId::new(42))
+//!     .with_validator(validator)
+//!     .with_balance(alice, 100_000_000)
+//!     .with_features(vec![FeatureFlag::STRUCT_CONSTRUCTORS])
+//!     .with_package(my_package)
+//!     .build()?;
+//! output.write_to(&genesis_dir)?;
+//! ```
+
+use crate::{builder::GenesisConfiguration, config::ValidatorConfiguration, GenesisInfo};
+use anyhow::{ensure, Context};
+use aptos_crypto::ed25519::Ed25519PublicKey;
+use aptos_framework::{ReleaseBundle, ReleasePackage};
+use aptos_types::{
+    account_address::AccountAddress,
+    chain_id::ChainId,
+    on_chain_config::{FeatureFlag, GasScheduleV2},
+    state_store::state_export::StateExport,
+    transaction::Transaction,
+    waypoint::Waypoint,
+};
+use aptos_vm_genesis::AccountBalance;
+use std::{fs, path::Path};
+
+const GENESIS_FILE: &str = "genesis.blob";
+const WAYPOINT_FILE: &str = "waypoint.txt";
+
+/// Configures and builds the genesis of a chain, see the module docs
+#[derive(Clone)]
+pub struct GenesisBuilder {
+    chain_id: ChainId,
+    root_key: Ed25519PublicKey,
+    framework: ReleaseBundle,
+    validators: Vec<ValidatorConfiguration>,
+    genesis_config: GenesisConfiguration,
+}
+
+impl GenesisBuilder {
+    /// `root_key` controls the core resources account, which can mint coins. Starts from a test
+    /// chain id, the head framework and [`GenesisConfiguration::default`].
+    pub fn new(root_key: Ed25519PublicKey) -> Self {
+        Self {
+            chain_id: ChainId::test(),
+            root_key,
+            framework: aptos_cached_packages::head_release_bundle().clone(),
+            validators: vec![],
+            genesis_config: GenesisConfiguration::default(),
+        }
+    }
+
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Framework published at genesis, instead of the head framework
+    pub fn with_framework(mut self, framework: ReleaseBundle) -> Self {
+        self.framework = framework;
+        self
+    }
+
+    /// Extra package published at genesis, at its own address. Packages are published in the
+    /// order they're added, after the framework, so they can depend on it and on each other.
+    pub fn with_package(mut self, package: ReleasePackage) -> Self {
+        self.framework.packages.push(package);
+        self
+    }
+
+    pub fn with_validator(mut self, validator: ValidatorConfiguration) -> Self {
+        self.validators.push(validator);
+        self
+    }
+
+    pub fn with_validators(mut self, validators: Vec<ValidatorConfiguration>) -> Self {
+        self.validators.extend(validators);
+        self
+    }
+
+    /// Creates the account, if needed, and funds it at genesis
+    pub fn with_balance(mut self, account_address: AccountAddress, balance: u64) -> Self {
+        self.genesis_config.initial_balances.push(AccountBalance {
+            account_address,
+            balance,
+        });
+        self
+    }
+
+    /// Features enabled at genesis, instead of `aptos_vm_genesis::default_features()`
+    pub fn with_features(mut self, features: Vec<FeatureFlag>) -> Self {
+        self.genesis_config.initial_features_override = Some(features);
+        self
+    }
+
+    /// State exported from another network with `aptos-db-tool export-state`, written over
+    /// the genesis state
+    pub fn with_imported_state(mut self, imported_state: StateExport) -> Self {
+        self.genesis_config.imported_state = Some(imported_state);
+        self
+    }
+
+    pub fn with_gas_schedule(mut self, gas_schedule: GasScheduleV2) -> Self {
+        self.genesis_config.gas_schedule = gas_schedule;
+        self
+    }
+
+    /// For the remaining settings (epoch duration, stake limits, consensus config, ...)
+    pub fn with_genesis_config(
+        mut self,
+        update_genesis_config: impl FnOnce(&mut GenesisConfiguration),
+    ) -> Self {
+        update_genesis_config(&mut self.genesis_config);
+        self
+    }
+
+    /// Generates the genesis transaction and the waypoint of the resulting state
+    pub fn build(self) -> anyhow::Result<GenesisOutput> {
+        ensure!(
+            !self.validators.is_empty(),
+            "Genesis needs at least one validator"
+        );
+        let mut genesis_info = GenesisInfo::new(
+            self.chain_id,
+            self.root_key,
+            self.validators,
+            self.framework,
+            &self.genesis_config,
+        )?;
+        let waypoint = genesis_info.generate_waypoint()?;
+        Ok(GenesisOutput {
+            genesis: genesis_info.get_genesis().clone(),
+            waypoint,
+        })
+    }
+}
+
+/// The genesis of a chain built by [`GenesisBuilder`]
+#[derive(Clone, Debug)]
+pub struct GenesisOutput {
+    pub genesis: Transaction,
+    pub waypoint: Waypoint,
+}
+
+impl GenesisOutput {
+    /// Writes `genesis.blob` and `waypoint.txt` to `dir`, as `aptos genesis generate-genesis`
+    /// does, so nodes can be pointed at them.
+    pub fn write_to(&self, dir: &Path) -> anyhow::Result<()> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let genesis_bytes = bcs::to_bytes(&self.genesis)?;
+        fs::write(dir.join(GENESIS_FILE), genesis_bytes)
+            .with_context(|| format!("Failed to write {}", GENESIS_FILE))?;
+        fs::write(dir.join(WAYPOINT_FILE), self.waypoint.to_string())
+            .with_context(|| format!("Failed to write {}", WAYPOINT_FILE))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::HostAndPort, keys::generate_key_objects};
+    use aptos_crypto::{bls12381, PrivateKey};
+    use aptos_keygen::KeyGen;
+    use aptos_temppath::TempPath;
+    use aptos_types::{
+        access_path::Path as AccessPathKind,
+        account_config::CoinStoreResource,
+        on_chain_config::Features,
+        state_store::state_key::StateKeyInner,
+        transaction::{ChangeSet, WriteSetPayload},
+    };
+    use serde::de::DeserializeOwned;
+    use std::str::FromStr;
+
+    /// A validator joining at genesis, with keys from `keygen`
+    fn validator(keygen: &mut KeyGen) -> ValidatorConfiguration {
+        let (_, _, private_identity, _) = generate_key_objects(keygen).unwrap();
+        let address = private_identity.account_address;
+        let account_public_key = private_identity.account_private_key.public_key();
+        ValidatorConfiguration {
+            owner_account_address: address.into(),
+            owner_account_public_key: account_public_key.clone(),
+            operator_account_address: address.into(),
+            operator_account_public_key: account_public_key.clone(),
+            voter_account_address: address.into(),
+            voter_account_public_key: account_public_key,
+            consensus_public_key: Some(private_identity.consensus_private_key.public_key()),
+            proof_of_possession: Some(bls12381::ProofOfPossession::create(
+                &private_identity.consensus_private_key,
+            )),
+            validator_network_public_key: Some(
+                private_identity.validator_network_private_key.public_key(),
+            ),
+            validator_host: Some(HostAndPort::local(6180).unwrap()),
+            full_node_network_public_key: None,
+            full_node_host: None,
+            stake_amount: 1,
+            commission_percentage: 0,
+            join_during_genesis: true,
+        }
+    }
+
+    /// The resource named `name` that genesis writes at `address`
+    fn resource<T: DeserializeOwned>(
+        change_set: &ChangeSet,
+        address: AccountAddress,
+        name: &str,
+    ) -> T {
+        let (_, write_op) = change_set
+            .write_set()
+            .iter()
+            .find(|(state_key, _)| match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) => {
+                    access_path.address == address
+                        && matches!(
+                            access_path.get_path(),
+                            AccessPathKind::Resource(struct_tag) if struct_tag.name.as_str() == name
+                        )
+                },
+                _ => false,
+            })
+            .unwrap_or_else(|| panic!("Genesis doesn't write {} at {}", name, address));
+        bcs::from_bytes(write_op.bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_build() {
+        let mut keygen = KeyGen::from_seed([0; 32]);
+        let root_key = keygen.generate_ed25519_private_key().public_key();
+        let alice = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let output = GenesisBuilder::new(root_key)
+            .with_validator(validator(&mut keygen))
+            .with_balance(alice, 1_000)
+            .with_features(vec![FeatureFlag::STRUCT_CONSTRUCTORS])
+            .build()
+            .unwrap();
+
+        let change_set = match &output.genesis {
+            Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set)) => change_set,
+            genesis => panic!("Unexpected genesis transaction {:?}", genesis),
+        };
+        let coin_store: CoinStoreResource = resource(change_set, alice, "CoinStore");
+        assert_eq!(coin_store.coin(), 1_000);
+        let features: Features = resource(change_set, AccountAddress::ONE, "Features");
+        assert!(features.is_enabled(FeatureFlag::STRUCT_CONSTRUCTORS));
+        assert!(!features.is_enabled(FeatureFlag::CODE_DEPENDENCY_CHECK));
+
+        let genesis_dir = TempPath::new();
+        output.write_to(genesis_dir.path()).unwrap();
+        let genesis_bytes = fs::read(genesis_dir.path().join(GENESIS_FILE)).unwrap();
+        assert_eq!(
+            bcs::from_bytes::<Transaction>(&genesis_bytes).unwrap(),
+            output.genesis
+        );
+        let waypoint = fs::read_to_string(genesis_dir.path().join(WAYPOINT_FILE)).unwrap();
+        assert_eq!(Waypoint::from_str(&waypoint).unwrap(), output.waypoint);
+    }
+
+    #[test]
+    fn test_build_without_validators() {
+        let root_key = KeyGen::from_seed([0; 32])
+            .generate_ed25519_private_key()
+            .public_key();
+        assert!(GenesisBuilder::new(root_key).build().is_err());
+    }
+}
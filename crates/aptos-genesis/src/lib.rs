@@ -5,6 +5,7 @@
 
 pub mod builder;
 pub mod config;
+pub mod genesis_builder;
 pub mod keys;
 pub mod mainnet;
 
@@ -19,16 +20,18 @@ use aptos_config::config::{
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_db::AptosDB;
 use aptos_framework::ReleaseBundle;
+use aptos_gas::{ChangeSetConfigs, LATEST_GAS_FEATURE_VERSION};
 use aptos_storage_interface::DbReaderWriter;
 use aptos_temppath::TempPath;
 use aptos_types::{
     chain_id::ChainId,
-    on_chain_config::{GasScheduleV2, OnChainConsensusConfig},
-    transaction::Transaction,
+    on_chain_config::{FeatureFlag, GasScheduleV2, OnChainConsensusConfig},
+    state_store::state_export::StateExport,
+    transaction::{ChangeSet, Transaction, WriteSetPayload},
     waypoint::Waypoint,
 };
 use aptos_vm::AptosVM;
-use aptos_vm_genesis::Validator;
+use aptos_vm_genesis::{AccountBalance, Validator};
 use std::convert::TryInto;
 
 /// Holder object for all pieces needed to generate a genesis transaction
@@ -69,6 +72,12 @@ pub struct GenesisInfo {
 
     pub consensus_config: OnChainConsensusConfig,
     pub gas_schedule: GasScheduleV2,
+    /// Features enabled at genesis, instead of the default ones
+    pub initial_features_override: Option<Vec<FeatureFlag>>,
+    /// Accounts funded at genesis
+    pub initial_balances: Vec<AccountBalance>,
+    /// State exported from another network, written over the genesis state
+    pub imported_state: Option<StateExport>,
 }
 
 impl GenesisInfo {
@@ -104,6 +113,9 @@ impl GenesisInfo {
             voting_power_increase_limit: genesis_config.voting_power_increase_limit,
             consensus_config: genesis_config.consensus_config.clone(),
             gas_schedule: genesis_config.gas_schedule.clone(),
+            initial_features_override: genesis_config.initial_features_override.clone(),
+            initial_balances: genesis_config.initial_balances.clone(),
+            imported_state: genesis_config.imported_state.clone(),
         })
     }
 
@@ -117,8 +129,8 @@ impl GenesisInfo {
     }
 
     fn generate_genesis_txn(&self) -> Transaction {
-        aptos_vm_genesis::encode_genesis_transaction(
-            self.root_key.clone(),
+        let change_set = aptos_vm_genesis::encode_genesis_change_set(
+            &self.root_key,
             &self.validators,
             &self.framework,
             self.chain_id,
@@ -136,10 +148,31 @@ impl GenesisInfo {
                 voting_power_increase_limit: self.voting_power_increase_limit,
                 employee_vesting_start: 1663456089,
                 employee_vesting_period_duration: 5 * 60, // 5 minutes
+                initial_features_override: self.initial_features_override.clone(),
+                initial_balances: self.initial_balances.clone(),
             },
             &self.consensus_config,
             &self.gas_schedule,
-        )
+        );
+        let change_set = match &self.imported_state {
+            Some(imported_state) => {
+                // The imported values replace the ones genesis wrote to the same keys
+                let (write_set, events) = change_set.into_inner();
+                let mut write_set = write_set.into_mut();
+                for (state_key, write_op) in &imported_state.to_write_set() {
+                    write_set.insert((state_key.clone(), write_op.clone()));
+                }
+                let write_set = write_set.freeze().expect("freezing a write set can't fail");
+                ChangeSet::new(
+                    write_set,
+                    events,
+                    &ChangeSetConfigs::unlimited_at_gas_feature_version(LATEST_GAS_FEATURE_VERSION),
+                )
+                .expect("genesis change set must be valid")
+            },
+            None => change_set,
+        };
+        Transaction::GenesisTransaction(WriteSetPayload::Direct(change_set))
     }
 
     pub fn generate_waypoint(&mut self) -> anyhow::Result<Waypoint> {
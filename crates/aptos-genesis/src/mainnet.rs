@@ -126,6 +126,8 @@ impl MainnetGenesisInfo {
                 voting_power_increase_limit: self.voting_power_increase_limit,
                 employee_vesting_start: self.employee_vesting_start,
                 employee_vesting_period_duration: self.employee_vesting_period_duration,
+                initial_features_override: None,
+                initial_balances: vec![],
             },
         )
     }
@@ -15,7 +15,7 @@ use crate::{
     telemetry_log_writer::{TelemetryLog, TelemetryLogWriter},
     Event, Filter, Key, Level, LevelFilter, Metadata,
 };
-use aptos_infallible::RwLock;
+use aptos_infallible::{Mutex, RwLock};
 use backtrace::Backtrace;
 use chrono::{SecondsFormat, Utc};
 use futures::channel;
@@ -710,17 +710,18 @@ fn json_format(entry: &LogEntry) -> Result<String, fmt::Error> {
 
 /// Periodically rebuilds the filter and replaces the current logger filter.
 /// This is useful for dynamically changing log levels at runtime via existing
-/// environment variables such as `RUST_LOG_TELEMETRY`.
+/// environment variables such as `RUST_LOG_TELEMETRY`, or via [`Self::set_level`].
+#[derive(Clone)]
 pub struct LoggerFilterUpdater {
     logger: Arc<AptosData>,
-    logger_builder: AptosDataBuilder,
+    logger_builder: Arc<Mutex<AptosDataBuilder>>,
 }
 
 impl LoggerFilterUpdater {
     pub fn new(logger: Arc<AptosData>, logger_builder: AptosDataBuilder) -> Self {
         Self {
             logger,
-            logger_builder,
+            logger_builder: Arc::new(Mutex::new(logger_builder)),
         }
     }
 
@@ -733,9 +734,15 @@ impl LoggerFilterUpdater {
         }
     }
 
+    /// Changes the level of the local logs right away. `RUST_LOG` still takes precedence.
+    pub fn set_level(&self, level: Level) {
+        self.logger_builder.lock().level(level);
+        self.update_filter();
+    }
+
     fn update_filter(&self) {
         // TODO: check for change to env var before rebuilding filter.
-        let filter = self.logger_builder.build_filter();
+        let filter = self.logger_builder.lock().build_filter();
         self.logger.set_filter(filter);
     }
 }
@@ -744,7 +751,7 @@ impl LoggerFilterUpdater {
 mod tests {
     use super::{AptosData, LogEntry};
     use crate::{
-        aptos_logger::{json_format, RUST_LOG_TELEMETRY},
+        aptos_logger::{json_format, RUST_LOG, RUST_LOG_TELEMETRY},
         debug, error, info,
         logger::Logger,
         trace, warn, AptosDataBuilder, Event, Key, KeyValue, Level, LoggerFilterUpdater, Metadata,
@@ -974,6 +981,25 @@ mod tests {
         (logger_builder, logger)
     }
 
+    #[test]
+    fn test_logger_filter_updater_set_level() {
+        // RUST_LOG takes precedence over the configured level
+        if std::env::var(RUST_LOG).is_ok() {
+            return;
+        }
+        let (logger_builder, logger) = new_async_logger();
+        let debug_metadata = &Metadata::new(Level::Debug, "target", "module_path", "source_path");
+        let updater = LoggerFilterUpdater::new(logger.clone(), logger_builder);
+
+        assert!(!logger.filter.read().local_filter.enabled(debug_metadata));
+        updater.set_level(Level::Debug);
+        assert!(logger.filter.read().local_filter.enabled(debug_metadata));
+
+        // The level sticks when the filter is rebuilt
+        updater.update_filter();
+        assert!(logger.filter.read().local_filter.enabled(debug_metadata));
+    }
+
     #[test]
     fn test_logger_filter_updater() {
         let (logger_builder, logger) = new_async_logger();
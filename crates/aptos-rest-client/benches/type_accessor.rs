@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::MoveModule;
+use aptos_rest_client::{Client, SharedTypeAccessor, TypeAccessor, TypeAccessorBuilder};
+use aptos_types::transaction::{EntryFunction, TransactionPayload};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::thread;
+
+const DECODES_PER_THREAD: usize = 1_000;
+
+/// The BCS layout of `0xcafe::bench::Order`.
+#[derive(Serialize)]
+struct Order {
+    id: u64,
+    owner: AccountAddress,
+    amounts: Vec<u64>,
+    memo: Vec<u8>,
+}
+
+fn module() -> MoveModule {
+    serde_json::from_value(json!({
+        "address": "0xcafe",
+        "name": "bench",
+        "friends": [],
+        "exposed_functions": [{
+            "name": "place_orders",
+            "visibility": "public",
+            "is_entry": true,
+            "is_view": false,
+            "generic_type_params": [],
+            "params": ["&signer", "u64", "vector<address>", "vector<0xcafe::bench::Order>"],
+            "return": [],
+        }],
+        "structs": [{
+            "name": "Order",
+            "is_native": false,
+            "abilities": ["copy", "drop", "store"],
+            "generic_type_params": [],
+            "fields": [
+                { "name": "id", "type": "u64" },
+                { "name": "owner", "type": "address" },
+                { "name": "amounts", "type": "vector<u64>" },
+                { "name": "memo", "type": "vector<u8>" },
+            ],
+        }],
+    }))
+    .unwrap()
+}
+
+/// Every type of the module is known, so building doesn't reach the client.
+fn accessor() -> TypeAccessor {
+    let client = Client::new("http://localhost:8080".parse().unwrap());
+    let builder = TypeAccessorBuilder::new(client).add_module(module());
+    tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(builder.build())
+        .unwrap()
+}
+
+fn payload() -> TransactionPayload {
+    let orders: Vec<_> = (0..16)
+        .map(|id| Order {
+            id,
+            owner: AccountAddress::random(),
+            amounts: (0..8).collect(),
+            memo: b"benchmark order".to_vec(),
+        })
+        .collect();
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(
+            AccountAddress::from_hex_literal("0xcafe").unwrap(),
+            Identifier::new("bench").unwrap(),
+        ),
+        Identifier::new("place_orders").unwrap(),
+        vec![],
+        vec![
+            bcs::to_bytes(&42u64).unwrap(),
+            bcs::to_bytes(&vec![AccountAddress::random(); 4]).unwrap(),
+            bcs::to_bytes(&orders).unwrap(),
+        ],
+    ))
+}
+
+fn decode(c: &mut Criterion) {
+    let accessor = accessor();
+    let payload = payload();
+    c.bench_function("decode_entry_function_args", |b| {
+        b.iter(|| accessor.decode_entry_function_args(&payload).unwrap())
+    });
+}
+
+/// Decoders taking a snapshot of a shared accessor for every payload, as long running decoders
+/// which refresh the accessor do.
+fn concurrent_decode(c: &mut Criterion) {
+    let shared = SharedTypeAccessor::new(accessor());
+    let payload = payload();
+    let mut group = c.benchmark_group("concurrent_decode");
+    for threads in [1, 2, 4, 8] {
+        group.throughput(Throughput::Elements((threads * DECODES_PER_THREAD) as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(threads),
+            &threads,
+            |b, threads| {
+                b.iter(|| {
+                    thread::scope(|s| {
+                        for _ in 0..*threads {
+                            s.spawn(|| {
+                                for _ in 0..DECODES_PER_THREAD {
+                                    shared
+                                        .snapshot()
+                                        .decode_entry_function_args(&payload)
+                                        .unwrap();
+                                }
+                            });
+                        }
+                    })
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, decode, concurrent_decode);
+criterion_main!(benches);
@@ -24,3 +24,35 @@ impl Balance {
 pub struct AptosVersion {
     pub major: U64,
 }
+
+/// The APT a transaction needs its sender to hold, see [`Client::estimate_required_balance`].
+///
+/// [`Client::estimate_required_balance`]: crate::Client::estimate_required_balance
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RequiredBalance {
+    /// The gas units the simulation used, storage fees included.
+    pub gas_used: u64,
+    pub gas_unit_price: u64,
+    /// The APT the transaction moves out of the account besides gas, e.g. by transferring it.
+    pub withdrawn: u64,
+}
+
+impl RequiredBalance {
+    /// The gas fee of the transaction, storage fees included.
+    pub fn gas_fee(&self) -> u64 {
+        self.gas_used.saturating_mul(self.gas_unit_price)
+    }
+
+    /// The balance the transaction needs when submitted with `max_gas_amount`: the prologue
+    /// requires the balance to cover the max gas amount, and the transaction to cover what it
+    /// withdraws on top of the gas it uses.
+    pub fn with_max_gas_amount(&self, max_gas_amount: u64) -> u64 {
+        let max_gas_fee = max_gas_amount.saturating_mul(self.gas_unit_price);
+        std::cmp::max(max_gas_fee, self.total())
+    }
+
+    /// The gas fee plus the APT withdrawn.
+    pub fn total(&self) -> u64 {
+        self.gas_fee().saturating_add(self.withdrawn)
+    }
+}
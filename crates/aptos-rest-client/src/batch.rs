@@ -0,0 +1,56 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_api_types::{BatchReadRequest, BatchResourceRequest, BatchTableItemRequest, ViewRequest};
+use aptos_types::account_address::AccountAddress;
+use move_core_types::language_storage::{StructTag, TypeTag};
+use serde_json::Value;
+
+/// Collects the reads of a batch read request, see [`crate::Client::batch`].
+#[derive(Debug, Default)]
+pub struct BatchReadBuilder {
+    pub(crate) requests: Vec<BatchReadRequest>,
+    pub(crate) ledger_version: Option<u64>,
+}
+
+impl BatchReadBuilder {
+    /// Reads the resource `resource_type` of `address`.
+    pub fn resource(&mut self, address: AccountAddress, resource_type: &StructTag) -> &mut Self {
+        self.requests
+            .push(BatchReadRequest::Resource(BatchResourceRequest {
+                address: address.into(),
+                resource_type: resource_type.into(),
+            }));
+        self
+    }
+
+    /// Executes a view function.
+    pub fn view(&mut self, request: ViewRequest) -> &mut Self {
+        self.requests.push(BatchReadRequest::View(request));
+        self
+    }
+
+    /// Reads the item under `key` of the table `table_handle`.
+    pub fn table_item(
+        &mut self,
+        table_handle: AccountAddress,
+        key_type: &TypeTag,
+        value_type: &TypeTag,
+        key: Value,
+    ) -> &mut Self {
+        self.requests
+            .push(BatchReadRequest::TableItem(BatchTableItemRequest {
+                table_handle: table_handle.into(),
+                key_type: key_type.into(),
+                value_type: value_type.into(),
+                key,
+            }));
+        self
+    }
+
+    /// Executes the reads at `version` instead of the latest version.
+    pub fn at_version(&mut self, version: u64) -> &mut Self {
+        self.ledger_version = Some(version);
+        self
+    }
+}
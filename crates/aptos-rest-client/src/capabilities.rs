@@ -0,0 +1,75 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! What the API of a node supports, so that one client works with fullnodes of different
+//! releases.
+
+use crate::{error::RestError, AptosResult};
+use anyhow::anyhow;
+use aptos_api_types::NodeInfo;
+
+/// What the API of a node supports, as returned by [`crate::Client::capabilities`].
+///
+/// Nodes predating the node info endpoint have no [`NodeInfo`]: they support none of the
+/// features listed by name, and their limits are unknown.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NodeCapabilities {
+    info: Option<NodeInfo>,
+}
+
+impl NodeCapabilities {
+    pub fn new(info: NodeInfo) -> Self {
+        Self { info: Some(info) }
+    }
+
+    /// The capabilities of a node predating the node info endpoint.
+    pub fn legacy() -> Self {
+        Self { info: None }
+    }
+
+    pub fn info(&self) -> Option<&NodeInfo> {
+        self.info.as_ref()
+    }
+
+    pub fn is_legacy(&self) -> bool {
+        self.info.is_none()
+    }
+
+    /// Whether the node supports `feature`, e.g. [`aptos_api_types::FEATURE_BATCH_READ`].
+    pub fn supports(&self, feature: &str) -> bool {
+        self.info
+            .as_ref()
+            .map_or(false, |info| info.supports(feature))
+    }
+
+    pub fn git_hash(&self) -> Option<&str> {
+        self.info.as_ref().map(|info| info.git_hash.as_str())
+    }
+
+    /// The maximum number of reads in a batch read, if known.
+    pub fn max_batch_read_size(&self) -> Option<usize> {
+        self.info
+            .as_ref()
+            .map(|info| info.max_batch_read_size.0 as usize)
+    }
+
+    /// The maximum number of transactions submitted in a batch, if known.
+    pub fn max_submit_transaction_batch_size(&self) -> Option<usize> {
+        self.info
+            .as_ref()
+            .map(|info| info.max_submit_transaction_batch_size.0 as usize)
+    }
+
+    /// Fails with an error naming `feature` if the node doesn't support it.
+    pub fn require(&self, feature: &str) -> AptosResult<()> {
+        if self.supports(feature) {
+            return Ok(());
+        }
+        Err(RestError::Unknown(anyhow!(
+            "The node doesn't support {} (release {})",
+            feature,
+            self.git_hash()
+                .unwrap_or("predating the node info endpoint")
+        )))
+    }
+}
@@ -0,0 +1,803 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Definitions of the Move structs a [`TypeAccessor`] resolved, so that clients don't have to
+//! mirror them by hand: Rust structs with the same BCS encoding as the Move structs, and a JSON
+//! schema and TypeScript declarations of the JSON the REST API returns for them.
+
+use crate::TypeAccessor;
+use anyhow::{anyhow, bail, Result};
+use aptos_api_types::{MoveStructTag, MoveType};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use serde_json::{json, Map, Value};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+/// Rust keywords which can be used as raw identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Generates a Rust struct deriving `Serialize` and `Deserialize` for every struct of a
+/// [`TypeAccessor`], in a Rust module per Move module, named after the module and its address,
+/// e.g. `coin_1::CoinStore<T0>`. Fields of struct types refer to the generated types, except for
+/// the strings, options and objects of the framework, which map to `String`, `Option` and
+/// `AccountAddress`.
+///
+/// Like `aptos move generate-rust-client` does for a package, but offline, for whatever the
+/// accessor was built from.
+pub struct RustStructGenerator<'a> {
+    accessor: &'a TypeAccessor,
+    move_core_types: String,
+}
+
+impl<'a> RustStructGenerator<'a> {
+    pub fn new(accessor: &'a TypeAccessor) -> Self {
+        Self {
+            accessor,
+            move_core_types: "::move_core_types".to_string(),
+        }
+    }
+
+    /// The path the generated code refers to the `move-core-types` crate by, for
+    /// `AccountAddress` and `U256`. `::move_core_types` by default, e.g.
+    /// `::aptos_sdk::move_types` for crates depending on the SDK instead.
+    pub fn move_core_types_path(mut self, path: impl Into<String>) -> Self {
+        self.move_core_types = path.into();
+        self
+    }
+
+    /// The Rust source of the generated modules, which depends on the `serde` crate.
+    pub fn generate(&self) -> Result<String> {
+        let mut modules: BTreeMap<ModuleId, Vec<Identifier>> = BTreeMap::new();
+        for (module_id, name) in self.accessor.struct_names() {
+            modules.entry(module_id).or_default().push(name);
+        }
+
+        let mut out = String::new();
+        for (module_id, names) in &modules {
+            writeln!(
+                out,
+                "/// `{}`\n\
+                #[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]\n\
+                pub mod {} {{",
+                module_id.short_str_lossless(),
+                rust_module_name(module_id)
+            )?;
+            for name in names {
+                let mut struct_ = String::new();
+                self.render_struct(module_id, name, &mut struct_)?;
+                for line in struct_.lines() {
+                    if line.is_empty() {
+                        writeln!(out)?;
+                    } else {
+                        writeln!(out, "    {}", line)?;
+                    }
+                }
+            }
+            writeln!(out, "}}\n")?;
+        }
+        Ok(out)
+    }
+
+    fn render_struct(
+        &self,
+        module_id: &ModuleId,
+        name: &Identifier,
+        out: &mut String,
+    ) -> Result<()> {
+        let fields = self
+            .accessor
+            .struct_field_types(module_id, name)
+            .ok_or_else(|| anyhow!("Struct {}::{} not found", module_id, name))?;
+        let type_params = self
+            .accessor
+            .struct_generics(module_id, name)
+            .map_or(0, |params| params.len());
+        let mut used = BTreeSet::new();
+        for (_, ty) in &fields {
+            generic_params(ty, &mut used);
+        }
+
+        writeln!(
+            out,
+            "/// `{}::{}`\n\
+            #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]\n\
+            pub struct {}{} {{",
+            module_id.short_str_lossless(),
+            name,
+            rust_ident(name.as_str()),
+            rust_type_params(type_params)
+        )?;
+        for (field_name, ty) in &fields {
+            writeln!(
+                out,
+                "    pub {}: {},",
+                rust_ident(field_name.as_str()),
+                self.rust_type(ty)?
+            )?;
+        }
+        // Phantom type parameters aren't used by fields, but Rust requires every parameter to be used
+        if used.len() < type_params {
+            let phantom: Vec<_> = (0..type_params)
+                .filter(|i| !used.contains(&(*i as u16)))
+                .map(|i| format!("T{}", i))
+                .collect();
+            writeln!(
+                out,
+                "    #[serde(skip)]\n    pub _phantom: ::std::marker::PhantomData<({},)>,",
+                phantom.join(", ")
+            )?;
+        }
+        writeln!(out, "}}\n")?;
+        Ok(())
+    }
+
+    /// The Rust type with the same BCS encoding as `ty`.
+    fn rust_type(&self, ty: &MoveType) -> Result<String> {
+        let account_address = format!("{}::account_address::AccountAddress", self.move_core_types);
+        Ok(match ty {
+            MoveType::Bool => "bool".to_string(),
+            MoveType::U8 => "u8".to_string(),
+            MoveType::U16 => "u16".to_string(),
+            MoveType::U32 => "u32".to_string(),
+            MoveType::U64 => "u64".to_string(),
+            MoveType::U128 => "u128".to_string(),
+            MoveType::U256 => format!("{}::u256::U256", self.move_core_types),
+            MoveType::Address | MoveType::Signer => account_address,
+            MoveType::Vector { items } => format!("::std::vec::Vec<{}>", self.rust_type(items)?),
+            MoveType::Struct(tag) => {
+                let address = AccountAddress::from(tag.address);
+                match framework_type(address, tag.module.as_str(), tag.name.as_str()) {
+                    Some(FrameworkType::String) => "::std::string::String".to_string(),
+                    Some(FrameworkType::Object) => account_address,
+                    Some(FrameworkType::Option) => format!(
+                        "::std::option::Option<{}>",
+                        self.rust_type(&tag.generic_type_params[0])?
+                    ),
+                    None => {
+                        let module_id = ModuleId::new(address, tag.module.0.clone());
+                        if self
+                            .accessor
+                            .struct_generics(&module_id, &tag.name.0)
+                            .is_none()
+                        {
+                            bail!("Struct {} isn't in the type accessor", tag);
+                        }
+                        let type_args = if tag.generic_type_params.is_empty() {
+                            String::new()
+                        } else {
+                            format!(
+                                "<{}>",
+                                tag.generic_type_params
+                                    .iter()
+                                    .map(|ty| self.rust_type(ty))
+                                    .collect::<Result<Vec<_>>>()?
+                                    .join(", ")
+                            )
+                        };
+                        format!(
+                            "super::{}::{}{}",
+                            rust_module_name(&module_id),
+                            rust_ident(tag.name.as_str()),
+                            type_args
+                        )
+                    },
+                }
+            },
+            MoveType::GenericTypeParam { index } => format!("T{}", index),
+            MoveType::Reference { to, .. } => self.rust_type(to)?,
+            MoveType::Unparsable(ty) => bail!("Unparsable type {}", ty),
+        })
+    }
+}
+
+/// How the fields of structs are named in a JSON schema or TypeScript declarations.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FieldNaming {
+    /// As declared in Move, which is how the REST API names them.
+    #[default]
+    SnakeCase,
+    /// e.g. `coin_store` as `coinStore`, for clients converting the keys of the JSON the REST API
+    /// returns.
+    CamelCase,
+}
+
+/// Generates a JSON schema or TypeScript declarations of the JSON the REST API returns for the
+/// structs of a [`TypeAccessor`], i.e. the JSON of [`TypeAccessor::view_resource`]: `u64`,
+/// `u128` and `u256` are decimal strings, addresses and `vector<u8>` are hex strings, strings
+/// are strings, options are `{"vec": [...]}` and objects are `{"inner": address}`.
+///
+/// Every struct has a definition, named after its module and address in TypeScript, e.g.
+/// `coin_1.CoinStore<T0>`, and by its type in the schema, e.g. `0x1::coin::CoinStore`. JSON
+/// schema has no generics, so the type parameters of a definition accept anything, unless
+/// generics are flattened.
+pub struct SchemaGenerator<'a> {
+    accessor: &'a TypeAccessor,
+    field_naming: FieldNaming,
+    flatten_generics: bool,
+}
+
+impl<'a> SchemaGenerator<'a> {
+    pub fn new(accessor: &'a TypeAccessor) -> Self {
+        Self {
+            accessor,
+            field_naming: FieldNaming::default(),
+            flatten_generics: false,
+        }
+    }
+
+    /// How fields are named, as in Move by default.
+    pub fn field_naming(mut self, field_naming: FieldNaming) -> Self {
+        self.field_naming = field_naming;
+        self
+    }
+
+    /// Whether fields of a generic struct type, e.g. `Coin<AptosCoin>`, are declared inline with
+    /// the type arguments substituted, instead of referring to the generic definition. Off by
+    /// default.
+    pub fn flatten_generics(mut self, flatten_generics: bool) -> Self {
+        self.flatten_generics = flatten_generics;
+        self
+    }
+
+    /// A draft-07 JSON schema with a definition per struct, and no root type.
+    pub fn json_schema(&self) -> Result<Value> {
+        let mut definitions = Map::new();
+        for (module_id, name) in self.accessor.struct_names() {
+            let fields = self.struct_fields(&module_id, &name)?;
+            definitions.insert(
+                schema_definition_name(&module_id, name.as_str()),
+                self.object_schema(&fields)?,
+            );
+        }
+        Ok(json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": definitions,
+        }))
+    }
+
+    /// TypeScript declarations, with a namespace per Move module and an interface per struct.
+    pub fn typescript(&self) -> Result<String> {
+        let mut modules: BTreeMap<ModuleId, Vec<Identifier>> = BTreeMap::new();
+        for (module_id, name) in self.accessor.struct_names() {
+            modules.entry(module_id).or_default().push(name);
+        }
+
+        let mut out = String::new();
+        for (module_id, names) in &modules {
+            writeln!(
+                out,
+                "/** `{}` */\nexport namespace {} {{",
+                module_id.short_str_lossless(),
+                rust_module_name(module_id)
+            )?;
+            for name in names {
+                let fields = self.struct_fields(module_id, name)?;
+                let type_params = self
+                    .accessor
+                    .struct_generics(module_id, name)
+                    .map_or(0, |params| params.len());
+                writeln!(
+                    out,
+                    "  /** `{}::{}` */\n  export interface {}{} {{",
+                    module_id.short_str_lossless(),
+                    name,
+                    name,
+                    rust_type_params(type_params)
+                )?;
+                for (field_name, ty) in &fields {
+                    writeln!(
+                        out,
+                        "    {}: {};",
+                        self.field_name(field_name),
+                        self.typescript_type(ty)?
+                    )?;
+                }
+                writeln!(out, "  }}")?;
+            }
+            writeln!(out, "}}\n")?;
+        }
+        Ok(out)
+    }
+
+    fn object_schema(&self, fields: &[(Identifier, MoveType)]) -> Result<Value> {
+        let mut properties = Map::new();
+        for (name, ty) in fields {
+            properties.insert(self.field_name(name), self.json_type(ty)?);
+        }
+        let required: Vec<_> = properties.keys().cloned().collect();
+        Ok(json!({
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        }))
+    }
+
+    /// The schema of the JSON of a value of type `ty`.
+    fn json_type(&self, ty: &MoveType) -> Result<Value> {
+        Ok(match ty {
+            MoveType::Bool => json!({ "type": "boolean" }),
+            MoveType::U8 => unsigned_schema(u8::MAX.into()),
+            MoveType::U16 => unsigned_schema(u16::MAX.into()),
+            MoveType::U32 => unsigned_schema(u32::MAX.into()),
+            MoveType::U64 | MoveType::U128 | MoveType::U256 => {
+                json!({ "type": "string", "pattern": "^[0-9]+$" })
+            },
+            MoveType::Address | MoveType::Signer => address_schema(),
+            MoveType::Vector { items } if **items == MoveType::U8 => {
+                json!({ "type": "string", "pattern": "^0x([0-9a-fA-F]{2})*$" })
+            },
+            MoveType::Vector { items } => {
+                json!({ "type": "array", "items": self.json_type(items)? })
+            },
+            MoveType::Struct(tag) => {
+                let address = AccountAddress::from(tag.address);
+                match framework_type(address, tag.module.as_str(), tag.name.as_str()) {
+                    Some(FrameworkType::String) => json!({ "type": "string" }),
+                    Some(FrameworkType::Option) => json!({
+                        "type": "object",
+                        "properties": {
+                            "vec": {
+                                "type": "array",
+                                "items": self.json_type(&tag.generic_type_params[0])?,
+                                "maxItems": 1,
+                            },
+                        },
+                        "required": ["vec"],
+                        "additionalProperties": false,
+                    }),
+                    Some(FrameworkType::Object) => json!({
+                        "type": "object",
+                        "properties": { "inner": address_schema() },
+                        "required": ["inner"],
+                        "additionalProperties": false,
+                    }),
+                    None if self.flatten_generics && !tag.generic_type_params.is_empty() => {
+                        self.object_schema(&self.instantiate(tag)?)?
+                    },
+                    None => {
+                        let module_id = self.struct_module_id(tag)?;
+                        json!({
+                            "$ref": format!(
+                                "#/definitions/{}",
+                                schema_definition_name(&module_id, tag.name.as_str())
+                            ),
+                        })
+                    },
+                }
+            },
+            MoveType::GenericTypeParam { index } => json!({ "description": format!("T{}", index) }),
+            MoveType::Reference { to, .. } => self.json_type(to)?,
+            MoveType::Unparsable(ty) => bail!("Unparsable type {}", ty),
+        })
+    }
+
+    /// The TypeScript type of the JSON of a value of type `ty`.
+    fn typescript_type(&self, ty: &MoveType) -> Result<String> {
+        Ok(match ty {
+            MoveType::Bool => "boolean".to_string(),
+            MoveType::U8 | MoveType::U16 | MoveType::U32 => "number".to_string(),
+            MoveType::U64 | MoveType::U128 | MoveType::U256 => "string".to_string(),
+            MoveType::Address | MoveType::Signer => "string".to_string(),
+            MoveType::Vector { items } if **items == MoveType::U8 => "string".to_string(),
+            MoveType::Vector { items } => format!("Array<{}>", self.typescript_type(items)?),
+            MoveType::Struct(tag) => {
+                let address = AccountAddress::from(tag.address);
+                match framework_type(address, tag.module.as_str(), tag.name.as_str()) {
+                    Some(FrameworkType::String) => "string".to_string(),
+                    Some(FrameworkType::Option) => format!(
+                        "{{ vec: Array<{}> }}",
+                        self.typescript_type(&tag.generic_type_params[0])?
+                    ),
+                    Some(FrameworkType::Object) => "{ inner: string }".to_string(),
+                    None if self.flatten_generics && !tag.generic_type_params.is_empty() => {
+                        let fields = self
+                            .instantiate(tag)?
+                            .iter()
+                            .map(|(name, ty)| {
+                                Ok(format!(
+                                    "{}: {}",
+                                    self.field_name(name),
+                                    self.typescript_type(ty)?
+                                ))
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        format!("{{ {} }}", fields.join("; "))
+                    },
+                    None => {
+                        let module_id = self.struct_module_id(tag)?;
+                        let type_args = if tag.generic_type_params.is_empty() {
+                            String::new()
+                        } else {
+                            format!(
+                                "<{}>",
+                                tag.generic_type_params
+                                    .iter()
+                                    .map(|ty| self.typescript_type(ty))
+                                    .collect::<Result<Vec<_>>>()?
+                                    .join(", ")
+                            )
+                        };
+                        format!("{}.{}{}", rust_module_name(&module_id), tag.name, type_args)
+                    },
+                }
+            },
+            MoveType::GenericTypeParam { index } => format!("T{}", index),
+            MoveType::Reference { to, .. } => self.typescript_type(to)?,
+            MoveType::Unparsable(ty) => bail!("Unparsable type {}", ty),
+        })
+    }
+
+    fn field_name(&self, name: &Identifier) -> String {
+        match self.field_naming {
+            FieldNaming::SnakeCase => name.to_string(),
+            FieldNaming::CamelCase => camel_case(name.as_str()),
+        }
+    }
+
+    fn struct_fields(
+        &self,
+        module_id: &ModuleId,
+        name: &Identifier,
+    ) -> Result<Vec<(Identifier, MoveType)>> {
+        self.accessor
+            .struct_field_types(module_id, name)
+            .ok_or_else(|| anyhow!("Struct {}::{} not found", module_id, name))
+    }
+
+    fn struct_module_id(&self, tag: &MoveStructTag) -> Result<ModuleId> {
+        let module_id = ModuleId::new(tag.address.into(), tag.module.0.clone());
+        if self
+            .accessor
+            .struct_generics(&module_id, &tag.name.0)
+            .is_none()
+        {
+            bail!("Struct {} isn't in the type accessor", tag);
+        }
+        Ok(module_id)
+    }
+
+    /// The fields of `tag` with its type arguments substituted. Unlike
+    /// [`TypeAccessor::instantiate_struct`], the type arguments may be type parameters of the
+    /// struct declaring the field. Move structs can't contain themselves, so this terminates.
+    fn instantiate(&self, tag: &MoveStructTag) -> Result<Vec<(Identifier, MoveType)>> {
+        let module_id = self.struct_module_id(tag)?;
+        Ok(self
+            .struct_fields(&module_id, &tag.name.0)?
+            .into_iter()
+            .map(|(name, ty)| (name, substitute(&ty, &tag.generic_type_params)))
+            .collect())
+    }
+}
+
+/// Framework structs whose BCS encoding is the one of a Rust type.
+enum FrameworkType {
+    String,
+    Option,
+    Object,
+}
+
+fn framework_type(address: AccountAddress, module: &str, name: &str) -> Option<FrameworkType> {
+    if address != AccountAddress::ONE {
+        return None;
+    }
+    match (module, name) {
+        ("string", "String") => Some(FrameworkType::String),
+        ("option", "Option") => Some(FrameworkType::Option),
+        ("object", "Object") => Some(FrameworkType::Object),
+        _ => None,
+    }
+}
+
+/// Modules are suffixed with their address, as modules of different addresses may share a name.
+fn rust_module_name(module_id: &ModuleId) -> String {
+    format!(
+        "{}_{}",
+        module_id.name(),
+        module_id.address().short_str_lossless()
+    )
+}
+
+/// Collects the indices of the type parameters `ty` uses.
+fn generic_params(ty: &MoveType, used: &mut BTreeSet<u16>) {
+    match ty {
+        MoveType::GenericTypeParam { index } => {
+            used.insert(*index);
+        },
+        MoveType::Vector { items } => generic_params(items, used),
+        MoveType::Reference { to, .. } => generic_params(to, used),
+        MoveType::Struct(tag) => {
+            // The type of an object is only a marker
+            if framework_type(tag.address.into(), tag.module.as_str(), tag.name.as_str())
+                .map_or(true, |ty| !matches!(ty, FrameworkType::Object))
+            {
+                for ty in &tag.generic_type_params {
+                    generic_params(ty, used);
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+fn rust_type_params(count: usize) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    let params: Vec<_> = (0..count).map(|i| format!("T{}", i)).collect();
+    format!("<{}>", params.join(", "))
+}
+
+/// `name` as a Rust identifier: keywords are escaped, or suffixed if they can't be.
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+fn schema_definition_name(module_id: &ModuleId, name: &str) -> String {
+    format!("{}::{}", module_id.short_str_lossless(), name)
+}
+
+fn unsigned_schema(max: u64) -> Value {
+    json!({ "type": "integer", "minimum": 0, "maximum": max })
+}
+
+fn address_schema() -> Value {
+    json!({ "type": "string", "pattern": "^0x[0-9a-fA-F]{1,64}$" })
+}
+
+/// `ty` with the type parameters replaced by `type_args`.
+fn substitute(ty: &MoveType, type_args: &[MoveType]) -> MoveType {
+    match ty {
+        MoveType::GenericTypeParam { index } => type_args
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| ty.clone()),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, type_args)),
+        },
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute(to, type_args)),
+        },
+        MoveType::Struct(tag) => {
+            let mut tag = tag.clone();
+            tag.generic_type_params = tag
+                .generic_type_params
+                .iter()
+                .map(|ty| substitute(ty, type_args))
+                .collect();
+            MoveType::Struct(tag)
+        },
+        _ => ty.clone(),
+    }
+}
+
+/// `name` in camelCase, keeping a leading underscore, e.g. `_phantom_data` as `_phantomData`.
+fn camel_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut upper = false;
+    for c in name.chars() {
+        if c == '_' && !out.trim_start_matches('_').is_empty() {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::market_accessor;
+
+    #[test]
+    fn test_rust_structs() {
+        let accessor = market_accessor();
+        let expected = r#"/// `0x1::option`
+#[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]
+pub mod option_1 {
+    /// `0x1::option::Option`
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+    pub struct Option<T0> {
+        pub vec: ::std::vec::Vec<T0>,
+    }
+
+}
+
+/// `0x1::string`
+#[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]
+pub mod string_1 {
+    /// `0x1::string::String`
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+    pub struct String {
+        pub bytes: ::std::vec::Vec<u8>,
+    }
+
+}
+
+/// `0xcafe::market`
+#[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]
+pub mod market_cafe {
+    /// `0xcafe::market::Listing`
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+    pub struct Listing<T0> {
+        pub id: u64,
+        pub items: ::std::vec::Vec<::std::vec::Vec<T0>>,
+        pub price: ::std::option::Option<super::market_cafe::Price>,
+        pub wrapped: super::market_cafe::Wrapper<::std::vec::Vec<T0>>,
+        pub seller: ::aptos_sdk::move_types::account_address::AccountAddress,
+    }
+
+    /// `0xcafe::market::Price`
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+    pub struct Price {
+        pub amount: u64,
+        pub currency: ::std::string::String,
+    }
+
+    /// `0xcafe::market::Wrapper`
+    #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+    pub struct Wrapper<T0> {
+        pub value: T0,
+    }
+
+}
+
+"#;
+        assert_eq!(
+            RustStructGenerator::new(&accessor)
+                .move_core_types_path("::aptos_sdk::move_types")
+                .generate()
+                .unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let accessor = market_accessor();
+        let t0_vector = json!({ "type": "array", "items": { "description": "T0" } });
+        let listing = |wrapped| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "string", "pattern": "^[0-9]+$" },
+                    "items": { "type": "array", "items": t0_vector },
+                    "price": {
+                        "type": "object",
+                        "properties": {
+                            "vec": {
+                                "type": "array",
+                                "items": { "$ref": "#/definitions/0xcafe::market::Price" },
+                                "maxItems": 1,
+                            },
+                        },
+                        "required": ["vec"],
+                        "additionalProperties": false,
+                    },
+                    "wrapped": wrapped,
+                    "seller": address_schema(),
+                },
+                "required": ["id", "items", "price", "wrapped", "seller"],
+                "additionalProperties": false,
+            })
+        };
+
+        let schema = SchemaGenerator::new(&accessor).json_schema().unwrap();
+        let definitions = schema["definitions"].as_object().unwrap();
+        assert_eq!(definitions.keys().collect::<Vec<_>>(), vec![
+            "0x1::option::Option",
+            "0x1::string::String",
+            "0xcafe::market::Listing",
+            "0xcafe::market::Price",
+            "0xcafe::market::Wrapper",
+        ]);
+        assert_eq!(
+            definitions["0xcafe::market::Listing"],
+            listing(json!({ "$ref": "#/definitions/0xcafe::market::Wrapper" }))
+        );
+        assert_eq!(
+            definitions["0xcafe::market::Wrapper"],
+            json!({
+                "type": "object",
+                "properties": { "value": { "description": "T0" } },
+                "required": ["value"],
+                "additionalProperties": false,
+            })
+        );
+
+        // The `Wrapper<vector<T0>>` is declared inline
+        let schema = SchemaGenerator::new(&accessor)
+            .flatten_generics(true)
+            .json_schema()
+            .unwrap();
+        assert_eq!(
+            schema["definitions"]["0xcafe::market::Listing"],
+            listing(json!({
+                "type": "object",
+                "properties": { "value": t0_vector },
+                "required": ["value"],
+                "additionalProperties": false,
+            }))
+        );
+    }
+
+    #[test]
+    fn test_typescript() {
+        let accessor = market_accessor();
+        let expected = r#"/** `0x1::option` */
+export namespace option_1 {
+  /** `0x1::option::Option` */
+  export interface Option<T0> {
+    vec: Array<T0>;
+  }
+}
+
+/** `0x1::string` */
+export namespace string_1 {
+  /** `0x1::string::String` */
+  export interface String {
+    bytes: string;
+  }
+}
+
+/** `0xcafe::market` */
+export namespace market_cafe {
+  /** `0xcafe::market::Listing` */
+  export interface Listing<T0> {
+    id: string;
+    items: Array<Array<T0>>;
+    price: { vec: Array<market_cafe.Price> };
+    wrapped: market_cafe.Wrapper<Array<T0>>;
+    seller: string;
+  }
+  /** `0xcafe::market::Price` */
+  export interface Price {
+    amount: string;
+    currency: string;
+  }
+  /** `0xcafe::market::Wrapper` */
+  export interface Wrapper<T0> {
+    value: T0;
+  }
+}
+
+"#;
+        assert_eq!(
+            SchemaGenerator::new(&accessor).typescript().unwrap(),
+            expected
+        );
+
+        let flattened = SchemaGenerator::new(&accessor)
+            .flatten_generics(true)
+            .field_naming(FieldNaming::CamelCase)
+            .typescript()
+            .unwrap();
+        assert!(flattened.contains("    wrapped: { value: Array<T0> };\n"));
+    }
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(camel_case("coin_store"), "coinStore");
+        assert_eq!(camel_case("_phantom_data"), "_phantomData");
+        assert_eq!(camel_case("amount"), "amount");
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Downloads of BCS responses which resume where they stopped when the connection drops, see
+//! [`crate::Client::with_resumable_downloads`].
+
+use crate::{error::RestError, response::Response, Client, State};
+use reqwest::{
+    header::{HeaderMap, HeaderValue, CONTENT_RANGE, RANGE},
+    Request, StatusCode,
+};
+use std::{fmt, sync::Arc};
+use url::Url;
+
+type AptosResult<T> = Result<T, RestError>;
+
+/// How far a download got, passed to the progress callback after each chunk received.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DownloadProgress {
+    /// The bytes of the response received so far.
+    pub received: u64,
+    /// The size of the whole response, if the server sent it.
+    pub total: Option<u64>,
+    /// How many retries the download took so far, including attempts to reconnect which failed.
+    pub retries: u32,
+}
+
+/// How the BCS responses of a client are downloaded: chunk by chunk, reporting progress, and
+/// resuming with a range request when the connection drops, up to `max_retries` times.
+///
+/// A response is only resumed if it can't change between attempts: it is of a given ledger
+/// version, or of committed transactions by version. Other downloads are restarted from scratch,
+/// as are downloads from servers which don't support range requests.
+#[derive(Clone)]
+pub struct ResumableDownloads {
+    max_retries: u32,
+    on_progress: Option<Arc<dyn Fn(&Url, DownloadProgress) + Send + Sync>>,
+}
+
+impl ResumableDownloads {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            on_progress: None,
+        }
+    }
+
+    /// Calls `on_progress` with the URL of the download and its progress after each chunk
+    /// received, e.g. to show a progress bar.
+    pub fn on_progress(
+        mut self,
+        on_progress: impl Fn(&Url, DownloadProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(on_progress));
+        self
+    }
+
+    /// Downloads the response of `request`, which must have no body, so that it can be sent
+    /// again.
+    pub(crate) async fn download(
+        &self,
+        client: &Client,
+        request: Request,
+    ) -> AptosResult<Response<bytes::Bytes>> {
+        // Cleared once the server doesn't resume as asked, so the download starts over instead
+        let mut resumable = is_resumable(request.url());
+        let mut retries = 0;
+        let (mut response, mut state) = client
+            .check_response(client.execute(clone_request(&request)?).await?)
+            .await?;
+        let mut total = response.content_length();
+        let mut body = Vec::with_capacity(total.unwrap_or(0) as usize);
+        loop {
+            let err = match response.chunk().await {
+                Ok(Some(chunk)) => {
+                    body.extend_from_slice(&chunk);
+                    self.report(request.url(), DownloadProgress {
+                        received: body.len() as u64,
+                        total,
+                        retries,
+                    });
+                    continue;
+                },
+                Ok(None) => break,
+                Err(err) => RestError::from(err),
+            };
+
+            let (next, next_state, resumed) = self
+                .reconnect(
+                    client,
+                    &request,
+                    &mut resumable,
+                    body.len() as u64,
+                    total,
+                    &mut retries,
+                    err,
+                )
+                .await?;
+            if !resumed {
+                body.clear();
+                total = next.content_length();
+                state = next_state;
+            }
+            response = next;
+        }
+        Ok(Response::new(body.into(), state))
+    }
+
+    /// Sends `request` again after its download failed with `err`. If the download is
+    /// `resumable`, only the bytes after the `received` ones are asked for, and the response is
+    /// only used as such if it's a `206 Partial Content` with a `Content-Range` starting right
+    /// there. Returns the response, its state, and whether it resumes the download rather than
+    /// starting it over.
+    #[allow(clippy::too_many_arguments)]
+    async fn reconnect(
+        &self,
+        client: &Client,
+        request: &Request,
+        resumable: &mut bool,
+        received: u64,
+        total: Option<u64>,
+        retries: &mut u32,
+        mut err: RestError,
+    ) -> AptosResult<(reqwest::Response, State, bool)> {
+        loop {
+            if *retries >= self.max_retries {
+                return Err(err);
+            }
+            *retries += 1;
+            let ranged = *resumable && received > 0;
+            let mut retry = clone_request(request)?;
+            if ranged {
+                retry.headers_mut().insert(
+                    RANGE,
+                    HeaderValue::from_str(&format!("bytes={}-", received))
+                        .expect("A byte range is a valid header value"),
+                );
+            }
+            // Reconnecting fails too while the connection is down, each attempt taking a retry
+            let next = match client.execute(retry).await {
+                Ok(next) => next,
+                Err(next_err) => {
+                    err = next_err;
+                    continue;
+                },
+            };
+            let status = next.status();
+            if !ranged {
+                let (next, state) = client.check_response(next).await?;
+                return Ok((next, state, false));
+            }
+            if status == StatusCode::PARTIAL_CONTENT {
+                if content_range(next.headers()) == Some((received, total)) {
+                    let (next, state) = client.check_response(next).await?;
+                    return Ok((next, state, true));
+                }
+            } else if status.is_success() {
+                // The server ignored the range and sent the whole response
+                let (next, state) = client.check_response(next).await?;
+                return Ok((next, state, false));
+            }
+            // Another part of the response, or the range was refused: download all of it again
+            *resumable = false;
+            err = anyhow::anyhow!(
+                "Asked for the response from byte {}, the server responded with {}",
+                received,
+                status
+            )
+            .into();
+        }
+    }
+
+    fn report(&self, url: &Url, progress: DownloadProgress) {
+        if let Some(on_progress) = &self.on_progress {
+            on_progress(url, progress);
+        }
+    }
+}
+
+impl fmt::Debug for ResumableDownloads {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumableDownloads")
+            .field("max_retries", &self.max_retries)
+            .field("on_progress", &self.on_progress.is_some())
+            .finish()
+    }
+}
+
+fn clone_request(request: &Request) -> AptosResult<Request> {
+    request
+        .try_clone()
+        .ok_or_else(|| anyhow::anyhow!("Requests with a streamed body can't be resent").into())
+}
+
+/// Whether the response to `url` is the same whenever it's requested: it's pinned to a ledger
+/// version, or it's of committed transactions.
+fn is_resumable(url: &Url) -> bool {
+    let has_param = |param: &str| url.query_pairs().any(|(key, _)| key == param);
+    let path = url.path();
+    has_param("ledger_version")
+        || path.contains("/transactions/by_version/")
+        || (path.ends_with("/transactions") && has_param("start"))
+}
+
+/// The first byte and the total size of a `Content-Range: bytes <first>-<last>/<total>` header.
+fn content_range(headers: &HeaderMap) -> Option<(u64, Option<u64>)> {
+    let range = headers
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?;
+    let (range, total) = range.split_once('/')?;
+    let (first, _) = range.split_once('-')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((first.parse().ok()?, total))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{state_headers, CHAIN_ID};
+    use std::sync::Mutex;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+        task::JoinHandle,
+    };
+
+    const BODY: &[u8] = b"0123456789";
+
+    /// A raw HTTP response of `body`, of which only the first `sent` bytes are sent before the
+    /// connection drops.
+    fn response(
+        status: &str,
+        headers: &[(&'static str, String)],
+        body: &[u8],
+        sent: usize,
+    ) -> Vec<u8> {
+        let mut head = format!("HTTP/1.1 {}\r\ncontent-length: {}\r\n", status, body.len());
+        for (name, value) in state_headers(CHAIN_ID, 1).iter().chain(headers) {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        head.push_str("\r\n");
+        let mut response = head.into_bytes();
+        response.extend_from_slice(&body[..sent]);
+        response
+    }
+
+    /// A server writing each of `responses` to a connection of its own in turn, then closing
+    /// it, so that an empty response drops the connection before responding. Returns the URL of
+    /// the server and the lowercased heads of the requests it received.
+    async fn serve(responses: Vec<Vec<u8>>) -> (Url, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let server = tokio::spawn(async move {
+            let mut requests = vec![];
+            for response in responses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut head = vec![];
+                while !head.ends_with(b"\r\n\r\n") {
+                    head.push(stream.read_u8().await.unwrap());
+                }
+                requests.push(String::from_utf8(head).unwrap().to_lowercase());
+                stream.write_all(&response).await.unwrap();
+                let _ = stream.shutdown().await;
+            }
+            requests
+        });
+        (url, server)
+    }
+
+    /// Downloads `path` from `url` with up to `max_retries` retries, returning the result and the
+    /// progress reported.
+    async fn download(
+        url: &Url,
+        path: &str,
+        max_retries: u32,
+    ) -> (AptosResult<Vec<u8>>, Vec<DownloadProgress>) {
+        let progress = Arc::new(Mutex::new(vec![]));
+        let reported = progress.clone();
+        let downloads = ResumableDownloads::new(max_retries)
+            .on_progress(move |_, progress| reported.lock().unwrap().push(progress));
+        let client = Client::new(url.clone());
+        let request = client.inner.get(url.join(path).unwrap()).build().unwrap();
+        let result = downloads
+            .download(&client, request)
+            .await
+            .map(|response| response.into_inner().to_vec());
+        let progress = progress.lock().unwrap().clone();
+        (result, progress)
+    }
+
+    #[test]
+    fn test_is_resumable() {
+        let resumable = |url: &str| is_resumable(&Url::parse(url).unwrap());
+        assert!(resumable(
+            "http://localhost/v1/accounts/0x1/modules?ledger_version=5"
+        ));
+        assert!(resumable("http://localhost/v1/transactions/by_version/5"));
+        assert!(resumable(
+            "http://localhost/v1/transactions?start=5&limit=10"
+        ));
+        assert!(!resumable("http://localhost/v1/accounts/0x1/modules"));
+        // The latest transactions change as the chain grows
+        assert!(!resumable("http://localhost/v1/transactions?limit=10"));
+        assert!(!resumable("http://localhost/v1/accounts/0x1/transactions"));
+    }
+
+    #[test]
+    fn test_content_range() {
+        let content_range = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(CONTENT_RANGE, HeaderValue::from_str(value).unwrap());
+            content_range(&headers)
+        };
+        assert_eq!(content_range("bytes 4-9/10"), Some((4, Some(10))));
+        assert_eq!(content_range("bytes 4-9/*"), Some((4, None)));
+        assert_eq!(content_range("bytes */10"), None);
+        assert_eq!(content_range("items 4-9/10"), None);
+        assert_eq!(content_range("bytes x-9/10"), None);
+        assert_eq!(content_range(&HeaderMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_resume() {
+        let (url, server) = serve(vec![
+            response("200 OK", &[], BODY, 4),
+            response(
+                "206 Partial Content",
+                &[("content-range", "bytes 4-9/10".to_string())],
+                &BODY[4..],
+                6,
+            ),
+        ])
+        .await;
+        let (result, progress) = download(&url, "v1/transactions/by_version/5", 1).await;
+        assert_eq!(result.unwrap(), BODY);
+        let requests = server.await.unwrap();
+        assert!(!requests[0].contains("range:"));
+        assert!(requests[1].contains("range: bytes=4-"));
+        assert_eq!(
+            progress.last(),
+            Some(&DownloadProgress {
+                received: 10,
+                total: Some(10),
+                retries: 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_restart() {
+        // The response may change between attempts, so the download starts over
+        let (url, server) = serve(vec![
+            response("200 OK", &[], BODY, 4),
+            response("200 OK", &[], BODY, BODY.len()),
+        ])
+        .await;
+        let (result, _) = download(&url, "v1/accounts/0x1/modules", 1).await;
+        assert_eq!(result.unwrap(), BODY);
+        assert!(!server.await.unwrap()[1].contains("range:"));
+
+        // The server ignores the range, so the download starts over
+        let (url, server) = serve(vec![
+            response("200 OK", &[], BODY, 4),
+            response("200 OK", &[], BODY, BODY.len()),
+        ])
+        .await;
+        let (result, _) = download(&url, "v1/transactions/by_version/5", 1).await;
+        assert_eq!(result.unwrap(), BODY);
+        assert!(server.await.unwrap()[1].contains("range: bytes=4-"));
+    }
+
+    #[tokio::test]
+    async fn test_restart_when_range_is_not_honored() {
+        // Another part than the one asked for, or the range is refused: only a full download of
+        // the response can be trusted, so no more ranges are asked for
+        for unexpected in [
+            response(
+                "206 Partial Content",
+                &[("content-range", "bytes 2-9/10".to_string())],
+                &BODY[2..],
+                8,
+            ),
+            response("206 Partial Content", &[], &BODY[4..], 6),
+            response(
+                "416 Range Not Satisfiable",
+                &[("content-type", "text/plain".to_string())],
+                b"",
+                0,
+            ),
+        ] {
+            let (url, server) = serve(vec![
+                response("200 OK", &[], BODY, 4),
+                unexpected,
+                response("200 OK", &[], BODY, 4),
+                response("200 OK", &[], BODY, BODY.len()),
+            ])
+            .await;
+            let (result, progress) = download(&url, "v1/transactions/by_version/5", 3).await;
+            assert_eq!(result.unwrap(), BODY);
+            assert_eq!(progress.last().unwrap().retries, 3);
+            let requests = server.await.unwrap();
+            assert!(requests[1].contains("range: bytes=4-"));
+            assert!(!requests[2].contains("range:"));
+            assert!(!requests[3].contains("range:"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_reconnect_takes_a_retry() {
+        let responses = || {
+            vec![
+                response("200 OK", &[], BODY, 4),
+                // Dropped before responding
+                vec![],
+                response(
+                    "206 Partial Content",
+                    &[("content-range", "bytes 4-9/10".to_string())],
+                    &BODY[4..],
+                    6,
+                ),
+            ]
+        };
+        let (url, server) = serve(responses()).await;
+        let (result, progress) = download(&url, "v1/transactions/by_version/5", 2).await;
+        assert_eq!(result.unwrap(), BODY);
+        assert_eq!(progress.last().unwrap().retries, 2);
+        assert!(server.await.unwrap()[2].contains("range: bytes=4-"));
+
+        let (url, server) = serve(responses()).await;
+        let (result, _) = download(&url, "v1/transactions/by_version/5", 1).await;
+        assert!(result.is_err());
+        server.abort();
+    }
+}
@@ -1,9 +1,11 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::State;
+use crate::{response::ResponseContext, State};
 use aptos_api_types::AptosError;
+use aptos_types::chain_id::ChainId;
 use reqwest::StatusCode;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Debug)]
@@ -159,6 +161,18 @@ pub enum RestError {
     Unknown(anyhow::Error),
     #[error("HTTP error {0}: {1}")]
     Http(StatusCode, reqwest::Error),
+    #[error(
+        "Stale response from ledger version {}: {:?} old, more than the bound of {:?}",
+        .context.ledger_version,
+        .context.age(),
+        .max_staleness
+    )]
+    Stale {
+        context: ResponseContext,
+        max_staleness: Duration,
+    },
+    #[error("Chain {received} doesn't match the chain {pinned} the client is pinned to")]
+    ChainIdMismatch { pinned: ChainId, received: ChainId },
 }
 
 impl From<(AptosError, Option<State>, StatusCode)> for RestError {
@@ -212,6 +226,13 @@ pub struct AptosErrorResponse {
     pub status_code: StatusCode,
 }
 
+impl AptosErrorResponse {
+    /// The ledger the error was served from, if the response was stamped with it
+    pub fn context(&self) -> Option<ResponseContext> {
+        self.state.as_ref().map(ResponseContext::from)
+    }
+}
+
 impl std::fmt::Display for AptosErrorResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.error)
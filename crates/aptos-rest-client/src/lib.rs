@@ -5,17 +5,45 @@
 extern crate core;
 
 pub mod aptos;
+pub mod auth;
+pub use auth::RequestAuth;
+pub mod batch;
+pub use batch::BatchReadBuilder;
+pub mod capabilities;
+pub use capabilities::NodeCapabilities;
+pub mod codegen;
+pub use codegen::{FieldNaming, RustStructGenerator, SchemaGenerator};
+pub mod download;
+pub use download::{DownloadProgress, ResumableDownloads};
 pub mod error;
 pub mod faucet;
 pub use faucet::FaucetClient;
+pub mod module_cache;
+pub use module_cache::{ModuleCache, ModuleFetchMode};
 pub mod response;
-pub use response::Response;
+pub use response::{Response, ResponseContext};
+pub mod package;
+pub use package::PackageBundle;
+pub mod quorum;
+pub use quorum::{QuorumClient, QuorumResponse};
 pub mod state;
+pub mod subscription;
+pub use subscription::ResourceDiff;
+#[cfg(test)]
+mod test_utils;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod type_accessor;
+pub use type_accessor::{
+    BuildReport, BytesFormat, BytesRendering, ModuleTypes, SharedTypeAccessor, TypeAccessor,
+    TypeAccessorBuilder,
+};
 pub mod types;
 
 use crate::{
-    aptos::{AptosVersion, Balance},
-    error::RestError,
+    aptos::{AptosVersion, Balance, RequiredBalance},
+    error::{AptosErrorResponse, RestError},
+    subscription::ResourceSubscription,
 };
 use anyhow::{anyhow, Result};
 pub use aptos_api_types::{
@@ -24,30 +52,50 @@ pub use aptos_api_types::{
 use aptos_api_types::{
     deserialize_from_string,
     mime_types::{BCS, BCS_SIGNED_TRANSACTION as BCS_CONTENT_TYPE, JSON},
-    AptosError, BcsBlock, Block, GasEstimation, HexEncodedBytes, IndexResponse, MoveModuleId,
-    TransactionData, TransactionOnChainData, TransactionsBatchSubmissionResult, UserTransaction,
-    VersionedEvent, ViewRequest,
+    well_known, AccountStateKey, AccountStateKeyKind, AptosError, BatchReadRequest,
+    BatchReadResult, BcsBlock, Block, EventFilter, GasEstimation, HexEncodedBytes, IndexResponse,
+    MoveModuleId, MoveStructTag, NodeInfo, TransactionData, TransactionOnChainData,
+    TransactionsBatchSubmissionResult, UserTransaction, VersionedEvent, ViewRequest,
+    FEATURE_ACCOUNT_STATE_KEYS, FEATURE_BATCH_READ, FEATURE_EVENTS_BY_TYPE,
+    FEATURE_RESOURCE_GROUPS,
+};
+use aptos_crypto::{
+    ed25519::{Ed25519PublicKey, Ed25519Signature},
+    hash::CryptoHash,
+    HashValue,
 };
-use aptos_crypto::HashValue;
 use aptos_logger::{debug, info, sample, sample::SampleRate};
 use aptos_types::{
+    access_path::AccessPath,
     account_address::AccountAddress,
     account_config::{AccountResource, CoinStoreResource, NewBlockEvent, CORE_CODE_ADDRESS},
+    chain_id::ChainId,
     contract_event::EventWithVersion,
-    transaction::SignedTransaction,
+    state_store::state_key::StateKey,
+    transaction::{
+        AbortInfo, ExecutionStatus, RawTransaction, SignedTransaction, TransactionPayload,
+    },
+};
+use futures::stream::{self, Stream};
+use move_core_types::{
+    language_storage::StructTag, move_resource::MoveStructType, vm_status::AbortLocation,
 };
-use move_core_types::language_storage::StructTag;
 use reqwest::{
     header::{ACCEPT, CONTENT_TYPE},
-    Client as ReqwestClient, StatusCode,
+    Client as ReqwestClient, RequestBuilder, StatusCode,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 pub use state::State;
-use std::{collections::BTreeMap, future::Future, time::Duration};
-use tokio::time::Instant;
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::{sync::OnceCell, time::Instant};
 pub use types::{deserialize_from_prefixed_hex_string, Account, Resource};
-use url::Url;
+pub use url::Url;
 
 pub const USER_AGENT: &str = concat!("aptos-client-sdk-rust / ", env!("CARGO_PKG_VERSION"));
 pub const DEFAULT_VERSION_PATH_BASE: &str = "v1/";
@@ -66,6 +114,14 @@ pub struct Client {
     inner: ReqwestClient,
     base_url: Url,
     version_path_base: String,
+    auth: Option<RequestAuth>,
+    max_staleness: Option<Duration>,
+    /// The chain id of the node, pinned by the first response and shared by clones of the client.
+    /// `None` if the check is disabled.
+    chain_id: Option<Arc<OnceCell<ChainId>>>,
+    /// What the node supports, fetched once and shared by clones of the client.
+    capabilities: Arc<OnceCell<NodeCapabilities>>,
+    downloads: Option<ResumableDownloads>,
 }
 
 impl Client {
@@ -103,6 +159,11 @@ impl Client {
             inner,
             base_url,
             version_path_base,
+            auth: None,
+            max_staleness: None,
+            chain_id: Some(Arc::new(OnceCell::new())),
+            capabilities: Arc::new(OnceCell::new()),
+            downloads: None,
         }
     }
 
@@ -110,6 +171,10 @@ impl Client {
         Self::new_with_timeout(base_url, Duration::from_secs(10))
     }
 
+    pub fn base_url(&self) -> &Url {
+        &self.base_url
+    }
+
     pub fn path_prefix_string(&self) -> String {
         self.base_url
             .join(&self.version_path_base)
@@ -127,6 +192,67 @@ impl Client {
         Ok(self)
     }
 
+    /// Authenticate every request with `auth`, e.g. for a gateway requiring an API key.
+    pub fn with_auth(mut self, auth: RequestAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Reject responses whose ledger timestamp is more than `max_staleness` behind the local clock
+    /// with [`RestError::Stale`], e.g. to avoid acting on a fullnode that fell behind. Skew between
+    /// the local clock and the chain counts towards the staleness.
+    pub fn with_max_staleness(mut self, max_staleness: Duration) -> Self {
+        self.max_staleness = Some(max_staleness);
+        self
+    }
+
+    /// Pin the chain id to `chain_id` up front, rather than to the one of the first response.
+    ///
+    /// By default, the client pins the chain id of the first response. It then rejects responses
+    /// from another chain, and transactions built for another chain before submitting or
+    /// simulating them, with [`RestError::ChainIdMismatch`].
+    pub fn with_chain_id(mut self, chain_id: ChainId) -> Self {
+        self.chain_id = Some(Arc::new(OnceCell::new_with(Some(chain_id))));
+        self
+    }
+
+    /// Don't check chain ids, e.g. to submit to several networks with one client on purpose, or
+    /// for a long lived client to follow a network whose ledger gets reset, like devnet.
+    pub fn without_chain_id_check(mut self) -> Self {
+        self.chain_id = None;
+        self
+    }
+
+    /// Download BCS responses as `downloads` says, resuming them when the connection drops and
+    /// reporting their progress, e.g. for crawlers of large modules and transaction pages on
+    /// flaky connections. By default, a dropped connection fails the request.
+    pub fn with_resumable_downloads(mut self, downloads: ResumableDownloads) -> Self {
+        self.downloads = Some(downloads);
+        self
+    }
+
+    /// What the API of the node supports, fetched from `/info` by the first call and cached
+    /// afterwards. Nodes predating the endpoint get [`NodeCapabilities::legacy`]. Helpers such as
+    /// [`Client::batch`] use it to pick code paths the node supports.
+    pub async fn capabilities(&self) -> AptosResult<NodeCapabilities> {
+        self.capabilities
+            .get_or_try_init(|| async {
+                let url = self.build_path("info")?;
+                match self.get::<NodeInfo>(url).await {
+                    Ok(response) => Ok(NodeCapabilities::new(response.into_inner())),
+                    Err(RestError::Api(AptosErrorResponse { status_code, .. }))
+                    | Err(RestError::Http(status_code, _))
+                        if status_code == StatusCode::NOT_FOUND =>
+                    {
+                        Ok(NodeCapabilities::legacy())
+                    },
+                    Err(err) => Err(err),
+                }
+            })
+            .await
+            .cloned()
+    }
+
     pub fn build_path(&self, path: &str) -> AptosResult<Url> {
         Ok(self.base_url.join(&self.version_path_base)?.join(path)?)
     }
@@ -239,7 +365,7 @@ impl Client {
         address: AccountAddress,
     ) -> AptosResult<Response<Balance>> {
         let resp = self
-            .get_account_resource(address, "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>")
+            .get_account_resource(address, &well_known::apt_coin_store().to_string())
             .await?;
         resp.and_then(|resource| {
             if let Some(res) = resource {
@@ -272,7 +398,7 @@ impl Client {
         let resp = self
             .get_account_resource_at_version(
                 address,
-                "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+                &well_known::apt_coin_store().to_string(),
                 version,
             )
             .await?;
@@ -327,11 +453,73 @@ impl Client {
         }
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, JSON)
-            .body(request)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, JSON)
+                    .body(request),
+            )
+            .await?;
+
+        self.json(response).await
+    }
+
+    /// Executes all reads added in `build` against a single ledger version, in one request.
+    /// Results are in the order the reads were added, and a failed read doesn't fail the others.
+    ///
+    /// ```ignore
+    /// let results = client
+    ///     .batch(|b| {
+    ///         b.resource(address, &coin_store_type).view(balance_request);
+    ///     })
+    ///     .await?;
+    /// ```
+    ///
+    /// Reads beyond the maximum batch size of the node are sent in further requests, pinned to the
+    /// ledger version of the first one.
+    pub async fn batch<F: FnOnce(&mut BatchReadBuilder)>(
+        &self,
+        build: F,
+    ) -> AptosResult<Response<Vec<BatchReadResult>>> {
+        let mut builder = BatchReadBuilder::default();
+        build(&mut builder);
+
+        let capabilities = self.capabilities().await?;
+        capabilities.require(FEATURE_BATCH_READ)?;
+        let max_batch_size = capabilities
+            .max_batch_read_size()
+            .unwrap_or(usize::MAX)
+            .max(1);
+        let mut chunks = builder.requests.chunks(max_batch_size);
+        let first = self
+            .batch_request(chunks.next().unwrap_or_default(), builder.ledger_version)
+            .await?;
+        let version = builder.ledger_version.unwrap_or(first.state().version);
+        let (mut results, state) = first.into_parts();
+        for chunk in chunks {
+            results.extend(self.batch_request(chunk, Some(version)).await?.into_inner());
+        }
+        Ok(Response::new(results, state))
+    }
+
+    async fn batch_request(
+        &self,
+        requests: &[BatchReadRequest],
+        ledger_version: Option<u64>,
+    ) -> AptosResult<Response<Vec<BatchReadResult>>> {
+        let request = serde_json::to_string(requests)?;
+        let mut url = self.build_path("batch")?;
+        if let Some(version) = ledger_version {
+            url.set_query(Some(format!("ledger_version={}", version).as_str()));
+        }
+
+        let response = self
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, JSON)
+                    .body(request),
+            )
             .await?;
 
         self.json(response).await
@@ -341,15 +529,17 @@ impl Client {
         &self,
         txn: &SignedTransaction,
     ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions/simulate")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
@@ -361,6 +551,7 @@ impl Client {
         estimate_max_gas_amount: bool,
         estimate_max_gas_unit_price: bool,
     ) -> AptosResult<Response<Vec<UserTransaction>>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
 
         let url = self.build_path(&format!(
@@ -369,11 +560,12 @@ impl Client {
         ))?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
@@ -383,16 +575,18 @@ impl Client {
         &self,
         txn: &SignedTransaction,
     ) -> AptosResult<Response<TransactionOnChainData>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions/simulate")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -405,6 +599,7 @@ impl Client {
         estimate_max_gas_amount: bool,
         estimate_max_gas_unit_price: bool,
     ) -> AptosResult<Response<TransactionOnChainData>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path(&format!(
             "transactions/simulate?estimate_max_gas_amount={}&estimate_gas_unit_price={}",
@@ -412,47 +607,123 @@ impl Client {
         ))?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
         Ok(response.and_then(|bytes| bcs::from_bytes(&bytes))?)
     }
 
+    /// Estimates the balance `sender`, whose current key is `public_key`, needs for `payload` to
+    /// succeed, by simulating it at the estimated gas unit price. Fails with the VM status if the
+    /// simulation fails, e.g. as the balance doesn't even cover the gas.
+    pub async fn estimate_required_balance(
+        &self,
+        sender: AccountAddress,
+        public_key: &Ed25519PublicKey,
+        payload: TransactionPayload,
+    ) -> AptosResult<Response<RequiredBalance>> {
+        let (account, state) = self.get_account_bcs(sender).await?.into_parts();
+        let expiration_timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|e| anyhow!(e))?
+            .as_secs()
+            + 60;
+        // The gas unit price and max gas amount are filled in by the simulation
+        let raw_txn = RawTransaction::new(
+            sender,
+            account.sequence_number(),
+            payload,
+            0,
+            0,
+            expiration_timestamp_secs,
+            ChainId::new(state.chain_id),
+        );
+        let txn = SignedTransaction::new(
+            raw_txn,
+            public_key.clone(),
+            Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+        );
+        let (simulated, state) = self
+            .simulate_bcs_with_gas_estimation(&txn, true, true)
+            .await?
+            .into_parts();
+        if !simulated.info.status().is_success() {
+            return Err(anyhow!("Simulation failed: {:?}", simulated.info.status()).into());
+        }
+        let gas_unit_price = match &simulated.transaction {
+            aptos_types::transaction::Transaction::UserTransaction(txn) => txn.gas_unit_price(),
+            _ => return Err(anyhow!("Simulation returned a non-user transaction").into()),
+        };
+
+        // What the sender's APT balance drops by, besides gas, is what the transaction withdraws
+        let coin_store_type = CoinStoreResource::struct_tag();
+        let before = self
+            .get_account_resource_at_version_bcs::<CoinStoreResource>(
+                sender,
+                &coin_store_type.to_string(),
+                simulated.version,
+            )
+            .await?
+            .into_inner()
+            .coin();
+        let state_key =
+            StateKey::access_path(AccessPath::resource_access_path(sender, coin_store_type)?);
+        let after = match simulated.changes.get(&state_key).and_then(|op| op.bytes()) {
+            Some(bytes) => bcs::from_bytes::<CoinStoreResource>(bytes)?.coin(),
+            None => before,
+        };
+        let gas_used = simulated.info.gas_used();
+        let gas_fee = gas_used.saturating_mul(gas_unit_price);
+        Ok(Response::new(
+            RequiredBalance {
+                gas_used,
+                gas_unit_price,
+                withdrawn: before.saturating_sub(after).saturating_sub(gas_fee),
+            },
+            state,
+        ))
+    }
+
     pub async fn submit(
         &self,
         txn: &SignedTransaction,
     ) -> AptosResult<Response<PendingTransaction>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .body(txn_payload),
+            )
             .await?;
 
         self.json(response).await
     }
 
     pub async fn submit_bcs(&self, txn: &SignedTransaction) -> AptosResult<Response<()>> {
+        self.check_chain_id(std::slice::from_ref(txn)).await?;
         let txn_payload = bcs::to_bytes(txn)?;
         let url = self.build_path("transactions")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -463,15 +734,17 @@ impl Client {
         &self,
         txns: &[SignedTransaction],
     ) -> AptosResult<Response<TransactionsBatchSubmissionResult>> {
+        self.check_chain_id(txns).await?;
         let txn_payload = bcs::to_bytes(&txns.to_vec())?;
         let url = self.build_path("transactions/batch")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .body(txn_payload),
+            )
             .await?;
         self.json(response).await
     }
@@ -480,16 +753,18 @@ impl Client {
         &self,
         txns: &[SignedTransaction],
     ) -> AptosResult<Response<TransactionsBatchSubmissionResult>> {
+        self.check_chain_id(txns).await?;
         let txn_payload = bcs::to_bytes(&txns.to_vec())?;
         let url = self.build_path("transactions/batch")?;
 
         let response = self
-            .inner
-            .post(url)
-            .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
-            .header(ACCEPT, BCS)
-            .body(txn_payload)
-            .send()
+            .send(
+                self.inner
+                    .post(url)
+                    .header(CONTENT_TYPE, BCS_CONTENT_TYPE)
+                    .header(ACCEPT, BCS)
+                    .body(txn_payload),
+            )
             .await?;
 
         let response = self.check_and_parse_bcs_response(response).await?;
@@ -592,29 +867,77 @@ impl Client {
         timeout_from_call: Option<Duration>,
         fetch: F,
     ) -> AptosResult<Response<T>>
+    where
+        F: Fn(HashValue) -> Fut,
+        Fut: Future<Output = AptosResult<WaitForTransactionResult<T>>>,
+    {
+        match self
+            .wait_for_transaction_end(
+                hash,
+                expiration_timestamp_secs,
+                max_server_lag_wait,
+                timeout_from_call,
+                fetch,
+            )
+            .await?
+        {
+            WaitForTransactionEnd::Committed(result) => Ok(result),
+            WaitForTransactionEnd::FailedExecution(vm_status, _) => Err(anyhow!(
+                "Transaction committed on chain, but failed execution: {}",
+                vm_status
+            ))?,
+            WaitForTransactionEnd::Expired {
+                seen_in_mempool: true,
+                ..
+            }
+            | WaitForTransactionEnd::Evicted(_) => Err(anyhow!(
+                "Transaction expired. It is guaranteed it will not be committed on chain."
+            )
+            .into()),
+            WaitForTransactionEnd::Expired {
+                seen_in_mempool: false,
+                ..
+            } => Err(anyhow!("Transaction expired, without being seen in mempool. It is guaranteed it will not be committed on chain.").into()),
+        }
+    }
+
+    /// Waits for a transaction like `wait_for_transaction_by_hash_inner`, returning how waiting
+    /// ended rather than turning failures into errors. Errors are only returned when it is unknown
+    /// whether the transaction will be committed, e.g. on timeouts.
+    async fn wait_for_transaction_end<F, Fut, T>(
+        &self,
+        hash: HashValue,
+        expiration_timestamp_secs: u64,
+        max_server_lag_wait: Option<Duration>,
+        timeout_from_call: Option<Duration>,
+        fetch: F,
+    ) -> AptosResult<WaitForTransactionEnd<T>>
     where
         F: Fn(HashValue) -> Fut,
         Fut: Future<Output = AptosResult<WaitForTransactionResult<T>>>,
     {
         const DEFAULT_DELAY: Duration = Duration::from_millis(500);
         let mut reached_mempool = false;
+        // Whether the transaction was pending, then not found before expiring
+        let mut left_mempool = false;
         let start = std::time::Instant::now();
         loop {
             let mut chain_timestamp_usecs = None;
             match fetch(hash).await {
                 Ok(WaitForTransactionResult::Success(result)) => {
-                    return Ok(result);
+                    return Ok(WaitForTransactionEnd::Committed(result));
                 },
-                Ok(WaitForTransactionResult::FailedExecution(vm_status)) => {
-                    return Err(anyhow!(
-                        "Transaction committed on chain, but failed execution: {}",
-                        vm_status
-                    ))?;
+                Ok(WaitForTransactionResult::FailedExecution(vm_status, result)) => {
+                    return Ok(WaitForTransactionEnd::FailedExecution(vm_status, result));
                 },
                 Ok(WaitForTransactionResult::Pending(state)) => {
                     reached_mempool = true;
+                    left_mempool = false;
                     if expiration_timestamp_secs <= state.timestamp_usecs / 1_000_000 {
-                        return Err(anyhow!("Transaction expired. It is guaranteed it will not be committed on chain.").into());
+                        return Ok(WaitForTransactionEnd::Expired {
+                            seen_in_mempool: true,
+                            state,
+                        });
                     }
                     chain_timestamp_usecs = Some(state.timestamp_usecs);
                 },
@@ -622,8 +945,13 @@ impl Client {
                     if let RestError::Api(aptos_error_response) = error {
                         if let Some(state) = aptos_error_response.state {
                             if expiration_timestamp_secs <= state.timestamp_usecs / 1_000_000 {
-                                if reached_mempool {
-                                    return Err(anyhow!("Transaction expired. It is guaranteed it will not be committed on chain.").into());
+                                if left_mempool {
+                                    return Ok(WaitForTransactionEnd::Evicted(state));
+                                } else if reached_mempool {
+                                    return Ok(WaitForTransactionEnd::Expired {
+                                        seen_in_mempool: true,
+                                        state,
+                                    });
                                 } else {
                                     // We want to know whether we ever got Pending state from the mempool,
                                     // to warn in case we didn't.
@@ -635,9 +963,15 @@ impl Client {
                                     // At the end, when the expiration happens, we might get NotFound or Pending
                                     // based on whether GC run on the full node to remove expired transaction,
                                     // so that information is not useful. So we need to keep this variable as state.
-                                    return Err(anyhow!("Transaction expired, without being seen in mempool. It is guaranteed it will not be committed on chain.").into());
+                                    return Ok(WaitForTransactionEnd::Expired {
+                                        seen_in_mempool: false,
+                                        state,
+                                    });
                                 }
                             }
+                            // Before expiring, a pending transaction is only removed from
+                            // mempool if it gets evicted, e.g. when mempool is full
+                            left_mempool |= reached_mempool;
                             chain_timestamp_usecs = Some(state.timestamp_usecs);
                         }
                     } else {
@@ -727,6 +1061,7 @@ impl Client {
                         if !transaction.success() {
                             Ok(WaitForTransactionResult::FailedExecution(
                                 transaction.vm_status(),
+                                Response::new(transaction, state),
                             ))
                         } else {
                             Ok(WaitForTransactionResult::Success(Response::new(
@@ -758,35 +1093,78 @@ impl Client {
             expiration_timestamp_secs,
             max_server_lag_wait,
             timeout_from_call,
-            |hash| async move {
-                let resp = self.get_transaction_by_hash_bcs_inner(hash).await?;
-                if resp.status() != StatusCode::NOT_FOUND {
-                    let resp = self.check_and_parse_bcs_response(resp).await?;
-                    let resp = resp.and_then(|bytes| bcs::from_bytes(&bytes))?;
-                    let (maybe_pending_txn, state) = resp.into_parts();
-
-                    // If we have a committed transaction, determine if it failed or not
-                    if let TransactionData::OnChain(txn) = maybe_pending_txn {
-                        let status = txn.info.status();
+            |hash| self.fetch_transaction_by_hash_bcs(hash),
+        )
+        .await
+    }
 
-                        if status.is_success() {
-                            Ok(WaitForTransactionResult::Success(Response::new(txn, state)))
-                        } else {
-                            Ok(WaitForTransactionResult::FailedExecution(format!(
-                                "{:?}",
-                                status
-                            )))
-                        }
-                    } else {
-                        Ok(WaitForTransactionResult::Pending(state))
-                    }
+    /// Fetches the transaction `hash` in BCS for the `wait_for_transaction_*` functions: whether
+    /// it's committed, successfully or not, pending or not found.
+    async fn fetch_transaction_by_hash_bcs(
+        &self,
+        hash: HashValue,
+    ) -> AptosResult<WaitForTransactionResult<TransactionOnChainData>> {
+        let resp = self.get_transaction_by_hash_bcs_inner(hash).await?;
+        if resp.status() != StatusCode::NOT_FOUND {
+            let resp = self.check_and_parse_bcs_response(resp).await?;
+            let resp = resp.and_then(|bytes| bcs::from_bytes(&bytes))?;
+            let (maybe_pending_txn, state) = resp.into_parts();
+
+            // If we have a committed transaction, determine if it failed or not
+            if let TransactionData::OnChain(txn) = maybe_pending_txn {
+                let status = txn.info.status();
+
+                if status.is_success() {
+                    Ok(WaitForTransactionResult::Success(Response::new(txn, state)))
                 } else {
-                    let error_response = parse_error(resp).await;
-                    Ok(WaitForTransactionResult::NotFound(error_response))
+                    Ok(WaitForTransactionResult::FailedExecution(
+                        format!("{:?}", status),
+                        Response::new(txn, state),
+                    ))
                 }
+            } else {
+                Ok(WaitForTransactionResult::Pending(state))
+            }
+        } else {
+            let error_response = parse_error(resp).await;
+            Ok(WaitForTransactionResult::NotFound(error_response))
+        }
+    }
+
+    /// Waits for a transaction like `wait_for_transaction`, but returns how it ended rather than
+    /// failing when it isn't committed successfully, so callers can tell aborts, expiration and
+    /// eviction apart without parsing the VM status. Errors are only returned when it is unknown
+    /// whether the transaction will be committed, e.g. if the node lags too far behind.
+    pub async fn wait_for_transaction_outcome(
+        &self,
+        pending_transaction: &PendingTransaction,
+    ) -> AptosResult<Response<TransactionOutcome>> {
+        let end = self
+            .wait_for_transaction_end(
+                pending_transaction.hash.into(),
+                *pending_transaction
+                    .request
+                    .expiration_timestamp_secs
+                    .inner(),
+                Some(DEFAULT_MAX_SERVER_LAG_WAIT_DURATION),
+                None,
+                |hash| self.fetch_transaction_by_hash_bcs(hash),
+            )
+            .await?;
+        Ok(match end {
+            // Failed transactions are committed too, their status is in the outcome
+            WaitForTransactionEnd::Committed(response)
+            | WaitForTransactionEnd::FailedExecution(_, response) => {
+                response.map(|txn| TransactionOutcome::from_committed(&txn))
             },
-        )
-        .await
+            WaitForTransactionEnd::Expired {
+                seen_in_mempool,
+                state,
+            } => Response::new(TransactionOutcome::Expired { seen_in_mempool }, state),
+            WaitForTransactionEnd::Evicted(state) => {
+                Response::new(TransactionOutcome::Evicted, state)
+            },
+        })
     }
 
     pub async fn wait_for_version(&self, version: u64) -> Result<State> {
@@ -828,7 +1206,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -865,7 +1243,7 @@ impl Client {
         hash: HashValue,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
+        let response = self.send(self.inner.get(url).header(ACCEPT, BCS)).await?;
         Ok(response)
     }
 
@@ -874,7 +1252,7 @@ impl Client {
         hash: HashValue,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_hash/{}", hash.to_hex_literal()))?;
-        Ok(self.inner.get(url).send().await?)
+        Ok(self.send(self.inner.get(url)).await?)
     }
 
     pub async fn get_transaction_by_version(
@@ -899,7 +1277,7 @@ impl Client {
         version: u64,
     ) -> AptosResult<reqwest::Response> {
         let url = self.build_path(&format!("transactions/by_version/{}", version))?;
-        Ok(self.inner.get(url).send().await?)
+        Ok(self.send(self.inner.get(url)).await?)
     }
 
     pub async fn get_account_transactions(
@@ -919,7 +1297,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        let response = self.send(request).await?;
 
         self.json(response).await
     }
@@ -947,6 +1325,74 @@ impl Client {
         .await
     }
 
+    /// Lists the state keys (resources, resource groups and modules) under `address` without
+    /// fetching their values. If `prefix` is set, only keys whose resource type or module id
+    /// starts with it are returned, e.g. `0x1::coin` or `0x1::coin::CoinStore`.
+    ///
+    /// Nodes without the state keys endpoint are asked for the resources and modules of the
+    /// account instead, whose keys are derived locally.
+    pub async fn get_account_state_keys(
+        &self,
+        address: AccountAddress,
+        prefix: Option<&str>,
+    ) -> AptosResult<Response<Vec<AccountStateKey>>> {
+        if !self
+            .capabilities()
+            .await?
+            .supports(FEATURE_ACCOUNT_STATE_KEYS)
+        {
+            return self.derive_account_state_keys(address, prefix).await;
+        }
+        let mut base_path = format!("accounts/{}/state_keys", address);
+        if let Some(prefix) = prefix {
+            let prefix: String = url::form_urlencoded::byte_serialize(prefix.as_bytes()).collect();
+            base_path = format!("{}?prefix={}", base_path, prefix);
+        }
+        self.paginate_with_cursor(&base_path, RESOURCES_PER_CALL_PAGINATION, None)
+            .await
+    }
+
+    async fn derive_account_state_keys(
+        &self,
+        address: AccountAddress,
+        prefix: Option<&str>,
+    ) -> AptosResult<Response<Vec<AccountStateKey>>> {
+        let resources = self.get_account_resources_bcs(address).await?;
+        let version = resources.state().version;
+        let (resources, _) = resources.into_parts();
+        let (modules, state) = self
+            .paginate_with_cursor_bcs::<MoveModuleId>(
+                &format!("accounts/{}/modules", address),
+                MODULES_PER_CALL_PAGINATION,
+                Some(version),
+            )
+            .await?
+            .into_parts();
+
+        let mut keys = vec![];
+        for struct_tag in resources.into_keys() {
+            let name = MoveStructTag::from(struct_tag.clone()).to_string();
+            let access_path = AccessPath::resource_access_path(address, struct_tag)
+                .map_err(RestError::Unknown)?;
+            keys.push((AccountStateKeyKind::Resource, name, access_path));
+        }
+        for module_id in modules.into_keys() {
+            let name = module_id.to_string();
+            let access_path = AccessPath::code_access_path(module_id.into());
+            keys.push((AccountStateKeyKind::Module, name, access_path));
+        }
+        let keys = keys
+            .into_iter()
+            .filter(|(_, name, _)| prefix.map_or(true, |prefix| name.starts_with(prefix)))
+            .map(|(kind, name, access_path)| AccountStateKey {
+                kind,
+                name,
+                state_key_hash: StateKey::access_path(access_path).hash().into(),
+            })
+            .collect();
+        Ok(Response::new(keys, state))
+    }
+
     pub async fn get_account_resources_bcs(
         &self,
         address: AccountAddress,
@@ -1014,9 +1460,7 @@ impl Client {
         let url = self.build_path(&format!("accounts/{}/resource/{}", address, resource_type))?;
 
         let response = self
-            .inner
-            .get(url)
-            .send()
+            .send(self.inner.get(url))
             .await
             .map_err(anyhow::Error::from)?;
         self.json(response).await
@@ -1032,6 +1476,46 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// Reads the resources of the resource group `group_type` under `address`, e.g.
+    /// `0x1::object::ObjectGroup`, each decoded on its own.
+    pub async fn get_resource_group(
+        &self,
+        address: AccountAddress,
+        group_type: &str,
+    ) -> AptosResult<Response<Vec<Resource>>> {
+        self.capabilities()
+            .await?
+            .require(FEATURE_RESOURCE_GROUPS)?;
+        let url = self.build_path(&format!(
+            "accounts/{}/resource_group/{}",
+            address, group_type
+        ))?;
+
+        let response = self
+            .send(self.inner.get(url))
+            .await
+            .map_err(anyhow::Error::from)?;
+        self.json(response).await
+    }
+
+    /// Reads the resources of the resource group `group_type` under `address`, BCS encoded, by
+    /// type.
+    pub async fn get_resource_group_bcs(
+        &self,
+        address: AccountAddress,
+        group_type: &str,
+    ) -> AptosResult<Response<BTreeMap<StructTag, Vec<u8>>>> {
+        self.capabilities()
+            .await?
+            .require(FEATURE_RESOURCE_GROUPS)?;
+        let url = self.build_path(&format!(
+            "accounts/{}/resource_group/{}",
+            address, group_type
+        ))?;
+        let response = self.get_bcs(url).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
     pub async fn get_account_resource_at_version_bcs<T: DeserializeOwned>(
         &self,
         address: AccountAddress,
@@ -1073,6 +1557,35 @@ impl Client {
         Ok(response.map(|inner| inner.to_vec()))
     }
 
+    /// Polls the resource `struct_tag` of `address` every `poll_interval`, yielding a
+    /// [`ResourceDiff`] whenever its value changed, decoded with a [`TypeAccessor`] built from the
+    /// modules of the resource. The value at the first poll is the baseline, and isn't yielded.
+    /// Changes between two polls are coalesced into one diff. Errors are yielded without ending
+    /// the stream, so a failed poll only delays the next diff.
+    pub fn subscribe_resource(
+        &self,
+        address: AccountAddress,
+        struct_tag: StructTag,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<ResourceDiff>> {
+        let subscription = ResourceSubscription::new(self.clone(), address, struct_tag);
+        stream::unfold(
+            (subscription, false),
+            move |(mut subscription, polled)| async move {
+                if polled {
+                    tokio::time::sleep(poll_interval).await;
+                }
+                loop {
+                    match subscription.poll().await {
+                        Ok(Some(diff)) => return Some((Ok(diff), (subscription, true))),
+                        Ok(None) => tokio::time::sleep(poll_interval).await,
+                        Err(err) => return Some((Err(err), (subscription, true))),
+                    }
+                }
+            },
+        )
+    }
+
     pub async fn get_account_resource_at_version(
         &self,
         address: AccountAddress,
@@ -1084,7 +1597,7 @@ impl Client {
             address, resource_type, version
         ))?;
 
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1112,6 +1625,19 @@ impl Client {
         .await
     }
 
+    pub async fn get_account_modules_bcs_at_version(
+        &self,
+        address: AccountAddress,
+        version: u64,
+    ) -> AptosResult<Response<BTreeMap<MoveModuleId, Vec<u8>>>> {
+        self.paginate_with_cursor_bcs(
+            &format!("accounts/{}/modules", address),
+            MODULES_PER_CALL_PAGINATION,
+            Some(version),
+        )
+        .await
+    }
+
     pub async fn get_account_module(
         &self,
         address: AccountAddress,
@@ -1143,6 +1669,40 @@ impl Client {
         self.get_bcs(url).await
     }
 
+    /// Downloads the package `package_name` published at `address`: its metadata from the
+    /// package registry, and the bytecode of all its modules at the same ledger version. The
+    /// bytecode is checked against the metadata, and the sources, if published, against the
+    /// digest of the registry, see [`PackageBundle::source_digest`].
+    pub async fn get_package(
+        &self,
+        address: AccountAddress,
+        package_name: &str,
+    ) -> AptosResult<Response<PackageBundle>> {
+        let (registry, state) = self
+            .get_account_resource_bcs::<package::PackageRegistry>(
+                address,
+                package::PACKAGE_REGISTRY_TYPE,
+            )
+            .await?
+            .into_parts();
+        let metadata = registry
+            .packages
+            .into_iter()
+            .find(|package| package.name == package_name)
+            .ok_or_else(|| anyhow!("No package {} at {}", package_name, address))?;
+
+        let modules = metadata.modules.iter().map(|module| {
+            self.get_account_module_bcs_at_version(address, &module.name, state.version)
+        });
+        let bytecode = futures::future::try_join_all(modules)
+            .await?
+            .into_iter()
+            .map(|response| response.into_inner().to_vec())
+            .collect();
+        let bundle = PackageBundle::new(address, metadata, bytecode)?;
+        Ok(Response::new(bundle, state))
+    }
+
     pub async fn get_account_events(
         &self,
         address: AccountAddress,
@@ -1150,6 +1710,34 @@ impl Client {
         field_name: &str,
         start: Option<u64>,
         limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<VersionedEvent>>> {
+        self.get_account_events_inner(address, struct_tag, field_name, start, limit, None)
+            .await
+    }
+
+    /// Like [`Client::get_account_events`], but only returns the events of the page matching
+    /// `filter`. The fullnode filters after paging, so a page may hold fewer than `limit` events.
+    pub async fn get_account_events_filtered(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+        field_name: &str,
+        start: Option<u64>,
+        limit: Option<u16>,
+        filter: &EventFilter,
+    ) -> AptosResult<Response<Vec<VersionedEvent>>> {
+        self.get_account_events_inner(address, struct_tag, field_name, start, limit, Some(filter))
+            .await
+    }
+
+    async fn get_account_events_inner(
+        &self,
+        address: AccountAddress,
+        struct_tag: &str,
+        field_name: &str,
+        start: Option<u64>,
+        limit: Option<u16>,
+        filter: Option<&EventFilter>,
     ) -> AptosResult<Response<Vec<VersionedEvent>>> {
         let url = self.build_path(&format!(
             "accounts/{}/events/{}/{}",
@@ -1166,7 +1754,11 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
+        if let Some(filter) = filter {
+            request = request.query(&filter.to_query_params())
+        }
+
+        let response = self.send(request).await?;
         self.json(response).await
     }
 
@@ -1189,6 +1781,61 @@ impl Client {
         Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
     }
 
+    /// The events of type `event_type`, e.g. `0x1::coin::DepositEvent`, emitted to any account
+    /// by the transactions from `start_version` up to `end_version` (exclusive), or up to the
+    /// latest version. The fullnode scans a bounded number of versions per request: if it
+    /// stopped before the end of the range, the version to continue at is the cursor of the
+    /// response's state.
+    pub async fn get_events_by_type(
+        &self,
+        event_type: &str,
+        start_version: u64,
+        end_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<VersionedEvent>>> {
+        self.capabilities().await?.require(FEATURE_EVENTS_BY_TYPE)?;
+        let request = self.events_by_type_request(event_type, start_version, end_version, limit)?;
+        let response = self.send(request).await?;
+        self.json(response).await
+    }
+
+    pub async fn get_events_by_type_bcs(
+        &self,
+        event_type: &str,
+        start_version: u64,
+        end_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<Response<Vec<EventWithVersion>>> {
+        self.capabilities().await?.require(FEATURE_EVENTS_BY_TYPE)?;
+        let request = self
+            .events_by_type_request(event_type, start_version, end_version, limit)?
+            .header(ACCEPT, BCS);
+        let response = self.send(request).await?;
+        let response = self.check_and_parse_bcs_response(response).await?;
+        Ok(response.and_then(|inner| bcs::from_bytes(&inner))?)
+    }
+
+    fn events_by_type_request(
+        &self,
+        event_type: &str,
+        start_version: u64,
+        end_version: Option<u64>,
+        limit: Option<u16>,
+    ) -> AptosResult<RequestBuilder> {
+        let url = self.build_path(&format!("events/by_type/{}", event_type))?;
+        let mut request = self
+            .inner
+            .get(url)
+            .query(&[("start_version", start_version)]);
+        if let Some(end_version) = end_version {
+            request = request.query(&[("end_version", end_version)])
+        }
+        if let Some(limit) = limit {
+            request = request.query(&[("limit", limit)])
+        }
+        Ok(request)
+    }
+
     pub async fn get_new_block_events_bcs(
         &self,
         start: Option<u64>,
@@ -1253,7 +1900,7 @@ impl Client {
             "key": json!(key),
         });
 
-        let response = self.inner.post(url).json(&data).send().await?;
+        let response = self.send(self.inner.post(url).json(&data)).await?;
         self.json(response).await
     }
 
@@ -1295,7 +1942,7 @@ impl Client {
 
     pub async fn get_account(&self, address: AccountAddress) -> AptosResult<Response<Account>> {
         let url = self.build_path(&format!("accounts/{}", address))?;
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1310,7 +1957,7 @@ impl Client {
 
     pub async fn estimate_gas_price(&self) -> AptosResult<Response<GasEstimation>> {
         let url = self.build_path("estimate_gas_price")?;
-        let response = self.inner.get(url).send().await?;
+        let response = self.send(self.inner.get(url)).await?;
         self.json(response).await
     }
 
@@ -1321,8 +1968,55 @@ impl Client {
             .append_pair("name", &name)
             .append_pair("actions", &actions)
             .finish();
-        let response = self.inner.get(url.clone()).send().await?;
+        let response = self.send(self.inner.get(url.clone())).await?;
+        self.response_text(response).await
+    }
 
+    /// Moves chain time forward by `by`, on a local testnet with admin endpoints enabled.
+    ///
+    /// Transaction expiration is checked against chain time, so afterwards transactions must
+    /// have their expiration derived from the ledger timestamp rather than the wall clock.
+    pub async fn advance_time(&self, by: Duration) -> AptosResult<String> {
+        let mut base = self.build_path("admin/advance_time")?;
+        let url = base
+            .query_pairs_mut()
+            .append_pair("seconds", &by.as_secs().to_string())
+            .finish();
+        let response = self.send(self.inner.post(url.clone())).await?;
+        self.response_text(response).await
+    }
+
+    /// Ends the current epoch, on a local testnet with admin endpoints enabled, by moving chain
+    /// time forward to the end of the epoch. Waits until the chain is in the next epoch, and
+    /// returns its ledger state.
+    ///
+    /// This needs a block to be committed, so it times out if the node only proposes blocks
+    /// when there are transactions to include.
+    pub async fn force_epoch_change(&self) -> AptosResult<State> {
+        const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+        const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+
+        let epoch = self.get_ledger_information().await?.into_inner().epoch;
+        let url = self.build_path("admin/force_epoch_change")?;
+        let response = self.send(self.inner.post(url)).await?;
+        self.response_text(response).await?;
+
+        let start = std::time::Instant::now();
+        loop {
+            let state = self.get_ledger_information().await?.into_inner();
+            if state.epoch > epoch {
+                return Ok(state);
+            }
+
+            if start.elapsed() >= DEFAULT_TIMEOUT {
+                return Err(anyhow!("timeout when waiting for the end of epoch {}", epoch).into());
+            }
+
+            tokio::time::sleep(DEFAULT_DELAY).await;
+        }
+    }
+
+    async fn response_text(&self, response: reqwest::Response) -> AptosResult<String> {
         if !response.status().is_success() {
             Err(parse_error(response).await)
         } else {
@@ -1341,11 +2035,54 @@ impl Client {
             Err(parse_error(response).await)
         } else {
             let state = parse_state(&response)?;
+            self.pin_chain_id(ChainId::new(state.chain_id))?;
+            if let Some(max_staleness) = self.max_staleness {
+                let context = ResponseContext::from(&state);
+                if context.age() > max_staleness {
+                    return Err(RestError::Stale {
+                        context,
+                        max_staleness,
+                    });
+                }
+            }
 
             Ok((response, state))
         }
     }
 
+    /// Pins `chain_id` if no chain id is pinned yet, or checks it is the pinned one.
+    fn pin_chain_id(&self, chain_id: ChainId) -> AptosResult<()> {
+        let pinned = match &self.chain_id {
+            Some(pinned) => pinned,
+            None => return Ok(()),
+        };
+        // Another response may pin its chain id first, so compare with what ends up pinned
+        let _ = pinned.set(chain_id);
+        match pinned.get() {
+            Some(pinned) if *pinned != chain_id => Err(RestError::ChainIdMismatch {
+                pinned: *pinned,
+                received: chain_id,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `txns` were built for the pinned chain, fetching the chain id of the node if none is
+    /// pinned yet.
+    async fn check_chain_id(&self, txns: &[SignedTransaction]) -> AptosResult<()> {
+        let pinned = match &self.chain_id {
+            Some(pinned) => pinned,
+            None => return Ok(()),
+        };
+        if pinned.get().is_none() {
+            self.get_index_bcs().await?;
+        }
+        for txn in txns {
+            self.pin_chain_id(txn.chain_id())?;
+        }
+        Ok(())
+    }
+
     async fn json<T: serde::de::DeserializeOwned>(
         &self,
         response: reqwest::Response,
@@ -1355,29 +2092,45 @@ impl Client {
         Ok(Response::new(json, state))
     }
 
-    pub async fn health_check(&self, seconds: u64) -> AptosResult<()> {
+    pub async fn health_check(&self, seconds: u64) -> AptosResult<Response<()>> {
         let url = self.build_path("-/healthy")?;
         let response = self
-            .inner
-            .get(url)
-            .query(&[("duration_secs", seconds)])
-            .send()
+            .send(self.inner.get(url).query(&[("duration_secs", seconds)]))
             .await?;
+        let (_, state) = self.check_response(response).await?;
+        Ok(Response::new((), state))
+    }
 
-        if !response.status().is_success() {
-            Err(parse_error(response).await)
-        } else {
-            Ok(())
+    async fn send(&self, request: RequestBuilder) -> AptosResult<reqwest::Response> {
+        self.execute(request.build()?).await
+    }
+
+    async fn execute(&self, mut request: reqwest::Request) -> AptosResult<reqwest::Response> {
+        if let Some(auth) = &self.auth {
+            auth.apply(&mut request)?;
         }
+        Ok(self.inner.execute(request).await?)
     }
 
     async fn get<T: DeserializeOwned>(&self, url: Url) -> AptosResult<Response<T>> {
-        self.json(self.inner.get(url).send().await?).await
+        self.json(self.send(self.inner.get(url)).await?).await
     }
 
     async fn get_bcs(&self, url: Url) -> AptosResult<Response<bytes::Bytes>> {
-        let response = self.inner.get(url).header(ACCEPT, BCS).send().await?;
-        self.check_and_parse_bcs_response(response).await
+        self.download_bcs(self.inner.get(url).header(ACCEPT, BCS))
+            .await
+    }
+
+    /// Sends a GET `request` for a BCS response, downloading it as set by
+    /// [`Client::with_resumable_downloads`].
+    async fn download_bcs(&self, request: RequestBuilder) -> AptosResult<Response<bytes::Bytes>> {
+        match &self.downloads {
+            Some(downloads) => downloads.download(self, request.build()?).await,
+            None => {
+                let response = self.send(request).await?;
+                self.check_and_parse_bcs_response(response).await
+            },
+        }
     }
 
     async fn post_bcs(
@@ -1386,11 +2139,7 @@ impl Client {
         data: serde_json::Value,
     ) -> AptosResult<Response<bytes::Bytes>> {
         let response = self
-            .inner
-            .post(url)
-            .header(ACCEPT, BCS)
-            .json(&data)
-            .send()
+            .send(self.inner.post(url).header(ACCEPT, BCS).json(&data))
             .await?;
         self.check_and_parse_bcs_response(response).await
     }
@@ -1410,8 +2159,7 @@ impl Client {
             request = request.query(&[("limit", limit)])
         }
 
-        let response = request.send().await?;
-        self.check_and_parse_bcs_response(response).await
+        self.download_bcs(request).await
     }
 
     async fn check_and_parse_bcs_response(
@@ -1453,7 +2201,9 @@ impl Client {
                     | RestError::Json(_)
                     | RestError::Timeout(_)
                     | RestError::Unknown(_) => true,
-                    RestError::UrlParse(_) => false,
+                    // The node may catch up
+                    RestError::Stale { .. } => true,
+                    RestError::UrlParse(_) | RestError::ChainIdMismatch { .. } => false,
                 },
             };
 
@@ -1483,7 +2233,9 @@ impl Client {
         ledger_version: Option<u64>,
         cursor: Option<String>,
     ) -> AptosResult<Url> {
-        let mut path = format!("{}?limit={}", base, limit_per_request);
+        // The base may already carry endpoint specific query parameters
+        let separator = if base.contains('?') { '&' } else { '?' };
+        let mut path = format!("{}{}limit={}", base, separator, limit_per_request);
         if let Some(ledger_version) = ledger_version {
             path = format!("{}&ledger_version={}", path, ledger_version);
         }
@@ -1515,7 +2267,7 @@ impl Client {
                 ledger_version,
                 cursor,
             )?;
-            let raw_response = self.inner.get(url).send().await?;
+            let raw_response = self.send(self.inner.get(url)).await?;
             let response: Response<Vec<T>> = self.json(raw_response).await?;
             cursor = response.state().cursor.clone();
             if cursor.is_none() {
@@ -1587,6 +2339,11 @@ impl From<(ReqwestClient, Url)> for Client {
             inner,
             base_url,
             version_path_base: DEFAULT_VERSION_PATH_BASE.to_string(),
+            auth: None,
+            max_staleness: None,
+            chain_id: Some(Arc::new(OnceCell::new())),
+            capabilities: Arc::new(OnceCell::new()),
+            downloads: None,
         }
     }
 }
@@ -1625,9 +2382,405 @@ pub struct GasEstimationParams {
     pub estimated_gas_price: u64,
 }
 
+/// How waiting for a transaction with `Client::wait_for_transaction_outcome` ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TransactionOutcome {
+    /// Committed and executed successfully.
+    Success { version: u64, gas_used: u64 },
+    /// Committed, but aborted by Move code with `code`. `info` has the name and description of
+    /// the error if the module declares it.
+    Aborted {
+        version: u64,
+        gas_used: u64,
+        location: AbortLocation,
+        code: u64,
+        info: Option<AbortInfo>,
+    },
+    /// Committed, but failed for another reason, e.g. running out of gas.
+    Failed {
+        version: u64,
+        gas_used: u64,
+        status: ExecutionStatus,
+    },
+    /// Expired before being committed, so it never will be. `seen_in_mempool` tells whether the
+    /// node ever had it in mempool.
+    Expired { seen_in_mempool: bool },
+    /// Removed from mempool before it expired, e.g. because mempool was full, so it will never be
+    /// committed. Behind a load balancer, not finding a transaction may also mean reaching a node
+    /// which never received it.
+    Evicted,
+}
+
+impl TransactionOutcome {
+    fn from_committed(txn: &TransactionOnChainData) -> Self {
+        let version = txn.version;
+        let gas_used = txn.info.gas_used();
+        match txn.info.status() {
+            ExecutionStatus::Success => TransactionOutcome::Success { version, gas_used },
+            ExecutionStatus::MoveAbort {
+                location,
+                code,
+                info,
+            } => TransactionOutcome::Aborted {
+                version,
+                gas_used,
+                location: location.clone(),
+                code: *code,
+                info: info.clone(),
+            },
+            status => TransactionOutcome::Failed {
+                version,
+                gas_used,
+                status: status.clone(),
+            },
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, TransactionOutcome::Success { .. })
+    }
+
+    /// Whether the transaction was committed, successfully or not.
+    pub fn is_committed(&self) -> bool {
+        matches!(
+            self,
+            TransactionOutcome::Success { .. }
+                | TransactionOutcome::Aborted { .. }
+                | TransactionOutcome::Failed { .. }
+        )
+    }
+}
+
+/// How `Client::wait_for_transaction_end` ended.
+enum WaitForTransactionEnd<T> {
+    Committed(Response<T>),
+    /// Committed, but failed with the VM status.
+    FailedExecution(String, Response<T>),
+    Expired {
+        seen_in_mempool: bool,
+        state: State,
+    },
+    Evicted(State),
+}
+
 enum WaitForTransactionResult<T> {
     NotFound(RestError),
-    FailedExecution(String),
+    FailedExecution(String, Response<T>),
     Pending(State),
     Success(Response<T>),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        client, state_headers, with_error, with_state, with_state_of, CHAIN_ID,
+    };
+    use aptos_api_types::{AptosErrorCode, X_APTOS_LEDGER_TIMESTAMP};
+    use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, Uniform};
+    use aptos_types::{
+        event::{EventHandle, EventKey},
+        transaction::{Script, Transaction, TransactionInfo},
+        write_set::{WriteOp, WriteSet, WriteSetMut},
+    };
+    use httpmock::{
+        Method::{GET, POST},
+        MockServer,
+    };
+    use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+
+    fn pending_transaction(expiration_timestamp_secs: u64) -> PendingTransaction {
+        serde_json::from_value(serde_json::json!({
+            "hash": HashValue::zero().to_hex_literal(),
+            "sender": "0x1",
+            "sequence_number": "0",
+            "max_gas_amount": "1000",
+            "gas_unit_price": "100",
+            "expiration_timestamp_secs": expiration_timestamp_secs.to_string(),
+            "payload": {
+                "type": "entry_function_payload",
+                "function": "0x1::aptos_account::transfer",
+                "type_arguments": [],
+                "arguments": [],
+            },
+        }))
+        .unwrap()
+    }
+
+    /// Waits for the outcome of a transaction which the node at `server` committed at version 5
+    /// with `status`.
+    async fn committed_outcome(status: ExecutionStatus) -> TransactionOutcome {
+        let server = MockServer::start();
+        let txn = TransactionData::OnChain(TransactionOnChainData {
+            version: 5,
+            transaction: Transaction::StateCheckpoint(HashValue::zero()),
+            info: TransactionInfo::new(
+                HashValue::zero(),
+                HashValue::zero(),
+                HashValue::zero(),
+                None,
+                7,
+                status,
+            ),
+            events: vec![],
+            accumulator_root_hash: HashValue::zero(),
+            changes: WriteSet::default(),
+        });
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/transactions/by_hash/");
+            with_state(then, 5).body(bcs::to_bytes(&txn).unwrap());
+        });
+        client(&server)
+            .wait_for_transaction_outcome(&pending_transaction(u32::MAX as u64))
+            .await
+            .unwrap()
+            .into_inner()
+    }
+
+    #[tokio::test]
+    async fn test_committed_outcome() {
+        assert_eq!(
+            committed_outcome(ExecutionStatus::Success).await,
+            TransactionOutcome::Success {
+                version: 5,
+                gas_used: 7,
+            }
+        );
+
+        let location = AbortLocation::Module(ModuleId::new(
+            AccountAddress::ONE,
+            Identifier::new("coin").unwrap(),
+        ));
+        let outcome = committed_outcome(ExecutionStatus::MoveAbort {
+            location: location.clone(),
+            code: 6,
+            info: None,
+        })
+        .await;
+        assert_eq!(outcome, TransactionOutcome::Aborted {
+            version: 5,
+            gas_used: 7,
+            location,
+            code: 6,
+            info: None,
+        });
+        assert!(outcome.is_committed() && !outcome.is_success());
+
+        assert_eq!(
+            committed_outcome(ExecutionStatus::OutOfGas).await,
+            TransactionOutcome::Failed {
+                version: 5,
+                gas_used: 7,
+                status: ExecutionStatus::OutOfGas,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expired_outcome() {
+        // The ledger timestamp of version 2_000_000 is 2_000s, past the expiration
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/transactions/by_hash/");
+            with_error(then, 404, AptosErrorCode::TransactionNotFound, 2_000_000);
+        });
+        let outcome = client(&server)
+            .wait_for_transaction_outcome(&pending_transaction(1))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(outcome, TransactionOutcome::Expired {
+            seen_in_mempool: false,
+        });
+        assert!(!outcome.is_committed());
+    }
+
+    /// Estimates the balance a transfer of 300 octas requires, which the node at `server`
+    /// simulates with `status`, using 10 gas units at 150 octas each.
+    async fn estimate_transfer(status: ExecutionStatus) -> AptosResult<RequiredBalance> {
+        let server = MockServer::start();
+        let sender = AccountAddress::from_hex_literal("0xa11ce").unwrap();
+        let public_key = Ed25519PrivateKey::generate_for_testing().public_key();
+        let payload = TransactionPayload::Script(Script::new(vec![], vec![], vec![]));
+        let event_handle = || EventHandle::new(EventKey::new(0, sender), 0);
+        let coin_store = |coin| {
+            bcs::to_bytes(&CoinStoreResource::new(
+                coin,
+                false,
+                event_handle(),
+                event_handle(),
+            ))
+            .unwrap()
+        };
+
+        let account = AccountResource::new(3, vec![], event_handle(), event_handle());
+        server.mock(|when, then| {
+            when.method(GET).path(format!("/v1/accounts/{}", sender));
+            with_state(then, 4).body(bcs::to_bytes(&account).unwrap());
+        });
+        // The balance before the transaction, at the version it is simulated at
+        server.mock(|when, then| {
+            when.method(GET)
+                .path_contains("/resource/")
+                .query_param("ledger_version", "5");
+            with_state(then, 5).body(coin_store(10_000));
+        });
+        let raw_txn = RawTransaction::new(
+            sender,
+            3,
+            payload.clone(),
+            1_000,
+            150,
+            u64::MAX,
+            ChainId::new(CHAIN_ID),
+        );
+        let state_key = StateKey::access_path(
+            AccessPath::resource_access_path(sender, CoinStoreResource::struct_tag()).unwrap(),
+        );
+        let simulated = TransactionOnChainData {
+            version: 5,
+            transaction: Transaction::UserTransaction(SignedTransaction::new(
+                raw_txn,
+                public_key.clone(),
+                Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+            )),
+            info: TransactionInfo::new(
+                HashValue::zero(),
+                HashValue::zero(),
+                HashValue::zero(),
+                None,
+                10,
+                status,
+            ),
+            events: vec![],
+            accumulator_root_hash: HashValue::zero(),
+            changes: WriteSetMut::new(vec![(
+                state_key,
+                WriteOp::Modification(coin_store(10_000 - 10 * 150 - 300)),
+            )])
+            .freeze()
+            .unwrap(),
+        };
+        server.mock(|when, then| {
+            when.method(POST).path_contains("/transactions/simulate");
+            with_state(then, 5).body(bcs::to_bytes(&simulated).unwrap());
+        });
+
+        client(&server)
+            .estimate_required_balance(sender, &public_key, payload)
+            .await
+            .map(Response::into_inner)
+    }
+
+    #[tokio::test]
+    async fn test_estimate_required_balance() {
+        let required = estimate_transfer(ExecutionStatus::Success).await.unwrap();
+        assert_eq!(required, RequiredBalance {
+            gas_used: 10,
+            gas_unit_price: 150,
+            withdrawn: 300,
+        });
+        assert_eq!(required.gas_fee(), 1_500);
+        assert_eq!(required.total(), 1_800);
+        // The prologue requires the balance to cover the max gas amount
+        assert_eq!(required.with_max_gas_amount(1_000), 150_000);
+        assert_eq!(required.with_max_gas_amount(10), 1_800);
+
+        assert!(estimate_transfer(ExecutionStatus::OutOfGas).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_staleness() {
+        let server = MockServer::start();
+        let read = |client: Client| async move {
+            client
+                .get_account_resource_bytes(AccountAddress::ONE, "0x1::account::Account")
+                .await
+        };
+        // The ledger timestamps of the mock fullnode are from 1970
+        let mut mock = server.mock(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            with_state(then, 5).body(vec![0]);
+        });
+        read(client(&server)).await.unwrap();
+        let bounded = client(&server).with_max_staleness(Duration::from_secs(60));
+        match read(bounded.clone()).await {
+            Err(RestError::Stale {
+                context,
+                max_staleness,
+            }) => {
+                assert_eq!(context.ledger_version, 5);
+                assert_eq!(max_staleness, Duration::from_secs(60));
+            },
+            result => panic!("Expected a stale response, got {:?}", result),
+        }
+        mock.delete();
+
+        let now_usecs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_micros();
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            state_headers(CHAIN_ID, 6)
+                .into_iter()
+                .map(|(name, value)| match name {
+                    X_APTOS_LEDGER_TIMESTAMP => (name, now_usecs.to_string()),
+                    _ => (name, value),
+                })
+                .fold(then.status(200), |then, (name, value)| {
+                    then.header(name, value)
+                })
+                .body(vec![0]);
+        });
+        let response = read(bounded).await.unwrap();
+        assert_eq!(response.context().ledger_version, 6);
+    }
+
+    /// Reads a resource from `client` while `server` is a node of the chain `chain_id`.
+    async fn read_from_chain(
+        server: &MockServer,
+        client: &Client,
+        chain_id: u8,
+    ) -> AptosResult<Response<Vec<u8>>> {
+        let mut mock = server.mock(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            with_state_of(then, chain_id, 1).body(vec![0]);
+        });
+        let result = client
+            .get_account_resource_bytes(AccountAddress::ONE, "0x1::account::Account")
+            .await;
+        mock.delete();
+        result
+    }
+
+    #[tokio::test]
+    async fn test_chain_id_check() {
+        let server = MockServer::start();
+        let checked = client(&server);
+        read_from_chain(&server, &checked, CHAIN_ID).await.unwrap();
+        // Clones share the pinned chain id
+        match read_from_chain(&server, &checked.clone(), CHAIN_ID + 1).await {
+            Err(RestError::ChainIdMismatch { pinned, received }) => {
+                assert_eq!(pinned, ChainId::new(CHAIN_ID));
+                assert_eq!(received, ChainId::new(CHAIN_ID + 1));
+            },
+            result => panic!("Expected a chain id mismatch, got {:?}", result),
+        }
+
+        let pinned = client(&server).with_chain_id(ChainId::new(CHAIN_ID + 1));
+        assert!(matches!(
+            read_from_chain(&server, &pinned, CHAIN_ID).await,
+            Err(RestError::ChainIdMismatch { .. })
+        ));
+
+        // Without the check, the client follows the node across a ledger reset
+        let unchecked = client(&server).without_chain_id_check();
+        read_from_chain(&server, &unchecked, CHAIN_ID)
+            .await
+            .unwrap();
+        read_from_chain(&server, &unchecked, CHAIN_ID + 1)
+            .await
+            .unwrap();
+    }
+}
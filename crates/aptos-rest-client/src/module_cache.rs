@@ -0,0 +1,194 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Bytecode of modules fetched from a fullnode, kept across builds of
+//! [`crate::TypeAccessor`]s.
+
+use crate::Client;
+use anyhow::Result;
+use aptos_crypto::HashValue;
+use aptos_infallible::Mutex;
+use aptos_logger::warn;
+use bytes::Bytes;
+use move_core_types::language_storage::ModuleId;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+/// Called with the id and the new bytecode of a module found to have changed on chain.
+type ChangeCallback = Arc<dyn Fn(&ModuleId, &Bytes) + Send + Sync>;
+
+/// How [`ModuleCache::get`] uses the cached bytecode of a module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ModuleFetchMode {
+    /// Always fetches the module from the fullnode, and updates the cache.
+    Fresh,
+    /// Returns the cached bytecode right away if there is one, and fetches the module again in
+    /// the background. The next call gets the bytecode fetched then. Fetches the module right
+    /// away if it isn't cached.
+    StaleWhileRevalidate,
+}
+
+/// The bytecode of modules fetched from a fullnode, shared by clones of the cache.
+///
+/// When fetching a module shows that it changed since it was cached, e.g. because it was
+/// upgraded, the callback set with [`ModuleCache::on_change`] is invoked, so that long lived
+/// decoders can rebuild their accessor, e.g. with [`crate::SharedTypeAccessor::refresh`].
+#[derive(Clone)]
+pub struct ModuleCache {
+    client: Client,
+    state: Arc<Mutex<CacheState>>,
+    on_change: Option<ChangeCallback>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    modules: HashMap<ModuleId, CachedModule>,
+    /// Modules being fetched in the background, which aren't fetched again meanwhile.
+    revalidating: HashSet<ModuleId>,
+}
+
+struct CachedModule {
+    bytecode: Bytes,
+    hash: HashValue,
+}
+
+impl ModuleCache {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            state: Arc::new(Mutex::new(CacheState::default())),
+            on_change: None,
+        }
+    }
+
+    /// Sets the callback invoked with the new bytecode of each cached module found to have
+    /// changed. With [`ModuleFetchMode::StaleWhileRevalidate`], it is invoked from a background
+    /// task.
+    pub fn on_change(
+        mut self,
+        callback: impl Fn(&ModuleId, &Bytes) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_change = Some(Arc::new(callback));
+        self
+    }
+
+    /// The bytecode of `module_id`, fetched according to `mode`.
+    pub async fn get(&self, module_id: &ModuleId, mode: ModuleFetchMode) -> Result<Bytes> {
+        if mode == ModuleFetchMode::StaleWhileRevalidate {
+            if let Some(bytecode) = self.cached(module_id) {
+                self.revalidate_in_background(module_id);
+                return Ok(bytecode);
+            }
+        }
+        self.fetch(module_id).await
+    }
+
+    /// The cached bytecode of `module_id`, without fetching it.
+    pub fn cached(&self, module_id: &ModuleId) -> Option<Bytes> {
+        self.state
+            .lock()
+            .modules
+            .get(module_id)
+            .map(|module| module.bytecode.clone())
+    }
+
+    async fn fetch(&self, module_id: &ModuleId) -> Result<Bytes> {
+        let bytecode = self
+            .client
+            .get_account_module_bcs(*module_id.address(), module_id.name().as_str())
+            .await?
+            .into_inner();
+        let hash = HashValue::sha3_256_of(&bytecode);
+        let previous = self
+            .state
+            .lock()
+            .modules
+            .insert(module_id.clone(), CachedModule {
+                bytecode: bytecode.clone(),
+                hash,
+            });
+        if let (Some(previous), Some(on_change)) = (previous, &self.on_change) {
+            if previous.hash != hash {
+                on_change(module_id, &bytecode);
+            }
+        }
+        Ok(bytecode)
+    }
+
+    fn revalidate_in_background(&self, module_id: &ModuleId) {
+        if !self.state.lock().revalidating.insert(module_id.clone()) {
+            return;
+        }
+        let cache = self.clone();
+        let module_id = module_id.clone();
+        tokio::spawn(async move {
+            if let Err(err) = cache.fetch(&module_id).await {
+                warn!("Failed to revalidate module {}: {:#}", module_id, err);
+            }
+            cache.state.lock().revalidating.remove(&module_id);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{client, market, with_state};
+    use httpmock::{Method::GET, Mock, MockServer};
+    use std::time::Duration;
+
+    fn mock_module<'a>(server: &'a MockServer, bytecode: &'static [u8]) -> Mock<'a> {
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/module/market");
+            with_state(then, 1).body(bytecode);
+        })
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        let server = MockServer::start();
+        let changes = Arc::new(Mutex::new(vec![]));
+        let changed = changes.clone();
+        let cache = ModuleCache::new(client(&server)).on_change(move |module_id, bytecode| {
+            changed.lock().push((module_id.clone(), bytecode.clone()))
+        });
+
+        let mut v1 = mock_module(&server, b"v1");
+        assert_eq!(cache.cached(&market()), None);
+        // Nothing is cached yet, so it's fetched right away
+        assert_eq!(
+            cache
+                .get(&market(), ModuleFetchMode::StaleWhileRevalidate)
+                .await
+                .unwrap(),
+            "v1"
+        );
+        assert_eq!(cache.cached(&market()), Some(Bytes::from_static(b"v1")));
+        v1.assert_hits(1);
+
+        // After an upgrade, the stale bytecode is returned while the new one is fetched
+        v1.delete();
+        let v2 = mock_module(&server, b"v2");
+        assert_eq!(
+            cache
+                .get(&market(), ModuleFetchMode::StaleWhileRevalidate)
+                .await
+                .unwrap(),
+            "v1"
+        );
+        while cache.cached(&market()) != Some(Bytes::from_static(b"v2")) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(*changes.lock(), vec![(market(), Bytes::from_static(b"v2"))]);
+
+        // Fetching the same bytecode again isn't a change
+        assert_eq!(
+            cache.get(&market(), ModuleFetchMode::Fresh).await.unwrap(),
+            "v2"
+        );
+        v2.assert_hits(2);
+        assert_eq!(changes.lock().len(), 1);
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Packages published on chain, as recorded in `0x1::code::PackageRegistry`, along with the code
+//! of their modules. See [`crate::Client::get_package`].
+
+use anyhow::{bail, ensure, Result};
+use flate2::read::GzDecoder;
+use move_binary_format::{access::ModuleAccess, CompiledModule};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeSet, io::Read};
+
+pub const PACKAGE_REGISTRY_TYPE: &str = "0x1::code::PackageRegistry";
+
+// The types below mirror the ones of `code.move`, so that the registry can be read without
+// depending on the framework.
+
+/// The packages published at an address.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PackageRegistry {
+    pub packages: Vec<PackageMetadata>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PackageMetadata {
+    pub name: String,
+    pub upgrade_policy: UpgradePolicy,
+    pub upgrade_number: u64,
+    /// Hex encoded SHA-256 of the sources and manifest the package was built from.
+    pub source_digest: String,
+    /// The gzipped `Move.toml`.
+    pub manifest: Vec<u8>,
+    pub modules: Vec<ModuleMetadata>,
+    pub deps: Vec<PackageDep>,
+    /// An `Option<Any>`, encoded as a vector of at most one element.
+    pub extension: Vec<Any>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UpgradePolicy {
+    pub policy: u8,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PackageDep {
+    pub account: AccountAddress,
+    pub package_name: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ModuleMetadata {
+    pub name: String,
+    /// The gzipped source, empty if the package was published without sources.
+    pub source: Vec<u8>,
+    /// The gzipped, BCS encoded source map, empty if the package was published without it.
+    pub source_map: Vec<u8>,
+    /// An `Option<Any>`, encoded as a vector of at most one element.
+    pub extension: Vec<Any>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Any {
+    pub type_name: String,
+    pub data: Vec<u8>,
+}
+
+/// A package as published at a single ledger version: its metadata, and the code of each of its
+/// modules in the order of the metadata.
+#[derive(Clone, Debug)]
+pub struct PackageBundle {
+    pub address: AccountAddress,
+    pub metadata: PackageMetadata,
+    pub modules: Vec<PackageModule>,
+    pub source_digest: SourceDigestCheck,
+}
+
+#[derive(Clone, Debug)]
+pub struct PackageModule {
+    pub name: String,
+    pub bytecode: Vec<u8>,
+    pub compiled: CompiledModule,
+    /// The source of the module, if the package was published with sources.
+    pub source: Option<String>,
+}
+
+/// How the sources of a bundle compare to the digest of the registry.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SourceDigestCheck {
+    /// The digest of the manifest and the sources is the one of the registry.
+    Matches,
+    /// The digest differs from the one of the registry. Besides tampering, this happens when the
+    /// package was built with files which aren't published, e.g. scripts.
+    Mismatch { expected: String, actual: String },
+    /// The package was published without its sources.
+    SourcesNotPublished,
+}
+
+impl PackageBundle {
+    /// Checks that `modules` hold the bytecode of the modules of `metadata` at `address`, in the
+    /// same order, and compares the published sources to the digest of the registry.
+    pub fn new(
+        address: AccountAddress,
+        metadata: PackageMetadata,
+        bytecode: Vec<Vec<u8>>,
+    ) -> Result<Self> {
+        ensure!(
+            metadata.modules.len() == bytecode.len(),
+            "Package {} has {} modules, but {} were given",
+            metadata.name,
+            metadata.modules.len(),
+            bytecode.len()
+        );
+        let modules = metadata
+            .modules
+            .iter()
+            .zip(bytecode)
+            .map(|(module, bytecode)| PackageModule::new(address, module, bytecode))
+            .collect::<Result<Vec<_>>>()?;
+        let source_digest = check_source_digest(&metadata, &modules)?;
+        Ok(Self {
+            address,
+            metadata,
+            modules,
+            source_digest,
+        })
+    }
+
+    /// The `Move.toml` of the package.
+    pub fn manifest(&self) -> Result<String> {
+        unzip(&self.metadata.manifest)
+    }
+
+    pub fn compiled_modules(&self) -> impl Iterator<Item = &CompiledModule> {
+        self.modules.iter().map(|module| &module.compiled)
+    }
+}
+
+impl PackageModule {
+    fn new(address: AccountAddress, metadata: &ModuleMetadata, bytecode: Vec<u8>) -> Result<Self> {
+        let compiled = match CompiledModule::deserialize(&bytecode) {
+            Ok(compiled) => compiled,
+            Err(err) => bail!("Invalid bytecode for module {}: {:?}", metadata.name, err),
+        };
+        let expected = ModuleId::new(address, Identifier::new(metadata.name.as_str())?);
+        ensure!(
+            compiled.self_id() == expected,
+            "Expected the bytecode of {}, got the one of {}",
+            expected,
+            compiled.self_id()
+        );
+        let source = if metadata.source.is_empty() {
+            None
+        } else {
+            Some(unzip(&metadata.source)?)
+        };
+        Ok(Self {
+            name: metadata.name.clone(),
+            bytecode,
+            compiled,
+            source,
+        })
+    }
+}
+
+/// Recomputes the digest of the package the way the Move package system does: the SHA-256 of the
+/// sorted, upper case hex encoded SHA-256 of each source file and the manifest. Modules sharing a
+/// source file share their source, so each source is only hashed once.
+fn check_source_digest(
+    metadata: &PackageMetadata,
+    modules: &[PackageModule],
+) -> Result<SourceDigestCheck> {
+    let sources = match modules
+        .iter()
+        .map(|module| module.source.as_deref())
+        .collect::<Option<BTreeSet<_>>>()
+    {
+        Some(sources) if !metadata.manifest.is_empty() => sources,
+        _ => return Ok(SourceDigestCheck::SourcesNotPublished),
+    };
+    let manifest = unzip(&metadata.manifest)?;
+    let mut file_hashes: Vec<_> = sources
+        .into_iter()
+        .chain(std::iter::once(manifest.as_str()))
+        .map(|file| hex::encode_upper(Sha256::digest(file.as_bytes())))
+        .collect();
+    file_hashes.sort();
+    let mut hasher = Sha256::new();
+    for file_hash in file_hashes {
+        hasher.update(file_hash.as_bytes());
+    }
+    let actual = hex::encode_upper(hasher.finalize());
+    Ok(if actual.eq_ignore_ascii_case(&metadata.source_digest) {
+        SourceDigestCheck::Matches
+    } else {
+        SourceDigestCheck::Mismatch {
+            expected: metadata.source_digest.clone(),
+            actual,
+        }
+    })
+}
+
+fn unzip(data: &[u8]) -> Result<String> {
+    let mut unzipped = String::new();
+    GzDecoder::new(data).read_to_string(&mut unzipped)?;
+    Ok(unzipped)
+}
@@ -0,0 +1,347 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Reads issued to several fullnodes in parallel, whose results are compared, for more confidence
+//! in them than trusting a single node gives.
+
+use crate::{error::RestError, AptosResult, Client, Response, State};
+use anyhow::anyhow;
+use aptos_logger::warn;
+use futures::future::join_all;
+use std::future::Future;
+use url::Url;
+
+/// How many versions a node may be behind the most up to date node by default, before its
+/// answers no longer count.
+pub const DEFAULT_MAX_VERSION_SKEW: u64 = 1_000;
+
+/// Issues reads to several fullnodes in parallel, and returns the value a quorum of them agree on.
+///
+/// Nodes serving different ledger versions may legitimately disagree, so disagreement is only
+/// flagged as divergence when it can't be explained by version skew: when the nodes answered at
+/// the same version.
+#[derive(Clone, Debug)]
+pub struct QuorumClient {
+    clients: Vec<Client>,
+    quorum: usize,
+    max_version_skew: u64,
+}
+
+/// The value a quorum of nodes agreed on, along with how the other nodes answered.
+#[derive(Debug)]
+pub struct QuorumResponse<T> {
+    pub value: T,
+    /// The state of the most up to date node of the quorum.
+    pub state: State,
+    /// The nodes which agreed on the value.
+    pub agreeing: Vec<Url>,
+    /// The nodes which answered with another value at the version of a node of the quorum, or at
+    /// the version the read was pinned to.
+    pub divergent: Vec<Url>,
+    /// The nodes more than the maximum version skew behind the most up to date node, whose
+    /// answers weren't counted.
+    pub lagging: Vec<Url>,
+    pub failed: Vec<(Url, RestError)>,
+}
+
+impl QuorumClient {
+    /// Reads from every client, requiring a majority of them to agree.
+    pub fn new(clients: Vec<Client>) -> Self {
+        assert!(
+            !clients.is_empty(),
+            "A quorum client needs at least one node"
+        );
+        let quorum = clients.len() / 2 + 1;
+        Self {
+            clients,
+            quorum,
+            max_version_skew: DEFAULT_MAX_VERSION_SKEW,
+        }
+    }
+
+    /// Requires `quorum` nodes to agree, rather than a majority.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        assert!(
+            quorum > 0 && quorum <= self.clients.len(),
+            "The quorum must be between 1 and the {} nodes",
+            self.clients.len()
+        );
+        self.quorum = quorum;
+        self
+    }
+
+    /// Ignores the answers of nodes more than `versions` behind the most up to date node.
+    pub fn with_max_version_skew(mut self, versions: u64) -> Self {
+        self.max_version_skew = versions;
+        self
+    }
+
+    pub fn clients(&self) -> &[Client] {
+        &self.clients
+    }
+
+    /// Issues `read` to every node in parallel, at each node's latest version, and returns the
+    /// value a quorum of them agree on. Fails if none has a quorum.
+    ///
+    /// For example, `quorum.read(|client| client.get_account_bcs(address))`.
+    pub async fn read<'a, T, F, Fut>(&'a self, read: F) -> AptosResult<QuorumResponse<T>>
+    where
+        T: PartialEq,
+        F: Fn(&'a Client) -> Fut,
+        Fut: Future<Output = AptosResult<Response<T>>>,
+    {
+        let replies = join_all(self.clients.iter().map(read)).await;
+        self.compare(replies, None)
+    }
+
+    /// Issues `read` to every node in parallel, at the latest version every node within the
+    /// maximum version skew has, so that nodes only disagree if they diverged. Fails if none has
+    /// a quorum.
+    ///
+    /// For example, `quorum.read_at_version(|client, version| {
+    /// client.get_account_resource_at_version_bytes(address, resource_type, version) })`.
+    pub async fn read_at_version<'a, T, F, Fut>(&'a self, read: F) -> AptosResult<QuorumResponse<T>>
+    where
+        T: PartialEq,
+        F: Fn(&'a Client, u64) -> Fut,
+        Fut: Future<Output = AptosResult<Response<T>>>,
+    {
+        let states = join_all(
+            self.clients
+                .iter()
+                .map(|client| client.get_ledger_information()),
+        )
+        .await;
+        let versions: Vec<u64> = states
+            .iter()
+            .filter_map(|state| state.as_ref().ok())
+            .map(|state| state.state().version)
+            .collect();
+        let newest = versions.iter().max().copied().ok_or_else(|| {
+            RestError::Unknown(anyhow!("None of the nodes returned its ledger version"))
+        })?;
+        let version = versions
+            .into_iter()
+            .filter(|version| newest - version <= self.max_version_skew)
+            .min()
+            .unwrap_or(newest);
+
+        let replies = join_all(self.clients.iter().map(|client| read(client, version))).await;
+        self.compare(replies, Some(version))
+    }
+
+    fn compare<T: PartialEq>(
+        &self,
+        replies: Vec<AptosResult<Response<T>>>,
+        pinned_version: Option<u64>,
+    ) -> AptosResult<QuorumResponse<T>> {
+        let mut failed = vec![];
+        let mut answers = vec![];
+        for (client, reply) in self.clients.iter().zip(replies) {
+            match reply {
+                Ok(response) => answers.push((client.base_url().clone(), response.into_parts())),
+                Err(err) => failed.push((client.base_url().clone(), err)),
+            }
+        }
+
+        // Responses are stamped with the latest version of the node, rather than the one pinned,
+        // so only lagging nodes at the latest version are set apart
+        let mut lagging = vec![];
+        if pinned_version.is_none() {
+            let newest = answers.iter().map(|(_, (_, state))| state.version).max();
+            if let Some(newest) = newest {
+                answers.retain(|(url, (_, state))| {
+                    let lags = newest - state.version > self.max_version_skew;
+                    if lags {
+                        lagging.push(url.clone());
+                    }
+                    !lags
+                });
+            }
+        }
+
+        let mut groups: Vec<(T, Vec<(Url, State)>)> = vec![];
+        for (url, (value, state)) in answers {
+            match groups.iter_mut().find(|(other, _)| *other == value) {
+                Some((_, nodes)) => nodes.push((url, state)),
+                None => groups.push((value, vec![(url, state)])),
+            }
+        }
+        let largest = groups
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (_, nodes))| nodes.len())
+            .map(|(index, (_, nodes))| (index, nodes.len()));
+        let index = match largest {
+            Some((index, agreeing)) if agreeing >= self.quorum => index,
+            largest => {
+                return Err(RestError::Unknown(anyhow!(
+                    "No quorum: at most {} of {} nodes agreed, {} needed ({} lagging, {} failed)",
+                    largest.map_or(0, |(_, agreeing)| agreeing),
+                    self.clients.len(),
+                    self.quorum,
+                    lagging.len(),
+                    failed.len()
+                )))
+            },
+        };
+        let (value, agreeing) = groups.swap_remove(index);
+
+        let mut divergent = vec![];
+        for (url, state) in groups.into_iter().flat_map(|(_, nodes)| nodes) {
+            if pinned_version.is_some()
+                || agreeing
+                    .iter()
+                    .any(|(_, agreeing)| agreeing.version == state.version)
+            {
+                warn!(
+                    "Node {} diverged from a quorum of {} nodes at version {}",
+                    url,
+                    agreeing.len(),
+                    pinned_version.unwrap_or(state.version)
+                );
+                divergent.push(url);
+            }
+        }
+
+        let state = agreeing
+            .iter()
+            .map(|(_, state)| state)
+            .max_by_key(|state| state.version)
+            .expect("a quorum has at least one node")
+            .clone();
+        Ok(QuorumResponse {
+            value,
+            state,
+            agreeing: agreeing.into_iter().map(|(url, _)| url).collect(),
+            divergent,
+            lagging,
+            failed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::CHAIN_ID;
+
+    fn quorum_client(nodes: usize) -> QuorumClient {
+        QuorumClient::new(
+            (0..nodes)
+                .map(|node| Client::new(format!("http://node{}/", node).parse().unwrap()))
+                .collect(),
+        )
+    }
+
+    fn url(node: usize) -> Url {
+        format!("http://node{}/", node).parse().unwrap()
+    }
+
+    /// The answer `value` of a node at the ledger version `version`.
+    fn reply(value: u64, version: u64) -> AptosResult<Response<u64>> {
+        Ok(Response::new(value, State {
+            chain_id: CHAIN_ID,
+            epoch: 1,
+            version,
+            timestamp_usecs: version * 1_000,
+            oldest_ledger_version: 0,
+            oldest_block_height: 0,
+            block_height: version / 2,
+            cursor: None,
+        }))
+    }
+
+    fn failure() -> AptosResult<Response<u64>> {
+        Err(RestError::Unknown(anyhow!("Connection refused")))
+    }
+
+    #[test]
+    fn test_unanimous() {
+        let response = quorum_client(3)
+            .compare(vec![reply(7, 10), reply(7, 12), reply(7, 11)], None)
+            .unwrap();
+        assert_eq!(response.value, 7);
+        assert_eq!(response.state.version, 12);
+        assert_eq!(response.agreeing, vec![url(0), url(1), url(2)]);
+        assert!(response.divergent.is_empty());
+        assert!(response.lagging.is_empty());
+        assert!(response.failed.is_empty());
+    }
+
+    #[test]
+    fn test_quorum() {
+        // The disagreement of node 2 may be explained by its version
+        let response = quorum_client(3)
+            .compare(vec![reply(7, 10), reply(7, 10), reply(8, 11)], None)
+            .unwrap();
+        assert_eq!(response.value, 7);
+        assert_eq!(response.agreeing, vec![url(0), url(1)]);
+        assert!(response.divergent.is_empty());
+
+        // Node 2 disagrees at the version of node 1
+        let response = quorum_client(3)
+            .compare(vec![reply(7, 10), reply(7, 11), reply(8, 11)], None)
+            .unwrap();
+        assert_eq!(response.divergent, vec![url(2)]);
+
+        // Every node answered at the pinned version
+        let response = quorum_client(3)
+            .compare(vec![reply(7, 10), reply(7, 12), reply(8, 11)], Some(9))
+            .unwrap();
+        assert_eq!(response.divergent, vec![url(2)]);
+
+        // Failed nodes don't count, but don't prevent a quorum either
+        let response = quorum_client(3)
+            .compare(vec![reply(7, 10), failure(), reply(7, 10)], None)
+            .unwrap();
+        assert_eq!(response.agreeing, vec![url(0), url(2)]);
+        assert_eq!(response.failed.len(), 1);
+        assert_eq!(response.failed[0].0, url(1));
+    }
+
+    #[test]
+    fn test_split() {
+        let err = quorum_client(4)
+            .compare(
+                vec![reply(7, 10), reply(7, 10), reply(8, 10), reply(8, 10)],
+                None,
+            )
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("No quorum: at most 2 of 4 nodes agreed, 3 needed"));
+
+        // A smaller quorum settles on one of the values
+        let response = quorum_client(4)
+            .with_quorum(2)
+            .compare(
+                vec![reply(7, 10), reply(7, 10), reply(8, 10), reply(8, 10)],
+                None,
+            )
+            .unwrap();
+        assert_eq!(response.agreeing.len(), 2);
+        assert_eq!(response.divergent.len(), 2);
+
+        let err = quorum_client(3)
+            .compare(vec![failure(), failure(), reply(7, 10)], None)
+            .unwrap_err();
+        assert!(err.to_string().contains("(0 lagging, 2 failed)"));
+    }
+
+    #[test]
+    fn test_lagging_nodes_dont_count() {
+        let quorum = quorum_client(3).with_max_version_skew(5);
+        let response = quorum
+            .compare(vec![reply(7, 20), reply(7, 16), reply(8, 10)], None)
+            .unwrap();
+        assert_eq!(response.agreeing, vec![url(0), url(1)]);
+        assert_eq!(response.lagging, vec![url(2)]);
+        assert!(response.divergent.is_empty());
+
+        let err = quorum
+            .compare(vec![reply(7, 20), reply(8, 10), reply(8, 10)], None)
+            .unwrap_err();
+        assert!(err.to_string().contains("(2 lagging, 0 failed)"));
+    }
+}
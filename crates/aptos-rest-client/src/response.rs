@@ -2,6 +2,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::state::State;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The ledger a response was served from, as stamped by the `X-Aptos-*` headers of every API
+/// response.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResponseContext {
+    pub chain_id: u8,
+    pub epoch: u64,
+    pub ledger_version: u64,
+    pub ledger_timestamp_usecs: u64,
+    pub block_height: u64,
+}
+
+impl ResponseContext {
+    /// How far the ledger timestamp is behind the local clock, zero if it is ahead.
+    pub fn age(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        now.saturating_sub(Duration::from_micros(self.ledger_timestamp_usecs))
+    }
+}
+
+impl From<&State> for ResponseContext {
+    fn from(state: &State) -> Self {
+        Self {
+            chain_id: state.chain_id,
+            epoch: state.epoch,
+            ledger_version: state.version,
+            ledger_timestamp_usecs: state.timestamp_usecs,
+            block_height: state.block_height,
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct Response<T> {
@@ -26,6 +60,10 @@ impl<T> Response<T> {
         &self.state
     }
 
+    pub fn context(&self) -> ResponseContext {
+        ResponseContext::from(&self.state)
+    }
+
     pub fn into_parts(self) -> (T, State) {
         (self.inner, self.state)
     }
@@ -0,0 +1,193 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Changes of a resource, decoded, as polled by [`crate::Client::subscribe_resource`].
+
+use crate::{
+    error::{AptosErrorResponse, RestError},
+    Client, TypeAccessor, TypeAccessorBuilder,
+};
+use anyhow::{anyhow, Result};
+use aptos_api_types::{AptosError, AptosErrorCode, MoveStructTag};
+use move_core_types::{account_address::AccountAddress, language_storage::StructTag};
+use serde_json::Value;
+
+/// A change of a resource, with its values decoded as by [`TypeAccessor::decode_struct`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceDiff {
+    /// The ledger version the new value was seen at. The change happened at or before it, after
+    /// the version of the previous diff.
+    pub version: u64,
+    /// The value before the change, `None` if the resource didn't exist.
+    pub old_value: Option<Value>,
+    /// The value after the change, `None` if the resource was deleted.
+    pub new_value: Option<Value>,
+}
+
+/// The state of a subscription to a resource between polls.
+pub(crate) struct ResourceSubscription {
+    client: Client,
+    address: AccountAddress,
+    struct_tag: StructTag,
+    /// Built from the modules of the resource at the first value to decode.
+    accessor: Option<TypeAccessor>,
+    /// The bytes and the decoded value of the resource at the last poll, if polled yet.
+    last: Option<(Option<Vec<u8>>, Option<Value>)>,
+}
+
+impl ResourceSubscription {
+    pub fn new(client: Client, address: AccountAddress, struct_tag: StructTag) -> Self {
+        Self {
+            client,
+            address,
+            struct_tag,
+            accessor: None,
+            last: None,
+        }
+    }
+
+    /// Reads the resource, returning how it changed since the last poll, if it did. The first
+    /// poll only records the value.
+    pub async fn poll(&mut self) -> Result<Option<ResourceDiff>> {
+        let resource_type = MoveStructTag::from(self.struct_tag.clone()).to_string();
+        let (bytes, version) = match self
+            .client
+            .get_account_resource_bytes(self.address, &resource_type)
+            .await
+        {
+            Ok(response) => {
+                let (bytes, state) = response.into_parts();
+                (Some(bytes), state.version)
+            },
+            Err(RestError::Api(AptosErrorResponse {
+                error:
+                    AptosError {
+                        error_code: AptosErrorCode::ResourceNotFound,
+                        ..
+                    },
+                state,
+                ..
+            })) => {
+                let state = state.ok_or_else(|| {
+                    anyhow!("No ledger version in the response for {}", resource_type)
+                })?;
+                (None, state.version)
+            },
+            Err(err) => return Err(err.into()),
+        };
+
+        if matches!(&self.last, Some((last_bytes, _)) if *last_bytes == bytes) {
+            return Ok(None);
+        }
+        let new_value = match &bytes {
+            Some(bytes) => Some(self.decode(bytes).await?),
+            None => None,
+        };
+        let last = self.last.replace((bytes, new_value.clone()));
+        Ok(last.map(|(_, old_value)| ResourceDiff {
+            version,
+            old_value,
+            new_value,
+        }))
+    }
+
+    async fn decode(&mut self, bytes: &[u8]) -> Result<Value> {
+        if self.accessor.is_none() {
+            let accessor = TypeAccessorBuilder::new(self.client.clone())
+                .add_struct_tag(&self.struct_tag)
+                .build()
+                .await?;
+            self.accessor = Some(accessor);
+        }
+        self.accessor
+            .as_ref()
+            .expect("The accessor was just built")
+            .decode_struct(&self.struct_tag, bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{client, mock_modules, module_bytes, with_error, with_state};
+    use httpmock::{Method::GET, Mock, MockServer};
+    use move_binary_format::file_format::SignatureToken;
+    use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+    use serde_json::json;
+
+    fn cafe() -> AccountAddress {
+        AccountAddress::from_hex_literal("0xcafe").unwrap()
+    }
+
+    /// Serves `0xcafe::orders::Order { id }` as the resource of `0xcafe` at `version`, or a
+    /// resource not found error.
+    fn mock_order(server: &MockServer, id: Option<u64>, version: u64) -> Mock<'_> {
+        server.mock(|when, then| {
+            when.method(GET).path_contains("/resource/");
+            match id {
+                Some(id) => {
+                    with_state(then, version).body(bcs::to_bytes(&id).unwrap());
+                },
+                None => {
+                    with_error(then, 404, AptosErrorCode::ResourceNotFound, version);
+                },
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_poll() {
+        let server = MockServer::start();
+        let orders = ModuleId::new(cafe(), Identifier::new("orders").unwrap());
+        let modules = mock_modules(&server, cafe(), None, vec![(
+            orders.clone(),
+            module_bytes(&orders, &[("Order", &[("id", SignatureToken::U64)])]),
+        )]);
+        let mut subscription = ResourceSubscription::new(client(&server), cafe(), StructTag {
+            address: cafe(),
+            module: orders.name().to_owned(),
+            name: Identifier::new("Order").unwrap(),
+            type_params: vec![],
+        });
+
+        // The first poll is the baseline, even if the resource doesn't exist yet
+        let mut order = mock_order(&server, None, 1);
+        assert_eq!(subscription.poll().await.unwrap(), None);
+
+        order.delete();
+        order = mock_order(&server, Some(1), 2);
+        assert_eq!(
+            subscription.poll().await.unwrap(),
+            Some(ResourceDiff {
+                version: 2,
+                old_value: None,
+                new_value: Some(json!({ "id": "1" })),
+            })
+        );
+        assert_eq!(subscription.poll().await.unwrap(), None);
+
+        order.delete();
+        order = mock_order(&server, Some(2), 5);
+        assert_eq!(
+            subscription.poll().await.unwrap(),
+            Some(ResourceDiff {
+                version: 5,
+                old_value: Some(json!({ "id": "1" })),
+                new_value: Some(json!({ "id": "2" })),
+            })
+        );
+
+        order.delete();
+        mock_order(&server, None, 6);
+        assert_eq!(
+            subscription.poll().await.unwrap(),
+            Some(ResourceDiff {
+                version: 6,
+                old_value: Some(json!({ "id": "2" })),
+                new_value: None,
+            })
+        );
+        // The modules are only fetched to decode the first value
+        modules.assert_hits(1);
+    }
+}
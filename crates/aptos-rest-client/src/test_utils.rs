@@ -0,0 +1,154 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A mock fullnode for the tests of the client, and modules for it to serve.
+
+pub(crate) use crate::testing::{market, market_accessor, market_modules, market_struct};
+use crate::Client;
+use aptos_api_types::{
+    AptosError, AptosErrorCode, MoveModuleId, X_APTOS_BLOCK_HEIGHT, X_APTOS_CHAIN_ID,
+    X_APTOS_EPOCH, X_APTOS_LEDGER_OLDEST_VERSION, X_APTOS_LEDGER_TIMESTAMP, X_APTOS_LEDGER_VERSION,
+    X_APTOS_OLDEST_BLOCK_HEIGHT,
+};
+use httpmock::{Method::GET, Mock, MockServer, Then};
+use move_binary_format::{
+    file_format::{
+        empty_module, AbilitySet, FieldDefinition, IdentifierIndex, ModuleHandleIndex,
+        SignatureToken, StructDefinition, StructFieldInformation, StructHandle, StructHandleIndex,
+        TypeSignature,
+    },
+    CompiledModule,
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+};
+use std::collections::BTreeMap;
+
+/// The chain id of the mock fullnode.
+pub(crate) const CHAIN_ID: u8 = 4;
+
+/// A client of `server`.
+pub(crate) fn client(server: &MockServer) -> Client {
+    Client::new(server.base_url().parse().unwrap())
+}
+
+/// The headers a fullnode sends along every successful response, as of the ledger version
+/// `version` of the chain `chain_id`.
+pub(crate) fn state_headers(chain_id: u8, version: u64) -> Vec<(&'static str, String)> {
+    vec![
+        (X_APTOS_CHAIN_ID, chain_id.to_string()),
+        (X_APTOS_LEDGER_VERSION, version.to_string()),
+        (X_APTOS_LEDGER_OLDEST_VERSION, "0".to_string()),
+        (X_APTOS_LEDGER_TIMESTAMP, (version * 1_000).to_string()),
+        (X_APTOS_EPOCH, "1".to_string()),
+        (X_APTOS_BLOCK_HEIGHT, (version / 2).to_string()),
+        (X_APTOS_OLDEST_BLOCK_HEIGHT, "0".to_string()),
+    ]
+}
+
+/// Responds with a success and the [`state_headers`] of the chain `chain_id`.
+pub(crate) fn with_state_of(then: Then, chain_id: u8, version: u64) -> Then {
+    state_headers(chain_id, version)
+        .into_iter()
+        .fold(then.status(200), |then, (name, value)| {
+            then.header(name, value)
+        })
+}
+
+/// Adds the headers of [`with_state_of`] for the chain of the mock fullnode.
+pub(crate) fn with_state(then: Then, version: u64) -> Then {
+    with_state_of(then, CHAIN_ID, version)
+}
+
+/// Responds with the error `error_code` and the [`state_headers`] of the mock fullnode, as the
+/// API does for errors at a known ledger version, e.g. of resources not found.
+pub(crate) fn with_error(
+    then: Then,
+    status: u16,
+    error_code: AptosErrorCode,
+    version: u64,
+) -> Then {
+    state_headers(CHAIN_ID, version)
+        .into_iter()
+        .fold(then.status(status), |then, (name, value)| {
+            then.header(name, value)
+        })
+        .json_body(
+            serde_json::to_value(AptosError::new_with_error_code(
+                format!("{:?}", error_code),
+                error_code,
+            ))
+            .unwrap(),
+        )
+}
+
+/// Serves `modules` as the modules of `address`, in BCS, at any ledger version or only at
+/// `ledger_version`.
+pub(crate) fn mock_modules<'a>(
+    server: &'a MockServer,
+    address: AccountAddress,
+    ledger_version: Option<u64>,
+    modules: Vec<(ModuleId, Vec<u8>)>,
+) -> Mock<'a> {
+    let modules: BTreeMap<MoveModuleId, Vec<u8>> = modules
+        .into_iter()
+        .map(|(module_id, bytes)| (module_id.into(), bytes))
+        .collect();
+    let body = bcs::to_bytes(&modules).unwrap();
+    server.mock(|when, then| {
+        let when = when
+            .method(GET)
+            .path_contains(format!("/accounts/{}/modules", address));
+        if let Some(version) = ledger_version {
+            when.query_param("ledger_version", version.to_string());
+        }
+        with_state(then, ledger_version.unwrap_or(1)).body(body);
+    })
+}
+
+/// The bytecode of the module `module_id`, declaring each struct of `structs` with the given
+/// fields, and no functions.
+pub(crate) fn module_bytes(
+    module_id: &ModuleId,
+    structs: &[(&str, &[(&str, SignatureToken)])],
+) -> Vec<u8> {
+    let mut module = empty_module();
+    module.address_identifiers[0] = *module_id.address();
+    module.identifiers[0] = module_id.name().to_owned();
+    for (name, fields) in structs {
+        let name = ident(&mut module, name);
+        module.struct_handles.push(StructHandle {
+            module: ModuleHandleIndex(0),
+            name,
+            abilities: AbilitySet::EMPTY,
+            type_parameters: vec![],
+        });
+        let fields = fields
+            .iter()
+            .map(|(name, ty)| FieldDefinition {
+                name: ident(&mut module, name),
+                signature: TypeSignature(ty.clone()),
+            })
+            .collect();
+        module.struct_defs.push(StructDefinition {
+            struct_handle: StructHandleIndex(module.struct_handles.len() as u16 - 1),
+            field_information: StructFieldInformation::Declared(fields),
+        });
+    }
+    let mut bytes = vec![];
+    module.serialize(&mut bytes).unwrap();
+    bytes
+}
+
+/// The index of the identifier `name` in `module`, added if missing.
+fn ident(module: &mut CompiledModule, name: &str) -> IdentifierIndex {
+    let name = Identifier::new(name).unwrap();
+    let index = match module.identifiers.iter().position(|ident| *ident == name) {
+        Some(index) => index,
+        None => {
+            module.identifiers.push(name);
+            module.identifiers.len() - 1
+        },
+    };
+    IdentifierIndex(index as u16)
+}
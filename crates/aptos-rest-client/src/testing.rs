@@ -0,0 +1,198 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Modules for the tests of the client and of crates using it, enabled by the `testing` feature.
+
+use crate::TypeAccessor;
+use aptos_api_types::{
+    MoveAbility, MoveFunction, MoveFunctionGenericTypeParam, MoveFunctionVisibility, MoveModule,
+    MoveStruct, MoveStructField, MoveStructGenericTypeParam, MoveStructTag, MoveType,
+};
+use move_core_types::{
+    ability::Ability,
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, StructTag, TypeTag},
+};
+
+/// `0xcafe::market`.
+pub fn market() -> ModuleId {
+    ModuleId::new(
+        AccountAddress::from_hex_literal("0xcafe").unwrap(),
+        Identifier::new("market").unwrap(),
+    )
+}
+
+/// The struct `name` of `0xcafe::market`, with the type arguments `type_args`.
+pub fn market_struct(name: &str, type_args: Vec<TypeTag>) -> StructTag {
+    StructTag {
+        address: *market().address(),
+        module: market().name().to_owned(),
+        name: Identifier::new(name).unwrap(),
+        type_params: type_args,
+    }
+}
+
+/// The modules `0x1::option`, `0x1::string` and `0xcafe::market`, whose structs are generic and
+/// nest each other:
+///
+/// ```move
+/// module 0xcafe::market {
+///     struct Price has copy, drop, store { amount: u64, currency: String }
+///     struct Wrapper<T> has store { value: T }
+///     struct Listing<T> has key {
+///         id: u64,
+///         items: vector<vector<T>>,
+///         price: Option<Price>,
+///         wrapped: Wrapper<vector<T>>,
+///         seller: address,
+///     }
+///
+///     public entry fun list<T>(
+///         seller: &signer,
+///         items: vector<vector<T>>,
+///         price: Option<u64>,
+///     ) { ... }
+/// }
+/// ```
+pub fn market_modules() -> Vec<MoveModule> {
+    let option = |ty| struct_type(AccountAddress::ONE, "option", "Option", vec![ty]);
+    let string = struct_type(AccountAddress::ONE, "string", "String", vec![]);
+    let vector = |ty| MoveType::Vector {
+        items: Box::new(ty),
+    };
+    let t0 = MoveType::GenericTypeParam { index: 0 };
+    let market_address = *market().address();
+    let market_type = |name, type_args| struct_type(market_address, "market", name, type_args);
+
+    let option_module = move_module(
+        AccountAddress::ONE,
+        "option",
+        vec![move_struct(
+            "Option",
+            &[Ability::Copy, Ability::Drop, Ability::Store],
+            1,
+            vec![("vec", vector(t0.clone()))],
+        )],
+        vec![],
+    );
+    let string_module = move_module(
+        AccountAddress::ONE,
+        "string",
+        vec![move_struct(
+            "String",
+            &[Ability::Copy, Ability::Drop, Ability::Store],
+            0,
+            vec![("bytes", vector(MoveType::U8))],
+        )],
+        vec![],
+    );
+    let market_module = move_module(
+        market_address,
+        "market",
+        vec![
+            move_struct(
+                "Price",
+                &[Ability::Copy, Ability::Drop, Ability::Store],
+                0,
+                vec![("amount", MoveType::U64), ("currency", string)],
+            ),
+            move_struct("Wrapper", &[Ability::Store], 1, vec![("value", t0.clone())]),
+            move_struct("Listing", &[Ability::Key], 1, vec![
+                ("id", MoveType::U64),
+                ("items", vector(vector(t0.clone()))),
+                ("price", option(market_type("Price", vec![]))),
+                ("wrapped", market_type("Wrapper", vec![vector(t0.clone())])),
+                ("seller", MoveType::Address),
+            ]),
+        ],
+        vec![MoveFunction {
+            name: Identifier::new("list").unwrap().into(),
+            visibility: MoveFunctionVisibility::Public,
+            is_entry: true,
+            is_view: false,
+            generic_type_params: vec![MoveFunctionGenericTypeParam {
+                constraints: vec![],
+            }],
+            params: vec![
+                MoveType::Reference {
+                    mutable: false,
+                    to: Box::new(MoveType::Signer),
+                },
+                vector(vector(t0)),
+                option(MoveType::U64),
+            ],
+            return_: vec![],
+        }],
+    );
+    vec![option_module, string_module, market_module]
+}
+
+/// A [`TypeAccessor`] of [`market_modules`], built from `0xcafe::market::Listing<u64>`.
+pub fn market_accessor() -> TypeAccessor {
+    let modules = market_modules();
+    let listing = market_struct("Listing", vec![TypeTag::U64]);
+    TypeAccessor::from_local_modules(&[TypeTag::Struct(Box::new(listing))], |module_id| {
+        modules
+            .iter()
+            .find(|module| {
+                ModuleId::new(module.address.into(), module.name.0.clone()) == *module_id
+            })
+            .cloned()
+    })
+}
+
+fn struct_type(
+    address: AccountAddress,
+    module: &str,
+    name: &str,
+    type_args: Vec<MoveType>,
+) -> MoveType {
+    MoveType::Struct(MoveStructTag::new(
+        address.into(),
+        Identifier::new(module).unwrap().into(),
+        Identifier::new(name).unwrap().into(),
+        type_args,
+    ))
+}
+
+fn move_struct(
+    name: &str,
+    abilities: &[Ability],
+    type_params: usize,
+    fields: Vec<(&str, MoveType)>,
+) -> MoveStruct {
+    MoveStruct {
+        name: Identifier::new(name).unwrap().into(),
+        is_native: false,
+        abilities: abilities.iter().copied().map(MoveAbility::from).collect(),
+        generic_type_params: (0..type_params)
+            .map(|_| MoveStructGenericTypeParam {
+                constraints: vec![],
+                is_phantom: false,
+            })
+            .collect(),
+        fields: fields
+            .into_iter()
+            .map(|(name, typ)| MoveStructField {
+                name: Identifier::new(name).unwrap().into(),
+                typ,
+            })
+            .collect(),
+    }
+}
+
+fn move_module(
+    address: AccountAddress,
+    name: &str,
+    structs: Vec<MoveStruct>,
+    exposed_functions: Vec<MoveFunction>,
+) -> MoveModule {
+    MoveModule {
+        address: address.into(),
+        name: Identifier::new(name).unwrap().into(),
+        friends: vec![],
+        exposed_functions,
+        structs,
+    }
+}
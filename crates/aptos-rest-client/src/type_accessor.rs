@@ -0,0 +1,2806 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Layouts of Move structs and signatures of Move functions resolved from on-chain modules, for
+//! decoding BCS values without a fullnode.
+
+use crate::{
+    codegen::SchemaGenerator,
+    error::{AptosErrorResponse, RestError},
+    module_cache::{ModuleCache, ModuleFetchMode},
+    package::{PackageBundle, PackageRegistry},
+    Client,
+};
+use anyhow::{anyhow, bail, ensure, Result};
+use aptos_api_types::{
+    MoveAbility, MoveModule, MoveStruct, MoveStructGenericTypeParam, MoveStructTag, MoveType,
+};
+use aptos_crypto::HashValue;
+use aptos_logger::info;
+use aptos_state_view::StateView;
+use aptos_types::{
+    access_path::AccessPath, state_store::state_key::StateKey, transaction::TransactionPayload,
+};
+use arc_swap::ArcSwap;
+use futures::stream::{self, Stream, StreamExt, TryStreamExt};
+use move_binary_format::{file_format::AbilitySet, CompiledModule};
+use move_core_types::{
+    account_address::AccountAddress,
+    ident_str,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, StructTag, TypeTag, CORE_CODE_ADDRESS},
+    resolver::ModuleResolver,
+    u256::U256,
+};
+use rayon::prelude::*;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::{hash_map::RandomState, BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Write,
+    fs,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    io,
+    mem::size_of,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// How many fetches a [`TypeAccessorBuilder`] has in flight at most by default.
+pub const DEFAULT_MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// How a [`TypeAccessorBuilder`] retries fetches failing with a transient error: rate limiting,
+/// a server error, a timeout or a connection failure. Other errors, e.g. a module not found,
+/// fail the build right away.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// The delay before the first retry, doubled before each following retry. Each delay is
+    /// shortened by a random jitter of up to half of it, so that concurrent fetches failing at
+    /// once don't retry at once.
+    pub base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// The delay before retry number `retry`, counting from 0.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry.min(31)));
+        // A fresh `RandomState` is randomly seeded, which is all the randomness jitter needs
+        let random = RandomState::new().build_hasher().finish();
+        delay.mul_f64(1.0 - (random as f64 / u64::MAX as f64) / 2.0)
+    }
+}
+
+/// Whether a fetch failing with `err` may succeed if retried.
+fn is_transient(err: &anyhow::Error) -> bool {
+    match err.downcast_ref::<RestError>() {
+        Some(RestError::Api(AptosErrorResponse { status_code, .. }))
+        | Some(RestError::Http(status_code, _)) => {
+            *status_code == StatusCode::TOO_MANY_REQUESTS
+                || *status_code == StatusCode::REQUEST_TIMEOUT
+                || status_code.is_server_error()
+        },
+        Some(RestError::Unknown(err)) => {
+            err.downcast_ref::<reqwest::Error>().map_or(false, |err| {
+                err.is_connect() || err.is_timeout() || err.is_request()
+            })
+        },
+        Some(RestError::Timeout(_)) => true,
+        _ => false,
+    }
+}
+
+/// How decoded `vector<u8>` values are rendered in JSON.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BytesFormat {
+    /// A hex string, e.g. `"0x6170"`, as the REST API renders them.
+    #[default]
+    Hex,
+    /// A string, if the bytes are valid UTF-8, else hex.
+    Utf8,
+    /// A standard base64 string, e.g. `"YXA="`.
+    Base64,
+    /// A string if the bytes are non-empty UTF-8 text without control characters, else hex. Text
+    /// stored as bytes, e.g. names and URIs of modules older than `0x1::string::String`, is then
+    /// readable, while hashes and keys stay hex.
+    Auto,
+}
+
+impl BytesFormat {
+    fn render(self, bytes: &[u8]) -> Value {
+        let text = std::str::from_utf8(bytes).ok();
+        match self {
+            BytesFormat::Hex => json!(format!("0x{}", hex::encode(bytes))),
+            BytesFormat::Base64 => json!(base64::encode(bytes)),
+            BytesFormat::Utf8 => match text {
+                Some(text) => json!(text),
+                None => BytesFormat::Hex.render(bytes),
+            },
+            BytesFormat::Auto => match text {
+                Some(text)
+                    if !text.is_empty()
+                        && text
+                            .chars()
+                            .all(|c| !c.is_control() || c == '\n' || c == '\t') =>
+                {
+                    json!(text)
+                },
+                _ => BytesFormat::Hex.render(bytes),
+            },
+        }
+    }
+}
+
+/// How [`TypeAccessor::decode_struct_with`] renders `vector<u8>` values: in a default format,
+/// except for the fields given a format of their own. The format of a field applies to the
+/// bytes anywhere in its value, e.g. in a `vector<vector<u8>>` or an `Option<vector<u8>>`,
+/// unless a field nested in it has a format of its own.
+#[derive(Clone, Debug, Default)]
+pub struct BytesRendering {
+    default: BytesFormat,
+    fields: HashMap<(ModuleId, Identifier, Identifier), BytesFormat>,
+}
+
+impl BytesRendering {
+    pub fn new(default: BytesFormat) -> Self {
+        Self {
+            default,
+            fields: HashMap::new(),
+        }
+    }
+
+    /// Renders the field `field_name` of the struct `struct_name`, whatever its type arguments,
+    /// in `format`.
+    pub fn field(
+        mut self,
+        module_id: ModuleId,
+        struct_name: Identifier,
+        field_name: Identifier,
+        format: BytesFormat,
+    ) -> Self {
+        self.fields
+            .insert((module_id, struct_name, field_name), format);
+        self
+    }
+
+    /// The format of a field of a struct whose value is rendered in `format`.
+    fn field_format(
+        &self,
+        format: BytesFormat,
+        address: AccountAddress,
+        module: &IdentStr,
+        name: &IdentStr,
+        field_name: &IdentStr,
+    ) -> BytesFormat {
+        // Most renderings have no field formats, which saves building a key for every field
+        if self.fields.is_empty() {
+            return format;
+        }
+        let key = (
+            ModuleId::new(address, module.to_owned()),
+            name.to_owned(),
+            field_name.to_owned(),
+        );
+        self.fields.get(&key).copied().unwrap_or(format)
+    }
+}
+
+/// A module, by address and interned name.
+type ModuleKey = (AccountAddress, IdentIndex);
+/// A struct or function, by address, interned module name and interned name.
+type MemberKey = (AccountAddress, IdentIndex, IdentIndex);
+/// A struct field, by interned name and interned type.
+type Field = (IdentIndex, TypeIndex);
+
+/// Version of the format of [`TypeAccessor::to_bytes`], bumped whenever it changes, so that
+/// accessors saved by other versions of the client are rejected rather than misread.
+const SERIALIZATION_FORMAT_VERSION: u8 = 2;
+
+/// The fields of the structs and the exposed functions of a set of modules, including the modules
+/// their types refer to. Built with a [`TypeAccessorBuilder`].
+///
+/// Identifiers and types are interned, so a type used by many fields and functions, or an
+/// instantiation shared by many modules, is only stored once.
+#[derive(Clone, Debug, Default)]
+pub struct TypeAccessor {
+    arena: TypeArena,
+    modules: HashSet<ModuleKey>,
+    /// Fields of each struct in declaration order.
+    structs: HashMap<MemberKey, Box<[Field]>>,
+    /// Abilities and generic type parameters of each struct.
+    struct_meta: HashMap<MemberKey, StructMeta>,
+    /// Parameter types of each exposed function.
+    functions: HashMap<MemberKey, Box<[TypeIndex]>>,
+}
+
+impl TypeAccessor {
+    /// Decodes the arguments of an entry function payload into JSON values, in the format of the
+    /// REST API, along with the type of each argument. Signer parameters aren't part of the payload
+    /// and are skipped.
+    ///
+    /// Bytecode doesn't keep parameter names, so arguments are named by position: `arg0`,
+    /// `arg1`, ...
+    pub fn decode_entry_function_args(
+        &self,
+        payload: &TransactionPayload,
+    ) -> Result<Vec<(String, MoveType, Value)>> {
+        let entry_function = match payload {
+            TransactionPayload::EntryFunction(entry_function) => entry_function,
+            _ => bail!("Only entry function payloads have entry function arguments"),
+        };
+        let module_id = entry_function.module();
+        let function = self
+            .member_key(module_id, entry_function.function())
+            .and_then(|key| self.functions.get(&key))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Function {}::{} is not known to the type accessor",
+                    module_id,
+                    entry_function.function()
+                )
+            })?;
+        let ty_args: Vec<_> = entry_function
+            .ty_args()
+            .iter()
+            .map(ScopedType::Tag)
+            .collect();
+        let params: Vec<_> = function
+            .iter()
+            .filter(|param| !self.is_signer(**param))
+            .map(|param| ScopedType::Interned {
+                ty: *param,
+                ty_args: &ty_args,
+            })
+            .collect();
+        ensure!(
+            params.len() == entry_function.args().len(),
+            "Function {}::{} takes {} arguments, but the payload has {}",
+            module_id,
+            entry_function.function(),
+            params.len(),
+            entry_function.args().len()
+        );
+
+        params
+            .into_iter()
+            .zip(entry_function.args())
+            .enumerate()
+            .map(|(i, (param, arg))| {
+                let mut reader = BcsReader::new(arg);
+                let value = self.decode_value(
+                    &mut reader,
+                    param,
+                    &BytesRendering::default(),
+                    BytesFormat::Hex,
+                )?;
+                let param = self.to_move_type(param);
+                ensure!(
+                    reader.is_empty(),
+                    "Argument {} has trailing bytes after a {}",
+                    i,
+                    param
+                );
+                Ok((format!("arg{}", i), param, value))
+            })
+            .collect()
+    }
+
+    /// Builds an accessor synchronously from modules `resolve` finds locally, e.g. in the state of
+    /// a test, starting from the modules `types` refer to and following the modules their structs
+    /// and functions refer to. Modules `resolve` doesn't find are left out, so values of their
+    /// structs can't be decoded.
+    pub fn from_local_modules(
+        types: &[TypeTag],
+        mut resolve: impl FnMut(&ModuleId) -> Option<MoveModule>,
+    ) -> Self {
+        let mut accessor = Self::default();
+        let mut to_resolve = BTreeSet::new();
+        for ty in types {
+            collect_modules(&MoveType::from(ty), &mut to_resolve);
+        }
+        let mut unresolved = HashSet::new();
+        while let Some(module_id) = to_resolve.pop_first() {
+            if accessor.contains_module(&module_id) || unresolved.contains(&module_id) {
+                continue;
+            }
+            match resolve(&module_id) {
+                Some(module) => to_resolve.extend(accessor.parse_module(&module)),
+                None => {
+                    unresolved.insert(module_id);
+                },
+            }
+        }
+        accessor
+    }
+
+    /// Builds an accessor synchronously from the modules `resolver` has, e.g. the modules seen in
+    /// an indexer transaction stream, see [`TypeAccessor::from_local_modules`].
+    pub fn from_module_resolver<R: ModuleResolver>(
+        types: &[TypeTag],
+        resolver: &R,
+    ) -> Result<Self> {
+        let mut error = None;
+        let accessor = Self::from_local_modules(types, |module_id| {
+            let module = resolver
+                .get_module(module_id)
+                .map_err(|err| anyhow!("Failed to resolve module {}: {:?}", module_id, err))
+                .and_then(|bytes| {
+                    bytes
+                        .map(|bytes| deserialize_module(module_id, &bytes))
+                        .transpose()
+                });
+            match module {
+                Ok(module) => module,
+                Err(err) => {
+                    error.get_or_insert(err);
+                    None
+                },
+            }
+        });
+        match error {
+            Some(err) => Err(err),
+            None => Ok(accessor),
+        }
+    }
+
+    /// Decodes the BCS bytes of a value of the struct `struct_tag`, e.g. a resource, into JSON, in
+    /// the format of the REST API.
+    pub fn decode_struct(&self, struct_tag: &StructTag, bytes: &[u8]) -> Result<Value> {
+        self.decode_struct_with(struct_tag, bytes, &BytesRendering::default())
+    }
+
+    /// Like [`Self::decode_struct`], with `vector<u8>` values rendered as `rendering` says
+    /// rather than always as hex.
+    pub fn decode_struct_with(
+        &self,
+        struct_tag: &StructTag,
+        bytes: &[u8],
+        rendering: &BytesRendering,
+    ) -> Result<Value> {
+        let ty = TypeTag::Struct(Box::new(struct_tag.clone()));
+        let mut reader = BcsReader::new(bytes);
+        let value = self.decode_value(
+            &mut reader,
+            ScopedType::Tag(&ty),
+            rendering,
+            rendering.default,
+        )?;
+        ensure!(reader.is_empty(), "Trailing bytes after a {}", struct_tag);
+        Ok(value)
+    }
+
+    /// Decodes the BCS bytes of a resource of type `struct_tag` into JSON, the way the REST API
+    /// returns resources: `{"type": ..., "data": ...}`. Nested structs, vectors and options are
+    /// decoded like the REST API decodes them, e.g. an option as `{"vec": [...]}`, so indexers
+    /// can decode resources offline into the JSON a fullnode would have returned.
+    pub fn view_resource(&self, struct_tag: &StructTag, bytes: &[u8]) -> Result<Value> {
+        Ok(json!({
+            "type": MoveStructTag::from(struct_tag.clone()).to_string(),
+            "data": self.decode_struct(struct_tag, bytes)?,
+        }))
+    }
+
+    /// A JSON schema of the JSON [`Self::view_resource`] returns for every struct the accessor
+    /// knows about, with fields named as in Move. See [`SchemaGenerator`] for other naming
+    /// conventions, flattening generics, or TypeScript declarations.
+    pub fn to_json_schema(&self) -> Result<Value> {
+        SchemaGenerator::new(self).json_schema()
+    }
+
+    /// Every struct the accessor knows about, by module and name, in order.
+    pub fn struct_names(&self) -> Vec<(ModuleId, Identifier)> {
+        let mut names: Vec<_> = self
+            .structs
+            .keys()
+            .map(|(address, module_name, name)| {
+                (
+                    ModuleId::new(*address, self.arena.ident(*module_name).to_owned()),
+                    self.arena.ident(*name).to_owned(),
+                )
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// The name and type of each field of the struct `struct_name`, in declaration order. Generic
+    /// type parameters of the struct are left as parameters, see [`Self::instantiate_struct`] to
+    /// substitute them.
+    pub fn struct_field_types(
+        &self,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+    ) -> Option<Vec<(Identifier, MoveType)>> {
+        let fields = self
+            .structs
+            .get(&self.member_key(module_id, struct_name)?)?;
+        Some(
+            fields
+                .iter()
+                .map(|(name, ty)| {
+                    (
+                        self.arena.ident(*name).to_owned(),
+                        self.to_move_type(ScopedType::Interned {
+                            ty: *ty,
+                            ty_args: &[],
+                        }),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// The name and type of each field of the struct `struct_tag`, in declaration order, with the
+    /// generic type parameters of the struct substituted by the type arguments of `struct_tag`
+    /// throughout, e.g. the `coin` field of `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>` is
+    /// a `0x1::coin::Coin<0x1::aptos_coin::AptosCoin>`.
+    pub fn instantiate_struct(
+        &self,
+        struct_tag: &StructTag,
+    ) -> Result<Vec<(Identifier, MoveType)>> {
+        let key = self
+            .member_key(&struct_tag.module_id(), &struct_tag.name)
+            .filter(|key| self.structs.contains_key(key))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Struct {} is not known to the type accessor",
+                    MoveStructTag::from(struct_tag.clone())
+                )
+            })?;
+        let type_params = self.struct_meta[&key].type_params.len();
+        ensure!(
+            struct_tag.type_params.len() == type_params,
+            "Struct {}::{} takes {} type arguments, but {} were given",
+            struct_tag.module_id(),
+            struct_tag.name,
+            type_params,
+            struct_tag.type_params.len()
+        );
+
+        let ty_args: Vec<ScopedType> = struct_tag.type_params.iter().map(ScopedType::Tag).collect();
+        Ok(self.structs[&key]
+            .iter()
+            .map(|(name, ty)| {
+                (
+                    self.arena.ident(*name).to_owned(),
+                    self.to_move_type(ScopedType::Interned {
+                        ty: *ty,
+                        ty_args: &ty_args,
+                    }),
+                )
+            })
+            .collect())
+    }
+
+    /// The type of the field `field_name` of the struct `struct_name`. Generic type parameters of
+    /// the struct are left as parameters.
+    pub fn get_type(
+        &self,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+        field_name: &IdentStr,
+    ) -> Option<MoveType> {
+        let fields = self
+            .structs
+            .get(&self.member_key(module_id, struct_name)?)?;
+        let (_, ty) = self.find_field(fields, field_name.as_str())?;
+        Some(self.to_move_type(ScopedType::Interned {
+            ty: *ty,
+            ty_args: &[],
+        }))
+    }
+
+    /// The type at an access path: a struct followed by a field of it, then a field of that field,
+    /// and so on, e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>.coin.value`. Fields may
+    /// be structs of other modules. Type arguments of the struct may be left out, in which case
+    /// the generic type parameters they stand for are left as parameters.
+    pub fn get_type_at_path(&self, path: &str) -> Result<MoveType> {
+        let mut parts = path.split('.');
+        let struct_tag = parts.next().unwrap_or_default();
+        let struct_tag = StructTag::from_str(struct_tag).map_err(|err| {
+            anyhow!(
+                "Invalid struct {} in access path {}: {}",
+                struct_tag,
+                path,
+                err
+            )
+        })?;
+        let fields: Vec<&str> = parts.collect();
+        let ty = TypeTag::Struct(Box::new(struct_tag));
+        self.field_type(ScopedType::Tag(&ty), &fields)
+    }
+
+    /// The event types the structs of `module_id` hold event handles for, e.g.
+    /// `0x1::coin::DepositEvent` for the `deposit_events` field of `0x1::coin::CoinStore`.
+    /// Handles in structs nested in them are found too, with the type arguments of the fields
+    /// substituted, as long as the accessor knows the modules of the nested structs.
+    ///
+    /// Handles whose event type depends on a type parameter of the struct holding them are
+    /// skipped, as they don't have a single event type.
+    pub fn event_types_for_module(&self, module_id: &ModuleId) -> Result<BTreeSet<StructTag>> {
+        ensure!(
+            self.contains_module(module_id),
+            "Module {} is not known to the type accessor",
+            module_id
+        );
+        let module_name = self.arena.find_ident(module_id.name());
+        let mut names: Vec<&IdentStr> = self
+            .structs
+            .keys()
+            .filter(|(address, module, _)| {
+                address == module_id.address() && Some(*module) == module_name
+            })
+            .map(|(_, _, name)| self.arena.ident(*name))
+            .collect();
+        names.sort();
+
+        let mut events = BTreeSet::new();
+        let mut visited = HashSet::new();
+        for name in names {
+            for (_, ty) in self.struct_field_types(module_id, name).unwrap_or_default() {
+                self.collect_event_types(&ty, &mut events, &mut visited)?;
+            }
+        }
+        Ok(events)
+    }
+
+    /// The abilities of the struct `struct_name`, e.g. whether it has `key` and can be stored as
+    /// a resource, or only `drop` and `store` and is likely only emitted as an event.
+    pub fn struct_abilities(
+        &self,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+    ) -> Option<Vec<MoveAbility>> {
+        let meta = self
+            .struct_meta
+            .get(&self.member_key(module_id, struct_name)?)?;
+        Some(abilities(meta.abilities))
+    }
+
+    /// The generic type parameters of the struct `struct_name`, in declaration order, with the
+    /// abilities each requires of its type argument.
+    pub fn struct_generics(
+        &self,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+    ) -> Option<Vec<MoveStructGenericTypeParam>> {
+        let meta = self
+            .struct_meta
+            .get(&self.member_key(module_id, struct_name)?)?;
+        Some(
+            meta.type_params
+                .iter()
+                .map(|(constraints, is_phantom)| MoveStructGenericTypeParam {
+                    constraints: abilities(*constraints),
+                    is_phantom: *is_phantom,
+                })
+                .collect(),
+        )
+    }
+
+    /// A hash of the layout of the struct `struct_name`: its fields and their types, including the
+    /// layouts of the structs it contains, but not its abilities. It only changes when the layout
+    /// does, so it tells whether a module upgrade affected a struct without comparing types.
+    pub fn struct_layout_hash(
+        &self,
+        module_id: &ModuleId,
+        struct_name: &IdentStr,
+    ) -> Option<HashValue> {
+        let fields = self
+            .structs
+            .get(&self.member_key(module_id, struct_name)?)?;
+        let mut layout = String::new();
+        self.write_fields_layout(fields, &[], &mut layout);
+        Some(HashValue::sha3_256_of(layout.as_bytes()))
+    }
+
+    /// Fetches the modules `module_ids` again with `builder`, e.g. after their package was
+    /// upgraded, and updates the accessor with them, along with any module they newly refer to.
+    /// The other modules aren't fetched again. Fetches go through the client, retry policy and
+    /// module cache of `builder`. If a fetch fails, the accessor is left unchanged.
+    pub async fn refresh(
+        &mut self,
+        builder: TypeAccessorBuilder,
+        module_ids: Vec<ModuleId>,
+    ) -> Result<()> {
+        let builder = module_ids
+            .into_iter()
+            .fold(builder, TypeAccessorBuilder::refresh_module_id);
+        *self = builder.extend(self).await?;
+        Ok(())
+    }
+
+    /// Serializes the accessor, e.g. to save it to a file and load it with
+    /// [`TypeAccessorBuilder::load_from_file`] instead of fetching its modules again. The bytes
+    /// are the same for accessors of the same modules built in the same order.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut modules: Vec<ModuleKey> = self.modules.iter().copied().collect();
+        modules.sort();
+        let mut structs: Vec<(MemberKey, Box<[Field]>, StructMeta)> = self
+            .structs
+            .iter()
+            .map(|(key, fields)| (*key, fields.clone(), self.struct_meta[key].clone()))
+            .collect();
+        structs.sort_by_key(|(key, ..)| *key);
+        let mut functions: Vec<(MemberKey, Box<[TypeIndex]>)> = self
+            .functions
+            .iter()
+            .map(|(key, params)| (*key, params.clone()))
+            .collect();
+        functions.sort_by_key(|(key, _)| *key);
+
+        let serialized = SerializedTypeAccessor {
+            idents: self.arena.idents.clone(),
+            types: self.arena.types.clone(),
+            modules,
+            structs,
+            functions,
+        };
+        let mut bytes = vec![SERIALIZATION_FORMAT_VERSION];
+        bytes.extend(bcs::to_bytes(&serialized)?);
+        Ok(bytes)
+    }
+
+    /// Deserializes an accessor serialized with [`TypeAccessor::to_bytes`]. Fails if it was
+    /// serialized by a version of the client with another format, or if the bytes are corrupted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (version, bytes) = bytes
+            .split_first()
+            .ok_or_else(|| anyhow!("A serialized type accessor can't be empty"))?;
+        ensure!(
+            *version == SERIALIZATION_FORMAT_VERSION,
+            "Type accessor was serialized in format version {}, but only version {} is supported",
+            version,
+            SERIALIZATION_FORMAT_VERSION
+        );
+        let serialized: SerializedTypeAccessor = bcs::from_bytes(bytes)?;
+
+        let arena = TypeArena::from_parts(serialized.idents, serialized.types)?;
+        let check_key = |(_, module, name): &MemberKey| {
+            arena.check_ident(*module)?;
+            arena.check_ident(*name)
+        };
+        let mut accessor = TypeAccessor::default();
+        for (address, name) in serialized.modules {
+            arena.check_ident(name)?;
+            accessor.modules.insert((address, name));
+        }
+        for (key, fields, meta) in serialized.structs {
+            check_key(&key)?;
+            ensure!(
+                AbilitySet::from_u8(meta.abilities).is_some()
+                    && meta
+                        .type_params
+                        .iter()
+                        .all(|(constraints, _)| AbilitySet::from_u8(*constraints).is_some()),
+                "Invalid abilities in serialized type accessor"
+            );
+            for (name, ty) in fields.iter() {
+                arena.check_ident(*name)?;
+                arena.check_type(*ty)?;
+            }
+            accessor.structs.insert(key, fields);
+            accessor.struct_meta.insert(key, meta);
+        }
+        for (key, params) in serialized.functions {
+            check_key(&key)?;
+            for ty in params.iter() {
+                arena.check_type(*ty)?;
+            }
+            accessor.functions.insert(key, params);
+        }
+        accessor.arena = arena;
+        Ok(accessor)
+    }
+
+    /// An estimate of the memory held by the accessor, in bytes. Identifiers and types shared by
+    /// several modules are only counted once.
+    pub fn memory_usage(&self) -> usize {
+        let fields: usize = self.structs.values().map(|fields| fields.len()).sum();
+        let type_params: usize = self
+            .struct_meta
+            .values()
+            .map(|meta| meta.type_params.len())
+            .sum();
+        let params: usize = self.functions.values().map(|params| params.len()).sum();
+        self.arena.memory_usage()
+            + self.modules.len() * size_of::<ModuleKey>()
+            + self.structs.len() * (size_of::<MemberKey>() + size_of::<Box<[Field]>>())
+            + self.struct_meta.len() * (size_of::<MemberKey>() + size_of::<StructMeta>())
+            + type_params * size_of::<(u8, bool)>()
+            + fields * size_of::<Field>()
+            + self.functions.len() * (size_of::<MemberKey>() + size_of::<Box<[TypeIndex]>>())
+            + params * size_of::<TypeIndex>()
+    }
+
+    fn write_fields_layout(&self, fields: &[Field], ty_args: &[ScopedType], layout: &mut String) {
+        layout.push('{');
+        for (name, ty) in fields {
+            write!(layout, "{}:", self.arena.ident(*name)).unwrap();
+            self.write_layout(ScopedType::Interned { ty: *ty, ty_args }, layout);
+            layout.push(',');
+        }
+        layout.push('}');
+    }
+
+    fn write_layout(&self, ty: ScopedType, layout: &mut String) {
+        match self.shape(ty) {
+            Shape::Vector(items) => {
+                layout.push_str("vector<");
+                self.write_layout(items, layout);
+                layout.push('>');
+            },
+            Shape::Struct {
+                address,
+                module,
+                name,
+                ty_args,
+            } => {
+                write!(layout, "{}", self.to_move_type(ty)).unwrap();
+                // Structs of modules which aren't known only contribute their name
+                if let Some(fields) = self.struct_fields(address, module, name) {
+                    self.write_fields_layout(fields, &ty_args, layout);
+                }
+            },
+            _ => write!(layout, "{}", self.to_move_type(ty)).unwrap(),
+        }
+    }
+
+    fn field_type(&self, ty: ScopedType, path: &[&str]) -> Result<MoveType> {
+        let (field_name, rest) = match path.split_first() {
+            Some(split) => split,
+            None => return Ok(self.to_move_type(ty)),
+        };
+        match self.shape(ty) {
+            Shape::Struct {
+                address,
+                module,
+                name,
+                ty_args,
+            } => {
+                let fields = self.struct_fields(address, module, name).ok_or_else(|| {
+                    anyhow!(
+                        "Struct {} is not known to the type accessor",
+                        self.to_move_type(ty)
+                    )
+                })?;
+                let (_, field_ty) = self.find_field(fields, field_name).ok_or_else(|| {
+                    anyhow!(
+                        "Struct {} has no field {}",
+                        self.to_move_type(ty),
+                        field_name
+                    )
+                })?;
+                self.field_type(
+                    ScopedType::Interned {
+                        ty: *field_ty,
+                        ty_args: &ty_args,
+                    },
+                    rest,
+                )
+            },
+            _ => bail!(
+                "Type {} has no field {}, as it isn't a struct",
+                self.to_move_type(ty),
+                field_name
+            ),
+        }
+    }
+
+    /// Adds the event types of the event handles in `ty` to `events`. `visited` holds the
+    /// struct types already walked, so that a struct used by many fields is walked once.
+    fn collect_event_types(
+        &self,
+        ty: &MoveType,
+        events: &mut BTreeSet<StructTag>,
+        visited: &mut HashSet<String>,
+    ) -> Result<()> {
+        match ty {
+            MoveType::Vector { items } => self.collect_event_types(items, events, visited)?,
+            MoveType::Struct(tag) if is_event_handle(tag) => {
+                if let Some(MoveType::Struct(event)) = tag.generic_type_params.first() {
+                    if !has_type_params(&MoveType::Struct(event.clone())) {
+                        events.insert(StructTag::try_from(event.clone())?);
+                    }
+                }
+            },
+            MoveType::Struct(tag) => {
+                if !visited.insert(tag.to_string()) {
+                    return Ok(());
+                }
+                let module_id = ModuleId::new(tag.address.into(), tag.module.0.clone());
+                for (_, field_ty) in self
+                    .struct_field_types(&module_id, &tag.name.0)
+                    .unwrap_or_default()
+                {
+                    let field_ty = substitute_type_params(&field_ty, &tag.generic_type_params);
+                    self.collect_event_types(&field_ty, events, visited)?;
+                }
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn find_field<'a>(&self, fields: &'a [Field], name: &str) -> Option<&'a Field> {
+        fields
+            .iter()
+            .find(|(field_name, _)| self.arena.ident(*field_name).as_str() == name)
+    }
+
+    /// Decodes a value of type `ty`, whose bytes are rendered in `format`.
+    fn decode_value(
+        &self,
+        reader: &mut BcsReader,
+        ty: ScopedType,
+        rendering: &BytesRendering,
+        format: BytesFormat,
+    ) -> Result<Value> {
+        Ok(match self.shape(ty) {
+            Shape::Bool => match reader.read_u8()? {
+                0 => json!(false),
+                1 => json!(true),
+                byte => bail!("Invalid bool {}", byte),
+            },
+            Shape::U8 => json!(reader.read_u8()?),
+            Shape::U16 => json!(u16::from_le_bytes(reader.read_array()?)),
+            Shape::U32 => json!(u32::from_le_bytes(reader.read_array()?)),
+            // Large integers are strings, as JSON numbers can't represent all of them
+            Shape::U64 => json!(u64::from_le_bytes(reader.read_array()?).to_string()),
+            Shape::U128 => json!(u128::from_le_bytes(reader.read_array()?).to_string()),
+            Shape::U256 => json!(U256::from_le_bytes(&reader.read_array()?).to_string()),
+            Shape::Address => {
+                json!(AccountAddress::new(reader.read_array()?).to_hex_literal())
+            },
+            Shape::Vector(items) => {
+                let len = reader.read_uleb128()?;
+                if matches!(self.shape(items), Shape::U8) {
+                    format.render(reader.read_bytes(len)?)
+                } else {
+                    Value::Array(
+                        (0..len)
+                            .map(|_| self.decode_value(reader, items, rendering, format))
+                            .collect::<Result<_>>()?,
+                    )
+                }
+            },
+            Shape::Struct {
+                address,
+                module,
+                name,
+                ..
+            } if is_string(address, module, name) => {
+                let len = reader.read_uleb128()?;
+                json!(String::from_utf8(reader.read_bytes(len)?.to_vec())?)
+            },
+            Shape::Struct {
+                address,
+                module,
+                name,
+                ty_args,
+            } => {
+                let fields = self.struct_fields(address, module, name).ok_or_else(|| {
+                    anyhow!(
+                        "Struct {} is not known to the type accessor",
+                        self.to_move_type(ty)
+                    )
+                })?;
+                let mut object = serde_json::Map::new();
+                for (field_name, field_ty) in fields.iter() {
+                    let field_name = self.arena.ident(*field_name);
+                    let field_format =
+                        rendering.field_format(format, address, module, name, field_name);
+                    let value = self.decode_value(
+                        reader,
+                        ScopedType::Interned {
+                            ty: *field_ty,
+                            ty_args: &ty_args,
+                        },
+                        rendering,
+                        field_format,
+                    )?;
+                    object.insert(field_name.to_string(), value);
+                }
+                Value::Object(object)
+            },
+            Shape::Other => bail!("Values of type {} can't be decoded", self.to_move_type(ty)),
+        })
+    }
+
+    /// What decoding `ty` needs to know about it, with generic type parameters resolved.
+    fn shape<'a>(&'a self, ty: ScopedType<'a>) -> Shape<'a> {
+        match ty {
+            ScopedType::Interned { ty, ty_args } => match self.arena.node(ty) {
+                TypeNode::Bool => Shape::Bool,
+                TypeNode::U8 => Shape::U8,
+                TypeNode::U16 => Shape::U16,
+                TypeNode::U32 => Shape::U32,
+                TypeNode::U64 => Shape::U64,
+                TypeNode::U128 => Shape::U128,
+                TypeNode::U256 => Shape::U256,
+                TypeNode::Address => Shape::Address,
+                TypeNode::Vector(items) => Shape::Vector(ScopedType::Interned {
+                    ty: *items,
+                    ty_args,
+                }),
+                TypeNode::Struct {
+                    address,
+                    module,
+                    name,
+                    type_args,
+                } => Shape::Struct {
+                    address: *address,
+                    module: self.arena.ident(*module),
+                    name: self.arena.ident(*name),
+                    ty_args: type_args
+                        .iter()
+                        .map(|arg| ScopedType::Interned { ty: *arg, ty_args })
+                        .collect(),
+                },
+                TypeNode::GenericTypeParam(index) => match ty_args.get(*index as usize) {
+                    Some(arg) => self.shape(*arg),
+                    None => Shape::Other,
+                },
+                TypeNode::Signer | TypeNode::Reference { .. } | TypeNode::Unparsable(_) => {
+                    Shape::Other
+                },
+            },
+            ScopedType::Tag(tag) => match tag {
+                TypeTag::Bool => Shape::Bool,
+                TypeTag::U8 => Shape::U8,
+                TypeTag::U16 => Shape::U16,
+                TypeTag::U32 => Shape::U32,
+                TypeTag::U64 => Shape::U64,
+                TypeTag::U128 => Shape::U128,
+                TypeTag::U256 => Shape::U256,
+                TypeTag::Address => Shape::Address,
+                TypeTag::Vector(items) => Shape::Vector(ScopedType::Tag(items)),
+                TypeTag::Struct(tag) => Shape::Struct {
+                    address: tag.address,
+                    module: &tag.module,
+                    name: &tag.name,
+                    ty_args: tag.type_params.iter().map(ScopedType::Tag).collect(),
+                },
+                TypeTag::Signer => Shape::Other,
+            },
+        }
+    }
+
+    /// Builds the [`MoveType`] of `ty`, with its generic type parameters substituted.
+    fn to_move_type(&self, ty: ScopedType) -> MoveType {
+        let (ty, ty_args) = match ty {
+            ScopedType::Interned { ty, ty_args } => (ty, ty_args),
+            ScopedType::Tag(tag) => return MoveType::from(tag),
+        };
+        let scoped = |ty: &TypeIndex| ScopedType::Interned { ty: *ty, ty_args };
+        match self.arena.node(ty) {
+            TypeNode::Bool => MoveType::Bool,
+            TypeNode::U8 => MoveType::U8,
+            TypeNode::U16 => MoveType::U16,
+            TypeNode::U32 => MoveType::U32,
+            TypeNode::U64 => MoveType::U64,
+            TypeNode::U128 => MoveType::U128,
+            TypeNode::U256 => MoveType::U256,
+            TypeNode::Address => MoveType::Address,
+            TypeNode::Signer => MoveType::Signer,
+            TypeNode::Vector(items) => MoveType::Vector {
+                items: Box::new(self.to_move_type(scoped(items))),
+            },
+            TypeNode::Struct {
+                address,
+                module,
+                name,
+                type_args,
+            } => MoveType::Struct(MoveStructTag::new(
+                (*address).into(),
+                self.arena.ident(*module).into(),
+                self.arena.ident(*name).into(),
+                type_args
+                    .iter()
+                    .map(|arg| self.to_move_type(scoped(arg)))
+                    .collect(),
+            )),
+            TypeNode::GenericTypeParam(index) => match ty_args.get(*index as usize) {
+                Some(arg) => self.to_move_type(*arg),
+                None => MoveType::GenericTypeParam { index: *index },
+            },
+            TypeNode::Reference { mutable, to } => MoveType::Reference {
+                mutable: *mutable,
+                to: Box::new(self.to_move_type(scoped(to))),
+            },
+            TypeNode::Unparsable(string) => MoveType::Unparsable(string.to_string()),
+        }
+    }
+
+    fn is_signer(&self, ty: TypeIndex) -> bool {
+        match self.arena.node(ty) {
+            TypeNode::Signer => true,
+            TypeNode::Reference { to, .. } => self.is_signer(*to),
+            _ => false,
+        }
+    }
+
+    fn struct_fields(
+        &self,
+        address: AccountAddress,
+        module: &IdentStr,
+        name: &IdentStr,
+    ) -> Option<&[Field]> {
+        let key = (
+            address,
+            self.arena.find_ident(module)?,
+            self.arena.find_ident(name)?,
+        );
+        self.structs.get(&key).map(|fields| &**fields)
+    }
+
+    fn member_key(&self, module_id: &ModuleId, name: &IdentStr) -> Option<MemberKey> {
+        Some((
+            *module_id.address(),
+            self.arena.find_ident(module_id.name())?,
+            self.arena.find_ident(name)?,
+        ))
+    }
+
+    /// Adds the structs and functions of `module`, returning the modules its types refer to. Those
+    /// of an earlier version of the module are replaced, so that the ones an upgrade removed are
+    /// gone.
+    fn parse_module(&mut self, module: &MoveModule) -> BTreeSet<ModuleId> {
+        let module_id = ModuleId::new(module.address.into(), module.name.0.clone());
+        let address = *module_id.address();
+        let module_name = self.arena.intern_ident(&module.name.0);
+        if !self.modules.insert((address, module_name)) {
+            self.remove_members(address, module_name);
+        }
+        let mut referenced = BTreeSet::new();
+
+        for s in &module.structs {
+            let fields = s
+                .fields
+                .iter()
+                .map(|field| {
+                    collect_modules(&field.typ, &mut referenced);
+                    (
+                        self.arena.intern_ident(&field.name.0),
+                        self.arena.intern_type(&field.typ),
+                    )
+                })
+                .collect();
+            let name = self.arena.intern_ident(&s.name.0);
+            self.structs.insert((address, module_name, name), fields);
+            self.struct_meta
+                .insert((address, module_name, name), StructMeta::new(s));
+        }
+
+        for function in &module.exposed_functions {
+            let params = function
+                .params
+                .iter()
+                .map(|param| {
+                    collect_modules(param, &mut referenced);
+                    self.arena.intern_type(param)
+                })
+                .collect();
+            let name = self.arena.intern_ident(&function.name.0);
+            self.functions.insert((address, module_name, name), params);
+        }
+
+        referenced.remove(&module_id);
+        referenced
+    }
+
+    /// Removes the structs and functions of the module `module_name` at `address`. Their types
+    /// stay interned, as other modules may use them.
+    fn remove_members(&mut self, address: AccountAddress, module_name: IdentIndex) {
+        let of_module = |(member_address, member_module, _): &MemberKey| {
+            *member_address == address && *member_module == module_name
+        };
+        self.structs.retain(|key, _| !of_module(key));
+        self.struct_meta.retain(|key, _| !of_module(key));
+        self.functions.retain(|key, _| !of_module(key));
+    }
+
+    fn contains_module(&self, module_id: &ModuleId) -> bool {
+        self.arena
+            .find_ident(module_id.name())
+            .map_or(false, |name| {
+                self.modules.contains(&(*module_id.address(), name))
+            })
+    }
+}
+
+/// Builds a [`TypeAccessor`] from modules given directly or fetched from a fullnode, along with
+/// every module their structs and functions refer to.
+pub struct TypeAccessorBuilder {
+    client: Client,
+    base: Option<TypeAccessor>,
+    modules: Vec<MoveModule>,
+    modules_to_retrieve: BTreeSet<ModuleId>,
+    modules_to_refresh: BTreeSet<ModuleId>,
+    module_memory_budget: Option<usize>,
+    module_cache: Option<(ModuleCache, ModuleFetchMode)>,
+    max_concurrent_fetches: usize,
+    retry_policy: Option<RetryPolicy>,
+    ledger_version: Option<u64>,
+    /// The only addresses whose modules are fetched for being referred to, if set.
+    allowed_addresses: Option<BTreeSet<AccountAddress>>,
+    /// The addresses whose modules are never fetched for being referred to.
+    excluded_addresses: BTreeSet<AccountAddress>,
+}
+
+impl TypeAccessorBuilder {
+    pub fn new(client: Client) -> Self {
+        Self {
+            client,
+            base: None,
+            modules: vec![],
+            modules_to_retrieve: BTreeSet::new(),
+            modules_to_refresh: BTreeSet::new(),
+            module_memory_budget: None,
+            module_cache: None,
+            max_concurrent_fetches: DEFAULT_MAX_CONCURRENT_FETCHES,
+            retry_policy: None,
+            ledger_version: None,
+            allowed_addresses: None,
+            excluded_addresses: BTreeSet::new(),
+        }
+    }
+
+    pub fn add_module(mut self, module: MoveModule) -> Self {
+        self.modules.push(module);
+        self
+    }
+
+    pub fn add_modules(mut self, modules: impl IntoIterator<Item = MoveModule>) -> Self {
+        self.modules.extend(modules);
+        self
+    }
+
+    /// Adds the modules of a package downloaded with [`Client::get_package`], e.g. to decode
+    /// offline.
+    pub fn add_package(self, package: &PackageBundle) -> Self {
+        self.add_modules(package.compiled_modules().cloned().map(MoveModule::from))
+    }
+
+    /// Adds a module from its compiled bytecode, e.g. a `.mv` file of a built package.
+    pub fn add_module_bytes(self, bytes: Vec<u8>) -> Result<Self> {
+        Ok(self.add_module(deserialize_module_bytes(&bytes)?))
+    }
+
+    /// Adds the modules of a Move package built locally, including its dependencies, from the
+    /// bytecode in its `build` directory. `path` is either the directory of the package or its
+    /// `build` directory. Together with [`TypeAccessorBuilder::add_module_bytes`], this builds
+    /// an accessor without any network access, as long as no module is missing.
+    pub fn add_package_dir(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let build_dir = if path.join("build").is_dir() {
+            path.join("build")
+        } else {
+            path.to_path_buf()
+        };
+        // Each package built into the directory has its modules, and those of its dependencies,
+        // under `<package>/bytecode_modules`
+        let mut files = vec![];
+        for entry in read_dir(&build_dir)? {
+            let bytecode_dir = entry.join("bytecode_modules");
+            if bytecode_dir.is_dir() {
+                collect_bytecode_files(&bytecode_dir, &mut files)?;
+            }
+        }
+        ensure!(
+            !files.is_empty(),
+            "No compiled modules in {}, build the package first",
+            build_dir.display()
+        );
+        // Deserializing is what takes time for large packages, e.g. the framework
+        let modules = files
+            .par_iter()
+            .map(|file| {
+                let bytes = fs::read(file)
+                    .map_err(|err| anyhow!("Failed to read module {}: {}", file.display(), err))?;
+                deserialize_module_bytes(&bytes)
+                    .map_err(|err| anyhow!("{}: {}", file.display(), err))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.modules.extend(modules);
+        Ok(self)
+    }
+
+    /// Adds modules read from `state_view`, e.g. of a local database or a restored backup, rather
+    /// than fetched from a fullnode, so that accessors can be built without running the API:
+    ///
+    /// * every module of the packages published at `addresses`, as listed by their
+    ///   `0x1::code::PackageRegistry`,
+    /// * the modules added so far with [`TypeAccessorBuilder::add_module_id`] or
+    ///   [`TypeAccessorBuilder::add_struct_tag`],
+    /// * and every module those and the modules added so far refer to, directly or not, at the
+    ///   addresses the builder follows.
+    ///
+    /// A state view can't list the modules at an address, hence the package registries. Modules
+    /// missing from the state are left to be fetched when building.
+    pub fn add_modules_from_state_view(
+        mut self,
+        state_view: &dyn StateView,
+        addresses: &[AccountAddress],
+    ) -> Result<Self> {
+        let registry_tag = StructTag {
+            address: CORE_CODE_ADDRESS,
+            module: ident_str!("code").to_owned(),
+            name: ident_str!("PackageRegistry").to_owned(),
+            type_params: vec![],
+        };
+        let mut to_read: Vec<ModuleId> = std::mem::take(&mut self.modules_to_retrieve)
+            .into_iter()
+            .collect();
+        for address in addresses {
+            let key = StateKey::access_path(AccessPath::resource_access_path(
+                *address,
+                registry_tag.clone(),
+            )?);
+            if let Some(bytes) = state_view.get_state_value_bytes(&key)? {
+                let registry: PackageRegistry = bcs::from_bytes(&bytes)
+                    .map_err(|err| anyhow!("Invalid package registry at {}: {}", address, err))?;
+                to_read.extend(registry.packages.iter().flat_map(|package| {
+                    package.modules.iter().filter_map(|module| {
+                        Identifier::new(module.name.as_str())
+                            .ok()
+                            .map(|name| ModuleId::new(*address, name))
+                    })
+                }));
+            }
+        }
+        for module in &self.modules {
+            to_read.extend(
+                referenced_modules(module)
+                    .into_iter()
+                    .filter(|module_id| self.follows(module_id.address())),
+            );
+        }
+
+        let mut seen: BTreeSet<ModuleId> = self.modules.iter().map(module_id).collect();
+        while let Some(module_id) = to_read.pop() {
+            if !seen.insert(module_id.clone()) {
+                continue;
+            }
+            let key = StateKey::access_path(AccessPath::code_access_path(module_id.clone()));
+            let bytes = match state_view.get_state_value_bytes(&key)? {
+                Some(bytes) => bytes,
+                None => {
+                    self.modules_to_retrieve.insert(module_id);
+                    continue;
+                },
+            };
+            let module = deserialize_module(&module_id, &bytes)?;
+            to_read.extend(
+                referenced_modules(&module)
+                    .into_iter()
+                    .filter(|module_id| self.follows(module_id.address())),
+            );
+            self.modules.push(module);
+        }
+        Ok(self)
+    }
+
+    /// Fetches `module_id` from the fullnode when building.
+    pub fn add_module_id(mut self, module_id: ModuleId) -> Self {
+        self.modules_to_retrieve.insert(module_id);
+        self
+    }
+
+    /// Fetches the modules of `struct_tag` and of the structs among its type arguments from the
+    /// fullnode when building, so that values of `struct_tag` can be decoded.
+    pub fn add_struct_tag(mut self, struct_tag: &StructTag) -> Self {
+        collect_modules(
+            &MoveType::Struct(struct_tag.clone().into()),
+            &mut self.modules_to_retrieve,
+        );
+        self
+    }
+
+    /// Fetches `module_id` from the fullnode when building, even if the accessor extended has it,
+    /// e.g. because it was upgraded. Bypasses the freshness mode of the module cache.
+    pub fn refresh_module_id(mut self, module_id: ModuleId) -> Self {
+        self.modules_to_refresh.insert(module_id);
+        self
+    }
+
+    /// Fails building if any module grows the accessor by more than `bytes`, as estimated by
+    /// [`TypeAccessor::memory_usage`]. Identifiers and types added by earlier modules are free.
+    pub fn module_memory_budget(mut self, bytes: usize) -> Self {
+        self.module_memory_budget = Some(bytes);
+        self
+    }
+
+    /// Fetches modules through `cache`, according to `mode`, rather than from the fullnode
+    /// directly. With [`ModuleFetchMode::StaleWhileRevalidate`], builds don't wait on the
+    /// fullnode for modules fetched by earlier builds.
+    pub fn module_cache(mut self, cache: ModuleCache, mode: ModuleFetchMode) -> Self {
+        self.module_cache = Some((cache, mode));
+        self
+    }
+
+    /// Has at most `fetches` requests to the fullnode in flight at once while building.
+    pub fn max_concurrent_fetches(mut self, fetches: usize) -> Self {
+        assert!(fetches > 0, "At least one fetch must be allowed");
+        self.max_concurrent_fetches = fetches;
+        self
+    }
+
+    /// Retries fetches failing with a transient error up to `max_retries` times, with an
+    /// exponential backoff from `base_delay`, see [`RetryPolicy`]. By default, any failed fetch
+    /// fails the build.
+    pub fn retry_policy(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.retry_policy = Some(RetryPolicy {
+            max_retries,
+            base_delay,
+        });
+        self
+    }
+
+    /// Fetches modules as they were at the ledger version `version` rather than at the latest
+    /// one, so that e.g. a backfill of historical transactions decodes them with the layouts
+    /// that were live when they ran. The module cache only holds the latest modules, so it isn't
+    /// used, and neither should an accessor built at another version be built on.
+    pub fn ledger_version(mut self, version: u64) -> Self {
+        self.ledger_version = Some(version);
+        self
+    }
+
+    /// Only follows references to modules at `addresses`, e.g. the framework addresses `0x1` to
+    /// `0x4` and the user's own, so that the modules of third parties the user's modules refer to
+    /// aren't fetched, nor those they refer to in turn. Modules added to the builder are fetched
+    /// wherever they are. Types of modules left out can't be decoded.
+    pub fn restrict_to_addresses(mut self, addresses: Vec<AccountAddress>) -> Self {
+        self.allowed_addresses
+            .get_or_insert_with(BTreeSet::new)
+            .extend(addresses);
+        self
+    }
+
+    /// Never follows references to modules at `addresses`, like
+    /// [`TypeAccessorBuilder::restrict_to_addresses`] leaves out the other addresses. Applies on
+    /// top of it.
+    pub fn exclude_addresses(mut self, addresses: Vec<AccountAddress>) -> Self {
+        self.excluded_addresses.extend(addresses);
+        self
+    }
+
+    /// Builds on the accessor saved in the file at `path`, serialized with
+    /// [`TypeAccessor::to_bytes`], so that only modules it doesn't have are fetched. Only applies
+    /// to [`TypeAccessorBuilder::build`].
+    pub fn load_from_file(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .map_err(|err| anyhow!("Failed to read type accessor {}: {}", path.display(), err))?;
+        let accessor = TypeAccessor::from_bytes(&bytes)
+            .map_err(|err| anyhow!("Failed to load type accessor {}: {}", path.display(), err))?;
+        self.base = Some(accessor);
+        Ok(self)
+    }
+
+    pub async fn build(self) -> Result<TypeAccessor> {
+        Ok(self.build_with_report().await?.0)
+    }
+
+    /// Builds like [`TypeAccessorBuilder::build`], along with a report of the dependency walk:
+    /// which modules were fetched and how long it took, which modules refer to which, and the
+    /// cycles among them.
+    pub async fn build_with_report(mut self) -> Result<(TypeAccessor, BuildReport)> {
+        let base = self.base.take().unwrap_or_default();
+        self.build_on(base).await
+    }
+
+    /// Builds on a copy of `accessor`, e.g. to add modules published or upgraded since it was
+    /// built. Modules added to the builder replace the ones of `accessor`.
+    pub async fn extend(self, accessor: &TypeAccessor) -> Result<TypeAccessor> {
+        Ok(self.build_on(accessor.clone()).await?.0)
+    }
+
+    /// Builds like [`TypeAccessorBuilder::build`], but yields each module as soon as it and
+    /// every module it refers to, directly or not, are resolved, so that its structs can be
+    /// decoded while the modules other modules refer to are still being fetched.
+    ///
+    /// Each module comes with a snapshot of the accessor as of when it was resolved, which
+    /// decodes its structs. The snapshot of the last module has every module. A snapshot is
+    /// taken once per round of fetches yielding modules.
+    pub fn build_stream(mut self) -> impl Stream<Item = Result<(ModuleId, ModuleTypes)>> {
+        let base = self.base.take().unwrap_or_default();
+        stream::try_unfold(Crawl::new(self, base), |mut crawl| async move {
+            loop {
+                let done = !crawl.next_round().await?;
+                let (resolved, pending): (Vec<_>, Vec<_>) = std::mem::take(&mut crawl.pending)
+                    .into_iter()
+                    .partition(|module| done || crawl.is_resolved(&module_id(module)));
+                crawl.pending = pending;
+                if !resolved.is_empty() {
+                    let accessor = Arc::new(crawl.accessor.clone());
+                    let items: Vec<Result<_>> = resolved
+                        .iter()
+                        .map(|module| {
+                            Ok((
+                                module_id(module),
+                                ModuleTypes::new(module, accessor.clone()),
+                            ))
+                        })
+                        .collect();
+                    return Ok(Some((stream::iter(items), crawl)));
+                }
+                if done {
+                    return Ok(None);
+                }
+            }
+        })
+        .try_flatten()
+    }
+
+    async fn build_on(self, accessor: TypeAccessor) -> Result<(TypeAccessor, BuildReport)> {
+        let mut crawl = Crawl::new(self, accessor);
+        while crawl.next_round().await? {
+            crawl.pending.clear();
+        }
+        let mut report = crawl.report;
+        report.dependencies = crawl.references.into_iter().collect();
+        report.cycles = find_cycles(&report.dependencies);
+        Ok((crawl.accessor, report))
+    }
+
+    fn parse_module(
+        &self,
+        accessor: &mut TypeAccessor,
+        module: &MoveModule,
+    ) -> Result<BTreeSet<ModuleId>> {
+        let before = accessor.memory_usage();
+        let referenced = accessor.parse_module(module);
+        if let Some(budget) = self.module_memory_budget {
+            let used = accessor.memory_usage() - before;
+            ensure!(
+                used <= budget,
+                "Module {}::{} takes {} bytes, over the budget of {} bytes per module",
+                module.address,
+                module.name,
+                used,
+                budget
+            );
+        }
+        Ok(referenced)
+    }
+
+    /// Whether to fetch the modules at `address` that other modules refer to.
+    fn follows(&self, address: &AccountAddress) -> bool {
+        !self.excluded_addresses.contains(address)
+            && self
+                .allowed_addresses
+                .as_ref()
+                .map_or(true, |allowed| allowed.contains(address))
+    }
+
+    /// Runs `fetch`, retrying it according to the retry policy while it fails with a transient
+    /// error.
+    async fn with_retries<T, F, Fut>(&self, fetch: F) -> Result<T>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut retries = 0;
+        loop {
+            match (fetch().await, &self.retry_policy) {
+                (Err(err), Some(policy)) if retries < policy.max_retries && is_transient(&err) => {
+                    let delay = policy.delay(retries);
+                    info!(
+                        "Failed to fetch modules, retrying in {}ms: {}",
+                        delay.as_millis(),
+                        err
+                    );
+                    tokio::time::sleep(delay).await;
+                    retries += 1;
+                },
+                (result, _) => return result,
+            }
+        }
+    }
+
+    /// Retrieves `module_ids` concurrently, fetching them from the fullnode rather than taking
+    /// them from the module cache if `fresh` is set, along with how long fetching each took.
+    /// Without a module cache, or at a ledger version, all the modules of an account are fetched
+    /// in a single batch, and kept in `accounts` for the modules of later rounds, each module
+    /// taking as long as its batch.
+    async fn retrieve_modules(
+        &self,
+        module_ids: &BTreeSet<ModuleId>,
+        accounts: &mut HashMap<AccountAddress, (HashMap<ModuleId, Vec<u8>>, Duration)>,
+        fresh: bool,
+    ) -> Result<Vec<(MoveModule, Duration)>> {
+        if let (Some((cache, mode)), None) = (&self.module_cache, self.ledger_version) {
+            let mode = if fresh { ModuleFetchMode::Fresh } else { *mode };
+            let fetches = module_ids.iter().map(|module_id| async move {
+                let start = Instant::now();
+                let bytes = self
+                    .with_retries(|| async move { cache.get(module_id, mode).await })
+                    .await?;
+                Ok::<_, anyhow::Error>((module_id.clone(), bytes, start.elapsed()))
+            });
+            let fetched = stream::iter(fetches)
+                .buffered(self.max_concurrent_fetches)
+                .try_collect()
+                .await?;
+            return deserialize_modules(fetched).await;
+        }
+
+        let addresses: BTreeSet<_> = module_ids
+            .iter()
+            .map(|module_id| *module_id.address())
+            .filter(|address| !accounts.contains_key(address))
+            .collect();
+        let fetches = addresses.into_iter().map(|address| async move {
+            let start = Instant::now();
+            let modules = self
+                .with_retries(|| async move {
+                    Ok(match self.ledger_version {
+                        Some(version) => {
+                            self.client
+                                .get_account_modules_bcs_at_version(address, version)
+                                .await?
+                        },
+                        None => self.client.get_account_modules_bcs(address).await?,
+                    })
+                })
+                .await?
+                .into_inner()
+                .into_iter()
+                .map(|(module_id, bytes)| (module_id.into(), bytes))
+                .collect();
+            Ok::<_, anyhow::Error>((address, (modules, start.elapsed())))
+        });
+        let fetched: Vec<_> = stream::iter(fetches)
+            .buffer_unordered(self.max_concurrent_fetches)
+            .try_collect()
+            .await?;
+        accounts.extend(fetched);
+
+        let modules = module_ids
+            .iter()
+            .map(|module_id| {
+                let (modules, latency) = accounts
+                    .get(module_id.address())
+                    .ok_or_else(|| anyhow!("Module {} not found on the fullnode", module_id))?;
+                let bytes = modules
+                    .get(module_id)
+                    .ok_or_else(|| anyhow!("Module {} not found on the fullnode", module_id))?;
+                Ok((module_id.clone(), bytes.clone(), *latency))
+            })
+            .collect::<Result<_>>()?;
+        deserialize_modules(modules).await
+    }
+}
+
+/// The types a module declares, yielded by [`TypeAccessorBuilder::build_stream`].
+#[derive(Clone, Debug)]
+pub struct ModuleTypes {
+    /// The name and type of each field of each struct, in declaration order. Generic type
+    /// parameters of the structs are left as parameters.
+    pub structs: BTreeMap<Identifier, Vec<(Identifier, MoveType)>>,
+    /// The parameter types of each exposed function.
+    pub functions: BTreeMap<Identifier, Vec<MoveType>>,
+    /// The accessor as of when the module was resolved, which decodes its structs.
+    pub accessor: Arc<TypeAccessor>,
+}
+
+impl ModuleTypes {
+    fn new(module: &MoveModule, accessor: Arc<TypeAccessor>) -> Self {
+        Self {
+            structs: module
+                .structs
+                .iter()
+                .map(|s| {
+                    let fields = s
+                        .fields
+                        .iter()
+                        .map(|field| (field.name.0.clone(), field.typ.clone()))
+                        .collect();
+                    (s.name.0.clone(), fields)
+                })
+                .collect(),
+            functions: module
+                .exposed_functions
+                .iter()
+                .map(|function| (function.name.0.clone(), function.params.clone()))
+                .collect(),
+            accessor,
+        }
+    }
+}
+
+/// What a [`TypeAccessorBuilder`] did to build an accessor, returned by
+/// [`TypeAccessorBuilder::build_with_report`], e.g. to find out why a build fetched many modules
+/// or was slow.
+#[derive(Clone, Debug, Default)]
+pub struct BuildReport {
+    /// The modules fetched, from the fullnode or the module cache, in the order they were
+    /// parsed. Modules given to the builder, or which the accessor built on already had, aren't.
+    pub modules_fetched: Vec<ModuleId>,
+    /// How long fetching each module took, including retries. Modules fetched in a batch with
+    /// the other modules of their account each take as long as the batch.
+    pub fetch_latencies: BTreeMap<ModuleId, Duration>,
+    /// The modules each module parsed by the build refers to through the types of its structs
+    /// and functions.
+    pub dependencies: BTreeMap<ModuleId, BTreeSet<ModuleId>>,
+    /// The groups of modules referring to each other, directly or not, each sorted. Modules
+    /// published on chain can't, but modules given to the builder may.
+    pub cycles: Vec<Vec<ModuleId>>,
+    /// How many rounds of fetches the build took, i.e. the depth of the dependency walk.
+    pub rounds: usize,
+}
+
+/// A build in progress: the modules given to the builder are parsed first, then the modules they
+/// refer to are retrieved in rounds, each one retrieving the modules the modules of the previous
+/// one refer to at once. Modules to refresh are retrieved in the first round, whether the
+/// accessor has them or not.
+struct Crawl {
+    builder: TypeAccessorBuilder,
+    accessor: TypeAccessor,
+    started: bool,
+    to_retrieve: BTreeSet<ModuleId>,
+    accounts: HashMap<AccountAddress, (HashMap<ModuleId, Vec<u8>>, Duration)>,
+    /// The modules each module parsed by the build refers to.
+    references: HashMap<ModuleId, BTreeSet<ModuleId>>,
+    /// The modules parsed by the build which weren't handed out yet.
+    pending: Vec<MoveModule>,
+    report: BuildReport,
+}
+
+impl Crawl {
+    fn new(builder: TypeAccessorBuilder, accessor: TypeAccessor) -> Self {
+        Self {
+            builder,
+            accessor,
+            started: false,
+            to_retrieve: BTreeSet::new(),
+            accounts: HashMap::new(),
+            references: HashMap::new(),
+            pending: vec![],
+            report: BuildReport::default(),
+        }
+    }
+
+    /// Runs the next round, adding the modules it parsed to `pending`. Returns false once every
+    /// module is resolved.
+    async fn next_round(&mut self) -> Result<bool> {
+        let (mut modules, fetched) = if !self.started {
+            self.started = true;
+            self.to_retrieve = self.builder.modules_to_retrieve.clone();
+            let modules = std::mem::take(&mut self.builder.modules);
+            let fetched = if self.builder.modules_to_refresh.is_empty() {
+                vec![]
+            } else {
+                self.builder
+                    .retrieve_modules(&self.builder.modules_to_refresh, &mut self.accounts, true)
+                    .await?
+            };
+            (modules, fetched)
+        } else {
+            let accessor = &self.accessor;
+            self.to_retrieve
+                .retain(|module_id| !accessor.contains_module(module_id));
+            if self.to_retrieve.is_empty() {
+                return Ok(false);
+            }
+            let fetched = self
+                .builder
+                .retrieve_modules(&self.to_retrieve, &mut self.accounts, false)
+                .await?;
+            self.to_retrieve.clear();
+            (vec![], fetched)
+        };
+        if !fetched.is_empty() {
+            self.report.rounds += 1;
+        }
+        for (module, latency) in fetched {
+            let module_id = module_id(&module);
+            self.report
+                .fetch_latencies
+                .insert(module_id.clone(), latency);
+            self.report.modules_fetched.push(module_id);
+            modules.push(module);
+        }
+        for module in modules {
+            let referenced = self.builder.parse_module(&mut self.accessor, &module)?;
+            let builder = &self.builder;
+            self.to_retrieve.extend(
+                referenced
+                    .iter()
+                    .filter(|module_id| builder.follows(module_id.address()))
+                    .cloned(),
+            );
+            self.references.insert(module_id(&module), referenced);
+            self.pending.push(module);
+        }
+        Ok(true)
+    }
+
+    /// Whether `module_id` and the modules it refers to, directly or not, are all resolved.
+    /// Modules the accessor had before the build are resolved along with what they refer to, and
+    /// modules the builder doesn't follow references to never will be.
+    fn is_resolved(&self, module_id: &ModuleId) -> bool {
+        let mut to_visit = vec![module_id.clone()];
+        let mut visited = HashSet::new();
+        while let Some(module_id) = to_visit.pop() {
+            if !visited.insert(module_id.clone()) {
+                continue;
+            }
+            match self.references.get(&module_id) {
+                Some(referenced) => to_visit.extend(referenced.iter().cloned()),
+                None if self.accessor.contains_module(&module_id) => {},
+                None if !self.builder.follows(module_id.address()) => {},
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+/// The strongly connected components of more than one module of `dependencies`, found with
+/// Tarjan's algorithm.
+fn find_cycles(dependencies: &BTreeMap<ModuleId, BTreeSet<ModuleId>>) -> Vec<Vec<ModuleId>> {
+    struct Tarjan<'a> {
+        dependencies: &'a BTreeMap<ModuleId, BTreeSet<ModuleId>>,
+        next_index: usize,
+        /// The order each module was visited in.
+        indices: HashMap<&'a ModuleId, usize>,
+        stack: Vec<&'a ModuleId>,
+        on_stack: HashSet<&'a ModuleId>,
+        cycles: Vec<Vec<ModuleId>>,
+    }
+
+    impl<'a> Tarjan<'a> {
+        fn visit(&mut self, module_id: &'a ModuleId) -> usize {
+            let index = self.next_index;
+            self.next_index += 1;
+            self.indices.insert(module_id, index);
+            self.stack.push(module_id);
+            self.on_stack.insert(module_id);
+
+            let dependencies = self.dependencies;
+            let mut low = index;
+            for referenced in dependencies.get(module_id).into_iter().flatten() {
+                match self.indices.get(referenced) {
+                    None => low = low.min(self.visit(referenced)),
+                    Some(referenced_index) if self.on_stack.contains(referenced) => {
+                        low = low.min(*referenced_index)
+                    },
+                    Some(_) => {},
+                }
+            }
+
+            if low == index {
+                let mut component = vec![];
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack.remove(member);
+                    component.push(member.clone());
+                    if member == module_id {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    component.sort();
+                    self.cycles.push(component);
+                }
+            }
+            low
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        dependencies,
+        next_index: 0,
+        indices: HashMap::new(),
+        stack: vec![],
+        on_stack: HashSet::new(),
+        cycles: vec![],
+    };
+    for module_id in dependencies.keys() {
+        if !tarjan.indices.contains_key(module_id) {
+            tarjan.visit(module_id);
+        }
+    }
+    tarjan.cycles
+}
+
+fn module_id(module: &MoveModule) -> ModuleId {
+    ModuleId::new(module.address.into(), module.name.0.clone())
+}
+
+/// The entries of the directory `path`, sorted so that builds don't depend on the file system.
+fn read_dir(path: &Path) -> Result<Vec<PathBuf>> {
+    let mut entries = fs::read_dir(path)
+        .and_then(|entries| {
+            entries
+                .map(|entry| Ok(entry?.path()))
+                .collect::<io::Result<Vec<_>>>()
+        })
+        .map_err(|err| anyhow!("Failed to read directory {}: {}", path.display(), err))?;
+    entries.sort();
+    Ok(entries)
+}
+
+/// Collects the `.mv` files in `dir` and its subdirectories.
+fn collect_bytecode_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in read_dir(dir)? {
+        if entry.is_dir() {
+            collect_bytecode_files(&entry, files)?;
+        } else if entry
+            .extension()
+            .map_or(false, |extension| extension == "mv")
+        {
+            files.push(entry);
+        }
+    }
+    Ok(())
+}
+
+fn deserialize_module(module_id: &ModuleId, bytes: &[u8]) -> Result<MoveModule> {
+    let module = CompiledModule::deserialize(bytes)
+        .map_err(|err| anyhow!("Invalid bytecode for module {}: {:?}", module_id, err))?;
+    Ok(MoveModule::from(module))
+}
+
+/// Deserializes a module whose id is only known from its bytecode, e.g. one read from a file.
+fn deserialize_module_bytes(bytes: &[u8]) -> Result<MoveModule> {
+    let module = CompiledModule::deserialize(bytes)
+        .map_err(|err| anyhow!("Invalid module bytecode: {:?}", err))?;
+    Ok(MoveModule::from(module))
+}
+
+/// Deserializes fetched modules in parallel, off the async runtime, keeping them in order so that
+/// builds are deterministic. Deserializing and converting the modules takes most of the time of
+/// parsing them, interning their types into the accessor is comparatively cheap.
+async fn deserialize_modules(
+    modules: Vec<(ModuleId, Vec<u8>, Duration)>,
+) -> Result<Vec<(MoveModule, Duration)>> {
+    tokio::task::spawn_blocking(move || {
+        modules
+            .into_par_iter()
+            .map(|(module_id, bytes, latency)| {
+                Ok((deserialize_module(&module_id, &bytes)?, latency))
+            })
+            .collect()
+    })
+    .await?
+}
+
+/// A [`TypeAccessor`] shared by concurrent decoders, which can be refreshed while they decode.
+///
+/// Readers take a snapshot of the current accessor without locking. A refresh builds a new
+/// accessor from a copy of the current one and swaps it in, so readers never wait on it, and keep
+/// the accessor they took until they drop it.
+pub struct SharedTypeAccessor {
+    current: ArcSwap<TypeAccessor>,
+    /// Serializes refreshes, so that none loses the modules added by another.
+    refresh: tokio::sync::Mutex<()>,
+}
+
+impl SharedTypeAccessor {
+    pub fn new(accessor: TypeAccessor) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(accessor),
+            refresh: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The current accessor.
+    pub fn snapshot(&self) -> Arc<TypeAccessor> {
+        self.current.load_full()
+    }
+
+    /// Adds the modules of `builder` to the accessor, returning the new snapshot. If building
+    /// fails, the current accessor is kept.
+    pub async fn refresh(&self, builder: TypeAccessorBuilder) -> Result<Arc<TypeAccessor>> {
+        let _guard = self.refresh.lock().await;
+        let accessor = Arc::new(builder.extend(&self.current.load_full()).await?);
+        self.current.store(accessor.clone());
+        Ok(accessor)
+    }
+}
+
+impl From<TypeAccessor> for SharedTypeAccessor {
+    fn from(accessor: TypeAccessor) -> Self {
+        Self::new(accessor)
+    }
+}
+
+/// The form [`TypeAccessor::to_bytes`] serializes an accessor in. Interning maps aren't
+/// serialized, but rebuilt when deserializing.
+#[derive(Deserialize, Serialize)]
+struct SerializedTypeAccessor {
+    idents: Vec<Identifier>,
+    types: Vec<TypeNode>,
+    modules: Vec<ModuleKey>,
+    structs: Vec<(MemberKey, Box<[Field]>, StructMeta)>,
+    functions: Vec<(MemberKey, Box<[TypeIndex]>)>,
+}
+
+/// The abilities of a struct and its generic type parameters, as the bits of an [`AbilitySet`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct StructMeta {
+    abilities: u8,
+    /// The ability constraints of each generic type parameter, and whether it's phantom.
+    type_params: Box<[(u8, bool)]>,
+}
+
+impl StructMeta {
+    fn new(s: &MoveStruct) -> Self {
+        let ability_bits = |abilities: &[MoveAbility]| {
+            abilities
+                .iter()
+                .fold(AbilitySet::EMPTY, |set, ability| set | ability.0)
+                .into_u8()
+        };
+        Self {
+            abilities: ability_bits(&s.abilities),
+            type_params: s
+                .generic_type_params
+                .iter()
+                .map(|param| (ability_bits(&param.constraints), param.is_phantom))
+                .collect(),
+        }
+    }
+}
+
+/// The abilities of the valid [`AbilitySet`] bits `bits`.
+fn abilities(bits: u8) -> Vec<MoveAbility> {
+    AbilitySet::from_u8(bits)
+        .expect("Ability bits are validated")
+        .into_iter()
+        .map(MoveAbility::from)
+        .collect()
+}
+
+/// Index of an identifier in a [`TypeArena`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+struct IdentIndex(u32);
+
+/// Index of a type in a [`TypeArena`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+struct TypeIndex(u32);
+
+/// A [`MoveType`] whose identifiers and inner types are interned.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+enum TypeNode {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(TypeIndex),
+    Struct {
+        address: AccountAddress,
+        module: IdentIndex,
+        name: IdentIndex,
+        type_args: Box<[TypeIndex]>,
+    },
+    GenericTypeParam(u16),
+    Reference {
+        mutable: bool,
+        to: TypeIndex,
+    },
+    Unparsable(Box<str>),
+}
+
+/// Identifiers and types of a [`TypeAccessor`], each stored once however many modules use it.
+#[derive(Clone, Debug, Default)]
+struct TypeArena {
+    idents: Vec<Identifier>,
+    ident_indices: HashMap<Identifier, IdentIndex>,
+    types: Vec<TypeNode>,
+    type_indices: HashMap<TypeNode, TypeIndex>,
+}
+
+impl TypeArena {
+    /// Rebuilds an arena from its identifiers and types, checking that they refer to each other
+    /// consistently: every type only refers to identifiers of the arena and to types before it,
+    /// and nothing is stored twice.
+    fn from_parts(idents: Vec<Identifier>, types: Vec<TypeNode>) -> Result<Self> {
+        let mut arena = TypeArena::default();
+        for ident in idents {
+            let index = IdentIndex(arena.idents.len() as u32);
+            ensure!(
+                arena.ident_indices.insert(ident.clone(), index).is_none(),
+                "Identifier {} is interned twice",
+                ident
+            );
+            arena.idents.push(ident);
+        }
+        for node in types {
+            let index = TypeIndex(arena.types.len() as u32);
+            let check_inner = |ty: &TypeIndex| {
+                ensure!(
+                    ty.0 < index.0,
+                    "Type {} refers to type {}, which doesn't precede it",
+                    index.0,
+                    ty.0
+                );
+                Ok(())
+            };
+            match &node {
+                TypeNode::Vector(items) => check_inner(items)?,
+                TypeNode::Struct {
+                    module,
+                    name,
+                    type_args,
+                    ..
+                } => {
+                    arena.check_ident(*module)?;
+                    arena.check_ident(*name)?;
+                    for arg in type_args.iter() {
+                        check_inner(arg)?;
+                    }
+                },
+                TypeNode::Reference { to, .. } => check_inner(to)?,
+                _ => {},
+            }
+            ensure!(
+                arena.type_indices.insert(node.clone(), index).is_none(),
+                "Type {} is interned twice",
+                index.0
+            );
+            arena.types.push(node);
+        }
+        Ok(arena)
+    }
+
+    fn check_ident(&self, index: IdentIndex) -> Result<()> {
+        ensure!(
+            (index.0 as usize) < self.idents.len(),
+            "Identifier {} is out of bounds",
+            index.0
+        );
+        Ok(())
+    }
+
+    fn check_type(&self, index: TypeIndex) -> Result<()> {
+        ensure!(
+            (index.0 as usize) < self.types.len(),
+            "Type {} is out of bounds",
+            index.0
+        );
+        Ok(())
+    }
+
+    fn intern_ident(&mut self, ident: &IdentStr) -> IdentIndex {
+        if let Some(index) = self.ident_indices.get(ident) {
+            return *index;
+        }
+        let index = IdentIndex(self.idents.len() as u32);
+        self.idents.push(ident.to_owned());
+        self.ident_indices.insert(ident.to_owned(), index);
+        index
+    }
+
+    fn find_ident(&self, ident: &IdentStr) -> Option<IdentIndex> {
+        self.ident_indices.get(ident).copied()
+    }
+
+    fn ident(&self, index: IdentIndex) -> &IdentStr {
+        &self.idents[index.0 as usize]
+    }
+
+    fn intern_type(&mut self, ty: &MoveType) -> TypeIndex {
+        let node = match ty {
+            MoveType::Bool => TypeNode::Bool,
+            MoveType::U8 => TypeNode::U8,
+            MoveType::U16 => TypeNode::U16,
+            MoveType::U32 => TypeNode::U32,
+            MoveType::U64 => TypeNode::U64,
+            MoveType::U128 => TypeNode::U128,
+            MoveType::U256 => TypeNode::U256,
+            MoveType::Address => TypeNode::Address,
+            MoveType::Signer => TypeNode::Signer,
+            MoveType::Vector { items } => TypeNode::Vector(self.intern_type(items)),
+            MoveType::Struct(tag) => TypeNode::Struct {
+                address: tag.address.into(),
+                module: self.intern_ident(&tag.module.0),
+                name: self.intern_ident(&tag.name.0),
+                type_args: tag
+                    .generic_type_params
+                    .iter()
+                    .map(|param| self.intern_type(param))
+                    .collect(),
+            },
+            MoveType::GenericTypeParam { index } => TypeNode::GenericTypeParam(*index),
+            MoveType::Reference { mutable, to } => TypeNode::Reference {
+                mutable: *mutable,
+                to: self.intern_type(to),
+            },
+            MoveType::Unparsable(string) => TypeNode::Unparsable(string.as_str().into()),
+        };
+        if let Some(index) = self.type_indices.get(&node) {
+            return *index;
+        }
+        let index = TypeIndex(self.types.len() as u32);
+        self.types.push(node.clone());
+        self.type_indices.insert(node, index);
+        index
+    }
+
+    fn node(&self, index: TypeIndex) -> &TypeNode {
+        &self.types[index.0 as usize]
+    }
+
+    /// Identifiers and nodes are held both by index and by value, so they count twice.
+    fn memory_usage(&self) -> usize {
+        let idents: usize = self
+            .idents
+            .iter()
+            .map(|ident| 2 * (size_of::<Identifier>() + ident.len()) + size_of::<IdentIndex>())
+            .sum();
+        let types: usize = self
+            .types
+            .iter()
+            .map(|node| {
+                let heap = match node {
+                    TypeNode::Struct { type_args, .. } => type_args.len() * size_of::<TypeIndex>(),
+                    TypeNode::Unparsable(string) => string.len(),
+                    _ => 0,
+                };
+                2 * (size_of::<TypeNode>() + heap) + size_of::<TypeIndex>()
+            })
+            .sum();
+        idents + types
+    }
+}
+
+/// A type met while walking the types of a [`TypeAccessor`], along with what its generic type
+/// parameters stand for: either an interned type, or a type argument of a payload, which may
+/// refer to structs the accessor doesn't know.
+#[derive(Clone, Copy)]
+enum ScopedType<'a> {
+    Interned {
+        ty: TypeIndex,
+        ty_args: &'a [ScopedType<'a>],
+    },
+    Tag(&'a TypeTag),
+}
+
+/// A [`ScopedType`] with generic type parameters resolved, as decoding and layouts see it.
+enum Shape<'a> {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Vector(ScopedType<'a>),
+    Struct {
+        address: AccountAddress,
+        module: &'a IdentStr,
+        name: &'a IdentStr,
+        ty_args: Vec<ScopedType<'a>>,
+    },
+    /// Signers, references, unbound generic type parameters and unparsable types.
+    Other,
+}
+
+fn is_string(address: AccountAddress, module: &IdentStr, name: &IdentStr) -> bool {
+    address == CORE_CODE_ADDRESS && module.as_str() == "string" && name.as_str() == "String"
+}
+
+fn is_event_handle(tag: &MoveStructTag) -> bool {
+    AccountAddress::from(tag.address) == CORE_CODE_ADDRESS
+        && tag.module.as_str() == "event"
+        && tag.name.as_str() == "EventHandle"
+}
+
+/// Whether `ty` refers to generic type parameters, i.e. isn't a concrete type.
+fn has_type_params(ty: &MoveType) -> bool {
+    match ty {
+        MoveType::GenericTypeParam { .. } => true,
+        MoveType::Vector { items } => has_type_params(items),
+        MoveType::Reference { to, .. } => has_type_params(to),
+        MoveType::Struct(tag) => tag.generic_type_params.iter().any(has_type_params),
+        _ => false,
+    }
+}
+
+/// `ty` with its generic type parameters replaced by `ty_args`.
+fn substitute_type_params(ty: &MoveType, ty_args: &[MoveType]) -> MoveType {
+    match ty {
+        MoveType::GenericTypeParam { index } => ty_args
+            .get(*index as usize)
+            .cloned()
+            .unwrap_or_else(|| ty.clone()),
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute_type_params(items, ty_args)),
+        },
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute_type_params(to, ty_args)),
+        },
+        MoveType::Struct(tag) => {
+            let mut tag = tag.clone();
+            tag.generic_type_params = tag
+                .generic_type_params
+                .iter()
+                .map(|ty| substitute_type_params(ty, ty_args))
+                .collect();
+            MoveType::Struct(tag)
+        },
+        _ => ty.clone(),
+    }
+}
+
+/// Adds the modules of the structs in `ty` to `modules`. Generic type parameters are resolved by
+/// whoever instantiates them, so they refer to no module.
+fn collect_modules(ty: &MoveType, modules: &mut BTreeSet<ModuleId>) {
+    match ty {
+        MoveType::Vector { items } => collect_modules(items, modules),
+        MoveType::Reference { to, .. } => collect_modules(to, modules),
+        MoveType::Struct(tag) => {
+            modules.insert(ModuleId::new(tag.address.into(), tag.module.0.clone()));
+            for param in &tag.generic_type_params {
+                collect_modules(param, modules);
+            }
+        },
+        _ => (),
+    }
+}
+
+/// The modules the structs and exposed functions of `module` refer to, other than itself.
+fn referenced_modules(module: &MoveModule) -> BTreeSet<ModuleId> {
+    let mut modules = BTreeSet::new();
+    for field in module.structs.iter().flat_map(|s| &s.fields) {
+        collect_modules(&field.typ, &mut modules);
+    }
+    for param in module
+        .exposed_functions
+        .iter()
+        .flat_map(|function| &function.params)
+    {
+        collect_modules(param, &mut modules);
+    }
+    modules.remove(&module_id(module));
+    modules
+}
+
+/// Reads BCS encoded values piece by piece, as their layout is only known at runtime.
+struct BcsReader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> BcsReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        ensure!(self.bytes.len() >= len, "Unexpected end of BCS bytes");
+        let (bytes, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.read_bytes(N)?.try_into()?)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_uleb128(&mut self) -> Result<usize> {
+        let mut value: u64 = 0;
+        for shift in (0..32).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= u64::from(byte & 0x7F) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value as usize);
+            }
+        }
+        bail!("Invalid ULEB128 length")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        client, market, market_accessor, market_modules, market_struct, mock_modules, module_bytes,
+    };
+    use aptos_api_types::{AptosError, AptosErrorCode};
+    use aptos_types::transaction::EntryFunction;
+    use httpmock::MockServer;
+    use move_binary_format::file_format::SignatureToken;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// The BCS layout of `0xcafe::market::Price`.
+    #[derive(Serialize)]
+    struct Price {
+        amount: u64,
+        currency: String,
+    }
+
+    /// The BCS layout of `0xcafe::market::Listing<T>`, a `Wrapper<vector<T>>` being laid out as
+    /// a `vector<T>`.
+    #[derive(Serialize)]
+    struct Listing<T> {
+        id: u64,
+        items: Vec<Vec<T>>,
+        price: Option<Price>,
+        wrapped: Vec<T>,
+        seller: AccountAddress,
+    }
+
+    fn cafe() -> AccountAddress {
+        AccountAddress::from_hex_literal("0xcafe").unwrap()
+    }
+
+    fn cafe_module(name: &str) -> ModuleId {
+        ModuleId::new(cafe(), Identifier::new(name).unwrap())
+    }
+
+    fn framework_module(name: &str) -> ModuleId {
+        ModuleId::new(AccountAddress::ONE, Identifier::new(name).unwrap())
+    }
+
+    fn unavailable() -> anyhow::Error {
+        RestError::from((
+            AptosError::new_with_error_code("Unavailable", AptosErrorCode::InternalError),
+            None,
+            StatusCode::SERVICE_UNAVAILABLE,
+        ))
+        .into()
+    }
+
+    fn field_names(accessor: &TypeAccessor, module_id: &ModuleId, name: &str) -> Vec<String> {
+        accessor
+            .struct_field_types(module_id, &Identifier::new(name).unwrap())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_refresh_after_upgrade() {
+        let server = MockServer::start();
+        let orders = cafe_module("orders");
+        let mut v1 = mock_modules(&server, cafe(), None, vec![(
+            orders.clone(),
+            module_bytes(&orders, &[
+                ("Order", &[("id", SignatureToken::U64)]),
+                ("Receipt", &[("order_id", SignatureToken::U64)]),
+            ]),
+        )]);
+        let mut accessor = TypeAccessorBuilder::new(client(&server))
+            .add_module_id(orders.clone())
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(field_names(&accessor, &orders, "Order"), vec!["id"]);
+        assert_eq!(field_names(&accessor, &orders, "Receipt"), vec!["order_id"]);
+
+        // The upgrade adds a field to `Order` and removes `Receipt`
+        v1.delete();
+        let v2 = mock_modules(&server, cafe(), None, vec![(
+            orders.clone(),
+            module_bytes(&orders, &[("Order", &[
+                ("id", SignatureToken::U64),
+                ("amount", SignatureToken::U128),
+            ])]),
+        )]);
+        accessor
+            .refresh(TypeAccessorBuilder::new(client(&server)), vec![
+                orders.clone()
+            ])
+            .await
+            .unwrap();
+        v2.assert();
+        assert_eq!(field_names(&accessor, &orders, "Order"), vec![
+            "id", "amount"
+        ]);
+        assert_eq!(accessor.struct_names(), vec![(
+            orders.clone(),
+            Identifier::new("Order").unwrap()
+        )]);
+        assert!(accessor
+            .struct_field_types(&orders, ident_str!("Receipt"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_decode_generic_struct() {
+        let accessor = market_accessor();
+        let listing = Listing {
+            id: 1,
+            items: vec![vec![2u64, 3], vec![]],
+            price: Some(Price {
+                amount: 100,
+                currency: "APT".to_string(),
+            }),
+            wrapped: vec![4],
+            seller: cafe(),
+        };
+        let value = accessor
+            .decode_struct(
+                &market_struct("Listing", vec![TypeTag::U64]),
+                &bcs::to_bytes(&listing).unwrap(),
+            )
+            .unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "id": "1",
+                "items": [["2", "3"], []],
+                "price": { "vec": [{ "amount": "100", "currency": "APT" }] },
+                "wrapped": { "value": ["4"] },
+                "seller": "0xcafe",
+            })
+        );
+    }
+
+    #[test]
+    fn test_decode_nested_options_and_vectors() {
+        let accessor = market_accessor();
+        let option_of_bytes = TypeTag::Struct(Box::new(StructTag {
+            address: AccountAddress::ONE,
+            module: ident_str!("option").to_owned(),
+            name: ident_str!("Option").to_owned(),
+            type_params: vec![TypeTag::Vector(Box::new(TypeTag::U8))],
+        }));
+        let tag = market_struct("Listing", vec![option_of_bytes]);
+        let listing = Listing::<Option<Vec<u8>>> {
+            id: 1,
+            items: vec![vec![Some(b"ab".to_vec()), None], vec![]],
+            price: None,
+            wrapped: vec![None],
+            seller: cafe(),
+        };
+        let mut bytes = bcs::to_bytes(&listing).unwrap();
+        assert_eq!(
+            accessor.decode_struct(&tag, &bytes).unwrap(),
+            json!({
+                "id": "1",
+                "items": [[{ "vec": ["0x6162"] }, { "vec": [] }], []],
+                "price": { "vec": [] },
+                "wrapped": { "value": [{ "vec": [] }] },
+                "seller": "0xcafe",
+            })
+        );
+
+        bytes.push(0);
+        assert!(accessor.decode_struct(&tag, &bytes).is_err());
+        assert!(accessor.decode_struct(&tag, &bytes[..4]).is_err());
+    }
+
+    #[test]
+    fn test_decode_entry_function_args() {
+        let accessor = market_accessor();
+        let payload = |args| {
+            TransactionPayload::EntryFunction(EntryFunction::new(
+                market(),
+                ident_str!("list").to_owned(),
+                vec![TypeTag::U64],
+                args,
+            ))
+        };
+        let args = accessor
+            .decode_entry_function_args(&payload(vec![
+                bcs::to_bytes(&vec![vec![1u64, 2]]).unwrap(),
+                bcs::to_bytes(&Some(3u64)).unwrap(),
+            ]))
+            .unwrap();
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|(name, ty, value)| (name, ty.to_string(), value))
+            .collect();
+        assert_eq!(args, vec![
+            (
+                "arg0".to_string(),
+                "vector<vector<u64>>".to_string(),
+                json!([["1", "2"]])
+            ),
+            (
+                "arg1".to_string(),
+                "0x1::option::Option<u64>".to_string(),
+                json!({ "vec": ["3"] })
+            ),
+        ]);
+
+        // The signer isn't part of the payload, so it takes two arguments
+        assert!(accessor
+            .decode_entry_function_args(&payload(vec![bcs::to_bytes(&3u64).unwrap()]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_instantiate_struct() {
+        let accessor = market_accessor();
+        let fields: Vec<_> = accessor
+            .instantiate_struct(&market_struct("Listing", vec![TypeTag::Address]))
+            .unwrap()
+            .into_iter()
+            .map(|(name, ty)| (name.to_string(), ty.to_string()))
+            .collect();
+        let expected = [
+            ("id", "u64"),
+            ("items", "vector<vector<address>>"),
+            ("price", "0x1::option::Option<0xcafe::market::Price>"),
+            ("wrapped", "0xcafe::market::Wrapper<vector<address>>"),
+            ("seller", "address"),
+        ];
+        assert_eq!(
+            fields,
+            expected
+                .iter()
+                .map(|(name, ty)| (name.to_string(), ty.to_string()))
+                .collect::<Vec<_>>()
+        );
+        // Without instantiating, the type parameters are left as parameters
+        assert_eq!(
+            accessor
+                .get_type(&market(), ident_str!("Listing"), ident_str!("items"))
+                .unwrap()
+                .to_string(),
+            "vector<vector<T0>>"
+        );
+
+        assert!(accessor
+            .instantiate_struct(&market_struct("Listing", vec![]))
+            .is_err());
+        assert!(accessor
+            .instantiate_struct(&market_struct("Auction", vec![]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_serialization_roundtrip() {
+        let accessor = market_accessor();
+        let bytes = accessor.to_bytes().unwrap();
+        let loaded = TypeAccessor::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.to_bytes().unwrap(), bytes);
+        assert_eq!(loaded.struct_names(), accessor.struct_names());
+        let tag = market_struct("Listing", vec![TypeTag::U64]);
+        assert_eq!(
+            loaded.instantiate_struct(&tag).unwrap(),
+            accessor.instantiate_struct(&tag).unwrap()
+        );
+
+        let mut other_version = bytes.clone();
+        other_version[0] += 1;
+        assert!(TypeAccessor::from_bytes(&other_version).is_err());
+        assert!(TypeAccessor::from_bytes(&[]).is_err());
+        assert!(TypeAccessor::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_deserialization_checks_bounds() {
+        let from_serialized = |serialized: SerializedTypeAccessor| {
+            let mut bytes = vec![SERIALIZATION_FORMAT_VERSION];
+            bytes.extend(bcs::to_bytes(&serialized).unwrap());
+            TypeAccessor::from_bytes(&bytes)
+        };
+        let err = from_serialized(SerializedTypeAccessor {
+            idents: vec![ident_str!("market").to_owned()],
+            types: vec![TypeNode::U64],
+            modules: vec![(cafe(), IdentIndex(1))],
+            structs: vec![],
+            functions: vec![],
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("out of bounds"), "{}", err);
+
+        let err = from_serialized(SerializedTypeAccessor {
+            idents: vec![ident_str!("market").to_owned()],
+            types: vec![TypeNode::Vector(TypeIndex(1)), TypeNode::U64],
+            modules: vec![(cafe(), IdentIndex(0))],
+            structs: vec![],
+            functions: vec![],
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("doesn't precede"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient_errors() {
+        let client = Client::new("http://localhost:8080".parse().unwrap());
+        let builder =
+            TypeAccessorBuilder::new(client.clone()).retry_policy(3, Duration::from_millis(1));
+        let attempts = &AtomicU32::new(0);
+
+        let result = builder
+            .with_retries(|| async move {
+                if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                    Err(unavailable())
+                } else {
+                    Ok(())
+                }
+            })
+            .await;
+        assert!(result.is_ok());
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 3);
+
+        let result: Result<()> = builder
+            .with_retries(|| async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(unavailable())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 4);
+
+        // Errors which aren't transient aren't retried
+        let result: Result<()> = builder
+            .with_retries(|| async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(anyhow!("Module not found"))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.swap(0, Ordering::SeqCst), 1);
+
+        // Nor is anything without a retry policy
+        let result: Result<()> = TypeAccessorBuilder::new(client)
+            .with_retries(|| async move {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(unavailable())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+        for retry in 0..3 {
+            let max = Duration::from_millis(100 << retry);
+            let delay = policy.delay(retry);
+            assert!(delay <= max && delay >= max / 2, "{:?}", delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_at_ledger_version() {
+        let server = MockServer::start();
+        let orders = cafe_module("orders");
+        let at_version = mock_modules(&server, cafe(), Some(5), vec![(
+            orders.clone(),
+            module_bytes(&orders, &[("Order", &[("id", SignatureToken::U64)])]),
+        )]);
+
+        let accessor = TypeAccessorBuilder::new(client(&server))
+            .add_module_id(orders.clone())
+            .ledger_version(5)
+            .build()
+            .await
+            .unwrap();
+        at_version.assert();
+        assert_eq!(field_names(&accessor, &orders, "Order"), vec!["id"]);
+
+        // Only the modules at version 5 are served
+        assert!(TypeAccessorBuilder::new(client(&server))
+            .add_module_id(orders)
+            .build()
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_address_bounds() {
+        // The server serves no module, so following a reference fails the build
+        let server = MockServer::start();
+        let market_module = market_modules().pop().unwrap();
+        let build =
+            |builder: TypeAccessorBuilder| builder.add_module(market_module.clone()).build();
+        let price = market_struct("Price", vec![]);
+        let price_bytes = bcs::to_bytes(&Price {
+            amount: 1,
+            currency: "APT".to_string(),
+        })
+        .unwrap();
+
+        assert!(build(TypeAccessorBuilder::new(client(&server)))
+            .await
+            .is_err());
+        for builder in [
+            TypeAccessorBuilder::new(client(&server)).restrict_to_addresses(vec![cafe()]),
+            TypeAccessorBuilder::new(client(&server)).exclude_addresses(vec![AccountAddress::ONE]),
+            // Exclusions apply on top of the allowed addresses
+            TypeAccessorBuilder::new(client(&server))
+                .restrict_to_addresses(vec![cafe(), AccountAddress::ONE])
+                .exclude_addresses(vec![AccountAddress::ONE]),
+        ] {
+            let accessor = build(builder).await.unwrap();
+            assert!(accessor.contains_module(&market()));
+            assert!(!accessor.contains_module(&framework_module("option")));
+            // Strings don't need their module to be decoded
+            assert!(accessor.decode_struct(&price, &price_bytes).is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_deserialize_modules_in_order() {
+        let modules: Vec<_> = (0..16)
+            .map(|i| {
+                let module_id = cafe_module(&format!("m{}", i));
+                let bytes = module_bytes(&module_id, &[]);
+                (module_id, bytes, Duration::from_millis(i))
+            })
+            .collect();
+        let deserialized: Vec<_> = deserialize_modules(modules.clone())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(module, latency)| (module_id(&module), latency))
+            .collect();
+        assert_eq!(
+            deserialized,
+            modules
+                .iter()
+                .map(|(module_id, _, latency)| (module_id.clone(), *latency))
+                .collect::<Vec<_>>()
+        );
+
+        let mut invalid = modules;
+        invalid[3].1 = vec![0xDE, 0xAD];
+        let err = deserialize_modules(invalid).await.unwrap_err();
+        assert!(err.to_string().contains("::m3"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_dependency_walk_report() {
+        let server = MockServer::start();
+        let option = framework_module("option");
+        let string = framework_module("string");
+        let framework = mock_modules(&server, AccountAddress::ONE, None, vec![
+            (option.clone(), module_bytes(&option, &[])),
+            (string.clone(), module_bytes(&string, &[])),
+        ]);
+
+        let (accessor, report) = TypeAccessorBuilder::new(client(&server))
+            .add_module(market_modules().pop().unwrap())
+            .build_with_report()
+            .await
+            .unwrap();
+        // Both modules of the account are fetched in one batch
+        framework.assert_hits(1);
+        assert!(accessor.contains_module(&option) && accessor.contains_module(&string));
+        assert_eq!(report.modules_fetched, vec![option.clone(), string.clone()]);
+        assert_eq!(report.fetch_latencies.keys().collect::<Vec<_>>(), vec![
+            &option, &string
+        ]);
+        assert_eq!(report.rounds, 1);
+        assert_eq!(
+            report.dependencies[&market()],
+            BTreeSet::from([option.clone(), string.clone()])
+        );
+        assert!(report.dependencies[&option].is_empty());
+        assert!(report.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_find_cycles() {
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(cafe_module);
+        let dependencies = BTreeMap::from([
+            (a.clone(), BTreeSet::from([b.clone()])),
+            (b.clone(), BTreeSet::from([a.clone(), c.clone()])),
+            (c.clone(), BTreeSet::from([d.clone()])),
+            (d, BTreeSet::new()),
+        ]);
+        assert_eq!(find_cycles(&dependencies), vec![vec![a, b]]);
+    }
+}
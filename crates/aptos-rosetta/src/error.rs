@@ -303,6 +303,10 @@ impl From<RestError> for ApiError {
             RestError::UrlParse(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Timeout(err) => ApiError::InternalError(Some(err.to_string())),
             RestError::Unknown(err) => ApiError::InternalError(Some(err.to_string())),
+            err @ RestError::Stale { .. } => ApiError::InternalError(Some(err.to_string())),
+            err @ RestError::ChainIdMismatch { .. } => {
+                ApiError::InternalError(Some(err.to_string()))
+            },
         }
     }
 }
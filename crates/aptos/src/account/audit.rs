@@ -0,0 +1,839 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account::key_rotation::OriginatingResource,
+    common::types::{
+        CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode, ProfileOptions,
+        RestOptions,
+    },
+};
+use aptos_rest_client::{
+    aptos_api_types::{AptosError, AptosErrorCode},
+    error::{AptosErrorResponse, RestError},
+    Client,
+};
+use aptos_types::{
+    account_address::AccountAddress,
+    account_config::{AccountResource, CORE_CODE_ADDRESS},
+    contract_event::EventWithVersion,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Number of events fetched per request
+const EVENTS_PAGE_SIZE: u16 = 100;
+
+const USECS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000;
+
+/// Audit the security of an account
+///
+/// Reports the key rotation history of the account, its multisig configuration, the capabilities
+/// it offered to other accounts and the token offers it left pending, i.e. the allowances of the
+/// token standard, flagging the ones pending for long as stale. Along with findings on what to
+/// revoke or look into. Useful after a suspected key compromise.
+#[derive(Debug, Parser)]
+pub struct AuditAccount {
+    /// Address of the account to audit
+    ///
+    /// Defaults to the account of the profile
+    #[clap(long, parse(try_from_str=crate::common::types::load_account_arg))]
+    pub(crate) address: Option<AccountAddress>,
+
+    /// Number of days after which a token offer still pending is stale
+    #[clap(long, default_value_t = 30)]
+    pub(crate) stale_after_days: u64,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountAudit {
+    pub address: AccountAddress,
+    pub authentication_key: String,
+    pub sequence_number: u64,
+    /// Every rotation of the authentication key, oldest first
+    pub key_rotations: Vec<KeyRotation>,
+    /// Whether `0x1::account::OriginatingAddress` maps the authentication key to the account, so
+    /// that wallets can find the account from its key. Only set once the key was rotated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discoverable_from_key: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigConfig>,
+    /// The account allowed to rotate the key of this account, if any
+    pub rotation_capability_offered_to: Option<AccountAddress>,
+    /// The account allowed to sign as this account, if any
+    pub signer_capability_offered_to: Option<AccountAddress>,
+    /// Resource accounts created by this account whose signer capability wasn't retrieved yet
+    pub unclaimed_resource_accounts: Vec<AccountAddress>,
+    /// Token offers neither claimed nor cancelled, oldest first
+    pub pending_token_offers: Vec<TokenOffer>,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenOffer {
+    pub receiver: AccountAddress,
+    pub token: TokenId,
+    pub amount: u64,
+    /// The version of the first offer of the token to the receiver since it was last claimed or
+    /// cancelled
+    pub version: u64,
+    pub days_pending: u64,
+    /// Whether the offer has been pending for more than `--stale-after-days`
+    pub stale: bool,
+}
+
+/// `0x3::token::TokenId`
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TokenId {
+    pub token_data_id: TokenDataId,
+    pub property_version: u64,
+}
+
+/// `0x3::token::TokenDataId`
+#[derive(Clone, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct TokenDataId {
+    pub creator: AccountAddress,
+    pub collection: String,
+    pub name: String,
+}
+
+impl fmt::Display for TokenId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}::{}::{} (property version {})",
+            self.token_data_id.creator.to_hex_literal(),
+            self.token_data_id.collection,
+            self.token_data_id.name,
+            self.property_version
+        )
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyRotation {
+    pub version: u64,
+    pub old_authentication_key: String,
+    pub new_authentication_key: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MultisigConfig {
+    pub owners: Vec<AccountAddress>,
+    pub num_signatures_required: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Critical,
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, message: String) -> Self {
+        Self { severity, message }
+    }
+}
+
+/// `0x1::account::KeyRotationEvent`
+#[derive(Deserialize)]
+struct KeyRotationEvent {
+    old_authentication_key: Vec<u8>,
+    new_authentication_key: Vec<u8>,
+}
+
+/// `0x3::token_transfers::TokenOfferEvent`, laid out as `TokenCancelOfferEvent` and
+/// `TokenClaimEvent` are
+#[derive(Deserialize)]
+struct TokenOfferEvent {
+    to_address: AccountAddress,
+    token_id: TokenId,
+    amount: u64,
+}
+
+#[async_trait]
+impl CliCommand<AccountAudit> for AuditAccount {
+    fn command_name(&self) -> &'static str {
+        "AuditAccount"
+    }
+
+    async fn execute(self) -> CliTypedResult<AccountAudit> {
+        let address = if let Some(address) = self.address {
+            address
+        } else if let Some(Some(account)) = CliConfig::load_profile(
+            self.profile_options.profile_name(),
+            ConfigSearchMode::CurrentDirAndParents,
+        )?
+        .map(|p| p.account)
+        {
+            account
+        } else {
+            return Err(CliError::CommandArgumentError(
+                "Please provide an account using --address or run aptos init".to_string(),
+            ));
+        };
+        let client = self.rest_options.client(&self.profile_options)?;
+
+        let response = client
+            .get_account_resource_bcs::<AccountResource>(address, "0x1::account::Account")
+            .await?;
+        let ledger_timestamp_usecs = response.state().timestamp_usecs;
+        let account = response.into_inner();
+        let key_rotations = key_rotations(&client, address, &account).await?;
+        let authentication_key = AccountAddress::from_bytes(account.authentication_key())
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let discoverable_from_key = if authentication_key != address {
+            Some(originating_address(&client, authentication_key).await? == Some(address))
+        } else {
+            None
+        };
+
+        let multisig = resource(&client, address, "0x1::multisig_account::MultisigAccount")
+            .await?
+            .map(|data| {
+                Ok::<_, CliError>(MultisigConfig {
+                    owners: parse_field(&data, "owners")?,
+                    num_signatures_required: parse_u64(&data, "num_signatures_required")?,
+                })
+            })
+            .transpose()?;
+        let unclaimed_resource_accounts =
+            match resource(&client, address, "0x1::resource_account::Container").await? {
+                Some(container) => container["store"]["data"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|entry| parse_field(entry, "key"))
+                    .collect::<CliTypedResult<_>>()?,
+                None => vec![],
+            };
+        let mut pending_token_offers =
+            match resource(&client, address, "0x3::token_transfers::PendingClaims").await? {
+                Some(claims) => pending_token_offers(&client, address, &claims).await?,
+                None => vec![],
+            };
+        for offer in &mut pending_token_offers {
+            let offered_at = client
+                .get_transaction_by_version(offer.version)
+                .await?
+                .into_inner()
+                .timestamp();
+            offer.days_pending = ledger_timestamp_usecs.saturating_sub(offered_at) / USECS_PER_DAY;
+            offer.stale = offer.days_pending > self.stale_after_days;
+        }
+
+        let mut audit = AccountAudit {
+            address,
+            authentication_key: authentication_key.to_hex_literal(),
+            sequence_number: account.sequence_number(),
+            key_rotations,
+            discoverable_from_key,
+            multisig,
+            rotation_capability_offered_to: account.rotation_capability_offer(),
+            signer_capability_offered_to: account.signer_capability_offer(),
+            unclaimed_resource_accounts,
+            pending_token_offers,
+            findings: vec![],
+        };
+        audit.findings = findings(&audit);
+        Ok(audit)
+    }
+}
+
+async fn key_rotations(
+    client: &Client,
+    address: AccountAddress,
+    account: &AccountResource,
+) -> CliTypedResult<Vec<KeyRotation>> {
+    let events = events(
+        client,
+        address,
+        "0x1::account::Account",
+        "key_rotation_events",
+        account.key_rotation_events().count(),
+    )
+    .await?;
+    events
+        .into_iter()
+        .map(|event| {
+            let data: KeyRotationEvent = bcs::from_bytes(event.event.event_data())
+                .map_err(|err| CliError::BCS("KeyRotationEvent", err))?;
+            Ok(KeyRotation {
+                version: event.transaction_version,
+                old_authentication_key: hex::encode(data.old_authentication_key),
+                new_authentication_key: hex::encode(data.new_authentication_key),
+            })
+        })
+        .collect()
+}
+
+/// The offers of `0x3::token_transfers::PendingClaims` neither claimed nor cancelled, replayed
+/// from its events, as the table of offers can't be listed. Their age isn't set yet.
+async fn pending_token_offers(
+    client: &Client,
+    address: AccountAddress,
+    claims: &serde_json::Value,
+) -> CliTypedResult<Vec<TokenOffer>> {
+    let offers = token_offer_events(client, address, claims, "offer_events").await?;
+    let mut removals = token_offer_events(client, address, claims, "cancel_offer_events").await?;
+    removals.extend(token_offer_events(client, address, claims, "claim_events").await?);
+    Ok(replay_token_offers(offers, removals))
+}
+
+/// The events of the event handle `handle` of a `0x3::token_transfers::PendingClaims`, with the
+/// versions which emitted them
+async fn token_offer_events(
+    client: &Client,
+    address: AccountAddress,
+    claims: &serde_json::Value,
+    handle: &'static str,
+) -> CliTypedResult<Vec<(u64, TokenOfferEvent)>> {
+    let count = parse_u64(&claims[handle], "counter")?;
+    let events = events(
+        client,
+        address,
+        "0x3::token_transfers::PendingClaims",
+        handle,
+        count,
+    )
+    .await?;
+    events
+        .into_iter()
+        .map(|event| {
+            let data = bcs::from_bytes(event.event.event_data())
+                .map_err(|err| CliError::BCS("TokenOfferEvent", err))?;
+            Ok((event.transaction_version, data))
+        })
+        .collect()
+}
+
+/// Replays the offers and the cancellations and claims of a `0x3::token_transfers::PendingClaims`
+/// into the offers still pending, oldest first.
+fn replay_token_offers(
+    offers: Vec<(u64, TokenOfferEvent)>,
+    removals: Vec<(u64, TokenOfferEvent)>,
+) -> Vec<TokenOffer> {
+    // A token can be offered and its offer cancelled by the same transaction, only in that order
+    let mut events: Vec<_> = offers
+        .into_iter()
+        .map(|(version, event)| (version, false, event))
+        .chain(
+            removals
+                .into_iter()
+                .map(|(version, event)| (version, true, event)),
+        )
+        .collect();
+    events.sort_by_key(|(version, removal, _)| (*version, *removal));
+
+    let mut pending = BTreeMap::new();
+    for (version, removal, event) in events {
+        let key = (event.to_address, event.token_id);
+        if removal {
+            // Claims and cancellations take the whole offer
+            pending.remove(&key);
+        } else {
+            // Offering the same token again adds to the pending offer
+            pending.entry(key).or_insert((version, 0)).1 += event.amount;
+        }
+    }
+    let mut offers: Vec<_> = pending
+        .into_iter()
+        .map(|((receiver, token), (version, amount))| TokenOffer {
+            receiver,
+            token,
+            amount,
+            version,
+            days_pending: 0,
+            stale: false,
+        })
+        .collect();
+    offers.sort_by_key(|offer| offer.version);
+    offers
+}
+
+/// The first `count` events of the event handle `field` of the resource `struct_tag` of
+/// `address`, or the ones left if older events were pruned
+async fn events(
+    client: &Client,
+    address: AccountAddress,
+    struct_tag: &str,
+    field: &str,
+    count: u64,
+) -> CliTypedResult<Vec<EventWithVersion>> {
+    let mut events = vec![];
+    while (events.len() as u64) < count {
+        let page = client
+            .get_account_events_bcs(
+                address,
+                struct_tag,
+                field,
+                Some(events.len() as u64),
+                Some(EVENTS_PAGE_SIZE),
+            )
+            .await?
+            .into_inner();
+        if page.is_empty() {
+            // Older events may have been pruned
+            break;
+        }
+        events.extend(page);
+    }
+    Ok(events)
+}
+
+/// Looks up the account `0x1::account::OriginatingAddress` maps `authentication_key` to
+async fn originating_address(
+    client: &Client,
+    authentication_key: AccountAddress,
+) -> CliTypedResult<Option<AccountAddress>> {
+    let originating_resource: OriginatingResource = client
+        .get_account_resource_bcs(CORE_CODE_ADDRESS, "0x1::account::OriginatingAddress")
+        .await?
+        .into_inner();
+    match client
+        .get_table_item_bcs(
+            originating_resource.address_map.handle,
+            "address",
+            "address",
+            authentication_key.to_hex_literal(),
+        )
+        .await
+    {
+        Ok(inner) => Ok(Some(inner.into_inner())),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::TableItemNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Reads a resource as JSON, if the account has it
+async fn resource(
+    client: &Client,
+    address: AccountAddress,
+    resource_type: &str,
+) -> CliTypedResult<Option<serde_json::Value>> {
+    match client.get_account_resource(address, resource_type).await {
+        Ok(inner) => Ok(inner.into_inner().map(|resource| resource.data)),
+        Err(RestError::Api(AptosErrorResponse {
+            error:
+                AptosError {
+                    error_code: AptosErrorCode::ResourceNotFound,
+                    ..
+                },
+            ..
+        })) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn parse_field<T: serde::de::DeserializeOwned>(
+    data: &serde_json::Value,
+    field: &'static str,
+) -> CliTypedResult<T> {
+    serde_json::from_value(data[field].clone())
+        .map_err(|err| CliError::UnableToParse(field, err.to_string()))
+}
+
+/// Move `u64`s are strings in JSON
+fn parse_u64(data: &serde_json::Value, field: &'static str) -> CliTypedResult<u64> {
+    parse_field::<String>(data, field)?
+        .parse()
+        .map_err(|err: std::num::ParseIntError| CliError::UnableToParse(field, err.to_string()))
+}
+
+/// What to revoke or look into, most severe first
+fn findings(audit: &AccountAudit) -> Vec<Finding> {
+    let mut findings = vec![];
+    if let Some(to) = audit.signer_capability_offered_to {
+        findings.push(Finding::new(
+            Severity::Critical,
+            format!(
+                "{} can sign transactions as this account: unless intended, revoke the offer with \
+                 0x1::account::revoke_any_signer_capability",
+                to
+            ),
+        ));
+    }
+    if let Some(to) = audit.rotation_capability_offered_to {
+        findings.push(Finding::new(
+            Severity::Critical,
+            format!(
+                "{} can rotate the authentication key of this account, i.e. take it over: unless \
+                 intended, revoke the offer with 0x1::account::revoke_any_rotation_capability",
+                to
+            ),
+        ));
+    }
+
+    if let Some(multisig) = &audit.multisig {
+        if multisig.num_signatures_required <= 1 && multisig.owners.len() > 1 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                format!(
+                    "Any one of the {} owners can execute transactions of this multisig account: \
+                     a single compromised owner is enough to take it over",
+                    multisig.owners.len()
+                ),
+            ));
+        }
+        findings.push(Finding::new(
+            Severity::Info,
+            format!(
+                "This is a {}-of-{} multisig account: check that each owner is still trusted",
+                multisig.num_signatures_required,
+                multisig.owners.len()
+            ),
+        ));
+    }
+    let (stale_offers, fresh_offers): (Vec<_>, Vec<_>) = audit
+        .pending_token_offers
+        .iter()
+        .partition(|offer| offer.stale);
+    for offer in stale_offers {
+        findings.push(Finding::new(
+            Severity::Warning,
+            format!(
+                "{} of {} offered to {} at version {} is still unclaimed after {} days: unless it \
+                 is still meant to be claimed, cancel the offer with \
+                 0x3::token_transfers::cancel_offer_script",
+                offer.amount,
+                offer.token,
+                offer.receiver.to_hex_literal(),
+                offer.version,
+                offer.days_pending
+            ),
+        ));
+    }
+    if !fresh_offers.is_empty() {
+        findings.push(Finding::new(
+            Severity::Info,
+            format!(
+                "{} recent token offers are still pending: cancel the ones not meant to be \
+                 claimed with 0x3::token_transfers::cancel_offer_script",
+                fresh_offers.len()
+            ),
+        ));
+    }
+    if audit.discoverable_from_key == Some(false) {
+        findings.push(Finding::new(
+            Severity::Warning,
+            "The authentication key isn't mapped to this account in \
+             0x1::account::OriginatingAddress, so wallets can't find the account from its key: \
+             it was rotated without proving ownership of the new key"
+                .to_string(),
+        ));
+    }
+
+    match audit.key_rotations.last() {
+        Some(last) => findings.push(Finding::new(
+            Severity::Info,
+            format!(
+                "The authentication key was rotated {} times, last at version {}: check that \
+                 every rotation was made by the owner",
+                audit.key_rotations.len(),
+                last.version
+            ),
+        )),
+        None if audit.authentication_key != audit.address.to_hex_literal() => {
+            findings.push(Finding::new(
+                Severity::Info,
+                "The authentication key differs from the address, but no rotation is recorded: \
+                 the account was created with another key, e.g. as a resource account"
+                    .to_string(),
+            ))
+        },
+        None => findings.push(Finding::new(
+            Severity::Info,
+            "The authentication key was never rotated: if the key may be compromised, rotate it \
+             with `aptos account rotate-key`"
+                .to_string(),
+        )),
+    }
+    if !audit.unclaimed_resource_accounts.is_empty() {
+        findings.push(Finding::new(
+            Severity::Info,
+            format!(
+                "The signer capabilities of resource accounts {} were never retrieved by their \
+                 modules",
+                audit
+                    .unclaimed_resource_accounts
+                    .iter()
+                    .map(|address| address.to_hex_literal())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        ));
+    }
+
+    findings.sort_by_key(|finding| finding.severity);
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(hex: &str) -> AccountAddress {
+        AccountAddress::from_hex_literal(hex).unwrap()
+    }
+
+    fn token(name: &str) -> TokenId {
+        TokenId {
+            token_data_id: TokenDataId {
+                creator: address("0xc"),
+                collection: "collection".to_string(),
+                name: name.to_string(),
+            },
+            property_version: 0,
+        }
+    }
+
+    fn offer_event(to: &str, name: &str, amount: u64) -> TokenOfferEvent {
+        TokenOfferEvent {
+            to_address: address(to),
+            token_id: token(name),
+            amount,
+        }
+    }
+
+    fn token_offer(name: &str, days_pending: u64, stale: bool) -> TokenOffer {
+        TokenOffer {
+            receiver: address("0xb"),
+            token: token(name),
+            amount: 1,
+            version: 10,
+            days_pending,
+            stale,
+        }
+    }
+
+    /// The audit of an account whose key was never rotated, and which granted nothing
+    fn audit() -> AccountAudit {
+        AccountAudit {
+            address: address("0xa"),
+            authentication_key: address("0xa").to_hex_literal(),
+            sequence_number: 0,
+            key_rotations: vec![],
+            discoverable_from_key: None,
+            multisig: None,
+            rotation_capability_offered_to: None,
+            signer_capability_offered_to: None,
+            unclaimed_resource_accounts: vec![],
+            pending_token_offers: vec![],
+            findings: vec![],
+        }
+    }
+
+    /// The findings of `audit` but the one about key rotations, which every audit has
+    fn findings_of(audit: &AccountAudit) -> Vec<(Severity, String)> {
+        findings(audit)
+            .into_iter()
+            .filter(|finding| !finding.message.starts_with("The authentication key"))
+            .map(|finding| (finding.severity, finding.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_no_findings() {
+        assert!(findings_of(&audit()).is_empty());
+    }
+
+    #[test]
+    fn test_offered_capabilities() {
+        let mut audit = audit();
+        audit.rotation_capability_offered_to = Some(address("0xb"));
+        audit.signer_capability_offered_to = Some(address("0xc"));
+        let findings = findings_of(&audit);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].0, Severity::Critical);
+        assert!(findings[0]
+            .1
+            .contains("can sign transactions as this account"));
+        assert_eq!(findings[1].0, Severity::Critical);
+        assert!(findings[1]
+            .1
+            .contains("can rotate the authentication key of this account"));
+    }
+
+    #[test]
+    fn test_multisig() {
+        let mut audit = audit();
+        audit.multisig = Some(MultisigConfig {
+            owners: vec![address("0xb"), address("0xc"), address("0xd")],
+            num_signatures_required: 2,
+        });
+        assert_eq!(findings_of(&audit), vec![(
+            Severity::Info,
+            "This is a 2-of-3 multisig account: check that each owner is still trusted".to_string()
+        )]);
+
+        audit.multisig.as_mut().unwrap().num_signatures_required = 1;
+        let findings = findings_of(&audit);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].0, Severity::Warning);
+        assert!(findings[0].1.starts_with("Any one of the 3 owners"));
+        assert_eq!(findings[1].0, Severity::Info);
+    }
+
+    #[test]
+    fn test_pending_token_offers() {
+        let mut audit = audit();
+        audit.pending_token_offers = vec![
+            token_offer("old", 45, true),
+            token_offer("new", 2, false),
+            token_offer("newer", 0, false),
+        ];
+        let findings = findings_of(&audit);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].0, Severity::Warning);
+        assert!(findings[0].1.starts_with(
+            "1 of 0xc::collection::old (property version 0) offered to 0xb at version 10 is still \
+             unclaimed after 45 days"
+        ));
+        assert_eq!(findings[1].0, Severity::Info);
+        assert!(findings[1]
+            .1
+            .starts_with("2 recent token offers are still pending"));
+    }
+
+    #[test]
+    fn test_undiscoverable_key() {
+        let mut audit = audit();
+        audit.discoverable_from_key = Some(true);
+        assert!(findings_of(&audit).is_empty());
+
+        audit.discoverable_from_key = Some(false);
+        let findings = findings_of(&audit);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].0, Severity::Warning);
+        assert!(findings[0].1.contains("wallets can't find the account"));
+    }
+
+    #[test]
+    fn test_key_rotations() {
+        let rotation_finding = |audit: &AccountAudit| {
+            let mut findings = findings(audit);
+            assert_eq!(findings.len(), 1);
+            let finding = findings.remove(0);
+            assert_eq!(finding.severity, Severity::Info);
+            finding.message
+        };
+
+        let mut audit = audit();
+        assert!(rotation_finding(&audit).starts_with("The authentication key was never rotated"));
+
+        audit.authentication_key = address("0xb").to_hex_literal();
+        assert!(rotation_finding(&audit).contains("but no rotation is recorded"));
+
+        audit.key_rotations = vec![
+            KeyRotation {
+                version: 5,
+                old_authentication_key: hex::encode(address("0xa")),
+                new_authentication_key: hex::encode(address("0xc")),
+            },
+            KeyRotation {
+                version: 8,
+                old_authentication_key: hex::encode(address("0xc")),
+                new_authentication_key: hex::encode(address("0xb")),
+            },
+        ];
+        assert!(rotation_finding(&audit)
+            .starts_with("The authentication key was rotated 2 times, last at version 8"));
+    }
+
+    #[test]
+    fn test_unclaimed_resource_accounts() {
+        let mut audit = audit();
+        audit.unclaimed_resource_accounts = vec![address("0xb"), address("0xc")];
+        assert_eq!(findings_of(&audit), vec![(
+            Severity::Info,
+            "The signer capabilities of resource accounts 0xb, 0xc were never retrieved by their \
+             modules"
+                .to_string()
+        )]);
+    }
+
+    #[test]
+    fn test_findings_by_severity() {
+        let mut audit = audit();
+        audit.unclaimed_resource_accounts = vec![address("0xb")];
+        audit.discoverable_from_key = Some(false);
+        audit.signer_capability_offered_to = Some(address("0xc"));
+        let severities: Vec<_> = findings(&audit)
+            .into_iter()
+            .map(|finding| finding.severity)
+            .collect();
+        assert_eq!(severities, vec![
+            Severity::Critical,
+            Severity::Warning,
+            Severity::Info,
+            Severity::Info
+        ]);
+    }
+
+    #[test]
+    fn test_replay_token_offers() {
+        let offers = replay_token_offers(
+            vec![
+                (1, offer_event("0xb", "claimed", 1)),
+                (2, offer_event("0xb", "merged", 1)),
+                (3, offer_event("0xb", "cancelled", 1)),
+                (4, offer_event("0xb", "merged", 2)),
+                (5, offer_event("0xb", "reoffered", 1)),
+                // The same token to another receiver is another offer
+                (6, offer_event("0xd", "claimed", 3)),
+                (
+                    7,
+                    offer_event("0xb", "cancelled in the same transaction", 1),
+                ),
+                (8, offer_event("0xb", "reoffered", 4)),
+            ],
+            vec![
+                (3, offer_event("0xb", "claimed", 1)),
+                (4, offer_event("0xb", "cancelled", 1)),
+                (6, offer_event("0xb", "reoffered", 1)),
+                (
+                    7,
+                    offer_event("0xb", "cancelled in the same transaction", 1),
+                ),
+            ],
+        );
+        let offers: Vec<_> = offers
+            .iter()
+            .map(|offer| {
+                (
+                    offer.receiver,
+                    offer.token.token_data_id.name.as_str(),
+                    offer.amount,
+                    offer.version,
+                )
+            })
+            .collect();
+        assert_eq!(offers, vec![
+            (address("0xb"), "merged", 3, 2),
+            (address("0xd"), "claimed", 3, 6),
+            (address("0xb"), "reoffered", 4, 8),
+        ]);
+    }
+}
@@ -36,6 +36,19 @@ impl std::fmt::Display for SeedEncoding {
     }
 }
 
+impl SeedEncoding {
+    /// Converts `seed` to the bytes the resource account address is derived from
+    pub fn encode(self, seed: &str) -> CliTypedResult<Vec<u8>> {
+        match self {
+            SeedEncoding::Bcs => Ok(bcs::to_bytes(seed)?),
+            SeedEncoding::Utf8 => Ok(seed.as_bytes().to_vec()),
+            SeedEncoding::Hex => HexEncodedBytes::from_str(seed)
+                .map(|inner| inner.0)
+                .map_err(|err| CliError::UnableToParse("seed", err.to_string())),
+        }
+    }
+}
+
 impl FromStr for SeedEncoding {
     type Err = CliError;
 
@@ -77,13 +90,7 @@ pub struct ResourceAccountSeed {
 
 impl ResourceAccountSeed {
     pub fn seed(self) -> CliTypedResult<Vec<u8>> {
-        match self.seed_encoding {
-            SeedEncoding::Bcs => Ok(bcs::to_bytes(self.seed.as_str())?),
-            SeedEncoding::Utf8 => Ok(self.seed.as_bytes().to_vec()),
-            SeedEncoding::Hex => HexEncodedBytes::from_str(self.seed.as_str())
-                .map(|inner| inner.0)
-                .map_err(|err| CliError::UnableToParse("seed", err.to_string())),
-        }
+        self.seed_encoding.encode(&self.seed)
     }
 }
 
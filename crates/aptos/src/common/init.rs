@@ -11,7 +11,7 @@ use crate::common::{
 };
 use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey, ValidCryptoMaterialStringExt};
 use aptos_rest_client::{
-    aptos_api_types::{AptosError, AptosErrorCode},
+    aptos_api_types::{Amount, AptosError, AptosErrorCode, APT_SYMBOL},
     error::{AptosErrorResponse, RestError},
 };
 use async_trait::async_trait;
@@ -104,27 +104,12 @@ impl CliCommand<()> for InitTool {
             }
         };
 
-        match network {
-            Network::Mainnet => {
-                profile_config.rest_url =
-                    Some("https://fullnode.mainnet.aptoslabs.com".to_string());
-                profile_config.faucet_url = None;
+        match network.sdk_network() {
+            Some(sdk_network) => {
+                profile_config.rest_url = Some(profile_url(&sdk_network.rest_url()));
+                profile_config.faucet_url = sdk_network.faucet_url().as_ref().map(profile_url);
             },
-            Network::Testnet => {
-                profile_config.rest_url =
-                    Some("https://fullnode.testnet.aptoslabs.com".to_string());
-                profile_config.faucet_url =
-                    Some("https://faucet.testnet.aptoslabs.com".to_string());
-            },
-            Network::Devnet => {
-                profile_config.rest_url = Some("https://fullnode.devnet.aptoslabs.com".to_string());
-                profile_config.faucet_url = Some("https://faucet.devnet.aptoslabs.com".to_string());
-            },
-            Network::Local => {
-                profile_config.rest_url = Some("http://localhost:8080".to_string());
-                profile_config.faucet_url = Some("http://localhost:8081".to_string());
-            },
-            Network::Custom => self.custom_network(&mut profile_config)?,
+            None => self.custom_network(&mut profile_config)?,
         }
 
         // Private key
@@ -208,8 +193,9 @@ impl CliCommand<()> for InitTool {
                 eprintln!("Account {} has been already found onchain", address);
             } else {
                 eprintln!(
-                    "Account {} doesn't exist, creating it and funding it with {} Octas",
-                    address, NUM_DEFAULT_OCTAS
+                    "Account {} doesn't exist, creating it and funding it with {}",
+                    address,
+                    Amount::from_octas(NUM_DEFAULT_OCTAS).display_with_symbol(APT_SYMBOL)
                 );
                 let hashes = fund_account(
                     Url::parse(faucet_url)
@@ -348,6 +334,26 @@ impl FromStr for Network {
     }
 }
 
+impl Network {
+    /// The network of the SDK, with its URLs. `None` for custom networks, whose URLs are
+    /// configured by the user.
+    pub fn sdk_network(&self) -> Option<aptos_sdk::network::Network> {
+        use aptos_sdk::network::Network as SdkNetwork;
+        match self {
+            Network::Mainnet => Some(SdkNetwork::Mainnet),
+            Network::Testnet => Some(SdkNetwork::Testnet),
+            Network::Devnet => Some(SdkNetwork::Devnet),
+            Network::Local => Some(SdkNetwork::Local),
+            Network::Custom => None,
+        }
+    }
+}
+
+/// `url` as written in profiles, without the trailing slash of an empty path.
+fn profile_url(url: &Url) -> String {
+    url.as_str().trim_end_matches('/').to_string()
+}
+
 impl Default for Network {
     fn default() -> Self {
         Self::Devnet
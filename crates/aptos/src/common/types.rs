@@ -19,13 +19,13 @@ use aptos_crypto::{
     x25519, PrivateKey, ValidCryptoMaterial, ValidCryptoMaterialStringExt,
 };
 use aptos_debugger::AptosDebugger;
-use aptos_gas_profiling::FrameName;
+use aptos_gas_profiling::{FrameName, GasBudget};
 use aptos_global_constants::adjust_gas_headroom;
 use aptos_keygen::KeyGen;
 use aptos_rest_client::{
-    aptos_api_types::{HashValue, ViewRequest},
+    aptos_api_types::{HashValue, UserTransaction, ViewRequest},
     error::RestError,
-    Client, Transaction,
+    Client, RequestAuth, Transaction,
 };
 use aptos_sdk::{transaction_builder::TransactionFactory, types::LocalAccount};
 use aptos_types::{
@@ -97,6 +97,8 @@ pub enum CliError {
     SimulationError(String),
     #[error("Coverage failed with status: {0}")]
     CoverageError(String),
+    #[error("Gas budget exceeded: {0}")]
+    GasBudgetError(String),
 }
 
 impl CliError {
@@ -117,6 +119,7 @@ impl CliError {
             CliError::UnexpectedError(_) => "UnexpectedError",
             CliError::SimulationError(_) => "SimulationError",
             CliError::CoverageError(_) => "CoverageError",
+            CliError::GasBudgetError(_) => "GasBudgetError",
         }
     }
 }
@@ -213,6 +216,9 @@ pub struct ProfileConfig {
     /// URL for the Faucet endpoint (if applicable)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub faucet_url: Option<String>,
+    /// Credentials for a rest endpoint behind an authenticated gateway
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rest_auth: Option<RequestAuth>,
 }
 
 /// ProfileConfig but without the private parts
@@ -907,11 +913,28 @@ impl RestOptions {
     }
 
     pub fn client(&self, profile: &ProfileOptions) -> CliTypedResult<Client> {
-        Ok(Client::new_with_timeout_and_user_agent(
+        let client = Client::new_with_timeout_and_user_agent(
             self.url(profile)?,
             Duration::from_secs(self.connection_timeout_secs),
             USER_AGENT,
-        ))
+        );
+        Ok(match self.auth(profile)? {
+            Some(auth) => client.with_auth(auth),
+            None => client,
+        })
+    }
+
+    /// Retrieve the credentials for the URL from the profile
+    pub fn auth(&self, profile: &ProfileOptions) -> CliTypedResult<Option<RequestAuth>> {
+        // Credentials are only for the profile's endpoint, not one given on the command line
+        if self.url.is_some() {
+            return Ok(None);
+        }
+        Ok(CliConfig::load_profile(
+            profile.profile_name(),
+            ConfigSearchMode::CurrentDirAndParents,
+        )?
+        .and_then(|p| p.rest_auth))
     }
 }
 
@@ -1306,14 +1329,21 @@ pub struct TransactionOptions {
     pub(crate) prompt_options: PromptOptions,
 
     /// If this option is set, simulate the transaction locally using the debugger and generate
-    /// flamegraphs that reflect the gas usage.
+    /// flamegraphs that reflect the gas usage, along with a JSON report attributing the gas to
+    /// the functions and modules that used it.
     #[clap(long)]
     pub(crate) profile_gas: bool,
+
+    /// JSON file with the max gas units functions can use, e.g.
+    /// `{ "0x42::market::place_order": 1500 }`. Requires `--profile-gas`, which then fails if any
+    /// of the functions goes over its budget.
+    #[clap(long, parse(from_os_str), requires = "profile_gas")]
+    pub(crate) gas_budget: Option<PathBuf>,
 }
 
 impl TransactionOptions {
     /// Builds a rest client
-    fn rest_client(&self) -> CliTypedResult<Client> {
+    pub(crate) fn rest_client(&self) -> CliTypedResult<Client> {
         self.rest_options.client(&self.profile_options)
     }
 
@@ -1463,6 +1493,48 @@ impl TransactionOptions {
         Ok(response.into_inner())
     }
 
+    /// Simulates a transaction without submitting it, estimating the gas unit price unless it's
+    /// given, and the max gas amount unless it's given.
+    pub async fn simulate_transaction(
+        &self,
+        payload: TransactionPayload,
+    ) -> CliTypedResult<UserTransaction> {
+        let client = self.rest_client()?;
+        let (sender_key, sender_address) = self.get_key_and_address()?;
+        let (account, state) = get_account_with_state(&client, sender_address).await?;
+
+        let mut transaction_factory = TransactionFactory::new(ChainId::new(state.chain_id))
+            .with_transaction_expiration_time(self.gas_options.expiration_secs);
+        if let Some(gas_unit_price) = self.gas_options.gas_unit_price {
+            transaction_factory = transaction_factory.with_gas_unit_price(gas_unit_price);
+        }
+        if let Some(max_gas) = self.gas_options.max_gas {
+            transaction_factory = transaction_factory.with_max_gas_amount(max_gas);
+        }
+        let unsigned_transaction = transaction_factory
+            .payload(payload)
+            .sender(sender_address)
+            .sequence_number(account.sequence_number)
+            .build();
+        let signed_transaction = SignedTransaction::new(
+            unsigned_transaction,
+            sender_key.public_key(),
+            Ed25519Signature::try_from([0u8; 64].as_ref()).unwrap(),
+        );
+
+        let txns = client
+            .simulate_with_gas_estimation(
+                &signed_transaction,
+                self.gas_options.max_gas.is_none(),
+                self.gas_options.gas_unit_price.is_none(),
+            )
+            .await?
+            .into_inner();
+        txns.into_iter().next().ok_or_else(|| {
+            CliError::UnexpectedError("The simulation returned no transaction".to_string())
+        })
+    }
+
     /// Simulate the transaction locally using the debugger, with the gas profiler enabled.
     pub async fn profile_gas(
         &self,
@@ -1603,8 +1675,33 @@ impl TransactionOptions {
             },
         }
 
+        // Generate the JSON report, to be checked by tools.
+        let gas_report = gas_log.to_gas_report();
+        create_dir!();
+        let report_file_path = Path::join(dir, format!("{}.json", raw_file_name));
+        let report_bytes = serde_json::to_vec_pretty(&gas_report)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        std::fs::write(&report_file_path, report_bytes).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Failed to write gas report to file {} : {:?}",
+                report_file_path.display(),
+                err
+            ))
+        })?;
+        println!("Gas report saved to {}", report_file_path.display());
+
         println!();
 
+        if let Some(gas_budget_file) = &self.gas_budget {
+            let gas_budget: GasBudget =
+                serde_json::from_slice(&read_from_file(gas_budget_file)?)
+                    .map_err(|err| CliError::UnableToParse("gas budget", err.to_string()))?;
+            let over_budget = gas_report.check_budget(&gas_budget);
+            if !over_budget.is_empty() {
+                return Err(CliError::GasBudgetError(over_budget.join(", ")));
+            }
+        }
+
         // Generate the transaction summary
 
         // TODO(Gas): double check if this is correct.
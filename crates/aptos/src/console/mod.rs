@@ -0,0 +1,489 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! An interactive console for calling view functions and inspecting resources of a network.
+
+use crate::{
+    common::types::{
+        load_account_arg, CliCommand, CliConfig, CliError, CliTypedResult, ConfigSearchMode,
+        ProfileOptions, RestOptions,
+    },
+    move_tool::{ArgWithType, MemberId},
+};
+use aptos_rest_client::{
+    aptos_api_types::{EntryFunctionId, MoveModule, MoveStructTag, MoveType, ViewRequest},
+    Client, TypeAccessor, TypeAccessorBuilder,
+};
+use aptos_types::account_address::AccountAddress;
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::{
+    language_storage::{ModuleId, StructTag, TypeTag},
+    parser::{parse_struct_tag, parse_type_tags},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, Write},
+    str::FromStr,
+};
+
+const COMMANDS: &[(&str, &str)] = &[
+    ("alias", "alias [<name> <address>]: list the address book, or add an address to it"),
+    ("modules", "modules <account>: list the modules of an account"),
+    (
+        "functions",
+        "functions <account>::<module>: list the exposed functions of a module",
+    ),
+    (
+        "view",
+        "view <account>::<module>::<function>[<type args>] [<type>:<arg>...]: call a view function, e.g. `view 0x1::coin::balance<0x1::aptos_coin::AptosCoin> address:alice`",
+    ),
+    ("resources", "resources <account>: list the resource types of an account"),
+    (
+        "resource",
+        "resource <account> <struct tag>: print a resource of an account",
+    ),
+    (
+        "complete",
+        "complete <prefix>: list the commands, names, modules or functions starting with a prefix",
+    ),
+    ("help", "help: print this help"),
+    ("exit", "exit: leave the console"),
+];
+
+/// Start an interactive console on the network of a profile
+///
+/// The console calls view functions and prints resources, decoded from the modules they're
+/// declared in. Accounts can be referred to by the names of the address book, which starts with
+/// the profiles of the config, both as arguments and in the place of addresses of module ids and
+/// types, e.g. `alice::coin`. Type `help` in the console for its commands.
+#[derive(Parser)]
+pub struct ConsoleTool {
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for ConsoleTool {
+    fn command_name(&self) -> &'static str {
+        "Console"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let mut console = Console::new(client, profile_address_book());
+        let mut line = String::new();
+        loop {
+            print!("aptos> ");
+            io::stdout()
+                .flush()
+                .map_err(|err| CliError::IO("stdout".to_string(), err))?;
+            // Stdin isn't locked across commands, as the future of the command must be `Send`
+            line.clear();
+            let read = io::stdin()
+                .read_line(&mut line)
+                .map_err(|err| CliError::IO("stdin".to_string(), err))?;
+            if read == 0 {
+                break;
+            }
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.first() {
+                None => continue,
+                Some(&"exit") | Some(&"quit") => break,
+                Some(_) => {},
+            }
+            match console.run_command(&words).await {
+                Ok(output) => println!("{}", output),
+                Err(err) => eprintln!("Error: {}", err),
+            }
+        }
+        Ok("Exited the console".to_string())
+    }
+}
+
+/// The accounts of the profiles of the config, by profile name
+fn profile_address_book() -> BTreeMap<String, AccountAddress> {
+    CliConfig::load(ConfigSearchMode::CurrentDirAndParents)
+        .ok()
+        .and_then(|config| config.profiles)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(name, profile)| Some((name, profile.account?)))
+        .collect()
+}
+
+struct Console {
+    client: Client,
+    address_book: BTreeMap<String, AccountAddress>,
+    /// The ABIs of the modules of the accounts listed so far
+    modules: BTreeMap<AccountAddress, Vec<MoveModule>>,
+    /// Decodes the resources printed so far, extended with the modules of each new type
+    accessor: TypeAccessor,
+}
+
+impl Console {
+    fn new(client: Client, address_book: BTreeMap<String, AccountAddress>) -> Self {
+        Self {
+            client,
+            address_book,
+            modules: BTreeMap::new(),
+            accessor: TypeAccessor::default(),
+        }
+    }
+
+    async fn run_command(&mut self, words: &[&str]) -> CliTypedResult<String> {
+        match words {
+            ["help"] => Ok(COMMANDS
+                .iter()
+                .map(|(_, usage)| *usage)
+                .collect::<Vec<_>>()
+                .join("\n")),
+            ["alias"] => Ok(self
+                .address_book
+                .iter()
+                .map(|(name, address)| format!("{} = {}", name, address.to_hex_literal()))
+                .collect::<Vec<_>>()
+                .join("\n")),
+            ["alias", name, address] => {
+                let address = self.address(address)?;
+                self.address_book.insert(name.to_string(), address);
+                Ok(format!("{} = {}", name, address.to_hex_literal()))
+            },
+            ["modules", account] => {
+                let address = self.address(account)?;
+                Ok(self
+                    .account_modules(address)
+                    .await?
+                    .iter()
+                    .map(|module| format!("{}::{}", address.to_hex_literal(), module.name))
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            },
+            ["functions", module_id] => {
+                let module = self.module(module_id).await?;
+                Ok(module
+                    .exposed_functions
+                    .iter()
+                    .map(|function| {
+                        let params: Vec<String> =
+                            function.params.iter().map(|ty| ty.to_string()).collect();
+                        let returns: Vec<String> =
+                            function.return_.iter().map(|ty| ty.to_string()).collect();
+                        let kind = match (function.is_view, function.is_entry) {
+                            (true, _) => "view ",
+                            (_, true) => "entry ",
+                            _ => "",
+                        };
+                        format!(
+                            "{}fun {}({}): ({})",
+                            kind,
+                            function.name,
+                            params.join(", "),
+                            returns.join(", ")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            },
+            ["view", function, args @ ..] => self.view(function, args).await,
+            ["resources", account] => {
+                let address = self.address(account)?;
+                let resources = self
+                    .client
+                    .get_account_resources_bcs(address)
+                    .await?
+                    .into_inner();
+                Ok(resources
+                    .into_keys()
+                    .map(|struct_tag| MoveStructTag::from(struct_tag).to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            },
+            ["resource", account, struct_tag] => self.resource(account, struct_tag).await,
+            ["complete", prefix] => Ok(self.complete(prefix).await?.join("\n")),
+            ["complete"] => Ok(self.complete("").await?.join("\n")),
+            [command, ..] => match COMMANDS.iter().find(|(name, _)| name == command) {
+                Some((_, usage)) => {
+                    Err(CliError::CommandArgumentError(format!("Usage: {}", usage)))
+                },
+                None => Err(CliError::CommandArgumentError(format!(
+                    "Unknown command {}, type `help` for the commands",
+                    command
+                ))),
+            },
+            [] => Ok(String::new()),
+        }
+    }
+
+    /// An address, a name of the address book or the name of a profile
+    fn address(&self, account: &str) -> CliTypedResult<AccountAddress> {
+        match self.address_book.get(account) {
+            Some(address) => Ok(*address),
+            None => load_account_arg(account),
+        }
+    }
+
+    /// Replaces the names of the address book in the place of addresses in `text`, e.g. in
+    /// `alice::coin::Coin<bob::coin::Bob>`
+    fn expand_names(&self, text: &str) -> String {
+        text.split_inclusive(|c| matches!(c, '<' | '>' | ','))
+            .map(|part| {
+                let trimmed = part.trim_start();
+                match trimmed
+                    .split_once("::")
+                    .and_then(|(name, rest)| Some((self.address_book.get(name)?, rest)))
+                {
+                    Some((address, rest)) => format!("{}::{}", address.to_hex_literal(), rest),
+                    None => part.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    async fn account_modules(&mut self, address: AccountAddress) -> CliTypedResult<&[MoveModule]> {
+        if !self.modules.contains_key(&address) {
+            let modules = self
+                .client
+                .get_account_modules(address)
+                .await?
+                .into_inner()
+                .into_iter()
+                .filter_map(|module| module.try_parse_abi().ok()?.abi)
+                .collect();
+            self.modules.insert(address, modules);
+        }
+        Ok(&self.modules[&address])
+    }
+
+    async fn module(&mut self, module_id: &str) -> CliTypedResult<&MoveModule> {
+        let (account, name) = module_id.split_once("::").ok_or_else(|| {
+            CliError::CommandArgumentError(format!(
+                "Module {} is not of the form <account>::<module>",
+                module_id
+            ))
+        })?;
+        let address = self.address(account)?;
+        self.account_modules(address)
+            .await?
+            .iter()
+            .find(|module| module.name.as_str() == name)
+            .ok_or_else(|| {
+                CliError::CommandArgumentError(format!(
+                    "Account {} has no module {}",
+                    address.to_hex_literal(),
+                    name
+                ))
+            })
+    }
+
+    async fn view(&self, function: &str, args: &[&str]) -> CliTypedResult<String> {
+        let function = self.expand_names(function);
+        let (function, type_args) = match function.split_once('<') {
+            Some((function, type_args)) => {
+                let type_args = type_args.strip_suffix('>').ok_or_else(|| {
+                    CliError::CommandArgumentError(format!(
+                        "Type arguments of {} aren't closed",
+                        function
+                    ))
+                })?;
+                (function.to_string(), parse_type_tags(type_args)?)
+            },
+            None => (function.clone(), vec![]),
+        };
+        let function = MemberId::from_str(&function)?;
+
+        let mut arguments = vec![];
+        for arg in args {
+            // Addresses may be names of the address book
+            let arg = match arg.split_once(':') {
+                Some(("address", account)) => {
+                    format!("address:{}", self.address(account)?.to_hex_literal())
+                },
+                _ => arg.to_string(),
+            };
+            arguments.push(ArgWithType::from_str(&arg)?.to_json()?);
+        }
+
+        let request = ViewRequest {
+            function: EntryFunctionId {
+                module: function.module_id.into(),
+                name: function.member_id.into(),
+            },
+            type_arguments: type_args.iter().map(MoveType::from).collect(),
+            arguments,
+        };
+        let values = self.client.view(&request, None).await?.into_inner();
+        serde_json::to_string_pretty(&values)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+
+    async fn resource(&mut self, account: &str, struct_tag: &str) -> CliTypedResult<String> {
+        let address = self.address(account)?;
+        let struct_tag = parse_struct_tag(&self.expand_names(struct_tag))?;
+        let bytes = self
+            .client
+            .get_account_resource_bytes(
+                address,
+                &MoveStructTag::from(struct_tag.clone()).to_string(),
+            )
+            .await?
+            .into_inner();
+
+        let mut module_ids = BTreeSet::new();
+        collect_module_ids(
+            &TypeTag::Struct(Box::new(struct_tag.clone())),
+            &mut module_ids,
+        );
+        let builder = module_ids.into_iter().fold(
+            TypeAccessorBuilder::new(self.client.clone()),
+            |builder, module_id| builder.add_module_id(module_id),
+        );
+        self.accessor = builder.extend(&self.accessor).await?;
+
+        let resource = self.accessor.view_resource(&struct_tag, &bytes)?;
+        serde_json::to_string_pretty(&resource)
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))
+    }
+
+    /// What `prefix` can be completed to: a command, a name of the address book, or if it has an
+    /// account followed by `::`, a module of the account or a function of the module
+    async fn complete(&mut self, prefix: &str) -> CliTypedResult<Vec<String>> {
+        let parts: Vec<&str> = prefix.split("::").collect();
+        let candidates = match parts.as_slice() {
+            [word] => COMMANDS
+                .iter()
+                .map(|(name, _)| name.to_string())
+                .chain(self.address_book.keys().cloned())
+                .filter(|candidate| candidate.starts_with(word))
+                .collect(),
+            [account, module] => {
+                let address = self.address(account)?;
+                self.account_modules(address)
+                    .await?
+                    .iter()
+                    .filter(|candidate| candidate.name.as_str().starts_with(module))
+                    .map(|candidate| format!("{}::{}", account, candidate.name))
+                    .collect()
+            },
+            [account, module, function] => {
+                let module_id = format!("{}::{}", account, module);
+                self.module(&module_id)
+                    .await?
+                    .exposed_functions
+                    .iter()
+                    .filter(|candidate| candidate.name.as_str().starts_with(function))
+                    .map(|candidate| format!("{}::{}", module_id, candidate.name))
+                    .collect()
+            },
+            _ => vec![],
+        };
+        Ok(candidates)
+    }
+}
+
+/// Collects the modules declaring the structs of `ty` and of its type arguments
+fn collect_module_ids(ty: &TypeTag, module_ids: &mut BTreeSet<ModuleId>) {
+    match ty {
+        TypeTag::Struct(struct_tag) => {
+            let StructTag {
+                address,
+                module,
+                type_params,
+                ..
+            } = struct_tag.as_ref();
+            module_ids.insert(ModuleId::new(*address, module.clone()));
+            for type_param in type_params {
+                collect_module_ids(type_param, module_ids);
+            }
+        },
+        TypeTag::Vector(items) => collect_module_ids(items, module_ids),
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn console() -> Console {
+        // Only commands which don't reach the network are run
+        Console::new(
+            Client::new("http://localhost:8080".parse().unwrap()),
+            BTreeMap::from([(
+                "alice".to_string(),
+                AccountAddress::from_hex_literal("0xa11ce").unwrap(),
+            )]),
+        )
+    }
+
+    async fn run(console: &mut Console, line: &str) -> CliTypedResult<String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        console.run_command(&words).await
+    }
+
+    #[tokio::test]
+    async fn test_address_book() {
+        let mut console = console();
+        assert_eq!(
+            run(&mut console, "alias bob 0xb0b").await.unwrap(),
+            "bob = 0xb0b"
+        );
+        // Names resolve to addresses, anything else is parsed as an address
+        assert_eq!(
+            run(&mut console, "alias carol bob").await.unwrap(),
+            "carol = 0xb0b"
+        );
+        assert_eq!(
+            run(&mut console, "alias").await.unwrap(),
+            "alice = 0xa11ce\nbob = 0xb0b\ncarol = 0xb0b"
+        );
+        assert!(run(&mut console, "alias dave nobody").await.is_err());
+
+        assert_eq!(
+            console.expand_names("alice::coin::Coin<bob::coin::Bob, 0x1::aptos_coin::AptosCoin>"),
+            "0xa11ce::coin::Coin<0xb0b::coin::Bob, 0x1::aptos_coin::AptosCoin>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let mut console = console();
+        assert_eq!(
+            run(&mut console, "help").await.unwrap().lines().count(),
+            COMMANDS.len()
+        );
+        assert_eq!(
+            run(&mut console, "complete a").await.unwrap(),
+            "alias\nalice"
+        );
+        assert_eq!(
+            run(&mut console, "complete re").await.unwrap(),
+            "resources\nresource"
+        );
+
+        let err = run(&mut console, "resource alice").await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Usage: resource <account> <struct tag>"));
+        let err = run(&mut console, "transfer alice").await.unwrap_err();
+        assert!(err.to_string().contains("Unknown command transfer"));
+    }
+
+    #[test]
+    fn test_collect_module_ids() {
+        let mut module_ids = BTreeSet::new();
+        collect_module_ids(
+            &TypeTag::from_str("vector<0x1::coin::CoinStore<0xcafe::market::Token>>").unwrap(),
+            &mut module_ids,
+        );
+        assert_eq!(
+            module_ids
+                .iter()
+                .map(|module_id| module_id.short_str_lossless())
+                .collect::<Vec<_>>(),
+            vec!["0x1::coin", "0xcafe::market"]
+        );
+    }
+}
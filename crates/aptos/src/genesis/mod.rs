@@ -256,6 +256,9 @@ pub fn fetch_mainnet_genesis_info(git_options: GitOptions) -> CliTypedResult<Mai
             employee_vesting_period_duration: layout.employee_vesting_period_duration,
             consensus_config: OnChainConsensusConfig::default(),
             gas_schedule: default_gas_schedule(),
+            initial_features_override: None,
+            initial_balances: vec![],
+            imported_state: None,
         },
     )?)
 }
@@ -295,6 +298,9 @@ pub fn fetch_genesis_info(git_options: GitOptions) -> CliTypedResult<GenesisInfo
             employee_vesting_period_duration: layout.employee_vesting_period_duration,
             consensus_config: OnChainConsensusConfig::default(),
             gas_schedule: default_gas_schedule(),
+            initial_features_override: None,
+            initial_balances: vec![],
+            imported_state: None,
         },
     )?)
 }
@@ -19,7 +19,7 @@ use aptos_crypto::HashValue;
 use aptos_framework::{BuildOptions, BuiltPackage, ReleasePackage};
 use aptos_logger::warn;
 use aptos_rest_client::{
-    aptos_api_types::{Address, HexEncodedBytes, U128, U64},
+    aptos_api_types::{well_known, Address, HexEncodedBytes, U128, U64},
     Client, Transaction,
 };
 use aptos_sdk::move_types::language_storage::CORE_CODE_ADDRESS;
@@ -527,7 +527,10 @@ impl CliCommand<Vec<TransactionSummary>> for SubmitVote {
             }
 
             let stake_pool = client
-                .get_account_resource_bcs::<StakePool>(pool_address, "0x1::stake::StakePool")
+                .get_account_resource_bcs::<StakePool>(
+                    pool_address,
+                    &well_known::stake_pool().to_string(),
+                )
                 .await?
                 .into_inner();
             let voting_power = stake_pool.get_governance_voting_power();
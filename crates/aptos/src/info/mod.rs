@@ -0,0 +1,21 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{CliCommand, CliResult};
+use clap::Parser;
+
+pub mod network;
+
+/// Tool for diagnosing the environment of the CLI
+#[derive(Parser)]
+pub enum InfoCommand {
+    Network(network::NetworkInfo),
+}
+
+impl InfoCommand {
+    pub async fn execute(self) -> CliResult {
+        match self {
+            InfoCommand::Network(tool) => tool.execute_serialized().await,
+        }
+    }
+}
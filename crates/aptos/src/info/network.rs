@@ -0,0 +1,447 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::common::types::{
+    CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions, USER_AGENT,
+};
+use aptos_rest_client::{aptos_api_types::IndexResponse, Client, RequestAuth};
+use async_trait::async_trait;
+use clap::Parser;
+use reqwest::{header::HeaderMap, StatusCode, Url};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Ledger timestamps further behind the local clock than this mean the node isn't keeping up
+const MAX_LEDGER_LAG_SECS: u64 = 30;
+/// Median latencies above this are worth looking into
+const SLOW_LATENCY_MS: u64 = 1000;
+
+/// Optional API features, by the path serving them in the OpenAPI spec of the node
+const API_FEATURES: &[(&str, &str)] = &[
+    ("view functions", "/view"),
+    ("gas estimation", "/estimate_gas_price"),
+    ("batch transaction submission", "/transactions/batch"),
+    ("batch reads", "/batch"),
+    ("account state keys", "/accounts/{address}/state_keys"),
+    (
+        "resource groups",
+        "/accounts/{address}/resource_group/{resource_group_type}",
+    ),
+    ("raw table items", "/tables/{table_handle}/raw_item"),
+];
+
+/// Diagnose the fullnode of a profile
+///
+/// Measures how far behind its ledger is, the latency of its API, the rate limit quota left and
+/// which optional API features it supports, then suggests what to look into. Useful to tell
+/// whether a problem is with the node or with the code calling it.
+#[derive(Debug, Parser)]
+pub struct NetworkInfo {
+    /// Other fullnodes of the same network to compare the ledger of the node against
+    #[clap(long, multiple_values = true)]
+    pub(crate) compare_with: Vec<Url>,
+
+    /// Number of requests to time for the latency percentiles
+    #[clap(long, default_value_t = 10)]
+    pub(crate) samples: usize,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// The health of the node, and of the nodes it was compared with
+#[derive(Debug, Serialize)]
+pub struct NetworkDiagnostics {
+    pub node: NodeDiagnostics,
+    pub compared_with: Vec<NodeDiagnostics>,
+    /// What to look into, based on the diagnostics
+    pub advice: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NodeDiagnostics {
+    pub url: String,
+    /// The ledger of the node, if it could be reached
+    pub ledger: Option<IndexResponse>,
+    /// How far the ledger timestamp is behind the local clock
+    pub ledger_lag_secs: Option<u64>,
+    /// How many versions the ledger is behind the most recent of the compared nodes
+    pub versions_behind: Option<u64>,
+    pub latency: Option<LatencyPercentiles>,
+    pub requests: usize,
+    pub failed_requests: usize,
+    pub rate_limited_requests: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimit>,
+    /// Which optional API features the node supports, if it serves its OpenAPI spec
+    pub features: Option<BTreeMap<&'static str, bool>>,
+    /// The first error of each kind, if any request failed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<String>,
+}
+
+/// Latencies of the successful requests, in milliseconds
+#[derive(Debug, Serialize)]
+pub struct LatencyPercentiles {
+    pub min: u64,
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub max: u64,
+}
+
+impl LatencyPercentiles {
+    fn new(mut latencies: Vec<u64>) -> Option<Self> {
+        latencies.sort_unstable();
+        // Nearest rank
+        let percentile = |p: usize| latencies[((latencies.len() * p + 99) / 100).max(1) - 1];
+        Some(Self {
+            min: *latencies.first()?,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: *latencies.last()?,
+        })
+    }
+}
+
+/// The quota of the last response, from the `x-ratelimit-*` headers gateways commonly send
+#[derive(Debug, Serialize)]
+pub struct RateLimit {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// When the quota resets, as sent by the gateway
+    pub reset: Option<String>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let rate_limit = Self {
+            limit: header("x-ratelimit-limit").and_then(|value| value.parse().ok()),
+            remaining: header("x-ratelimit-remaining").and_then(|value| value.parse().ok()),
+            reset: header("x-ratelimit-reset"),
+        };
+        (rate_limit.limit.is_some() || rate_limit.remaining.is_some()).then_some(rate_limit)
+    }
+}
+
+#[async_trait]
+impl CliCommand<NetworkDiagnostics> for NetworkInfo {
+    fn command_name(&self) -> &'static str {
+        "NetworkInfo"
+    }
+
+    async fn execute(self) -> CliTypedResult<NetworkDiagnostics> {
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(
+                self.rest_options.connection_timeout_secs,
+            ))
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let prober = Prober {
+            http,
+            samples: self.samples.max(1),
+        };
+
+        let mut node = prober
+            .probe(
+                &self.rest_options.client(&self.profile_options)?,
+                self.rest_options.auth(&self.profile_options)?,
+            )
+            .await?;
+        let mut compared_with = vec![];
+        for url in self.compare_with {
+            compared_with.push(prober.probe(&Client::new(url), None).await?);
+        }
+
+        let latest = compared_with
+            .iter()
+            .chain(std::iter::once(&node))
+            .filter_map(|node| node.ledger.as_ref())
+            .map(|ledger| ledger.ledger_version.0)
+            .max();
+        if !compared_with.is_empty() {
+            for node in compared_with.iter_mut().chain(std::iter::once(&mut node)) {
+                node.versions_behind = node
+                    .ledger
+                    .as_ref()
+                    .zip(latest)
+                    .map(|(ledger, latest)| latest - ledger.ledger_version.0);
+            }
+        }
+
+        let advice = advise(&node, &compared_with);
+        Ok(NetworkDiagnostics {
+            node,
+            compared_with,
+            advice,
+        })
+    }
+}
+
+struct Prober {
+    http: reqwest::Client,
+    samples: usize,
+}
+
+impl Prober {
+    /// Times requests for the ledger info of the node behind `client`, then fetches its spec
+    async fn probe(
+        &self,
+        client: &Client,
+        auth: Option<RequestAuth>,
+    ) -> CliTypedResult<NodeDiagnostics> {
+        let mut diagnostics = NodeDiagnostics {
+            url: client.path_prefix_string(),
+            ledger: None,
+            ledger_lag_secs: None,
+            versions_behind: None,
+            latency: None,
+            requests: self.samples,
+            failed_requests: 0,
+            rate_limited_requests: 0,
+            rate_limit: None,
+            features: None,
+            errors: vec![],
+        };
+        let index_url = client
+            .build_path("")
+            .map_err(|err| CliError::UnableToParse("Rest URL", err.to_string()))?;
+
+        let mut latencies = vec![];
+        for _ in 0..self.samples {
+            let start = Instant::now();
+            let result = self.get(&index_url, auth.as_ref()).await;
+            let elapsed = start.elapsed().as_millis() as u64;
+            let response = match result {
+                Ok(response) => response,
+                Err(err) => {
+                    diagnostics.record_error(err);
+                    continue;
+                },
+            };
+            if let Some(rate_limit) = RateLimit::from_headers(response.headers()) {
+                diagnostics.rate_limit = Some(rate_limit);
+            }
+            let status = response.status();
+            if !status.is_success() {
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    diagnostics.rate_limited_requests += 1;
+                }
+                diagnostics.record_error(format!("GET {} returned {}", index_url, status));
+                continue;
+            }
+            match response.json::<IndexResponse>().await {
+                Ok(ledger) => {
+                    latencies.push(elapsed);
+                    diagnostics.ledger = Some(ledger);
+                },
+                Err(err) => diagnostics.record_error(format!(
+                    "GET {} returned an unexpected body: {}",
+                    index_url, err
+                )),
+            }
+        }
+        diagnostics.latency = LatencyPercentiles::new(latencies);
+
+        if let Some(ledger) = &diagnostics.ledger {
+            let now_usecs = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?
+                .as_micros() as u64;
+            diagnostics.ledger_lag_secs =
+                Some(now_usecs.saturating_sub(ledger.ledger_timestamp.0) / 1_000_000);
+            diagnostics.features = self.features(client, auth.as_ref()).await;
+        }
+        Ok(diagnostics)
+    }
+
+    /// Reads the optional features off the paths of the OpenAPI spec of the node
+    async fn features(
+        &self,
+        client: &Client,
+        auth: Option<&RequestAuth>,
+    ) -> Option<BTreeMap<&'static str, bool>> {
+        let response = self
+            .get(&client.build_path("spec.json").ok()?, auth)
+            .await
+            .ok()?;
+        let spec = response.json::<serde_json::Value>().await.ok()?;
+        let paths = spec.get("paths")?.as_object()?;
+        Some(
+            API_FEATURES
+                .iter()
+                .map(|(feature, path)| (*feature, paths.contains_key(*path)))
+                .collect(),
+        )
+    }
+
+    async fn get(
+        &self,
+        url: &Url,
+        auth: Option<&RequestAuth>,
+    ) -> Result<reqwest::Response, String> {
+        let mut request = self
+            .http
+            .get(url.clone())
+            .build()
+            .map_err(|err| err.to_string())?;
+        if let Some(auth) = auth {
+            auth.apply(&mut request).map_err(|err| err.to_string())?;
+        }
+        self.http.execute(request).await.map_err(|err| {
+            if err.is_timeout() {
+                format!("GET {} timed out", url)
+            } else {
+                format!("GET {} failed: {}", url, err)
+            }
+        })
+    }
+}
+
+impl NodeDiagnostics {
+    fn record_error(&mut self, error: String) {
+        self.failed_requests += 1;
+        if !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+}
+
+/// Turns the diagnostics into things to look into, most important first
+fn advise(node: &NodeDiagnostics, compared_with: &[NodeDiagnostics]) -> Vec<String> {
+    let mut advice = vec![];
+    let ledger = match &node.ledger {
+        Some(ledger) => ledger,
+        None => {
+            advice.push(format!(
+                "{} could not be reached: check the URL of the profile and the network access of \
+                 this machine, or the node may be down (see `errors`)",
+                node.url
+            ));
+            if node.rate_limited_requests > 0 {
+                advice.push(rate_limited_advice(node));
+            }
+            return advice;
+        },
+    };
+
+    for other in compared_with {
+        match &other.ledger {
+            Some(other_ledger) if other_ledger.chain_id != ledger.chain_id => advice.push(format!(
+                "{} is on chain {} but {} is on chain {}: they are different networks",
+                node.url, ledger.chain_id, other.url, other_ledger.chain_id
+            )),
+            Some(_) => (),
+            None => advice.push(format!(
+                "{} could not be reached, so it wasn't compared",
+                other.url
+            )),
+        }
+    }
+    if let Some(versions_behind) = node.versions_behind.filter(|behind| *behind > 0) {
+        advice.push(format!(
+            "{} is {} versions behind the most recent of the compared nodes: reads from it may be \
+             stale, and transactions may take longer to show up",
+            node.url, versions_behind
+        ));
+    }
+    if let Some(lag) = node
+        .ledger_lag_secs
+        .filter(|lag| *lag > MAX_LEDGER_LAG_SECS)
+    {
+        advice.push(format!(
+            "The ledger of {} is {}s behind the local clock: the node may be catching up or the \
+             network halted, otherwise check the clock of this machine",
+            node.url, lag
+        ));
+    }
+
+    if let Some(latency) = node.latency.as_ref().filter(|l| l.p50 > SLOW_LATENCY_MS) {
+        advice.push(format!(
+            "The median latency of {} is {}ms: the node may be overloaded or far away, a closer \
+             fullnode would be faster",
+            node.url, latency.p50
+        ));
+    }
+    if node.failed_requests > node.rate_limited_requests {
+        advice.push(format!(
+            "{} of {} requests to {} failed: the node or the connection to it is unreliable (see \
+             `errors`), so calls to it need retries",
+            node.failed_requests - node.rate_limited_requests,
+            node.requests,
+            node.url
+        ));
+    }
+    if node.rate_limited_requests > 0 {
+        advice.push(rate_limited_advice(node));
+    } else if let Some(RateLimit {
+        limit: Some(limit),
+        remaining: Some(remaining),
+        ..
+    }) = &node.rate_limit
+    {
+        if remaining * 10 < *limit {
+            advice.push(format!(
+                "Only {} of the {} requests of the rate limit of {} are left: requests will soon \
+                 be rejected with 429s",
+                remaining, limit, node.url
+            ));
+        }
+    }
+
+    match &node.features {
+        Some(features) => {
+            let missing = features
+                .iter()
+                .filter(|(_, supported)| !**supported)
+                .map(|(feature, _)| *feature)
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                advice.push(format!(
+                    "{} doesn't support {}: it runs an older version of the node{}, calls to \
+                     these endpoints will return 404s",
+                    node.url,
+                    missing.join(", "),
+                    ledger
+                        .git_hash
+                        .as_ref()
+                        .map(|hash| format!(" (built from {})", hash))
+                        .unwrap_or_default()
+                ));
+            }
+        },
+        None => advice.push(format!(
+            "{} doesn't serve its OpenAPI spec, so its supported features are unknown",
+            node.url
+        )),
+    }
+
+    if advice.is_empty() {
+        advice.push(format!(
+            "No problems found with {}: if requests still fail, look into the calling code",
+            node.url
+        ));
+    }
+    advice
+}
+
+fn rate_limited_advice(node: &NodeDiagnostics) -> String {
+    format!(
+        "{} requests to {} were rate limited: add credentials for the gateway with a `rest_auth` \
+         entry in the profile, or send fewer requests",
+        node.rate_limited_requests, node.url
+    )
+}
@@ -6,8 +6,10 @@
 pub mod account;
 pub mod common;
 pub mod config;
+pub mod console;
 pub mod genesis;
 pub mod governance;
+pub mod info;
 pub mod move_tool;
 pub mod node;
 pub mod op;
@@ -32,6 +34,7 @@ pub enum Tool {
     Account(account::AccountTool),
     #[clap(subcommand)]
     Config(config::ConfigTool),
+    Console(console::ConsoleTool),
     #[clap(subcommand)]
     Genesis(genesis::GenesisTool),
     #[clap(subcommand)]
@@ -55,8 +58,12 @@ impl Tool {
         match self {
             Account(tool) => tool.execute().await,
             Config(tool) => tool.execute().await,
+            Console(tool) => tool.execute_serialized_without_logger().await,
             Genesis(tool) => tool.execute().await,
             Governance(tool) => tool.execute().await,
+            Info(InfoTool {
+                command: Some(command),
+            }) => command.execute().await,
             Info(tool) => tool.execute_serialized().await,
             // TODO: Replace entirely with config init
             Init(tool) => tool.execute_serialized_success().await,
@@ -71,9 +78,13 @@ impl Tool {
 
 /// Show build information about the CLI
 ///
-/// This is useful for debugging as well as determining what versions are compatible with the CLI
+/// This is useful for debugging as well as determining what versions are compatible with the CLI.
+/// `aptos info network` diagnoses the fullnode of a profile instead.
 #[derive(Parser)]
-pub struct InfoTool {}
+pub struct InfoTool {
+    #[clap(subcommand)]
+    command: Option<info::InfoCommand>,
+}
 
 #[async_trait]
 impl CliCommand<BTreeMap<String, String>> for InfoTool {
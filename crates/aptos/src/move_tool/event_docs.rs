@@ -0,0 +1,554 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Documentation of the events of a package: the fields of each event type, the functions
+//! emitting it and the event handles it is emitted to.
+
+use crate::{
+    common::{
+        types::{
+            AccountAddressWrapper, CliCommand, CliError, CliTypedResult, ProfileOptions,
+            RestOptions,
+        },
+        utils::write_to_file,
+    },
+    move_tool::rust_client::parse_package_id,
+};
+use anyhow::{anyhow, Result};
+use aptos_framework::{BuildOptions, BuiltPackage};
+use aptos_rest_client::{
+    aptos_api_types::{Bytecode as _, MoveModule, MoveStructTag, MoveType},
+    TypeAccessor, TypeAccessorBuilder,
+};
+use async_trait::async_trait;
+use clap::{ArgEnum, Parser};
+use move_binary_format::{file_format::Bytecode, CompiledModule};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::{ModuleId, CORE_CODE_ADDRESS},
+};
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter, Write},
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Functions of `0x1::event` emitting their type argument as an event.
+const EMIT_FUNCTIONS: &[&str] = &["emit", "emit_event"];
+
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum EventDocsFormat {
+    Markdown,
+    Json,
+}
+
+impl Display for EventDocsFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EventDocsFormat::Markdown => "markdown",
+            EventDocsFormat::Json => "json",
+        })
+    }
+}
+
+impl FromStr for EventDocsFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "markdown" => Ok(EventDocsFormat::Markdown),
+            "json" => Ok(EventDocsFormat::Json),
+            _ => Err("Invalid format. Valid values are markdown, json"),
+        }
+    }
+}
+
+/// Documents the events of a Move package
+///
+/// For each event type emitted by the modules of the package, lists its fields, the functions
+/// emitting it, and the fields of the structs holding event handles for it. Types of other
+/// packages are resolved as well, so the fields of framework events are documented too.
+#[derive(Parser)]
+pub struct DocumentEvents {
+    /// The package to document: the path to a local package, or `<address>::<package name>` for a
+    /// published package
+    ///
+    /// The address can also be a profile name.
+    #[clap(long)]
+    pub(crate) package: String,
+
+    /// Named addresses for building a local package
+    ///
+    /// Example: alice=0x1234, bob=0x5678
+    #[clap(long, parse(try_from_str = crate::common::utils::parse_map), default_value = "")]
+    pub(crate) named_addresses: BTreeMap<String, AccountAddressWrapper>,
+
+    /// Format of the documentation: [markdown, json]
+    #[clap(long, default_value_t = EventDocsFormat::Markdown)]
+    pub(crate) format: EventDocsFormat,
+
+    /// File to write the documentation to, rather than returning it
+    #[clap(long, parse(from_os_str))]
+    pub(crate) output_file: Option<PathBuf>,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+#[async_trait]
+impl CliCommand<String> for DocumentEvents {
+    fn command_name(&self) -> &'static str {
+        "DocumentEvents"
+    }
+
+    async fn execute(self) -> CliTypedResult<String> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let path = PathBuf::from(&self.package);
+        let (package_name, package_modules, dependencies) = if path.is_dir() {
+            let options = BuildOptions {
+                named_addresses: self
+                    .named_addresses
+                    .iter()
+                    .map(|(name, address)| (name.clone(), address.account_address))
+                    .collect(),
+                ..BuildOptions::default()
+            };
+            let package = BuiltPackage::build(path, options)?;
+            let package_modules: Vec<_> = package.modules().cloned().collect();
+            let dependencies: Vec<_> = package.all_modules().cloned().collect();
+            (package.name().to_string(), package_modules, dependencies)
+        } else {
+            let (address, name) = parse_package_id(&self.package)?;
+            let bundle = client.get_package(address, &name).await?.into_inner();
+            (
+                format!("{}::{}", address.to_hex_literal(), name),
+                bundle.compiled_modules().cloned().collect(),
+                vec![],
+            )
+        };
+
+        // Modules of the package and of its local dependencies are known, others are fetched
+        let accessor = TypeAccessorBuilder::new(client)
+            .add_modules(package_modules.iter().cloned().map(MoveModule::from))
+            .add_modules(dependencies.into_iter().map(MoveModule::from))
+            .build()
+            .await?;
+        let events = document_events(&package_modules, &accessor)?;
+
+        let docs = match self.format {
+            EventDocsFormat::Markdown => render_markdown(&package_name, &events)?,
+            EventDocsFormat::Json => serde_json::to_string_pretty(&events)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+        };
+        match self.output_file {
+            Some(output_file) => {
+                write_to_file(
+                    &output_file,
+                    &output_file.display().to_string(),
+                    docs.as_bytes(),
+                )?;
+                Ok(output_file.display().to_string())
+            },
+            None => Ok(docs),
+        }
+    }
+}
+
+/// An event type emitted by a package.
+#[derive(Debug, Serialize)]
+pub struct EventDoc {
+    #[serde(rename = "type")]
+    pub typ: MoveType,
+    /// `None` if the event type isn't a struct, or its module couldn't be resolved.
+    pub fields: Option<Vec<EventFieldDoc>>,
+    /// The functions calling `0x1::event` to emit the event, as `<module id>::<function>`.
+    pub emitted_by: BTreeSet<String>,
+    /// The fields holding event handles for the event, as `<struct tag>.<field>`.
+    pub handles: BTreeSet<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventFieldDoc {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: MoveType,
+}
+
+/// The events emitted by `modules`, by type.
+fn document_events(modules: &[CompiledModule], accessor: &TypeAccessor) -> Result<Vec<EventDoc>> {
+    let mut handles: BTreeMap<String, (MoveType, BTreeSet<String>)> = BTreeMap::new();
+    let mut emitted_by: BTreeMap<String, (MoveType, BTreeSet<String>)> = BTreeMap::new();
+    for module in modules {
+        let abi = MoveModule::from(module.clone());
+        for s in &abi.structs {
+            for field in &s.fields {
+                if let Some(event) = event_handle_type(&field.typ) {
+                    handles
+                        .entry(event.to_string())
+                        .or_insert_with(|| (event.clone(), BTreeSet::new()))
+                        .1
+                        .insert(format!(
+                            "{}::{}::{}.{}",
+                            abi.address, abi.name, s.name, field.name
+                        ));
+                }
+            }
+        }
+        for (function, event) in emitting_functions(module) {
+            emitted_by
+                .entry(event.to_string())
+                .or_insert_with(|| (event.clone(), BTreeSet::new()))
+                .1
+                .insert(function);
+        }
+    }
+
+    let types: BTreeSet<String> = handles.keys().chain(emitted_by.keys()).cloned().collect();
+    types
+        .into_iter()
+        .map(|key| {
+            let (typ, handles) = handles
+                .remove(&key)
+                .unwrap_or_else(|| (emitted_by[&key].0.clone(), BTreeSet::new()));
+            let emitted_by = emitted_by
+                .remove(&key)
+                .map(|(_, functions)| functions)
+                .unwrap_or_default();
+            Ok(EventDoc {
+                fields: event_fields(&typ, accessor)?,
+                typ,
+                emitted_by,
+                handles,
+            })
+        })
+        .collect()
+}
+
+/// The type of the events of `ty`, if it is an `0x1::event::EventHandle`.
+fn event_handle_type(ty: &MoveType) -> Option<&MoveType> {
+    match ty {
+        MoveType::Struct(tag) if is_event_member(tag, "EventHandle") => {
+            tag.generic_type_params.first()
+        },
+        _ => None,
+    }
+}
+
+fn is_event_member(tag: &MoveStructTag, name: &str) -> bool {
+    AccountAddress::from(tag.address) == CORE_CODE_ADDRESS
+        && tag.module.as_str() == "event"
+        && tag.name.as_str() == name
+}
+
+/// The functions of `module` calling `0x1::event` to emit an event, along with the type of the
+/// event.
+fn emitting_functions(module: &CompiledModule) -> Vec<(String, MoveType)> {
+    let mut emitting = vec![];
+    for def in &module.function_defs {
+        let code = match &def.code {
+            Some(code) => code,
+            None => continue,
+        };
+        let function = format!(
+            "{}::{}",
+            // By path, as the methods of `ModuleAccess` clash with the ones of the API `Bytecode`
+            move_binary_format::access::ModuleAccess::self_id(module).short_str_lossless(),
+            module.identifier_at(module.function_handle_at(def.function).name)
+        );
+        for instruction in &code.code {
+            let instantiation = match instruction {
+                Bytecode::CallGeneric(index) => &module.function_instantiations[index.0 as usize],
+                _ => continue,
+            };
+            let handle = module.function_handle_at(instantiation.handle);
+            let callee_module = module.module_handle_at(handle.module);
+            let callee = ModuleId::new(
+                *module.address_identifier_at(callee_module.address),
+                module.identifier_at(callee_module.name).to_owned(),
+            );
+            if callee.address() != &CORE_CODE_ADDRESS
+                || callee.name().as_str() != "event"
+                || !EMIT_FUNCTIONS.contains(&module.identifier_at(handle.name).as_str())
+            {
+                continue;
+            }
+            if let Some(event) = module.signature_at(instantiation.type_parameters).0.first() {
+                emitting.push((function.clone(), module.new_move_type(event)));
+            }
+        }
+    }
+    emitting
+}
+
+/// The fields of the event type `ty`, with the type arguments of `ty` substituted.
+fn event_fields(ty: &MoveType, accessor: &TypeAccessor) -> Result<Option<Vec<EventFieldDoc>>> {
+    let tag = match ty {
+        MoveType::Struct(tag) => tag,
+        _ => return Ok(None),
+    };
+    let module_id = ModuleId::new(tag.address.into(), tag.module.0.clone());
+    let fields = match accessor.struct_field_types(&module_id, &tag.name.0) {
+        Some(fields) => fields,
+        None => return Ok(None),
+    };
+    fields
+        .into_iter()
+        .map(|(name, typ)| {
+            Ok(EventFieldDoc {
+                name: name.to_string(),
+                typ: substitute(&typ, &tag.generic_type_params)?,
+            })
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+/// `ty` with its generic type parameters replaced by `ty_args`.
+fn substitute(ty: &MoveType, ty_args: &[MoveType]) -> Result<MoveType> {
+    Ok(match ty {
+        MoveType::GenericTypeParam { index } => ty_args
+            .get(*index as usize)
+            .cloned()
+            .ok_or_else(|| anyhow!("Missing type argument {} for {}", index, ty))?,
+        MoveType::Vector { items } => MoveType::Vector {
+            items: Box::new(substitute(items, ty_args)?),
+        },
+        MoveType::Reference { mutable, to } => MoveType::Reference {
+            mutable: *mutable,
+            to: Box::new(substitute(to, ty_args)?),
+        },
+        MoveType::Struct(tag) => MoveType::Struct(MoveStructTag::new(
+            tag.address,
+            tag.module.clone(),
+            tag.name.clone(),
+            tag.generic_type_params
+                .iter()
+                .map(|ty| substitute(ty, ty_args))
+                .collect::<Result<_>>()?,
+        )),
+        ty => ty.clone(),
+    })
+}
+
+fn render_markdown(package_name: &str, events: &[EventDoc]) -> Result<String> {
+    let mut out = String::new();
+    writeln!(out, "# Events of `{}`\n", package_name)?;
+    if events.is_empty() {
+        writeln!(out, "The package emits no events.")?;
+    }
+    for event in events {
+        writeln!(out, "## `{}`\n", event.typ)?;
+        match &event.fields {
+            Some(fields) => {
+                writeln!(out, "| Field | Type |\n| --- | --- |")?;
+                for field in fields {
+                    writeln!(out, "| `{}` | `{}` |", field.name, field.typ)?;
+                }
+            },
+            None => writeln!(out, "The fields of this type are unknown.")?,
+        }
+        writeln!(out)?;
+        if !event.emitted_by.is_empty() {
+            writeln!(out, "Emitted by:\n")?;
+            for function in &event.emitted_by {
+                writeln!(out, "- `{}`", function)?;
+            }
+            writeln!(out)?;
+        }
+        if !event.handles.is_empty() {
+            writeln!(out, "Event handles:\n")?;
+            for handle in &event.handles {
+                writeln!(out, "- `{}`", handle)?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::{
+        empty_module, Ability, AbilitySet, AddressIdentifierIndex, CodeUnit, FieldDefinition,
+        FunctionDefinition, FunctionHandle, FunctionHandleIndex, FunctionInstantiation,
+        FunctionInstantiationIndex, IdentifierIndex, ModuleHandle, ModuleHandleIndex, Signature,
+        SignatureIndex, SignatureToken, StructDefinition, StructFieldInformation, StructHandle,
+        StructHandleIndex, StructTypeParameter, TypeSignature, Visibility,
+    };
+    use move_core_types::identifier::Identifier;
+
+    /// The bytecode of:
+    ///
+    /// ```move
+    /// module 0xcafe::market {
+    ///     struct Sold has drop, store { price: u64 }
+    ///     struct Store has key { sold_events: EventHandle<Sold> }
+    ///
+    ///     fun sell() { event::emit<Sold>(..) }
+    /// }
+    /// ```
+    fn market_module() -> CompiledModule {
+        let mut module = empty_module();
+        module.address_identifiers = vec![
+            AccountAddress::from_hex_literal("0xcafe").unwrap(),
+            CORE_CODE_ADDRESS,
+        ];
+        module.identifiers = [
+            "market",
+            "event",
+            "EventHandle",
+            "emit",
+            "Sold",
+            "Store",
+            "price",
+            "sold_events",
+            "sell",
+        ]
+        .iter()
+        .map(|name| Identifier::new(*name).unwrap())
+        .collect();
+        module.module_handles.push(ModuleHandle {
+            address: AddressIdentifierIndex(1),
+            name: IdentifierIndex(1),
+        });
+        let struct_handle = |module, name, abilities, type_parameters| StructHandle {
+            module: ModuleHandleIndex(module),
+            name: IdentifierIndex(name),
+            abilities,
+            type_parameters,
+        };
+        module.struct_handles = vec![
+            struct_handle(1, 2, AbilitySet::singleton(Ability::Store), vec![
+                StructTypeParameter {
+                    constraints: AbilitySet::EMPTY,
+                    is_phantom: true,
+                },
+            ]),
+            struct_handle(
+                0,
+                4,
+                AbilitySet::singleton(Ability::Drop) | Ability::Store,
+                vec![],
+            ),
+            struct_handle(0, 5, AbilitySet::singleton(Ability::Key), vec![]),
+        ];
+        let struct_def = |handle, field, ty| StructDefinition {
+            struct_handle: StructHandleIndex(handle),
+            field_information: StructFieldInformation::Declared(vec![FieldDefinition {
+                name: IdentifierIndex(field),
+                signature: TypeSignature(ty),
+            }]),
+        };
+        let sold = SignatureToken::Struct(StructHandleIndex(1));
+        module.struct_defs = vec![
+            struct_def(1, 6, SignatureToken::U64),
+            struct_def(
+                2,
+                7,
+                SignatureToken::StructInstantiation(StructHandleIndex(0), vec![sold.clone()]),
+            ),
+        ];
+        module.signatures.push(Signature(vec![sold]));
+        let function_handle = |module, name, type_parameters| FunctionHandle {
+            module: ModuleHandleIndex(module),
+            name: IdentifierIndex(name),
+            parameters: SignatureIndex(0),
+            return_: SignatureIndex(0),
+            type_parameters,
+        };
+        module.function_handles = vec![
+            function_handle(1, 3, vec![
+                AbilitySet::singleton(Ability::Drop) | Ability::Store,
+            ]),
+            function_handle(0, 8, vec![]),
+        ];
+        module.function_instantiations = vec![FunctionInstantiation {
+            handle: FunctionHandleIndex(0),
+            type_parameters: SignatureIndex(1),
+        }];
+        module.function_defs = vec![FunctionDefinition {
+            function: FunctionHandleIndex(1),
+            visibility: Visibility::Private,
+            is_entry: false,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code: vec![
+                    Bytecode::CallGeneric(FunctionInstantiationIndex(0)),
+                    Bytecode::Ret,
+                ],
+            }),
+        }];
+        module
+    }
+
+    #[test]
+    fn test_document_events() {
+        let module = market_module();
+        let abi = MoveModule::from(module.clone());
+        // `0x1::event` isn't resolved, which the fields of the events don't need
+        let accessor = TypeAccessor::from_local_modules(
+            &["0xcafe::market::Sold".parse().unwrap()],
+            |module_id| {
+                (ModuleId::new(abi.address.into(), abi.name.0.clone()) == *module_id)
+                    .then(|| abi.clone())
+            },
+        );
+
+        let events = document_events(&[module], &accessor).unwrap();
+        assert_eq!(events.len(), 1);
+        let sold = &events[0];
+        assert_eq!(sold.typ.to_string(), "0xcafe::market::Sold");
+        let fields: Vec<_> = sold
+            .fields
+            .iter()
+            .flatten()
+            .map(|field| (field.name.as_str(), field.typ.clone()))
+            .collect();
+        assert_eq!(fields, vec![("price", MoveType::U64)]);
+        assert_eq!(
+            sold.emitted_by,
+            BTreeSet::from(["0xcafe::market::sell".to_string()])
+        );
+        assert_eq!(
+            sold.handles,
+            BTreeSet::from(["0xcafe::market::Store.sold_events".to_string()])
+        );
+
+        assert_eq!(
+            render_markdown("market", &events).unwrap(),
+            "# Events of `market`\n\n\
+            ## `0xcafe::market::Sold`\n\n\
+            | Field | Type |\n\
+            | --- | --- |\n\
+            | `price` | `u64` |\n\n\
+            Emitted by:\n\n\
+            - `0xcafe::market::sell`\n\n\
+            Event handles:\n\n\
+            - `0xcafe::market::Store.sold_events`\n\n"
+        );
+    }
+
+    #[test]
+    fn test_substitute() {
+        let tag = |name: &str, type_args| {
+            MoveType::Struct(MoveStructTag::new(
+                AccountAddress::ONE.into(),
+                Identifier::new("coin").unwrap().into(),
+                Identifier::new(name).unwrap().into(),
+                type_args,
+            ))
+        };
+        let coin = tag("Coin", vec![MoveType::GenericTypeParam { index: 0 }]);
+        assert_eq!(
+            substitute(&coin, &[tag("AptosCoin", vec![])]).unwrap(),
+            tag("Coin", vec![tag("AptosCoin", vec![])])
+        );
+        assert!(substitute(&coin, &[]).is_err());
+    }
+}
@@ -3,14 +3,18 @@
 
 mod aptos_debug_natives;
 pub mod coverage;
+mod event_docs;
 mod manifest;
+mod package_history;
 pub mod package_hooks;
+mod publish_report;
+mod rust_client;
 mod show;
 pub mod stored_package;
 mod transactional_tests_runner;
 
 use crate::{
-    account::derive_resource_account::ResourceAccountSeed,
+    account::derive_resource_account::{ResourceAccountSeed, SeedEncoding},
     common::{
         types::{
             load_account_arg, CliConfig, CliError, CliTypedResult, ConfigSearchMode,
@@ -26,20 +30,31 @@ use crate::{
     move_tool::{
         coverage::SummaryCoverage,
         manifest::{Dependency, ManifestNamedAddress, MovePackageManifest, PackageInfo},
+        publish_report::{CostReport, SizeReport},
     },
     CliCommand, CliResult,
 };
-use aptos_crypto::HashValue;
+use aptos_crypto::{HashValue, PrivateKey};
 use aptos_framework::{
-    build_model, docgen::DocgenOptions, extended_checks, natives::code::UpgradePolicy,
-    prover::ProverOptions, BuildOptions, BuiltPackage,
+    build_model,
+    docgen::DocgenOptions,
+    extended_checks,
+    natives::code::{PackageRegistry, UpgradePolicy},
+    prover::ProverOptions,
+    BuildOptions, BuiltPackage,
 };
 use aptos_gas::{AbstractValueSizeGasParameters, NativeGasParameters};
-use aptos_rest_client::aptos_api_types::{EntryFunctionId, MoveType, ViewRequest};
+use aptos_rest_client::{
+    aptos_api_types::{well_known, EntryFunctionId, MoveType, Transaction, ViewRequest},
+    error::{AptosErrorResponse, RestError},
+};
 use aptos_transactional_test_harness::run_aptos_test;
 use aptos_types::{
     account_address::{create_resource_address, AccountAddress},
-    transaction::{EntryFunction, Script, TransactionArgument, TransactionPayload},
+    transaction::{
+        authenticator::AuthenticationKey, EntryFunction, Script, TransactionArgument,
+        TransactionPayload,
+    },
 };
 use async_trait::async_trait;
 use clap::{ArgEnum, Parser, Subcommand};
@@ -55,9 +70,13 @@ use move_core_types::{
     language_storage::{ModuleId, TypeTag},
     u256::U256,
 };
-use move_package::{source_package::layout::SourcePackageLayout, BuildConfig};
+use move_package::{
+    source_package::{layout::SourcePackageLayout, manifest_parser::parse_move_manifest_from_file},
+    BuildConfig,
+};
 use move_unit_test::UnitTestingConfig;
 pub use package_hooks::*;
+use reqwest::StatusCode;
 use serde::Serialize;
 use std::{
     collections::BTreeMap,
@@ -85,9 +104,12 @@ pub enum MoveTool {
     Coverage(coverage::CoveragePackage),
     CreateResourceAccountAndPublishPackage(CreateResourceAccountAndPublishPackage),
     Document(DocumentPackage),
+    DocumentEvents(event_docs::DocumentEvents),
     Download(DownloadPackage),
+    GenerateRustClient(rust_client::GenerateRustClient),
     Init(InitPackage),
     List(ListPackage),
+    PackageHistory(package_history::PackageHistory),
     Prove(ProvePackage),
     Publish(PublishPackage),
     Run(RunFunction),
@@ -111,9 +133,12 @@ impl MoveTool {
                 tool.execute_serialized_success().await
             },
             MoveTool::Document(tool) => tool.execute_serialized().await,
+            MoveTool::DocumentEvents(tool) => tool.execute_serialized().await,
             MoveTool::Download(tool) => tool.execute_serialized().await,
+            MoveTool::GenerateRustClient(tool) => tool.execute_serialized().await,
             MoveTool::Init(tool) => tool.execute_serialized_success().await,
             MoveTool::List(tool) => tool.execute_serialized().await,
+            MoveTool::PackageHistory(tool) => tool.execute_serialized().await,
             MoveTool::Prove(tool) => tool.execute_serialized().await,
             MoveTool::Publish(tool) => tool.execute_serialized().await,
             MoveTool::Run(tool) => tool.execute_serialized().await,
@@ -592,6 +617,7 @@ impl CliCommand<&'static str> for DocumentPackage {
             docgen_options: Some(docgen_options),
             skip_fetch_latest_git_deps: move_options.skip_fetch_latest_git_deps,
             bytecode_version: move_options.bytecode_version,
+            max_publish_size: None,
         };
         BuiltPackage::build(move_options.get_package_path()?, build_options)?;
         Ok("succeeded")
@@ -620,14 +646,49 @@ pub struct PublishPackage {
     #[clap(long)]
     pub(crate) override_size_check: bool,
 
+    /// Simulates the publish instead of submitting it
+    #[clap(long)]
+    pub(crate) dry_run: bool,
+
+    /// Prints the size of each module and of the payload against the size limit
+    ///
+    /// Along with `--dry-run`, also prints the estimated gas of the publish and the part of it
+    /// paid for storage.
+    #[clap(long)]
+    pub(crate) report: bool,
+
     #[clap(flatten)]
     pub(crate) included_artifacts_args: IncludedArtifactsArgs,
     #[clap(flatten)]
+    pub(crate) resource_account_args: ResourceAccountPublishArgs,
+    #[clap(flatten)]
     pub(crate) move_options: MovePackageDir,
     #[clap(flatten)]
     pub(crate) txn_options: TransactionOptions,
 }
 
+#[derive(Default, Parser)]
+pub struct ResourceAccountPublishArgs {
+    /// Publishes the package under the resource account derived from the sender and this seed
+    ///
+    /// The resource account is created along with the package. If it already exists, e.g.
+    /// because it was created with `aptos account create-resource-account` before a publish
+    /// failed, and is still controlled by the sender's key, the package is published from it.
+    #[clap(long)]
+    pub(crate) resource_account_seed: Option<String>,
+
+    /// Encoding of `--resource-account-seed`, as `--seed-encoding` of
+    /// `aptos account derive-resource-account-address`
+    #[clap(long, default_value_t = SeedEncoding::Bcs)]
+    pub(crate) resource_account_seed_encoding: SeedEncoding,
+
+    /// Named addresses set to the address of the resource account
+    ///
+    /// Defaults to every named address left unassigned (`_`) in the package's Move.toml.
+    #[clap(long, multiple_values = true)]
+    pub(crate) resource_account_address_names: Vec<String>,
+}
+
 #[derive(ArgEnum, Clone, Copy, Debug)]
 pub enum IncludedArtifacts {
     None,
@@ -714,12 +775,38 @@ impl CliCommand<TransactionSummary> for PublishPackage {
 
     async fn execute(self) -> CliTypedResult<TransactionSummary> {
         let PublishPackage {
-            move_options,
-            txn_options,
+            mut move_options,
+            mut txn_options,
             override_size_check,
+            dry_run,
+            report,
             included_artifacts_args,
+            resource_account_args,
         } = self;
         let package_path = move_options.get_package_path()?;
+
+        // Derive the resource account first, as the package is compiled with its address
+        let resource_account = if let Some(seed) = &resource_account_args.resource_account_seed {
+            let seed = resource_account_args
+                .resource_account_seed_encoding
+                .encode(seed)?;
+            let resource_address = create_resource_address(txn_options.sender_address()?, &seed);
+            let address_names = if resource_account_args
+                .resource_account_address_names
+                .is_empty()
+            {
+                unassigned_named_addresses(&package_path, &move_options)?
+            } else {
+                resource_account_args.resource_account_address_names
+            };
+            for address_name in address_names {
+                move_options.add_named_address(address_name, resource_address.to_string());
+            }
+            Some((seed, resource_address))
+        } else {
+            None
+        };
+
         let options = included_artifacts_args.included_artifacts.build_options(
             move_options.skip_fetch_latest_git_deps,
             move_options.named_addresses(),
@@ -730,12 +817,45 @@ impl CliCommand<TransactionSummary> for PublishPackage {
 
         // Send the compiled module and metadata using the code::publish_package_txn.
         let metadata = package.extract_metadata()?;
-        let payload = aptos_cached_packages::aptos_stdlib::code_publish_package_txn(
-            bcs::to_bytes(&metadata).expect("PackageMetadata has BCS"),
-            compiled_units,
-        );
+        let metadata_bytes = bcs::to_bytes(&metadata).expect("PackageMetadata has BCS");
+        let metadata_size = metadata_bytes.len();
+        let code = compiled_units.clone();
+        let payload = match resource_account {
+            None => aptos_cached_packages::aptos_stdlib::code_publish_package_txn(
+                metadata_bytes,
+                compiled_units,
+            ),
+            Some((seed, resource_address)) => {
+                let message = format!(
+                    "Do you want to publish this package under the resource account's address {}?",
+                    resource_address
+                );
+                prompt_yes_with_override(&message, txn_options.prompt_options)?;
+
+                if resource_account_exists(&txn_options, resource_address, &metadata.name).await? {
+                    // Finish the publish from the resource account itself, signing with the
+                    // sender's key
+                    txn_options.sender_account = Some(resource_address);
+                    aptos_cached_packages::aptos_stdlib::code_publish_package_txn(
+                        metadata_bytes,
+                        compiled_units,
+                    )
+                } else {
+                    aptos_cached_packages::aptos_stdlib::resource_account_create_resource_account_and_publish_package(
+                        seed,
+                        metadata_bytes,
+                        compiled_units,
+                    )
+                }
+            },
+        };
         let size = bcs::serialized_size(&payload)?;
-        println!("package size {} bytes", size);
+        let size_report = SizeReport::new(&code, metadata_size, size);
+        if report {
+            println!("{}", size_report);
+        } else {
+            println!("package size {} bytes", size);
+        }
         if !override_size_check && size > MAX_PUBLISH_PACKAGE_SIZE {
             return Err(CliError::UnexpectedError(format!(
                 "The package is larger than {} bytes ({} bytes)! To lower the size \
@@ -744,7 +864,19 @@ impl CliCommand<TransactionSummary> for PublishPackage {
                 MAX_PUBLISH_PACKAGE_SIZE, size
             )));
         }
-        if txn_options.profile_gas {
+        if dry_run {
+            let simulated = txn_options.simulate_transaction(payload).await?;
+            if report {
+                let client = txn_options.rest_client()?;
+                println!(
+                    "{}",
+                    CostReport::new(&client, &size_report, &simulated).await?
+                );
+            }
+            Ok(TransactionSummary::from(&Transaction::UserTransaction(
+                Box::new(simulated),
+            )))
+        } else if txn_options.profile_gas {
             txn_options.profile_gas(payload).await
         } else {
             txn_options
@@ -755,6 +887,99 @@ impl CliCommand<TransactionSummary> for PublishPackage {
     }
 }
 
+/// The named addresses of the package at `package_path` its Move.toml leaves unassigned, and
+/// which aren't given with `--named-addresses`
+fn unassigned_named_addresses(
+    package_path: &Path,
+    move_options: &MovePackageDir,
+) -> CliTypedResult<Vec<String>> {
+    let manifest = parse_move_manifest_from_file(package_path).map_err(|err| {
+        CliError::UnableToReadFile(package_path.display().to_string(), err.to_string())
+    })?;
+    let assigned = move_options.named_addresses();
+    let names: Vec<String> = manifest
+        .addresses
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|(_, address)| address.is_none())
+        .map(|(name, _)| name.to_string())
+        .filter(|name| !assigned.contains_key(name))
+        .collect();
+    if names.is_empty() {
+        return Err(CliError::CommandArgumentError(
+            "The package has no unassigned named addresses to set to the resource account's \
+            address, please provide them with --resource-account-address-names"
+                .to_string(),
+        ));
+    }
+    Ok(names)
+}
+
+/// Whether the resource account a package is published under already exists, in which case
+/// the sender's key must still control it for the package to be published from it. That is the
+/// case if it was created without publishing, or if a previous publish created it but failed.
+async fn resource_account_exists(
+    txn_options: &TransactionOptions,
+    resource_address: AccountAddress,
+    package_name: &str,
+) -> CliTypedResult<bool> {
+    let client = txn_options
+        .rest_options
+        .client(&txn_options.profile_options)?;
+    let account = match unless_not_found(client.get_account(resource_address).await)? {
+        Some(account) => account.into_inner(),
+        None => return Ok(false),
+    };
+
+    let (key, _) = txn_options.get_key_and_address()?;
+    if account.authentication_key == AuthenticationKey::ed25519(&key.public_key()) {
+        return Ok(true);
+    }
+    let registry = unless_not_found(
+        client
+            .get_account_resource_bcs::<PackageRegistry>(
+                resource_address,
+                &well_known::package_registry().to_string(),
+            )
+            .await,
+    )?;
+    let published = registry.map_or(false, |registry| {
+        registry
+            .inner()
+            .packages
+            .iter()
+            .any(|package| package.name == package_name)
+    });
+    Err(CliError::CommandArgumentError(
+        if published {
+            format!(
+            "Package {} is already published under the resource account {}, which the sender no \
+            longer controls. Upgrade it through the package, which holds the account's signer \
+            capability",
+            package_name, resource_address
+        )
+        } else {
+            format!(
+                "The resource account {} already exists, but isn't controlled by the sender's key",
+                resource_address
+            )
+        },
+    ))
+}
+
+/// `None` if the node responded that what was asked for doesn't exist, passing other errors up
+fn unless_not_found<T>(result: Result<T, RestError>) -> CliTypedResult<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(RestError::Api(AptosErrorResponse {
+            status_code: StatusCode::NOT_FOUND,
+            ..
+        }))
+        | Err(RestError::Http(StatusCode::NOT_FOUND, _)) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
 /// Publishes the modules in a Move package to the Aptos blockchain under a resource account
 #[derive(Parser)]
 pub struct CreateResourceAccountAndPublishPackage {
@@ -1678,3 +1903,73 @@ impl FromStr for MemberId {
         parse_member_id(s)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_rest_client::aptos_api_types::{AptosError, AptosErrorCode};
+    use std::fs;
+
+    fn api_error(error_code: AptosErrorCode, status_code: StatusCode) -> RestError {
+        RestError::Api(AptosErrorResponse {
+            error: AptosError::new_with_error_code("error", error_code),
+            state: None,
+            status_code,
+        })
+    }
+
+    #[test]
+    fn test_unless_not_found() {
+        assert_eq!(unless_not_found(Ok(1)).unwrap(), Some(1));
+        let not_found = api_error(AptosErrorCode::AccountNotFound, StatusCode::NOT_FOUND);
+        assert_eq!(unless_not_found::<u64>(Err(not_found)).unwrap(), None);
+        let not_found = api_error(AptosErrorCode::ResourceNotFound, StatusCode::NOT_FOUND);
+        assert_eq!(unless_not_found::<u64>(Err(not_found)).unwrap(), None);
+
+        // Only a missing account or resource means the resource account is free
+        for error in [
+            api_error(
+                AptosErrorCode::InternalError,
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            api_error(AptosErrorCode::VersionPruned, StatusCode::GONE),
+            RestError::Timeout("waiting for the node"),
+        ] {
+            assert!(unless_not_found::<u64>(Err(error)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_unassigned_named_addresses() {
+        let package_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            package_dir.path().join("Move.toml"),
+            r#"
+[package]
+name = "Example"
+version = "0.0.1"
+
+[addresses]
+fixed = "0x1"
+first = "_"
+given = "_"
+second = "_"
+"#,
+        )
+        .unwrap();
+        let mut move_options = MovePackageDir::new(package_dir.path().to_path_buf());
+        move_options.add_named_address("given".to_string(), "0x2".to_string());
+        assert_eq!(
+            unassigned_named_addresses(package_dir.path(), &move_options).unwrap(),
+            vec!["first".to_string(), "second".to_string()]
+        );
+
+        move_options.add_named_address("first".to_string(), "0x3".to_string());
+        move_options.add_named_address("second".to_string(), "0x4".to_string());
+        assert!(unassigned_named_addresses(package_dir.path(), &move_options).is_err());
+
+        assert!(
+            unassigned_named_addresses(&package_dir.path().join("missing"), &move_options).is_err()
+        );
+    }
+}
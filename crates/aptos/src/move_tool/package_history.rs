@@ -0,0 +1,398 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The upgrade history of a published package, from the transactions which changed the package
+//! registry of its address.
+
+use crate::common::types::{CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions};
+use aptos_crypto::HashValue;
+use aptos_framework::natives::code::{PackageMetadata, PackageRegistry};
+use aptos_rest_client::{
+    aptos_api_types::{well_known, TransactionOnChainData},
+    error::{AptosErrorResponse, RestError},
+};
+use aptos_types::{
+    access_path::AccessPath, account_address::AccountAddress, state_store::state_key::StateKey,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use move_core_types::language_storage::StructTag;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+/// How many transactions are fetched per request.
+const PAGE_SIZE: u16 = 100;
+
+/// Shows the upgrade history of a package published on-chain
+///
+/// Walks the transactions sent by the account the package is published at, and lists each
+/// transaction which published or upgraded the package: the upgrade number, policy and source
+/// digest it set, and the modules it added or removed. The history is then checked: upgrade
+/// numbers must follow each other, the policy may only get stricter, compatible upgrades may
+/// not remove modules, and the last upgrade must be the package as currently published.
+///
+/// Publishing transactions sent by another account, e.g. the one creating a resource account
+/// and publishing to it at once, aren't found, which shows as a gap in the upgrade numbers.
+#[derive(Parser)]
+pub struct PackageHistory {
+    /// Address the package is published at
+    #[clap(long, alias = "account", parse(try_from_str = crate::common::types::load_account_arg))]
+    pub(crate) address: AccountAddress,
+
+    /// Name of the package
+    #[clap(long)]
+    pub(crate) name: String,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+/// A transaction publishing or upgrading the package.
+#[derive(Debug, Serialize)]
+pub struct PackageUpgrade {
+    pub version: u64,
+    pub transaction_hash: String,
+    pub upgrade_number: u64,
+    pub upgrade_policy: String,
+    pub source_digest: String,
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PackageHistoryResult {
+    pub upgrades: Vec<PackageUpgrade>,
+    /// What doesn't add up in the history, empty if it checks out.
+    pub problems: Vec<String>,
+}
+
+#[async_trait]
+impl CliCommand<PackageHistoryResult> for PackageHistory {
+    fn command_name(&self) -> &'static str {
+        "PackageHistory"
+    }
+
+    async fn execute(self) -> CliTypedResult<PackageHistoryResult> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let registry_tag = StructTag::try_from(well_known::package_registry())
+            .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+        let registry_key = StateKey::access_path(
+            AccessPath::resource_access_path(self.address, registry_tag)
+                .map_err(|err| CliError::UnexpectedError(err.to_string()))?,
+        );
+
+        let mut history = History::default();
+        let mut start = 0;
+        loop {
+            let page = client
+                .get_account_transactions_bcs(self.address, Some(start), Some(PAGE_SIZE))
+                .await?
+                .into_inner();
+            for txn in &page {
+                if let Some(package) = self.package_written(txn, &registry_key)? {
+                    history.record(txn.version, txn.info.transaction_hash(), package);
+                }
+            }
+            if page.len() < PAGE_SIZE as usize {
+                break;
+            }
+            start += PAGE_SIZE as u64;
+        }
+
+        let current = match client
+            .get_account_resource_bcs::<PackageRegistry>(
+                self.address,
+                &well_known::package_registry().to_string(),
+            )
+            .await
+        {
+            Ok(registry) => registry
+                .into_inner()
+                .packages
+                .into_iter()
+                .find(|package| package.name == self.name),
+            // Neither the account nor its package registry exist
+            Err(RestError::Api(AptosErrorResponse {
+                status_code: StatusCode::NOT_FOUND,
+                ..
+            }))
+            | Err(RestError::Http(StatusCode::NOT_FOUND, _)) => None,
+            Err(err) => return Err(err.into()),
+        };
+        history.check_current(current.as_ref());
+        if history.upgrades.is_empty() && current.is_none() {
+            return Err(CliError::CommandArgumentError(format!(
+                "Package {} isn't published at {}",
+                self.name,
+                self.address.to_hex_literal()
+            )));
+        }
+        Ok(PackageHistoryResult {
+            upgrades: history.upgrades,
+            problems: history.problems,
+        })
+    }
+}
+
+impl PackageHistory {
+    /// The metadata of the package as written by `txn`, if it wrote the package registry and
+    /// the package is in it.
+    fn package_written(
+        &self,
+        txn: &TransactionOnChainData,
+        registry_key: &StateKey,
+    ) -> CliTypedResult<Option<PackageMetadata>> {
+        let bytes = match txn.changes.get(registry_key).and_then(|op| op.bytes()) {
+            Some(bytes) => bytes,
+            None => return Ok(None),
+        };
+        let registry: PackageRegistry = bcs::from_bytes(bytes).map_err(|err| {
+            CliError::UnexpectedError(format!(
+                "Invalid package registry at version {}: {}",
+                txn.version, err
+            ))
+        })?;
+        Ok(registry
+            .packages
+            .into_iter()
+            .find(|package| package.name == self.name))
+    }
+}
+
+#[derive(Default)]
+struct History {
+    upgrades: Vec<PackageUpgrade>,
+    problems: Vec<String>,
+    last: Option<PackageMetadata>,
+}
+
+impl History {
+    /// Records `package` as written by the transaction `transaction_hash` at `version`, if it
+    /// differs from the last version of it, as transactions publishing other packages at the
+    /// address write it unchanged.
+    fn record(&mut self, version: u64, transaction_hash: HashValue, package: PackageMetadata) {
+        if self.last.as_ref() == Some(&package) {
+            return;
+        }
+        let modules = module_names(Some(&package));
+        let previous = module_names(self.last.as_ref());
+        let upgrade = PackageUpgrade {
+            version,
+            transaction_hash: transaction_hash.to_hex_literal(),
+            upgrade_number: package.upgrade_number,
+            upgrade_policy: package.upgrade_policy.to_string(),
+            source_digest: package.source_digest.clone(),
+            added_modules: modules.difference(&previous).cloned().collect(),
+            removed_modules: previous.difference(&modules).cloned().collect(),
+        };
+
+        let expected_number = self.last.as_ref().map_or(0, |last| last.upgrade_number + 1);
+        if package.upgrade_number != expected_number {
+            self.problems.push(format!(
+                "Upgrade number {} at version {}, expected {}: upgrades are missing",
+                package.upgrade_number, version, expected_number
+            ));
+        }
+        if let Some(last) = &self.last {
+            if package.upgrade_policy.policy < last.upgrade_policy.policy {
+                self.problems.push(format!(
+                    "Upgrade policy weakened from {} to {} at version {}",
+                    last.upgrade_policy, package.upgrade_policy, version
+                ));
+            }
+            if last.upgrade_policy.policy > 0 && !upgrade.removed_modules.is_empty() {
+                self.problems.push(format!(
+                    "Modules {} removed by a {} upgrade at version {}",
+                    upgrade.removed_modules.join(", "),
+                    last.upgrade_policy,
+                    version
+                ));
+            }
+        }
+        self.upgrades.push(upgrade);
+        self.last = Some(package);
+    }
+
+    /// Checks the last upgrade found is the package as currently published.
+    fn check_current(&mut self, current: Option<&PackageMetadata>) {
+        match (current, &self.last) {
+            (Some(current), Some(last)) if current == last => {},
+            (Some(current), _) => self.problems.push(format!(
+                "The package is currently at upgrade number {}, with source digest {}, \
+                which no transaction found published",
+                current.upgrade_number, current.source_digest
+            )),
+            (None, Some(_)) => self
+                .problems
+                .push("The package isn't in the current package registry".to_string()),
+            (None, None) => {},
+        }
+    }
+}
+
+fn module_names(package: Option<&PackageMetadata>) -> BTreeSet<String> {
+    package
+        .into_iter()
+        .flat_map(|package| package.modules.iter().map(|module| module.name.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_framework::natives::code::{ModuleMetadata, UpgradePolicy};
+
+    fn package(upgrade_number: u64, policy: UpgradePolicy, modules: &[&str]) -> PackageMetadata {
+        PackageMetadata {
+            name: "market".to_string(),
+            upgrade_policy: policy,
+            upgrade_number,
+            source_digest: format!("digest{}", upgrade_number),
+            manifest: vec![],
+            modules: modules
+                .iter()
+                .map(|name| ModuleMetadata {
+                    name: name.to_string(),
+                    source: vec![],
+                    source_map: vec![],
+                    extension: Default::default(),
+                })
+                .collect(),
+            deps: vec![],
+            extension: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_history_checks_out() {
+        let mut history = History::default();
+        let v1 = package(1, UpgradePolicy::compat(), &["orders", "receipts"]);
+        history.record(
+            10,
+            HashValue::zero(),
+            package(0, UpgradePolicy::arbitrary(), &["orders"]),
+        );
+        history.record(15, HashValue::zero(), v1.clone());
+        // Publishing another package at the address writes this one unchanged
+        history.record(20, HashValue::zero(), v1.clone());
+        history.check_current(Some(&v1));
+
+        assert!(history.problems.is_empty(), "{:?}", history.problems);
+        assert_eq!(history.upgrades.len(), 2);
+        assert_eq!(history.upgrades[1].version, 15);
+        assert_eq!(history.upgrades[1].added_modules, vec!["receipts"]);
+        assert!(history.upgrades[1].removed_modules.is_empty());
+    }
+
+    #[test]
+    fn test_upgrade_number_gap() {
+        let mut history = History::default();
+        history.record(
+            10,
+            HashValue::zero(),
+            package(0, UpgradePolicy::compat(), &["a"]),
+        );
+        history.record(
+            20,
+            HashValue::zero(),
+            package(2, UpgradePolicy::compat(), &["a"]),
+        );
+        assert_eq!(history.problems, vec![
+            "Upgrade number 2 at version 20, expected 1: upgrades are missing"
+        ]);
+
+        // The transaction publishing the package wasn't found
+        let mut history = History::default();
+        history.record(
+            10,
+            HashValue::zero(),
+            package(3, UpgradePolicy::compat(), &["a"]),
+        );
+        assert_eq!(history.problems, vec![
+            "Upgrade number 3 at version 10, expected 0: upgrades are missing"
+        ]);
+    }
+
+    #[test]
+    fn test_weakened_policy() {
+        let mut history = History::default();
+        history.record(
+            10,
+            HashValue::zero(),
+            package(0, UpgradePolicy::compat(), &["a"]),
+        );
+        history.record(
+            20,
+            HashValue::zero(),
+            package(1, UpgradePolicy::arbitrary(), &["a"]),
+        );
+        assert_eq!(history.problems, vec![
+            "Upgrade policy weakened from compatible to arbitrary at version 20"
+        ]);
+    }
+
+    #[test]
+    fn test_removed_module() {
+        let mut history = History::default();
+        history.record(
+            10,
+            HashValue::zero(),
+            package(0, UpgradePolicy::compat(), &["a", "b"]),
+        );
+        history.record(
+            20,
+            HashValue::zero(),
+            package(1, UpgradePolicy::compat(), &["a"]),
+        );
+        assert_eq!(history.upgrades[1].removed_modules, vec!["b"]);
+        assert_eq!(history.problems, vec![
+            "Modules b removed by a compatible upgrade at version 20"
+        ]);
+
+        // An arbitrary policy allows removing modules
+        let mut history = History::default();
+        history.record(
+            10,
+            HashValue::zero(),
+            package(0, UpgradePolicy::arbitrary(), &["a", "b"]),
+        );
+        history.record(
+            20,
+            HashValue::zero(),
+            package(1, UpgradePolicy::arbitrary(), &["a"]),
+        );
+        assert!(history.problems.is_empty(), "{:?}", history.problems);
+    }
+
+    #[test]
+    fn test_check_current() {
+        let v0 = package(0, UpgradePolicy::compat(), &["a"]);
+        let v1 = package(1, UpgradePolicy::compat(), &["a"]);
+
+        let mut history = History::default();
+        history.record(10, HashValue::zero(), v0.clone());
+        history.check_current(Some(&v1));
+        assert_eq!(history.problems, vec![
+            "The package is currently at upgrade number 1, with source digest digest1, which no \
+             transaction found published"
+        ]);
+
+        let mut history = History::default();
+        history.record(10, HashValue::zero(), v0.clone());
+        history.check_current(None);
+        assert_eq!(history.problems, vec![
+            "The package isn't in the current package registry"
+        ]);
+
+        let mut history = History::default();
+        history.check_current(Some(&v0));
+        assert_eq!(history.problems.len(), 1);
+
+        let mut history = History::default();
+        history.check_current(None);
+        assert!(history.problems.is_empty());
+    }
+}
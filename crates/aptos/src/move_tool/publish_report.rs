@@ -0,0 +1,237 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The size and cost budget of a package, printed by `aptos move publish --report`.
+
+use crate::{
+    common::types::{CliError, CliTypedResult},
+    move_tool::MAX_PUBLISH_PACKAGE_SIZE,
+};
+use aptos_rest_client::{aptos_api_types::UserTransaction, Client};
+use aptos_types::{
+    account_config::CORE_CODE_ADDRESS,
+    on_chain_config::{GasScheduleV2, StorageGasSchedule},
+};
+use move_binary_format::CompiledModule;
+use std::{collections::BTreeMap, fmt};
+
+/// The sizes of the parts of a publish transaction.
+pub(crate) struct SizeReport {
+    /// Name and size in bytes of each module.
+    modules: Vec<(String, usize)>,
+    metadata_size: usize,
+    payload_size: usize,
+}
+
+impl SizeReport {
+    pub fn new(code: &[Vec<u8>], metadata_size: usize, payload_size: usize) -> Self {
+        let modules = code
+            .iter()
+            .map(|bytes| {
+                let name = CompiledModule::deserialize(bytes)
+                    .map(|module| module.self_id().name().to_string())
+                    .unwrap_or_else(|_| "<script>".to_string());
+                (name, bytes.len())
+            })
+            .collect();
+        Self {
+            modules,
+            metadata_size,
+            payload_size,
+        }
+    }
+
+    /// The sizes of the code and the metadata, written to storage on publish.
+    fn stored_sizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.modules
+            .iter()
+            .map(|(_, size)| *size)
+            .chain([self.metadata_size])
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Module sizes:")?;
+        for (name, size) in &self.modules {
+            writeln!(f, "  {:<32} {:>8} bytes", name, size)?;
+        }
+        writeln!(
+            f,
+            "  {:<32} {:>8} bytes",
+            "(package metadata)", self.metadata_size
+        )?;
+        write!(
+            f,
+            "Payload size: {} of {} bytes ({:.1}%)",
+            self.payload_size,
+            MAX_PUBLISH_PACKAGE_SIZE,
+            self.payload_size as f64 * 100.0 / MAX_PUBLISH_PACKAGE_SIZE as f64
+        )?;
+        if self.payload_size > MAX_PUBLISH_PACKAGE_SIZE {
+            write!(f, ", over the limit")?;
+        }
+        Ok(())
+    }
+}
+
+/// The estimated costs of a publish transaction, in Octas.
+pub(crate) struct CostReport {
+    gas_used: u64,
+    gas_unit_price: u64,
+    /// The part of the gas paid for the storage the package takes.
+    storage_cost: u64,
+}
+
+impl CostReport {
+    /// Estimates the costs from a simulation of the publish transaction, and the storage gas
+    /// parameters on chain. The storage cost is an upper bound, as every module and the metadata
+    /// are counted as new items, even those overwriting a previous version.
+    pub async fn new(
+        client: &Client,
+        sizes: &SizeReport,
+        simulated: &UserTransaction,
+    ) -> CliTypedResult<Self> {
+        let storage_gas = client
+            .get_account_resource_bcs::<StorageGasSchedule>(
+                CORE_CODE_ADDRESS,
+                "0x1::storage_gas::StorageGas",
+            )
+            .await?
+            .into_inner();
+        let gas_schedule = client
+            .get_account_resource_bcs::<GasScheduleV2>(
+                CORE_CODE_ADDRESS,
+                "0x1::gas_schedule::GasScheduleV2",
+            )
+            .await?
+            .into_inner()
+            .to_btree_map();
+        Self::estimate(
+            sizes,
+            &storage_gas,
+            &gas_schedule,
+            simulated.info.gas_used.0,
+            simulated.request.gas_unit_price.0,
+        )
+    }
+
+    fn estimate(
+        sizes: &SizeReport,
+        storage_gas: &StorageGasSchedule,
+        gas_schedule: &BTreeMap<String, u64>,
+        gas_used: u64,
+        gas_unit_price: u64,
+    ) -> CliTypedResult<Self> {
+        let scaling_factor = gas_schedule
+            .get("txn.gas_unit_scaling_factor")
+            .copied()
+            .filter(|factor| *factor > 0)
+            .ok_or_else(|| {
+                CliError::UnexpectedError(
+                    "The gas schedule has no gas unit scaling factor".to_string(),
+                )
+            })?;
+        let free_bytes = gas_schedule
+            .get("txn.free_write_bytes_quota")
+            .copied()
+            .unwrap_or(0);
+
+        let storage_internal_gas: u64 = sizes
+            .stored_sizes()
+            .map(|size| {
+                storage_gas.per_item_create
+                    + (size as u64).saturating_sub(free_bytes) * storage_gas.per_byte_create
+            })
+            .sum();
+        Ok(Self {
+            gas_used,
+            gas_unit_price,
+            storage_cost: storage_internal_gas / scaling_factor * gas_unit_price,
+        })
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Estimated gas: {} gas units at {} Octas, {} Octas",
+            self.gas_used,
+            self.gas_unit_price,
+            self.gas_used * self.gas_unit_price
+        )?;
+        write!(
+            f,
+            "Estimated storage cost: at most {} Octas, included in the gas",
+            self.storage_cost
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_binary_format::file_format::empty_module;
+    use move_core_types::identifier::Identifier;
+
+    fn module_bytes(name: &str) -> Vec<u8> {
+        let mut module = empty_module();
+        module.identifiers[0] = Identifier::new(name).unwrap();
+        let mut bytes = vec![];
+        module.serialize(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_size_report() {
+        let market = module_bytes("market");
+        let sizes = SizeReport::new(&[market.clone(), vec![0; 10]], 20, 30_000);
+        assert_eq!(sizes.stored_sizes().collect::<Vec<_>>(), vec![
+            market.len(),
+            10,
+            20
+        ]);
+        assert_eq!(
+            sizes.to_string(),
+            format!(
+                "Module sizes:\n  \
+                market                           {:>8} bytes\n  \
+                <script>                               10 bytes\n  \
+                (package metadata)                     20 bytes\n\
+                Payload size: 30000 of 60000 bytes (50.0%)",
+                market.len()
+            )
+        );
+
+        let sizes = SizeReport::new(&[], 20, 60_001);
+        assert!(sizes
+            .to_string()
+            .ends_with("Payload size: 60001 of 60000 bytes (100.0%), over the limit"));
+    }
+
+    #[test]
+    fn test_cost_report() {
+        let sizes = SizeReport::new(&[vec![0; 1_500]], 500, 2_000);
+        let storage_gas = StorageGasSchedule {
+            per_item_create: 50_000,
+            per_byte_create: 1_000,
+            ..StorageGasSchedule::zeros()
+        };
+        let gas_schedule = BTreeMap::from([
+            ("txn.gas_unit_scaling_factor".to_string(), 10_000),
+            ("txn.free_write_bytes_quota".to_string(), 1_024),
+        ]);
+        // The module is charged for its 476 bytes over the quota, the metadata only as an item:
+        // (50_000 + 476 * 1_000) + 50_000 = 576_000 internal gas units, 57 gas units
+        let costs = CostReport::estimate(&sizes, &storage_gas, &gas_schedule, 1_000, 100).unwrap();
+        assert_eq!(
+            costs.to_string(),
+            "Estimated gas: 1000 gas units at 100 Octas, 100000 Octas\n\
+            Estimated storage cost: at most 5700 Octas, included in the gas"
+        );
+
+        let gas_schedule = BTreeMap::from([("txn.gas_unit_scaling_factor".to_string(), 0)]);
+        assert!(CostReport::estimate(&sizes, &storage_gas, &gas_schedule, 1_000, 100).is_err());
+    }
+}
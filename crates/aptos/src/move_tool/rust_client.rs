@@ -0,0 +1,594 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation of a Rust client for a published package: a type for each of the structs of its
+//! modules, and a function building the payload of each of its entry functions.
+
+use crate::common::{
+    types::{load_account_arg, CliCommand, CliError, CliTypedResult, ProfileOptions, RestOptions},
+    utils::{create_dir_if_not_exist, write_to_file},
+};
+use anyhow::{anyhow, bail, Result};
+use aptos_rest_client::{
+    aptos_api_types::{MoveModule, MoveStruct, MoveType},
+    Client,
+};
+use async_trait::async_trait;
+use clap::Parser;
+use itertools::Itertools;
+use move_binary_format::CompiledModule;
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::Identifier,
+    language_storage::{ModuleId, CORE_CODE_ADDRESS},
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    path::PathBuf,
+};
+
+/// Rust keywords which can be used as raw identifiers.
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "static",
+    "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+    "where", "while", "yield",
+];
+
+/// Generates a Rust client for a published package
+///
+/// For each module of the package, a Rust module is generated with a type for each struct, which
+/// (de)serializes with BCS like the Move struct, and a function for each entry function, which
+/// builds the payload calling it. Types of other packages that the structs use are generated as
+/// well, in modules named after their module and address, e.g. `coin_1`.
+///
+/// The generated code depends on the `aptos-sdk` and `serde` crates. Include it with
+/// `mod <dir>;` and regenerate it whenever the package is upgraded: types which no longer match
+/// the published ones then fail to compile where they're used.
+#[derive(Parser)]
+pub struct GenerateRustClient {
+    /// The package to generate a client for, as `<address>::<package name>`
+    ///
+    /// The address can also be a profile name.
+    #[clap(long, parse(try_from_str = parse_package_id))]
+    pub(crate) package: (AccountAddress, String),
+
+    /// Directory to write the Rust modules to. Existing files are overwritten.
+    #[clap(long, parse(from_os_str))]
+    pub(crate) out: PathBuf,
+
+    #[clap(flatten)]
+    pub(crate) rest_options: RestOptions,
+    #[clap(flatten)]
+    pub(crate) profile_options: ProfileOptions,
+}
+
+pub(crate) fn parse_package_id(str: &str) -> CliTypedResult<(AccountAddress, String)> {
+    match str.rsplit_once("::") {
+        Some((address, name)) if !name.is_empty() => {
+            Ok((load_account_arg(address)?, name.to_string()))
+        },
+        _ => Err(CliError::CommandArgumentError(format!(
+            "Invalid package {}, expected <address>::<package name>",
+            str
+        ))),
+    }
+}
+
+#[async_trait]
+impl CliCommand<Vec<String>> for GenerateRustClient {
+    fn command_name(&self) -> &'static str {
+        "GenerateRustClient"
+    }
+
+    async fn execute(self) -> CliTypedResult<Vec<String>> {
+        let client = self.rest_options.client(&self.profile_options)?;
+        let (address, package_name) = self.package;
+        let (bundle, state) = client
+            .get_package(address, &package_name)
+            .await?
+            .into_parts();
+
+        let mut generator = RustClientGenerator::new(client, state.version);
+        for module in bundle.compiled_modules() {
+            generator.add_package_module(MoveModule::from(module.clone()));
+        }
+        let files = generator.generate().await?;
+
+        create_dir_if_not_exist(&self.out)?;
+        let header = format!(
+            "// Generated by `aptos move generate-rust-client` from the package {}::{} at \
+            version {}. Do not edit.\n\n",
+            address.to_hex_literal(),
+            package_name,
+            state.version
+        );
+        let mut written = vec![];
+        for (name, contents) in files {
+            let path = self.out.join(name);
+            write_to_file(
+                &path,
+                &path.display().to_string(),
+                format!("{}{}", header, contents).as_bytes(),
+            )?;
+            written.push(path.display().to_string());
+        }
+        Ok(written)
+    }
+}
+
+/// Collects the modules of a package and the structs of other modules their structs refer to, and
+/// renders them as Rust modules.
+struct RustClientGenerator {
+    client: Client,
+    /// The version the package was read at, at which referenced modules are read as well.
+    version: u64,
+    modules: BTreeMap<ModuleId, MoveModule>,
+    package_modules: BTreeSet<ModuleId>,
+    /// Structs to generate, by module.
+    structs: BTreeMap<ModuleId, BTreeSet<Identifier>>,
+    to_generate: Vec<(ModuleId, Identifier)>,
+}
+
+impl RustClientGenerator {
+    fn new(client: Client, version: u64) -> Self {
+        Self {
+            client,
+            version,
+            modules: BTreeMap::new(),
+            package_modules: BTreeSet::new(),
+            structs: BTreeMap::new(),
+            to_generate: vec![],
+        }
+    }
+
+    fn add_package_module(&mut self, module: MoveModule) {
+        let module_id = ModuleId::new(module.address.into(), module.name.0.clone());
+        self.structs.entry(module_id.clone()).or_default();
+        self.to_generate.extend(
+            module
+                .structs
+                .iter()
+                .filter(|s| !s.is_native)
+                .map(|s| (module_id.clone(), s.name.0.clone())),
+        );
+        self.package_modules.insert(module_id.clone());
+        self.modules.insert(module_id, module);
+    }
+
+    /// The name and contents of each file of the client, including a `mod.rs` declaring the
+    /// modules.
+    async fn generate(mut self) -> Result<Vec<(String, String)>> {
+        // Structs may refer to structs of any module, so resolve them until none is missing
+        while let Some((module_id, name)) = self.to_generate.pop() {
+            if !self
+                .structs
+                .entry(module_id.clone())
+                .or_default()
+                .insert(name.clone())
+            {
+                continue;
+            }
+            let struct_ = self.find_struct(&module_id, &name).await?;
+            if struct_.is_native {
+                bail!(
+                    "Native struct {}::{} has no Rust representation",
+                    module_id,
+                    name
+                );
+            }
+            let mut referenced = vec![];
+            for field in &struct_.fields {
+                referenced_structs(&field.typ, &mut referenced)?;
+            }
+            self.to_generate.extend(referenced);
+        }
+
+        let mut files = vec![];
+        let mut mod_rs = String::new();
+        for module_id in self.structs.keys() {
+            let rust_name = self.rust_module_name(module_id);
+            writeln!(
+                mod_rs,
+                "#[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]\npub mod {};",
+                rust_name
+            )?;
+            files.push((
+                format!("{}.rs", rust_name.trim_start_matches("r#")),
+                self.render_module(module_id)?,
+            ));
+        }
+        files.push(("mod.rs".to_string(), mod_rs));
+        Ok(files)
+    }
+
+    async fn find_struct(&mut self, module_id: &ModuleId, name: &Identifier) -> Result<MoveStruct> {
+        if !self.modules.contains_key(module_id) {
+            let bytes = self
+                .client
+                .get_account_module_bcs_at_version(
+                    *module_id.address(),
+                    module_id.name().as_str(),
+                    self.version,
+                )
+                .await?
+                .into_inner();
+            let module = CompiledModule::deserialize(&bytes)
+                .map_err(|err| anyhow!("Invalid bytecode for module {}: {:?}", module_id, err))?;
+            self.modules
+                .insert(module_id.clone(), MoveModule::from(module));
+        }
+        self.modules[module_id]
+            .structs
+            .iter()
+            .find(|s| &s.name.0 == name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Module {} has no struct {}", module_id, name))
+    }
+
+    /// Modules of the package keep their name, others are suffixed with their address, as
+    /// modules of different addresses may share a name.
+    fn rust_module_name(&self, module_id: &ModuleId) -> String {
+        if self.package_modules.contains(module_id) {
+            rust_ident(module_id.name().as_str())
+        } else {
+            format!(
+                "{}_{}",
+                module_id.name(),
+                module_id.address().short_str_lossless()
+            )
+        }
+    }
+
+    fn render_module(&self, module_id: &ModuleId) -> Result<String> {
+        let module = &self.modules[module_id];
+        let mut out = String::new();
+        for name in &self.structs[module_id] {
+            let struct_ = module
+                .structs
+                .iter()
+                .find(|s| &s.name.0 == name)
+                .expect("structs to generate are resolved");
+            self.render_struct(module_id, struct_, &mut out)?;
+        }
+
+        if !self.package_modules.contains(module_id) {
+            return Ok(out);
+        }
+        writeln!(
+            out,
+            "pub const MODULE_ADDRESS: ::aptos_sdk::types::account_address::AccountAddress =\n    \
+            ::aptos_sdk::types::account_address::AccountAddress::new({:?});\n",
+            module_id.address().into_bytes()
+        )?;
+        writeln!(
+            out,
+            "pub const MODULE_NAME: &str = \"{}\";\n",
+            module_id.name()
+        )?;
+        for function in module.exposed_functions.iter().filter(|f| f.is_entry) {
+            let mut params: Vec<_> = (0..function.generic_type_params.len())
+                .map(|i| format!("t{}: ::aptos_sdk::move_types::language_storage::TypeTag", i))
+                .collect();
+            let mut args = vec![];
+            for param in function.params.iter().filter(|param| !is_signer(param)) {
+                let arg = format!("arg{}", args.len());
+                let ty = if is_generic(param) {
+                    "&impl ::serde::Serialize".to_string()
+                } else {
+                    self.rust_type(param)?
+                };
+                params.push(format!("{}: {}", arg, ty));
+                args.push(format!("::aptos_sdk::bcs::to_bytes(&{}).unwrap()", arg));
+            }
+            writeln!(
+                out,
+                "/// The payload calling `{}::{}`.\n\
+                pub fn {}({}) -> ::aptos_sdk::types::transaction::TransactionPayload {{\n    \
+                    ::aptos_sdk::types::transaction::TransactionPayload::EntryFunction(\n        \
+                        ::aptos_sdk::types::transaction::EntryFunction::new(\n            \
+                            ::aptos_sdk::move_types::language_storage::ModuleId::new(\n                \
+                                MODULE_ADDRESS,\n                \
+                                ::aptos_sdk::move_types::identifier::Identifier::new(MODULE_NAME).unwrap(),\n            \
+                            ),\n            \
+                            ::aptos_sdk::move_types::identifier::Identifier::new(\"{}\").unwrap(),\n            \
+                            vec![{}],\n            \
+                            vec![{}],\n        \
+                        ),\n    \
+                    )\n\
+                }}\n",
+                module_id.short_str_lossless(),
+                function.name,
+                rust_ident(function.name.as_str()),
+                params.join(", "),
+                function.name,
+                (0..function.generic_type_params.len())
+                    .map(|i| format!("t{}", i))
+                    .join(", "),
+                args.join(", "),
+            )?;
+        }
+        Ok(out)
+    }
+
+    fn render_struct(
+        &self,
+        module_id: &ModuleId,
+        struct_: &MoveStruct,
+        out: &mut String,
+    ) -> Result<()> {
+        let type_params = struct_.generic_type_params.len();
+        let mut used = BTreeSet::new();
+        for field in &struct_.fields {
+            generic_params(&field.typ, &mut used);
+        }
+
+        writeln!(
+            out,
+            "/// `{}::{}`\n\
+            #[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]\n\
+            pub struct {}{} {{",
+            module_id.short_str_lossless(),
+            struct_.name,
+            rust_ident(struct_.name.as_str()),
+            rust_type_params(type_params)
+        )?;
+        for field in &struct_.fields {
+            writeln!(
+                out,
+                "    pub {}: {},",
+                rust_ident(field.name.as_str()),
+                self.rust_type(&field.typ)?
+            )?;
+        }
+        // Phantom type parameters aren't used by fields, but Rust requires every parameter to be used
+        if used.len() < type_params {
+            writeln!(
+                out,
+                "    #[serde(skip)]\n    pub _phantom: ::std::marker::PhantomData<({},)>,",
+                (0..type_params)
+                    .filter(|i| !used.contains(&(*i as u16)))
+                    .map(|i| format!("T{}", i))
+                    .join(", ")
+            )?;
+        }
+        writeln!(out, "}}\n")?;
+        Ok(())
+    }
+
+    /// The Rust type with the same BCS encoding as `ty`. Strings, options and objects of the
+    /// framework map to their Rust equivalents, other structs to the generated types.
+    fn rust_type(&self, ty: &MoveType) -> Result<String> {
+        Ok(match ty {
+            MoveType::Bool => "bool".to_string(),
+            MoveType::U8 => "u8".to_string(),
+            MoveType::U16 => "u16".to_string(),
+            MoveType::U32 => "u32".to_string(),
+            MoveType::U64 => "u64".to_string(),
+            MoveType::U128 => "u128".to_string(),
+            MoveType::U256 => "::aptos_sdk::move_types::u256::U256".to_string(),
+            MoveType::Address | MoveType::Signer => {
+                "::aptos_sdk::types::account_address::AccountAddress".to_string()
+            },
+            MoveType::Vector { items } => format!("::std::vec::Vec<{}>", self.rust_type(items)?),
+            MoveType::Struct(tag) => {
+                let address = AccountAddress::from(tag.address);
+                match framework_type(address, tag.module.as_str(), tag.name.as_str()) {
+                    Some(FrameworkType::String) => "::std::string::String".to_string(),
+                    Some(FrameworkType::Object) => {
+                        "::aptos_sdk::types::account_address::AccountAddress".to_string()
+                    },
+                    Some(FrameworkType::Option) => format!(
+                        "::std::option::Option<{}>",
+                        self.rust_type(&tag.generic_type_params[0])?
+                    ),
+                    None => {
+                        let module_id = ModuleId::new(address, tag.module.0.clone());
+                        let type_args = if tag.generic_type_params.is_empty() {
+                            String::new()
+                        } else {
+                            format!(
+                                "<{}>",
+                                tag.generic_type_params
+                                    .iter()
+                                    .map(|ty| self.rust_type(ty))
+                                    .collect::<Result<Vec<_>>>()?
+                                    .join(", ")
+                            )
+                        };
+                        format!(
+                            "super::{}::{}{}",
+                            self.rust_module_name(&module_id),
+                            rust_ident(tag.name.as_str()),
+                            type_args
+                        )
+                    },
+                }
+            },
+            MoveType::GenericTypeParam { index } => format!("T{}", index),
+            MoveType::Reference { to, .. } => self.rust_type(to)?,
+            MoveType::Unparsable(ty) => bail!("Unparsable type {}", ty),
+        })
+    }
+}
+
+/// Framework structs whose BCS encoding is the one of a Rust type.
+enum FrameworkType {
+    String,
+    Option,
+    Object,
+}
+
+fn framework_type(address: AccountAddress, module: &str, name: &str) -> Option<FrameworkType> {
+    if address != CORE_CODE_ADDRESS {
+        return None;
+    }
+    match (module, name) {
+        ("string", "String") => Some(FrameworkType::String),
+        ("option", "Option") => Some(FrameworkType::Option),
+        ("object", "Object") => Some(FrameworkType::Object),
+        _ => None,
+    }
+}
+
+/// Collects the structs `ty` refers to which need a generated type.
+fn referenced_structs(ty: &MoveType, referenced: &mut Vec<(ModuleId, Identifier)>) -> Result<()> {
+    match ty {
+        MoveType::Vector { items } => referenced_structs(items, referenced)?,
+        MoveType::Reference { to, .. } => referenced_structs(to, referenced)?,
+        MoveType::Struct(tag) => {
+            let address = AccountAddress::from(tag.address);
+            match framework_type(address, tag.module.as_str(), tag.name.as_str()) {
+                // The type of an object is only a marker
+                Some(FrameworkType::String) | Some(FrameworkType::Object) => {},
+                Some(FrameworkType::Option) => {
+                    for ty in &tag.generic_type_params {
+                        referenced_structs(ty, referenced)?;
+                    }
+                },
+                None => {
+                    for ty in &tag.generic_type_params {
+                        referenced_structs(ty, referenced)?;
+                    }
+                    referenced.push((
+                        ModuleId::new(address, tag.module.0.clone()),
+                        tag.name.0.clone(),
+                    ));
+                },
+            }
+        },
+        MoveType::Unparsable(ty) => bail!("Unparsable type {}", ty),
+        _ => {},
+    }
+    Ok(())
+}
+
+/// Collects the indices of the type parameters `ty` uses.
+fn generic_params(ty: &MoveType, used: &mut BTreeSet<u16>) {
+    match ty {
+        MoveType::GenericTypeParam { index } => {
+            used.insert(*index);
+        },
+        MoveType::Vector { items } => generic_params(items, used),
+        MoveType::Reference { to, .. } => generic_params(to, used),
+        MoveType::Struct(tag) => {
+            if framework_type(tag.address.into(), tag.module.as_str(), tag.name.as_str())
+                .map_or(true, |ty| !matches!(ty, FrameworkType::Object))
+            {
+                for ty in &tag.generic_type_params {
+                    generic_params(ty, used);
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+fn is_generic(ty: &MoveType) -> bool {
+    let mut used = BTreeSet::new();
+    generic_params(ty, &mut used);
+    !used.is_empty()
+}
+
+fn is_signer(ty: &MoveType) -> bool {
+    match ty {
+        MoveType::Signer => true,
+        MoveType::Reference { to, .. } => is_signer(to),
+        _ => false,
+    }
+}
+
+fn rust_type_params(count: usize) -> String {
+    if count == 0 {
+        return String::new();
+    }
+    format!("<{}>", (0..count).map(|i| format!("T{}", i)).join(", "))
+}
+
+/// `name` as a Rust identifier: keywords are escaped, or suffixed if they can't be.
+fn rust_ident(name: &str) -> String {
+    match name {
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        _ if RUST_KEYWORDS.contains(&name) => format!("r#{}", name),
+        _ => name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_rest_client::testing::market_modules;
+
+    #[tokio::test]
+    async fn test_generate() {
+        // Every struct the package refers to is in the package or the framework, so nothing is
+        // fetched from the node
+        let client = Client::new("http://localhost:8080".parse().unwrap());
+        let mut generator = RustClientGenerator::new(client, 1);
+        generator.add_package_module(market_modules().pop().unwrap());
+        let files = generator.generate().await.unwrap();
+
+        let expected = r#"/// `0xcafe::market::Listing`
+#[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Listing<T0> {
+    pub id: u64,
+    pub items: ::std::vec::Vec<::std::vec::Vec<T0>>,
+    pub price: ::std::option::Option<super::market::Price>,
+    pub wrapped: super::market::Wrapper<::std::vec::Vec<T0>>,
+    pub seller: ::aptos_sdk::types::account_address::AccountAddress,
+}
+
+/// `0xcafe::market::Price`
+#[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Price {
+    pub amount: u64,
+    pub currency: ::std::string::String,
+}
+
+/// `0xcafe::market::Wrapper`
+#[derive(Clone, Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+pub struct Wrapper<T0> {
+    pub value: T0,
+}
+
+pub const MODULE_ADDRESS: ::aptos_sdk::types::account_address::AccountAddress =
+    ::aptos_sdk::types::account_address::AccountAddress::new([0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 202, 254]);
+
+pub const MODULE_NAME: &str = "market";
+
+/// The payload calling `0xcafe::market::list`.
+pub fn list(t0: ::aptos_sdk::move_types::language_storage::TypeTag, arg0: &impl ::serde::Serialize, arg1: ::std::option::Option<u64>) -> ::aptos_sdk::types::transaction::TransactionPayload {
+    ::aptos_sdk::types::transaction::TransactionPayload::EntryFunction(
+        ::aptos_sdk::types::transaction::EntryFunction::new(
+            ::aptos_sdk::move_types::language_storage::ModuleId::new(
+                MODULE_ADDRESS,
+                ::aptos_sdk::move_types::identifier::Identifier::new(MODULE_NAME).unwrap(),
+            ),
+            ::aptos_sdk::move_types::identifier::Identifier::new("list").unwrap(),
+            vec![t0],
+            vec![::aptos_sdk::bcs::to_bytes(&arg0).unwrap(), ::aptos_sdk::bcs::to_bytes(&arg1).unwrap()],
+        ),
+    )
+}
+
+"#;
+        assert_eq!(files, vec![
+            ("market.rs".to_string(), expected.to_string()),
+            (
+                "mod.rs".to_string(),
+                "#[allow(dead_code, non_camel_case_types, non_snake_case, clippy::all)]\n\
+                pub mod market;\n"
+                    .to_string()
+            ),
+        ]);
+    }
+
+    #[test]
+    fn test_rust_ident() {
+        assert_eq!(rust_ident("type"), "r#type");
+        assert_eq!(rust_ident("self"), "self_");
+        assert_eq!(rust_ident("amount"), "amount");
+    }
+}
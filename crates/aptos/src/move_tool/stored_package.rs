@@ -6,8 +6,9 @@ use aptos_framework::{
     natives::code::{ModuleMetadata, PackageMetadata, PackageRegistry, UpgradePolicy},
     unzip_metadata_str,
 };
-use aptos_rest_client::Client;
+use aptos_rest_client::{aptos_api_types::well_known, Client};
 use aptos_types::account_address::AccountAddress;
+use move_bytecode_source_map::source_map::SourceMap;
 use move_package::compilation::package_layout::CompiledPackageLayout;
 use reqwest::Url;
 use std::{fs, path::Path};
@@ -36,7 +37,10 @@ impl CachedPackageRegistry {
         let client = Client::new(url);
         // Need to use a different type to deserialize JSON
         let inner = client
-            .get_account_resource_bcs::<PackageRegistry>(addr, "0x1::code::PackageRegistry")
+            .get_account_resource_bcs::<PackageRegistry>(
+                addr,
+                &well_known::package_registry().to_string(),
+            )
             .await?
             .into_inner();
         Ok(Self { inner })
@@ -211,4 +215,12 @@ impl<'a> CachedModuleMetadata<'a> {
     pub fn zipped_source_map_raw(&self) -> &[u8] {
         &self.metadata.source_map
     }
+
+    pub fn source(&self) -> anyhow::Result<Option<String>> {
+        self.metadata.source()
+    }
+
+    pub fn source_map(&self) -> anyhow::Result<Option<SourceMap>> {
+        self.metadata.source_map()
+    }
 }
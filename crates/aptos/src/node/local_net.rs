@@ -0,0 +1,375 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs a single node local testnet inside the current process, so Rust integration tests can
+//! run against a real node (and faucet) without shelling out to the CLI.
+//!
+//! ```ignore
+//! let local_net = LocalNet::builder()
+//!     .with_features(vec![FeatureFlag::STRUCT_CONSTRUCTORS])
+//!     .start()
+//!     .await?;
+//! let client = local_net.rest_client();
+//! local_net.faucet_client().unwrap().fund(address, 100_000_000).await?;
+//! ...
+//! local_net.shutdown().await?;
+//! ```
+//!
+//! The node sets up process wide state (logger, node identity), so only one `LocalNet` can be
+//! started per process. Tests that need separate networks should run in separate processes,
+//! e.g. separate integration test files.
+
+use anyhow::{anyhow, bail, Context};
+use aptos_config::{config::ChaosConfig, utils::get_available_port};
+use aptos_faucet_core::server::{FunderKeyEnum, RunConfig};
+use aptos_framework::ReleaseBundle;
+use aptos_rest_client::{Client, FaucetClient};
+use aptos_temppath::TempPath;
+use aptos_types::{on_chain_config::FeatureFlag, state_store::state_export::StateExport};
+use rand::{rngs::StdRng, SeedableRng};
+use reqwest::Url;
+use std::{
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+use tokio::{task::JoinHandle, time::Instant};
+
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(60);
+const WAIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Configures and starts a [`LocalNet`]
+pub struct LocalNetBuilder {
+    test_dir: Option<PathBuf>,
+    framework: ReleaseBundle,
+    features: Option<Vec<FeatureFlag>>,
+    seed: Option<[u8; 32]>,
+    with_faucet: bool,
+    startup_timeout: Duration,
+    chaos: Option<ChaosConfig>,
+    imported_state: Option<StateExport>,
+}
+
+impl Default for LocalNetBuilder {
+    fn default() -> Self {
+        Self {
+            test_dir: None,
+            framework: aptos_cached_packages::head_release_bundle().clone(),
+            features: None,
+            seed: None,
+            with_faucet: true,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+            chaos: None,
+            imported_state: None,
+        }
+    }
+}
+
+impl LocalNetBuilder {
+    /// Features enabled at genesis, instead of `aptos_vm_genesis::default_features()`
+    pub fn with_features(mut self, features: Vec<FeatureFlag>) -> Self {
+        self.features = Some(features);
+        self
+    }
+
+    /// Framework published at genesis, instead of the head framework
+    pub fn with_framework(mut self, framework: ReleaseBundle) -> Self {
+        self.framework = framework;
+        self
+    }
+
+    /// Directory to keep the node's files in. If it already holds a network it is restarted,
+    /// otherwise a new one is created. Defaults to a temporary directory that is deleted when
+    /// the `LocalNet` is dropped.
+    pub fn with_test_dir(mut self, test_dir: PathBuf) -> Self {
+        self.test_dir = Some(test_dir);
+        self
+    }
+
+    /// Seed for generating the keys at genesis, for deterministic keys across runs
+    pub fn with_seed(mut self, seed: [u8; 32]) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Whether to run a faucet alongside the node. Defaults to true.
+    pub fn with_faucet(mut self, with_faucet: bool) -> Self {
+        self.with_faucet = with_faucet;
+        self
+    }
+
+    /// How long to wait for the node and the faucet to be ready
+    pub fn with_startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Faults for the node's API to inject, for testing client retry logic. Faults are drawn
+    /// from a seeded RNG, so a given sequence of requests always sees the same faults.
+    pub fn with_chaos(mut self, chaos: ChaosConfig) -> Self {
+        self.chaos = Some(chaos);
+        self
+    }
+
+    /// State exported from another network with `aptos-db-tool export-state`, loaded into the
+    /// chain at genesis. Ignored if the test dir already holds a network.
+    pub fn with_imported_state(mut self, imported_state: StateExport) -> Self {
+        self.imported_state = Some(imported_state);
+        self
+    }
+
+    /// Creates the network (unless the test dir already holds one), starts the node and the
+    /// faucet, and waits until both are ready to serve requests.
+    pub async fn start(self) -> anyhow::Result<LocalNet> {
+        let (temp_dir, test_dir) = match self.test_dir {
+            Some(test_dir) => (None, test_dir),
+            None => {
+                let temp_dir = TempPath::new();
+                let test_dir = temp_dir.path().to_path_buf();
+                (Some(temp_dir), test_dir)
+            },
+        };
+        let rng = self
+            .seed
+            .map(StdRng::from_seed)
+            .unwrap_or_else(StdRng::from_entropy);
+        let init_genesis_config =
+            (self.features.is_some() || self.imported_state.is_some()).then(|| {
+                let features = self.features;
+                let imported_state = self.imported_state;
+                Arc::new(
+                    move |genesis_config: &mut aptos_genesis::builder::GenesisConfiguration| {
+                        if let Some(features) = &features {
+                            genesis_config.initial_features_override = Some(features.clone());
+                        }
+                        genesis_config.imported_state = imported_state.clone();
+                    },
+                ) as aptos_genesis::builder::InitGenesisConfigFn
+            });
+
+        let framework = self.framework;
+        let mut environment = tokio::task::spawn_blocking(move || {
+            aptos_node::create_test_environment(
+                None,
+                test_dir,
+                true,
+                false,
+                &framework,
+                init_genesis_config,
+                rng,
+            )
+        })
+        .await??;
+        environment.config.api.chaos = self.chaos;
+        let test_dir = environment.test_dir.clone();
+        let root_key_path = environment.root_key_path();
+        let rest_url = Url::parse(&format!(
+            "http://127.0.0.1:{}",
+            environment.config.api.address.port()
+        ))?;
+
+        let node = NodeThread::spawn(environment).await?;
+        let mut local_net = LocalNet {
+            rest_url,
+            faucet_url: None,
+            test_dir,
+            root_key_path,
+            node,
+            faucet: None,
+            _temp_dir: temp_dir,
+        };
+
+        let deadline = Instant::now() + self.startup_timeout;
+        let rest_client = local_net.rest_client();
+        wait_until(deadline, "REST API", || async {
+            rest_client.get_index().await.is_ok()
+        })
+        .await?;
+
+        if self.with_faucet {
+            let faucet_port = get_available_port();
+            let faucet_url = Url::parse(&format!("http://127.0.0.1:{}", faucet_port))?;
+            let faucet_config = RunConfig::build_for_cli(
+                local_net.rest_url.clone(),
+                faucet_port,
+                FunderKeyEnum::KeyFile(local_net.root_key_path.clone()),
+                false,
+                None,
+            );
+            local_net.faucet = Some(tokio::spawn(faucet_config.run()));
+
+            let http_client = reqwest::Client::new();
+            wait_until(deadline, "faucet", || async {
+                match http_client.get(faucet_url.clone()).send().await {
+                    Ok(response) => response.status().is_success(),
+                    Err(_) => false,
+                }
+            })
+            .await?;
+            local_net.faucet_url = Some(faucet_url);
+        }
+
+        Ok(local_net)
+    }
+}
+
+/// A single node local testnet running in this process, see the module docs. The node and the
+/// faucet are stopped when this is dropped, which blocks until the node exits, or explicitly with
+/// [`LocalNet::shutdown`], which doesn't block the runtime.
+pub struct LocalNet {
+    rest_url: Url,
+    faucet_url: Option<Url>,
+    test_dir: PathBuf,
+    root_key_path: PathBuf,
+    node: NodeThread,
+    faucet: Option<JoinHandle<anyhow::Result<()>>>,
+    // Deletes the test dir on drop, if it's a temporary one
+    _temp_dir: Option<TempPath>,
+}
+
+impl LocalNet {
+    pub fn builder() -> LocalNetBuilder {
+        LocalNetBuilder::default()
+    }
+
+    pub fn rest_url(&self) -> &Url {
+        &self.rest_url
+    }
+
+    /// `None` if the faucet was disabled
+    pub fn faucet_url(&self) -> Option<&Url> {
+        self.faucet_url.as_ref()
+    }
+
+    pub fn rest_client(&self) -> Client {
+        Client::new(self.rest_url.clone())
+    }
+
+    /// `None` if the faucet was disabled
+    pub fn faucet_client(&self) -> Option<FaucetClient> {
+        self.faucet_url
+            .clone()
+            .map(|faucet_url| FaucetClient::new(faucet_url, self.rest_url.clone()))
+    }
+
+    /// Directory holding the node's data, config and logs
+    pub fn test_dir(&self) -> &Path {
+        &self.test_dir
+    }
+
+    /// Key of the root account, which can mint coins
+    pub fn root_key_path(&self) -> &Path {
+        &self.root_key_path
+    }
+
+    /// Stops the faucet and the node, and waits for the node to exit.
+    pub async fn shutdown(mut self) -> anyhow::Result<()> {
+        if let Some(faucet) = self.faucet.take() {
+            faucet.abort();
+        }
+        let node = self.node.stop();
+        tokio::task::spawn_blocking(move || match node {
+            Some(node) => node
+                .join()
+                .map_err(|_| anyhow!("The local node panicked while shutting down")),
+            None => Ok(()),
+        })
+        .await?
+    }
+}
+
+impl Drop for LocalNet {
+    fn drop(&mut self) {
+        if let Some(faucet) = self.faucet.take() {
+            faucet.abort();
+        }
+        // The node must be gone before the temporary test dir holding its db is deleted, which
+        // happens once this returns
+        let _ = self.node.join();
+    }
+}
+
+/// The node runs on its own thread, which owns the node's runtimes: they can't be dropped from
+/// within an async context.
+struct NodeThread {
+    shutdown_tx: mpsc::Sender<()>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl NodeThread {
+    async fn spawn(environment: aptos_node::TestEnvironment) -> anyhow::Result<Self> {
+        let (shutdown_tx, shutdown_rx) = mpsc::channel();
+        let (started_tx, started_rx) = futures::channel::oneshot::channel();
+        let log_file = environment.log_file();
+        let handle = thread::Builder::new()
+            .name("local-net-node".into())
+            .spawn(move || {
+                match aptos_node::initialize_and_start_node(
+                    environment.config,
+                    None,
+                    Some(log_file),
+                    false,
+                ) {
+                    Ok(node_handle) => {
+                        let _ = started_tx.send(Ok(()));
+                        // Either shutdown was requested or the LocalNet was dropped
+                        let _ = shutdown_rx.recv();
+                        drop(node_handle);
+                    },
+                    Err(err) => {
+                        let _ = started_tx.send(Err(err));
+                    },
+                }
+            })
+            .context("Failed to spawn the local node thread")?;
+
+        match started_rx.await {
+            Ok(Ok(())) => Ok(Self {
+                shutdown_tx,
+                handle: Some(handle),
+            }),
+            Ok(Err(err)) => Err(err.context("Failed to start the local node")),
+            Err(_) => bail!("The local node panicked while starting"),
+        }
+    }
+
+    fn stop(&mut self) -> Option<thread::JoinHandle<()>> {
+        let _ = self.shutdown_tx.send(());
+        self.handle.take()
+    }
+
+    /// Stops the node and blocks until its thread exits, i.e. its runtimes are shut down.
+    fn join(&mut self) -> anyhow::Result<()> {
+        match self.stop() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| anyhow!("The local node panicked while shutting down")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for NodeThread {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+async fn wait_until<F, Fut>(
+    deadline: Instant,
+    component: &str,
+    mut is_ready: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    while Instant::now() < deadline {
+        if is_ready().await {
+            return Ok(());
+        }
+        tokio::time::sleep(WAIT_INTERVAL).await;
+    }
+    bail!("The local {} did not start up in time", component)
+}
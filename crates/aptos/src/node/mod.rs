@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 pub mod analyze;
+pub mod local_net;
 
 use crate::{
     common::{
@@ -29,11 +30,17 @@ use aptos_cached_packages::aptos_stdlib;
 use aptos_config::config::NodeConfig;
 use aptos_crypto::{bls12381, bls12381::PublicKey, x25519, ValidCryptoMaterialStringExt};
 use aptos_faucet_core::server::{FunderKeyEnum, RunConfig};
-use aptos_genesis::config::{HostAndPort, OperatorConfiguration};
+use aptos_genesis::{
+    builder::{GenesisConfiguration, InitGenesisConfigFn},
+    config::{HostAndPort, OperatorConfiguration},
+};
 use aptos_network_checker::args::{
     validate_address, CheckEndpointArgs, HandshakeArgs, NodeAddressArgs,
 };
-use aptos_rest_client::{aptos_api_types::VersionedEvent, Client, State};
+use aptos_rest_client::{
+    aptos_api_types::{well_known, VersionedEvent},
+    Client, State,
+};
 use aptos_types::{
     account_address::AccountAddress,
     account_config::{BlockResource, CORE_CODE_ADDRESS},
@@ -42,6 +49,7 @@ use aptos_types::{
     on_chain_config::{ConfigurationResource, ConsensusScheme, ValidatorSet},
     stake_pool::StakePool,
     staking_contract::StakingContractStore,
+    state_store::state_export::StateExport,
     validator_info::ValidatorInfo,
     validator_performances::ValidatorPerformances,
     vesting::VestingAdminStore,
@@ -527,7 +535,7 @@ pub async fn get_stake_pool_info(
     vesting_contract: Option<AccountAddress>,
 ) -> CliTypedResult<StakePoolResult> {
     let stake_pool = client
-        .get_account_resource_bcs::<StakePool>(pool_address, "0x1::stake::StakePool")
+        .get_account_resource_bcs::<StakePool>(pool_address, &well_known::stake_pool().to_string())
         .await?
         .into_inner();
     let validator_config = client
@@ -1086,6 +1094,13 @@ pub struct RunLocalTestnet {
     #[clap(long)]
     do_not_delegate: bool,
 
+    /// State to load into the chain at genesis, exported from another network
+    ///
+    /// Created with `aptos-db-tool export-state`, e.g. to test against protocols deployed on
+    /// mainnet. Only applies when a new chain is created.
+    #[clap(long, parse(from_os_str))]
+    import_state: Option<PathBuf>,
+
     #[clap(flatten)]
     prompt_options: PromptOptions,
 }
@@ -1121,6 +1136,17 @@ impl CliCommand<()> for RunLocalTestnet {
             })?;
         }
 
+        let init_genesis_config = match &self.import_state {
+            Some(path) => {
+                let imported_state = StateExport::read_from(path)
+                    .map_err(|err| CliError::UnexpectedError(err.to_string()))?;
+                Some(Arc::new(move |genesis_config: &mut GenesisConfiguration| {
+                    genesis_config.imported_state = Some(imported_state.clone());
+                }) as InitGenesisConfigFn)
+            },
+            None => None,
+        };
+
         // Spawn the node in a separate thread
         let config_path = self.config_path.clone();
         let test_dir_copy = test_dir.clone();
@@ -1131,6 +1157,7 @@ impl CliCommand<()> for RunLocalTestnet {
                 false,
                 false,
                 aptos_cached_packages::head_release_bundle(),
+                init_genesis_config,
                 rng,
             );
             eprintln!("Node stopped unexpectedly {:#?}", result);
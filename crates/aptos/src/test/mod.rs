@@ -26,8 +26,8 @@ use crate::{
     },
     move_tool::{
         ArgWithType, CompilePackage, DownloadPackage, FrameworkPackageArgs, IncludedArtifacts,
-        IncludedArtifactsArgs, InitPackage, MemberId, PublishPackage, RunFunction, RunScript,
-        TestPackage,
+        IncludedArtifactsArgs, InitPackage, MemberId, PublishPackage, ResourceAccountPublishArgs,
+        RunFunction, RunScript, TestPackage,
     },
     node::{
         AnalyzeMode, AnalyzeValidatorPerformance, GetStakePool, InitializeValidator,
@@ -856,9 +856,12 @@ impl CliTestFramework {
             move_options: self.move_options(account_strs),
             txn_options: self.transaction_options(index, gas_options),
             override_size_check: false,
+            dry_run: false,
+            report: false,
             included_artifacts_args: IncludedArtifactsArgs {
                 included_artifacts: included_artifacts.unwrap_or(IncludedArtifacts::Sparse),
             },
+            resource_account_args: ResourceAccountPublishArgs::default(),
         }
         .execute()
         .await
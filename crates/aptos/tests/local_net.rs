@@ -0,0 +1,32 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Only one `LocalNet` can be started per process, so this test has a file of its own.
+
+use aptos::node::local_net::LocalNet;
+use aptos_types::account_address::AccountAddress;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_local_net() {
+    let local_net = LocalNet::builder()
+        .with_seed([1; 32])
+        .start()
+        .await
+        .unwrap();
+    let client = local_net.rest_client();
+    assert!(local_net.test_dir().join("0").join("node.yaml").exists());
+    assert!(local_net.root_key_path().exists());
+
+    let address = AccountAddress::random();
+    local_net
+        .faucet_client()
+        .unwrap()
+        .fund(address, 1_000)
+        .await
+        .unwrap();
+    let balance = client.get_account_balance(address).await.unwrap();
+    assert_eq!(balance.inner().get(), 1_000);
+
+    local_net.shutdown().await.unwrap();
+    assert!(client.get_index().await.is_err());
+}
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS ut_efca_index;
+ALTER TABLE user_transactions DROP COLUMN IF EXISTS gas_used,
+  DROP COLUMN IF EXISTS gas_fee,
+  DROP COLUMN IF EXISTS entry_function_contract_address;
@@ -0,0 +1,8 @@
+-- Your SQL goes here
+-- Gas and fee attribution per user transaction. All amounts are in octas except gas_used (gas units).
+-- gas_fee includes storage fees: they're charged as gas, with no fee statement to split them out.
+ALTER TABLE user_transactions
+ADD COLUMN gas_used NUMERIC NOT NULL DEFAULT 0,
+  ADD COLUMN gas_fee NUMERIC NOT NULL DEFAULT 0,
+  ADD COLUMN entry_function_contract_address VARCHAR(66) NOT NULL DEFAULT '';
+CREATE INDEX ut_efca_index ON user_transactions (entry_function_contract_address);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE coin_activities DROP COLUMN IF EXISTS coin_symbol,
+  DROP COLUMN IF EXISTS coin_decimals,
+  DROP COLUMN IF EXISTS normalized_amount;
@@ -0,0 +1,7 @@
+-- Your SQL goes here
+-- The metadata of the coin of each activity, and its amount in whole coins. Null if the coin info
+-- wasn't indexed when the activity was.
+ALTER TABLE coin_activities
+ADD COLUMN coin_symbol VARCHAR(10),
+  ADD COLUMN coin_decimals INT,
+  ADD COLUMN normalized_amount NUMERIC;
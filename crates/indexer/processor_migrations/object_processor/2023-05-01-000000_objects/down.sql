@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS o_object_address_index;
+DROP INDEX IF EXISTS o_owner_address_index;
+DROP INDEX IF EXISTS o_insat_index;
+DROP TABLE IF EXISTS objects;
+DROP INDEX IF EXISTS co_owner_address_index;
+DROP INDEX IF EXISTS co_insat_index;
+DROP TABLE IF EXISTS current_objects;
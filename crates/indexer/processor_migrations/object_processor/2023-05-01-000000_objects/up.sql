@@ -0,0 +1,33 @@
+-- Your SQL goes here
+-- history of object ownership, one row per ObjectCore write or object deletion
+CREATE TABLE IF NOT EXISTS objects (
+  transaction_version BIGINT NOT NULL,
+  write_set_change_index BIGINT NOT NULL,
+  object_address VARCHAR(66) NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  state_key_hash VARCHAR(66) NOT NULL,
+  guid_creation_num BIGINT NOT NULL,
+  allow_ungated_transfer BOOLEAN NOT NULL,
+  is_deleted BOOLEAN NOT NULL,
+  transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW(),
+  -- constraints
+  PRIMARY KEY (transaction_version, write_set_change_index)
+);
+CREATE INDEX IF NOT EXISTS o_object_address_index ON objects (object_address);
+CREATE INDEX IF NOT EXISTS o_owner_address_index ON objects (owner_address);
+CREATE INDEX IF NOT EXISTS o_insat_index ON objects (inserted_at);
+-- latest owner of every object
+CREATE TABLE IF NOT EXISTS current_objects (
+  object_address VARCHAR(66) UNIQUE PRIMARY KEY NOT NULL,
+  owner_address VARCHAR(66) NOT NULL,
+  state_key_hash VARCHAR(66) NOT NULL,
+  allow_ungated_transfer BOOLEAN NOT NULL,
+  last_guid_creation_num BIGINT NOT NULL,
+  last_transaction_version BIGINT NOT NULL,
+  is_deleted BOOLEAN NOT NULL,
+  last_transaction_timestamp TIMESTAMP NOT NULL,
+  inserted_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+CREATE INDEX IF NOT EXISTS co_owner_address_index ON current_objects (owner_address);
+CREATE INDEX IF NOT EXISTS co_insat_index ON current_objects (inserted_at);
@@ -0,0 +1,230 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Versioned schema migrations.
+//!
+//! Tables shared by every processor live in `migrations/` and are always applied. A processor that
+//! owns tables of its own keeps its up/down scripts under `processor_migrations/<processor name>/`
+//! (same layout as `migrations/`, one `<version>_<name>` directory per migration) and returns them
+//! from `TransactionProcessor::migrations`. The tailer applies them on startup right after the
+//! shared migrations, so deploying a new processor version is enough to bring its schema up to date.
+//!
+//! All migration sets are tracked in diesel's `__diesel_schema_migrations` table, so versions must
+//! be unique across `migrations/` and every `processor_migrations/` directory.
+
+use anyhow::{anyhow, Result};
+use aptos_logger::info;
+use diesel::{
+    migration::{Migration, MigrationSource},
+    pg::{Pg, PgConnection},
+};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+use std::collections::HashSet;
+
+/// Migrations for the tables shared by all processors.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
+/// Name used in logs for the shared migrations.
+pub const SHARED_MIGRATIONS_OWNER: &str = "shared";
+
+/// Applies all pending migrations in `migrations`, returning the versions that were applied in
+/// ascending order. `owner` is only used for logging.
+pub fn run_pending_migrations(
+    conn: &mut PgConnection,
+    owner: &str,
+    migrations: EmbeddedMigrations,
+) -> Result<Vec<String>> {
+    let applied = conn
+        .run_pending_migrations(migrations)
+        .map_err(|e| anyhow!("[{}] failed to run migrations: {:?}", owner, e))?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect::<Vec<_>>();
+    if !applied.is_empty() {
+        info!(
+            owner = owner,
+            versions = format!("{:?}", applied),
+            "Applied schema migrations"
+        );
+    }
+    Ok(applied)
+}
+
+/// Returns the versions of `migrations` that have already been applied, newest first.
+pub fn applied_migrations(
+    conn: &mut PgConnection,
+    migrations: &EmbeddedMigrations,
+) -> Result<Vec<String>> {
+    let known = migration_versions(migrations)?;
+    let applied = conn
+        .applied_migrations()
+        .map_err(|e| anyhow!("failed to read applied migrations: {:?}", e))?;
+    let mut applied = applied
+        .into_iter()
+        .map(|version| version.to_string())
+        .filter(|version| known.contains(version))
+        .map(|version| Ok((parse_version(&version)?, version)))
+        .collect::<Result<Vec<_>>>()?;
+    applied.sort_by(|(a, _), (b, _)| b.cmp(a));
+    Ok(applied.into_iter().map(|(_, version)| version).collect())
+}
+
+/// Runs the down scripts of every applied migration in `migrations` that is newer than
+/// `target_version`, newest first. Passing `None` reverts the whole set. Migrations belonging to
+/// other owners are never touched, even if they were applied later.
+pub fn revert_migrations(
+    conn: &mut PgConnection,
+    owner: &str,
+    migrations: EmbeddedMigrations,
+    target_version: Option<&str>,
+) -> Result<Vec<String>> {
+    let target = match target_version {
+        Some(target_version) => {
+            let target = parse_version(target_version)?;
+            let known = migration_versions(&migrations)?
+                .iter()
+                .map(|version| parse_version(version))
+                .collect::<Result<HashSet<_>>>()?;
+            if !known.contains(&target) {
+                return Err(anyhow!(
+                    "[{}] unknown target migration version {}",
+                    owner,
+                    target_version
+                ));
+            }
+            Some(target)
+        },
+        None => None,
+    };
+
+    let to_revert = applied_migrations(conn, &migrations)?
+        .into_iter()
+        .map(|version| Ok((parse_version(&version)?, version)))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|(version, _)| target.map_or(true, |target| *version > target))
+        .map(|(_, version)| version)
+        .collect::<Vec<_>>();
+    let available: Vec<Box<dyn Migration<Pg>>> = migrations
+        .migrations()
+        .map_err(|e| anyhow!("[{}] failed to load migrations: {:?}", owner, e))?;
+
+    for version in &to_revert {
+        let migration = available
+            .iter()
+            .find(|m| m.name().version().to_string() == *version)
+            .expect("Applied version was looked up from this migration set");
+        conn.revert_migration(migration.as_ref())
+            .map_err(|e| anyhow!("[{}] failed to revert {}: {:?}", owner, version, e))?;
+        info!(
+            owner = owner,
+            version = version,
+            "Reverted schema migration"
+        );
+    }
+    Ok(to_revert)
+}
+
+/// Migration versions are timestamps, ex: `2023-05-01-000000`, which diesel may also store without
+/// the dashes. They only order correctly as numbers, since the dashes sort before the digits.
+fn parse_version(version: &str) -> Result<u64> {
+    version
+        .chars()
+        .filter(|c| *c != '-')
+        .collect::<String>()
+        .parse()
+        .map_err(|_| anyhow!("invalid migration version {}", version))
+}
+
+fn migration_versions(migrations: &EmbeddedMigrations) -> Result<HashSet<String>> {
+    let migrations: Vec<Box<dyn Migration<Pg>>> = migrations
+        .migrations()
+        .map_err(|e| anyhow!("failed to load migrations: {:?}", e))?;
+    Ok(migrations
+        .iter()
+        .map(|m| m.name().version().to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_harness::setup_database;
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(parse_version("2023-05-01-000000").unwrap(), 20230501000000);
+        assert_eq!(
+            parse_version("20230501000000").unwrap(),
+            parse_version("2023-05-01-000000").unwrap()
+        );
+        // As strings, "2023-05-02-000000" < "20230501000000"
+        assert!(
+            parse_version("2023-05-02-000000").unwrap() > parse_version("20230501000000").unwrap()
+        );
+        assert!(
+            parse_version("00000000000000").unwrap() < parse_version("2022-08-08-043603").unwrap()
+        );
+        assert!(parse_version("latest").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_revert_to_target_version() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let pool = setup_database();
+        let mut conn = pool.get().unwrap();
+        let applied = applied_migrations(&mut conn, &MIGRATIONS).unwrap();
+        assert!(applied.len() > 2);
+
+        let reverted = revert_migrations(
+            &mut conn,
+            SHARED_MIGRATIONS_OWNER,
+            MIGRATIONS,
+            Some(&applied[2]),
+        )
+        .unwrap();
+        assert_eq!(reverted, applied[..2]);
+        assert_eq!(
+            applied_migrations(&mut conn, &MIGRATIONS).unwrap(),
+            applied[2..]
+        );
+
+        // Reverting to the newest applied version is a no-op
+        assert!(revert_migrations(
+            &mut conn,
+            SHARED_MIGRATIONS_OWNER,
+            MIGRATIONS,
+            Some(&applied[2])
+        )
+        .unwrap()
+        .is_empty());
+
+        let mut reapplied =
+            run_pending_migrations(&mut conn, SHARED_MIGRATIONS_OWNER, MIGRATIONS).unwrap();
+        reapplied.reverse();
+        assert_eq!(reapplied, applied[..2]);
+    }
+
+    #[tokio::test]
+    async fn test_revert_to_unknown_version() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let pool = setup_database();
+        let mut conn = pool.get().unwrap();
+        let applied = applied_migrations(&mut conn, &MIGRATIONS).unwrap();
+
+        for target in ["2000-01-01-000000", "latest"] {
+            assert!(revert_migrations(
+                &mut conn,
+                SHARED_MIGRATIONS_OWNER,
+                MIGRATIONS,
+                Some(target)
+            )
+            .is_err());
+        }
+        assert_eq!(applied_migrations(&mut conn, &MIGRATIONS).unwrap(), applied);
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod errors;
 pub mod fetcher;
+pub mod migrations;
 pub mod processing_result;
 pub mod tailer;
 pub mod transaction_processor;
@@ -5,6 +5,7 @@ use crate::{
     indexer::{
         errors::TransactionProcessingError,
         fetcher::{TransactionFetcher, TransactionFetcherOptions, TransactionFetcherTrait},
+        migrations::{run_pending_migrations, MIGRATIONS, SHARED_MIGRATIONS_OWNER},
         processing_result::ProcessingResult,
         transaction_processor::TransactionProcessor,
     },
@@ -13,10 +14,14 @@ use crate::{
         processor_status::{ProcessorStatusV2, ProcessorStatusV2Query},
     },
     schema::{ledger_infos, processor_status},
+    util::parse_timestamp,
 };
-use anyhow::{ensure, Context, Result};
+use anyhow::{anyhow, ensure, Context, Result};
 use aptos_api::context::Context as ApiContext;
+use aptos_api_types::{AsConverter, WriteSetChange};
 use aptos_logger::{debug, info};
+use aptos_types::{state_store::state_key::StateKeyInner, write_set::WriteOp};
+use aptos_vm::data_cache::IntoMoveResolver;
 use chrono::ParseError;
 use diesel::{
     pg::upsert::excluded,
@@ -24,17 +29,15 @@ use diesel::{
     sql_types::{BigInt, Text},
     ExpressionMethods, RunQueryDsl,
 };
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
 use std::{fmt::Debug, sync::Arc};
 use tokio::{sync::Mutex, task::JoinHandle};
 
-pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
-
 #[derive(Clone)]
 pub struct Tailer {
     pub transaction_fetcher: Arc<Mutex<dyn TransactionFetcherTrait>>,
     processor: Arc<dyn TransactionProcessor>,
     connection_pool: PgDbPool,
+    context: Arc<ApiContext>,
 }
 
 impl Tailer {
@@ -45,22 +48,28 @@ impl Tailer {
         options: TransactionFetcherOptions,
     ) -> Result<Tailer, ParseError> {
         let resolver = Arc::new(context.move_resolver().unwrap());
-        let transaction_fetcher = TransactionFetcher::new(context, resolver, 0, options);
+        let transaction_fetcher = TransactionFetcher::new(context.clone(), resolver, 0, options);
 
         Ok(Self {
             transaction_fetcher: Arc::new(Mutex::new(transaction_fetcher)),
             connection_pool,
             processor,
+            context,
         })
     }
 
+    /// Applies the shared migrations followed by the ones owned by the processor
     pub fn run_migrations(&self) {
-        let _ = &self
+        let mut conn = self
             .connection_pool
             .get()
-            .expect("Could not get connection for migrations")
-            .run_pending_migrations(MIGRATIONS)
+            .expect("Could not get connection for migrations");
+        run_pending_migrations(&mut conn, SHARED_MIGRATIONS_OWNER, MIGRATIONS)
             .expect("migrations failed!");
+        if let Some(migrations) = self.processor.migrations() {
+            run_pending_migrations(&mut conn, self.processor.name(), migrations)
+                .expect("processor migrations failed!");
+        }
     }
 
     /// If chain id doesn't exist, save it. Otherwise, make sure that we're indexing the same chain
@@ -166,6 +175,91 @@ impl Tailer {
         (num_txns, Some(results))
     }
 
+    /// Initializes the processor from the latest state snapshot on this node instead of replaying
+    /// every transaction since genesis: all resources in the snapshot are handed to
+    /// `TransactionProcessor::process_state_snapshot`, `chunk_size` state values at a time, and the
+    /// snapshot version is then recorded as processed. Returns the snapshot version, so streaming
+    /// should resume from the version right after it.
+    pub async fn bootstrap_from_snapshot(&self, chunk_size: usize) -> Result<u64> {
+        let processor_name = self.processor.name();
+        ensure!(
+            self.processor.supports_snapshot_bootstrap(),
+            "{} can't be bootstrapped from a state snapshot",
+            processor_name
+        );
+        let db = self.context.db.clone();
+        let latest_checkpoint_version = db
+            .get_latest_state_checkpoint_version()?
+            .context("No state checkpoint to bootstrap from")?;
+        let (version, _) = db
+            .get_state_snapshot_before(latest_checkpoint_version + 1)?
+            .context("No state snapshot to bootstrap from")?;
+        let timestamp = parse_timestamp(db.get_block_timestamp(version)?, version as i64);
+        let num_state_values = db.get_state_leaf_count(version)?;
+        info!(
+            processor_name = processor_name,
+            version = version,
+            num_state_values = num_state_values,
+            "Bootstrapping from state snapshot"
+        );
+
+        let mut change_index = 0;
+        for start_idx in (0..num_state_values).step_by(chunk_size) {
+            let changes = self.get_snapshot_changes(version, start_idx, chunk_size)?;
+            let num_changes = changes.len() as u64;
+            self.processor
+                .process_state_snapshot(changes, version, timestamp, change_index)
+                .await
+                .map_err(|tpe| anyhow!("Failed to process state snapshot: {:?}", tpe))?;
+            change_index += num_changes;
+            debug!(
+                processor_name = processor_name,
+                version = version,
+                processed = std::cmp::min(start_idx + chunk_size, num_state_values),
+                total = num_state_values,
+                "Processed state snapshot chunk"
+            );
+        }
+
+        self.update_last_processed_version(processor_name, version)?;
+        info!(
+            processor_name = processor_name,
+            version = version,
+            num_changes = change_index,
+            "Finished bootstrapping from state snapshot"
+        );
+        Ok(version)
+    }
+
+    /// Converts a chunk of the state at `version` into write set changes. Table items are
+    /// skipped since decoding them requires table info which may not be available.
+    fn get_snapshot_changes(
+        &self,
+        version: u64,
+        start_idx: usize,
+        chunk_size: usize,
+    ) -> Result<Vec<WriteSetChange>> {
+        let db = self.context.db.clone();
+        let chunk = db.get_state_value_chunk_with_proof(version, start_idx, chunk_size)?;
+        let resolver = self
+            .context
+            .state_view_at_version(version)?
+            .into_move_resolver();
+        let converter = resolver.as_converter(db);
+
+        let mut changes = vec![];
+        for (state_key, state_value) in chunk.raw_values {
+            if !matches!(state_key.inner(), StateKeyInner::AccessPath(_)) {
+                continue;
+            }
+            changes.extend(converter.try_into_write_set_changes(
+                state_key,
+                WriteOp::Modification(state_value.into_bytes()),
+            )?);
+        }
+        Ok(changes)
+    }
+
     /// Store last processed version from database. We can assume that all previously processed
     /// versions are successful because any gap would cause the processor to panic
     pub fn update_last_processed_version(&self, processor_name: &str, version: u64) -> Result<()> {
@@ -918,4 +1012,24 @@ mod test {
         tailer.set_fetcher_version(4).await;
         assert!(tailer.check_or_update_chain_id().await.is_ok());
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_snapshot_bootstrap_unsupported() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let (_conn_pool, tailer) = setup_indexer().unwrap();
+        assert!(!tailer.processor.supports_snapshot_bootstrap());
+
+        let err = tailer
+            .processor
+            .process_state_snapshot(vec![], 5, parse_timestamp(0, 5), 0)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            TransactionProcessingError::UnsupportedOperation((_, 5, 5, "default_processor"))
+        ));
+        assert!(tailer.bootstrap_from_snapshot(100).await.is_err());
+    }
 }
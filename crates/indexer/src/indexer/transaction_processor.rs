@@ -11,9 +11,10 @@ use crate::{
     models::processor_statuses::ProcessorStatusModel,
     schema,
 };
-use aptos_api_types::Transaction;
+use aptos_api_types::{Transaction, WriteSetChange};
 use async_trait::async_trait;
 use diesel::{pg::upsert::excluded, prelude::*};
+use diesel_migrations::EmbeddedMigrations;
 use field_count::FieldCount;
 use schema::processor_statuses::{self, dsl};
 use std::fmt::Debug;
@@ -38,6 +39,41 @@ pub trait TransactionProcessor: Send + Sync + Debug {
     /// This is used by the `get_conn()` helper below
     fn connection_pool(&self) -> &PgDbPool;
 
+    /// Schema migrations for the tables owned by this processor, see `indexer::migrations`.
+    /// Processors that only write to the shared tables don't need to override this.
+    fn migrations(&self) -> Option<EmbeddedMigrations> {
+        None
+    }
+
+    /// Whether this processor can be initialized from a state snapshot instead of replaying
+    /// history from genesis, see `Tailer::bootstrap_from_snapshot`. Only processors that derive
+    /// everything they store from current state (as opposed to events or history) should opt in.
+    fn supports_snapshot_bootstrap(&self) -> bool {
+        false
+    }
+
+    /// Processes a chunk of the state at snapshot `version` as if every resource in it had just
+    /// been written at that version. `first_change_index` is the index of the first change in the
+    /// chunk within the whole snapshot. Only called if `supports_snapshot_bootstrap` is true, the
+    /// default implementation returns `TransactionProcessingError::UnsupportedOperation`.
+    async fn process_state_snapshot(
+        &self,
+        _changes: Vec<WriteSetChange>,
+        version: u64,
+        _timestamp: chrono::NaiveDateTime,
+        _first_change_index: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        Err(TransactionProcessingError::UnsupportedOperation((
+            anyhow::anyhow!(
+                "{} can't be bootstrapped from a state snapshot",
+                self.name()
+            ),
+            version,
+            version,
+            self.name(),
+        )))
+    }
+
     //* Below are helper methods that don't need to be implemented *//
 
     /// Gets the connection.
@@ -18,6 +18,8 @@ pub mod models;
 pub mod processors;
 pub mod runtime;
 pub mod schema;
+#[cfg(test)]
+mod test_harness;
 mod util;
 
 /// By default, skips test unless `INDEXER_DATABASE_URL` is set.
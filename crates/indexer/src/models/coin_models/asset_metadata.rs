@@ -0,0 +1,236 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::coin_infos::{CoinInfo, CoinInfoQuery};
+use crate::{database::PgPoolConnection, schema::move_resources, util::standardize_address};
+use aptos_infallible::Mutex;
+use bigdecimal::BigDecimal;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl};
+use std::{collections::HashMap, fmt::Debug};
+
+const FUNGIBLE_ASSET_METADATA_TYPE: &str = "0x1::fungible_asset::Metadata";
+
+/// What processors need to know about an asset to store human readable amounts.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AssetMetadata {
+    pub symbol: String,
+    pub decimals: i32,
+}
+
+impl AssetMetadata {
+    /// `amount` in whole units of the asset, e.g. 1.5 for 150000000 octas.
+    pub fn normalize(&self, amount: &BigDecimal) -> BigDecimal {
+        let (digits, scale) = amount.as_bigint_and_exponent();
+        BigDecimal::new(digits, scale + self.decimals as i64).normalized()
+    }
+
+    /// From the data of a `0x1::fungible_asset::Metadata` resource.
+    pub fn from_fungible_asset_metadata(data: &serde_json::Value) -> Option<Self> {
+        Some(Self {
+            symbol: data.get("symbol")?.as_str()?.to_string(),
+            decimals: data.get("decimals")?.as_u64()? as i32,
+        })
+    }
+
+    /// The metadata of the fungible asset whose metadata object is at `address`, from the
+    /// latest `0x1::fungible_asset::Metadata` resource the default processor stored there.
+    fn get_fungible_asset(
+        address: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Option<Self>> {
+        let resource = move_resources::table
+            .filter(move_resources::address.eq(standardize_address(address)))
+            .filter(move_resources::module.eq("fungible_asset"))
+            .filter(move_resources::name.eq("Metadata"))
+            .filter(move_resources::type_.eq(FUNGIBLE_ASSET_METADATA_TYPE))
+            .order(move_resources::transaction_version.desc())
+            .select((move_resources::data, move_resources::is_deleted))
+            .first::<(Option<serde_json::Value>, bool)>(conn)
+            .optional()?;
+        Ok(match resource {
+            Some((Some(data), false)) => Self::from_fungible_asset_metadata(&data),
+            _ => None,
+        })
+    }
+
+    /// The metadata of `asset_type` in the database: a coin type, looked up in `coin_infos`, or
+    /// the address of a fungible asset's metadata object.
+    fn get(asset_type: &str, conn: &mut PgPoolConnection) -> diesel::QueryResult<Option<Self>> {
+        if asset_type.contains("::") {
+            Ok(
+                CoinInfoQuery::get_by_coin_type(asset_type.to_string(), conn)?
+                    .as_ref()
+                    .map(Self::from),
+            )
+        } else {
+            Self::get_fungible_asset(asset_type, conn)
+        }
+    }
+}
+
+impl From<&CoinInfo> for AssetMetadata {
+    fn from(coin_info: &CoinInfo) -> Self {
+        Self {
+            symbol: coin_info.symbol.clone(),
+            decimals: coin_info.decimals,
+        }
+    }
+}
+
+impl From<&CoinInfoQuery> for AssetMetadata {
+    fn from(coin_info: &CoinInfoQuery) -> Self {
+        Self {
+            symbol: coin_info.symbol.clone(),
+            decimals: coin_info.decimals,
+        }
+    }
+}
+
+/// Where processors get the metadata of the assets in the rows they store, to enrich them. Asset
+/// types are coin types, ex: `0x1::aptos_coin::AptosCoin`, or the addresses of the metadata
+/// objects of fungible assets.
+pub trait AssetMetadataProvider: Send + Sync + Debug {
+    /// Makes the metadata of an asset created in the batch being processed known, as it isn't in
+    /// the database yet.
+    fn insert(&self, asset_type: String, metadata: AssetMetadata);
+
+    /// The metadata of `asset_type`, or `None` if it isn't indexed (yet).
+    fn lookup(
+        &self,
+        asset_type: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Option<AssetMetadata>>;
+}
+
+/// Looks up the metadata of assets in the database and keeps what it found, since the symbol and
+/// decimals of an asset can't change once created. Assets that weren't found are remembered too,
+/// so unindexed coins don't cost a query per activity; `insert` replaces those once the asset
+/// shows up in a batch.
+#[derive(Debug, Default)]
+pub struct AssetMetadataCache {
+    cache: Mutex<HashMap<String, Option<AssetMetadata>>>,
+}
+
+impl AssetMetadataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl AssetMetadataProvider for AssetMetadataCache {
+    fn insert(&self, asset_type: String, metadata: AssetMetadata) {
+        self.cache.lock().insert(asset_type, Some(metadata));
+    }
+
+    fn lookup(
+        &self,
+        asset_type: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Option<AssetMetadata>> {
+        if let Some(metadata) = self.cache.lock().get(asset_type) {
+            return Ok(metadata.clone());
+        }
+        let metadata = AssetMetadata::get(asset_type, conn)?;
+        self.cache
+            .lock()
+            .entry(asset_type.to_string())
+            .or_insert_with(|| metadata.clone());
+        Ok(metadata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{models::move_resources::MoveResource, test_harness::setup_database};
+    use serde_json::json;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_normalize() {
+        let apt = AssetMetadata {
+            symbol: "APT".to_string(),
+            decimals: 8,
+        };
+        assert_eq!(
+            apt.normalize(&BigDecimal::from(150_000_000)),
+            BigDecimal::from_str("1.5").unwrap()
+        );
+        assert_eq!(
+            apt.normalize(&BigDecimal::from(1)),
+            BigDecimal::from_str("0.00000001").unwrap()
+        );
+        let no_decimals = AssetMetadata {
+            symbol: "NFT".to_string(),
+            decimals: 0,
+        };
+        assert_eq!(
+            no_decimals.normalize(&BigDecimal::from(42)),
+            BigDecimal::from(42)
+        );
+    }
+
+    #[test]
+    fn test_from_fungible_asset_metadata() {
+        let data = json!({
+            "supply": "1000",
+            "maximum": { "vec": [] },
+            "name": "Tether USD",
+            "symbol": "USDT",
+            "decimals": 6,
+        });
+        assert_eq!(
+            AssetMetadata::from_fungible_asset_metadata(&data),
+            Some(AssetMetadata {
+                symbol: "USDT".to_string(),
+                decimals: 6,
+            })
+        );
+        assert_eq!(
+            AssetMetadata::from_fungible_asset_metadata(&json!({ "symbol": "USDT" })),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_asset_metadata_cache() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let pool = setup_database();
+        let mut conn = pool.get().unwrap();
+        let cache = AssetMetadataCache::new();
+        let usdt = AssetMetadata {
+            symbol: "USDT".to_string(),
+            decimals: 6,
+        };
+
+        // Not indexed yet, and remembered as such
+        assert_eq!(cache.lookup("0xa", &mut conn).unwrap(), None);
+        diesel::insert_into(move_resources::table)
+            .values(&MoveResource {
+                transaction_version: 1,
+                write_set_change_index: 0,
+                transaction_block_height: 0,
+                name: "Metadata".to_string(),
+                type_: FUNGIBLE_ASSET_METADATA_TYPE.to_string(),
+                address: standardize_address("0xa"),
+                module: "fungible_asset".to_string(),
+                generic_type_params: None,
+                data: Some(json!({ "symbol": "USDT", "decimals": 6 })),
+                is_deleted: false,
+            })
+            .execute(&mut conn)
+            .unwrap();
+        assert_eq!(cache.lookup("0xa", &mut conn).unwrap(), None);
+        assert_eq!(
+            AssetMetadataCache::new().lookup("0xa", &mut conn).unwrap(),
+            Some(usdt.clone())
+        );
+
+        // Until the asset shows up in a batch
+        cache.insert("0xa".to_string(), usdt.clone());
+        assert_eq!(cache.lookup("0xa", &mut conn).unwrap(), Some(usdt));
+        assert_eq!(cache.lookup("0x1::coin::Unknown", &mut conn).unwrap(), None);
+    }
+}
@@ -6,6 +6,7 @@
 #![allow(clippy::unused_unit)]
 
 use super::{
+    asset_metadata::AssetMetadata,
     coin_balances::{CoinBalance, CurrentCoinBalance},
     coin_infos::{CoinInfo, CoinInfoQuery},
     coin_supply::CoinSupply,
@@ -60,6 +61,11 @@ pub struct CoinActivity {
     pub block_height: i64,
     pub transaction_timestamp: chrono::NaiveDateTime,
     pub event_index: Option<i64>,
+    /// The symbol and decimals of the coin, and `amount` in whole coins, if the coin info was
+    /// indexed. See `set_asset_metadata`.
+    pub coin_symbol: Option<String>,
+    pub coin_decimals: Option<i32>,
+    pub normalized_amount: Option<BigDecimal>,
 }
 
 impl CoinActivity {
@@ -246,6 +252,9 @@ impl CoinActivity {
             block_height,
             transaction_timestamp,
             event_index: Some(event_index),
+            coin_symbol: None,
+            coin_decimals: None,
+            normalized_amount: None,
         }
     }
 
@@ -275,6 +284,16 @@ impl CoinActivity {
             block_height: txn_info.block_height.unwrap().0 as i64,
             transaction_timestamp,
             event_index: Some(BURN_GAS_EVENT_INDEX),
+            coin_symbol: None,
+            coin_decimals: None,
+            normalized_amount: None,
         }
     }
+
+    /// Enriches the activity with the metadata of its coin, e.g. from an `AssetMetadataProvider`.
+    pub fn set_asset_metadata(&mut self, metadata: &AssetMetadata) {
+        self.coin_symbol = Some(metadata.symbol.clone());
+        self.coin_decimals = Some(metadata.decimals);
+        self.normalized_amount = Some(metadata.normalize(&self.amount));
+    }
 }
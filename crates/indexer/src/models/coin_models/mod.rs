@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod asset_metadata;
 pub mod coin_activities;
 pub mod coin_balances;
 pub mod coin_infos;
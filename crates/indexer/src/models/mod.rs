@@ -8,6 +8,7 @@ pub mod ledger_info;
 pub mod move_modules;
 pub mod move_resources;
 pub mod move_tables;
+pub mod object_models;
 pub mod processor_status;
 pub mod processor_statuses;
 pub mod property_map;
@@ -0,0 +1,324 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+// This is required because a diesel macro makes clippy sad
+#![allow(clippy::extra_unused_lifetimes)]
+#![allow(clippy::unused_unit)]
+
+use crate::{
+    database::PgPoolConnection,
+    schema::{current_objects, objects},
+    util::{parse_timestamp, standardize_address},
+};
+use anyhow::Context;
+use aptos_api_types::{
+    deserialize_from_string, DeleteResource, MoveStructTag, Transaction, WriteResource,
+    WriteSetChange,
+};
+use diesel::{
+    prelude::*,
+    sql_query,
+    sql_types::{Bool, Int4, Text},
+};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const OBJECT_CORE_TYPE: &str = "0x1::object::ObjectCore";
+const OBJECT_GROUP_TYPE: &str = "0x1::object::ObjectGroup";
+/// Objects cannot own each other in a cycle, but we still bound the walk in case the db is
+/// inconsistent (e.g. while backfilling).
+pub const MAX_OWNERSHIP_DEPTH: i32 = 32;
+
+type ObjectAddress = String;
+pub type CurrentObjectMap = HashMap<ObjectAddress, CurrentObject>;
+
+#[derive(Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(transaction_version, write_set_change_index))]
+#[diesel(table_name = objects)]
+pub struct Object {
+    pub transaction_version: i64,
+    pub write_set_change_index: i64,
+    pub object_address: String,
+    pub owner_address: String,
+    pub state_key_hash: String,
+    pub guid_creation_num: i64,
+    pub allow_ungated_transfer: bool,
+    pub is_deleted: bool,
+    pub transaction_timestamp: chrono::NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Identifiable, Insertable, Serialize)]
+#[diesel(primary_key(object_address))]
+#[diesel(table_name = current_objects)]
+pub struct CurrentObject {
+    pub object_address: String,
+    pub owner_address: String,
+    pub state_key_hash: String,
+    pub allow_ungated_transfer: bool,
+    pub last_guid_creation_num: i64,
+    pub last_transaction_version: i64,
+    pub is_deleted: bool,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+}
+
+/// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
+#[derive(Debug, Identifiable, Queryable)]
+#[diesel(primary_key(object_address))]
+#[diesel(table_name = current_objects)]
+pub struct CurrentObjectQuery {
+    pub object_address: String,
+    pub owner_address: String,
+    pub state_key_hash: String,
+    pub allow_ungated_transfer: bool,
+    pub last_guid_creation_num: i64,
+    pub last_transaction_version: i64,
+    pub is_deleted: bool,
+    pub last_transaction_timestamp: chrono::NaiveDateTime,
+    pub inserted_at: chrono::NaiveDateTime,
+}
+
+/// An object reachable from an owner through a chain of object ownership. `depth` is 1 for
+/// objects owned directly by the queried address, 2 for objects owned by those objects, etc.
+#[derive(Debug, QueryableByName, Serialize)]
+pub struct TransitivelyOwnedObject {
+    #[diesel(sql_type = Text)]
+    pub object_address: String,
+    #[diesel(sql_type = Text)]
+    pub owner_address: String,
+    #[diesel(sql_type = Bool)]
+    pub allow_ungated_transfer: bool,
+    #[diesel(sql_type = Int4)]
+    pub depth: i32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ObjectCoreResource {
+    #[serde(deserialize_with = "deserialize_from_string")]
+    pub guid_creation_num: i64,
+    pub owner: String,
+    /// Objects that can't be transferred without a `TransferRef` (soul bound)
+    pub allow_ungated_transfer: bool,
+}
+
+impl Object {
+    pub fn from_transaction(
+        transaction: &Transaction,
+        current_objects: &CurrentObjectMap,
+        conn: &mut PgPoolConnection,
+    ) -> anyhow::Result<(Vec<Self>, CurrentObjectMap)> {
+        let mut objects = vec![];
+        let mut current_objects_in_txn: CurrentObjectMap = HashMap::new();
+        if let Transaction::UserTransaction(user_txn) = transaction {
+            let txn_version = user_txn.info.version.0 as i64;
+            let txn_timestamp = parse_timestamp(user_txn.timestamp.0, txn_version);
+            for (index, wsc) in user_txn.info.changes.iter().enumerate() {
+                let maybe_object = match wsc {
+                    WriteSetChange::WriteResource(write_resource) => Self::from_write_resource(
+                        write_resource,
+                        index as i64,
+                        txn_version,
+                        txn_timestamp,
+                    )?,
+                    WriteSetChange::DeleteResource(delete_resource) => {
+                        // Prefer what we've seen in this batch, fall back to the db
+                        let address = standardize_address(&delete_resource.address.to_string());
+                        let previous = match current_objects_in_txn
+                            .get(&address)
+                            .or_else(|| current_objects.get(&address))
+                        {
+                            Some(previous) => Some(previous.clone()),
+                            None => CurrentObjectQuery::get_by_address(&address, conn)
+                                .context("Failed to look up object being deleted")?
+                                .map(CurrentObject::from),
+                        };
+                        Self::from_delete_resource(
+                            delete_resource,
+                            index as i64,
+                            txn_version,
+                            txn_timestamp,
+                            previous,
+                        )
+                    },
+                    _ => None,
+                };
+                if let Some((object, current_object)) = maybe_object {
+                    objects.push(object);
+                    current_objects_in_txn
+                        .insert(current_object.object_address.clone(), current_object);
+                }
+            }
+        }
+        Ok((objects, current_objects_in_txn))
+    }
+
+    pub fn from_write_resource(
+        write_resource: &WriteResource,
+        write_set_change_index: i64,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+    ) -> anyhow::Result<Option<(Self, CurrentObject)>> {
+        if type_str(&write_resource.data.typ) != OBJECT_CORE_TYPE {
+            return Ok(None);
+        }
+        let data = serde_json::to_value(&write_resource.data.data)?;
+        let inner: ObjectCoreResource = serde_json::from_value(data.clone()).context(format!(
+            "version {} failed! failed to parse type {}, data {:?}",
+            txn_version, OBJECT_CORE_TYPE, data
+        ))?;
+        let object_address = standardize_address(&write_resource.address.to_string());
+        let owner_address = standardize_address(&inner.owner);
+        Ok(Some((
+            Self {
+                transaction_version: txn_version,
+                write_set_change_index,
+                object_address: object_address.clone(),
+                owner_address: owner_address.clone(),
+                state_key_hash: write_resource.state_key_hash.clone(),
+                guid_creation_num: inner.guid_creation_num,
+                allow_ungated_transfer: inner.allow_ungated_transfer,
+                is_deleted: false,
+                transaction_timestamp: txn_timestamp,
+            },
+            CurrentObject {
+                object_address,
+                owner_address,
+                state_key_hash: write_resource.state_key_hash.clone(),
+                allow_ungated_transfer: inner.allow_ungated_transfer,
+                last_guid_creation_num: inner.guid_creation_num,
+                last_transaction_version: txn_version,
+                is_deleted: false,
+                last_transaction_timestamp: txn_timestamp,
+            },
+        )))
+    }
+
+    /// Deleting the object group (or the `ObjectCore` inside it) deletes the object. The
+    /// deletion doesn't carry the owner, so we keep the last known one. Objects we've never seen
+    /// (e.g. created before this processor's starting version) are skipped.
+    pub fn from_delete_resource(
+        delete_resource: &DeleteResource,
+        write_set_change_index: i64,
+        txn_version: i64,
+        txn_timestamp: chrono::NaiveDateTime,
+        previous: Option<CurrentObject>,
+    ) -> Option<(Self, CurrentObject)> {
+        let resource_type = type_str(&delete_resource.resource);
+        if resource_type != OBJECT_CORE_TYPE && resource_type != OBJECT_GROUP_TYPE {
+            return None;
+        }
+        let previous = match previous {
+            Some(previous) => previous,
+            None => {
+                aptos_logger::warn!(
+                    transaction_version = txn_version,
+                    object_address = delete_resource.address.to_string(),
+                    "Deleted object was never indexed, skipping"
+                );
+                return None;
+            },
+        };
+        Some((
+            Self {
+                transaction_version: txn_version,
+                write_set_change_index,
+                object_address: previous.object_address.clone(),
+                owner_address: previous.owner_address.clone(),
+                state_key_hash: previous.state_key_hash.clone(),
+                guid_creation_num: previous.last_guid_creation_num,
+                allow_ungated_transfer: previous.allow_ungated_transfer,
+                is_deleted: true,
+                transaction_timestamp: txn_timestamp,
+            },
+            CurrentObject {
+                last_transaction_version: txn_version,
+                is_deleted: true,
+                last_transaction_timestamp: txn_timestamp,
+                ..previous
+            },
+        ))
+    }
+}
+
+impl From<CurrentObjectQuery> for CurrentObject {
+    fn from(query: CurrentObjectQuery) -> Self {
+        Self {
+            object_address: query.object_address,
+            owner_address: query.owner_address,
+            state_key_hash: query.state_key_hash,
+            allow_ungated_transfer: query.allow_ungated_transfer,
+            last_guid_creation_num: query.last_guid_creation_num,
+            last_transaction_version: query.last_transaction_version,
+            is_deleted: query.is_deleted,
+            last_transaction_timestamp: query.last_transaction_timestamp,
+        }
+    }
+}
+
+impl CurrentObjectQuery {
+    pub fn get_by_address(
+        object_address: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Option<Self>> {
+        current_objects::table
+            .filter(current_objects::object_address.eq(object_address))
+            .first::<Self>(conn)
+            .optional()
+    }
+
+    /// All live objects owned by `owner_address`, either directly or through other objects it
+    /// owns, ordered by depth. This is what wallets need to show everything an account controls.
+    pub fn get_owned_transitively(
+        owner_address: &str,
+        conn: &mut PgPoolConnection,
+    ) -> diesel::QueryResult<Vec<TransitivelyOwnedObject>> {
+        let sql = "
+        WITH RECURSIVE owned AS
+        (
+            SELECT
+                object_address,
+                owner_address,
+                allow_ungated_transfer,
+                1 AS depth
+            FROM
+                current_objects
+            WHERE
+                owner_address = $1
+                AND is_deleted = FALSE
+            UNION
+            SELECT
+                c.object_address,
+                c.owner_address,
+                c.allow_ungated_transfer,
+                owned.depth + 1
+            FROM
+                current_objects c
+                JOIN owned ON c.owner_address = owned.object_address
+            WHERE
+                c.is_deleted = FALSE
+                AND owned.depth < $2
+        )
+        SELECT
+            object_address,
+            owner_address,
+            allow_ungated_transfer,
+            depth
+        FROM
+            owned
+        ORDER BY
+            depth,
+            object_address
+        ";
+        sql_query(sql)
+            .bind::<Text, _>(standardize_address(owner_address))
+            .bind::<Int4, _>(MAX_OWNERSHIP_DEPTH)
+            .load(conn)
+    }
+}
+
+fn type_str(struct_tag: &MoveStructTag) -> String {
+    format!(
+        "{}::{}::{}",
+        struct_tag.address, struct_tag.module, struct_tag.name
+    )
+}
@@ -36,6 +36,9 @@ pub struct UserTransaction {
     pub timestamp: chrono::NaiveDateTime,
     pub entry_function_id_str: String,
     pub epoch: i64,
+    pub gas_used: BigDecimal,
+    pub gas_fee: BigDecimal,
+    pub entry_function_contract_address: String,
 }
 
 /// Need a separate struct for queryable because we don't want to define the inserted_at column (letting DB fill)
@@ -56,6 +59,9 @@ pub struct UserTransactionQuery {
     pub entry_function_id_str: String,
     pub inserted_at: chrono::NaiveDateTime,
     pub epoch: i64,
+    pub gas_used: BigDecimal,
+    pub gas_fee: BigDecimal,
+    pub entry_function_contract_address: String,
 }
 
 impl UserTransaction {
@@ -65,6 +71,13 @@ impl UserTransaction {
         epoch: i64,
     ) -> (Self, Vec<Signature>) {
         let version = txn.info.version.0 as i64;
+        let (entry_function_id_str, entry_function_contract_address) = match &txn.request.payload {
+            TransactionPayload::EntryFunctionPayload(payload) => (
+                payload.function.to_string(),
+                standardize_address(&payload.function.module.address.inner().to_hex_literal()),
+            ),
+            _ => (String::default(), String::default()),
+        };
         (
             Self {
                 version,
@@ -84,13 +97,12 @@ impl UserTransaction {
                 ),
                 gas_unit_price: u64_to_bigdecimal(txn.request.gas_unit_price.0),
                 timestamp: parse_timestamp(txn.timestamp.0, version),
-                entry_function_id_str: match &txn.request.payload {
-                    TransactionPayload::EntryFunctionPayload(payload) => {
-                        payload.function.to_string()
-                    },
-                    _ => String::default(),
-                },
+                entry_function_id_str,
                 epoch,
+                gas_used: u64_to_bigdecimal(txn.info.gas_used.0),
+                gas_fee: u64_to_bigdecimal(txn.info.gas_used.0)
+                    * u64_to_bigdecimal(txn.request.gas_unit_price.0),
+                entry_function_contract_address,
             },
             txn.request
                 .signature
@@ -111,3 +123,79 @@ impl UserTransaction {
 
 // Prevent conflicts with other things named `Transaction`
 pub type UserTransactionModel = UserTransaction;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_api_types::Transaction as APITransaction;
+    use serde_json::{json, Value};
+
+    fn user_transaction(payload: Value) -> APIUserTransaction {
+        let txn: APITransaction = serde_json::from_value(json!({
+            "type": "user_transaction",
+            "version": "260885",
+            "hash": "0xb8bbd3936b05e3643f4b4f910bb00c9b6fa817c1935c74b9a16b5b7a2c8a69a3",
+            "state_change_hash": "0xde91b595abbeef217fb0be956df0909c1459ba8d82ed12b983e226ecbf0a4ec5",
+            "event_root_hash": "0x414343554d554c41544f525f504c414345484f4c4445525f4841534800000000",
+            "gas_used": "143",
+            "success": true,
+            "vm_status": "Executed successfully",
+            "accumulator_root_hash": "0xef40b1120b1873d2c3a4a91eafa4084e24ff1529a0f31959e88f6387054c8fe0",
+            "changes": [],
+            "sender": "0x2a0e66fde889cebf0401e676bb9bfa073e03caa9c009c66b739c30d24dccad81",
+            "sequence_number": "6",
+            "max_gas_amount": "1000",
+            "gas_unit_price": "100",
+            "expiration_timestamp_secs": "1651789617",
+            "payload": payload,
+            "signature": {
+                "type": "ed25519_signature",
+                "public_key": "0xe355b88fc001857a2cc9fe55007889cd1561aed56d187fe65729c50274c37398",
+                "signature": "0x9c1fef826ead87392f945bce527169b6627205a8d3bae77c5d8293c00b6e6a7657b4464b1fe2b36b89f5a2e64468ce7a04191d5fba431f1dc084f90292c9eb04"
+            },
+            "events": [],
+            "timestamp": "1651789018411640"
+        }))
+        .unwrap();
+        match txn {
+            APITransaction::UserTransaction(txn) => *txn,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_fees_of_entry_function() {
+        let txn = user_transaction(json!({
+            "type": "entry_function_payload",
+            "function": "0x1::aptos_account::transfer",
+            "type_arguments": [],
+            "arguments": ["0x2a0e66fde889cebf0401e676bb9bfa073e03caa9c009c66b739c30d24dccad81", "1"]
+        }));
+        let (user_transaction, signatures) = UserTransaction::from_transaction(&txn, 100, 3);
+        assert_eq!(signatures.len(), 1);
+        assert_eq!(user_transaction.gas_used, BigDecimal::from(143));
+        assert_eq!(user_transaction.gas_fee, BigDecimal::from(14_300));
+        assert_eq!(
+            user_transaction.entry_function_id_str,
+            "0x1::aptos_account::transfer"
+        );
+        assert_eq!(
+            user_transaction.entry_function_contract_address,
+            standardize_address("0x1")
+        );
+    }
+
+    #[test]
+    fn test_fees_of_script() {
+        let txn = user_transaction(json!({
+            "type": "script_payload",
+            "code": { "bytecode": "0xa11ceb0b" },
+            "type_arguments": [],
+            "arguments": []
+        }));
+        let (user_transaction, _) = UserTransaction::from_transaction(&txn, 100, 3);
+        assert_eq!(user_transaction.gas_fee, BigDecimal::from(14_300));
+        assert_eq!(user_transaction.entry_function_id_str, "");
+        assert_eq!(user_transaction.entry_function_contract_address, "");
+    }
+}
@@ -10,6 +10,7 @@ use crate::{
         transaction_processor::TransactionProcessor,
     },
     models::coin_models::{
+        asset_metadata::{AssetMetadata, AssetMetadataProvider},
         coin_activities::{CoinActivity, CurrentCoinBalancePK},
         coin_balances::{CoinBalance, CurrentCoinBalance},
         coin_infos::{CoinInfo, CoinInfoQuery},
@@ -22,16 +23,20 @@ use aptos_types::APTOS_COIN_TYPE;
 use async_trait::async_trait;
 use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
 use field_count::FieldCount;
-use std::{collections::HashMap, fmt::Debug};
+use std::{collections::HashMap, fmt::Debug, sync::Arc};
 
 pub const NAME: &str = "coin_processor";
 pub struct CoinTransactionProcessor {
     connection_pool: PgDbPool,
+    asset_metadata: Arc<dyn AssetMetadataProvider>,
 }
 
 impl CoinTransactionProcessor {
-    pub fn new(connection_pool: PgDbPool) -> Self {
-        Self { connection_pool }
+    pub fn new(connection_pool: PgDbPool, asset_metadata: Arc<dyn AssetMetadataProvider>) -> Self {
+        Self {
+            connection_pool,
+            asset_metadata,
+        }
     }
 }
 
@@ -136,6 +141,9 @@ fn insert_coin_activities(
                 .set((
                     inserted_at.eq(excluded(inserted_at)),
                     event_index.eq(excluded(event_index)),
+                    coin_symbol.eq(excluded(coin_symbol)),
+                    coin_decimals.eq(excluded(coin_decimals)),
+                    normalized_amount.eq(excluded(normalized_amount)),
                 )),
             None,
         )?;
@@ -282,6 +290,28 @@ impl TransactionProcessor for CoinTransactionProcessor {
             }
             all_current_coin_balances.extend(current_coin_balances);
         }
+        // Coins created in this batch aren't in the database yet
+        for (coin_type, coin_info) in &all_coin_infos {
+            self.asset_metadata
+                .insert(coin_type.clone(), AssetMetadata::from(coin_info));
+        }
+        for activity in &mut all_coin_activities {
+            let maybe_metadata = self
+                .asset_metadata
+                .lookup(&activity.coin_type, &mut conn)
+                .map_err(|err| {
+                    TransactionProcessingError::TransactionCommitError((
+                        anyhow::Error::from(err),
+                        start_version,
+                        end_version,
+                        self.name(),
+                    ))
+                })?;
+            if let Some(metadata) = maybe_metadata {
+                activity.set_asset_metadata(&metadata);
+            }
+        }
+
         let mut all_coin_infos = all_coin_infos.into_values().collect::<Vec<CoinInfo>>();
         let mut all_current_coin_balances = all_current_coin_balances
             .into_values()
@@ -209,7 +209,15 @@ fn insert_user_transactions(
             diesel::insert_into(schema::user_transactions::table)
                 .values(&items_to_insert[start_ind..end_ind])
                 .on_conflict(version)
-                .do_nothing(),
+                // The fee columns were added to rows which already existed with placeholder
+                // values, and reprocessing their versions is the only way to fill them in. Only
+                // those columns are updated: the others never change for a committed version.
+                .do_update()
+                .set((
+                    gas_used.eq(excluded(gas_used)),
+                    gas_fee.eq(excluded(gas_fee)),
+                    entry_function_contract_address.eq(excluded(entry_function_contract_address)),
+                )),
             None,
         )?;
     }
@@ -507,3 +515,81 @@ impl TransactionProcessor for DefaultTransactionProcessor {
         &self.connection_pool
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_harness::{count_changes, setup_database, ProcessorHarness};
+    use aptos_api_types::WriteSetChange;
+    use diesel::{QueryDsl, RunQueryDsl};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_synthetic_transactions() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let pool = setup_database();
+        let processor = DefaultTransactionProcessor::new(pool.clone());
+        let mut harness = ProcessorHarness::new(0);
+        harness.generate(30);
+        let txns = harness.pending().to_vec();
+        harness.process(&processor).await;
+
+        let events = txns
+            .iter()
+            .map(|txn| match txn {
+                Transaction::UserTransaction(txn) => txn.events.len() as i64,
+                _ => 0,
+            })
+            .sum::<i64>();
+        let mut conn = pool.get().unwrap();
+        assert_eq!(
+            schema::transactions::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            txns.len() as i64
+        );
+        assert_eq!(
+            schema::user_transactions::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            txns.len() as i64
+        );
+        assert_eq!(
+            schema::events::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            events
+        );
+        assert_eq!(
+            schema::write_set_changes::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            count_changes(&txns, |_| true)
+        );
+        assert_eq!(
+            schema::move_modules::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            count_changes(&txns, |change| matches!(
+                change,
+                WriteSetChange::WriteModule(_)
+            ))
+        );
+        assert_eq!(
+            schema::table_items::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            count_changes(&txns, |change| matches!(
+                change,
+                WriteSetChange::WriteTableItem(_) | WriteSetChange::DeleteTableItem(_)
+            ))
+        );
+    }
+}
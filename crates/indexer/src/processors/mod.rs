@@ -3,12 +3,16 @@
 
 pub mod coin_processor;
 pub mod default_processor;
+pub mod object_processor;
 pub mod stake_processor;
 pub mod token_processor;
+pub mod webhook_processor;
 
 use self::{
     coin_processor::NAME as COIN_PROCESSOR_NAME, default_processor::NAME as DEFAULT_PROCESSOR_NAME,
-    stake_processor::NAME as STAKE_PROCESSOR_NAME, token_processor::NAME as TOKEN_PROCESSOR_NAME,
+    object_processor::NAME as OBJECT_PROCESSOR_NAME, stake_processor::NAME as STAKE_PROCESSOR_NAME,
+    token_processor::NAME as TOKEN_PROCESSOR_NAME,
+    webhook_processor::NAME as WEBHOOK_PROCESSOR_NAME,
 };
 
 pub enum Processor {
@@ -16,6 +20,8 @@ pub enum Processor {
     DefaultProcessor,
     TokenProcessor,
     StakeProcessor,
+    ObjectProcessor,
+    WebhookProcessor,
 }
 
 impl Processor {
@@ -25,6 +31,8 @@ impl Processor {
             TOKEN_PROCESSOR_NAME => Self::TokenProcessor,
             COIN_PROCESSOR_NAME => Self::CoinProcessor,
             STAKE_PROCESSOR_NAME => Self::StakeProcessor,
+            OBJECT_PROCESSOR_NAME => Self::ObjectProcessor,
+            WEBHOOK_PROCESSOR_NAME => Self::WebhookProcessor,
             _ => panic!("Processor unsupported {}", input_str),
         }
     }
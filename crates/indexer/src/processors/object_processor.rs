@@ -0,0 +1,345 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::{
+        clean_data_for_db, execute_with_better_error, get_chunks, PgDbPool, PgPoolConnection,
+    },
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::object_models::objects::{CurrentObject, CurrentObjectMap, Object},
+    schema,
+};
+use aptos_api_types::{Transaction, WriteSetChange};
+use async_trait::async_trait;
+use diesel::{pg::upsert::excluded, result::Error, ExpressionMethods, PgConnection};
+use diesel_migrations::EmbeddedMigrations;
+use field_count::FieldCount;
+use std::{collections::HashMap, fmt::Debug};
+
+pub const NAME: &str = "object_processor";
+pub const MIGRATIONS: EmbeddedMigrations =
+    embed_migrations!("processor_migrations/object_processor");
+
+/// Maintains the object ownership graph (`objects`, `current_objects`), so "everything owned by
+/// X, directly or through its objects" can be answered with a single query. ANS registrations
+/// (`current_ans_lookup`) are left to the token processor, which owns that table.
+pub struct ObjectTransactionProcessor {
+    connection_pool: PgDbPool,
+}
+
+impl ObjectTransactionProcessor {
+    pub fn new(connection_pool: PgDbPool) -> Self {
+        aptos_logger::info!("init ObjectTransactionProcessor");
+        Self { connection_pool }
+    }
+}
+
+impl Debug for ObjectTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "ObjectTransactionProcessor {{ connections: {:?}  idle_connections: {:?} }}",
+            state.connections, state.idle_connections
+        )
+    }
+}
+
+fn insert_to_db_impl(
+    conn: &mut PgConnection,
+    objects: &[Object],
+    current_objects: &[CurrentObject],
+) -> Result<(), diesel::result::Error> {
+    insert_objects(conn, objects)?;
+    insert_current_objects(conn, current_objects)?;
+    Ok(())
+}
+
+fn insert_to_db(
+    conn: &mut PgPoolConnection,
+    name: &'static str,
+    start_version: u64,
+    end_version: u64,
+    objects: Vec<Object>,
+    current_objects: Vec<CurrentObject>,
+) -> Result<(), diesel::result::Error> {
+    aptos_logger::trace!(
+        name = name,
+        start_version = start_version,
+        end_version = end_version,
+        "Inserting to db",
+    );
+    match conn
+        .build_transaction()
+        .read_write()
+        .run::<_, Error, _>(|pg_conn| insert_to_db_impl(pg_conn, &objects, &current_objects))
+    {
+        Ok(_) => Ok(()),
+        Err(_) => conn
+            .build_transaction()
+            .read_write()
+            .run::<_, Error, _>(|pg_conn| {
+                let objects = clean_data_for_db(objects, true);
+                let current_objects = clean_data_for_db(current_objects, true);
+
+                insert_to_db_impl(pg_conn, &objects, &current_objects)
+            }),
+    }
+}
+
+fn insert_objects(
+    conn: &mut PgConnection,
+    items_to_insert: &[Object],
+) -> Result<(), diesel::result::Error> {
+    use schema::objects::dsl::*;
+
+    let chunks = get_chunks(items_to_insert.len(), Object::field_count());
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::objects::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict((transaction_version, write_set_change_index))
+                .do_nothing(),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+fn insert_current_objects(
+    conn: &mut PgConnection,
+    items_to_insert: &[CurrentObject],
+) -> Result<(), diesel::result::Error> {
+    use schema::current_objects::dsl::*;
+
+    let chunks = get_chunks(items_to_insert.len(), CurrentObject::field_count());
+    for (start_ind, end_ind) in chunks {
+        execute_with_better_error(
+            conn,
+            diesel::insert_into(schema::current_objects::table)
+                .values(&items_to_insert[start_ind..end_ind])
+                .on_conflict(object_address)
+                .do_update()
+                .set((
+                    owner_address.eq(excluded(owner_address)),
+                    state_key_hash.eq(excluded(state_key_hash)),
+                    allow_ungated_transfer.eq(excluded(allow_ungated_transfer)),
+                    last_guid_creation_num.eq(excluded(last_guid_creation_num)),
+                    last_transaction_version.eq(excluded(last_transaction_version)),
+                    is_deleted.eq(excluded(is_deleted)),
+                    last_transaction_timestamp.eq(excluded(last_transaction_timestamp)),
+                    inserted_at.eq(excluded(inserted_at)),
+                )),
+            Some(" WHERE current_objects.last_transaction_version <= excluded.last_transaction_version "),
+        )?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl TransactionProcessor for ObjectTransactionProcessor {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    fn migrations(&self) -> Option<EmbeddedMigrations> {
+        Some(MIGRATIONS)
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let mut conn = self.get_conn();
+
+        let mut all_objects = vec![];
+        // Hashmap key will be the PK of the table, we do not want to send duplicates writes to the db within a batch
+        let mut all_current_objects: CurrentObjectMap = HashMap::new();
+
+        for txn in &transactions {
+            let (mut objects, current_objects) =
+                match Object::from_transaction(txn, &all_current_objects, &mut conn) {
+                    Ok(res) => res,
+                    Err(err) => {
+                        return Err(TransactionProcessingError::TransactionCommitError((
+                            err,
+                            start_version,
+                            end_version,
+                            self.name(),
+                        )))
+                    },
+                };
+            all_objects.append(&mut objects);
+            // Given versions will always be increasing here (within a single batch), we can just override current values
+            all_current_objects.extend(current_objects);
+        }
+
+        // Sort by PK in order to avoid postgres deadlock since we're doing multi threaded db writes
+        let mut all_current_objects = all_current_objects
+            .into_values()
+            .collect::<Vec<CurrentObject>>();
+        all_current_objects.sort_by(|a, b| a.object_address.cmp(&b.object_address));
+
+        let tx_result = insert_to_db(
+            &mut conn,
+            self.name(),
+            start_version,
+            end_version,
+            all_objects,
+            all_current_objects,
+        );
+        match tx_result {
+            Ok(_) => Ok(ProcessingResult::new(
+                self.name(),
+                start_version,
+                end_version,
+            )),
+            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                anyhow::Error::from(err),
+                start_version,
+                end_version,
+                self.name(),
+            ))),
+        }
+    }
+
+    /// Ownership is fully described by the current `ObjectCore` resources, so we can start from a
+    /// snapshot.
+    fn supports_snapshot_bootstrap(&self) -> bool {
+        true
+    }
+
+    async fn process_state_snapshot(
+        &self,
+        changes: Vec<WriteSetChange>,
+        version: u64,
+        timestamp: chrono::NaiveDateTime,
+        first_change_index: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let mut conn = self.get_conn();
+
+        let mut all_objects = vec![];
+        let mut all_current_objects = vec![];
+        for (index, wsc) in changes.iter().enumerate() {
+            if let WriteSetChange::WriteResource(write_resource) = wsc {
+                match Object::from_write_resource(
+                    write_resource,
+                    (first_change_index + index as u64) as i64,
+                    version as i64,
+                    timestamp,
+                ) {
+                    Ok(Some((object, current_object))) => {
+                        all_objects.push(object);
+                        all_current_objects.push(current_object);
+                    },
+                    Ok(None) => {},
+                    Err(err) => {
+                        return Err(TransactionProcessingError::TransactionCommitError((
+                            err,
+                            version,
+                            version,
+                            self.name(),
+                        )))
+                    },
+                }
+            }
+        }
+        // Sort by PK in order to avoid postgres deadlock since we're doing multi threaded db writes
+        all_current_objects.sort_by(|a, b| a.object_address.cmp(&b.object_address));
+
+        match insert_to_db(
+            &mut conn,
+            self.name(),
+            version,
+            version,
+            all_objects,
+            all_current_objects,
+        ) {
+            Ok(_) => Ok(ProcessingResult::new(self.name(), version, version)),
+            Err(err) => Err(TransactionProcessingError::TransactionCommitError((
+                anyhow::Error::from(err),
+                version,
+                version,
+                self.name(),
+            ))),
+        }
+    }
+
+    fn connection_pool(&self) -> &PgDbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        indexer::migrations::run_pending_migrations,
+        models::object_models::objects::{CurrentObjectQuery, MAX_OWNERSHIP_DEPTH},
+        test_harness::setup_database,
+        util::standardize_address,
+    };
+
+    /// A live object at `object_address`, owned by `owner_address`.
+    fn current_object(object_address: &str, owner_address: &str) -> CurrentObject {
+        CurrentObject {
+            object_address: standardize_address(object_address),
+            owner_address: standardize_address(owner_address),
+            state_key_hash: object_address.to_string(),
+            allow_ungated_transfer: true,
+            last_guid_creation_num: 0,
+            last_transaction_version: 1,
+            is_deleted: false,
+            last_transaction_timestamp: chrono::NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_owned_transitively() {
+        if crate::should_skip_pg_tests() {
+            return;
+        }
+        let pool = setup_database();
+        let mut conn = pool.get().unwrap();
+        run_pending_migrations(&mut conn, NAME, MIGRATIONS).unwrap();
+
+        let mut deleted = current_object("0x13", "0x12");
+        deleted.is_deleted = true;
+        insert_current_objects(&mut conn, &[
+            // 0xa -> 0x11 -> 0x12 -> 0x13 (deleted) -> 0x14
+            current_object("0x11", "0xa"),
+            current_object("0x12", "0x11"),
+            deleted,
+            current_object("0x14", "0x13"),
+            current_object("0x15", "0xa"),
+            // Owned by someone else
+            current_object("0x21", "0xb"),
+            // Inconsistent data owning each other in a cycle
+            current_object("0x31", "0x32"),
+            current_object("0x32", "0x31"),
+        ])
+        .unwrap();
+
+        let owned = CurrentObjectQuery::get_owned_transitively("0xa", &mut conn).unwrap();
+        let owned = owned
+            .iter()
+            .map(|object| (object.object_address.clone(), object.depth))
+            .collect::<Vec<_>>();
+        assert_eq!(owned, vec![
+            (standardize_address("0x11"), 1),
+            (standardize_address("0x15"), 1),
+            (standardize_address("0x12"), 2),
+        ]);
+
+        // The walk through the cycle stops at the maximum depth
+        let owned = CurrentObjectQuery::get_owned_transitively("0x31", &mut conn).unwrap();
+        assert_eq!(owned.len(), MAX_OWNERSHIP_DEPTH as usize);
+        assert_eq!(owned.last().unwrap().depth, MAX_OWNERSHIP_DEPTH);
+    }
+}
@@ -0,0 +1,501 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    database::PgDbPool,
+    indexer::{
+        errors::TransactionProcessingError, processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+    models::{events::EventModel, transactions::TransactionModel},
+};
+use anyhow::{anyhow, Context};
+use aptos_api_types::{Address, EventFilter, Transaction, TypePattern};
+use aptos_config::config::WebhookConfig;
+use async_trait::async_trait;
+use redis::{aio::ConnectionLike, RedisResult};
+use reqwest::header::CONTENT_TYPE;
+use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
+use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
+use ring::hmac;
+use serde::Serialize;
+use std::{fmt::Debug, time::Duration};
+use tokio::sync::Mutex;
+use url::Url;
+
+pub const NAME: &str = "webhook_processor";
+pub const SIGNATURE_HEADER: &str = "X-Aptos-Signature";
+const DEFAULT_MAX_RETRIES: u32 = 3;
+const REDIS_RETRY_BASE_DELAY: Duration = Duration::from_millis(100);
+
+/// Body of every webhook request or stream entry: all events in a batch that matched the webhook's filters.
+#[derive(Debug, Serialize)]
+pub struct EventNotification<'a> {
+    pub start_version: u64,
+    pub end_version: u64,
+    pub events: Vec<&'a EventModel>,
+}
+
+/// The filter of a webhook: its event types and accounts, along with its filter expression.
+pub fn webhook_filter(config: &WebhookConfig) -> anyhow::Result<EventFilter> {
+    let types = config
+        .event_types
+        .iter()
+        .map(|event_type| event_type.parse::<TypePattern>())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let accounts = config
+        .account_addresses
+        .iter()
+        .map(|address| address.parse::<Address>())
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let filter = match &config.filter {
+        Some(filter) => filter.parse()?,
+        None => EventFilter::new(),
+    };
+    Ok(EventFilter::new()
+        .types(types)
+        .accounts(accounts)
+        .and(filter))
+}
+
+/// The sink of a webhook, depending on the scheme of its url: `redis://` and `rediss://` urls
+/// append to a Redis stream, everything else is POSTed to.
+pub fn webhook_sink(config: WebhookConfig) -> anyhow::Result<Box<dyn EventSink>> {
+    let url = Url::parse(&config.url)
+        .with_context(|| format!("Invalid webhook url {}", config.redacted_url()))?;
+    Ok(match url.scheme() {
+        "redis" | "rediss" => Box::new(RedisStreamSink::new(config)?),
+        _ => Box::new(WebhookSink::new(config)?),
+    })
+}
+
+/// Whether `event` matches `filter`. Events with an unparsable account never match.
+pub fn event_matches(filter: &EventFilter, event: &EventModel) -> bool {
+    event
+        .account_address
+        .parse::<Address>()
+        .map_or(false, |account| {
+            filter.matches(&account, &event.type_, &event.data)
+        })
+}
+
+/// Somewhere to push matching events. Delivery is at least once: a failed batch is retried by the
+/// tailer, and batches may be delivered out of order, so receivers should deduplicate on
+/// (account_address, creation_number, sequence_number).
+#[async_trait]
+pub trait EventSink: Send + Sync + Debug {
+    fn filter(&self) -> &EventFilter;
+
+    async fn send(&self, notification: &EventNotification<'_>) -> anyhow::Result<()>;
+}
+
+fn signing_key(config: &WebhookConfig) -> Option<hmac::Key> {
+    config
+        .signing_secret
+        .as_ref()
+        .map(|secret| hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes()))
+}
+
+/// The hex encoded HMAC-SHA256 of `body`.
+fn sign(signing_key: &hmac::Key, body: &[u8]) -> String {
+    hex::encode(hmac::sign(signing_key, body).as_ref())
+}
+
+/// POSTs notifications as JSON, retrying transient failures with exponential backoff.
+pub struct WebhookSink {
+    url: String,
+    filter: EventFilter,
+    signing_key: Option<hmac::Key>,
+    client: ClientWithMiddleware,
+}
+
+impl WebhookSink {
+    pub fn new(config: WebhookConfig) -> anyhow::Result<Self> {
+        let filter = webhook_filter(&config)
+            .with_context(|| format!("Invalid filter for webhook {}", config.url))?;
+        let retry_policy = ExponentialBackoff::builder()
+            .build_with_max_retries(config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES));
+        let client = ClientBuilder::new(reqwest::Client::new())
+            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
+            .build();
+        Ok(Self {
+            signing_key: signing_key(&config),
+            url: config.url,
+            filter,
+            client,
+        })
+    }
+}
+
+impl Debug for WebhookSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookSink")
+            .field("url", &self.url)
+            .field("filter", &self.filter)
+            .field("signed", &self.signing_key.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl EventSink for WebhookSink {
+    fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+
+    async fn send(&self, notification: &EventNotification<'_>) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(notification)?;
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/json");
+        if let Some(signing_key) = &self.signing_key {
+            request = request.header(SIGNATURE_HEADER, sign(signing_key, &body));
+        }
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .context(format!("Failed to reach webhook {}", self.url))?;
+        if !response.status().is_success() {
+            return Err(anyhow!(
+                "Webhook {} responded with {}",
+                self.url,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Appends notifications to a Redis stream, for consumers reading it as a queue (ex: with
+/// `XREADGROUP`). Each entry holds the JSON body in its `notification` field and, when a secret is
+/// set, the signature of the body in its `signature` field. Failures are retried on a new
+/// connection with exponential backoff.
+pub struct RedisStreamSink {
+    url: String,
+    stream: String,
+    filter: EventFilter,
+    signing_key: Option<hmac::Key>,
+    max_retries: u32,
+    client: redis::Client,
+    connection: Mutex<Option<redis::aio::Connection>>,
+}
+
+impl RedisStreamSink {
+    pub fn new(config: WebhookConfig) -> anyhow::Result<Self> {
+        let url = config.redacted_url();
+        let stream = config
+            .stream
+            .clone()
+            .ok_or_else(|| anyhow!("Webhook {} has no stream to append to", url))?;
+        let filter = webhook_filter(&config)
+            .with_context(|| format!("Invalid filter for webhook {}", url))?;
+        let client = redis::Client::open(config.url.as_str())
+            .with_context(|| format!("Invalid redis url {}", url))?;
+        Ok(Self {
+            signing_key: signing_key(&config),
+            max_retries: config.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            url,
+            stream,
+            filter,
+            client,
+            connection: Mutex::new(None),
+        })
+    }
+
+    async fn try_send(&self, body: &[u8], signature: Option<&str>) -> RedisResult<String> {
+        let mut connection = self.connection.lock().await;
+        if connection.is_none() {
+            *connection = Some(self.client.get_async_connection().await?);
+        }
+        let result = append(connection.as_mut().unwrap(), &self.stream, body, signature).await;
+        if result.is_err() {
+            // The connection may be broken, so the next attempt opens a new one
+            *connection = None;
+        }
+        result
+    }
+}
+
+/// Appends a notification to `stream`, returning the id of the new entry.
+async fn append<C: ConnectionLike>(
+    connection: &mut C,
+    stream: &str,
+    body: &[u8],
+    signature: Option<&str>,
+) -> RedisResult<String> {
+    let mut cmd = redis::cmd("XADD");
+    cmd.arg(stream).arg("*").arg("notification").arg(body);
+    if let Some(signature) = signature {
+        cmd.arg("signature").arg(signature);
+    }
+    cmd.query_async(connection).await
+}
+
+impl Debug for RedisStreamSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisStreamSink")
+            .field("url", &self.url)
+            .field("stream", &self.stream)
+            .field("filter", &self.filter)
+            .field("signed", &self.signing_key.is_some())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl EventSink for RedisStreamSink {
+    fn filter(&self) -> &EventFilter {
+        &self.filter
+    }
+
+    async fn send(&self, notification: &EventNotification<'_>) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(notification)?;
+        let signature = self
+            .signing_key
+            .as_ref()
+            .map(|signing_key| sign(signing_key, &body));
+        let mut retries = 0;
+        loop {
+            match self.try_send(&body, signature.as_deref()).await {
+                Ok(_) => return Ok(()),
+                Err(err) if retries < self.max_retries => {
+                    aptos_logger::warn!(
+                        url = self.url,
+                        stream = self.stream,
+                        error = format!("{}", err),
+                        "Failed to append to the stream, retrying"
+                    );
+                    tokio::time::sleep(REDIS_RETRY_BASE_DELAY * 2u32.pow(retries.min(10))).await;
+                    retries += 1;
+                },
+                Err(err) => {
+                    return Err(anyhow::Error::new(err).context(format!(
+                        "Failed to append to stream {} of {}",
+                        self.stream, self.url
+                    )))
+                },
+            }
+        }
+    }
+}
+
+/// Pushes events matching each configured webhook's filters instead of writing them to the db,
+/// so applications can get notified without running a processor of their own. Only the
+/// processor status is stored, which is what lets the tailer resume after a restart.
+pub struct WebhookTransactionProcessor {
+    connection_pool: PgDbPool,
+    sinks: Vec<Box<dyn EventSink>>,
+}
+
+impl WebhookTransactionProcessor {
+    pub fn new(connection_pool: PgDbPool, webhooks: Vec<WebhookConfig>) -> anyhow::Result<Self> {
+        if webhooks.is_empty() {
+            return Err(anyhow!(
+                "'config.indexer.webhooks' must be set to run the {}",
+                NAME
+            ));
+        }
+        aptos_logger::info!(
+            webhooks = format!("{:?}", webhooks),
+            "init WebhookTransactionProcessor"
+        );
+        Ok(Self {
+            connection_pool,
+            sinks: webhooks
+                .into_iter()
+                .map(webhook_sink)
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+}
+
+impl Debug for WebhookTransactionProcessor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = &self.connection_pool.state();
+        write!(
+            f,
+            "WebhookTransactionProcessor {{ connections: {:?}  idle_connections: {:?} sinks: {:?} }}",
+            state.connections, state.idle_connections, self.sinks
+        )
+    }
+}
+
+#[async_trait]
+impl TransactionProcessor for WebhookTransactionProcessor {
+    fn name(&self) -> &'static str {
+        NAME
+    }
+
+    async fn process_transactions(
+        &self,
+        transactions: Vec<Transaction>,
+        start_version: u64,
+        end_version: u64,
+    ) -> Result<ProcessingResult, TransactionProcessingError> {
+        let events = transactions
+            .iter()
+            .filter(|txn| txn.success())
+            .flat_map(|txn| TransactionModel::from_transaction(txn).2)
+            .collect::<Vec<EventModel>>();
+
+        for sink in &self.sinks {
+            let matching = events
+                .iter()
+                .filter(|event| event_matches(sink.filter(), event))
+                .collect::<Vec<_>>();
+            if matching.is_empty() {
+                continue;
+            }
+            let notification = EventNotification {
+                start_version,
+                end_version,
+                events: matching,
+            };
+            if let Err(err) = sink.send(&notification).await {
+                return Err(TransactionProcessingError::TransactionCommitError((
+                    err,
+                    start_version,
+                    end_version,
+                    self.name(),
+                )));
+            }
+        }
+
+        Ok(ProcessingResult::new(
+            self.name(),
+            start_version,
+            end_version,
+        ))
+    }
+
+    fn connection_pool(&self) -> &PgDbPool {
+        &self.connection_pool
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use redis_test::{MockCmd, MockRedisConnection};
+
+    fn type_matches(typ: &str, event_type: &str) -> bool {
+        let filter = webhook_filter(&WebhookConfig {
+            event_types: vec![event_type.to_string()],
+            ..WebhookConfig::default()
+        })
+        .unwrap();
+        filter.matches(&"0x1".parse().unwrap(), typ, &serde_json::json!({}))
+    }
+
+    #[test]
+    fn test_type_matches() {
+        assert!(type_matches(
+            "0x1::coin::DepositEvent",
+            "0x1::coin::DepositEvent"
+        ));
+        assert!(type_matches("0x1::coin::DepositEvent", "0x1::coin"));
+        assert!(type_matches(
+            "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>",
+            "0x1::coin::CoinStore"
+        ));
+        assert!(!type_matches(
+            "0x1::coin::DepositEvent",
+            "0x1::coin::Deposit"
+        ));
+        assert!(!type_matches("0x1::coins::DepositEvent", "0x1::coin"));
+        assert!(!type_matches("0x1::coin::DepositEvent", "0x3::token"));
+    }
+
+    #[test]
+    fn test_webhook_filter() {
+        let filter = webhook_filter(&WebhookConfig {
+            event_types: vec!["0x1::coin".to_string()],
+            account_addresses: vec![
+                "0x0000000000000000000000000000000000000000000000000000000000000abc".to_string(),
+            ],
+            filter: Some("data.amount >= 100".to_string()),
+            ..WebhookConfig::default()
+        })
+        .unwrap();
+        let event = |account: &str, amount: &str| EventModel {
+            sequence_number: 0,
+            creation_number: 0,
+            account_address: account.to_string(),
+            transaction_version: 0,
+            transaction_block_height: 0,
+            type_: "0x1::coin::DepositEvent".to_string(),
+            data: serde_json::json!({ "amount": amount }),
+            event_index: None,
+        };
+        assert!(event_matches(&filter, &event("0xabc", "100")));
+        assert!(!event_matches(&filter, &event("0xabc", "99")));
+        assert!(!event_matches(&filter, &event("0xabd", "100")));
+
+        assert!(webhook_filter(&WebhookConfig {
+            filter: Some("data.amount 100".to_string()),
+            ..WebhookConfig::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_webhook_sink() {
+        let sink = |url: &str, stream: Option<&str>| {
+            webhook_sink(WebhookConfig {
+                url: url.to_string(),
+                stream: stream.map(str::to_string),
+                ..WebhookConfig::default()
+            })
+        };
+        assert!(
+            format!("{:?}", sink("https://example.com/events", None).unwrap())
+                .starts_with("WebhookSink")
+        );
+        assert!(format!(
+            "{:?}",
+            sink("redis://localhost:6379", Some("events")).unwrap()
+        )
+        .starts_with("RedisStreamSink"));
+        let error = sink("redis://:password@localhost:6379", None).unwrap_err();
+        assert!(!format!("{:#}", error).contains("password"), "{:#}", error);
+        assert!(sink("not a url", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_append() {
+        let mut connection = MockRedisConnection::new(vec![
+            MockCmd::new(
+                redis::cmd("XADD")
+                    .arg("events")
+                    .arg("*")
+                    .arg("notification")
+                    .arg(b"{}"),
+                Ok("1-0"),
+            ),
+            MockCmd::new(
+                redis::cmd("XADD")
+                    .arg("events")
+                    .arg("*")
+                    .arg("notification")
+                    .arg(b"{}")
+                    .arg("signature")
+                    .arg("abc"),
+                Ok("1-1"),
+            ),
+        ]);
+        assert_eq!(
+            append(&mut connection, "events", b"{}", None)
+                .await
+                .unwrap(),
+            "1-0"
+        );
+        assert_eq!(
+            append(&mut connection, "events", b"{}", Some("abc"))
+                .await
+                .unwrap(),
+            "1-1"
+        );
+    }
+}
@@ -7,9 +7,11 @@ use crate::{
         fetcher::TransactionFetcherOptions, processing_result::ProcessingResult, tailer::Tailer,
         transaction_processor::TransactionProcessor,
     },
+    models::coin_models::asset_metadata::AssetMetadataCache,
     processors::{
         coin_processor::CoinTransactionProcessor, default_processor::DefaultTransactionProcessor,
-        stake_processor::StakeTransactionProcessor, token_processor::TokenTransactionProcessor,
+        object_processor::ObjectTransactionProcessor, stake_processor::StakeTransactionProcessor,
+        token_processor::TokenTransactionProcessor, webhook_processor::WebhookTransactionProcessor,
         Processor,
     },
 };
@@ -102,6 +104,7 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
     let processor_name = config.processor.clone().unwrap();
     let check_chain_id = config.check_chain_id.unwrap();
     let skip_migrations = config.skip_migrations.unwrap();
+    let bootstrap_from_snapshot = config.bootstrap_from_snapshot.unwrap();
     let fetch_tasks = config.fetch_tasks.unwrap();
     let processor_tasks = config.processor_tasks.unwrap();
     let emit_every = config.emit_every.unwrap();
@@ -133,8 +136,16 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
             config.ans_contract_address,
             config.nft_points_contract,
         )),
-        Processor::CoinProcessor => Arc::new(CoinTransactionProcessor::new(conn_pool.clone())),
+        Processor::CoinProcessor => Arc::new(CoinTransactionProcessor::new(
+            conn_pool.clone(),
+            Arc::new(AssetMetadataCache::new()),
+        )),
         Processor::StakeProcessor => Arc::new(StakeTransactionProcessor::new(conn_pool.clone())),
+        Processor::ObjectProcessor => Arc::new(ObjectTransactionProcessor::new(conn_pool.clone())),
+        Processor::WebhookProcessor => Arc::new(
+            WebhookTransactionProcessor::new(conn_pool.clone(), config.webhooks)
+                .unwrap_or_else(|e| panic!("Failed to create the webhook processor: {:#}", e)),
+        ),
     };
 
     let options =
@@ -154,16 +165,30 @@ pub async fn run_forever(config: IndexerConfig, context: Arc<Context>) {
         "Fetching starting version from db..."
     );
     // For now this is not being used but we'd want to track it anyway
-    let starting_version_from_db_short = tailer
+    let starting_version_from_db_short = match tailer
         .get_start_version(&processor_name)
         .unwrap_or_else(|e| panic!("Failed to get starting version: {:?}", e))
-        .unwrap_or_else(|| {
+    {
+        Some(version) => version as u64,
+        None if bootstrap_from_snapshot && config.starting_version.is_none() => {
+            info!(
+                processor_name = processor_name,
+                "No starting version from db so bootstrapping from state snapshot"
+            );
+            tailer
+                .bootstrap_from_snapshot(batch_size as usize)
+                .await
+                .unwrap_or_else(|e| panic!("Failed to bootstrap from state snapshot: {:?}", e))
+                + 1
+        },
+        None => {
             info!(
                 processor_name = processor_name,
                 "No starting version from db so starting from version 0"
             );
             0
-        }) as u64;
+        },
+    };
     let start_version = match config.starting_version {
         None => starting_version_from_db_short,
         Some(version) => version,
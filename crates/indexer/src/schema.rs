@@ -34,6 +34,9 @@ diesel::table! {
         transaction_timestamp -> Timestamp,
         inserted_at -> Timestamp,
         event_index -> Nullable<Int8>,
+        coin_symbol -> Nullable<Varchar>,
+        coin_decimals -> Nullable<Int4>,
+        normalized_amount -> Nullable<Numeric>,
     }
 }
 
@@ -151,6 +154,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    current_objects (object_address) {
+        object_address -> Varchar,
+        owner_address -> Varchar,
+        state_key_hash -> Varchar,
+        allow_ungated_transfer -> Bool,
+        last_guid_creation_num -> Int8,
+        last_transaction_version -> Int8,
+        is_deleted -> Bool,
+        last_transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     current_staking_pool_voter (staking_pool_address) {
         staking_pool_address -> Varchar,
@@ -331,6 +348,21 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    objects (transaction_version, write_set_change_index) {
+        transaction_version -> Int8,
+        write_set_change_index -> Int8,
+        object_address -> Varchar,
+        owner_address -> Varchar,
+        state_key_hash -> Varchar,
+        guid_creation_num -> Int8,
+        allow_ungated_transfer -> Bool,
+        is_deleted -> Bool,
+        transaction_timestamp -> Timestamp,
+        inserted_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     processor_status (processor) {
         processor -> Varchar,
@@ -523,6 +555,9 @@ diesel::table! {
         entry_function_id_str -> Text,
         inserted_at -> Timestamp,
         epoch -> Int8,
+        gas_used -> Numeric,
+        gas_fee -> Numeric,
+        entry_function_contract_address -> Varchar,
     }
 }
 
@@ -550,6 +585,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     current_coin_balances,
     current_collection_datas,
     current_delegator_balances,
+    current_objects,
     current_staking_pool_voter,
     current_table_items,
     current_token_datas,
@@ -563,6 +599,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     move_modules,
     move_resources,
     nft_points,
+    objects,
     processor_status,
     processor_statuses,
     proposal_votes,
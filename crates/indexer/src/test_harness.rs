@@ -0,0 +1,363 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runs processors end to end without a node: synthetic transactions are executed with a
+//! `MoveHarness`, converted to API transactions like the fullnode does, and fed to the processor
+//! under test, which writes to the database at `INDEXER_DATABASE_URL`.
+
+use crate::{
+    database::{new_db_pool, PgDbPool},
+    indexer::{
+        migrations::{run_pending_migrations, MIGRATIONS, SHARED_MIGRATIONS_OWNER},
+        processing_result::ProcessingResult,
+        transaction_processor::TransactionProcessor,
+    },
+};
+use anyhow::{anyhow, Result};
+use aptos_api_types::{AsConverter, Transaction, TransactionOnChainData, WriteSetChange};
+use aptos_crypto::{
+    hash::{CryptoHash, EventAccumulatorHasher},
+    HashValue,
+};
+use aptos_language_e2e_tests::account::Account;
+use aptos_package_builder::PackageBuilder;
+use aptos_resource_viewer::find_tables;
+use aptos_storage_interface::DbReader;
+use aptos_types::{
+    access_path::Path,
+    proof::accumulator::InMemoryAccumulator,
+    state_store::{
+        state_key::{StateKey, StateKeyInner},
+        table::{TableHandle, TableInfo},
+    },
+    transaction::{SignedTransaction, TransactionInfo, TransactionOutput, TransactionStatus},
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use diesel::RunQueryDsl;
+use e2e_move_tests::{assert_success, MoveHarness};
+use move_core_types::language_storage::TypeTag;
+use move_resource_viewer::MoveValueAnnotator;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{collections::HashMap, sync::Arc};
+
+/// Connects to `INDEXER_DATABASE_URL`, wipes the database and applies the shared migrations.
+pub fn setup_database() -> PgDbPool {
+    let database_url = std::env::var("INDEXER_DATABASE_URL")
+        .expect("must set 'INDEXER_DATABASE_URL' to run tests!");
+    let pool = new_db_pool(&database_url).unwrap();
+    let mut conn = pool.get().unwrap();
+    for command in [
+        "DROP SCHEMA public CASCADE",
+        "CREATE SCHEMA public",
+        "GRANT ALL ON SCHEMA public TO postgres",
+        "GRANT ALL ON SCHEMA public TO public",
+    ] {
+        diesel::sql_query(command).execute(&mut conn).unwrap();
+    }
+    run_pending_migrations(&mut conn, SHARED_MIGRATIONS_OWNER, MIGRATIONS).unwrap();
+    pool
+}
+
+/// The kinds of transactions `ProcessorHarness::generate` picks from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SyntheticTransaction {
+    /// Publishes a module, from a new account.
+    Publish,
+    /// Transfers APT between two accounts, emitting withdraw and deposit events.
+    Transfer,
+    /// Creates a `0x3::token` collection, writing to the tables of the creator.
+    CreateCollection,
+    /// Creates a token in an existing collection, writing table items and emitting events.
+    CreateToken,
+}
+
+/// Runs synthetic transactions and keeps them as the API transactions a processor gets from the
+/// fullnode, until `process` feeds them to one.
+pub struct ProcessorHarness {
+    pub chain: MoveHarness,
+    rng: StdRng,
+    accounts: Vec<Account>,
+    collections: Vec<(Account, String)>,
+    /// The key and value types of the tables seen so far, which the internal indexer of a
+    /// fullnode would provide to decode table items.
+    table_infos: HashMap<TableHandle, TableInfo>,
+    next_version: u64,
+    pending: Vec<Transaction>,
+}
+
+impl ProcessorHarness {
+    /// Creates a harness at genesis, with random choices seeded by `seed`.
+    pub fn new(seed: u64) -> Self {
+        let mut harness = Self {
+            chain: MoveHarness::new(),
+            rng: StdRng::seed_from_u64(seed),
+            accounts: vec![],
+            collections: vec![],
+            table_infos: HashMap::new(),
+            // Genesis is version 0, but isn't processed
+            next_version: 1,
+            pending: vec![],
+        };
+        let genesis = harness
+            .chain
+            .executor
+            .get_state_view()
+            .inner()
+            .iter()
+            .map(|(key, bytes)| (key.clone(), bytes.clone()))
+            .collect();
+        harness.index_tables(genesis);
+        harness
+    }
+
+    /// The transactions not processed yet.
+    pub fn pending(&self) -> &[Transaction] {
+        &self.pending
+    }
+
+    /// Runs `txn`, and keeps it for the next `process` if it isn't discarded.
+    pub fn run(&mut self, txn: SignedTransaction) -> TransactionStatus {
+        // Each transaction is in its own block, a second after the previous one
+        self.chain.fast_forward(1);
+        let output = self.chain.run_raw(txn.clone());
+        if let TransactionStatus::Keep(_) = output.status() {
+            let changes = output
+                .write_set()
+                .iter()
+                .filter_map(|(key, op)| Some((key.clone(), op.bytes()?.to_vec())))
+                .collect();
+            self.index_tables(changes);
+            let txn = self.convert(txn, &output);
+            self.pending.push(txn);
+            self.next_version += 1;
+        }
+        output.status().clone()
+    }
+
+    /// Runs `count` transactions of random kinds, all of which must succeed.
+    pub fn generate(&mut self, count: usize) {
+        for _ in 0..count {
+            let kind = match self.rng.gen_range(0, 10) {
+                0 => SyntheticTransaction::Publish,
+                1..=5 => SyntheticTransaction::Transfer,
+                6..=7 => SyntheticTransaction::CreateCollection,
+                _ => SyntheticTransaction::CreateToken,
+            };
+            self.run_synthetic(kind);
+        }
+    }
+
+    /// Runs a transaction of the given kind, which must succeed.
+    pub fn run_synthetic(&mut self, kind: SyntheticTransaction) {
+        let status = match kind {
+            SyntheticTransaction::Publish => {
+                let account = self.chain.new_account_with_key_pair();
+                let mut builder = PackageBuilder::new("Synthetic");
+                builder.add_source(
+                    "counter.move",
+                    &format!(
+                        "module {}::counter {{
+                            struct Counter has key {{ value: u64 }}
+                            public entry fun init(account: &signer) {{
+                                move_to(account, Counter {{ value: 0 }})
+                            }}
+                        }}",
+                        account.address().to_hex_literal()
+                    ),
+                );
+                let dir = builder.write_to_temp().unwrap();
+                let txn = self
+                    .chain
+                    .create_publish_package(&account, dir.path(), None, |_| {});
+                self.run(txn)
+            },
+            SyntheticTransaction::Transfer => {
+                let from = self.account();
+                let to = *self.account().address();
+                let amount = self.rng.gen_range(1, 1_000_000u64);
+                let txn = self.chain.create_entry_function(
+                    &from,
+                    str::parse("0x1::aptos_account::transfer").unwrap(),
+                    vec![],
+                    vec![bcs::to_bytes(&to).unwrap(), bcs::to_bytes(&amount).unwrap()],
+                );
+                self.run(txn)
+            },
+            SyntheticTransaction::CreateCollection => {
+                let creator = self.account();
+                let name = format!("Collection {}", self.collections.len());
+                let txn = self.chain.create_entry_function(
+                    &creator,
+                    str::parse("0x3::token::create_collection_script").unwrap(),
+                    vec![],
+                    vec![
+                        bcs::to_bytes(&name).unwrap(),
+                        bcs::to_bytes("A synthetic collection").unwrap(),
+                        bcs::to_bytes("https://aptos.dev").unwrap(),
+                        bcs::to_bytes(&0u64).unwrap(),
+                        bcs::to_bytes(&vec![false; 3]).unwrap(),
+                    ],
+                );
+                self.collections.push((creator, name));
+                self.run(txn)
+            },
+            SyntheticTransaction::CreateToken => {
+                if self.collections.is_empty() {
+                    return self.run_synthetic(SyntheticTransaction::CreateCollection);
+                }
+                let index = self.rng.gen_range(0, self.collections.len());
+                let (creator, collection) = self.collections[index].clone();
+                let name = format!("Token {}", self.next_version);
+                let txn = self.chain.create_entry_function(
+                    &creator,
+                    str::parse("0x3::token::create_token_script").unwrap(),
+                    vec![],
+                    vec![
+                        bcs::to_bytes(&collection).unwrap(),
+                        bcs::to_bytes(&name).unwrap(),
+                        bcs::to_bytes("A synthetic token").unwrap(),
+                        bcs::to_bytes(&1u64).unwrap(),
+                        bcs::to_bytes(&1u64).unwrap(),
+                        bcs::to_bytes("https://aptos.dev").unwrap(),
+                        bcs::to_bytes(creator.address()).unwrap(),
+                        bcs::to_bytes(&100u64).unwrap(),
+                        bcs::to_bytes(&1u64).unwrap(),
+                        bcs::to_bytes(&vec![false; 5]).unwrap(),
+                        bcs::to_bytes(&Vec::<String>::new()).unwrap(),
+                        bcs::to_bytes(&Vec::<Vec<u8>>::new()).unwrap(),
+                        bcs::to_bytes(&Vec::<String>::new()).unwrap(),
+                    ],
+                );
+                self.run(txn)
+            },
+        };
+        assert_success!(status);
+    }
+
+    /// Feeds the pending transactions to `processor`.
+    pub async fn process(&mut self, processor: &dyn TransactionProcessor) -> ProcessingResult {
+        let txns = std::mem::take(&mut self.pending);
+        let start_version = self.next_version - txns.len() as u64;
+        processor
+            .process_transactions(txns, start_version, self.next_version - 1)
+            .await
+            .unwrap()
+    }
+
+    /// One of a few accounts, created on first use.
+    fn account(&mut self) -> Account {
+        if self.accounts.len() < 3 {
+            let account = self.chain.new_account_with_key_pair();
+            self.accounts.push(account);
+        }
+        let index = self.rng.gen_range(0, self.accounts.len());
+        self.accounts[index].clone()
+    }
+
+    /// Converts `txn` to the API transaction a fullnode serves, with the state after `output`.
+    fn convert(&mut self, txn: SignedTransaction, output: &TransactionOutput) -> Transaction {
+        let status = match output.status() {
+            TransactionStatus::Keep(status) => status.clone(),
+            _ => unreachable!("only kept transactions are converted"),
+        };
+        let event_hashes = output
+            .events()
+            .iter()
+            .map(CryptoHash::hash)
+            .collect::<Vec<_>>();
+        let transaction = aptos_types::transaction::Transaction::UserTransaction(txn);
+        let info = TransactionInfo::new(
+            transaction.hash(),
+            CryptoHash::hash(output.write_set()),
+            InMemoryAccumulator::<EventAccumulatorHasher>::from_leaves(&event_hashes).root_hash(),
+            None,
+            output.gas_used(),
+            status,
+        );
+        let data = TransactionOnChainData {
+            version: self.next_version,
+            transaction,
+            info,
+            events: output.events().to_vec(),
+            accumulator_root_hash: HashValue::zero(),
+            changes: output.write_set().clone(),
+        };
+
+        let timestamp = self.chain.executor.get_block_time();
+        let resolver = self.chain.executor.get_state_view().as_move_resolver();
+        let db = Arc::new(TableInfoReader(self.table_infos.clone()));
+        let mut txn = resolver
+            .as_converter(db)
+            .try_into_onchain_transaction(timestamp, data)
+            .unwrap();
+        // Set by the fetcher from the block metadata transactions
+        if let Transaction::UserTransaction(user_txn) = &mut txn {
+            user_txn.info.block_height = Some(self.next_version.into());
+            user_txn.info.epoch = Some(1.into());
+        }
+        txn
+    }
+
+    /// Finds the tables in `values`, like the internal indexer of a fullnode. Table items are only
+    /// decoded once the table holding them is found, which may be in a later value.
+    fn index_tables(&mut self, mut values: Vec<(StateKey, Vec<u8>)>) {
+        let resolver = self.chain.executor.get_state_view().as_move_resolver();
+        let annotator = MoveValueAnnotator::new(&resolver);
+        loop {
+            let count = values.len();
+            values.retain(|(key, bytes)| {
+                let type_tag = match key.inner() {
+                    StateKeyInner::AccessPath(access_path) => match access_path.get_path() {
+                        Path::Resource(struct_tag) => TypeTag::Struct(Box::new(struct_tag)),
+                        Path::Code(_) | Path::ResourceGroup(_) => return false,
+                    },
+                    StateKeyInner::TableItem { handle, .. } => match self.table_infos.get(handle) {
+                        Some(table_info) => table_info.value_type.clone(),
+                        None => return true,
+                    },
+                    StateKeyInner::Raw(_) => return false,
+                };
+                if let Ok(value) = annotator.view_value(&type_tag, bytes) {
+                    let mut found = vec![];
+                    find_tables(&value, &mut found);
+                    self.table_infos.extend(found.into_iter().map(|table| {
+                        (table.handle, TableInfo {
+                            key_type: table.key_type,
+                            value_type: table.value_type,
+                        })
+                    }));
+                }
+                false
+            });
+            if values.len() == count {
+                break;
+            }
+        }
+    }
+}
+
+/// Counts the changes of `txns` of the kind matched by `filter`.
+pub fn count_changes(txns: &[Transaction], filter: impl Fn(&WriteSetChange) -> bool) -> i64 {
+    txns.iter()
+        .map(|txn| {
+            txn.transaction_info()
+                .unwrap()
+                .changes
+                .iter()
+                .filter(|change| filter(change))
+                .count() as i64
+        })
+        .sum()
+}
+
+/// Serves the table infos found by the harness to the API converter.
+struct TableInfoReader(HashMap<TableHandle, TableInfo>);
+
+impl DbReader for TableInfoReader {
+    fn get_table_info(&self, handle: TableHandle) -> Result<TableInfo> {
+        self.0
+            .get(&handle)
+            .cloned()
+            .ok_or_else(|| anyhow!("no table info for {:?}", handle))
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Context;
+use aptos_protos::transaction::testing1::v1::{
+    write_set_change::Change as WriteSetChangeEnum, MoveModuleId, Transaction,
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, language_storage::ModuleId,
+    resolver::ModuleResolver,
+};
+use std::collections::HashMap;
+
+/// The bytecode of the modules published by the transactions of the stream, kept up to date as a
+/// processor observes them. It resolves modules for a `TypeAccessor`, see
+/// `TypeAccessor::from_module_resolver`, from data the processor already receives rather than
+/// from a fullnode.
+///
+/// Only modules published in the observed transactions are known, so a processor starting after
+/// genesis needs to insert the modules published before its starting version.
+#[derive(Clone, Debug, Default)]
+pub struct StreamModuleStore {
+    modules: HashMap<ModuleId, Vec<u8>>,
+    /// The version of the last observed transaction.
+    version: Option<u64>,
+}
+
+impl StreamModuleStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module published before the observed transactions.
+    pub fn insert(&mut self, module_id: ModuleId, bytecode: Vec<u8>) {
+        self.modules.insert(module_id, bytecode);
+    }
+
+    /// Records the modules written and deleted by `transactions`, which follow the transactions
+    /// observed so far. Transactions at or below the last observed version are skipped, so that
+    /// batches processed again after a retry don't undo later upgrades.
+    pub fn observe(&mut self, transactions: &[Transaction]) -> anyhow::Result<()> {
+        for transaction in transactions {
+            if self
+                .version
+                .map_or(false, |version| transaction.version <= version)
+            {
+                continue;
+            }
+            let changes = transaction
+                .info
+                .as_ref()
+                .filter(|info| info.success)
+                .map_or(&[][..], |info| &info.changes[..]);
+            for change in changes {
+                match &change.change {
+                    Some(WriteSetChangeEnum::WriteModule(write_module)) => {
+                        let data = write_module
+                            .data
+                            .as_ref()
+                            .context("Module write without bytecode")?;
+                        let abi = data.abi.as_ref().context("Module write without ABI")?;
+                        let module_id = module_id(&MoveModuleId {
+                            address: abi.address.clone(),
+                            name: abi.name.clone(),
+                        })?;
+                        self.modules.insert(module_id, data.bytecode.clone());
+                    },
+                    Some(WriteSetChangeEnum::DeleteModule(delete_module)) => {
+                        let module = delete_module
+                            .module
+                            .as_ref()
+                            .context("Module deletion without module id")?;
+                        self.modules.remove(&module_id(module)?);
+                    },
+                    _ => (),
+                }
+            }
+            self.version = Some(transaction.version);
+        }
+        Ok(())
+    }
+
+    /// The version of the last observed transaction.
+    pub fn version(&self) -> Option<u64> {
+        self.version
+    }
+
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+}
+
+impl ModuleResolver for StreamModuleStore {
+    type Error = anyhow::Error;
+
+    fn get_module(&self, id: &ModuleId) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.modules.get(id).cloned())
+    }
+}
+
+fn module_id(module: &MoveModuleId) -> anyhow::Result<ModuleId> {
+    Ok(ModuleId::new(
+        AccountAddress::from_hex_literal(&module.address)?,
+        Identifier::new(module.name.as_str())?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_protos::transaction::testing1::v1::{
+        DeleteModule, MoveModule, MoveModuleBytecode, TransactionInfo, WriteModule, WriteSetChange,
+    };
+
+    fn transaction(version: u64, success: bool, change: WriteSetChangeEnum) -> Transaction {
+        Transaction {
+            version,
+            info: Some(TransactionInfo {
+                success,
+                changes: vec![WriteSetChange {
+                    change: Some(change),
+                    ..WriteSetChange::default()
+                }],
+                ..TransactionInfo::default()
+            }),
+            ..Transaction::default()
+        }
+    }
+
+    fn write_module(bytecode: Vec<u8>) -> WriteSetChangeEnum {
+        WriteSetChangeEnum::WriteModule(WriteModule {
+            address: "0xcafe".to_string(),
+            data: Some(MoveModuleBytecode {
+                bytecode,
+                abi: Some(MoveModule {
+                    address: "0xcafe".to_string(),
+                    name: "m".to_string(),
+                    ..MoveModule::default()
+                }),
+            }),
+            ..WriteModule::default()
+        })
+    }
+
+    #[test]
+    fn test_observe_module_changes() {
+        let module_id = module_id(&MoveModuleId {
+            address: "0xcafe".to_string(),
+            name: "m".to_string(),
+        })
+        .unwrap();
+        let mut store = StreamModuleStore::new();
+
+        store
+            .observe(&[
+                transaction(1, true, write_module(vec![1])),
+                transaction(2, false, write_module(vec![2])),
+            ])
+            .unwrap();
+        assert_eq!(store.get_module(&module_id).unwrap(), Some(vec![1]));
+        assert_eq!(store.version(), Some(2));
+
+        // An upgrade replaces the bytecode, and replaying it later doesn't undo the deletion
+        let upgrade = transaction(3, true, write_module(vec![3]));
+        store.observe(&[upgrade.clone()]).unwrap();
+        assert_eq!(store.get_module(&module_id).unwrap(), Some(vec![3]));
+        let deletion = transaction(
+            4,
+            true,
+            WriteSetChangeEnum::DeleteModule(DeleteModule {
+                address: "0xcafe".to_string(),
+                module: Some(MoveModuleId {
+                    address: "0xcafe".to_string(),
+                    name: "m".to_string(),
+                }),
+                ..DeleteModule::default()
+            }),
+        );
+        store.observe(&[deletion, upgrade]).unwrap();
+        assert_eq!(store.get_module(&module_id).unwrap(), None);
+        assert!(store.is_empty());
+    }
+}
@@ -76,6 +76,9 @@ impl PrunerOpt {
                 prune_window: self.ledger_prune_window,
                 batch_size: self.ledger_pruning_batch_size,
                 user_pruning_window_offset: 0,
+                transaction_prune_window: None,
+                write_set_prune_window: None,
+                event_prune_window: None,
             },
         }
     }
@@ -296,6 +296,17 @@ impl Mempool {
         block
     }
 
+    /// Changes the capacity limits of the mempool, e.g. when the node config is reloaded
+    pub fn set_capacity(
+        &mut self,
+        capacity: usize,
+        capacity_bytes: usize,
+        capacity_per_user: usize,
+    ) {
+        self.transactions
+            .set_capacity(capacity, capacity_bytes, capacity_per_user);
+    }
+
     /// Periodic core mempool garbage collection.
     /// Removes all expired transactions and clears expired entries in metrics
     /// cache and sequence number cache.
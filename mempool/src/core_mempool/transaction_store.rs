@@ -115,6 +115,19 @@ impl TransactionStore {
         }
     }
 
+    /// Changes the capacity limits. Transactions already over a lowered limit are kept, but no
+    /// new ones are accepted until enough have been removed.
+    pub(crate) fn set_capacity(
+        &mut self,
+        capacity: usize,
+        capacity_bytes: usize,
+        capacity_per_user: usize,
+    ) {
+        self.capacity = capacity;
+        self.capacity_bytes = capacity_bytes;
+        self.capacity_per_user = capacity_per_user;
+    }
+
     #[inline]
     fn get_mempool_txn(
         &self,
@@ -158,6 +158,7 @@ pub enum LogEntry {
     JsonRpc,
     GetTransaction,
     GetBlock,
+    LoaderCache,
     QuorumStore,
     StateSyncCommit,
     BroadcastTransaction,
@@ -166,6 +166,28 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 ))
                 .await;
         },
+        MempoolClientRequest::GetLoaderCacheStats(callback) => {
+            let stats = smp.validator.read().loader_cache_stats();
+            if callback.send(stats).is_err() {
+                warn!(LogSchema::event_log(
+                    LogEntry::LoaderCache,
+                    LogEvent::CallbackFail
+                ));
+            }
+        },
+        MempoolClientRequest::InvalidateCachedModules(module_ids, callback) => {
+            let invalidated = smp.validator.read().invalidate_cached_modules(&module_ids);
+            info!(
+                "Invalidated the loader cache of the validation VM: {} (modules: {:?})",
+                invalidated, module_ids
+            );
+            if callback.send(invalidated).is_err() {
+                warn!(LogSchema::event_log(
+                    LogEntry::LoaderCache,
+                    LogEvent::CallbackFail
+                ));
+            }
+        },
     }
 }
 
@@ -11,7 +11,7 @@ use crate::{
     },
     QuorumStoreRequest,
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{ConfigReloader, NodeConfig};
 use aptos_event_notifications::ReconfigNotificationListener;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::Level;
@@ -87,9 +87,18 @@ pub fn bootstrap(
     quorum_store_requests: Receiver<QuorumStoreRequest>,
     mempool_listener: MempoolNotificationListener,
     mempool_reconfig_events: ReconfigNotificationListener,
+    config_reloader: &ConfigReloader,
 ) -> Runtime {
     let runtime = aptos_runtimes::spawn_named_runtime("shared-mem".into(), None);
     let mempool = Arc::new(Mutex::new(CoreMempool::new(config)));
+    let reloaded_mempool = mempool.clone();
+    config_reloader.subscribe(move |config| {
+        reloaded_mempool.lock().set_capacity(
+            config.mempool_capacity,
+            config.mempool_capacity_bytes,
+            config.mempool_capacity_per_user,
+        );
+    });
     let vm_validator = Arc::new(RwLock::new(VMValidator::new(Arc::clone(&db))));
     start_shared_mempool(
         runtime.handle(),
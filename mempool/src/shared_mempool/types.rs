@@ -28,6 +28,8 @@ use futures::{
     future::Future,
     task::{Context, Poll},
 };
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::move_vm::LoaderCacheStats;
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
@@ -226,6 +228,11 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Describe the loader cache of the VM used to validate transactions
+    GetLoaderCacheStats(oneshot::Sender<LoaderCacheStats>),
+    /// Flush the loader cache of the validation VM if it holds any of the given modules, or
+    /// unconditionally if none are given. Replies with whether it will be flushed.
+    InvalidateCachedModules(Vec<ModuleId>, oneshot::Sender<bool>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
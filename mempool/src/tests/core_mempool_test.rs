@@ -508,6 +508,25 @@ fn test_capacity() {
     assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_ok());
 }
 
+#[test]
+fn test_set_capacity() {
+    let mut config = NodeConfig::random();
+    config.mempool.capacity = 1;
+    let mut pool = CoreMempool::new(&config);
+
+    add_txn(&mut pool, TestTransaction::new(1, 0, 1)).unwrap();
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 1, 1)).is_err());
+
+    // Raising the capacity makes room right away
+    pool.set_capacity(
+        2,
+        config.mempool.capacity_bytes,
+        config.mempool.capacity_per_user,
+    );
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 1, 1)).is_ok());
+    assert!(add_txn(&mut pool, TestTransaction::new(1, 2, 1)).is_err());
+}
+
 #[test]
 fn test_capacity_bytes() {
     let capacity_bytes = 2_048;
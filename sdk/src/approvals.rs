@@ -0,0 +1,379 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Helpers to let another account take tokens, and to inspect and revoke what was granted. The
+//! asset standards have no allowance, so each uses its closest equivalent:
+//!
+//! * Tokens of `0x3::token`: an offer of `0x3::token_transfers` escrows an amount of a token
+//!   until the receiver claims it, or the sender cancels it.
+//! * Objects, e.g. the digital assets of `0x4::token`: whether their owner can transfer them
+//!   freely, which the creator of an `0x4::aptos_token` token controls with `freeze_transfer` and
+//!   `unfreeze_transfer`.
+//! * Fungible assets of `0x1::fungible_asset`: whether the owner of a store can transfer its
+//!   balance freely. Only the holder of the `TransferRef` of the asset can change it, and it
+//!   can't be handed out by a transaction, so it can only be inspected.
+//!
+//! Granting helpers return [`ApprovalWarning`]s along with the payload, for grants worth double
+//! checking before they are signed.
+
+use crate::{
+    move_types::{
+        identifier::Identifier,
+        language_storage::{ModuleId, TypeTag},
+    },
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode, U64},
+        error::{AptosErrorResponse, RestError},
+        Client as ApiClient, Resource,
+    },
+    transaction_builder::aptos_stdlib::aptos_token_stdlib,
+    types::{
+        account_address::AccountAddress,
+        transaction::{EntryFunction, TransactionPayload},
+    },
+};
+use anyhow::{anyhow, Context, Result};
+use serde::Serialize;
+use std::{fmt, str::FromStr};
+
+/// The type of the digital assets of `0x4::token`.
+const DIGITAL_ASSET_TYPE: &str = "0x4::token::Token";
+
+/// A token of `0x3::token`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct TokenId {
+    pub creator: AccountAddress,
+    pub collection: String,
+    pub name: String,
+    pub property_version: u64,
+}
+
+/// A reason to double check a grant before signing it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ApprovalWarning {
+    /// The offer is for the largest possible amount of the token.
+    UnlimitedAmount,
+    /// The offer is for all of the balance of the sender in the token, or more.
+    EntireBalance { balance: u64 },
+    /// The owner of the token can transfer it to anyone at any time, without the creator.
+    UngatedTransfer,
+}
+
+impl fmt::Display for ApprovalWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApprovalWarning::UnlimitedAmount => {
+                write!(f, "The offer is for an unlimited amount of the token")
+            },
+            ApprovalWarning::EntireBalance { balance } => write!(
+                f,
+                "The offer is for the entire balance of {} of the token",
+                balance
+            ),
+            ApprovalWarning::UngatedTransfer => write!(
+                f,
+                "The owner of the token will be able to transfer it freely"
+            ),
+        }
+    }
+}
+
+/// The payload granting an approval, along with the warnings about it.
+#[derive(Clone, Debug)]
+pub struct Approval {
+    pub payload: TransactionPayload,
+    pub warnings: Vec<ApprovalWarning>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ApprovalClient<'a> {
+    api_client: &'a ApiClient,
+}
+
+impl<'a> ApprovalClient<'a> {
+    pub fn new(api_client: &'a ApiClient) -> Self {
+        Self { api_client }
+    }
+
+    /// Offers `amount` of `token_id` from `sender` to `receiver`, who can claim it with
+    /// [`claim_token_offer`] until it's cancelled with [`cancel_token_offer`]. Warns if the offer
+    /// is for an unlimited amount or the entire balance of the sender.
+    pub async fn offer_token(
+        &self,
+        sender: AccountAddress,
+        receiver: AccountAddress,
+        token_id: &TokenId,
+        amount: u64,
+    ) -> Result<Approval> {
+        let balance = self.token_balance(sender, token_id).await?;
+        Ok(Approval {
+            payload: aptos_token_stdlib::token_transfers_offer_script(
+                receiver,
+                token_id.creator,
+                token_id.collection.as_bytes().to_vec(),
+                token_id.name.as_bytes().to_vec(),
+                token_id.property_version,
+                amount,
+            ),
+            warnings: offer_warnings(amount, balance),
+        })
+    }
+
+    /// The amount of `token_id` `sender` offered to `receiver` which wasn't claimed or cancelled
+    /// yet, if any.
+    pub async fn pending_token_offer(
+        &self,
+        sender: AccountAddress,
+        receiver: AccountAddress,
+        token_id: &TokenId,
+    ) -> Result<Option<u64>> {
+        let handle = match self
+            .table_handle(
+                sender,
+                "0x3::token_transfers::PendingClaims",
+                "pending_claims",
+            )
+            .await?
+        {
+            Some(handle) => handle,
+            None => return Ok(None),
+        };
+        self.token_amount(
+            handle,
+            "0x3::token_transfers::TokenOfferId",
+            TokenOfferIdKey {
+                to_addr: receiver,
+                token_id: TokenIdKey::from(token_id),
+            },
+        )
+        .await
+    }
+
+    /// The amount of `token_id` `owner` holds, offers excluded.
+    pub async fn token_balance(&self, owner: AccountAddress, token_id: &TokenId) -> Result<u64> {
+        let handle = match self
+            .table_handle(owner, "0x3::token::TokenStore", "tokens")
+            .await?
+        {
+            Some(handle) => handle,
+            None => return Ok(0),
+        };
+        Ok(self
+            .token_amount(handle, "0x3::token::TokenId", TokenIdKey::from(token_id))
+            .await?
+            .unwrap_or(0))
+    }
+
+    /// Whether the owner of `object` can transfer it freely, i.e. its transfer isn't frozen.
+    pub async fn object_allows_ungated_transfer(&self, object: AccountAddress) -> Result<bool> {
+        let object_core = resource(self.api_client, object, "0x1::object::ObjectCore")
+            .await?
+            .ok_or_else(|| anyhow!("{} is not an object", object))?;
+        allows_ungated_transfer(&object_core)
+    }
+
+    /// Whether the owner of the fungible store `store` can transfer its balance freely, i.e. the
+    /// store isn't frozen.
+    pub async fn fungible_store_allows_ungated_transfer(
+        &self,
+        store: AccountAddress,
+    ) -> Result<bool> {
+        let store_resource = resource(self.api_client, store, "0x1::fungible_asset::FungibleAsset")
+            .await?
+            .ok_or_else(|| anyhow!("{} is not a fungible store", store))?;
+        allows_ungated_transfer(&store_resource)
+    }
+
+    /// The handle of the table in the field `field` of the resource `resource_type` of
+    /// `address`, if it has the resource.
+    async fn table_handle(
+        &self,
+        address: AccountAddress,
+        resource_type: &str,
+        field: &str,
+    ) -> Result<Option<AccountAddress>> {
+        resource(self.api_client, address, resource_type)
+            .await?
+            .map(|resource| {
+                resource.data[field]["handle"]
+                    .as_str()
+                    .and_then(|handle| AccountAddress::from_hex_literal(handle).ok())
+                    .ok_or_else(|| anyhow!("Invalid table handle in {}", resource_type))
+            })
+            .transpose()
+    }
+
+    /// The amount of the `0x3::token::Token` at `key` in the table `handle`, if any.
+    async fn token_amount<K: Serialize>(
+        &self,
+        handle: AccountAddress,
+        key_type: &str,
+        key: K,
+    ) -> Result<Option<u64>> {
+        match self
+            .api_client
+            .get_table_item(handle, key_type, "0x3::token::Token", key)
+            .await
+        {
+            Ok(token) => {
+                let amount = &token.inner()["amount"];
+                amount
+                    .as_str()
+                    .and_then(|amount| amount.parse().ok())
+                    .map(Some)
+                    .ok_or_else(|| anyhow!("Invalid token amount {}", amount))
+            },
+            Err(err) if is_not_found(&err) => Ok(None),
+            Err(err) => Err(err).context("Failed to get token"),
+        }
+    }
+}
+
+/// Cancels the offer of `token_id` to `receiver`, returning the token to the sender.
+pub fn cancel_token_offer(receiver: AccountAddress, token_id: &TokenId) -> TransactionPayload {
+    aptos_token_stdlib::token_transfers_cancel_offer_script(
+        receiver,
+        token_id.creator,
+        token_id.collection.as_bytes().to_vec(),
+        token_id.name.as_bytes().to_vec(),
+        token_id.property_version,
+    )
+}
+
+/// Claims the offer of `token_id` from `sender`.
+pub fn claim_token_offer(sender: AccountAddress, token_id: &TokenId) -> TransactionPayload {
+    aptos_token_stdlib::token_transfers_claim_script(
+        sender,
+        token_id.creator,
+        token_id.collection.as_bytes().to_vec(),
+        token_id.name.as_bytes().to_vec(),
+        token_id.property_version,
+    )
+}
+
+/// Lets the owner of the digital asset `token` transfer it freely. Signed by the creator of the
+/// token, whose collection must allow it.
+pub fn unfreeze_token_transfer(token: AccountAddress) -> Approval {
+    Approval {
+        payload: aptos_token_transfer_call("unfreeze_transfer", token),
+        warnings: vec![ApprovalWarning::UngatedTransfer],
+    }
+}
+
+/// Prevents the owner of the digital asset `token` from transferring it. Signed by the creator
+/// of the token, whose collection must allow it.
+pub fn freeze_token_transfer(token: AccountAddress) -> TransactionPayload {
+    aptos_token_transfer_call("freeze_transfer", token)
+}
+
+fn aptos_token_transfer_call(function: &str, token: AccountAddress) -> TransactionPayload {
+    TransactionPayload::EntryFunction(EntryFunction::new(
+        ModuleId::new(
+            AccountAddress::from_hex_literal("0x4").unwrap(),
+            Identifier::new("aptos_token").unwrap(),
+        ),
+        Identifier::new(function).unwrap(),
+        vec![TypeTag::from_str(DIGITAL_ASSET_TYPE).unwrap()],
+        vec![bcs::to_bytes(&token).unwrap()],
+    ))
+}
+
+/// The warnings about offering `amount` of a token of which the sender holds `balance`.
+pub fn offer_warnings(amount: u64, balance: u64) -> Vec<ApprovalWarning> {
+    let mut warnings = vec![];
+    if amount == u64::MAX {
+        warnings.push(ApprovalWarning::UnlimitedAmount);
+    }
+    if balance > 0 && amount >= balance {
+        warnings.push(ApprovalWarning::EntireBalance { balance });
+    }
+    warnings
+}
+
+/// A `0x3::token::TokenId`, as a JSON table key.
+#[derive(Serialize)]
+struct TokenIdKey {
+    token_data_id: TokenDataIdKey,
+    property_version: U64,
+}
+
+#[derive(Serialize)]
+struct TokenDataIdKey {
+    creator: AccountAddress,
+    collection: String,
+    name: String,
+}
+
+/// A `0x3::token_transfers::TokenOfferId`, as a JSON table key.
+#[derive(Serialize)]
+struct TokenOfferIdKey {
+    to_addr: AccountAddress,
+    token_id: TokenIdKey,
+}
+
+impl From<&TokenId> for TokenIdKey {
+    fn from(token_id: &TokenId) -> Self {
+        Self {
+            token_data_id: TokenDataIdKey {
+                creator: token_id.creator,
+                collection: token_id.collection.clone(),
+                name: token_id.name.clone(),
+            },
+            property_version: U64(token_id.property_version),
+        }
+    }
+}
+
+/// The resource `resource_type` of `address`, if it has one.
+async fn resource(
+    api_client: &ApiClient,
+    address: AccountAddress,
+    resource_type: &str,
+) -> Result<Option<Resource>> {
+    match api_client
+        .get_account_resource(address, resource_type)
+        .await
+    {
+        Ok(resource) => Ok(resource.into_inner()),
+        Err(err) if is_not_found(&err) => Ok(None),
+        Err(err) => Err(err).with_context(|| format!("Failed to get {}", resource_type)),
+    }
+}
+
+fn is_not_found(err: &RestError) -> bool {
+    matches!(
+        err,
+        RestError::Api(AptosErrorResponse {
+            error: AptosError {
+                error_code: AptosErrorCode::ResourceNotFound | AptosErrorCode::TableItemNotFound,
+                ..
+            },
+            ..
+        })
+    )
+}
+
+fn allows_ungated_transfer(resource: &Resource) -> Result<bool> {
+    let value = &resource.data["allow_ungated_transfer"];
+    value
+        .as_bool()
+        .ok_or_else(|| anyhow!("Invalid allow_ungated_transfer {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offer_warnings() {
+        assert_eq!(offer_warnings(1, 10), vec![]);
+        assert_eq!(offer_warnings(1, 0), vec![]);
+        assert_eq!(offer_warnings(10, 10), vec![
+            ApprovalWarning::EntireBalance { balance: 10 }
+        ]);
+        assert_eq!(offer_warnings(u64::MAX, 10), vec![
+            ApprovalWarning::UnlimitedAmount,
+            ApprovalWarning::EntireBalance { balance: 10 }
+        ]);
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The addresses of accounts and objects derived on chain from another address, computed the
+//! same way as the Move functions creating them:
+//!
+//! * `create_resource_address` - `0x1::account::create_resource_account`
+//! * `create_object_address`, `create_named_object_address` - `0x1::object::create_named_object`
+//! * `create_guid_object_address` - `0x1::object::create_object_from_account` and
+//!   `0x1::object::create_object_from_object`, from the GUID creation number of the creator
+//! * `create_user_derived_object_address` - `0x1::object::create_user_derived_object`
+//! * `create_collection_address` - the named collections of `0x4::collection`
+//! * `create_token_address` - `0x4::token::create_named_token`
+
+use crate::types::account_address::AccountAddress;
+pub use aptos_types::account_address::{
+    create_collection_address, create_guid_object_address, create_object_address,
+    create_resource_address, create_token_address, create_user_derived_object_address,
+};
+
+/// The address of the object `0x1::object::create_named_object` creates for `creator` and
+/// `seed`.
+pub fn create_named_object_address(creator: AccountAddress, seed: &[u8]) -> AccountAddress {
+    create_object_address(creator, seed)
+}
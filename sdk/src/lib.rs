@@ -8,10 +8,16 @@
 //!
 //! This SDK provides all the necessary components for building on top of the Aptos Blockchain. Some of the important modules are:
 //!
+//! * `approvals` - Offering tokens to other accounts, and inspecting and revoking what was granted
 //! * `crypto` - Types used for signing and verifying
+//! * `derived_address` - The addresses of resource accounts and objects derived on chain
 //! * `move_types` - Includes types used when interacting with the Move VM
+//! * `network` - The chain ids and service URLs of the Aptos networks
 //! * `rest_client` - The Aptos API Client, used for sending requests to the Aptos Blockchain.
+//! * `script_composer` - Composes calls of several Move functions into a single atomic script
 //! * `transaction_builder` - Includes helpers for constructing transactions
+//! * `transaction_journal` - A crash-safe journal of the transactions a submitter signs
+//! * `transaction_lifecycle` - Notifications of the lifecycle of the transactions a service submits
 //! * `types` - Includes types for Aptos on-chain data structures
 //!
 //! ## Example
@@ -22,8 +28,12 @@
 
 pub use bcs;
 
+pub mod approvals;
+
 pub mod coin_client;
 
+pub mod derived_address;
+
 pub mod crypto {
     pub use aptos_crypto::*;
 }
@@ -32,10 +42,18 @@ pub mod move_types {
     pub use move_core_types::*;
 }
 
+pub mod network;
+
 pub mod rest_client {
     pub use aptos_rest_client::*;
 }
 
+pub mod script_composer;
+
 pub mod transaction_builder;
 
+pub mod transaction_journal;
+
+pub mod transaction_lifecycle;
+
 pub mod types;
@@ -0,0 +1,177 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The public Aptos networks, with their chain ids and the URLs of their services, so that
+//! applications don't each hard-code them.
+
+use crate::{
+    rest_client::{Client, FaucetClient, Url},
+    types::{account_address::AccountAddress, chain_id::ChainId},
+};
+use anyhow::{anyhow, Result};
+use std::{fmt, str::FromStr};
+
+/// The addresses the framework is published at on every network: `0x1` for the Move standard
+/// library and the Aptos framework, `0x3` for tokens, and `0x4` for token objects.
+pub const FRAMEWORK_ADDRESSES: [AccountAddress; 3] = [
+    AccountAddress::ONE,
+    framework_address(3),
+    framework_address(4),
+];
+
+/// An Aptos network, with the chain id and the URLs of its services.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    /// Devnet is reset regularly, with a new chain id each time.
+    Devnet,
+    /// A local testnet, as run by `aptos node run-local-testnet` with its default ports.
+    Local,
+    Custom(CustomNetwork),
+}
+
+/// A network other than the public ones, e.g. a private testnet.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CustomNetwork {
+    pub chain_id: ChainId,
+    pub rest_url: Url,
+    pub indexer_url: Option<Url>,
+    pub faucet_url: Option<Url>,
+}
+
+impl Network {
+    /// The chain id of the network. `None` for devnet, whose chain id changes whenever it's
+    /// reset, and can be read from the node instead.
+    pub fn chain_id(&self) -> Option<ChainId> {
+        match self {
+            Network::Mainnet => Some(ChainId::mainnet()),
+            Network::Testnet => Some(ChainId::testnet()),
+            Network::Devnet => None,
+            Network::Local => Some(ChainId::test()),
+            Network::Custom(network) => Some(network.chain_id),
+        }
+    }
+
+    /// The URL of the REST API of the network's fullnodes.
+    pub fn rest_url(&self) -> Url {
+        match self {
+            Network::Mainnet => parse_url("https://fullnode.mainnet.aptoslabs.com"),
+            Network::Testnet => parse_url("https://fullnode.testnet.aptoslabs.com"),
+            Network::Devnet => parse_url("https://fullnode.devnet.aptoslabs.com"),
+            Network::Local => parse_url("http://localhost:8080"),
+            Network::Custom(network) => network.rest_url.clone(),
+        }
+    }
+
+    /// The URL of the GraphQL API of the network's indexer, if it has one.
+    pub fn indexer_url(&self) -> Option<Url> {
+        match self {
+            Network::Mainnet => Some(parse_url(
+                "https://indexer.mainnet.aptoslabs.com/v1/graphql",
+            )),
+            Network::Testnet => Some(parse_url(
+                "https://indexer-testnet.staging.gcp.aptosdev.com/v1/graphql",
+            )),
+            Network::Devnet => Some(parse_url(
+                "https://indexer-devnet.staging.gcp.aptosdev.com/v1/graphql",
+            )),
+            Network::Local => None,
+            Network::Custom(network) => network.indexer_url.clone(),
+        }
+    }
+
+    /// The URL of the network's faucet. Mainnet has none.
+    pub fn faucet_url(&self) -> Option<Url> {
+        match self {
+            Network::Mainnet => None,
+            Network::Testnet => Some(parse_url("https://faucet.testnet.aptoslabs.com")),
+            Network::Devnet => Some(parse_url("https://faucet.devnet.aptoslabs.com")),
+            Network::Local => Some(parse_url("http://localhost:8081")),
+            Network::Custom(network) => network.faucet_url.clone(),
+        }
+    }
+
+    /// The addresses the framework is published at, see [`FRAMEWORK_ADDRESSES`].
+    pub fn framework_addresses(&self) -> &'static [AccountAddress] {
+        &FRAMEWORK_ADDRESSES
+    }
+
+    /// A client of the REST API of the network's fullnodes.
+    pub fn rest_client(&self) -> Client {
+        Client::new(self.rest_url())
+    }
+
+    /// A client of the network's faucet, if it has one.
+    pub fn faucet_client(&self) -> Option<FaucetClient> {
+        self.faucet_url()
+            .map(|faucet_url| FaucetClient::new(faucet_url, self.rest_url()))
+    }
+}
+
+/// Parses the names of the public networks and `local`. Custom networks can't be named.
+impl FromStr for Network {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "mainnet" => Ok(Network::Mainnet),
+            "testnet" => Ok(Network::Testnet),
+            "devnet" => Ok(Network::Devnet),
+            "local" => Ok(Network::Local),
+            other => Err(anyhow!(
+                "Invalid network {}, must be one of [mainnet, testnet, devnet, local]",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Network::Mainnet => f.write_str("mainnet"),
+            Network::Testnet => f.write_str("testnet"),
+            Network::Devnet => f.write_str("devnet"),
+            Network::Local => f.write_str("local"),
+            Network::Custom(network) => write!(f, "custom ({})", network.rest_url),
+        }
+    }
+}
+
+const fn framework_address(last_byte: u8) -> AccountAddress {
+    let mut address = [0u8; AccountAddress::LENGTH];
+    address[AccountAddress::LENGTH - 1] = last_byte;
+    AccountAddress::new(address)
+}
+
+fn parse_url(url: &str) -> Url {
+    Url::parse(url).expect("The URLs of the networks are valid")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_network_names_round_trip() {
+        for network in [
+            Network::Mainnet,
+            Network::Testnet,
+            Network::Devnet,
+            Network::Local,
+        ] {
+            assert_eq!(Network::from_str(&network.to_string()).unwrap(), network);
+        }
+        assert!(Network::from_str("custom").is_err());
+    }
+
+    #[test]
+    fn test_framework_addresses() {
+        let addresses: Vec<_> = FRAMEWORK_ADDRESSES
+            .iter()
+            .map(AccountAddress::to_hex_literal)
+            .collect();
+        assert_eq!(addresses, ["0x1", "0x3", "0x4"]);
+    }
+}
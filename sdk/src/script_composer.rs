@@ -0,0 +1,660 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Composes calls of several Move functions into a single script, so they run atomically in one
+//! transaction, e.g. withdrawing from a swap and depositing the output elsewhere. Values returned
+//! by a call can be passed to later calls.
+//!
+//! Scripts can only call public functions. The composer reads their signatures from the compiled
+//! modules declaring them, which can be fetched with
+//! [`Client::get_account_module_bcs`](crate::rest_client::Client::get_account_module_bcs).
+
+use anyhow::{anyhow, ensure, Result};
+use aptos_types::transaction::{Script, TransactionPayload};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        AbilitySet, AddressIdentifierIndex, Bytecode, CodeUnit, CompiledScript, FunctionHandle,
+        FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex, IdentifierIndex,
+        LocalIndex, ModuleHandle, ModuleHandleIndex, Signature, SignatureIndex, SignatureToken,
+        StructHandle, StructHandleIndex, TableIndex, Visibility,
+    },
+    file_format_common::VERSION_MIN,
+    CompiledModule,
+};
+use move_core_types::{
+    identifier::IdentStr, language_storage::TypeTag, transaction_argument::TransactionArgument,
+};
+
+/// A value passed to a call of a [`ScriptComposer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum CallArgument {
+    /// The signer of the transaction at this index, the sender being 0. It can be passed by
+    /// reference any number of times, but by value only once.
+    Signer(u16),
+    /// A value passed to the script as a transaction argument.
+    Value(TransactionArgument),
+    /// A value returned by an earlier call. It can be passed by reference any number of times,
+    /// but by value only once.
+    Result(CallResult),
+}
+
+impl From<TransactionArgument> for CallArgument {
+    fn from(value: TransactionArgument) -> Self {
+        CallArgument::Value(value)
+    }
+}
+
+impl From<CallResult> for CallArgument {
+    fn from(result: CallResult) -> Self {
+        CallArgument::Result(result)
+    }
+}
+
+/// A value returned by a call added to a [`ScriptComposer`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CallResult(usize);
+
+/// Builds a script payload calling public functions one after the other.
+///
+/// ```ignore
+/// let mut composer = ScriptComposer::new(1);
+/// let coin = composer.add_call(
+///     &coin_module,
+///     ident_str!("withdraw"),
+///     vec![aptos_coin_tag],
+///     vec![CallArgument::Signer(0), TransactionArgument::U64(100).into()],
+/// )?;
+/// composer.add_call(
+///     &coin_module,
+///     ident_str!("deposit"),
+///     vec![aptos_coin_tag],
+///     vec![TransactionArgument::Address(receiver).into(), coin[0].into()],
+/// )?;
+/// let payload = composer.build()?;
+/// ```
+#[derive(Clone)]
+pub struct ScriptComposer {
+    script: CompiledScript,
+    num_signers: u16,
+    /// The type arguments of the calls, which are passed as type arguments of the script.
+    ty_args: Vec<TypeTag>,
+    /// The values passed to the calls, which are passed as arguments of the script.
+    args: Vec<TransactionArgument>,
+    calls: Vec<ComposedCall>,
+    /// The type of each value returned by a call, and whether it was passed by value.
+    results: Vec<(SignatureToken, bool)>,
+    moved_signers: Vec<bool>,
+}
+
+#[derive(Clone)]
+struct ComposedCall {
+    operands: Vec<Operand>,
+    function: CallHandle,
+    results: Vec<usize>,
+}
+
+#[derive(Clone, Copy)]
+enum CallHandle {
+    Function(FunctionHandleIndex),
+    Generic(FunctionInstantiationIndex),
+}
+
+/// How an argument of a call is pushed, before the indices of locals are known.
+#[derive(Clone, Copy)]
+enum Operand {
+    Signer(u16, Access),
+    Arg(usize, Access),
+    Result(usize, Access),
+}
+
+#[derive(Clone, Copy)]
+enum Access {
+    Copy,
+    Move,
+    Borrow,
+    BorrowMut,
+}
+
+impl ScriptComposer {
+    /// A composer for transactions with `num_signers` signers: 1 for a single sender, more for
+    /// multi-agent transactions.
+    pub fn new(num_signers: u16) -> Self {
+        Self {
+            script: CompiledScript {
+                version: VERSION_MIN,
+                module_handles: vec![],
+                struct_handles: vec![],
+                function_handles: vec![],
+                function_instantiations: vec![],
+                signatures: vec![],
+                identifiers: vec![],
+                address_identifiers: vec![],
+                constant_pool: vec![],
+                metadata: vec![],
+                code: CodeUnit {
+                    locals: SignatureIndex(0),
+                    code: vec![],
+                },
+                type_parameters: vec![],
+                parameters: SignatureIndex(0),
+            },
+            num_signers,
+            ty_args: vec![],
+            args: vec![],
+            calls: vec![],
+            results: vec![],
+            moved_signers: vec![false; num_signers as usize],
+        }
+    }
+
+    /// Adds a call of the public function `function` of `module`, returning the values it
+    /// returns, which later calls can take as arguments. The composer is left unchanged if the
+    /// call is invalid.
+    pub fn add_call(
+        &mut self,
+        module: &CompiledModule,
+        function: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<CallArgument>,
+    ) -> Result<Vec<CallResult>> {
+        let checkpoint = self.clone();
+        let results = self.try_add_call(module, function, ty_args, args);
+        if results.is_err() {
+            *self = checkpoint;
+        }
+        results
+    }
+
+    fn try_add_call(
+        &mut self,
+        module: &CompiledModule,
+        function: &IdentStr,
+        ty_args: Vec<TypeTag>,
+        args: Vec<CallArgument>,
+    ) -> Result<Vec<CallResult>> {
+        let definition = module
+            .function_defs()
+            .iter()
+            .find(|definition| {
+                module.identifier_at(module.function_handle_at(definition.function).name)
+                    == function
+            })
+            .ok_or_else(|| anyhow!("Function {}::{} not found", module.self_id(), function))?;
+        ensure!(
+            definition.visibility == Visibility::Public,
+            "Function {}::{} isn't public, so scripts can't call it",
+            module.self_id(),
+            function
+        );
+        let handle = module.function_handle_at(definition.function);
+        ensure!(
+            handle.type_parameters.len() == ty_args.len(),
+            "Function {}::{} takes {} type arguments, but {} were given",
+            module.self_id(),
+            function,
+            handle.type_parameters.len(),
+            ty_args.len()
+        );
+        let params = self.import_signature(module, handle.parameters);
+        ensure!(
+            params.len() == args.len(),
+            "Function {}::{} takes {} arguments, but {} were given",
+            module.self_id(),
+            function,
+            params.len(),
+            args.len()
+        );
+        let returns = self.import_signature(module, handle.return_);
+        self.script.version = self.script.version.max(module.version);
+
+        // Each type argument becomes a type parameter of the script, constrained by the
+        // abilities every function taking it requires
+        let ty_params: Vec<_> = ty_args
+            .into_iter()
+            .zip(&handle.type_parameters)
+            .map(|(ty_arg, constraints)| self.import_ty_arg(ty_arg, *constraints))
+            .collect();
+
+        let operands = params
+            .iter()
+            .zip(args)
+            .enumerate()
+            .map(|(i, (param, arg))| {
+                self.operand(&substitute(param, &ty_params), arg)
+                    .map_err(|err| anyhow!("Argument {} of {}: {}", i, function, err))
+            })
+            .collect::<Result<_>>()?;
+
+        let module_handle = self.import_module(module, module.self_handle_idx());
+        let name = self.import_identifier(function);
+        let parameters = self.import_tokens(params);
+        let return_ = self.import_tokens(returns.clone());
+        let function_handle =
+            FunctionHandleIndex(intern(&mut self.script.function_handles, FunctionHandle {
+                module: module_handle,
+                name,
+                parameters,
+                return_,
+                type_parameters: handle.type_parameters.clone(),
+            }));
+        let function = if ty_params.is_empty() {
+            CallHandle::Function(function_handle)
+        } else {
+            let type_parameters = self.import_tokens(
+                ty_params
+                    .iter()
+                    .map(|index| SignatureToken::TypeParameter(*index))
+                    .collect(),
+            );
+            CallHandle::Generic(FunctionInstantiationIndex(intern(
+                &mut self.script.function_instantiations,
+                FunctionInstantiation {
+                    handle: function_handle,
+                    type_parameters,
+                },
+            )))
+        };
+
+        let results: Vec<_> = returns
+            .iter()
+            .map(|ty| {
+                self.results.push((substitute(ty, &ty_params), false));
+                self.results.len() - 1
+            })
+            .collect();
+        self.calls.push(ComposedCall {
+            operands,
+            function,
+            results: results.clone(),
+        });
+        Ok(results.into_iter().map(CallResult).collect())
+    }
+
+    /// Builds the script payload. Every returned value which wasn't passed by value to a later
+    /// call must have the `drop` ability.
+    pub fn build(mut self) -> Result<TransactionPayload> {
+        for (i, (ty, moved)) in self.results.iter().enumerate() {
+            ensure!(
+                *moved || self.has_drop(ty),
+                "Returned value {} isn't passed to any call by value, and can't be dropped",
+                i
+            );
+        }
+
+        let first_arg = self.num_signers as usize;
+        let first_result = first_arg + self.args.len();
+        ensure!(
+            first_result + self.results.len() <= LocalIndex::MAX as usize + 1,
+            "Too many arguments and returned values for a script"
+        );
+        let local = |index: usize| index as LocalIndex;
+
+        let mut code = vec![];
+        for call in &self.calls {
+            for operand in &call.operands {
+                let (index, access) = match *operand {
+                    Operand::Signer(index, access) => (local(index as usize), access),
+                    Operand::Arg(index, access) => (local(first_arg + index), access),
+                    Operand::Result(index, access) => (local(first_result + index), access),
+                };
+                code.push(match access {
+                    Access::Copy => Bytecode::CopyLoc(index),
+                    Access::Move => Bytecode::MoveLoc(index),
+                    Access::Borrow => Bytecode::ImmBorrowLoc(index),
+                    Access::BorrowMut => Bytecode::MutBorrowLoc(index),
+                });
+            }
+            code.push(match call.function {
+                CallHandle::Function(handle) => Bytecode::Call(handle),
+                CallHandle::Generic(instantiation) => Bytecode::CallGeneric(instantiation),
+            });
+            // Returned values are on the stack in order, so the last one is stored first
+            for result in call.results.iter().rev() {
+                code.push(Bytecode::StLoc(local(first_result + result)));
+            }
+        }
+        code.push(Bytecode::Ret);
+
+        let params = std::iter::repeat(SignatureToken::Signer)
+            .take(self.num_signers as usize)
+            .chain(self.args.iter().map(arg_type))
+            .collect();
+        self.script.parameters = self.import_tokens(params);
+        let locals = self.results.iter().map(|(ty, _)| ty.clone()).collect();
+        self.script.code = CodeUnit {
+            locals: self.import_tokens(locals),
+            code,
+        };
+
+        let mut bytes = vec![];
+        self.script
+            .serialize_for_version(Some(self.script.version), &mut bytes)?;
+        Ok(TransactionPayload::Script(Script::new(
+            bytes,
+            self.ty_args,
+            self.args,
+        )))
+    }
+
+    /// How to pass `arg` for a parameter of type `param`.
+    fn operand(&mut self, param: &SignatureToken, arg: CallArgument) -> Result<Operand> {
+        let (target, by_value_access) = match param {
+            SignatureToken::Reference(inner) => (&**inner, Access::Borrow),
+            SignatureToken::MutableReference(inner) => (&**inner, Access::BorrowMut),
+            _ => (param, Access::Move),
+        };
+        Ok(match arg {
+            CallArgument::Signer(index) => {
+                ensure!(
+                    index < self.num_signers,
+                    "There are only {} signers",
+                    self.num_signers
+                );
+                ensure!(
+                    *target == SignatureToken::Signer,
+                    "Expected a value of type {:?}, got a signer",
+                    param
+                );
+                let moved = &mut self.moved_signers[index as usize];
+                ensure!(!*moved, "Signer {} was already passed by value", index);
+                *moved = matches!(by_value_access, Access::Move);
+                Operand::Signer(index, by_value_access)
+            },
+            CallArgument::Value(value) => {
+                ensure!(
+                    *target == arg_type(&value),
+                    "Expected a value of type {:?}, got {:?}",
+                    param,
+                    value
+                );
+                self.args.push(value);
+                let access = match by_value_access {
+                    // Transaction arguments are copyable, so they're copied rather than moved
+                    Access::Move => Access::Copy,
+                    access => access,
+                };
+                Operand::Arg(self.args.len() - 1, access)
+            },
+            CallArgument::Result(CallResult(index)) => {
+                let (ty, moved) = self
+                    .results
+                    .get_mut(index)
+                    .ok_or_else(|| anyhow!("Unknown returned value {}", index))?;
+                ensure!(
+                    target == ty,
+                    "Expected a value of type {:?}, got a returned value of type {:?}",
+                    param,
+                    ty
+                );
+                ensure!(
+                    !*moved,
+                    "Returned value {} was already passed by value",
+                    index
+                );
+                *moved = matches!(by_value_access, Access::Move);
+                Operand::Result(index, by_value_access)
+            },
+        })
+    }
+
+    fn has_drop(&self, ty: &SignatureToken) -> bool {
+        match ty {
+            SignatureToken::Vector(inner) => self.has_drop(inner),
+            SignatureToken::Struct(handle) => self.struct_abilities(*handle).has_drop(),
+            SignatureToken::StructInstantiation(handle, ty_args) => {
+                let handle = &self.script.struct_handles[handle.0 as usize];
+                handle.abilities.has_drop()
+                    && handle
+                        .type_parameters
+                        .iter()
+                        .zip(ty_args)
+                        .all(|(param, ty_arg)| param.is_phantom || self.has_drop(ty_arg))
+            },
+            SignatureToken::TypeParameter(index) => {
+                self.script.type_parameters[*index as usize].has_drop()
+            },
+            _ => true,
+        }
+    }
+
+    fn struct_abilities(&self, handle: StructHandleIndex) -> AbilitySet {
+        self.script.struct_handles[handle.0 as usize].abilities
+    }
+
+    fn import_ty_arg(&mut self, ty_arg: TypeTag, constraints: AbilitySet) -> u16 {
+        let index = match self.ty_args.iter().position(|known| *known == ty_arg) {
+            Some(index) => index,
+            None => {
+                self.ty_args.push(ty_arg);
+                self.script.type_parameters.push(AbilitySet::EMPTY);
+                self.ty_args.len() - 1
+            },
+        };
+        let abilities = &mut self.script.type_parameters[index];
+        *abilities = abilities.union(constraints);
+        index as u16
+    }
+
+    /// The tokens of `signature` in `module`, with the structs they refer to imported.
+    fn import_signature(
+        &mut self,
+        module: &CompiledModule,
+        signature: SignatureIndex,
+    ) -> Vec<SignatureToken> {
+        module
+            .signature_at(signature)
+            .0
+            .iter()
+            .map(|token| self.import_token(module, token))
+            .collect()
+    }
+
+    fn import_token(&mut self, module: &CompiledModule, token: &SignatureToken) -> SignatureToken {
+        match token {
+            SignatureToken::Vector(inner) => {
+                SignatureToken::Vector(Box::new(self.import_token(module, inner)))
+            },
+            SignatureToken::Reference(inner) => {
+                SignatureToken::Reference(Box::new(self.import_token(module, inner)))
+            },
+            SignatureToken::MutableReference(inner) => {
+                SignatureToken::MutableReference(Box::new(self.import_token(module, inner)))
+            },
+            SignatureToken::Struct(handle) => {
+                SignatureToken::Struct(self.import_struct(module, *handle))
+            },
+            SignatureToken::StructInstantiation(handle, ty_args) => {
+                SignatureToken::StructInstantiation(
+                    self.import_struct(module, *handle),
+                    ty_args
+                        .iter()
+                        .map(|ty_arg| self.import_token(module, ty_arg))
+                        .collect(),
+                )
+            },
+            _ => token.clone(),
+        }
+    }
+
+    fn import_struct(
+        &mut self,
+        module: &CompiledModule,
+        handle: StructHandleIndex,
+    ) -> StructHandleIndex {
+        let handle = module.struct_handle_at(handle);
+        let struct_handle = StructHandle {
+            module: self.import_module(module, handle.module),
+            name: self.import_identifier(module.identifier_at(handle.name)),
+            abilities: handle.abilities,
+            type_parameters: handle.type_parameters.clone(),
+        };
+        StructHandleIndex(intern(&mut self.script.struct_handles, struct_handle))
+    }
+
+    fn import_module(
+        &mut self,
+        module: &CompiledModule,
+        handle: ModuleHandleIndex,
+    ) -> ModuleHandleIndex {
+        let handle = module.module_handle_at(handle);
+        let address = AddressIdentifierIndex(intern(
+            &mut self.script.address_identifiers,
+            *module.address_identifier_at(handle.address),
+        ));
+        let name = self.import_identifier(module.identifier_at(handle.name));
+        ModuleHandleIndex(intern(&mut self.script.module_handles, ModuleHandle {
+            address,
+            name,
+        }))
+    }
+
+    fn import_identifier(&mut self, identifier: &IdentStr) -> IdentifierIndex {
+        IdentifierIndex(intern(&mut self.script.identifiers, identifier.to_owned()))
+    }
+
+    fn import_tokens(&mut self, tokens: Vec<SignatureToken>) -> SignatureIndex {
+        SignatureIndex(intern(&mut self.script.signatures, Signature(tokens)))
+    }
+}
+
+/// The index of `item` in `pool`, which is added if missing, as tables of scripts can't hold
+/// duplicates.
+fn intern<T: PartialEq>(pool: &mut Vec<T>, item: T) -> TableIndex {
+    match pool.iter().position(|known| *known == item) {
+        Some(index) => index as TableIndex,
+        None => {
+            pool.push(item);
+            (pool.len() - 1) as TableIndex
+        },
+    }
+}
+
+/// Replaces the type parameters of a function in `token` by the script type parameters they are
+/// instantiated with.
+fn substitute(token: &SignatureToken, ty_params: &[u16]) -> SignatureToken {
+    match token {
+        SignatureToken::TypeParameter(index) => {
+            SignatureToken::TypeParameter(ty_params[*index as usize])
+        },
+        SignatureToken::Vector(inner) => {
+            SignatureToken::Vector(Box::new(substitute(inner, ty_params)))
+        },
+        SignatureToken::Reference(inner) => {
+            SignatureToken::Reference(Box::new(substitute(inner, ty_params)))
+        },
+        SignatureToken::MutableReference(inner) => {
+            SignatureToken::MutableReference(Box::new(substitute(inner, ty_params)))
+        },
+        SignatureToken::StructInstantiation(handle, ty_args) => {
+            SignatureToken::StructInstantiation(
+                *handle,
+                ty_args
+                    .iter()
+                    .map(|ty_arg| substitute(ty_arg, ty_params))
+                    .collect(),
+            )
+        },
+        _ => token.clone(),
+    }
+}
+
+fn arg_type(arg: &TransactionArgument) -> SignatureToken {
+    match arg {
+        TransactionArgument::U8(_) => SignatureToken::U8,
+        TransactionArgument::U16(_) => SignatureToken::U16,
+        TransactionArgument::U32(_) => SignatureToken::U32,
+        TransactionArgument::U64(_) => SignatureToken::U64,
+        TransactionArgument::U128(_) => SignatureToken::U128,
+        TransactionArgument::U256(_) => SignatureToken::U256,
+        TransactionArgument::Address(_) => SignatureToken::Address,
+        TransactionArgument::U8Vector(_) => SignatureToken::Vector(Box::new(SignatureToken::U8)),
+        TransactionArgument::Bool(_) => SignatureToken::Bool,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use move_core_types::{
+        account_address::AccountAddress, ident_str, language_storage::StructTag,
+    };
+
+    fn framework_module(name: &str) -> CompiledModule {
+        aptos_cached_packages::head_release_bundle()
+            .compiled_modules()
+            .into_iter()
+            .find(|module| module.self_id().name().as_str() == name)
+            .unwrap()
+    }
+
+    fn aptos_coin() -> TypeTag {
+        TypeTag::Struct(Box::new(StructTag {
+            address: AccountAddress::ONE,
+            module: ident_str!("aptos_coin").to_owned(),
+            name: ident_str!("AptosCoin").to_owned(),
+            type_params: vec![],
+        }))
+    }
+
+    #[test]
+    fn test_withdraw_and_deposit() {
+        let coin = framework_module("coin");
+        let mut composer = ScriptComposer::new(1);
+        let withdrawn = composer
+            .add_call(&coin, ident_str!("withdraw"), vec![aptos_coin()], vec![
+                CallArgument::Signer(0),
+                TransactionArgument::U64(100).into(),
+            ])
+            .unwrap();
+        assert_eq!(withdrawn.len(), 1);
+        composer
+            .add_call(&coin, ident_str!("deposit"), vec![aptos_coin()], vec![
+                TransactionArgument::Address(AccountAddress::TWO).into(),
+                withdrawn[0].into(),
+            ])
+            .unwrap();
+
+        let script = match composer.build().unwrap() {
+            TransactionPayload::Script(script) => script,
+            payload => panic!("Expected a script payload, got {:?}", payload),
+        };
+        assert_eq!(script.ty_args(), &[aptos_coin()]);
+        assert_eq!(script.args(), &[
+            TransactionArgument::U64(100),
+            TransactionArgument::Address(AccountAddress::TWO),
+        ]);
+        let compiled = CompiledScript::deserialize(script.code()).unwrap();
+        move_bytecode_verifier::verify_script(&compiled).unwrap();
+        move_bytecode_verifier::dependencies::verify_script(&compiled, [&coin]).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_calls() {
+        let coin = framework_module("coin");
+        let mut composer = ScriptComposer::new(1);
+        // Wrong argument type
+        assert!(composer
+            .add_call(&coin, ident_str!("withdraw"), vec![aptos_coin()], vec![
+                CallArgument::Signer(0),
+                TransactionArgument::U8(1).into(),
+            ])
+            .is_err());
+        // Not public
+        assert!(composer
+            .add_call(&coin, ident_str!("initialize_supply_config"), vec![], vec![
+                CallArgument::Signer(0)
+            ])
+            .is_err());
+
+        // A coin can't be dropped, so it must be passed on
+        let withdrawn = composer
+            .add_call(&coin, ident_str!("withdraw"), vec![aptos_coin()], vec![
+                CallArgument::Signer(0),
+                TransactionArgument::U64(100).into(),
+            ])
+            .unwrap();
+        assert_eq!(withdrawn.len(), 1);
+        assert!(composer.build().is_err());
+    }
+}
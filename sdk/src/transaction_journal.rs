@@ -0,0 +1,547 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A write-ahead journal of the transactions a submitter signs and submits, so that after a
+//! crash it knows exactly which of them may still commit, and never signs a second transaction
+//! for a payment whose first transaction could still go through.
+//!
+//! A transaction is journaled before it is submitted, and each change of its status is journaled
+//! as it is observed. On restart, [`TransactionJournal::recover`] resolves every transaction the
+//! journal doesn't know the outcome of: committed transactions are recorded as such,
+//! transactions which can no longer commit are recorded as expired, and the others are submitted
+//! again. As a transaction is identified by its hash, submitting it again can't make it commit
+//! twice.
+//!
+//! Records are persisted by a [`JournalStore`]: [`FileJournalStore`] appends them to a file,
+//! other backends, e.g. a database the submitter already uses, implement the trait.
+//!
+//! Given a [`TxnLifecycleNotifier`], the journal notifies each step of the lifecycle of its
+//! transactions as it observes it.
+
+use crate::{
+    crypto::HashValue,
+    rest_client::{
+        aptos_api_types::{AptosError, AptosErrorCode},
+        error::{AptosErrorResponse, RestError},
+        Client,
+    },
+    transaction_lifecycle::{TxnLifecycleEvent, TxnLifecycleNotifier, TxnLifecycleUpdate},
+    types::{account_address::AccountAddress, transaction::SignedTransaction},
+};
+use anyhow::{bail, format_err, Result};
+use aptos_infallible::Mutex;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Arc,
+};
+
+/// What the journal knows about a transaction.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum JournalStatus {
+    /// Signed, and maybe submitted: the submitter may have crashed before recording it.
+    Signed,
+    /// Accepted by a fullnode, not known to be committed yet.
+    Submitted,
+    /// Committed on chain, successfully or not. The sequence number is used either way.
+    Committed {
+        version: u64,
+        success: bool,
+        vm_status: String,
+    },
+    /// Not committed, and expired as of the ledger timestamp, so it will never commit.
+    Expired,
+}
+
+impl JournalStatus {
+    /// Whether the outcome of the transaction is final.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JournalStatus::Committed { .. } | JournalStatus::Expired
+        )
+    }
+
+    /// The lifecycle event of a transaction reaching this status.
+    fn lifecycle_event(&self) -> TxnLifecycleEvent {
+        match self {
+            JournalStatus::Signed => TxnLifecycleEvent::Built,
+            JournalStatus::Submitted => TxnLifecycleEvent::MempoolAccepted,
+            JournalStatus::Committed {
+                version,
+                success: true,
+                ..
+            } => TxnLifecycleEvent::Committed { version: *version },
+            JournalStatus::Committed {
+                version,
+                success: false,
+                vm_status,
+            } => TxnLifecycleEvent::Failed {
+                version: *version,
+                vm_status: vm_status.clone(),
+            },
+            JournalStatus::Expired => TxnLifecycleEvent::Expired,
+        }
+    }
+}
+
+/// A journaled transaction along with its last known status.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct JournaledTransaction {
+    pub hash: HashValue,
+    pub transaction: SignedTransaction,
+    pub status: JournalStatus,
+}
+
+impl JournaledTransaction {
+    pub fn sender(&self) -> AccountAddress {
+        self.transaction.sender()
+    }
+
+    pub fn sequence_number(&self) -> u64 {
+        self.transaction.sequence_number()
+    }
+
+    fn lifecycle_update(&self, event: TxnLifecycleEvent) -> TxnLifecycleUpdate {
+        TxnLifecycleUpdate {
+            hash: self.hash,
+            sender: self.sender(),
+            sequence_number: self.sequence_number(),
+            event,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+enum JournalRecord {
+    Signed(SignedTransaction),
+    StatusChanged(HashValue, JournalStatus),
+}
+
+/// Where the records of a [`TransactionJournal`] are persisted, as opaque bytes.
+pub trait JournalStore: Send + Sync {
+    /// Appends a record. Must only return once the record would survive a crash.
+    fn append(&self, record: &[u8]) -> Result<()>;
+
+    /// All the records appended, in the order they were appended.
+    fn read_all(&self) -> Result<Vec<Vec<u8>>>;
+}
+
+/// Keeps the records in memory, e.g. for tests.
+#[derive(Default)]
+pub struct InMemoryJournalStore {
+    records: Mutex<Vec<Vec<u8>>>,
+}
+
+impl JournalStore for InMemoryJournalStore {
+    fn append(&self, record: &[u8]) -> Result<()> {
+        self.records.lock().push(record.to_vec());
+        Ok(())
+    }
+
+    fn read_all(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(self.records.lock().clone())
+    }
+}
+
+/// Appends the records to a file, each prefixed with its length, syncing the file after each
+/// one. A record only partly written when the process crashed is dropped when the file is
+/// opened again.
+pub struct FileJournalStore {
+    file: Mutex<File>,
+}
+
+impl FileJournalStore {
+    /// Opens the journal at `path`, creating it if it doesn't exist.
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .open(path)
+            .map_err(|err| format_err!("Failed to open journal {}: {}", path.display(), err))?;
+        let (_, complete_len) = Self::read_records(&mut file)?;
+        file.set_len(complete_len)?;
+        file.sync_all()?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The complete records of the file, along with their length in bytes.
+    fn read_records(file: &mut File) -> Result<(Vec<Vec<u8>>, u64)> {
+        let mut bytes = vec![];
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_end(&mut bytes)?;
+
+        let mut records = vec![];
+        let mut offset = 0;
+        while let Some(len_bytes) = bytes.get(offset..offset + 4) {
+            let len = u32::from_le_bytes(len_bytes.try_into().expect("4 bytes")) as usize;
+            match bytes.get(offset + 4..offset + 4 + len) {
+                Some(record) => records.push(record.to_vec()),
+                None => break,
+            }
+            offset += 4 + len;
+        }
+        Ok((records, offset as u64))
+    }
+}
+
+impl JournalStore for FileJournalStore {
+    fn append(&self, record: &[u8]) -> Result<()> {
+        let len = u32::try_from(record.len())
+            .map_err(|_| format_err!("Journal record of {} bytes is too large", record.len()))?;
+        let mut file = self.file.lock();
+        file.seek(SeekFrom::End(0))?;
+        file.write_all(&len.to_le_bytes())?;
+        file.write_all(record)?;
+        file.sync_data()?;
+        Ok(())
+    }
+
+    fn read_all(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(Self::read_records(&mut self.file.lock())?.0)
+    }
+}
+
+/// A journal of the transactions a submitter signs, see the [module documentation](self).
+pub struct TransactionJournal {
+    store: Box<dyn JournalStore>,
+    transactions: Mutex<HashMap<HashValue, JournaledTransaction>>,
+    notifier: Option<Arc<TxnLifecycleNotifier>>,
+}
+
+impl TransactionJournal {
+    /// Opens the journal persisted in `store`, replaying its records.
+    pub fn open(store: impl JournalStore + 'static) -> Result<Self> {
+        let mut transactions = HashMap::new();
+        for record in store.read_all()? {
+            match bcs::from_bytes(&record)? {
+                JournalRecord::Signed(transaction) => {
+                    let hash = transaction.clone().committed_hash();
+                    transactions.insert(hash, JournaledTransaction {
+                        hash,
+                        transaction,
+                        status: JournalStatus::Signed,
+                    });
+                },
+                JournalRecord::StatusChanged(hash, status) => {
+                    transactions
+                        .get_mut(&hash)
+                        .ok_or_else(|| {
+                            format_err!(
+                                "Journal changes the status of unknown transaction {}",
+                                hash
+                            )
+                        })?
+                        .status = status;
+                },
+            }
+        }
+        Ok(Self {
+            store: Box::new(store),
+            transactions: Mutex::new(transactions),
+            notifier: None,
+        })
+    }
+
+    /// Notifies the lifecycle of the transactions journaled from now on to `notifier`.
+    pub fn with_notifier(mut self, notifier: Arc<TxnLifecycleNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Opens the journal in the file at `path`, creating it if it doesn't exist.
+    pub fn open_file(path: &Path) -> Result<Self> {
+        Self::open(FileJournalStore::open(path)?)
+    }
+
+    /// Journals a signed transaction, before submitting it. Journaling the same transaction
+    /// again does nothing.
+    pub fn record_signed(&self, transaction: &SignedTransaction) -> Result<HashValue> {
+        let hash = transaction.clone().committed_hash();
+        let journaled = JournaledTransaction {
+            hash,
+            transaction: transaction.clone(),
+            status: JournalStatus::Signed,
+        };
+        {
+            let mut transactions = self.transactions.lock();
+            if transactions.contains_key(&hash) {
+                return Ok(hash);
+            }
+            self.store
+                .append(&bcs::to_bytes(&JournalRecord::Signed(transaction.clone()))?)?;
+            transactions.insert(hash, journaled.clone());
+        }
+        self.notify(&journaled, TxnLifecycleEvent::Built);
+        Ok(hash)
+    }
+
+    /// Journals the new status of a transaction. The status of a transaction whose outcome is
+    /// final can't change.
+    pub fn record_status(&self, hash: HashValue, status: JournalStatus) -> Result<()> {
+        let transaction = {
+            let mut transactions = self.transactions.lock();
+            let transaction = transactions
+                .get_mut(&hash)
+                .ok_or_else(|| format_err!("Transaction {} isn't journaled", hash))?;
+            if transaction.status == status {
+                return Ok(());
+            }
+            if transaction.status.is_terminal() {
+                bail!(
+                    "Transaction {} is already {:?}, it can't become {:?}",
+                    hash,
+                    transaction.status,
+                    status
+                );
+            }
+            self.store
+                .append(&bcs::to_bytes(&JournalRecord::StatusChanged(
+                    hash,
+                    status.clone(),
+                ))?)?;
+            transaction.status = status;
+            transaction.clone()
+        };
+        self.notify(&transaction, transaction.status.lifecycle_event());
+        Ok(())
+    }
+
+    pub fn get(&self, hash: &HashValue) -> Option<JournaledTransaction> {
+        self.transactions.lock().get(hash).cloned()
+    }
+
+    /// The transactions whose outcome isn't known yet, by sender and sequence number.
+    pub fn in_flight(&self) -> Vec<JournaledTransaction> {
+        let mut in_flight: Vec<_> = self
+            .transactions
+            .lock()
+            .values()
+            .filter(|transaction| !transaction.status.is_terminal())
+            .cloned()
+            .collect();
+        in_flight.sort_by_key(|transaction| {
+            (
+                transaction.sender(),
+                transaction.sequence_number(),
+                transaction.hash,
+            )
+        });
+        in_flight
+    }
+
+    /// Journals `transaction`, then submits it.
+    pub async fn submit(
+        &self,
+        client: &Client,
+        transaction: &SignedTransaction,
+    ) -> Result<HashValue> {
+        let hash = self.record_signed(transaction)?;
+        let journaled = self.get(&hash).expect("the transaction was just journaled");
+        self.submit_journaled(client, &journaled).await?;
+        self.record_status(hash, JournalStatus::Submitted)?;
+        Ok(hash)
+    }
+
+    /// Resolves the transactions in flight, e.g. after a restart: records those committed or
+    /// expired, and submits the others again. Returns the transactions still in flight, which
+    /// `recover` can be called again for until they are resolved.
+    ///
+    /// Resubmitting a transaction which was committed in the meantime fails, so it is left in
+    /// flight until the next call finds it committed.
+    pub async fn recover(&self, client: &Client) -> Result<Vec<JournaledTransaction>> {
+        // The ledger timestamp must be read before looking the transactions up: a transaction
+        // not found then, and already expired as of that timestamp, can't ever commit
+        let ledger_timestamp_secs = client
+            .get_ledger_information()
+            .await?
+            .inner()
+            .timestamp_usecs
+            / 1_000_000;
+
+        for transaction in self.in_flight() {
+            match client.get_transaction_by_hash(transaction.hash).await {
+                Ok(response) => {
+                    let found = response.into_inner();
+                    let status = match found.version() {
+                        Some(version) if !found.is_pending() => JournalStatus::Committed {
+                            version,
+                            success: found.success(),
+                            vm_status: found.vm_status(),
+                        },
+                        _ => JournalStatus::Submitted,
+                    };
+                    self.record_status(transaction.hash, status)?;
+                },
+                Err(RestError::Api(AptosErrorResponse {
+                    error:
+                        AptosError {
+                            error_code: AptosErrorCode::TransactionNotFound,
+                            ..
+                        },
+                    ..
+                })) => {
+                    if ledger_timestamp_secs >= transaction.transaction.expiration_timestamp_secs()
+                    {
+                        self.record_status(transaction.hash, JournalStatus::Expired)?;
+                    } else if self.submit_journaled(client, &transaction).await.is_ok() {
+                        self.record_status(transaction.hash, JournalStatus::Submitted)?;
+                    }
+                },
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(self.in_flight())
+    }
+
+    /// Submits a journaled transaction, notifying the submission and its rejection, if any.
+    async fn submit_journaled(
+        &self,
+        client: &Client,
+        transaction: &JournaledTransaction,
+    ) -> Result<(), RestError> {
+        self.notify(transaction, TxnLifecycleEvent::Submitted);
+        let result = client.submit(&transaction.transaction).await;
+        if let Err(err) = &result {
+            self.notify(transaction, TxnLifecycleEvent::Rejected {
+                error: err.to_string(),
+            });
+        }
+        result.map(|_| ())
+    }
+
+    fn notify(&self, transaction: &JournaledTransaction, event: TxnLifecycleEvent) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify(transaction.lifecycle_update(event));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        transaction_builder::TransactionBuilder,
+        types::{chain_id::ChainId, LocalAccount},
+    };
+    use aptos_cached_packages::aptos_stdlib;
+
+    fn transaction(account: &mut LocalAccount) -> SignedTransaction {
+        account.sign_with_transaction_builder(TransactionBuilder::new(
+            aptos_stdlib::aptos_account_transfer(AccountAddress::ONE, 1),
+            100,
+            ChainId::test(),
+        ))
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let mut account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let journal = TransactionJournal::open(InMemoryJournalStore::default()).unwrap();
+        let txn = transaction(&mut account);
+        let hash = journal.record_signed(&txn).unwrap();
+        assert_eq!(journal.record_signed(&txn).unwrap(), hash);
+        assert_eq!(journal.in_flight().len(), 1);
+
+        journal
+            .record_status(hash, JournalStatus::Submitted)
+            .unwrap();
+        journal.record_status(hash, JournalStatus::Expired).unwrap();
+        assert!(journal.in_flight().is_empty());
+        assert!(journal
+            .record_status(hash, JournalStatus::Submitted)
+            .is_err());
+        assert!(journal
+            .record_status(HashValue::zero(), JournalStatus::Submitted)
+            .is_err());
+    }
+
+    #[test]
+    fn test_lifecycle_notifications() {
+        let mut account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let notifier = Arc::new(TxnLifecycleNotifier::new());
+        let updates = notifier.subscribe_channel();
+        let journal = TransactionJournal::open(InMemoryJournalStore::default())
+            .unwrap()
+            .with_notifier(notifier);
+        let txn = transaction(&mut account);
+        let hash = journal.record_signed(&txn).unwrap();
+        journal.record_signed(&txn).unwrap();
+        journal
+            .record_status(hash, JournalStatus::Submitted)
+            .unwrap();
+        journal
+            .record_status(hash, JournalStatus::Submitted)
+            .unwrap();
+        journal
+            .record_status(hash, JournalStatus::Committed {
+                version: 7,
+                success: false,
+                vm_status: "Move abort".to_string(),
+            })
+            .unwrap();
+
+        let events: Vec<_> = updates.try_iter().map(|update| update.event).collect();
+        assert_eq!(events, vec![
+            TxnLifecycleEvent::Built,
+            TxnLifecycleEvent::MempoolAccepted,
+            TxnLifecycleEvent::Failed {
+                version: 7,
+                vm_status: "Move abort".to_string(),
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_file_journal_survives_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal");
+        let mut account = LocalAccount::generate(&mut rand::rngs::OsRng);
+        let first = transaction(&mut account);
+        let second = transaction(&mut account);
+
+        let journal = TransactionJournal::open_file(&path).unwrap();
+        let first_hash = journal.record_signed(&first).unwrap();
+        let second_hash = journal.record_signed(&second).unwrap();
+        journal
+            .record_status(first_hash, JournalStatus::Committed {
+                version: 7,
+                success: true,
+                vm_status: "Executed successfully".to_string(),
+            })
+            .unwrap();
+        drop(journal);
+
+        // A record cut short by a crash is dropped
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[100, 0, 0, 0, 1, 2]).unwrap();
+        drop(file);
+
+        let journal = TransactionJournal::open_file(&path).unwrap();
+        assert!(matches!(
+            journal.get(&first_hash).unwrap().status,
+            JournalStatus::Committed { version: 7, .. }
+        ));
+        let in_flight = journal.in_flight();
+        assert_eq!(in_flight.len(), 1);
+        assert_eq!(in_flight[0].hash, second_hash);
+        assert_eq!(in_flight[0].transaction, second);
+
+        // Records appended after the dropped one are read back
+        journal
+            .record_status(second_hash, JournalStatus::Submitted)
+            .unwrap();
+        drop(journal);
+        let journal = TransactionJournal::open_file(&path).unwrap();
+        assert_eq!(
+            journal.get(&second_hash).unwrap().status,
+            JournalStatus::Submitted
+        );
+    }
+}
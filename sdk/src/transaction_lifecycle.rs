@@ -0,0 +1,167 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Typed notifications of the lifecycle of the transactions a service submits, so it can wire
+//! monitoring, e.g. metrics or alerts on expired transactions, without polling their status.
+//!
+//! A [`TxnLifecycleNotifier`] is given to a
+//! [`TransactionJournal`](crate::transaction_journal::TransactionJournal), which notifies each
+//! change of the transactions it manages to the listeners subscribed to the notifier.
+
+use crate::{crypto::HashValue, types::account_address::AccountAddress};
+use aptos_infallible::{Mutex, RwLock};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// A step in the lifecycle of a transaction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TxnLifecycleEvent {
+    /// Signed and journaled, not submitted yet.
+    Built,
+    /// Being submitted to a fullnode, either for the first time or again.
+    Submitted,
+    /// Accepted by the mempool of a fullnode, not known to be committed yet.
+    MempoolAccepted,
+    /// Rejected by the fullnode it was submitted to. The transaction is still in flight, as
+    /// another submission may be accepted.
+    Rejected { error: String },
+    /// Committed on chain successfully.
+    Committed { version: u64 },
+    /// Committed on chain, but failed, e.g. because it aborted. Its sequence number is used.
+    Failed { version: u64, vm_status: String },
+    /// Not committed, and expired, so it will never commit.
+    Expired,
+}
+
+impl TxnLifecycleEvent {
+    /// Whether the event is the last one of the transaction.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            TxnLifecycleEvent::Committed { .. }
+                | TxnLifecycleEvent::Failed { .. }
+                | TxnLifecycleEvent::Expired
+        )
+    }
+}
+
+/// An event of a transaction, along with the transaction it happened to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TxnLifecycleUpdate {
+    pub hash: HashValue,
+    pub sender: AccountAddress,
+    pub sequence_number: u64,
+    pub event: TxnLifecycleEvent,
+}
+
+/// Receives the updates of a [`TxnLifecycleNotifier`]. Listeners are called synchronously by
+/// whoever manages the transactions, so they should hand slow work off, e.g. to a channel.
+pub trait TxnLifecycleListener: Send + Sync {
+    fn on_update(&self, update: &TxnLifecycleUpdate);
+}
+
+impl<F: Fn(&TxnLifecycleUpdate) + Send + Sync> TxnLifecycleListener for F {
+    fn on_update(&self, update: &TxnLifecycleUpdate) {
+        self(update)
+    }
+}
+
+/// Sends the updates to a channel, dropping them once its receiver is dropped.
+struct ChannelListener(Mutex<Sender<TxnLifecycleUpdate>>);
+
+impl TxnLifecycleListener for ChannelListener {
+    fn on_update(&self, update: &TxnLifecycleUpdate) {
+        let _ = self.0.lock().send(update.clone());
+    }
+}
+
+/// Notifies the lifecycle updates of transactions to the subscribed listeners, see the
+/// [module documentation](self).
+#[derive(Default)]
+pub struct TxnLifecycleNotifier {
+    listeners: RwLock<Vec<Box<dyn TxnLifecycleListener>>>,
+}
+
+impl TxnLifecycleNotifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `listener` with every update from now on.
+    pub fn subscribe(&self, listener: impl TxnLifecycleListener + 'static) {
+        self.listeners.write().push(Box::new(listener));
+    }
+
+    /// Returns a channel receiving every update from now on.
+    pub fn subscribe_channel(&self) -> Receiver<TxnLifecycleUpdate> {
+        let (sender, receiver) = channel();
+        self.subscribe(ChannelListener(Mutex::new(sender)));
+        receiver
+    }
+
+    pub fn notify(&self, update: TxnLifecycleUpdate) {
+        for listener in self.listeners.read().iter() {
+            listener.on_update(&update);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    fn update(sequence_number: u64, event: TxnLifecycleEvent) -> TxnLifecycleUpdate {
+        TxnLifecycleUpdate {
+            hash: HashValue::zero(),
+            sender: AccountAddress::ONE,
+            sequence_number,
+            event,
+        }
+    }
+
+    #[test]
+    fn test_notify() {
+        let notifier = TxnLifecycleNotifier::new();
+        // Nothing is subscribed yet, so the update is lost
+        notifier.notify(update(0, TxnLifecycleEvent::Built));
+
+        let seen = Arc::new(Mutex::new(vec![]));
+        let listener_seen = seen.clone();
+        notifier.subscribe(move |update: &TxnLifecycleUpdate| {
+            listener_seen.lock().push(update.event.clone())
+        });
+        let receiver = notifier.subscribe_channel();
+        notifier.notify(update(1, TxnLifecycleEvent::Submitted));
+        notifier.notify(update(1, TxnLifecycleEvent::Committed { version: 5 }));
+
+        assert_eq!(*seen.lock(), vec![
+            TxnLifecycleEvent::Submitted,
+            TxnLifecycleEvent::Committed { version: 5 },
+        ]);
+        assert_eq!(receiver.try_iter().collect::<Vec<_>>(), vec![
+            update(1, TxnLifecycleEvent::Submitted),
+            update(1, TxnLifecycleEvent::Committed { version: 5 }),
+        ]);
+
+        // Dropping the receiver of a channel doesn't fail the other listeners
+        drop(receiver);
+        notifier.notify(update(2, TxnLifecycleEvent::Expired));
+        assert_eq!(seen.lock().last(), Some(&TxnLifecycleEvent::Expired));
+    }
+
+    #[test]
+    fn test_is_terminal() {
+        assert!(!TxnLifecycleEvent::Built.is_terminal());
+        assert!(!TxnLifecycleEvent::Rejected {
+            error: "mempool is full".to_string(),
+        }
+        .is_terminal());
+        assert!(TxnLifecycleEvent::Committed { version: 5 }.is_terminal());
+        assert!(TxnLifecycleEvent::Failed {
+            version: 5,
+            vm_status: "Out of gas".to_string(),
+        }
+        .is_terminal());
+        assert!(TxnLifecycleEvent::Expired.is_terminal());
+    }
+}
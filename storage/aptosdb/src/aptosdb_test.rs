@@ -5,6 +5,7 @@ use crate::{
     get_first_seq_num_and_limit,
     pruner::{
         ledger_pruner_manager::LedgerPrunerManager,
+        ledger_store::ledger_store_pruner::LedgerDataKind,
         state_merkle_pruner_manager::StateMerklePrunerManager,
     },
     test_helper,
@@ -109,6 +110,9 @@ fn test_pruner_config() {
                 prune_window: 100,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                transaction_prune_window: None,
+                write_set_prune_window: None,
+                event_prune_window: None,
             });
         assert_eq!(ledger_pruner.is_pruner_enabled(), enable);
         assert_eq!(ledger_pruner.get_prune_window(), 100);
@@ -132,12 +136,14 @@ fn test_error_if_version_pruned() {
     );
     assert!(db.error_if_state_merkle_pruned("State", 5).is_ok());
     assert_eq!(
-        db.error_if_ledger_pruned("Transaction", 9)
+        db.error_if_ledger_data_pruned(LedgerDataKind::Transaction, "Transaction", 9)
             .unwrap_err()
             .to_string(),
         "Transaction at version 9 is pruned, min available version is 10."
     );
-    assert!(db.error_if_ledger_pruned("Transaction", 10).is_ok());
+    assert!(db
+        .error_if_ledger_data_pruned(LedgerDataKind::Transaction, "Transaction", 10)
+        .is_ok());
 }
 
 #[test]
@@ -189,6 +195,9 @@ pub fn test_state_merkle_pruning_impl(
                 prune_window: 10,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                transaction_prune_window: None,
+                write_set_prune_window: None,
+                event_prune_window: None,
             },
             state_merkle_pruner_config: StateMerklePrunerConfig {
                 enable: true,
@@ -55,11 +55,15 @@ use crate::{
         OTHER_TIMERS_SECONDS, ROCKSDB_PROPERTIES,
     },
     pruner::{
-        db_pruner::DBPruner, ledger_pruner_manager::LedgerPrunerManager,
-        ledger_store::ledger_store_pruner::LedgerPruner, pruner_manager::PrunerManager,
-        pruner_utils, state_kv_pruner::StateKvPruner,
+        db_pruner::DBPruner,
+        ledger_pruner_manager::LedgerPrunerManager,
+        ledger_store::ledger_store_pruner::{LedgerDataKind, LedgerPruner},
+        pruner_manager::PrunerManager,
+        pruner_utils,
+        state_kv_pruner::StateKvPruner,
         state_kv_pruner_manager::StateKvPrunerManager,
-        state_merkle_pruner_manager::StateMerklePrunerManager, state_store::StateMerklePruner,
+        state_merkle_pruner_manager::StateMerklePrunerManager,
+        state_store::StateMerklePruner,
     },
     schema::*,
     stale_node_index::StaleNodeIndexSchema,
@@ -636,7 +640,10 @@ impl AptosDB {
         ledger_version: Version,
         fetch_events: bool,
     ) -> Result<TransactionWithProof> {
-        self.error_if_ledger_pruned("Transaction", version)?;
+        self.error_if_ledger_data_pruned(LedgerDataKind::Transaction, "Transaction", version)?;
+        if fetch_events {
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "Events", version)?;
+        }
 
         let proof = self
             .ledger_store
@@ -898,8 +905,13 @@ impl AptosDB {
         }
     }
 
-    fn error_if_ledger_pruned(&self, data_type: &str, version: Version) -> Result<()> {
-        let min_readable_version = self.ledger_pruner.get_min_readable_version();
+    fn error_if_ledger_data_pruned(
+        &self,
+        kind: LedgerDataKind,
+        data_type: &str,
+        version: Version,
+    ) -> Result<()> {
+        let min_readable_version = self.ledger_pruner.get_min_readable_version_of(kind);
         ensure!(
             version >= min_readable_version,
             "{} at version {} is pruned, min available version is {}.",
@@ -1081,7 +1093,14 @@ impl DbReader for AptosDB {
             if start_version > ledger_version || limit == 0 {
                 return Ok(TransactionListWithProof::new_empty());
             }
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction",
+                start_version,
+            )?;
+            if fetch_events {
+                self.error_if_ledger_data_pruned(LedgerDataKind::Event, "Events", start_version)?;
+            }
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -1153,7 +1172,10 @@ impl DbReader for AptosDB {
     /// Get the first version that txn starts existent.
     fn get_first_txn_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_txn_version", || {
-            Ok(Some(self.ledger_pruner.get_min_readable_version()))
+            Ok(Some(
+                self.ledger_pruner
+                    .get_min_readable_version_of(LedgerDataKind::Transaction),
+            ))
         })
     }
 
@@ -1167,7 +1189,10 @@ impl DbReader for AptosDB {
     /// Get the first version that write set starts existent.
     fn get_first_write_set_version(&self) -> Result<Option<Version>> {
         gauged_api("get_first_write_set_version", || {
-            Ok(Some(self.ledger_pruner.get_min_readable_version()))
+            Ok(Some(
+                self.ledger_pruner
+                    .get_min_readable_version_of(LedgerDataKind::WriteSet),
+            ))
         })
     }
 
@@ -1190,7 +1215,13 @@ impl DbReader for AptosDB {
                 return Ok(TransactionOutputListWithProof::new_empty());
             }
 
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction",
+                start_version,
+            )?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::WriteSet, "Write set", start_version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "Events", start_version)?;
 
             let limit = std::cmp::min(limit, ledger_version - start_version + 1);
 
@@ -1248,7 +1279,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Transaction>> + '_>> {
         gauged_api("get_transaction_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction",
+                start_version,
+            )?;
 
             let iter = self
                 .transaction_store
@@ -1264,7 +1299,11 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<TransactionInfo>> + '_>> {
         gauged_api("get_transaction_info_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction info",
+                start_version,
+            )?;
 
             let iter = self
                 .ledger_store
@@ -1280,7 +1319,7 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<Vec<ContractEvent>>> + '_>> {
         gauged_api("get_events_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "Events", start_version)?;
 
             let iter = self
                 .event_store
@@ -1299,7 +1338,7 @@ impl DbReader for AptosDB {
     ) -> Result<Box<dyn Iterator<Item = Result<WriteSet>> + '_>> {
         gauged_api("get_write_set_iterator", || {
             error_if_too_many_requested(limit, MAX_REQUEST_LIMIT)?;
-            self.error_if_ledger_pruned("Transaction", start_version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::WriteSet, "Write set", start_version)?;
 
             let iter = self
                 .transaction_store
@@ -1315,7 +1354,11 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<TransactionAccumulatorRangeProof> {
         gauged_api("get_transaction_accumulator_range_proof", || {
-            self.error_if_ledger_pruned("Transaction", first_version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction",
+                first_version,
+            )?;
 
             self.ledger_store.get_transaction_range_proof(
                 Some(first_version),
@@ -1439,7 +1482,7 @@ impl DbReader for AptosDB {
 
     fn get_block_timestamp(&self, version: u64) -> Result<u64> {
         gauged_api("get_block_timestamp", || {
-            self.error_if_ledger_pruned("NewBlockEvent", version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "NewBlockEvent", version)?;
             ensure!(version <= self.get_latest_version()?);
 
             let (_first_version, new_block_event) = self.event_store.get_block_metadata(version)?;
@@ -1449,7 +1492,7 @@ impl DbReader for AptosDB {
 
     fn get_next_block_event(&self, version: Version) -> Result<(Version, NewBlockEvent)> {
         gauged_api("get_next_block_event", || {
-            self.error_if_ledger_pruned("NewBlockEvent", version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "NewBlockEvent", version)?;
             if let Some((block_version, _, _)) = self
                 .event_store
                 .lookup_event_at_or_after_version(&new_block_event_key(), version)?
@@ -1469,7 +1512,7 @@ impl DbReader for AptosDB {
         version: Version,
     ) -> Result<(Version, Version, NewBlockEvent)> {
         gauged_api("get_block_info", || {
-            self.error_if_ledger_pruned("NewBlockEvent", version)?;
+            self.error_if_ledger_data_pruned(LedgerDataKind::Event, "NewBlockEvent", version)?;
 
             let latest_li = self.get_latest_ledger_info()?;
             let committed_version = latest_li.ledger_info().version();
@@ -1552,7 +1595,11 @@ impl DbReader for AptosDB {
 
     fn get_accumulator_root_hash(&self, version: Version) -> Result<HashValue> {
         gauged_api("get_accumulator_root_hash", || {
-            self.error_if_ledger_pruned("Transaction accumulator", version)?;
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
+                "Transaction accumulator",
+                version,
+            )?;
             self.ledger_store.get_root_hash(version)
         })
     }
@@ -1563,7 +1610,8 @@ impl DbReader for AptosDB {
         ledger_version: Version,
     ) -> Result<AccumulatorConsistencyProof> {
         gauged_api("get_accumulator_consistency_proof", || {
-            self.error_if_ledger_pruned(
+            self.error_if_ledger_data_pruned(
+                LedgerDataKind::Transaction,
                 "Transaction accumulator",
                 client_known_version.unwrap_or(0),
             )?;
@@ -1629,7 +1677,9 @@ impl DbReader for AptosDB {
 
     fn get_ledger_prune_window(&self) -> Result<usize> {
         gauged_api("get_ledger_prune_window", || {
-            Ok(self.ledger_pruner.get_prune_window() as usize)
+            Ok(self
+                .ledger_pruner
+                .get_prune_window_of(LedgerDataKind::Transaction) as usize)
         })
     }
 
@@ -1648,7 +1698,11 @@ impl DbReader for AptosDB {
     fn get_state_storage_usage(&self, version: Option<Version>) -> Result<StateStorageUsage> {
         gauged_api("get_state_storage_usage", || {
             if let Some(v) = version {
-                self.error_if_ledger_pruned("state storage usage", v)?;
+                self.error_if_ledger_data_pruned(
+                    LedgerDataKind::VersionData,
+                    "state storage usage",
+                    v,
+                )?;
             }
             self.state_store.get_usage(version)
         })
@@ -1,7 +1,10 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{AptosDB, EventStore, LedgerPrunerManager, PrunerManager};
+use crate::{
+    pruner::ledger_store::ledger_store_pruner::LedgerDataKind, AptosDB, EventStore,
+    LedgerPrunerManager, PrunerManager,
+};
 use aptos_config::config::LedgerPrunerConfig;
 use aptos_proptest_helpers::Index;
 use aptos_schemadb::SchemaBatch;
@@ -31,7 +34,43 @@ proptest! {
             })
             .collect();
 
-        verify_event_store_pruner(event_batches);
+        verify_event_store_pruner(event_batches, 0);
+    }
+
+    #[test]
+    fn test_event_store_pruner_with_longer_event_window(
+        mut universe in any_with::<AccountInfoUniverse>(3),
+        gen_batches in vec(vec((any::<Index>(), any::<ContractEventGen>()), 0..=2), 0..100),
+        event_prune_window in 1..10u64,
+    ) {
+        let event_batches = gen_batches
+            .into_iter()
+            .map(|gens| {
+                gens.into_iter()
+                    .map(|(index, gen)| gen.materialize(*index, &mut universe))
+                    .collect()
+            })
+            .collect();
+
+        verify_event_store_pruner(event_batches, event_prune_window);
+    }
+
+    #[test]
+    fn test_event_store_pruner_with_shorter_event_window(
+        mut universe in any_with::<AccountInfoUniverse>(3),
+        gen_batches in vec(vec((any::<Index>(), any::<ContractEventGen>()), 0..=2), 10..100),
+        event_prune_window in 0..5u64,
+    ) {
+        let event_batches = gen_batches
+            .into_iter()
+            .map(|gens| {
+                gens.into_iter()
+                    .map(|(index, gen)| gen.materialize(*index, &mut universe))
+                    .collect()
+            })
+            .collect();
+
+        verify_min_viable_version_with_shorter_event_window(event_batches, event_prune_window);
     }
 
         #[test]
@@ -52,7 +91,7 @@ proptest! {
     }
 }
 
-fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
+fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>, event_prune_window: u64) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
     let event_store = &aptos_db.event_store;
@@ -72,12 +111,21 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        transaction_prune_window: None,
+        write_set_prune_window: None,
+        event_prune_window: Some(event_prune_window),
     });
     // start pruning events batches of size 2 and verify transactions have been pruned from DB
     for i in (0..=num_versions).step_by(2) {
         pruner
             .wake_and_wait_pruner(i as u64 /* latest_version */)
             .unwrap();
+        // events are kept for `event_prune_window` versions more than the other ledger data
+        let i = i.saturating_sub(event_prune_window as usize);
+        assert_eq!(
+            pruner.get_min_readable_version_of(LedgerDataKind::Event),
+            i as Version
+        );
         // ensure that all events up to i has been pruned
         for j in 0..i {
             verify_events_not_in_store(j as u64, event_store);
@@ -93,6 +141,53 @@ fn verify_event_store_pruner(events: Vec<Vec<ContractEvent>>) {
     }
 }
 
+fn verify_min_viable_version_with_shorter_event_window(
+    events: Vec<Vec<ContractEvent>>,
+    event_prune_window: u64,
+) {
+    let tmp_dir = TempPath::new();
+    let aptos_db = AptosDB::new_for_test(&tmp_dir);
+    let event_store = &aptos_db.event_store;
+    let batch = SchemaBatch::new();
+    let num_versions = events.len();
+
+    // Write events to DB
+    for (version, events_for_version) in events.iter().enumerate() {
+        event_store
+            .put_events(version as u64, events_for_version, &batch)
+            .unwrap();
+    }
+    aptos_db.ledger_db.write_schemas(batch).unwrap();
+
+    let transaction_prune_window = event_prune_window + 5;
+    let pruner = LedgerPrunerManager::new(Arc::clone(&aptos_db.ledger_db), LedgerPrunerConfig {
+        enable: true,
+        prune_window: transaction_prune_window,
+        batch_size: 1,
+        user_pruning_window_offset: 0,
+        transaction_prune_window: None,
+        write_set_prune_window: None,
+        event_prune_window: Some(event_prune_window),
+    });
+    pruner
+        .wake_and_wait_pruner(num_versions as u64 /* latest_version */)
+        .unwrap();
+
+    // the transactions are kept for longer than the events, but the first viable version must
+    // still have its events, or the block it's in can't be found
+    let min_event_version = pruner.get_min_readable_version_of(LedgerDataKind::Event);
+    assert_eq!(
+        min_event_version,
+        (num_versions as u64).saturating_sub(event_prune_window)
+    );
+    assert!(pruner.get_min_readable_version_of(LedgerDataKind::Transaction) < min_event_version);
+    let min_viable_version = pruner.get_min_viable_version();
+    assert_eq!(min_viable_version, min_event_version);
+    for j in min_viable_version..num_versions as u64 {
+        verify_events_in_store(&events, j, event_store);
+    }
+}
+
 fn verify_event_store_pruner_disabled(events: Vec<Vec<ContractEvent>>) {
     let tmp_dir = TempPath::new();
     let aptos_db = AptosDB::new_for_test(&tmp_dir);
@@ -4,8 +4,10 @@
 use crate::{
     metrics::{PRUNER_BATCH_SIZE, PRUNER_WINDOW},
     pruner::{
-        db_pruner::DBPruner, ledger_pruner_worker::LedgerPrunerWorker,
-        ledger_store::ledger_store_pruner::LedgerPruner, pruner_manager::PrunerManager,
+        db_pruner::DBPruner,
+        ledger_pruner_worker::LedgerPrunerWorker,
+        ledger_store::ledger_store_pruner::{LedgerDataKind, LedgerPruner},
+        pruner_manager::PrunerManager,
     },
     pruner_utils,
 };
@@ -21,6 +23,8 @@ pub(crate) struct LedgerPrunerManager {
     /// DB version window, which dictates how many version of other stores like transaction, ledger
     /// info, events etc to keep.
     prune_window: Version,
+    /// The prune window of each kind of ledger data, indexed by `LedgerDataKind`.
+    kind_prune_windows: [Version; LedgerDataKind::ALL.len()],
     /// Ledger pruner. Is always initialized regardless if the pruner is enabled to keep tracks
     /// of the min_readable_version.
     pruner: Arc<LedgerPruner>,
@@ -60,17 +64,14 @@ impl PrunerManager for LedgerPrunerManager {
         self.pruner.as_ref().min_readable_version()
     }
 
+    /// The min version of the transactions, together with their events, that will stay readable
+    /// for a while. The events are needed too as the block of a version is found by its
+    /// `NewBlockEvent`.
     fn get_min_viable_version(&self) -> Version {
-        let min_version = self.get_min_readable_version();
-        if self.is_pruner_enabled() {
-            let adjusted_window = self
-                .prune_window
-                .saturating_sub(self.user_pruning_window_offset);
-            let adjusted_cutoff = self.latest_version.lock().saturating_sub(adjusted_window);
-            std::cmp::max(min_version, adjusted_cutoff)
-        } else {
-            min_version
-        }
+        std::cmp::max(
+            self.get_min_viable_version_of(LedgerDataKind::Transaction),
+            self.get_min_viable_version_of(LedgerDataKind::Event),
+        )
     }
 
     /// Sets pruner target version when necessary.
@@ -93,14 +94,15 @@ impl PrunerManager for LedgerPrunerManager {
         assert!(self.pruner_enabled);
         self.pruner_worker
             .as_ref()
-            .set_target_db_version(latest_version.saturating_sub(self.prune_window));
+            .set_target_db_version(latest_version.saturating_sub(self.min_prune_window()));
     }
 }
 
 impl LedgerPrunerManager {
     /// Creates a worker thread that waits on a channel for pruning commands.
     pub fn new(ledger_rocksdb: Arc<DB>, ledger_pruner_config: LedgerPrunerConfig) -> Self {
-        let ledger_pruner = pruner_utils::create_ledger_pruner(ledger_rocksdb);
+        let ledger_pruner =
+            pruner_utils::create_ledger_pruner(ledger_rocksdb, &ledger_pruner_config);
 
         if ledger_pruner_config.enable {
             PRUNER_WINDOW
@@ -135,6 +137,8 @@ impl LedgerPrunerManager {
         Self {
             pruner_enabled: ledger_pruner_config.enable,
             prune_window: ledger_pruner_config.prune_window,
+            kind_prune_windows: LedgerDataKind::ALL
+                .map(|kind| kind.prune_window(&ledger_pruner_config)),
             pruner: ledger_pruner,
             pruner_worker: ledger_pruner_worker,
             worker_thread: ledger_pruner_worker_thread,
@@ -145,6 +149,37 @@ impl LedgerPrunerManager {
         }
     }
 
+    /// The min readable version of one kind of ledger data, see
+    /// `LedgerPruner::min_readable_version_of`.
+    pub fn get_min_readable_version_of(&self, kind: LedgerDataKind) -> Version {
+        self.pruner.min_readable_version_of(kind)
+    }
+
+    /// The prune window of one kind of ledger data.
+    pub fn get_prune_window_of(&self, kind: LedgerDataKind) -> Version {
+        self.kind_prune_windows[kind as usize]
+    }
+
+    fn get_min_viable_version_of(&self, kind: LedgerDataKind) -> Version {
+        let min_version = self.get_min_readable_version_of(kind);
+        if self.is_pruner_enabled() {
+            let adjusted_window = self.kind_prune_windows[kind as usize]
+                .saturating_sub(self.user_pruning_window_offset);
+            let adjusted_cutoff = self.latest_version.lock().saturating_sub(adjusted_window);
+            std::cmp::max(min_version, adjusted_cutoff)
+        } else {
+            min_version
+        }
+    }
+
+    fn min_prune_window(&self) -> Version {
+        self.kind_prune_windows
+            .iter()
+            .copied()
+            .min()
+            .unwrap_or(self.prune_window)
+    }
+
     #[cfg(test)]
     pub fn testonly_update_min_version(&self, version: Version) {
         self.pruner.testonly_update_min_version(version);
@@ -20,19 +20,84 @@ use crate::{
     },
     EventStore, TransactionStore,
 };
-use aptos_logger::warn;
+use aptos_config::config::LedgerPrunerConfig;
+use aptos_logger::{info, warn};
 use aptos_schemadb::{ReadOptions, SchemaBatch, DB};
 use aptos_types::transaction::{AtomicVersion, Version};
-use std::sync::{atomic::Ordering, Arc};
+use std::{
+    cmp::{max, min},
+    sync::{atomic::Ordering, Arc},
+};
 
 pub const LEDGER_PRUNER_NAME: &str = "ledger_pruner";
 
+/// The kinds of ledger data whose prune window can be configured separately, see
+/// `LedgerPrunerConfig`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum LedgerDataKind {
+    /// Transactions, their infos and the transaction accumulator.
+    Transaction,
+    WriteSet,
+    Event,
+    /// Per version data like the state storage usage, always kept for `prune_window`.
+    VersionData,
+}
+
+const NUM_LEDGER_DATA_KINDS: usize = 4;
+
+impl LedgerDataKind {
+    pub const ALL: [LedgerDataKind; NUM_LEDGER_DATA_KINDS] = [
+        LedgerDataKind::Transaction,
+        LedgerDataKind::WriteSet,
+        LedgerDataKind::Event,
+        LedgerDataKind::VersionData,
+    ];
+
+    pub fn prune_window(self, config: &LedgerPrunerConfig) -> Version {
+        match self {
+            LedgerDataKind::Transaction => config.transaction_prune_window,
+            LedgerDataKind::WriteSet => config.write_set_prune_window,
+            LedgerDataKind::Event => config.event_prune_window,
+            LedgerDataKind::VersionData => None,
+        }
+        .unwrap_or(config.prune_window)
+    }
+
+    fn progress_key(self) -> DbMetadataKey {
+        match self {
+            LedgerDataKind::Transaction => DbMetadataKey::TransactionPrunerProgress,
+            LedgerDataKind::WriteSet => DbMetadataKey::WriteSetPrunerProgress,
+            LedgerDataKind::Event => DbMetadataKey::EventPrunerProgress,
+            LedgerDataKind::VersionData => DbMetadataKey::VersionDataPrunerProgress,
+        }
+    }
+
+    fn metrics_label(self) -> &'static str {
+        match self {
+            LedgerDataKind::Transaction => "ledger_pruner_transaction",
+            LedgerDataKind::WriteSet => "ledger_pruner_write_set",
+            LedgerDataKind::Event => "ledger_pruner_event",
+            LedgerDataKind::VersionData => "ledger_pruner_version_data",
+        }
+    }
+}
+
 /// Responsible for pruning everything except for the state tree.
+///
+/// Every kind of ledger data is pruned up to its own version: the target version is the latest
+/// version minus the shortest of the prune windows, and the data kept for longer lags behind it
+/// by the difference between its window and the shortest one.
 pub(crate) struct LedgerPruner {
     db: Arc<DB>,
     /// Keeps track of the target version that the pruner needs to achieve.
     target_version: AtomicVersion,
+    /// The min readable version of the ledger data of all kinds.
     min_readable_version: AtomicVersion,
+    /// The min readable version of each kind of ledger data, indexed by `LedgerDataKind`.
+    min_readable_versions: [AtomicVersion; NUM_LEDGER_DATA_KINDS],
+    /// How far behind the target version each kind of ledger data is pruned, indexed by
+    /// `LedgerDataKind`.
+    lags: [Version; NUM_LEDGER_DATA_KINDS],
     transaction_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
     version_data_pruner: Arc<dyn DBSubPruner + Send + Sync>,
     event_store_pruner: Arc<dyn DBSubPruner + Send + Sync>,
@@ -40,6 +105,18 @@ pub(crate) struct LedgerPruner {
 }
 
 impl DBPruner for LedgerPruner {
+    fn initialize(&self) {
+        let min_readable_version = self
+            .initialize_min_readable_version()
+            .expect("Ledger pruner failed to initialize.");
+        info!(
+            min_readable_version = min_readable_version,
+            min_readable_versions = ?self.kind_min_readable_versions(),
+            "{} initialized.",
+            self.name()
+        );
+    }
+
     fn name(&self) -> &'static str {
         LEDGER_PRUNER_NAME
     }
@@ -51,27 +128,25 @@ impl DBPruner for LedgerPruner {
 
         // Collect the schema batch writes
         let mut db_batch = SchemaBatch::new();
-        let current_target_version = self.prune_inner(max_versions, &mut db_batch)?;
-        self.save_min_readable_version(current_target_version, &db_batch)?;
+        let progress = self.prune_inner(max_versions, &mut db_batch)?;
+        self.save_progress(&progress, &db_batch)?;
         // Commit all the changes to DB atomically
         self.db.write_schemas(db_batch)?;
 
         // TODO(zcc): recording progress after writing schemas might provide wrong answers to
         // API calls when they query min_readable_version while the write_schemas are still in
         // progress.
-        self.record_progress(current_target_version);
-        Ok(current_target_version)
+        self.record_kind_progress(&progress);
+        Ok(self.min_readable_version())
     }
 
+    /// Saves `version` as the min readable version of the ledger data of all kinds.
     fn save_min_readable_version(
         &self,
         version: Version,
         batch: &SchemaBatch,
     ) -> anyhow::Result<()> {
-        batch.put::<DbMetadataSchema>(
-            &DbMetadataKey::LedgerPrunerProgress,
-            &DbMetadataValue::Version(version),
-        )
+        self.save_progress(&[version; NUM_LEDGER_DATA_KINDS], batch)
     }
 
     fn initialize_min_readable_version(&self) -> anyhow::Result<Version> {
@@ -79,31 +154,47 @@ impl DBPruner for LedgerPruner {
             .db
             .get::<DbMetadataSchema>(&DbMetadataKey::LedgerPrunerProgress)?
             .map_or(0, |v| v.expect_version());
+        // DBs written before the prune windows could be configured per kind only have the
+        // overall progress, which all kinds start from.
+        let mut progress = [stored_min_version; NUM_LEDGER_DATA_KINDS];
+        for kind in LedgerDataKind::ALL {
+            if let Some(version) = self.db.get::<DbMetadataSchema>(&kind.progress_key())? {
+                progress[kind as usize] = max(version.expect_version(), stored_min_version);
+            }
+        }
+
+        let stored_txn_version = progress[LedgerDataKind::Transaction as usize];
         let mut iter = self.db.iter::<TransactionSchema>(ReadOptions::default())?;
-        iter.seek(&stored_min_version)?;
+        iter.seek(&stored_txn_version)?;
         let version = match iter.next().transpose()? {
             Some((version, _)) => version,
             None => 0,
         };
-        match version.cmp(&stored_min_version) {
+        match version.cmp(&stored_txn_version) {
             std::cmp::Ordering::Greater => {
-                let res = self.db.put::<DbMetadataSchema>(
-                    &DbMetadataKey::LedgerPrunerProgress,
-                    &DbMetadataValue::Version(version),
-                );
+                // Nothing before the first transaction is in the DB, e.g. it was restored from a
+                // backup.
+                for kind_progress in progress.iter_mut() {
+                    *kind_progress = max(*kind_progress, version);
+                }
+                let batch = SchemaBatch::new();
+                self.save_progress(&progress, &batch)?;
+                let res = self.db.write_schemas(batch);
                 warn!(
-                    stored_min_version = stored_min_version,
+                    stored_min_version = stored_txn_version,
                     actual_min_version = version,
                     res = ?res,
                     "Try to update stored min readable transaction version to the actual one.",
                 );
-                Ok(version)
             },
-            std::cmp::Ordering::Equal => Ok(version),
+            std::cmp::Ordering::Equal => (),
             std::cmp::Ordering::Less => {
-                panic!("No transaction is found at or after stored ledger pruner progress ({}), db might be corrupted.", stored_min_version)
+                panic!("No transaction is found at or after stored ledger pruner progress ({}), db might be corrupted.", stored_txn_version)
             },
         }
+
+        self.record_kind_progress(&progress);
+        Ok(self.min_readable_version())
     }
 
     fn min_readable_version(&self) -> Version {
@@ -121,16 +212,23 @@ impl DBPruner for LedgerPruner {
         self.target_version.load(Ordering::Relaxed)
     }
 
+    /// Records `min_readable_version` as the min readable version of the ledger data of all
+    /// kinds.
     fn record_progress(&self, min_readable_version: Version) {
-        self.min_readable_version
-            .store(min_readable_version, Ordering::Relaxed);
-        PRUNER_VERSIONS
-            .with_label_values(&["ledger_pruner", "min_readable"])
-            .set(min_readable_version as i64);
+        self.record_kind_progress(&[min_readable_version; NUM_LEDGER_DATA_KINDS]);
+    }
+
+    fn is_pruning_pending(&self) -> bool {
+        LedgerDataKind::ALL
+            .into_iter()
+            .any(|kind| self.kind_target_version(kind) > self.min_readable_version_of(kind))
     }
 
     /// (For tests only.) Updates the minimal readable version kept by pruner.
     fn testonly_update_min_version(&self, version: Version) {
+        for min_readable_version in &self.min_readable_versions {
+            min_readable_version.store(version, Ordering::Relaxed);
+        }
         self.min_readable_version.store(version, Ordering::Relaxed)
     }
 }
@@ -140,11 +238,16 @@ impl LedgerPruner {
         db: Arc<DB>,
         transaction_store: Arc<TransactionStore>,
         event_store: Arc<EventStore>,
+        ledger_pruner_config: &LedgerPrunerConfig,
     ) -> Self {
+        let windows = LedgerDataKind::ALL.map(|kind| kind.prune_window(ledger_pruner_config));
+        let min_window = windows.iter().copied().min().unwrap_or_default();
         let pruner = LedgerPruner {
             db,
             target_version: AtomicVersion::new(0),
             min_readable_version: AtomicVersion::new(0),
+            min_readable_versions: Default::default(),
+            lags: windows.map(|window| window - min_window),
             transaction_store_pruner: Arc::new(TransactionStorePruner::new(
                 transaction_store.clone(),
             )),
@@ -161,40 +264,97 @@ impl LedgerPruner {
         let target_version = 1; // The genesis version is 0. Delete [0,1) (exclusive)
         let max_version = 1; // We should only be pruning a single version
 
-        let ledger_pruner = pruner_utils::create_ledger_pruner(ledger_db);
+        let ledger_pruner =
+            pruner_utils::create_ledger_pruner(ledger_db, &LedgerPrunerConfig::default());
         ledger_pruner.set_target_version(target_version);
         ledger_pruner.prune_inner(max_version, db_batch)?;
 
         Ok(())
     }
 
+    /// The min readable version of one kind of ledger data, which can be lower than
+    /// `min_readable_version()` if it's kept for longer than the other kinds.
+    pub fn min_readable_version_of(&self, kind: LedgerDataKind) -> Version {
+        self.min_readable_versions[kind as usize].load(Ordering::Relaxed)
+    }
+
+    fn kind_min_readable_versions(&self) -> [Version; NUM_LEDGER_DATA_KINDS] {
+        LedgerDataKind::ALL.map(|kind| self.min_readable_version_of(kind))
+    }
+
+    fn kind_target_version(&self, kind: LedgerDataKind) -> Version {
+        self.target_version()
+            .saturating_sub(self.lags[kind as usize])
+    }
+
+    fn sub_pruner(&self, kind: LedgerDataKind) -> &Arc<dyn DBSubPruner + Send + Sync> {
+        match kind {
+            LedgerDataKind::Transaction => &self.transaction_store_pruner,
+            LedgerDataKind::WriteSet => &self.write_set_pruner,
+            LedgerDataKind::Event => &self.event_store_pruner,
+            LedgerDataKind::VersionData => &self.version_data_pruner,
+        }
+    }
+
+    fn save_progress(
+        &self,
+        progress: &[Version; NUM_LEDGER_DATA_KINDS],
+        batch: &SchemaBatch,
+    ) -> anyhow::Result<()> {
+        for kind in LedgerDataKind::ALL {
+            batch.put::<DbMetadataSchema>(
+                &kind.progress_key(),
+                &DbMetadataValue::Version(progress[kind as usize]),
+            )?;
+        }
+        // Kept up to date for the tools reading it, and to open the DB with an older binary.
+        batch.put::<DbMetadataSchema>(
+            &DbMetadataKey::LedgerPrunerProgress,
+            &DbMetadataValue::Version(progress.iter().copied().min().unwrap_or_default()),
+        )
+    }
+
+    fn record_kind_progress(&self, progress: &[Version; NUM_LEDGER_DATA_KINDS]) {
+        for kind in LedgerDataKind::ALL {
+            let version = progress[kind as usize];
+            self.min_readable_versions[kind as usize].store(version, Ordering::Relaxed);
+            PRUNER_VERSIONS
+                .with_label_values(&[kind.metrics_label(), "min_readable"])
+                .set(version as i64);
+        }
+        let min_readable_version = progress.iter().copied().min().unwrap_or_default();
+        self.min_readable_version
+            .store(min_readable_version, Ordering::Relaxed);
+        PRUNER_VERSIONS
+            .with_label_values(&["ledger_pruner", "min_readable"])
+            .set(min_readable_version as i64);
+    }
+
+    /// Prunes at most `max_versions` versions of each kind of ledger data, returning the new min
+    /// readable version of each kind.
     fn prune_inner(
         &self,
         max_versions: usize,
         db_batch: &mut SchemaBatch,
-    ) -> anyhow::Result<Version> {
-        let min_readable_version = self.min_readable_version();
-
-        // Current target version might be less than the target version to ensure we don't prune
-        // more than max_version in one go.
-        let current_target_version = self.get_current_batch_target(max_versions as Version);
+    ) -> anyhow::Result<[Version; NUM_LEDGER_DATA_KINDS]> {
+        let mut progress = self.kind_min_readable_versions();
+        for kind in LedgerDataKind::ALL {
+            let min_readable_version = progress[kind as usize];
+            // Current target version might be less than the target version to ensure we don't
+            // prune more than max_version in one go.
+            let current_target_version = min(
+                min_readable_version + max_versions as Version,
+                self.kind_target_version(kind),
+            );
+            if current_target_version <= min_readable_version {
+                continue;
+            }
 
-        if current_target_version < min_readable_version {
-            return Ok(min_readable_version);
+            self.sub_pruner(kind)
+                .prune(db_batch, min_readable_version, current_target_version)?;
+            progress[kind as usize] = current_target_version;
         }
 
-        self.transaction_store_pruner.prune(
-            db_batch,
-            min_readable_version,
-            current_target_version,
-        )?;
-        self.write_set_pruner
-            .prune(db_batch, min_readable_version, current_target_version)?;
-        self.version_data_pruner
-            .prune(db_batch, min_readable_version, current_target_version)?;
-        self.event_store_pruner
-            .prune(db_batch, min_readable_version, current_target_version)?;
-
-        Ok(current_target_version)
+        Ok(progress)
     }
 }
@@ -13,6 +13,7 @@ use crate::{
     state_merkle_db::StateMerkleDb,
     EventStore, TransactionStore,
 };
+use aptos_config::config::LedgerPrunerConfig;
 use aptos_jellyfish_merkle::StaleNodeIndex;
 use aptos_schemadb::{schema::KeyCodec, DB};
 use std::sync::Arc;
@@ -28,11 +29,15 @@ where
 }
 
 /// A utility function to instantiate the ledger pruner
-pub(crate) fn create_ledger_pruner(ledger_db: Arc<DB>) -> Arc<LedgerPruner> {
+pub(crate) fn create_ledger_pruner(
+    ledger_db: Arc<DB>,
+    ledger_pruner_config: &LedgerPrunerConfig,
+) -> Arc<LedgerPruner> {
     Arc::new(LedgerPruner::new(
         Arc::clone(&ledger_db),
         Arc::new(TransactionStore::new(Arc::clone(&ledger_db))),
         Arc::new(EventStore::new(Arc::clone(&ledger_db))),
+        ledger_pruner_config,
     ))
 }
 
@@ -388,6 +388,9 @@ fn verify_state_value_pruner(inputs: Vec<Vec<(StateKey, Option<StateValue>)>>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        transaction_prune_window: None,
+        write_set_prune_window: None,
+        event_prune_window: None,
     });
     for batch in inputs {
         update_store(store, batch.clone().into_iter(), version);
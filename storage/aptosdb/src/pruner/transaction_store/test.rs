@@ -55,6 +55,9 @@ fn verify_write_set_pruner(write_sets: Vec<WriteSet>) {
         prune_window: 0,
         batch_size: 1,
         user_pruning_window_offset: 0,
+        transaction_prune_window: None,
+        write_set_prune_window: None,
+        event_prune_window: None,
     });
 
     // write sets
@@ -120,6 +123,9 @@ fn verify_txn_store_pruner(
                 prune_window: 0,
                 batch_size: 1,
                 user_pruning_window_offset: 0,
+                transaction_prune_window: None,
+                write_set_prune_window: None,
+                event_prune_window: None,
             });
         pruner
             .wake_and_wait_pruner(i as u64 /* latest_version */)
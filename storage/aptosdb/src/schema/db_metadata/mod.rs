@@ -57,6 +57,10 @@ pub enum DbMetadataKey {
     StateKvShardCommitProgress(ShardId),
     StateMerkleCommitProgress,
     StateMerkleShardCommitProgress(ShardId),
+    TransactionPrunerProgress,
+    WriteSetPrunerProgress,
+    EventPrunerProgress,
+    VersionDataPrunerProgress,
 }
 
 define_schema!(
@@ -8,6 +8,8 @@ mod backup_maintenance;
 mod debugger;
 mod replay_verify;
 mod restore;
+mod state_diff;
+mod state_export;
 #[cfg(test)]
 mod tests;
 mod utils;
@@ -27,6 +29,8 @@ pub enum DBTool {
     Debug(debugger::Command),
     #[clap(subcommand)]
     BackupMaintenance(backup_maintenance::Command),
+    ExportState(state_export::Opt),
+    ExportStateDiff(state_diff::Opt),
 }
 
 impl DBTool {
@@ -37,6 +41,8 @@ impl DBTool {
             DBTool::ReplayVerify(cmd) => cmd.run().await,
             DBTool::BackupMaintenance(cmd) => cmd.run().await,
             DBTool::Debug(cmd) => cmd.run(),
+            DBTool::ExportState(cmd) => cmd.run(),
+            DBTool::ExportStateDiff(cmd) => cmd.run(),
         }
     }
 }
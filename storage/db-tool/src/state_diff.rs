@@ -0,0 +1,454 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Context, Result};
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_resource_viewer::{find_tables, find_tables_in_struct, AptosValueAnnotator, FoundTable};
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader, MAX_REQUEST_LIMIT};
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::{
+        state_key::{StateKey, StateKeyInner},
+        state_key_prefix::StateKeyPrefix,
+        table::TableHandle,
+    },
+    transaction::Version,
+};
+use aptos_vm::{data_cache::AsMoveResolver, move_vm_ext::MoveResolverExt};
+use clap::Parser;
+use move_core_types::language_storage::StructTag;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    fmt::{self, Write},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Export the changes between two versions to the state attributable to a package, decoded: its
+/// modules, the resources of the types it declares, in any account, and the items of the tables
+/// in those resources. Meant for upgrade post-mortems and for verifying migrations.
+///
+/// Tables are found through the package's resources under its own account and the resources and
+/// table items which changed, so changes to a table only reachable through unchanged resources of
+/// other accounts are missed.
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, parse(from_os_str))]
+    db_dir: PathBuf,
+    #[clap(long)]
+    package: AccountAddress,
+    #[clap(long, help = "Version to diff the state from")]
+    from_version: Version,
+    #[clap(
+        long,
+        help = "Version to diff the state to. [Defaults to the latest version]"
+    )]
+    to_version: Option<Version>,
+    #[clap(long, parse(from_os_str))]
+    output_file: PathBuf,
+}
+
+impl Opt {
+    pub fn run(self) -> Result<()> {
+        let db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            &self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            RocksdbConfigs::default(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        let to_version = match self.to_version {
+            Some(version) => version,
+            None => db.get_latest_version()?,
+        };
+        ensure!(
+            self.from_version < to_version,
+            "Version {} to diff from is not before version {} to diff to",
+            self.from_version,
+            to_version
+        );
+
+        let changes = diff_package_state(&db, self.package, self.from_version, to_version)?;
+        let mut output = format!(
+            "State changes of package {} from version {} to version {}\n",
+            self.package, self.from_version, to_version
+        );
+        for change in &changes {
+            write!(output, "\n{}", change)?;
+        }
+        fs::write(&self.output_file, output)
+            .with_context(|| format!("Failed to write {}", self.output_file.display()))?;
+        info!(
+            "Exported {} state changes of package {} from version {} to version {} to {}",
+            changes.len(),
+            self.package,
+            self.from_version,
+            to_version,
+            self.output_file.display()
+        );
+        Ok(())
+    }
+}
+
+/// A state value of the package which changed, decoded before and after the change. `None` if it
+/// didn't exist.
+struct StateChange {
+    description: String,
+    before: Option<String>,
+    after: Option<String>,
+}
+
+impl fmt::Display for StateChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match (&self.before, &self.after) {
+            (None, _) => "created",
+            (_, None) => "deleted",
+            _ => "modified",
+        };
+        writeln!(f, "{} {}", action, self.description)?;
+        for (sign, value) in [("-", &self.before), ("+", &self.after)] {
+            for line in value.iter().flat_map(|value| value.lines()) {
+                writeln!(f, "{} {}", sign, line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn diff_package_state(
+    db: &Arc<dyn DbReader>,
+    package: AccountAddress,
+    from_version: Version,
+    to_version: Version,
+) -> Result<Vec<StateChange>> {
+    let before_view = db.state_view_at_version(Some(from_version))?;
+    let before_resolver = before_view.as_move_resolver();
+    let after_view = db.state_view_at_version(Some(to_version))?;
+    let after_resolver = after_view.as_move_resolver();
+    let mut diff = PackageDiff {
+        db,
+        package,
+        from_version,
+        to_version,
+        before: AptosValueAnnotator::new(&before_resolver),
+        after: AptosValueAnnotator::new(&after_resolver),
+        tables: VecDeque::new(),
+        changes: vec![],
+    };
+
+    let mut changed_items: HashMap<TableHandle, Vec<StateKey>> = HashMap::new();
+    for state_key in changed_state_keys(db, from_version, to_version)? {
+        match state_key.inner() {
+            StateKeyInner::AccessPath(_) => diff.diff_access_path(&state_key)?,
+            StateKeyInner::TableItem { handle, .. } => {
+                changed_items.entry(*handle).or_default().push(state_key)
+            },
+            StateKeyInner::Raw(_) => {},
+        }
+    }
+    // Resources which didn't change may own tables whose items did
+    for version in [from_version, to_version] {
+        diff.find_tables_under_package(version)?;
+    }
+
+    let mut diffed_tables = HashSet::new();
+    while let Some(table) = diff.tables.pop_front() {
+        if !diffed_tables.insert(table.handle) {
+            continue;
+        }
+        for state_key in changed_items.get(&table.handle).into_iter().flatten() {
+            diff.diff_table_item(&table, state_key)?;
+        }
+    }
+    Ok(diff.changes)
+}
+
+/// The keys written from `from_version` (excluded) to `to_version` (included), in the order they
+/// were first written
+fn changed_state_keys(
+    db: &Arc<dyn DbReader>,
+    from_version: Version,
+    to_version: Version,
+) -> Result<Vec<StateKey>> {
+    let mut seen = HashSet::new();
+    let mut state_keys = vec![];
+    let mut version = from_version + 1;
+    while version <= to_version {
+        let limit = MAX_REQUEST_LIMIT.min(to_version - version + 1);
+        for write_set in db.get_write_set_iterator(version, limit)? {
+            for (state_key, _) in &write_set? {
+                if seen.insert(state_key.clone()) {
+                    state_keys.push(state_key.clone());
+                }
+            }
+        }
+        version += limit;
+    }
+    Ok(state_keys)
+}
+
+struct PackageDiff<'a, T> {
+    db: &'a Arc<dyn DbReader>,
+    package: AccountAddress,
+    from_version: Version,
+    to_version: Version,
+    before: AptosValueAnnotator<'a, T>,
+    after: AptosValueAnnotator<'a, T>,
+    /// Tables found in the values of the package, whose changed items are still to be diffed
+    tables: VecDeque<FoundTable>,
+    changes: Vec<StateChange>,
+}
+
+impl<'a, T: MoveResolverExt> PackageDiff<'a, T> {
+    /// The value of `state_key` before and after, unless it's the same
+    fn values(&self, state_key: &StateKey) -> Result<Option<(Option<Vec<u8>>, Option<Vec<u8>>)>> {
+        let before = self
+            .db
+            .get_state_value_by_version(state_key, self.from_version)?
+            .map(|value| value.bytes().to_vec());
+        let after = self
+            .db
+            .get_state_value_by_version(state_key, self.to_version)?
+            .map(|value| value.bytes().to_vec());
+        Ok((before != after).then_some((before, after)))
+    }
+
+    fn diff_access_path(&mut self, state_key: &StateKey) -> Result<()> {
+        let access_path = match state_key.inner() {
+            StateKeyInner::AccessPath(access_path) => access_path,
+            _ => return Ok(()),
+        };
+        let address = access_path.address;
+        match Path::try_from(access_path.path.as_slice())? {
+            Path::Code(module_id) if module_id.address() == &self.package => {
+                if let Some((before, after)) = self.values(state_key)? {
+                    let describe = |bytes: Vec<u8>| format!("{} bytes of bytecode", bytes.len());
+                    self.changes.push(StateChange {
+                        description: format!("module {}", module_id),
+                        before: before.map(describe),
+                        after: after.map(describe),
+                    });
+                }
+            },
+            Path::Resource(struct_tag) if struct_tag.address == self.package => {
+                if let Some((before, after)) = self.values(state_key)? {
+                    self.diff_resource(
+                        format!("resource {} at {}", struct_tag, address),
+                        &struct_tag,
+                        before,
+                        after,
+                    )?;
+                }
+            },
+            Path::ResourceGroup(group_tag) => {
+                if let Some((before, after)) = self.values(state_key)? {
+                    let mut before = decode_group(before)?;
+                    let mut after = decode_group(after)?;
+                    let struct_tags: BTreeSet<StructTag> = before
+                        .keys()
+                        .chain(after.keys())
+                        .filter(|struct_tag| struct_tag.address == self.package)
+                        .cloned()
+                        .collect();
+                    for struct_tag in struct_tags {
+                        let before = before.remove(&struct_tag);
+                        let after = after.remove(&struct_tag);
+                        if before != after {
+                            self.diff_resource(
+                                format!(
+                                    "resource {} in group {} at {}",
+                                    struct_tag, group_tag, address
+                                ),
+                                &struct_tag,
+                                before,
+                                after,
+                            )?;
+                        }
+                    }
+                }
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    fn diff_resource(
+        &mut self,
+        description: String,
+        struct_tag: &StructTag,
+        before: Option<Vec<u8>>,
+        after: Option<Vec<u8>>,
+    ) -> Result<()> {
+        let mut decode = |annotator: &AptosValueAnnotator<'a, T>, bytes: Vec<u8>| {
+            let resource = annotator.view_resource(struct_tag, &bytes)?;
+            find_tables_in_struct(&resource, &mut self.tables);
+            Ok::<_, anyhow::Error>(resource.to_string())
+        };
+        let before = before
+            .map(|bytes| decode(&self.before, bytes))
+            .transpose()?;
+        let after = after.map(|bytes| decode(&self.after, bytes)).transpose()?;
+        self.changes.push(StateChange {
+            description,
+            before,
+            after,
+        });
+        Ok(())
+    }
+
+    fn diff_table_item(&mut self, table: &FoundTable, state_key: &StateKey) -> Result<()> {
+        let key = match state_key.inner() {
+            StateKeyInner::TableItem { key, .. } => key,
+            _ => return Ok(()),
+        };
+        let (before, after) = match self.values(state_key)? {
+            Some(values) => values,
+            None => return Ok(()),
+        };
+        let key = self.after.view_value(&table.key_type, key)?;
+        let mut decode = |annotator: &AptosValueAnnotator<'a, T>, bytes: Vec<u8>| {
+            let value = annotator.view_value(&table.value_type, &bytes)?;
+            find_tables(&value, &mut self.tables);
+            Ok::<_, anyhow::Error>(value.to_string())
+        };
+        let before = before
+            .map(|bytes| decode(&self.before, bytes))
+            .transpose()?;
+        let after = after.map(|bytes| decode(&self.after, bytes)).transpose()?;
+        self.changes.push(StateChange {
+            description: format!("item {} of table {}", key, table.handle.0),
+            before,
+            after,
+        });
+        Ok(())
+    }
+
+    /// Collects the tables in the resources of the package under its own account at `version`
+    fn find_tables_under_package(&mut self, version: Version) -> Result<()> {
+        let annotator = if version == self.from_version {
+            &self.before
+        } else {
+            &self.after
+        };
+        let prefix = StateKeyPrefix::from(self.package);
+        for item in self
+            .db
+            .get_prefixed_state_value_iterator(&prefix, None, version)?
+        {
+            let (state_key, state_value) = item?;
+            let access_path = match state_key.inner() {
+                StateKeyInner::AccessPath(access_path) => access_path,
+                _ => continue,
+            };
+            let resources = match Path::try_from(access_path.path.as_slice())? {
+                Path::Code(_) => continue,
+                Path::Resource(struct_tag) => {
+                    BTreeMap::from([(struct_tag, state_value.bytes().to_vec())])
+                },
+                Path::ResourceGroup(_) => bcs::from_bytes(state_value.bytes())?,
+            };
+            for (struct_tag, bytes) in resources {
+                if struct_tag.address == self.package {
+                    let resource = annotator.view_resource(&struct_tag, &bytes)?;
+                    find_tables_in_struct(&resource, &mut self.tables);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn decode_group(bytes: Option<Vec<u8>>) -> Result<BTreeMap<StructTag, Vec<u8>>> {
+    Ok(match bytes {
+        Some(bytes) => bcs::from_bytes(&bytes)?,
+        None => BTreeMap::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_types::transaction::Transaction;
+    use move_core_types::language_storage::CORE_CODE_ADDRESS;
+    use std::str::FromStr;
+
+    /// The sender of the user transaction at `version`
+    fn sender(db: &Arc<dyn DbReader>, version: Version) -> AccountAddress {
+        let latest_version = db.get_latest_version().unwrap();
+        match db
+            .get_transaction_by_version(version, latest_version, false)
+            .unwrap()
+            .transaction
+        {
+            Transaction::UserTransaction(txn) => txn.sender(),
+            txn => panic!(
+                "Transaction {} is not a user transaction: {:?}",
+                version, txn
+            ),
+        }
+    }
+
+    fn change<'a>(changes: &'a [StateChange], description: &str) -> &'a StateChange {
+        changes
+            .iter()
+            .find(|change| change.description == description)
+            .unwrap_or_else(|| panic!("No change to {}", description))
+    }
+
+    #[test]
+    fn test_diff_package_state() {
+        let db: Arc<dyn DbReader> = test_execution_with_storage_impl();
+        // Version 2 creates the account sending a transfer at version 8, to the sender of version 9
+        let sender_address = sender(&db, 8);
+        let receiver_address = sender(&db, 9);
+        let coin_store =
+            StructTag::from_str("0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>").unwrap();
+        let account = StructTag::from_str("0x1::account::Account").unwrap();
+
+        let changes = diff_package_state(&db, CORE_CODE_ADDRESS, 1, 2).unwrap();
+        let created = change(
+            &changes,
+            &format!("resource {} at {}", account, sender_address),
+        );
+        assert!(created.before.is_none());
+        assert!(created.to_string().starts_with(&format!(
+            "created resource {} at {}\n+ ",
+            account, sender_address
+        )));
+
+        let changes = diff_package_state(&db, CORE_CODE_ADDRESS, 7, 8).unwrap();
+        for address in [sender_address, receiver_address] {
+            let modified = change(&changes, &format!("resource {} at {}", coin_store, address));
+            assert!(modified.before.is_some() && modified.after.is_some());
+            assert_ne!(modified.before, modified.after);
+            assert!(modified.to_string().starts_with("modified "));
+        }
+        // The sender's sequence number goes up, the receiver's doesn't
+        let sequence_number = change(
+            &changes,
+            &format!("resource {} at {}", account, sender_address),
+        );
+        assert_ne!(sequence_number.before, sequence_number.after);
+        // Nor does the code of the framework
+        assert!(changes.iter().all(|change| {
+            change.description != format!("resource {} at {}", account, receiver_address)
+                && !change.description.starts_with("module ")
+        }));
+
+        // An account which publishes no code has no package state to change
+        let latest_version = db.get_latest_version().unwrap();
+        assert!(diff_package_state(&db, sender_address, 0, latest_version)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_config::config::{
+    RocksdbConfigs, BUFFERED_STATE_TARGET_ITEMS, DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+    NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_logger::info;
+use aptos_resource_viewer::{find_tables, find_tables_in_struct, AptosValueAnnotator};
+use aptos_storage_interface::{state_view::DbStateViewAtVersion, DbReader};
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::{
+        state_export::StateExport,
+        state_key::{StateKeyInner, StateKeyTag},
+        state_key_prefix::StateKeyPrefix,
+    },
+    transaction::Version,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use clap::Parser;
+use move_core_types::language_storage::StructTag;
+use std::{
+    collections::{BTreeMap, HashSet, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+};
+
+/// Export all the state (resources, modules and the items of the tables they own) of a set of
+/// accounts at a version, to load it into a MoveHarness or the genesis of a local testnet
+#[derive(Parser)]
+pub struct Opt {
+    #[clap(long, parse(from_os_str))]
+    db_dir: PathBuf,
+    #[clap(
+        long,
+        help = "Version to export the state at. [Defaults to the latest version]"
+    )]
+    version: Option<Version>,
+    #[clap(long, multiple = true, required = true)]
+    accounts: Vec<AccountAddress>,
+    #[clap(long, parse(from_os_str))]
+    output_file: PathBuf,
+}
+
+impl Opt {
+    pub fn run(self) -> Result<()> {
+        let db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            &self.db_dir,
+            true,                        /* read_only */
+            NO_OP_STORAGE_PRUNER_CONFIG, /* pruner config */
+            RocksdbConfigs::default(),
+            false,
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        let version = match self.version {
+            Some(version) => version,
+            None => db.get_latest_version()?,
+        };
+
+        let export = export_state(&db, self.accounts, version)?;
+        export.write_to(&self.output_file)?;
+        info!(
+            "Exported {} state values of {} accounts at version {} to {}",
+            export.state.len(),
+            export.accounts.len(),
+            version,
+            self.output_file.display()
+        );
+        Ok(())
+    }
+}
+
+fn export_state(
+    db: &Arc<dyn DbReader>,
+    accounts: Vec<AccountAddress>,
+    version: Version,
+) -> Result<StateExport> {
+    let state_view = db.state_view_at_version(Some(version))?;
+    let resolver = state_view.as_move_resolver();
+    let annotator = AptosValueAnnotator::new(&resolver);
+
+    let mut state = vec![];
+    // Tables are not stored under the account owning them, they are found through the handles
+    // in the account's resources, and in the items of those tables
+    let mut tables = VecDeque::new();
+    for account in &accounts {
+        for item in
+            db.get_prefixed_state_value_iterator(&StateKeyPrefix::from(*account), None, version)?
+        {
+            let (state_key, state_value) = item?;
+            if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+                match Path::try_from(access_path.path.as_slice())? {
+                    Path::Code(_) => {},
+                    Path::Resource(struct_tag) => {
+                        let resource = annotator.view_resource(&struct_tag, state_value.bytes())?;
+                        find_tables_in_struct(&resource, &mut tables);
+                    },
+                    Path::ResourceGroup(_) => {
+                        let group: BTreeMap<StructTag, Vec<u8>> =
+                            bcs::from_bytes(state_value.bytes())?;
+                        for (struct_tag, bytes) in group {
+                            let resource = annotator.view_resource(&struct_tag, &bytes)?;
+                            find_tables_in_struct(&resource, &mut tables);
+                        }
+                    },
+                }
+            }
+            state.push((state_key, state_value));
+        }
+    }
+    ensure!(
+        !state.is_empty(),
+        "None of the accounts have any state at version {}",
+        version
+    );
+
+    let mut exported_tables = HashSet::new();
+    while let Some(table) = tables.pop_front() {
+        if !exported_tables.insert(table.handle) {
+            continue;
+        }
+        let prefix = StateKeyPrefix::new(StateKeyTag::TableItem, bcs::to_bytes(&table.handle)?);
+        for item in db.get_prefixed_state_value_iterator(&prefix, None, version)? {
+            let (state_key, state_value) = item?;
+            let value = annotator.view_value(&table.value_type, state_value.bytes())?;
+            find_tables(&value, &mut tables);
+            state.push((state_key, state_value));
+        }
+    }
+
+    Ok(StateExport {
+        version,
+        accounts,
+        state,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aptos_executor_test_helpers::integration_test_impl::test_execution_with_storage_impl;
+    use aptos_types::{
+        access_path::AccessPath,
+        account_config::CORE_CODE_ADDRESS,
+        state_store::{state_key::StateKey, table::TableHandle},
+        transaction::Transaction,
+    };
+    use std::str::FromStr;
+
+    fn resource_key(address: AccountAddress, struct_tag: &str) -> StateKey {
+        let struct_tag = StructTag::from_str(struct_tag).unwrap();
+        StateKey::access_path(AccessPath::resource_access_path(address, struct_tag).unwrap())
+    }
+
+    #[test]
+    fn test_export_state() {
+        let db: Arc<dyn DbReader> = test_execution_with_storage_impl();
+        let version = db.get_latest_version().unwrap();
+        let sender = match db
+            .get_transaction_by_version(8, version, false)
+            .unwrap()
+            .transaction
+        {
+            Transaction::UserTransaction(txn) => txn.sender(),
+            txn => panic!("Transaction 8 is not a user transaction: {:?}", txn),
+        };
+
+        let export = export_state(&db, vec![CORE_CODE_ADDRESS, sender], version).unwrap();
+        assert_eq!(export.version, version);
+        assert_eq!(export.accounts, vec![CORE_CODE_ADDRESS, sender]);
+        let state = export.state.iter().cloned().collect::<BTreeMap<_, _>>();
+        assert_eq!(state.len(), export.state.len());
+
+        for (address, struct_tag) in [
+            (sender, "0x1::account::Account"),
+            (sender, "0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>"),
+            (
+                CORE_CODE_ADDRESS,
+                "0x1::aggregator_factory::AggregatorFactory",
+            ),
+        ] {
+            let key = resource_key(address, struct_tag);
+            assert_eq!(
+                state.get(&key),
+                db.get_state_value_by_version(&key, version)
+                    .unwrap()
+                    .as_ref(),
+                "{} at {}",
+                struct_tag,
+                address
+            );
+        }
+
+        // The aggregators of the APT supply live in the table of the aggregator factory, which
+        // isn't stored under 0x1: all of its items are exported along with it
+        let factory = &state[&resource_key(
+            CORE_CODE_ADDRESS,
+            "0x1::aggregator_factory::AggregatorFactory",
+        )];
+        let handle = TableHandle(bcs::from_bytes(factory.bytes()).unwrap());
+        let prefix = StateKeyPrefix::new(StateKeyTag::TableItem, bcs::to_bytes(&handle).unwrap());
+        let items = db
+            .get_prefixed_state_value_iterator(&prefix, None, version)
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(!items.is_empty());
+        for (key, value) in &items {
+            assert_eq!(state.get(key), Some(value));
+        }
+
+        // Nothing of other accounts is exported
+        for key in state.keys() {
+            if let StateKeyInner::AccessPath(access_path) = key.inner() {
+                assert!(export.accounts.contains(&access_path.address), "{:?}", key);
+            }
+        }
+
+        assert!(export_state(&db, vec![AccountAddress::random()], version).is_err());
+    }
+}
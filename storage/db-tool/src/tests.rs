@@ -79,6 +79,18 @@ fn test_various_cmd_parsing() {
         "--start-version",
         "Max",
     ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export-state-diff",
+        "--db-dir",
+        ".",
+        "--package",
+        "0x1",
+        "--from-version",
+        "100",
+        "--output-file",
+        "diff.txt",
+    ]);
 }
 
 fn run_cmd(args: &[&str]) {
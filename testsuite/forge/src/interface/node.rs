@@ -211,7 +211,8 @@ pub trait NodeExt: Node {
     }
 
     async fn liveness_check(&self, seconds: u64) -> Result<()> {
-        Ok(self.rest_client().health_check(seconds).await?)
+        self.rest_client().health_check(seconds).await?;
+        Ok(())
     }
 
     async fn wait_until_healthy(&mut self, deadline: Instant) -> Result<()> {
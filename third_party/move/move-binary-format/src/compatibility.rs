@@ -8,8 +8,8 @@ use crate::{
     file_format_common::VERSION_5,
     normalized::Module,
 };
-use move_core_types::vm_status::StatusCode;
-use std::collections::BTreeSet;
+use move_core_types::{identifier::Identifier, language_storage::ModuleId, vm_status::StatusCode};
+use std::{collections::BTreeSet, fmt};
 
 /// The result of a linking and layout compatibility check. Here is what the different combinations. NOTE that if `check_struct_layout` is false, type safety over a series of upgrades cannot be guaranteed.
 /// mean:
@@ -69,15 +69,57 @@ impl Compatibility {
             || self.check_struct_layout
     }
 
-    /// Check compatibility for `new_module` relative to old module `old_module`.
+    /// Check compatibility for `new_module` relative to old module `old_module`. On failure,
+    /// the message of the error lists the reasons, see `check_with_details`.
     pub fn check(&self, old_module: &Module, new_module: &Module) -> PartialVMResult<()> {
-        let mut struct_and_pub_function_linking = true;
-        let mut struct_layout = true;
-        let mut friend_linking = true;
+        let incompatibilities = self.check_with_details(old_module, new_module);
+        if incompatibilities.is_empty() {
+            return Ok(());
+        }
+        let reasons = incompatibilities
+            .iter()
+            .map(|incompatibility| incompatibility.to_string())
+            .collect::<Vec<_>>();
+        Err(
+            PartialVMError::new(StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE).with_message(
+                format!(
+                    "Incompatible upgrade of module 0x{}::{}: {}",
+                    old_module.address.short_str_lossless(),
+                    old_module.name,
+                    reasons.join("; ")
+                ),
+            ),
+        )
+    }
+
+    /// Check compatibility for `new_module` relative to old module `old_module`, returning
+    /// every reason the upgrade is incompatible, among the ones this `Compatibility` checks.
+    pub fn check_with_details(
+        &self,
+        old_module: &Module,
+        new_module: &Module,
+    ) -> Vec<Incompatibility> {
+        Self::find_incompatibilities(old_module, new_module)
+            .into_iter()
+            .filter(|(breaks, _)| {
+                (self.check_struct_and_pub_function_linking
+                    && breaks.struct_and_pub_function_linking)
+                    || (self.check_struct_layout && breaks.struct_layout)
+                    || (self.check_friend_linking && breaks.friend_linking)
+            })
+            .map(|(_, incompatibility)| incompatibility)
+            .collect()
+    }
+
+    fn find_incompatibilities(
+        old_module: &Module,
+        new_module: &Module,
+    ) -> Vec<(Breaks, Incompatibility)> {
+        let mut incompatibilities = vec![];
 
         // module's name and address are unchanged
         if old_module.address != new_module.address || old_module.name != new_module.name {
-            struct_and_pub_function_linking = false;
+            incompatibilities.push((Breaks::LINKING, Incompatibility::ModuleRenamed));
         }
 
         // old module's structs are a subset of the new module's structs
@@ -88,19 +130,28 @@ impl Compatibility {
                     // Struct not present in new . Existing modules that depend on this struct will fail to link with the new version of the module.
                     // Also, struct layout cannot be guaranteed transitively, because after
                     // removing the struct, it could be re-added later with a different layout.
-                    struct_and_pub_function_linking = false;
-                    struct_layout = false;
-                    break;
+                    incompatibilities.push((
+                        Breaks::LINKING_AND_LAYOUT,
+                        Incompatibility::StructRemoved(name.clone()),
+                    ));
+                    continue;
                 },
             };
 
-            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities)
-                || !struct_type_parameters_compatibile(
-                    &old_struct.type_parameters,
-                    &new_struct.type_parameters,
-                )
-            {
-                struct_and_pub_function_linking = false;
+            if !struct_abilities_compatibile(old_struct.abilities, new_struct.abilities) {
+                incompatibilities.push((
+                    Breaks::LINKING,
+                    Incompatibility::StructAbilitiesRemoved(name.clone()),
+                ));
+            }
+            if !struct_type_parameters_compatibile(
+                &old_struct.type_parameters,
+                &new_struct.type_parameters,
+            ) {
+                incompatibilities.push((
+                    Breaks::LINKING,
+                    Incompatibility::StructTypeParametersChanged(name.clone()),
+                ));
             }
             if new_struct.fields != old_struct.fields {
                 // Fields changed. Code in this module will fail at runtime if it tries to
@@ -109,7 +160,10 @@ impl Compatibility {
                 // choose that changing the name (but not position or type) of a field is
                 // compatible. The VM does not care about the name of a field
                 // (it's purely informational), but clients presumably do.
-                struct_layout = false
+                incompatibilities.push((
+                    Breaks::LAYOUT,
+                    Incompatibility::StructFieldsChanged(name.clone()),
+                ));
             }
         }
 
@@ -128,14 +182,18 @@ impl Compatibility {
         // friend list. But for simplicity, we decided to go to the more restrictive form now and
         // we may revisit this in the future.
         for (name, old_func) in &old_module.exposed_functions {
+            let breaks = if matches!(old_func.visibility, Visibility::Friend) {
+                Breaks::FRIEND_LINKING
+            } else {
+                Breaks::LINKING
+            };
             let new_func = match new_module.exposed_functions.get(name) {
                 Some(new_func) => new_func,
                 None => {
-                    if matches!(old_func.visibility, Visibility::Friend) {
-                        friend_linking = false;
-                    } else {
-                        struct_and_pub_function_linking = false;
-                    }
+                    incompatibilities.push((
+                        breaks,
+                        Incompatibility::FunctionRemoved(name.clone(), old_func.visibility),
+                    ));
                     continue;
                 },
             };
@@ -161,20 +219,30 @@ impl Compatibility {
                 // If it was not an entry function, it is allowed to become one.
                 !old_func.is_entry || new_func.is_entry
             };
-            if !is_vis_compatible
-                || !is_entry_compatible
-                || old_func.parameters != new_func.parameters
-                || old_func.return_ != new_func.return_
-                || !fun_type_parameters_compatibile(
-                    &old_func.type_parameters,
-                    &new_func.type_parameters,
-                )
-            {
-                if matches!(old_func.visibility, Visibility::Friend) {
-                    friend_linking = false;
-                } else {
-                    struct_and_pub_function_linking = false;
-                }
+            let mut changes = vec![];
+            if !is_vis_compatible {
+                changes.push(FunctionChange::Visibility(new_func.visibility));
+            }
+            if !is_entry_compatible {
+                changes.push(FunctionChange::Entry(new_func.is_entry));
+            }
+            if old_func.parameters != new_func.parameters {
+                changes.push(FunctionChange::Parameters);
+            }
+            if old_func.return_ != new_func.return_ {
+                changes.push(FunctionChange::ReturnType);
+            }
+            if !fun_type_parameters_compatibile(
+                &old_func.type_parameters,
+                &new_func.type_parameters,
+            ) {
+                changes.push(FunctionChange::TypeParameters);
+            }
+            for change in changes {
+                incompatibilities.push((
+                    breaks,
+                    Incompatibility::FunctionChanged(name.clone(), old_func.visibility, change),
+                ));
             }
         }
 
@@ -183,29 +251,152 @@ impl Compatibility {
         // - additions to the list are allowed
         // - removals are not allowed
         //
-        let old_friend_module_ids: BTreeSet<_> = old_module.friends.iter().cloned().collect();
         let new_friend_module_ids: BTreeSet<_> = new_module.friends.iter().cloned().collect();
-        if !old_friend_module_ids.is_subset(&new_friend_module_ids) {
-            friend_linking = false;
+        for friend in &old_module.friends {
+            if !new_friend_module_ids.contains(friend) {
+                incompatibilities.push((
+                    Breaks::FRIEND_LINKING,
+                    Incompatibility::FriendRemoved(friend.clone()),
+                ));
+            }
         }
 
-        if self.check_struct_and_pub_function_linking && !struct_and_pub_function_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
-        }
-        if self.check_struct_layout && !struct_layout {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
-        }
-        if self.check_friend_linking && !friend_linking {
-            return Err(PartialVMError::new(
-                StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE,
-            ));
-        }
+        incompatibilities
+    }
+}
+
+/// The properties of `Compatibility` an incompatibility breaks.
+#[derive(Clone, Copy)]
+struct Breaks {
+    struct_and_pub_function_linking: bool,
+    struct_layout: bool,
+    friend_linking: bool,
+}
+
+impl Breaks {
+    const FRIEND_LINKING: Self = Self {
+        struct_and_pub_function_linking: false,
+        struct_layout: false,
+        friend_linking: true,
+    };
+    const LAYOUT: Self = Self {
+        struct_and_pub_function_linking: false,
+        struct_layout: true,
+        friend_linking: false,
+    };
+    const LINKING: Self = Self {
+        struct_and_pub_function_linking: true,
+        struct_layout: false,
+        friend_linking: false,
+    };
+    const LINKING_AND_LAYOUT: Self = Self {
+        struct_and_pub_function_linking: true,
+        struct_layout: true,
+        friend_linking: false,
+    };
+}
+
+/// A reason why a module upgrade is not backward compatible.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Incompatibility {
+    /// The address or the name of the module changed
+    ModuleRenamed,
+    StructRemoved(Identifier),
+    /// Some of the abilities of the struct were removed
+    StructAbilitiesRemoved(Identifier),
+    /// The number, constraints or phantom declarations of the struct's type parameters changed
+    StructTypeParametersChanged(Identifier),
+    /// The fields of the struct changed, so values already stored can't be read anymore
+    StructFieldsChanged(Identifier),
+    /// A function was removed, with its visibility before the upgrade
+    FunctionRemoved(Identifier, Visibility),
+    /// A function changed, with its visibility before the upgrade
+    FunctionChanged(Identifier, Visibility, FunctionChange),
+    FriendRemoved(ModuleId),
+}
+
+/// How the declaration of a function changed, see `Incompatibility::FunctionChanged`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FunctionChange {
+    /// The visibility was reduced, with the new visibility
+    Visibility(Visibility),
+    /// The function stopped being an entry function, or became one when that isn't allowed
+    Entry(bool),
+    Parameters,
+    ReturnType,
+    /// The number of type parameters changed, or their constraints were extended
+    TypeParameters,
+}
+
+fn visibility_str(visibility: Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "public",
+        Visibility::Friend => "friend",
+        Visibility::Private => "private",
+    }
+}
 
-        Ok(())
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::ModuleRenamed => write!(f, "the module address or name changed"),
+            Incompatibility::StructRemoved(name) => write!(f, "struct `{}` was removed", name),
+            Incompatibility::StructAbilitiesRemoved(name) => {
+                write!(f, "abilities of struct `{}` were removed", name)
+            },
+            Incompatibility::StructTypeParametersChanged(name) => {
+                write!(f, "type parameters of struct `{}` changed", name)
+            },
+            Incompatibility::StructFieldsChanged(name) => {
+                write!(f, "fields of struct `{}` changed", name)
+            },
+            Incompatibility::FunctionRemoved(name, visibility) => write!(
+                f,
+                "{} function `{}` was removed",
+                visibility_str(*visibility),
+                name
+            ),
+            Incompatibility::FunctionChanged(name, visibility, change) => {
+                let visibility = visibility_str(*visibility);
+                match change {
+                    FunctionChange::Visibility(new_visibility) => write!(
+                        f,
+                        "{} function `{}` became {}",
+                        visibility,
+                        name,
+                        visibility_str(*new_visibility)
+                    ),
+                    FunctionChange::Entry(true) => write!(
+                        f,
+                        "{} function `{}` became an entry function",
+                        visibility, name
+                    ),
+                    FunctionChange::Entry(false) => write!(
+                        f,
+                        "{} function `{}` is no longer an entry function",
+                        visibility, name
+                    ),
+                    FunctionChange::Parameters => write!(
+                        f,
+                        "parameters of {} function `{}` changed",
+                        visibility, name
+                    ),
+                    FunctionChange::ReturnType => write!(
+                        f,
+                        "return type of {} function `{}` changed",
+                        visibility, name
+                    ),
+                    FunctionChange::TypeParameters => write!(
+                        f,
+                        "type parameters of {} function `{}` changed",
+                        visibility, name
+                    ),
+                }
+            },
+            Incompatibility::FriendRemoved(module_id) => {
+                write!(f, "friend `{}` was removed", module_id.short_str_lossless())
+            },
+        }
     }
 }
 
@@ -1,8 +1,15 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{compatibility::Compatibility, file_format::*, normalized};
-use move_core_types::{account_address::AccountAddress, identifier::Identifier};
+use crate::{
+    compatibility::{Compatibility, FunctionChange, Incompatibility},
+    errors::Location,
+    file_format::*,
+    normalized,
+};
+use move_core_types::{
+    account_address::AccountAddress, identifier::Identifier, vm_status::StatusCode,
+};
 use std::convert::TryFrom;
 
 fn mk_module(vis: u8) -> normalized::Module {
@@ -114,3 +121,40 @@ fn deprecated_add_script_visibility() {
         .check(&friend_module, &script_module)
         .is_err());
 }
+
+#[test]
+fn incompatibility_details() {
+    let name = Identifier::new("fn").unwrap();
+    let public_module = mk_module(Visibility::Public as u8);
+    let friend_module = mk_module(Visibility::Friend as u8);
+    let private_module = mk_module(Visibility::Private as u8);
+    assert_eq!(
+        Compatibility::full_check().check_with_details(&public_module, &friend_module),
+        vec![Incompatibility::FunctionChanged(
+            name.clone(),
+            Visibility::Public,
+            FunctionChange::Visibility(Visibility::Friend)
+        )]
+    );
+    assert_eq!(
+        Compatibility::full_check().check_with_details(&friend_module, &private_module),
+        vec![Incompatibility::FunctionRemoved(name, Visibility::Friend)]
+    );
+    // friend functions are only checked with friend linking
+    assert!(Compatibility::new(true, true, false)
+        .check_with_details(&friend_module, &private_module)
+        .is_empty());
+
+    let err = Compatibility::full_check()
+        .check(&public_module, &friend_module)
+        .unwrap_err()
+        .finish(Location::Undefined);
+    assert_eq!(
+        err.major_status(),
+        StatusCode::BACKWARD_INCOMPATIBLE_MODULE_UPDATE
+    );
+    assert_eq!(
+        err.message().unwrap(),
+        "Incompatible upgrade of module 0x0::M: public function `fn` became friend"
+    );
+}
@@ -15,7 +15,10 @@ use move_core_types::{
     identifier::{IdentStr, Identifier},
     language_storage::ModuleId,
 };
-use move_vm_runtime::{config::VMConfig, move_vm::MoveVM};
+use move_vm_runtime::{
+    config::{ModuleCacheConfig, VMConfig},
+    move_vm::MoveVM,
+};
 use move_vm_test_utils::InMemoryStorage;
 use move_vm_types::gas::UnmeteredGasMeter;
 use std::{path::PathBuf, sync::Arc, thread};
@@ -67,11 +70,16 @@ impl Adapter {
     }
 
     fn fresh(self) -> Self {
+        self.fresh_with_module_cache(ModuleCacheConfig::default())
+    }
+
+    fn fresh_with_module_cache(self, module_cache: ModuleCacheConfig) -> Self {
         let config = VMConfig {
             verifier: VerifierConfig {
                 max_dependency_depth: Some(100),
                 ..Default::default()
             },
+            module_cache,
             ..Default::default()
         };
         Self {
@@ -196,6 +204,35 @@ fn load_concurrent_many() {
     adapter.call_functions_async(30);
 }
 
+#[test]
+fn evict_over_module_cache_limits() {
+    let data_store = InMemoryStorage::new();
+    let mut adapter = Adapter::new(data_store);
+    let modules = get_modules();
+    adapter.publish_modules(modules);
+    let adapter = adapter.fresh_with_module_cache(ModuleCacheConfig {
+        max_modules: Some(2),
+        ..Default::default()
+    });
+
+    adapter.call_functions();
+    let stats = adapter.vm.loader_cache_stats();
+    assert!(stats.modules.len() > 2);
+    assert_eq!(stats.evictions, 0);
+
+    // the cache is only flushed between sessions
+    adapter.vm.flush_loader_cache_if_invalidated();
+    assert!(adapter.vm.loader_cache_stats().modules.is_empty());
+    let counts = adapter.vm.take_loader_cache_counts();
+    assert_eq!(counts.evictions, stats.modules.len() as u64);
+    assert_eq!(counts.misses, stats.misses);
+    assert_eq!(adapter.vm.take_loader_cache_counts().evictions, 0);
+
+    // loads go to storage again
+    adapter.call_functions();
+    assert!(adapter.vm.take_loader_cache_counts().misses > 0);
+}
+
 #[test]
 fn deep_dependency_list_err_0() {
     let data_store = InMemoryStorage::new();
@@ -3,6 +3,7 @@
 
 use move_binary_format::file_format_common::VERSION_MAX;
 use move_bytecode_verifier::VerifierConfig;
+use std::time::Duration;
 
 /// Dynamic config options for the Move VM.
 pub struct VMConfig {
@@ -11,6 +12,20 @@ pub struct VMConfig {
     // When this flag is set to true, MoveVM will perform type check at every instruction
     // execution to ensure that type safety cannot be violated at runtime.
     pub paranoid_type_checks: bool,
+    pub module_cache: ModuleCacheConfig,
+}
+
+/// Limits of the loader cache. The cache can't drop single modules, so once it goes over any
+/// of them, it is flushed as a whole before the next session, like after a module upgrade.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ModuleCacheConfig {
+    /// Max number of modules in the cache
+    pub max_modules: Option<usize>,
+    /// Max total size of the serialized modules in the cache
+    pub max_size_bytes: Option<usize>,
+    /// Max time modules stay in the cache. Modules upgraded by transactions that didn't run on
+    /// this VM aren't detected, so this bounds how long such upgrades can go unseen.
+    pub ttl: Option<Duration>,
 }
 
 impl Default for VMConfig {
@@ -19,6 +34,7 @@ impl Default for VMConfig {
             verifier: VerifierConfig::default(),
             max_binary_format_version: VERSION_MAX,
             paranoid_type_checks: false,
+            module_cache: ModuleCacheConfig::default(),
         }
     }
 }
@@ -5,6 +5,7 @@
 use crate::{
     config::VMConfig,
     logging::expect_no_verification_errors,
+    move_vm::{CachedModule, LoaderCacheCounts, LoaderCacheStats},
     native_functions::{NativeFunction, NativeFunctions, UnboxedNativeFunction},
     session::LoadedFunctionInstantiation,
 };
@@ -33,13 +34,17 @@ use move_vm_types::{
     data_store::DataStore,
     loaded_data::runtime_types::{CachedStructIndex, StructType, Type},
 };
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 use sha3::{Digest, Sha3_256};
 use std::{
     collections::{btree_map, BTreeMap, BTreeSet, HashMap},
     fmt::Debug,
     hash::Hash,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
 };
 use tracing::error;
 
@@ -129,6 +134,10 @@ pub struct ModuleCache {
     modules: BinaryCache<ModuleId, Module>,
     structs: Vec<Arc<StructType>>,
     functions: Vec<Arc<Function>>,
+    // total size of the serialized modules, and when the first of them was cached, to enforce
+    // the limits of `VMConfig::module_cache`
+    size_bytes: usize,
+    created_at: Option<Instant>,
 }
 
 impl ModuleCache {
@@ -137,6 +146,8 @@ impl ModuleCache {
             modules: BinaryCache::new(),
             structs: vec![],
             functions: vec![],
+            size_bytes: 0,
+            created_at: None,
         }
     }
 
@@ -170,6 +181,7 @@ impl ModuleCache {
         natives: &NativeFunctions,
         id: ModuleId,
         module: CompiledModule,
+        size_bytes: usize,
     ) -> VMResult<Arc<Module>> {
         if let Some(cached) = self.module_at(&id) {
             return Ok(cached);
@@ -179,7 +191,11 @@ impl ModuleCache {
         // leave a clean state
         self.add_module(natives, &module)?;
         match Module::new(module, self) {
-            Ok(module) => Ok(Arc::clone(self.modules.insert(id, module))),
+            Ok(module) => {
+                self.size_bytes += size_bytes;
+                self.created_at.get_or_insert_with(Instant::now);
+                Ok(Arc::clone(self.modules.insert(id, module)))
+            },
             Err((err, module)) => {
                 // remove all structs and functions that have been pushed
                 let strut_def_count = module.struct_defs().len();
@@ -507,6 +523,14 @@ pub(crate) struct Loader {
     // other transactions.
     module_cache_hits: RwLock<BTreeSet<ModuleId>>,
 
+    // Number of module loads served from the cache, and loaded from storage, for diagnostics.
+    module_cache_hit_count: AtomicU64,
+    module_cache_miss_count: AtomicU64,
+    // Number of modules flushed for going over the limits of `VMConfig::module_cache`.
+    module_cache_eviction_count: AtomicU64,
+    // The counts as of the last call to `take_cache_counts`.
+    reported_cache_counts: Mutex<LoaderCacheCounts>,
+
     vm_config: VMConfig,
 }
 
@@ -519,6 +543,10 @@ impl Loader {
             natives,
             invalidated: RwLock::new(false),
             module_cache_hits: RwLock::new(BTreeSet::new()),
+            module_cache_hit_count: AtomicU64::new(0),
+            module_cache_miss_count: AtomicU64::new(0),
+            module_cache_eviction_count: AtomicU64::new(0),
+            reported_cache_counts: Mutex::new(LoaderCacheCounts::default()),
             vm_config,
         }
     }
@@ -556,10 +584,17 @@ impl Loader {
         }
     }
 
-    /// Flush this cache if it is marked as invalidated.
+    /// Flush this cache if it is marked as invalidated, or if it is over the limits of
+    /// `VMConfig::module_cache`.
     pub(crate) fn flush_if_invalidated(&self) {
         let mut invalidated = self.invalidated.write();
-        if *invalidated {
+        let over_limits = self.is_over_limits();
+        if over_limits {
+            let num_modules = self.module_cache.read().modules.id_map.len();
+            self.module_cache_eviction_count
+                .fetch_add(num_modules as u64, Ordering::Relaxed);
+        }
+        if *invalidated || over_limits {
             *self.scripts.write() = ScriptCache::new();
             *self.module_cache.write() = ModuleCache::new();
             *self.type_cache.write() = TypeCache::new();
@@ -567,6 +602,21 @@ impl Loader {
         }
     }
 
+    fn is_over_limits(&self) -> bool {
+        let limits = &self.vm_config.module_cache;
+        let module_cache = self.module_cache.read();
+        limits
+            .max_modules
+            .map_or(false, |max| module_cache.modules.id_map.len() > max)
+            || limits
+                .max_size_bytes
+                .map_or(false, |max| module_cache.size_bytes > max)
+            || limits
+                .ttl
+                .zip(module_cache.created_at)
+                .map_or(false, |(ttl, created_at)| created_at.elapsed() > ttl)
+    }
+
     /// Mark this cache as invalidated.
     pub(crate) fn mark_as_invalid(&self) {
         *self.invalidated.write() = true;
@@ -577,6 +627,71 @@ impl Loader {
         *self.invalidated.read()
     }
 
+    /// Mark this cache as invalidated if it holds any of the given modules, or any module at
+    /// all if none are given. The cache can't drop single modules, so it is flushed as a whole.
+    /// Returns whether the cache was invalidated.
+    pub(crate) fn invalidate_modules(&self, ids: &[ModuleId]) -> bool {
+        let cached = {
+            let module_cache = self.module_cache.read();
+            if ids.is_empty() {
+                !module_cache.modules.id_map.is_empty()
+            } else {
+                ids.iter().any(|id| module_cache.module_at(id).is_some())
+            }
+        };
+        if cached {
+            self.mark_as_invalid();
+        }
+        cached
+    }
+
+    /// Describes the modules in this cache and how well it has been serving module loads.
+    pub(crate) fn cache_stats(&self) -> LoaderCacheStats {
+        let module_cache = self.module_cache.read();
+        let mut modules = module_cache
+            .modules
+            .id_map
+            .keys()
+            .filter_map(|id| module_cache.module_at(id))
+            .map(|module| {
+                let mut bytes = vec![];
+                let size_bytes = module
+                    .module()
+                    .serialize(&mut bytes)
+                    .map_or(0, |_| bytes.len());
+                CachedModule {
+                    id: module.module().self_id(),
+                    size_bytes,
+                }
+            })
+            .collect::<Vec<_>>();
+        modules.sort_by(|a, b| a.id.cmp(&b.id));
+        LoaderCacheStats {
+            modules,
+            hits: self.module_cache_hit_count.load(Ordering::Relaxed),
+            misses: self.module_cache_miss_count.load(Ordering::Relaxed),
+            evictions: self.module_cache_eviction_count.load(Ordering::Relaxed),
+            invalidated: self.is_invalidated(),
+        }
+    }
+
+    /// Returns the cache hits, misses and evictions since the last call.
+    pub(crate) fn take_cache_counts(&self) -> LoaderCacheCounts {
+        let mut reported = self.reported_cache_counts.lock();
+        let total = LoaderCacheCounts {
+            hits: self.module_cache_hit_count.load(Ordering::Relaxed),
+            misses: self.module_cache_miss_count.load(Ordering::Relaxed),
+            evictions: self.module_cache_eviction_count.load(Ordering::Relaxed),
+        };
+        let counts = LoaderCacheCounts {
+            hits: total.hits - reported.hits,
+            misses: total.misses - reported.misses,
+            evictions: total.evictions - reported.evictions,
+        };
+        *reported = total;
+        counts
+    }
+
     /// Copies metadata out of a modules bytecode if available.
     pub(crate) fn get_metadata(&self, module: ModuleId, key: &[u8]) -> Option<Metadata> {
         let cache = self.module_cache.read();
@@ -1111,8 +1226,10 @@ impl Loader {
         // if the module is already in the code cache, load the cached version
         if let Some(cached) = self.module_cache.read().module_at(id) {
             self.module_cache_hits.write().insert(id.clone());
+            self.module_cache_hit_count.fetch_add(1, Ordering::Relaxed);
             return Ok(cached);
         }
+        self.module_cache_miss_count.fetch_add(1, Ordering::Relaxed);
 
         // otherwise, load the transitive closure of the target module
         let module_ref = self.load_and_verify_module_and_dependencies_and_friends(
@@ -1140,7 +1257,7 @@ impl Loader {
         id: &ModuleId,
         data_store: &impl DataStore,
         allow_loading_failure: bool,
-    ) -> VMResult<CompiledModule> {
+    ) -> VMResult<(CompiledModule, usize)> {
         // bytes fetching, allow loading to fail if the flag is set
         let bytes = match data_store.load_module(id) {
             Ok(bytes) => bytes,
@@ -1165,7 +1282,9 @@ impl Loader {
         })
         .map_err(expect_no_verification_errors)?;
 
-        fail::fail_point!("verifier-failpoint-2", |_| { Ok(module.clone()) });
+        fail::fail_point!("verifier-failpoint-2", |_| {
+            Ok((module.clone(), bytes.len()))
+        });
 
         if self.vm_config.paranoid_type_checks && &module.self_id() != id {
             return Err(
@@ -1180,7 +1299,7 @@ impl Loader {
             .map_err(expect_no_verification_errors)?;
         self.check_natives(&module)
             .map_err(expect_no_verification_errors)?;
-        Ok(module)
+        Ok((module, bytes.len()))
     }
 
     // Everything in `load_and_verify_module` and also recursively load and verify all the
@@ -1202,7 +1321,8 @@ impl Loader {
         }
 
         // module self-check
-        let module = self.load_and_verify_module(id, data_store, allow_module_loading_failure)?;
+        let (module, size_bytes) =
+            self.load_and_verify_module(id, data_store, allow_module_loading_failure)?;
         visited.insert(id.clone());
         friends_discovered.extend(module.immediate_friends());
 
@@ -1220,7 +1340,7 @@ impl Loader {
 
         // if linking goes well, insert the module to the code cache
         let mut locked_cache = self.module_cache.write();
-        let module_ref = locked_cache.insert(&self.natives, id.clone(), module)?;
+        let module_ref = locked_cache.insert(&self.natives, id.clone(), module, size_bytes)?;
         drop(locked_cache); // explicit unlock
 
         Ok(module_ref)
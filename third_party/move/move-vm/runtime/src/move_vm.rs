@@ -20,6 +20,36 @@ pub struct MoveVM {
     runtime: VMRuntime,
 }
 
+/// A snapshot of the loader cache of a VM, for diagnostics
+#[derive(Clone, Debug, Default)]
+pub struct LoaderCacheStats {
+    /// The modules in the cache, ordered by id
+    pub modules: Vec<CachedModule>,
+    /// Number of module loads served from the cache
+    pub hits: u64,
+    /// Number of module loads that went to storage
+    pub misses: u64,
+    /// Number of modules flushed for going over the limits of `VMConfig::module_cache`
+    pub evictions: u64,
+    /// Whether the cache has been marked as invalid and is waiting to be flushed
+    pub invalidated: bool,
+}
+
+/// Activity of the loader cache of a VM, see `MoveVM::take_loader_cache_counts`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LoaderCacheCounts {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CachedModule {
+    pub id: ModuleId,
+    /// Size of the serialized module
+    pub size_bytes: usize,
+}
+
 impl MoveVM {
     pub fn new(
         natives: impl IntoIterator<Item = (AccountAddress, Identifier, Identifier, NativeFunction)>,
@@ -95,13 +125,33 @@ impl MoveVM {
     }
 
     /// If the loader cache has been invalidated (either by the above call or by internal logic)
-    /// flush it so it is valid again. Notice that should only be called if there are no
+    /// flush it so it is valid again. The cache is also flushed if it is over the limits of
+    /// `VMConfig::module_cache`. Notice that should only be called if there are no
     /// outstanding sessions created from this VM.
     /// TODO: new loader architecture
     pub fn flush_loader_cache_if_invalidated(&self) {
         self.runtime.loader().flush_if_invalidated()
     }
 
+    /// Describes the contents of the loader cache, and how often it was hit.
+    pub fn loader_cache_stats(&self) -> LoaderCacheStats {
+        self.runtime.loader().cache_stats()
+    }
+
+    /// Returns the loader cache hits, misses and evictions since the last call, for reporting
+    /// them as metrics.
+    pub fn take_loader_cache_counts(&self) -> LoaderCacheCounts {
+        self.runtime.loader().take_cache_counts()
+    }
+
+    /// Marks the loader cache as invalid if it holds any of the given modules, or any module
+    /// if none are given, so it gets flushed like after `mark_loader_cache_as_invalid`. The
+    /// cache does not support removing single modules, so it is flushed as a whole.
+    /// Returns whether the cache was invalidated.
+    pub fn invalidate_cached_modules(&self, module_ids: &[ModuleId]) -> bool {
+        self.runtime.loader().invalidate_modules(module_ids)
+    }
+
     /// Gets and clears module cache hits. This is hack which allows the adapter to see module
     /// reads if executing multiple transactions in a VM. Without this, the adapter only sees
     /// the first load of a module.
@@ -170,6 +170,26 @@ pub fn create_object_address(creator: AccountAddress, seed: &[u8]) -> AccountAdd
     AccountAddress::from_bytes(hash.as_ref()).unwrap()
 }
 
+pub fn create_guid_object_address(creator: AccountAddress, creation_num: u64) -> AccountAddress {
+    // The BCS of a `0x1::guid::ID`
+    let mut input = bcs::to_bytes(&creation_num).unwrap();
+    input.extend(bcs::to_bytes(&creator).unwrap());
+    input.push(Scheme::DeriveObjectAddressFromGuid as u8);
+    let hash = HashValue::sha3_256_of(&input);
+    AccountAddress::from_bytes(hash.as_ref()).unwrap()
+}
+
+pub fn create_user_derived_object_address(
+    creator: AccountAddress,
+    derive_from: AccountAddress,
+) -> AccountAddress {
+    let mut input = bcs::to_bytes(&creator).unwrap();
+    input.extend(bcs::to_bytes(&derive_from).unwrap());
+    input.push(Scheme::DeriveObjectAddressFromObject as u8);
+    let hash = HashValue::sha3_256_of(&input);
+    AccountAddress::from_bytes(hash.as_ref()).unwrap()
+}
+
 pub fn default_owner_stake_pool_address(owner: AccountAddress) -> AccountAddress {
     default_stake_pool_address(owner, owner)
 }
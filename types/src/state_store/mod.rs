@@ -1,6 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
+pub mod state_export;
 pub mod state_key;
 pub mod state_key_prefix;
 pub mod state_storage_usage;
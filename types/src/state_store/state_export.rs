@@ -0,0 +1,85 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    account_address::AccountAddress,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    write_set::{WriteOp, WriteSet, WriteSetMut},
+};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+/// All the state (resources, modules and table items) of a set of accounts at a version,
+/// exported from a DB with `aptos-db-tool export-state`. It can be loaded into a MoveHarness or
+/// into the genesis of a local testnet, to run against a copy of e.g. mainnet state.
+///
+/// Stored as BCS, so it round trips byte for byte.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateExport {
+    /// Version the state was read at
+    pub version: Version,
+    /// Accounts whose state was exported
+    pub accounts: Vec<AccountAddress>,
+    /// Resources and modules of the accounts, followed by the items of the tables they own
+    pub state: Vec<(StateKey, StateValue)>,
+}
+
+impl StateExport {
+    pub fn read_from(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)
+            .with_context(|| format!("Failed to read state export {}", path.display()))?;
+        bcs::from_bytes(&bytes)
+            .with_context(|| format!("Failed to parse state export {}", path.display()))
+    }
+
+    pub fn write_to(&self, path: &Path) -> Result<()> {
+        fs::write(path, bcs::to_bytes(self)?)
+            .with_context(|| format!("Failed to write state export {}", path.display()))
+    }
+
+    /// Write set creating all the exported state, overwriting any existing value of the same
+    /// keys
+    pub fn to_write_set(&self) -> WriteSet {
+        WriteSetMut::new(self.state.iter().map(|(state_key, state_value)| {
+            (
+                state_key.clone(),
+                WriteOp::Creation(state_value.bytes().to_vec()),
+            )
+        }))
+        .freeze()
+        .expect("freezing a write set can't fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{access_path::AccessPath, state_store::table::TableHandle};
+
+    #[test]
+    fn test_state_export_to_write_set() {
+        let address = AccountAddress::random();
+        let resource_key = StateKey::access_path(AccessPath::new(address, b"resource".to_vec()));
+        let table_key = StateKey::table_item(TableHandle(AccountAddress::random()), vec![1]);
+        let export = StateExport {
+            version: 42,
+            accounts: vec![address],
+            state: vec![
+                (resource_key.clone(), StateValue::new_legacy(vec![1, 2])),
+                (table_key.clone(), StateValue::new_legacy(vec![3])),
+            ],
+        };
+
+        let bytes = bcs::to_bytes(&export).unwrap();
+        assert_eq!(bcs::from_bytes::<StateExport>(&bytes).unwrap(), export);
+
+        let write_set = export.to_write_set();
+        assert_eq!(
+            write_set.get(&resource_key),
+            Some(&WriteOp::Creation(vec![1, 2]))
+        );
+        assert_eq!(write_set.get(&table_key), Some(&WriteOp::Creation(vec![3])));
+    }
+}
@@ -7,6 +7,10 @@ use move_core_types::{
     move_resource::{MoveResource, MoveStructType},
 };
 use serde::{Deserialize, Serialize};
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TimestampResource {
@@ -24,3 +28,36 @@ impl MoveResource for TimestampResource {}
 pub struct Timestamp {
     pub microseconds: u64,
 }
+
+/// Offset that the consensus clock of a local testnet adds to the wall clock when proposing
+/// blocks, which lets it fast forward chain time. It can only be changed through the API's admin
+/// endpoints, and only the local testnet's clock (see `ClockTimeService::new_local_testnet`)
+/// reads it, so it never affects real networks.
+static CHAIN_CLOCK_OFFSET_USECS: AtomicU64 = AtomicU64::new(0);
+
+/// How far chain time has been moved ahead of the wall clock in this process
+pub fn chain_clock_offset() -> Duration {
+    Duration::from_micros(CHAIN_CLOCK_OFFSET_USECS.load(Ordering::SeqCst))
+}
+
+/// Moves chain time forward by `by`, and returns the new total offset, or `None` if chain time
+/// would no longer fit in a `u64` of microseconds. Chain time can't move backwards, so there's no
+/// way to undo this other than restarting the node.
+pub fn advance_chain_clock(by: Duration) -> Option<Duration> {
+    let by = u64::try_from(by.as_micros()).ok()?;
+    let now = u64::try_from(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros(),
+    )
+    .ok()?;
+    let previous = CHAIN_CLOCK_OFFSET_USECS
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |offset| {
+            offset
+                .checked_add(by)
+                .filter(|offset| now.checked_add(*offset).is_some())
+        })
+        .ok()?;
+    Some(Duration::from_micros(previous + by))
+}
@@ -12,6 +12,8 @@ use aptos_types::{
     vm_status::StatusCode,
 };
 use aptos_vm::VMValidator;
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::move_vm::LoaderCacheStats;
 
 pub const ACCOUNT_DNE_TEST_ADD: AccountAddress =
     AccountAddress::new([0_u8; AccountAddress::LENGTH]);
@@ -81,4 +83,12 @@ impl TransactionValidation for MockVMValidator {
     }
 
     fn notify_commit(&mut self) {}
+
+    fn loader_cache_stats(&self) -> LoaderCacheStats {
+        LoaderCacheStats::default()
+    }
+
+    fn invalidate_cached_modules(&self, _module_ids: &[ModuleId]) -> bool {
+        false
+    }
 }
@@ -17,6 +17,8 @@ use aptos_types::{
 };
 use aptos_vm::AptosVM;
 use fail::fail_point;
+use move_core_types::language_storage::ModuleId;
+use move_vm_runtime::move_vm::LoaderCacheStats;
 use std::sync::Arc;
 
 #[cfg(test)]
@@ -34,6 +36,13 @@ pub trait TransactionValidation: Send + Sync + Clone {
 
     /// Notify about new commit
     fn notify_commit(&mut self);
+
+    /// Describe the loader cache of the validation VM
+    fn loader_cache_stats(&self) -> LoaderCacheStats;
+
+    /// Flush the loader cache of the validation VM if it holds any of the given modules, or
+    /// any module if none are given. Returns whether it will be flushed.
+    fn invalidate_cached_modules(&self, module_ids: &[ModuleId]) -> bool;
 }
 
 pub struct VMValidator {
@@ -91,6 +100,17 @@ impl TransactionValidation for VMValidator {
             .expect("Get db view cannot fail")
             .into();
     }
+
+    fn loader_cache_stats(&self) -> LoaderCacheStats {
+        self.vm.internals().move_vm().loader_cache_stats()
+    }
+
+    fn invalidate_cached_modules(&self, module_ids: &[ModuleId]) -> bool {
+        self.vm
+            .internals()
+            .move_vm()
+            .invalidate_cached_modules(module_ids)
+    }
 }
 
 /// returns account's sequence number from storage